@@ -2,27 +2,66 @@
 //!
 //! # Modules
 //!
+//! - [`address`](address) - Encodes the base58 and bech32 addresses outputs pay to.
 //! - [`block`](block) - Defines the structure and operations related to blocks.
+//! - [`block_filter`](block_filter) - Builds and matches the compact filters of blocks (BIP158).
 //! - [`block_header`](block_header) - Provides utilities for working with block headers.
+//! - [`block_stats`](block_stats) - Summarizes the transactions, size and fees of a block.
+//! - [`block_undo`](block_undo) - Records the outputs spent by a block, so it can be disconnected.
+//! - [`block_validation`](block_validation) - Validates the consensus rules of the blocks received.
 //! - [`blockchain`](blockchain) - Implements the main blockchain functionality.
+//! - [`difficulty`](difficulty) - Computes the difficulty each header must have from the retarget rules.
+//! - [`header_chain`](header_chain) - Keeps the chain of block headers indexed by height.
+//! - [`chain_state`](chain_state) - Groups the shared structures holding the state of the chain.
+//! - [`chain_tip`](chain_tip) - Describes the tips of the branches of the blockchain.
+//! - [`chain_update`](chain_update) - Describes how the main chain changed when a block was connected.
 //! - [`coinbase`](coinbase) - Defines the structure of a coinbase transaction.
+//! - [`invalid_blocks`](invalid_blocks) - Keeps the blocks found invalid along with the rule they violated.
+//! - [`mempool_snapshot`](mempool_snapshot) - Keeps snapshots of the mempool and the changes between them.
+//! - [`miner`](miner) - Mines blocks from the mempool on regtest, so the node can be tested offline.
 //! - [`outpoint`](outpoint) - Defines the structure of a transaction outpoint.
+//! - [`relative_lock_time`](relative_lock_time) - Decodes the BIP68 relative lock times of inputs.
+//! - [`script_interpreter`](script_interpreter) - Evaluates scripts to validate the inputs of transactions.
+//! - [`script_type`](script_type) - Classifies output scripts by the standard template they follow.
+//! - [`size_breakdown`](size_breakdown) - Breaks the size of a transaction down into its base and witness bytes.
 //! - [`transaction`](transaction) - Defines the structure of a transaction.
 //! - [`tx_in`](tx_in) - Defines the structure of a transaction input.
 //! - [`tx_in_coinbase`](tx_in_coinbase) - Defines the structure of a coinbase transaction input.
 //! - [`tx_out`](tx_out) - Defines the structure of a transaction output.
+//! - [`tx_validation`](tx_validation) - Validates transactions before they are admitted to the mempool.
 //! - [`utxo`](utxo) - Implements the unspent transaction output (UTXO) model.
 
+pub mod address;
 pub mod block;
+pub mod block_constants;
+pub mod block_filter;
 pub mod block_header;
+pub mod block_stats;
+pub mod block_undo;
+pub mod block_validation;
 pub mod blockchain;
+pub mod chain_state;
+pub mod chain_tip;
+pub mod chain_update;
 pub mod coinbase;
+pub mod difficulty;
+pub mod header_chain;
+pub mod invalid_blocks;
 pub mod mempool;
+pub mod mempool_snapshot;
+pub mod miner;
 pub mod outpoint;
+pub mod relative_lock_time;
 pub mod script;
+pub mod script_command;
+pub mod script_error;
+pub mod script_interpreter;
+pub mod script_type;
+pub mod size_breakdown;
 pub mod transaction;
 pub mod tx_in;
 pub mod tx_in_coinbase;
 pub mod tx_out;
+pub mod tx_validation;
 pub mod utxo;
 pub mod witness;
@@ -0,0 +1,125 @@
+use super::block_constants::{
+    BASE58_CHECKSUM_SIZE, BECH32M_CONST, BECH32_CHARSET, BECH32_CHECKSUM_SIZE, BECH32_CONST,
+    BECH32_GENERATOR,
+};
+use bitcoin_hashes::{sha256d, Hash};
+
+/// Encodes a payload in Base58Check: its version byte, the payload and the first bytes of the
+/// double SHA-256 of both as a checksum.
+///
+/// # Arguments
+///
+/// * `version` - The version byte, which tells the network and type of the address.
+/// * `payload` - The hash the address pays to.
+pub fn encode_base58_check(version: u8, payload: &[u8]) -> String {
+    let mut bytes = vec![version];
+    bytes.extend(payload);
+
+    let checksum = sha256d::Hash::hash(&bytes).to_byte_array();
+    bytes.extend(&checksum[..BASE58_CHECKSUM_SIZE]);
+    bs58::encode(bytes).into_string()
+}
+
+/// Encodes a witness program as a segwit address: Bech32 for version 0 programs (BIP173) and
+/// Bech32m for every later version (BIP350).
+///
+/// # Arguments
+///
+/// * `hrp` - The human-readable part of the addresses of the network.
+/// * `version` - The witness version, from 0 to 16.
+/// * `program` - The witness program.
+pub fn encode_segwit_address(hrp: &str, version: u8, program: &[u8]) -> String {
+    let mut data = vec![version];
+    data.extend(to_base32(program));
+
+    let constant = if version == 0 {
+        BECH32_CONST
+    } else {
+        BECH32M_CONST
+    };
+    let mut values = expand_hrp(hrp);
+    values.extend(&data);
+    values.extend([0; BECH32_CHECKSUM_SIZE]);
+    let checksum = polymod(&values) ^ constant;
+
+    data.extend(
+        (0..BECH32_CHECKSUM_SIZE).map(|index| ((checksum >> (5 * (5 - index))) & 0x1f) as u8),
+    );
+
+    let encoded: String = data
+        .iter()
+        .map(|value| BECH32_CHARSET[*value as usize] as char)
+        .collect();
+    format!("{}1{}", hrp, encoded)
+}
+
+/// Splits bytes into groups of 5 bits, padding the last group with zeros.
+fn to_base32(bytes: &[u8]) -> Vec<u8> {
+    let mut values = vec![];
+    let mut accumulator = 0u32;
+    let mut bits = 0;
+
+    for byte in bytes {
+        accumulator = (accumulator << 8) | *byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            values.push(((accumulator >> bits) & 0x1f) as u8);
+        }
+    }
+    if bits > 0 {
+        values.push(((accumulator << (5 - bits)) & 0x1f) as u8);
+    }
+    values
+}
+
+/// Expands the human-readable part into the values its checksum is computed over: the high bits
+/// of each character, a zero and the low bits of each character.
+fn expand_hrp(hrp: &str) -> Vec<u8> {
+    let mut values: Vec<u8> = hrp.bytes().map(|byte| byte >> 5).collect();
+    values.push(0);
+    values.extend(hrp.bytes().map(|byte| byte & 0x1f));
+    values
+}
+
+fn polymod(values: &[u8]) -> u32 {
+    let mut checksum = 1u32;
+
+    for value in values {
+        let top = checksum >> 25;
+        checksum = ((checksum & 0x1ffffff) << 5) ^ *value as u32;
+
+        for (index, generator) in BECH32_GENERATOR.iter().enumerate() {
+            if (top >> index) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+    }
+    checksum
+}
+
+#[cfg(test)]
+mod address_test {
+    use super::*;
+
+    #[test]
+    fn test_addresses_match_the_known_encodings() -> Result<(), hex::FromHexError> {
+        let pubkey_hash = hex::decode("751e76e8199196d454941c45d1b3a323f1433bd6")?;
+        let output_key =
+            hex::decode("000000c4a5cad46221b2a187905e5266362b99d5e91c6ce24d165dab93e86433")?;
+
+        assert_eq!(
+            encode_segwit_address("bc", 0, &pubkey_hash),
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+        );
+        assert_eq!(
+            encode_segwit_address("tb", 1, &output_key),
+            "tb1pqqqqp399et2xygdj5xreqhjjvcmzhxw4aywxecjdzew6hylgvsesf3hn0c"
+        );
+        assert_eq!(
+            encode_base58_check(0x00, &pubkey_hash),
+            "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH"
+        );
+        Ok(())
+    }
+}
@@ -1,10 +1,15 @@
 use bitcoin_hashes::{sha256d, Hash};
 
+use super::block_constants::WITNESS_SCALE_FACTOR;
+use super::block_stats::BlockStats;
 use super::coinbase::Coinbase;
 use super::transaction::Transaction;
+use super::utxo::{OutpointKey, UnspentTx};
 use crate::block_mod::block_header::BlockHeader;
 use crate::messages::compact_size::CompactSizeUInt;
+use crate::messages::message_constants::MAX_BLOCK_TX_COUNT;
 use crate::messages::message_error::MessageError;
+use std::collections::HashMap;
 use std::io::Read;
 use std::vec;
 
@@ -30,7 +35,10 @@ impl Block {
     /// - `Err(MessageError)` if an error occurs during parsing.
     pub fn from_bytes(stream: &mut dyn Read) -> Result<Block, MessageError> {
         let block_header = BlockHeader::from_bytes(stream)?;
-        let txn_count = CompactSizeUInt::from_bytes(stream)?;
+        let txn_count = CompactSizeUInt::from_bytes_bounded(stream, MAX_BLOCK_TX_COUNT)?;
+        if txn_count.value() == 0 {
+            return Err(MessageError::ReadFromBytes);
+        }
         let coinbase = Coinbase::from_bytes(stream)?;
         let mut txn_list = Vec::new();
 
@@ -48,6 +56,56 @@ impl Block {
         Ok(block)
     }
 
+    /// Converts the block into its byte representation, as it is received from the network.
+    ///
+    /// # Returns
+    ///
+    /// A vector of bytes representing the block.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes_with_witness(true)
+    }
+
+    /// Converts the block into its byte representation, with or without the witness data of its
+    /// transactions, as requested by peers through `MSG_WITNESS_BLOCK` or `MSG_BLOCK` inventories.
+    ///
+    /// # Arguments
+    ///
+    /// * `witness` - Whether the witness data of the segwit transactions is serialized.
+    ///
+    /// # Returns
+    ///
+    /// A vector of bytes representing the block.
+    pub fn to_bytes_with_witness(&self, witness: bool) -> Vec<u8> {
+        let mut buff = Vec::new();
+
+        self.to_bytes_with_witness_into(witness, &mut buff);
+        buff
+    }
+
+    /// Appends the byte representation of the block to the given buffer, as it is received from
+    /// the network.
+    pub fn to_bytes_into(&self, buff: &mut Vec<u8>) {
+        self.to_bytes_with_witness_into(true, buff);
+    }
+
+    /// Appends the byte representation of the block to the given buffer, with or without the
+    /// witness data of its transactions. Reusing the buffer across blocks saves allocating one for
+    /// each of them, and every transaction is written straight into it.
+    ///
+    /// # Arguments
+    ///
+    /// * `witness` - Whether the witness data of the segwit transactions is serialized.
+    /// * `buff` - The buffer the bytes are appended to.
+    pub fn to_bytes_with_witness_into(&self, witness: bool, buff: &mut Vec<u8>) {
+        self.block_header.to_bytes_into(buff);
+        self.txn_count.to_bytes_into(buff);
+        self.coinbase.to_bytes_with_witness_into(witness, buff);
+
+        for tx in self.txn_list.iter() {
+            tx.to_bytes_into(witness, buff);
+        }
+    }
+
     /// Returns the header of the block.
     pub fn get_header(&self) -> &BlockHeader {
         &self.block_header
@@ -67,6 +125,22 @@ impl Block {
         txn_ids
     }
 
+    /// Returns the root of the merkle tree of the wtxids of the block, the wtxid of the coinbase
+    /// being all zeros (BIP141).
+    pub fn get_witness_root(&self) -> Vec<u8> {
+        calculate_witness_root(&self.txn_list)
+    }
+
+    /// Returns whether any transaction of the block, besides the coinbase, has witness data.
+    pub fn has_witness(&self) -> bool {
+        self.txn_list.iter().any(|tx| tx.is_segwit())
+    }
+
+    /// Returns the coinbase transaction of the block.
+    pub fn get_coinbase(&self) -> &Coinbase {
+        &self.coinbase
+    }
+
     /// Returns a vector with the transactions of the block.
     pub fn get_txn_list(&self) -> &Vec<Transaction> {
         &self.txn_list
@@ -85,6 +159,96 @@ impl Block {
     pub fn proof_of_work(&self) -> bool {
         self.block_header.proof_of_work()
     }
+
+    /// Returns the weight of the block (BIP141): its size without witness data times three, plus
+    /// its size with witness data.
+    pub fn get_weight(&self) -> usize {
+        let base_size = self.to_bytes_with_witness(false).len();
+
+        base_size * (WITNESS_SCALE_FACTOR - 1) + self.to_bytes().len()
+    }
+
+    /// Computes the statistics of the block: its transaction, input and output counts, its size
+    /// and weight, and the fees its transactions pay.
+    ///
+    /// The value of each spent output is looked up among the outputs created earlier in the
+    /// block, the undo data of the block, if it is connected, and the UTXO set, if it is not.
+    ///
+    /// # Arguments
+    ///
+    /// * `utxo` - The UTXO set the spent outputs are looked up in.
+    pub fn stats(&self, utxo: &UnspentTx) -> BlockStats {
+        let undo: HashMap<OutpointKey, i64> = utxo
+            .get_undo_data(&self.block_header.get_header())
+            .map(|spent| {
+                spent
+                    .iter()
+                    .map(|(outpoint, coin)| (OutpointKey::from(outpoint), coin.get_value()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let mut created: HashMap<OutpointKey, i64> = HashMap::new();
+        let mut total_fee = Some(0);
+        let mut input_count = 0;
+        let mut output_count = self.coinbase.tx_out_list.len();
+        let mut fee_paying_vsize = 0;
+
+        for tx in self.txn_list.iter() {
+            let input_values: Option<Vec<i64>> = tx
+                .get_tx_in_list()
+                .iter()
+                .map(|tx_in| {
+                    let key = OutpointKey::from(tx_in.get_prev_output());
+                    created
+                        .get(&key)
+                        .or_else(|| undo.get(&key))
+                        .copied()
+                        .or_else(|| {
+                            utxo.get_tx_out(tx_in.get_prev_output())
+                                .map(|tx_out| tx_out.get_value())
+                        })
+                })
+                .collect();
+            let fee = input_values.and_then(|input_values| tx.fee(&input_values));
+            total_fee = total_fee.zip(fee).map(|(total, fee)| total + fee);
+
+            let tx_id = tx.get_id(false);
+            for (index, tx_out) in tx.get_tx_out_list().iter().enumerate() {
+                created.insert(OutpointKey::new(&tx_id, index as u32), tx_out.get_value());
+            }
+            input_count += tx.get_tx_in_list().len();
+            output_count += tx.get_tx_out_list().len();
+            fee_paying_vsize += tx.vsize();
+        }
+
+        BlockStats::new(
+            (self.txn_list.len() + 1, input_count, output_count),
+            self.to_bytes().len(),
+            self.get_weight(),
+            total_fee,
+            fee_paying_vsize,
+        )
+    }
+}
+
+/// Calculates the root of the merkle tree of the wtxids of the transactions of a block, the wtxid
+/// of the coinbase being all zeros (BIP141).
+///
+/// # Arguments
+/// * `txn_list` - The transactions of the block, besides the coinbase.
+pub fn calculate_witness_root(txn_list: &[Transaction]) -> Vec<u8> {
+    let mut wtxids = vec![vec![0; 32]];
+    wtxids.extend(txn_list.iter().map(|tx| tx.wtxid()));
+
+    calculate_merkle_root(&mut wtxids)
+}
+
+/// Calculates the witness commitment the coinbase of a block holds (BIP141): the double SHA-256
+/// of the witness root followed by the reserved value.
+pub fn calculate_witness_commitment(witness_root: &[u8], reserved_value: &[u8]) -> Vec<u8> {
+    sha256d::Hash::hash(&[witness_root, reserved_value].concat())
+        .to_byte_array()
+        .to_vec()
 }
 
 /// Recursively calculates the Merkle root by concatenating and hashing the levels of the Merkle tree.
@@ -94,7 +258,7 @@ impl Block {
 ///
 /// # Returns
 /// The calculated Merkle root as a vector of bytes.
-fn calculate_merkle_root(txn_list: &mut Vec<Vec<u8>>) -> Vec<u8> {
+pub fn calculate_merkle_root(txn_list: &mut Vec<Vec<u8>>) -> Vec<u8> {
     let mut new_level_txn_list: Vec<Vec<u8>> = Vec::new();
     let mut merkle_root_hash: Vec<u8> = Vec::new();
 
@@ -141,11 +305,34 @@ impl std::fmt::Display for Block {
     }
 }
 
+/// Returns the bytes of a block containing only a coinbase transaction, for testing purposes.
+#[cfg(test)]
+pub fn test_block_bytes() -> Vec<u8> {
+    let header = BlockHeader::new(1, vec![0; 32], vec![0; 32], 0, 0, 0);
+    let mut bytes = header.to_bytes();
+
+    bytes.push(1); // transaction count
+    bytes.extend(1i32.to_le_bytes());
+    bytes.push(1); // input count
+    bytes.extend([0; 32]);
+    bytes.extend(u32::MAX.to_le_bytes());
+    bytes.extend([4, 3, 1, 0, 0]); // script length and height
+    bytes.extend(u32::MAX.to_le_bytes());
+    bytes.push(1); // output count
+    bytes.extend(50i64.to_le_bytes());
+    bytes.push(0); // pk script length
+    bytes.extend(0u32.to_le_bytes());
+
+    bytes
+}
+
 #[cfg(test)]
 mod block_test {
     use super::*;
     use crate::block_mod::block::Block;
+    use crate::block_mod::{miner::mine_block, tx_in::TxIn, tx_out::TxOut};
     use crate::messages::message_error::MessageError;
+    use crate::network::bitcoin_network::Network;
     use std::fs::OpenOptions;
 
     #[test]
@@ -167,4 +354,77 @@ mod block_test {
 
         Ok(())
     }
+
+    #[test]
+    fn test_block_to_bytes() -> Result<(), MessageError> {
+        let bytes = test_block_bytes();
+        let block = Block::from_bytes(&mut bytes.as_slice())?;
+
+        assert_eq!(block.to_bytes(), bytes);
+        Ok(())
+    }
+
+    #[test]
+    fn test_blocks_are_appended_to_the_buffer() -> Result<(), MessageError> {
+        let bytes = test_block_bytes();
+        let block = Block::from_bytes(&mut bytes.as_slice())?;
+        let mut buffer = vec![7; 3];
+
+        block.to_bytes_into(&mut buffer);
+        block.to_bytes_with_witness_into(false, &mut buffer);
+
+        assert_eq!(buffer[..3], [7; 3]);
+        assert_eq!(buffer[3..3 + bytes.len()], bytes);
+        assert_eq!(
+            buffer[3 + bytes.len()..],
+            block.to_bytes_with_witness(false)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_block_stats() -> Result<(), MessageError> {
+        let transaction = |prev_tx_id: Vec<u8>, value: i64| {
+            Transaction::new(
+                1,
+                vec![TxIn::new(prev_tx_id, 0, vec![], 0xffffffff)],
+                vec![TxOut::new(value, vec![0x51])],
+                0,
+                false,
+            )
+        };
+        let mut utxo = UnspentTx::new();
+        let funding = transaction(vec![0; 32], 10000);
+        utxo.update_transaction(&funding);
+
+        let spending = transaction(funding.get_id(false), 9000);
+        let chained = transaction(spending.get_id(false), 8500);
+        let genesis = Network::Regtest.get_genesis_header();
+        let txn_list = vec![spending.clone(), chained.clone()];
+        let block = mine_block(Network::Regtest, &genesis, 1, (txn_list, 1500), vec![0x51])
+            .ok_or(MessageError::InvalidInputSubmitBlock)?;
+
+        let stats = block.stats(&utxo);
+        assert_eq!(stats.get_tx_count(), 3);
+        assert_eq!(stats.get_input_count(), 2);
+        assert_eq!(
+            stats.get_output_count(),
+            block.get_coinbase().tx_out_list.len() + 2
+        );
+        assert_eq!(stats.get_total_size(), block.to_bytes().len());
+        assert_eq!(stats.get_total_weight(), block.get_weight());
+        assert_eq!(stats.get_total_fee(), Some(1500));
+        assert_eq!(
+            stats.get_avg_fee_rate(),
+            Some(1500 * 1000 / (spending.vsize() + chained.vsize()) as u64)
+        );
+
+        // Once connected, the spent outputs are found in the undo data of the block
+        utxo.update(&block, 1);
+        assert_eq!(block.stats(&utxo).get_total_fee(), Some(1500));
+
+        assert_eq!(block.stats(&UnspentTx::new()).get_total_fee(), None);
+        assert_eq!(block.stats(&UnspentTx::new()).get_avg_fee_rate(), None);
+        Ok(())
+    }
 }
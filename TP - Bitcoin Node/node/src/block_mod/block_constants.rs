@@ -0,0 +1,163 @@
+//relative lock time (BIP68)
+pub const BIP68_MIN_VERSION: i32 = 2;
+pub const SEQUENCE_LOCKTIME_DISABLE_FLAG: u32 = 1 << 31;
+pub const SEQUENCE_LOCKTIME_TYPE_FLAG: u32 = 1 << 22;
+pub const SEQUENCE_LOCKTIME_MASK: u32 = 0x0000ffff;
+pub const SEQUENCE_LOCKTIME_GRANULARITY: u32 = 9;
+
+//absolute lock time
+pub const LOCKTIME_THRESHOLD: u32 = 500_000_000;
+pub const SEQUENCE_FINAL: u32 = u32::MAX;
+pub const MEDIAN_TIME_SPAN: usize = 11;
+pub const MAX_LOCKTIME_NUM_SIZE: usize = 5;
+
+//transaction weight (BIP141)
+pub const WITNESS_SCALE_FACTOR: usize = 4;
+pub const MAX_STANDARD_TX_WEIGHT: usize = 400000;
+pub const MAX_BLOCK_WEIGHT: usize = 4000000;
+pub const WITNESS_COMMITMENT_HEADER: [u8; 6] = [0x6a, 0x24, 0xaa, 0x21, 0xa9, 0xed];
+pub const WITNESS_RESERVED_VALUE_SIZE: usize = 32;
+
+//chain reorganizations
+pub const MAX_REORG_DEPTH: usize = 100;
+
+//coinbase maturity
+pub const COINBASE_MATURITY: u32 = 100;
+
+//coinbase height (BIP34)
+pub const MAINNET_BIP34_HEIGHT: u32 = 227_931;
+pub const TESTNET_BIP34_HEIGHT: u32 = 21_111;
+pub const BIP34_HEIGHT: u32 = 1;
+
+//block locators
+pub const LOCATOR_DENSE_COUNT: usize = 10;
+
+//block subsidy
+pub const INITIAL_BLOCK_SUBSIDY: i64 = 5_000_000_000;
+pub const MAX_MONEY: i64 = 21_000_000 * 100_000_000;
+pub const HALVING_INTERVAL: u32 = 210_000;
+pub const REGTEST_HALVING_INTERVAL: u32 = 150;
+
+//difficulty
+pub const DIFFICULTY_ADJUSTMENT_INTERVAL: u32 = 2016;
+pub const TARGET_TIMESPAN: u32 = 14 * 24 * 60 * 60;
+pub const TARGET_SPACING: u32 = 10 * 60;
+pub const TARGET_SIZE: usize = 32;
+
+//regtest mining
+pub const MINED_BLOCK_VERSION: i32 = 0x20000000;
+pub const COINBASE_NULL_INDEX: u32 = u32::MAX;
+pub const COINBASE_SEQUENCE: u32 = u32::MAX;
+
+//addresses
+pub const BECH32_CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+pub const BECH32_GENERATOR: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+pub const BECH32_CONST: u32 = 1;
+pub const BECH32M_CONST: u32 = 0x2bc830a3;
+pub const BECH32_CHECKSUM_SIZE: usize = 6;
+pub const BASE58_CHECKSUM_SIZE: usize = 4;
+
+//compact block filters (BIP158)
+pub const BASIC_FILTER_P: u8 = 19;
+pub const BASIC_FILTER_M: u64 = 784931;
+pub const OP_RETURN: u8 = 0x6a;
+
+//scripts
+pub const MAX_SCRIPT_ELEMENT_SIZE: usize = 520;
+pub const MAX_SCRIPT_SIZE: usize = 10_000;
+pub const MAX_OPS_PER_SCRIPT: usize = 201;
+pub const MAX_STACK_SIZE: usize = 1000;
+pub const MAX_PUBKEYS_PER_MULTISIG: usize = 20;
+pub const MAX_SCRIPT_NUM_SIZE: usize = 4;
+pub const SIGHASH_ALL: u8 = 0x01;
+pub const MIN_WITNESS_PROGRAM_SIZE: usize = 2;
+pub const MAX_WITNESS_PROGRAM_SIZE: usize = 40;
+pub const TAPROOT_VERSION: u8 = 1;
+pub const TAPROOT_PROGRAM_SIZE: usize = 32;
+pub const DISABLED_OPCODES: [u8; 15] = [
+    0x7e, 0x7f, 0x80, 0x81, 0x83, 0x84, 0x85, 0x86, 0x8d, 0x8e, 0x95, 0x96, 0x97, 0x98, 0x99,
+];
+pub const OPCODES: [(u8, &str); 82] = [
+    (0x00, "OP_0"),
+    (0x4c, "OP_PUSHDATA1"),
+    (0x4d, "OP_PUSHDATA2"),
+    (0x4e, "OP_PUSHDATA4"),
+    (0x4f, "OP_1NEGATE"),
+    (0x51, "OP_1"),
+    (0x52, "OP_2"),
+    (0x53, "OP_3"),
+    (0x54, "OP_4"),
+    (0x55, "OP_5"),
+    (0x56, "OP_6"),
+    (0x57, "OP_7"),
+    (0x58, "OP_8"),
+    (0x59, "OP_9"),
+    (0x5a, "OP_10"),
+    (0x5b, "OP_11"),
+    (0x5c, "OP_12"),
+    (0x5d, "OP_13"),
+    (0x5e, "OP_14"),
+    (0x5f, "OP_15"),
+    (0x60, "OP_16"),
+    (0x61, "OP_NOP"),
+    (0x63, "OP_IF"),
+    (0x64, "OP_NOTIF"),
+    (0x67, "OP_ELSE"),
+    (0x68, "OP_ENDIF"),
+    (0x69, "OP_VERIFY"),
+    (0x6a, "OP_RETURN"),
+    (0x6b, "OP_TOALTSTACK"),
+    (0x6c, "OP_FROMALTSTACK"),
+    (0x6d, "OP_2DROP"),
+    (0x6e, "OP_2DUP"),
+    (0x6f, "OP_3DUP"),
+    (0x70, "OP_2OVER"),
+    (0x71, "OP_2ROT"),
+    (0x72, "OP_2SWAP"),
+    (0x73, "OP_IFDUP"),
+    (0x74, "OP_DEPTH"),
+    (0x75, "OP_DROP"),
+    (0x76, "OP_DUP"),
+    (0x77, "OP_NIP"),
+    (0x78, "OP_OVER"),
+    (0x79, "OP_PICK"),
+    (0x7a, "OP_ROLL"),
+    (0x7b, "OP_ROT"),
+    (0x7c, "OP_SWAP"),
+    (0x7d, "OP_TUCK"),
+    (0x82, "OP_SIZE"),
+    (0x87, "OP_EQUAL"),
+    (0x88, "OP_EQUALVERIFY"),
+    (0x8b, "OP_1ADD"),
+    (0x8c, "OP_1SUB"),
+    (0x8f, "OP_NEGATE"),
+    (0x90, "OP_ABS"),
+    (0x91, "OP_NOT"),
+    (0x92, "OP_0NOTEQUAL"),
+    (0x93, "OP_ADD"),
+    (0x94, "OP_SUB"),
+    (0x9a, "OP_BOOLAND"),
+    (0x9b, "OP_BOOLOR"),
+    (0x9c, "OP_NUMEQUAL"),
+    (0x9d, "OP_NUMEQUALVERIFY"),
+    (0x9e, "OP_NUMNOTEQUAL"),
+    (0x9f, "OP_LESSTHAN"),
+    (0xa0, "OP_GREATERTHAN"),
+    (0xa1, "OP_LESSTHANOREQUAL"),
+    (0xa2, "OP_GREATERTHANOREQUAL"),
+    (0xa3, "OP_MIN"),
+    (0xa4, "OP_MAX"),
+    (0xa5, "OP_WITHIN"),
+    (0xa6, "OP_RIPEMD160"),
+    (0xa7, "OP_SHA1"),
+    (0xa8, "OP_SHA256"),
+    (0xa9, "OP_HASH160"),
+    (0xaa, "OP_HASH256"),
+    (0xab, "OP_CODESEPARATOR"),
+    (0xac, "OP_CHECKSIG"),
+    (0xad, "OP_CHECKSIGVERIFY"),
+    (0xae, "OP_CHECKMULTISIG"),
+    (0xaf, "OP_CHECKMULTISIGVERIFY"),
+    (0xb1, "OP_CHECKLOCKTIMEVERIFY"),
+    (0xb2, "OP_CHECKSEQUENCEVERIFY"),
+];
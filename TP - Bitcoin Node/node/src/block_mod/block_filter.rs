@@ -0,0 +1,278 @@
+use super::{
+    block::Block,
+    block_constants::{BASIC_FILTER_M, BASIC_FILTER_P, OP_RETURN},
+    outpoint::Outpoint,
+    utxo::Coin,
+};
+use crate::messages::{compact_size::CompactSizeUInt, message_error::MessageError};
+use bitcoin_hashes::siphash24;
+use std::collections::HashSet;
+
+/// Writes the bits of a Golomb-coded set, most significant bit first.
+struct BitWriter {
+    bytes: Vec<u8>,
+    used_bits: u8,
+}
+
+impl BitWriter {
+    fn new() -> BitWriter {
+        BitWriter {
+            bytes: vec![],
+            used_bits: 8,
+        }
+    }
+
+    fn write_bit(&mut self, bit: bool) {
+        if self.used_bits == 8 {
+            self.bytes.push(0);
+            self.used_bits = 0;
+        }
+
+        if bit {
+            if let Some(byte) = self.bytes.last_mut() {
+                *byte |= 0x80 >> self.used_bits;
+            }
+        }
+        self.used_bits += 1;
+    }
+
+    fn write_bits(&mut self, value: u64, count: u8) {
+        for position in (0..count).rev() {
+            self.write_bit((value >> position) & 1 == 1);
+        }
+    }
+}
+
+/// Reads the bits of a Golomb-coded set, most significant bit first.
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl BitReader<'_> {
+    fn read_bit(&mut self) -> Option<bool> {
+        let byte = self.bytes.get(self.position / 8)?;
+        let bit = (byte >> (7 - self.position % 8)) & 1 == 1;
+        self.position += 1;
+        Some(bit)
+    }
+
+    fn read_bits(&mut self, count: u8) -> Option<u64> {
+        let mut value = 0;
+        for _ in 0..count {
+            value = (value << 1) | self.read_bit()? as u64;
+        }
+        Some(value)
+    }
+}
+
+/// Represents the basic compact filter of a block (BIP158), a Golomb-coded set holding the
+/// scripts of the outputs the block creates and spends.
+///
+/// Light clients download the filters instead of whole blocks, and only request the blocks whose
+/// filters match their scripts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockFilter {
+    bytes: Vec<u8>,
+}
+
+/// Returns the SipHash keys of the filter of a block, taken from the first 16 bytes of its hash.
+fn filter_keys(block_hash: &[u8]) -> (u64, u64) {
+    let mut k0 = [0u8; 8];
+    let mut k1 = [0u8; 8];
+    k0.copy_from_slice(&block_hash[0..8]);
+    k1.copy_from_slice(&block_hash[8..16]);
+
+    (u64::from_le_bytes(k0), u64::from_le_bytes(k1))
+}
+
+/// Hashes the elements of a filter into the range `[0, count * M)`, sorting the results.
+fn hash_elements(block_hash: &[u8], elements: &[Vec<u8>], count: u64) -> Vec<u64> {
+    let (k0, k1) = filter_keys(block_hash);
+    let range = count as u128 * BASIC_FILTER_M as u128;
+
+    let mut values: Vec<u64> = elements
+        .iter()
+        .map(|element| {
+            let hash = siphash24::Hash::hash_to_u64_with_keys(k0, k1, element);
+            ((hash as u128 * range) >> 64) as u64
+        })
+        .collect();
+    values.sort_unstable();
+    values
+}
+
+impl BlockFilter {
+    /// Builds the basic filter of a block.
+    ///
+    /// # Arguments
+    ///
+    /// * `block` - The block the filter is built for.
+    /// * `spent` - The coins spent by the block, as kept in its undo data.
+    pub fn new(block: &Block, spent: &[(Outpoint, Coin)]) -> BlockFilter {
+        let created = block
+            .get_coinbase()
+            .tx_out_list
+            .iter()
+            .chain(
+                block
+                    .get_txn_list()
+                    .iter()
+                    .flat_map(|tx| tx.get_tx_out_list()),
+            )
+            .map(|tx_out| tx_out.get_pk_script())
+            .filter(|script| script.first().is_some_and(|opcode| *opcode != OP_RETURN));
+        let spent_scripts = spent.iter().map(|(_, coin)| coin.get_pk_script().to_vec());
+
+        let scripts: Vec<Vec<u8>> = created
+            .chain(spent_scripts)
+            .filter(|script| !script.is_empty())
+            .collect();
+
+        BlockFilter::from_scripts(&block.get_header().get_header(), &scripts)
+    }
+
+    /// Builds a filter holding the given scripts, duplicates being kept once.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_hash` - The hash of the block, whose first 16 bytes key the filter.
+    /// * `scripts` - The scripts the filter holds.
+    pub fn from_scripts(block_hash: &[u8], scripts: &[Vec<u8>]) -> BlockFilter {
+        let unique: HashSet<&Vec<u8>> = scripts.iter().collect();
+        let elements: Vec<Vec<u8>> = unique.into_iter().cloned().collect();
+        let count = elements.len() as u64;
+
+        let mut bytes = CompactSizeUInt::from_number(count).to_bytes();
+        let mut writer = BitWriter::new();
+        let mut last = 0;
+
+        for value in hash_elements(block_hash, &elements, count) {
+            let delta = value - last;
+            last = value;
+
+            for _ in 0..(delta >> BASIC_FILTER_P) {
+                writer.write_bit(true);
+            }
+            writer.write_bit(false);
+            writer.write_bits(delta, BASIC_FILTER_P);
+        }
+
+        bytes.extend(writer.bytes);
+        BlockFilter { bytes }
+    }
+
+    /// Parses a serialized filter.
+    ///
+    /// # Returns
+    ///
+    /// The filter, or a `MessageError` if its element count cannot be read.
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<BlockFilter, MessageError> {
+        CompactSizeUInt::from_bytes(&mut bytes.as_slice())?;
+        Ok(BlockFilter { bytes })
+    }
+
+    /// Returns whether any of the given scripts may belong to the filter.
+    ///
+    /// False positives happen with a probability of about 1 in `M`, while false negatives never
+    /// happen.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_hash` - The hash of the block the filter belongs to.
+    /// * `scripts` - The scripts looked for.
+    pub fn match_any(&self, block_hash: &[u8], scripts: &[Vec<u8>]) -> bool {
+        let mut stream = self.bytes.as_slice();
+        let count = match CompactSizeUInt::from_bytes(&mut stream) {
+            Ok(count) => count.value(),
+            Err(_) => return false,
+        };
+
+        if count == 0 || scripts.is_empty() {
+            return false;
+        }
+
+        let queries = hash_elements(block_hash, scripts, count);
+        let mut reader = BitReader {
+            bytes: stream,
+            position: 0,
+        };
+        let mut value = 0;
+        let mut queries = queries.iter().peekable();
+
+        for _ in 0..count {
+            let mut quotient = 0;
+            loop {
+                match reader.read_bit() {
+                    Some(true) => quotient += 1,
+                    Some(false) => break,
+                    None => return false,
+                }
+            }
+
+            let remainder = match reader.read_bits(BASIC_FILTER_P) {
+                Some(remainder) => remainder,
+                None => return false,
+            };
+            value += (quotient << BASIC_FILTER_P) + remainder;
+
+            while let Some(query) = queries.peek() {
+                if **query == value {
+                    return true;
+                }
+                if **query > value {
+                    break;
+                }
+                queries.next();
+            }
+
+            if queries.peek().is_none() {
+                return false;
+            }
+        }
+        false
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.bytes.clone()
+    }
+}
+
+#[cfg(test)]
+mod block_filter_test {
+    use super::*;
+    use crate::messages::read_from_bytes::decode_hex;
+
+    #[test]
+    fn test_filter_of_testnet_genesis_block() -> Result<(), MessageError> {
+        let mut block_hash =
+            decode_hex("000000000933ea01ad0ee984209779baaec3ced90fa3f408719526f8d77f4943")
+                .map_err(|_| MessageError::ReadFromBytes)?;
+        block_hash.reverse();
+        let script = decode_hex("4104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac")
+            .map_err(|_| MessageError::ReadFromBytes)?;
+
+        let filter = BlockFilter::from_scripts(&block_hash, std::slice::from_ref(&script));
+
+        assert_eq!(filter.to_bytes(), vec![0x01, 0x9d, 0xfc, 0xa8]);
+        assert!(filter.match_any(&block_hash, &[vec![0x51], script]));
+        assert!(!filter.match_any(&block_hash, &[vec![0x51]]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_filter_matches_every_script() {
+        let block_hash = vec![7; 32];
+        let scripts: Vec<Vec<u8>> = (0..50u8).map(|byte| vec![0x76, byte]).collect();
+        let filter = BlockFilter::from_scripts(&block_hash, &scripts);
+
+        assert_eq!(
+            BlockFilter::from_bytes(filter.to_bytes()).ok(),
+            Some(filter.clone())
+        );
+        assert!(scripts
+            .iter()
+            .all(|script| filter.match_any(&block_hash, std::slice::from_ref(script))));
+        assert!(!BlockFilter::from_scripts(&block_hash, &[]).match_any(&block_hash, &scripts));
+    }
+}
@@ -1,4 +1,5 @@
 use super::super::messages::read_from_bytes::*;
+use super::difficulty::{decode_compact, encode_compact};
 use crate::messages::message_error::MessageError;
 use bitcoin_hashes::sha256d;
 use bitcoin_hashes::Hash;
@@ -83,16 +84,20 @@ impl BlockHeader {
     ///
     /// A `Vec<u8>` containing the byte representation of the `BlockHeader`.
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
+        let mut buffer = Vec::with_capacity(80);
 
+        self.to_bytes_into(&mut buffer);
+        buffer
+    }
+
+    /// Appends the byte representation of the `BlockHeader` to the given buffer.
+    pub fn to_bytes_into(&self, buffer: &mut Vec<u8>) {
         buffer.extend(self.block_version.to_le_bytes());
         buffer.extend(&self.previous_block_header_hash);
         buffer.extend(&self.merkle_root_hash);
         buffer.extend(self.time.to_le_bytes());
         buffer.extend(self.nbits.to_le_bytes());
         buffer.extend(self.nonce.to_le_bytes());
-
-        buffer
     }
 
     /// Computes the header hash of the `BlockHeader`.
@@ -135,25 +140,48 @@ impl BlockHeader {
     /// - `true` if the block satisfies the proof-of-work requirement.
     /// - `false` if the block does not satisfy the proof-of-work requirement.
     pub fn proof_of_work(&self) -> bool {
-        let nbits = self.nbits.to_be_bytes();
-        let exp = nbits[0];
-        let mantissa: Vec<u8> = nbits[1..].to_vec();
-
-        let mut threshold = vec![0u8; (32 - exp) as usize];
-        threshold.extend(mantissa);
-        threshold.extend(&vec![0u8; (exp - 3) as usize]);
-
-        let mut block_header = self.get_header();
-        block_header.reverse();
-
-        for i in 0..32 {
-            match block_header[i].cmp(&threshold[i]) {
-                Ordering::Greater => return false,
-                Ordering::Less => return true,
-                Ordering::Equal => {}
-            }
+        let target = self.target();
+
+        self.get_header().iter().rev().cmp(target.iter().rev()) != Ordering::Greater
+    }
+
+    /// Returns the 256-bit target encoded by the nbits of the header, as little-endian bytes.
+    pub fn target(&self) -> Vec<u8> {
+        decode_compact(self.nbits)
+    }
+
+    /// Returns the difficulty of the header: how many times harder its target is to meet than the
+    /// target of the nbits `0x1d00ffff`, the easiest one on mainnet.
+    pub fn difficulty(&self) -> f64 {
+        let mantissa = self.nbits & 0x00ffffff;
+        if mantissa == 0 {
+            return 0.0;
+        }
+
+        let shift = (self.nbits >> 24) as i32;
+        0x0000ffff as f64 / mantissa as f64 * 256f64.powi(29 - shift)
+    }
+
+    /// Compresses a 256-bit target, as little-endian bytes, into the nbits encoding it.
+    pub fn compact_from_target(target: &[u8]) -> u32 {
+        encode_compact(target)
+    }
+
+    /// Returns the work needed to mine the block, the expected amount of hashes needed to meet
+    /// its target, approximated as `2^256 / target`.
+    pub fn get_work(&self) -> u128 {
+        let exp = (self.nbits >> 24).clamp(3, 32);
+        let mantissa = (self.nbits & 0x00ffffff) as u128;
+
+        if mantissa == 0 {
+            return 0;
+        }
+
+        let shift = 256 - 8 * (exp - 3);
+        if shift >= 128 {
+            return u128::MAX / mantissa;
         }
-        true
+        (1u128 << shift) / mantissa
     }
 }
 
@@ -168,6 +196,7 @@ impl std::fmt::Display for BlockHeader {
         writeln!(f, "Merkle root: {:?}", self.merkle_root_hash)?;
         writeln!(f, "Time: {}", self.time)?;
         writeln!(f, "Number of bits: {}", self.nbits)?;
+        writeln!(f, "Difficulty: {}", self.difficulty())?;
         writeln!(f, "Nonce: {}", self.nonce)?;
         writeln!(f, "---------------------------------")?;
 
@@ -179,10 +208,7 @@ impl std::fmt::Display for BlockHeader {
 mod block_header_test {
     use std::num::ParseIntError;
 
-    use crate::network::network_constants::{
-        GENESIS_MERKLE_ROOT_HASH, GENESIS_NBITS, GENESIS_NONCE, GENESIS_PREVIOUS_BLOCK_HEADER_HASH,
-        GENESIS_TIME, GENESIS_VERSION,
-    };
+    use crate::network::{bitcoin_network::Network, network_constants::TESTNET_GENESIS_NBITS};
 
     use super::*;
 
@@ -244,17 +270,40 @@ mod block_header_test {
 
     #[test]
     fn test_proof_of_work_of_genesis_block() {
-        let genesis = BlockHeader::new(
-            GENESIS_VERSION,
-            GENESIS_PREVIOUS_BLOCK_HEADER_HASH.to_vec(),
-            GENESIS_MERKLE_ROOT_HASH.to_vec(),
-            GENESIS_TIME,
-            GENESIS_NBITS,
-            GENESIS_NONCE,
-        );
+        let genesis = Network::Testnet.get_genesis_header();
 
         println!("{}", genesis);
 
         assert!(genesis.proof_of_work());
     }
+
+    #[test]
+    fn test_work_of_genesis_block() {
+        let genesis = BlockHeader::new(1, vec![0; 32], vec![0; 32], 0, TESTNET_GENESIS_NBITS, 0);
+        let easier = BlockHeader::new(1, vec![0; 32], vec![0; 32], 0, 0x207fffff, 0);
+
+        assert_eq!(genesis.get_work(), 0x100010001);
+        assert_eq!(easier.get_work(), 2);
+    }
+
+    #[test]
+    fn test_target_and_difficulty_conversions() {
+        let genesis = BlockHeader::new(1, vec![0; 32], vec![0; 32], 0, 0x1d00ffff, 0);
+        let retargeted = BlockHeader::new(1, vec![0; 32], vec![0; 32], 0, 0x1b0404cb, 0);
+
+        let mut expected = vec![0u8; 32];
+        expected[26..28].copy_from_slice(&[0xff, 0xff]);
+        assert_eq!(genesis.target(), expected);
+        assert_eq!(
+            BlockHeader::compact_from_target(&genesis.target()),
+            0x1d00ffff
+        );
+        assert_eq!(
+            BlockHeader::compact_from_target(&retargeted.target()),
+            0x1b0404cb
+        );
+
+        assert_eq!(genesis.difficulty(), 1.0);
+        assert!((retargeted.difficulty() - 16307.420938523983).abs() < 1e-6);
+    }
 }
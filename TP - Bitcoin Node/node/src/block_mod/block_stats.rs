@@ -0,0 +1,100 @@
+/// Represents the statistics of a block, as reported by `getblockstats`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockStats {
+    tx_count: usize,
+    input_count: usize,
+    output_count: usize,
+    total_size: usize,
+    total_weight: usize,
+    total_fee: Option<u64>,
+    fee_paying_vsize: usize,
+}
+
+impl BlockStats {
+    /// Creates the statistics of a block.
+    ///
+    /// # Arguments
+    ///
+    /// * `counts` - The amount of transactions, inputs and outputs of the block.
+    /// * `total_size` - The size of the block, witness data included.
+    /// * `total_weight` - The weight of the block.
+    /// * `total_fee` - The fees paid by the transactions of the block, if they are known.
+    /// * `fee_paying_vsize` - The virtual size of every transaction of the block but the coinbase.
+    pub fn new(
+        counts: (usize, usize, usize),
+        total_size: usize,
+        total_weight: usize,
+        total_fee: Option<u64>,
+        fee_paying_vsize: usize,
+    ) -> BlockStats {
+        let (tx_count, input_count, output_count) = counts;
+
+        BlockStats {
+            tx_count,
+            input_count,
+            output_count,
+            total_size,
+            total_weight,
+            total_fee,
+            fee_paying_vsize,
+        }
+    }
+
+    /// Returns the amount of transactions of the block, the coinbase included.
+    pub fn get_tx_count(&self) -> usize {
+        self.tx_count
+    }
+
+    /// Returns the amount of inputs of the block, leaving out the input of the coinbase.
+    pub fn get_input_count(&self) -> usize {
+        self.input_count
+    }
+
+    /// Returns the amount of outputs of the block, the outputs of the coinbase included.
+    pub fn get_output_count(&self) -> usize {
+        self.output_count
+    }
+
+    pub fn get_total_size(&self) -> usize {
+        self.total_size
+    }
+
+    pub fn get_total_weight(&self) -> usize {
+        self.total_weight
+    }
+
+    /// Returns the fees paid by the transactions of the block, or `None` if the value of an output
+    /// they spend is not known.
+    pub fn get_total_fee(&self) -> Option<u64> {
+        self.total_fee
+    }
+
+    /// Returns the average fee rate of the transactions of the block, in satoshis per kilobyte of
+    /// virtual size, or `None` if the fees are not known. Blocks with only a coinbase have a fee
+    /// rate of 0.
+    pub fn get_avg_fee_rate(&self) -> Option<u64> {
+        let total_fee = self.total_fee?;
+
+        if self.fee_paying_vsize == 0 {
+            return Some(0);
+        }
+        Some(total_fee * 1000 / self.fee_paying_vsize as u64)
+    }
+}
+
+impl std::fmt::Display for BlockStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Transactions: {}", self.tx_count)?;
+        writeln!(f, "Inputs: {}", self.input_count)?;
+        writeln!(f, "Outputs: {}", self.output_count)?;
+        writeln!(f, "Size: {} bytes", self.total_size)?;
+        writeln!(f, "Weight: {} weight units", self.total_weight)?;
+        match (self.total_fee, self.get_avg_fee_rate()) {
+            (Some(total_fee), Some(fee_rate)) => {
+                writeln!(f, "Fees: {} satoshis", total_fee)?;
+                writeln!(f, "Average fee rate: {} satoshis/kB", fee_rate)
+            }
+            _ => writeln!(f, "Fees: unknown"),
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use super::{outpoint::Outpoint, utxo::Coin};
+use crate::messages::{
+    compact_size::CompactSizeUInt, message_error::MessageError,
+    read_from_bytes::read_vec_from_bytes,
+};
+use std::io::Read;
+
+/// Represents the undo data of a block: the coins it spent from the UTXO set when it was
+/// connected, so it can be disconnected later on and the set restored as it was before it.
+#[derive(Debug, Clone)]
+pub struct BlockUndo {
+    block_hash: Vec<u8>,
+    spent: Vec<(Outpoint, Coin)>,
+}
+
+impl BlockUndo {
+    pub fn new(block_hash: Vec<u8>, spent: Vec<(Outpoint, Coin)>) -> BlockUndo {
+        BlockUndo { block_hash, spent }
+    }
+
+    pub fn get_block_hash(&self) -> &Vec<u8> {
+        &self.block_hash
+    }
+
+    /// Returns the coins spent by the block, along with their outpoints.
+    pub fn get_spent(&self) -> &Vec<(Outpoint, Coin)> {
+        &self.spent
+    }
+
+    /// Consumes the undo data, returning the hash of the block and the coins it spent.
+    pub fn into_parts(self) -> (Vec<u8>, Vec<(Outpoint, Coin)>) {
+        (self.block_hash, self.spent)
+    }
+
+    /// Parses the undo data of a block from a byte stream.
+    ///
+    /// # Returns
+    ///
+    /// The undo data, or a `MessageError` if the bytes are truncated or malformed.
+    pub fn from_bytes(stream: &mut dyn Read) -> Result<BlockUndo, MessageError> {
+        let block_hash = read_vec_from_bytes(stream, 32)?;
+        let mut spent = vec![];
+
+        for _ in 0..CompactSizeUInt::from_bytes(stream)?.value() {
+            spent.push((Outpoint::from_bytes(stream)?, Coin::from_bytes(stream)?));
+        }
+        Ok(BlockUndo { block_hash, spent })
+    }
+
+    /// Serializes the undo data as the hash of the block, the count of coins it spent and each
+    /// outpoint followed by its output and, for coinbase outputs, the height of their block.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.block_hash.clone();
+
+        bytes.extend(CompactSizeUInt::from_number(self.spent.len() as u64).to_bytes());
+        for (outpoint, coin) in self.spent.iter() {
+            bytes.extend(outpoint.to_bytes());
+            coin.to_bytes_into(&mut bytes);
+        }
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod block_undo_test {
+    use super::*;
+    use crate::block_mod::tx_out::TxOut;
+
+    #[test]
+    fn test_undo_data_round_trip() -> Result<(), MessageError> {
+        let undo = BlockUndo::new(
+            vec![3; 32],
+            vec![
+                (
+                    Outpoint::new(vec![1; 32], 0),
+                    Coin::from(&TxOut::new(1000, vec![0x51])),
+                ),
+                (
+                    Outpoint::new(vec![2; 32], 7),
+                    Coin::from_coinbase(&TxOut::new(2500, vec![0; 25]), 120),
+                ),
+            ],
+        );
+        let bytes = undo.to_bytes();
+        let parsed = BlockUndo::from_bytes(&mut bytes.as_slice())?;
+
+        assert_eq!(parsed.get_block_hash(), &vec![3; 32]);
+        assert_eq!(
+            parsed
+                .get_spent()
+                .iter()
+                .map(|(_, coin)| coin.get_coinbase_height())
+                .collect::<Vec<_>>(),
+            vec![None, Some(120)]
+        );
+        assert_eq!(parsed.to_bytes(), bytes);
+        assert!(BlockUndo::from_bytes(&mut &bytes[..bytes.len() - 1]).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,404 @@
+use super::{
+    block::{calculate_witness_commitment, Block},
+    block_constants::{COINBASE_NULL_INDEX, MAX_BLOCK_WEIGHT},
+    invalid_blocks::{BlockRule, InvalidBlock},
+    transaction::add_money,
+    utxo::UnspentTx,
+};
+use crate::network::bitcoin_network::Network;
+use std::collections::{HashMap, HashSet};
+
+/// Validates the rules of a block that do not depend on the chain it extends: its weight must not
+/// exceed `MAX_BLOCK_WEIGHT`, its first transaction must be a coinbase, and the only one, and the
+/// coinbase must commit to the witness data of the block, if it has any.
+///
+/// # Errors
+///
+/// Returns the `InvalidBlock` holding the rule the block violates and the offending transaction,
+/// if any.
+pub fn check_block_structure(block: &Block) -> Result<(), InvalidBlock> {
+    if block.get_weight() > MAX_BLOCK_WEIGHT {
+        return Err(InvalidBlock::new(BlockRule::BadBlockWeight, None));
+    }
+
+    let coinbase_inputs = &block.get_coinbase().tx_in_list;
+    if coinbase_inputs.len() != 1
+        || !is_null_outpoint(&coinbase_inputs[0].hash, coinbase_inputs[0].index)
+    {
+        return Err(InvalidBlock::new(BlockRule::BadCoinbaseMissing, None));
+    }
+
+    for tx in block.get_txn_list() {
+        let spends_null_outpoint = tx.get_tx_in_list().iter().any(|tx_in| {
+            let outpoint = tx_in.get_prev_output();
+            is_null_outpoint(outpoint.get_tx_id(), outpoint.get_index())
+        });
+
+        if spends_null_outpoint {
+            return Err(InvalidBlock::new(
+                BlockRule::BadCoinbaseMultiple,
+                Some(tx.get_id(false)),
+            ));
+        }
+    }
+    check_witness_commitment(block)
+}
+
+/// Validates the witness commitment of a block (BIP141). If the coinbase holds a commitment, its
+/// witness must be a single reserved value and the commitment must be the hash of the witness
+/// root and that value, so the witness data cannot be tampered with. Otherwise, no transaction of
+/// the block may have witness data.
+fn check_witness_commitment(block: &Block) -> Result<(), InvalidBlock> {
+    let coinbase = block.get_coinbase();
+    let commitment = match coinbase.get_witness_commitment() {
+        Some(commitment) => commitment,
+        None if block.has_witness() || !coinbase.witness.is_empty() => {
+            return Err(InvalidBlock::new(BlockRule::UnexpectedWitness, None));
+        }
+        None => return Ok(()),
+    };
+
+    let reserved_value = coinbase
+        .get_witness_reserved_value()
+        .ok_or(InvalidBlock::new(BlockRule::BadWitnessNonceSize, None))?;
+
+    if calculate_witness_commitment(&block.get_witness_root(), reserved_value) != commitment {
+        return Err(InvalidBlock::new(BlockRule::BadWitnessMerkleMatch, None));
+    }
+    Ok(())
+}
+
+/// Validates the height the coinbase of a block encodes (BIP34), which must be the height of the
+/// block once the rule is active on the network, so no two coinbases have the same ID.
+///
+/// # Arguments
+///
+/// * `block` - The block to validate.
+/// * `network` - The network the block belongs to.
+/// * `height` - The height the block is connected at.
+///
+/// # Errors
+///
+/// Returns the `InvalidBlock` holding `BlockRule::BadCoinbaseHeight` if the heights differ.
+pub fn check_coinbase_height(
+    block: &Block,
+    network: Network,
+    height: u32,
+) -> Result<(), InvalidBlock> {
+    if height >= network.get_bip34_height() && block.get_coinbase().height() != Some(height) {
+        return Err(InvalidBlock::new(
+            BlockRule::BadCoinbaseHeight,
+            Some(block.get_coinbase().get_id()),
+        ));
+    }
+    Ok(())
+}
+
+/// Validates the transactions of a block against the UTXO set of the block it extends: every
+/// output they spend must be unspent, either in the set or created earlier in the block, their
+/// inputs must be worth at least as much as their outputs, and the coinbase must not claim more
+/// than the subsidy plus the fees of the block. Every value created, spent or paid as fees, and
+/// every total of them, must be in the range `0..=MAX_MONEY`.
+///
+/// If the UTXO set does not hold every unspent output of the chain, the outputs missing from it
+/// are assumed to exist, and the value of the coinbase is only checked if the value of every
+/// input is known.
+///
+/// # Arguments
+///
+/// * `block` - The block to validate.
+/// * `utxo` - The UTXO set, as of the parent of the block.
+/// * `subsidy` - The subsidy of the block at its height.
+/// * `complete` - Whether the UTXO set holds every unspent output of the chain.
+///
+/// # Errors
+///
+/// Returns the `InvalidBlock` holding the rule the block violates and the offending transaction,
+/// if any.
+pub fn check_block_spends(
+    block: &Block,
+    utxo: &UnspentTx,
+    subsidy: i64,
+    complete: bool,
+) -> Result<(), InvalidBlock> {
+    let mut created: HashMap<(Vec<u8>, u32), i64> = HashMap::new();
+    let mut spent: HashSet<(Vec<u8>, u32)> = HashSet::new();
+    let mut fees: Option<i64> = Some(0);
+
+    for tx in block.get_txn_list() {
+        let tx_id = tx.get_id(false);
+        let invalid = |rule| InvalidBlock::new(rule, Some(tx_id.clone()));
+        let output_value = tx
+            .output_value()
+            .ok_or_else(|| invalid(BlockRule::OutputValueOutOfRange))?;
+        let mut input_value: Option<i64> = Some(0);
+
+        for tx_in in tx.get_tx_in_list() {
+            let outpoint = tx_in.get_prev_output();
+            let key = (outpoint.get_tx_id().clone(), outpoint.get_index());
+
+            if !spent.insert(key.clone()) {
+                return Err(invalid(BlockRule::MissingOrSpentInputs));
+            }
+
+            let value = created
+                .get(&key)
+                .copied()
+                .or_else(|| utxo.get_tx_out(outpoint).map(|tx_out| tx_out.get_value()));
+            match value {
+                Some(value) => {
+                    input_value = input_value
+                        .map(|input_value| {
+                            add_money(input_value, value)
+                                .ok_or_else(|| invalid(BlockRule::InputValuesOutOfRange))
+                        })
+                        .transpose()?;
+                }
+                None if complete => return Err(invalid(BlockRule::MissingOrSpentInputs)),
+                None => input_value = None,
+            }
+        }
+
+        match input_value {
+            Some(input_value) if input_value < output_value => {
+                return Err(invalid(BlockRule::NegativeFee));
+            }
+            Some(input_value) => {
+                fees = fees
+                    .map(|fees| {
+                        add_money(fees, input_value - output_value)
+                            .ok_or_else(|| invalid(BlockRule::AccumulatedFeeOutOfRange))
+                    })
+                    .transpose()?;
+            }
+            None => fees = None,
+        }
+
+        for (index, tx_out) in tx.get_tx_out_list().iter().enumerate() {
+            created.insert((tx_id.clone(), index as u32), tx_out.get_value());
+        }
+    }
+
+    let coinbase_id = block.get_coinbase().get_id();
+    let claimed = block
+        .get_coinbase()
+        .tx_out_list
+        .iter()
+        .try_fold(0, |total, tx_out| add_money(total, tx_out.get_value()))
+        .ok_or_else(|| {
+            InvalidBlock::new(BlockRule::OutputValueOutOfRange, Some(coinbase_id.clone()))
+        })?;
+
+    match fees {
+        Some(fees) if claimed > subsidy.saturating_add(fees) => Err(InvalidBlock::new(
+            BlockRule::BadCoinbaseAmount,
+            Some(coinbase_id),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Returns whether the outpoint is the null one spent by coinbase transactions.
+fn is_null_outpoint(tx_id: &[u8], index: u32) -> bool {
+    index == COINBASE_NULL_INDEX && tx_id.iter().all(|byte| *byte == 0)
+}
+
+#[cfg(test)]
+mod block_validation_test {
+    use super::*;
+    use crate::block_mod::{
+        block_constants::INITIAL_BLOCK_SUBSIDY, miner::mine_block, transaction::Transaction,
+        tx_in::TxIn, tx_out::TxOut,
+    };
+    use crate::messages::message_error::MessageError;
+
+    fn transaction(prev_tx_id: Vec<u8>, prev_index: u32, value: i64) -> Transaction {
+        Transaction::new(
+            1,
+            vec![TxIn::new(prev_tx_id, prev_index, vec![], 0xffffffff)],
+            vec![TxOut::new(value, vec![0x51])],
+            0,
+            false,
+        )
+    }
+
+    /// Mines a regtest block whose coinbase claims the subsidy plus the given fees.
+    fn block(txn_list: Vec<Transaction>, fees: u64) -> Result<Block, MessageError> {
+        let genesis = Network::Regtest.get_genesis_header();
+
+        mine_block(Network::Regtest, &genesis, 1, (txn_list, fees), vec![0x51])
+            .ok_or(MessageError::InvalidInputSubmitBlock)
+    }
+
+    fn spends_rule(block: &Block, utxo: &UnspentTx, complete: bool) -> Option<BlockRule> {
+        check_block_spends(block, utxo, INITIAL_BLOCK_SUBSIDY, complete)
+            .err()
+            .map(|invalid_block| invalid_block.get_rule())
+    }
+
+    #[test]
+    fn test_block_spends_are_validated_against_the_utxo_set() -> Result<(), MessageError> {
+        let mut utxo = UnspentTx::new();
+        let funding = transaction(vec![0; 32], 0, 10000);
+        utxo.update_transaction(&funding);
+
+        let spending = transaction(funding.get_id(false), 0, 9000);
+        let chained = transaction(spending.get_id(false), 0, 8500);
+        let double_spending = transaction(funding.get_id(false), 0, 8000);
+        let unknown = transaction(vec![7; 32], 0, 1000);
+
+        let valid = block(vec![spending.clone(), chained], 1500)?;
+        assert_eq!(spends_rule(&valid, &utxo, true), None);
+
+        let greedy = block(vec![spending.clone()], 1001)?;
+        assert_eq!(
+            spends_rule(&greedy, &utxo, true),
+            Some(BlockRule::BadCoinbaseAmount)
+        );
+
+        let double_spend = block(vec![spending.clone(), double_spending.clone()], 0)?;
+        assert_eq!(
+            check_block_spends(&double_spend, &utxo, INITIAL_BLOCK_SUBSIDY, true),
+            Err(InvalidBlock::new(
+                BlockRule::MissingOrSpentInputs,
+                Some(double_spending.get_id(false))
+            ))
+        );
+
+        let overspending = block(vec![transaction(funding.get_id(false), 0, 11000)], 0)?;
+        assert_eq!(
+            spends_rule(&overspending, &utxo, true),
+            Some(BlockRule::NegativeFee)
+        );
+
+        // Outputs missing from an incomplete set are assumed to exist, so the fees are unknown
+        let missing = block(vec![unknown], 5000)?;
+        assert_eq!(
+            spends_rule(&missing, &utxo, true),
+            Some(BlockRule::MissingOrSpentInputs)
+        );
+        assert_eq!(spends_rule(&missing, &utxo, false), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_out_of_range_output_values_are_rejected() -> Result<(), MessageError> {
+        let mut utxo = UnspentTx::new();
+        let funding = transaction(vec![0; 32], 0, 10000);
+        utxo.update_transaction(&funding);
+
+        // The negative output would let the coinbase claim 15000 satoshis of fees
+        let mut inflating = transaction(funding.get_id(false), 0, 10000);
+        inflating.tx_out_list.push(TxOut::new(-15000, vec![0x51]));
+        let negative = block(vec![inflating], 15000)?;
+        assert_eq!(
+            spends_rule(&negative, &utxo, true),
+            Some(BlockRule::OutputValueOutOfRange)
+        );
+
+        let mut overflowing = transaction(funding.get_id(false), 0, i64::MAX);
+        overflowing
+            .tx_out_list
+            .push(TxOut::new(i64::MAX, vec![0x51]));
+        let overflow = block(vec![overflowing], 0)?;
+        assert_eq!(
+            spends_rule(&overflow, &utxo, false),
+            Some(BlockRule::OutputValueOutOfRange)
+        );
+
+        let mut negative_coinbase = block(vec![], 0)?;
+        negative_coinbase
+            .coinbase
+            .tx_out_list
+            .push(TxOut::new(-1, vec![0x51]));
+        assert_eq!(
+            spends_rule(&negative_coinbase, &utxo, true),
+            Some(BlockRule::OutputValueOutOfRange)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_only_the_first_transaction_is_a_coinbase() -> Result<(), MessageError> {
+        let valid = block(vec![transaction(vec![1; 32], 0, 1000)], 0)?;
+        assert_eq!(check_block_structure(&valid), Ok(()));
+
+        let extra_coinbase = transaction(vec![0; 32], COINBASE_NULL_INDEX, 1000);
+        let invalid = block(vec![extra_coinbase.clone()], 0)?;
+        assert_eq!(
+            check_block_structure(&invalid),
+            Err(InvalidBlock::new(
+                BlockRule::BadCoinbaseMultiple,
+                Some(extra_coinbase.get_id(false))
+            ))
+        );
+
+        let mut missing = block(vec![], 0)?;
+        missing.coinbase.tx_in_list[0].index = 0;
+        assert_eq!(
+            check_block_structure(&missing).map_err(|error| error.get_rule()),
+            Err(BlockRule::BadCoinbaseMissing)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_witness_data_must_match_the_commitment() -> Result<(), MessageError> {
+        let mut segwit_tx = transaction(vec![1; 32], 0, 1000);
+        segwit_tx.flag = 1;
+        segwit_tx.set_witness(vec![vec![0x51]]);
+
+        let valid = block(vec![segwit_tx.clone()], 0)?;
+        assert_eq!(check_block_structure(&valid), Ok(()));
+        let bytes = valid.to_bytes();
+        assert_eq!(Block::from_bytes(&mut bytes.as_slice())?.to_bytes(), bytes);
+
+        let mut tampered = block(vec![segwit_tx.clone()], 0)?;
+        tampered.txn_list[0].witness[0].stack_items[0] = vec![0x52];
+        assert_eq!(
+            check_block_structure(&tampered).map_err(|error| error.get_rule()),
+            Err(BlockRule::BadWitnessMerkleMatch)
+        );
+
+        let mut bad_nonce = block(vec![segwit_tx.clone()], 0)?;
+        bad_nonce.coinbase.witness[0].stack_items.push(vec![]);
+        assert_eq!(
+            check_block_structure(&bad_nonce).map_err(|error| error.get_rule()),
+            Err(BlockRule::BadWitnessNonceSize)
+        );
+
+        let mut uncommitted = block(vec![segwit_tx], 0)?;
+        uncommitted.coinbase.tx_out_list.pop();
+        uncommitted.coinbase.witness.clear();
+        assert_eq!(
+            check_block_structure(&uncommitted).map_err(|error| error.get_rule()),
+            Err(BlockRule::UnexpectedWitness)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_coinbase_must_encode_the_height_of_the_block() -> Result<(), MessageError> {
+        let valid = block(vec![], 0)?;
+        assert_eq!(valid.get_coinbase().height(), Some(1));
+        assert_eq!(check_coinbase_height(&valid, Network::Regtest, 1), Ok(()));
+        assert_eq!(
+            check_coinbase_height(&valid, Network::Regtest, 2).map_err(|error| error.get_rule()),
+            Err(BlockRule::BadCoinbaseHeight)
+        );
+        // Blocks older than BIP34 may encode anything
+        assert_eq!(check_coinbase_height(&valid, Network::Mainnet, 2), Ok(()));
+
+        let mut small_int = block(vec![], 0)?;
+        small_int.coinbase.tx_in_list[0].height.bytes = 0x51;
+        small_int.coinbase.tx_in_list[0].height.read_bytes = vec![];
+        assert_eq!(small_int.get_coinbase().height(), Some(1));
+
+        // Heights must be pushed with as few bytes as possible
+        let mut padded = block(vec![], 0)?;
+        padded.coinbase.tx_in_list[0].height.bytes = 2;
+        padded.coinbase.tx_in_list[0].height.read_bytes = vec![1, 0];
+        assert_eq!(padded.get_coinbase().height(), None);
+        Ok(())
+    }
+}
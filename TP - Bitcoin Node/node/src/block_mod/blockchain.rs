@@ -1,31 +1,65 @@
-use std::collections::HashMap;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
 
-use crate::network::network_constants::{
-    GENESIS_MERKLE_ROOT_HASH, GENESIS_NBITS, GENESIS_NONCE, GENESIS_PREVIOUS_BLOCK_HEADER_HASH,
-    GENESIS_TIME, GENESIS_VERSION,
+use crate::network::bitcoin_network::Network;
+
+use super::{
+    block::Block,
+    block_constants::{LOCATOR_DENSE_COUNT, MAX_REORG_DEPTH},
+    block_filter::BlockFilter,
+    block_header::BlockHeader,
+    chain_tip::{ChainTip, ChainTipStatus},
+    chain_update::ChainUpdate,
+    outpoint::Outpoint,
+    utxo::Coin,
 };
 
-use super::{block::Block, block_header::BlockHeader};
+/// Maps the ID of a transaction to the hash of the block containing it and its position within it.
+type TxIndex = HashMap<Vec<u8>, (Vec<u8>, u32)>;
 
 /// Represents a blockchain and maintains information about blocks and the last block header.
+///
+/// The compact filters (BIP158) of the blocks connected to the UTXO set are kept as well, so they
+/// can be served to light clients.
+///
+/// The blocks of the main chain are indexed by height, once the height of the chain is known
+/// through `set_main_chain`, and the index follows the blocks connected afterwards.
+///
+/// Every block keeps its cumulative work since the first downloaded block, so the branch with the
+/// most work is found without walking back through its blocks.
+///
+/// The tips of every branch, the blocks no other block builds on, are kept along with the main
+/// chain, so competing branches can be listed.
+///
+/// If the transaction index is enabled, every transaction is mapped to the hash of the block
+/// containing it and its position within the block, so it is found without walking the chain.
 pub struct BlockChain {
     blocks: HashMap<Vec<u8>, Block>,
+    filters: HashMap<Vec<u8>, BlockFilter>,
     last_block_header: BlockHeader,
+    hashes_by_height: HashMap<u32, Vec<u8>>,
+    heights: HashMap<Vec<u8>, u32>,
+    chain_work: HashMap<Vec<u8>, u128>,
+    best_tip: Vec<u8>,
+    tips: HashSet<Vec<u8>>,
+    parents: HashSet<Vec<u8>>,
+    tx_index: Option<TxIndex>,
 }
 
 impl BlockChain {
-    /// Creates a new `BlockChain` object with default values for the genesis block.
-    pub fn new() -> BlockChain {
+    /// Creates a new `BlockChain` object starting at the genesis block of the given network.
+    pub fn new(network: Network) -> BlockChain {
         BlockChain {
             blocks: HashMap::new(),
-            last_block_header: BlockHeader::new(
-                GENESIS_VERSION,
-                GENESIS_PREVIOUS_BLOCK_HEADER_HASH.to_vec(),
-                GENESIS_MERKLE_ROOT_HASH.to_vec(),
-                GENESIS_TIME,
-                GENESIS_NBITS,
-                GENESIS_NONCE,
-            ),
+            filters: HashMap::new(),
+            last_block_header: network.get_genesis_header(),
+            hashes_by_height: HashMap::new(),
+            heights: HashMap::new(),
+            chain_work: HashMap::new(),
+            best_tip: network.get_genesis_header().get_header(),
+            tips: HashSet::new(),
+            parents: HashSet::new(),
+            tx_index: None,
         }
     }
 
@@ -40,9 +74,367 @@ impl BlockChain {
             self.last_block_header = block_header.clone();
         }
 
+        self.index_work(block_header);
+        self.index_tip(block_header);
+        self.index_transactions(&block);
         self.blocks.insert(block_header.get_header(), block);
     }
 
+    /// Enables the transaction index, indexing the transactions of the blocks already added. The
+    /// transactions of the blocks added afterwards are indexed as they are added.
+    pub fn enable_tx_index(&mut self) {
+        self.tx_index = Some(HashMap::new());
+
+        let blocks: Vec<Vec<u8>> = self.blocks.keys().cloned().collect();
+        for block_hash in blocks {
+            self.reindex_transactions(&block_hash);
+        }
+        // Transactions repeated on a side branch point to the block of the main chain
+        let main_chain: Vec<Vec<u8>> = self.hashes_by_height.values().cloned().collect();
+        for block_hash in main_chain {
+            self.reindex_transactions(&block_hash);
+        }
+    }
+
+    /// Returns whether the transaction index is enabled.
+    pub fn has_tx_index(&self) -> bool {
+        self.tx_index.is_some()
+    }
+
+    /// Maps the transactions of a block to its hash and their position within it, if the
+    /// transaction index is enabled. The coinbase is at position 0.
+    fn index_transactions(&mut self, block: &Block) {
+        if let Some(tx_index) = self.tx_index.as_mut() {
+            let block_hash = block.get_header().get_header();
+
+            for (position, tx_id) in block.get_txn_ids().into_iter().enumerate() {
+                tx_index.insert(tx_id, (block_hash.clone(), position as u32));
+            }
+        }
+    }
+
+    /// Indexes the transactions of a stored block again, so they point to it.
+    fn reindex_transactions(&mut self, block_hash: &Vec<u8>) {
+        if let Some(block) = self.blocks.remove(block_hash) {
+            self.index_transactions(&block);
+            self.blocks.insert(block_hash.clone(), block);
+        }
+    }
+
+    /// Sets the main chain, such as the blocks persisted by a previous run, making its last block
+    /// the tip and indexing its blocks by height, by cumulative work and, if the transaction index
+    /// is enabled, its transactions.
+    ///
+    /// # Arguments
+    ///
+    /// * `headers` - The headers of the main chain, in chain order.
+    /// * `start_height` - The height of the first header.
+    pub fn set_main_chain(&mut self, headers: &[BlockHeader], start_height: u32) {
+        self.hashes_by_height.clear();
+        self.heights.clear();
+
+        // Blocks added while downloading may have arrived before their parents
+        for (offset, header) in headers.iter().enumerate() {
+            self.index_height(header.get_header(), start_height + offset as u32);
+            self.index_work(header);
+            if self.has_tx_index() {
+                self.reindex_transactions(&header.get_header());
+            }
+        }
+        if let Some(last) = headers.last() {
+            self.last_block_header = last.clone();
+
+            if self.get_chain_work(&last.get_header()) >= self.get_chain_work(&self.best_tip) {
+                self.best_tip = last.get_header();
+            }
+        }
+    }
+
+    /// Records the cumulative work of a block, from the work of its parent, and makes it the best
+    /// tip if no known block has more work. A block whose parent is not known only counts its own
+    /// work.
+    ///
+    /// # Returns
+    ///
+    /// The cumulative work of the block.
+    fn index_work(&mut self, block_header: &BlockHeader) -> u128 {
+        let parent_work = self
+            .get_chain_work(block_header.get_previuos_block_header())
+            .unwrap_or(0);
+        let chain_work = parent_work.saturating_add(block_header.get_work());
+        let block_hash = block_header.get_header();
+
+        if self
+            .get_chain_work(&self.best_tip)
+            .is_none_or(|best_work| chain_work > best_work)
+        {
+            self.best_tip = block_hash.clone();
+        }
+        self.chain_work.insert(block_hash, chain_work);
+        chain_work
+    }
+
+    /// Makes a block a tip, unless a block building on it was already added, and makes its parent
+    /// no longer one.
+    fn index_tip(&mut self, block_header: &BlockHeader) {
+        let parent = block_header.get_previuos_block_header();
+        let block_hash = block_header.get_header();
+
+        self.tips.remove(parent);
+        self.parents.insert(parent.clone());
+        if !self.parents.contains(&block_hash) {
+            self.tips.insert(block_hash);
+        }
+    }
+
+    /// Indexes a block of the main chain at the given height.
+    fn index_height(&mut self, block_hash: Vec<u8>, height: u32) {
+        if let Some(replaced) = self.hashes_by_height.insert(height, block_hash.clone()) {
+            self.heights.remove(&replaced);
+        }
+        self.heights.insert(block_hash, height);
+    }
+
+    /// Connects a block received from the network, following the branch with most work.
+    ///
+    /// If the block extends the tip of the main chain, it becomes the new tip. If it extends a
+    /// competing branch whose cumulative work exceeds the work of the main chain, the main chain is
+    /// reorganized onto that branch. Otherwise, the block is only stored, so a branch with as much
+    /// work as the main chain does not replace it.
+    ///
+    /// Reorganizations deeper than `MAX_REORG_DEPTH` blocks are not followed, since the UTXO set
+    /// cannot disconnect that many blocks.
+    ///
+    /// # Arguments
+    ///
+    /// * `block` - The `Block` object to be connected to the blockchain.
+    ///
+    /// # Returns
+    ///
+    /// A `ChainUpdate` describing how the main chain changed.
+    pub fn connect(&mut self, block: Block) -> ChainUpdate {
+        let block_header = block.get_header().clone();
+        let block_hash = block_header.get_header();
+        let tip = self.get_last_block_header();
+        let tip_height = self.tip_height();
+        let tip_work = self.get_chain_work(&tip).unwrap_or(0);
+        let chain_work = self.index_work(&block_header);
+        self.index_tip(&block_header);
+        self.index_transactions(&block);
+        self.blocks.insert(block_hash.clone(), block);
+
+        if *block_header.get_previuos_block_header() == tip {
+            self.last_block_header = block_header;
+            if let Some(tip_height) = tip_height {
+                self.index_height(block_hash, tip_height + 1);
+            }
+            return ChainUpdate::Extended;
+        }
+
+        if chain_work <= tip_work {
+            return ChainUpdate::SideBranch;
+        }
+
+        let main_chain = self.get_ancestors(&tip, MAX_REORG_DEPTH + 1);
+        let positions: HashMap<&Vec<u8>, usize> = main_chain
+            .iter()
+            .enumerate()
+            .map(|(position, hash)| (hash, position))
+            .collect();
+
+        let mut connected = vec![];
+        let mut hash = block_hash;
+
+        let fork_position = loop {
+            if let Some(position) = positions.get(&hash) {
+                break *position;
+            }
+
+            let block = match self.blocks.get(&hash) {
+                Some(block) if connected.len() <= MAX_REORG_DEPTH => block,
+                _ => return ChainUpdate::SideBranch,
+            };
+
+            connected.push(hash);
+            hash = block.get_previuos_block_header().clone();
+        };
+
+        connected.reverse();
+        self.last_block_header = block_header;
+
+        for hash in main_chain[..fork_position].iter() {
+            if let Some(height) = self.heights.remove(hash) {
+                self.hashes_by_height.remove(&height);
+            }
+        }
+        if let Some(tip_height) = tip_height {
+            let fork_height = tip_height - fork_position as u32;
+
+            for (offset, hash) in connected.iter().enumerate() {
+                self.index_height(hash.clone(), fork_height + 1 + offset as u32);
+            }
+        }
+        for hash in connected.iter() {
+            self.reindex_transactions(hash);
+        }
+
+        ChainUpdate::Reorganized {
+            disconnected: main_chain[..fork_position].to_vec(),
+            connected,
+        }
+    }
+
+    /// Restores the main chain as it was before a block was connected, once a block of the new
+    /// branch fails the validation of its spends. The blocks of the new branch stay stored, but are
+    /// no longer indexed by height, and the blocks of the old branch are indexed again.
+    ///
+    /// # Arguments
+    ///
+    /// * `tip` - The header of the tip of the main chain before the block was connected.
+    /// * `disconnected` - The blocks `connect` disconnected from the main chain, the old tip first.
+    /// * `connected` - The blocks `connect` connected after the fork point, the new tip last.
+    pub fn restore_main_chain(
+        &mut self,
+        tip: BlockHeader,
+        disconnected: &[Vec<u8>],
+        connected: &[Vec<u8>],
+    ) {
+        let fork_height = connected
+            .first()
+            .and_then(|hash| self.get_height(hash))
+            .and_then(|height| height.checked_sub(1));
+
+        for hash in connected.iter() {
+            if let Some(height) = self.heights.remove(hash) {
+                self.hashes_by_height.remove(&height);
+            }
+        }
+        if let Some(fork_height) = fork_height {
+            for (offset, hash) in disconnected.iter().rev().enumerate() {
+                self.index_height(hash.clone(), fork_height + 1 + offset as u32);
+            }
+        }
+        for hash in disconnected.iter() {
+            self.reindex_transactions(hash);
+        }
+
+        self.best_tip = tip.get_header();
+        self.last_block_header = tip;
+    }
+
+    /// Returns the cumulative work of a block since the first downloaded block, if the block is
+    /// known.
+    pub fn get_chain_work(&self, block_hash: &Vec<u8>) -> Option<u128> {
+        self.chain_work.get(block_hash).copied()
+    }
+
+    /// Returns the hash of the known block with the most cumulative work.
+    ///
+    /// It is the tip of the main chain, unless the branch it belongs to forks from the main chain
+    /// deeper than `MAX_REORG_DEPTH` blocks, or some of its blocks are missing.
+    pub fn best_tip(&self) -> &Vec<u8> {
+        &self.best_tip
+    }
+
+    /// Returns the tips of the blockchain, the tip of the main chain first and the rest by
+    /// decreasing cumulative work.
+    ///
+    /// The branch of each tip is walked back until it meets one of the last `MAX_REORG_DEPTH`
+    /// blocks of the main chain. Tips whose branch does not meet it are stale.
+    pub fn get_chain_tips(&self) -> Vec<ChainTip> {
+        let active = self.get_last_block_header();
+        let main_chain = self.get_ancestors(&active, MAX_REORG_DEPTH + 1);
+        let positions: HashMap<&Vec<u8>, usize> = main_chain
+            .iter()
+            .enumerate()
+            .map(|(position, hash)| (hash, position))
+            .collect();
+        let active_height = self.tip_height();
+
+        let mut chain_tips: Vec<ChainTip> = self
+            .tips
+            .iter()
+            .filter(|tip| **tip != active)
+            .map(|tip| {
+                let mut branch_len = 0;
+                let mut hash = tip;
+
+                let fork = loop {
+                    if let Some(position) = positions.get(hash) {
+                        break Some(*position);
+                    }
+                    match self.blocks.get(hash) {
+                        Some(block) if branch_len <= MAX_REORG_DEPTH => {
+                            branch_len += 1;
+                            hash = block.get_previuos_block_header();
+                        }
+                        _ => break None,
+                    }
+                };
+                let (height, status) = match fork {
+                    Some(position) => (
+                        active_height.map(|height| height - position as u32 + branch_len as u32),
+                        ChainTipStatus::ValidFork,
+                    ),
+                    None => (None, ChainTipStatus::Stale),
+                };
+                let chain_work = self.get_chain_work(tip).unwrap_or(0);
+
+                ChainTip::new(tip.clone(), height, branch_len, chain_work, status)
+            })
+            .collect();
+        chain_tips.sort_by_key(|chain_tip| Reverse(chain_tip.get_chain_work()));
+
+        chain_tips.insert(
+            0,
+            ChainTip::new(
+                active.clone(),
+                active_height,
+                0,
+                self.get_chain_work(&active).unwrap_or(0),
+                ChainTipStatus::Active,
+            ),
+        );
+        chain_tips
+    }
+
+    /// Returns the hashes of a block and at most `limit - 1` of its downloaded ancestors, the block
+    /// first.
+    fn get_ancestors(&self, block_hash: &[u8], limit: usize) -> Vec<Vec<u8>> {
+        let mut ancestors = vec![];
+        let mut hash = block_hash.to_vec();
+
+        while let Some(block) = self.blocks.get(&hash) {
+            if ancestors.len() >= limit {
+                break;
+            }
+            ancestors.push(hash);
+            hash = block.get_previuos_block_header().clone();
+        }
+        ancestors
+    }
+
+    /// Builds the block locator of the main chain, so a peer on a different branch can find the
+    /// point where its chain forks from this one.
+    ///
+    /// # Returns
+    ///
+    /// The hashes of the tip and its downloaded ancestors at the positions given by
+    /// `locator_positions`, the tip first and the oldest downloaded block last.
+    pub fn build_locator(&self) -> Vec<Vec<u8>> {
+        let tip = self.get_last_block_header();
+        let ancestors = self.get_ancestors(&tip, usize::MAX);
+
+        if ancestors.is_empty() {
+            return vec![tip];
+        }
+
+        locator_positions(ancestors.len())
+            .into_iter()
+            .map(|position| ancestors[position].clone())
+            .collect()
+    }
+
     /// Returns the number of blocks in the blockchain.
     ///
     /// # Returns
@@ -71,6 +463,27 @@ impl BlockChain {
         self.last_block_header.get_header()
     }
 
+    /// Returns the header of the tip of the main chain.
+    pub fn get_tip_header(&self) -> &BlockHeader {
+        &self.last_block_header
+    }
+
+    /// Returns the block of the main chain at the given height, if it is downloaded and indexed.
+    pub fn get_block_by_height(&self, height: u32) -> Option<&Block> {
+        self.blocks.get(self.hashes_by_height.get(&height)?)
+    }
+
+    /// Returns the height of the block with the given hash, if it belongs to the main chain and is
+    /// indexed.
+    pub fn get_height(&self, block_hash: &Vec<u8>) -> Option<u32> {
+        self.heights.get(block_hash).copied()
+    }
+
+    /// Returns the height of the tip of the main chain, if it is indexed.
+    pub fn tip_height(&self) -> Option<u32> {
+        self.get_height(&self.get_last_block_header())
+    }
+
     /// Returns a reference to the block associated with the given block header.
     ///
     /// # Arguments
@@ -83,10 +496,359 @@ impl BlockChain {
     pub fn get_block(&self, block_header: &Vec<u8>) -> Option<&Block> {
         self.blocks.get(block_header)
     }
+
+    /// Builds and keeps the compact filter of a block of the blockchain.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_hash` - The hash of the block.
+    /// * `spent` - The coins spent by the block, as kept in its undo data.
+    pub fn add_filter(&mut self, block_hash: &Vec<u8>, spent: &[(Outpoint, Coin)]) {
+        if let Some(block) = self.blocks.get(block_hash) {
+            self.filters
+                .insert(block_hash.clone(), BlockFilter::new(block, spent));
+        }
+    }
+
+    /// Keeps a compact filter that was already built, such as one persisted by a previous run.
+    pub fn insert_filter(&mut self, block_hash: Vec<u8>, filter: BlockFilter) {
+        self.filters.insert(block_hash, filter);
+    }
+
+    /// Returns the compact filters of the blocks, keyed by block hash.
+    pub fn get_filters(&self) -> &HashMap<Vec<u8>, BlockFilter> {
+        &self.filters
+    }
+
+    /// Returns the compact filter of the block with the given hash, if it was built.
+    pub fn get_filter(&self, block_hash: &Vec<u8>) -> Option<&BlockFilter> {
+        self.filters.get(block_hash)
+    }
+
+    /// Returns the header of the block containing the given transaction.
+    ///
+    /// It is looked up in the transaction index, if it is enabled, and by walking the downloaded
+    /// blocks otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx_id` - The ID of the transaction.
+    ///
+    /// # Returns
+    ///
+    /// An `Option` containing the header of the block, or `None` if the transaction is not in any
+    /// downloaded block.
+    pub fn get_transaction_block(&self, tx_id: &Vec<u8>) -> Option<&BlockHeader> {
+        if let Some(tx_index) = &self.tx_index {
+            let (block_hash, _) = tx_index.get(tx_id)?;
+            return self.blocks.get(block_hash).map(|block| block.get_header());
+        }
+
+        self.blocks
+            .values()
+            .find(|block| block.get_txn_ids().contains(tx_id))
+            .map(|block| block.get_header())
+    }
+
+    /// Returns the hash of the block containing the given transaction and its position within the
+    /// block, the coinbase being at position 0, or `None` if the transaction index is not enabled
+    /// or the transaction is not in any downloaded block.
+    pub fn get_tx_location(&self, tx_id: &Vec<u8>) -> Option<(&Vec<u8>, u32)> {
+        self.tx_index
+            .as_ref()?
+            .get(tx_id)
+            .map(|(block_hash, position)| (block_hash, *position))
+    }
+}
+
+/// Returns the positions, counted back from the tip, of the hashes of a block locator over a
+/// chain of the given length: the last `LOCATOR_DENSE_COUNT` blocks one by one, then with a step
+/// that doubles with every hash, ending with the first block of the chain.
+///
+/// # Arguments
+///
+/// * `length` - The amount of blocks of the chain.
+pub fn locator_positions(length: usize) -> Vec<usize> {
+    let mut positions = vec![];
+    let mut position = 0;
+    let mut step = 1;
+
+    while position < length {
+        positions.push(position);
+        if positions.len() >= LOCATOR_DENSE_COUNT {
+            step *= 2;
+        }
+        position += step;
+    }
+
+    if let Some(first) = length.checked_sub(1) {
+        if positions.last() != Some(&first) {
+            positions.push(first);
+        }
+    }
+    positions
 }
 
 impl Default for BlockChain {
     fn default() -> Self {
-        Self::new()
+        Self::new(Network::default())
+    }
+}
+
+#[cfg(test)]
+mod blockchain_test {
+    use super::*;
+    use crate::block_mod::block::test_block_bytes;
+    use crate::messages::message_error::MessageError;
+
+    fn block_after(previous: Vec<u8>, nonce: u32) -> Result<Block, MessageError> {
+        let mut block = Block::from_bytes(&mut test_block_bytes().as_slice())?;
+        block.block_header = BlockHeader::new(1, previous, vec![0; 32], 0, 0x207fffff, nonce);
+        Ok(block)
+    }
+
+    #[test]
+    fn test_chain_is_reorganized_onto_branch_with_most_work() -> Result<(), MessageError> {
+        let mut blockchain = BlockChain::new(Network::Testnet);
+
+        let first = block_after(blockchain.get_last_block_header(), 0)?;
+        let first_hash = first.get_header().get_header();
+        let tip = block_after(first_hash.clone(), 1)?;
+        let tip_hash = tip.get_header().get_header();
+        let competing = block_after(first_hash.clone(), 2)?;
+        let competing_hash = competing.get_header().get_header();
+        let new_tip = block_after(competing_hash.clone(), 3)?;
+        let new_tip_hash = new_tip.get_header().get_header();
+
+        assert_eq!(blockchain.connect(first), ChainUpdate::Extended);
+        assert_eq!(blockchain.connect(tip), ChainUpdate::Extended);
+        assert_eq!(blockchain.connect(competing), ChainUpdate::SideBranch);
+        assert_eq!(blockchain.get_last_block_header(), tip_hash);
+
+        assert_eq!(
+            blockchain.connect(new_tip),
+            ChainUpdate::Reorganized {
+                disconnected: vec![tip_hash],
+                connected: vec![competing_hash, new_tip_hash.clone()],
+            }
+        );
+        assert_eq!(blockchain.get_last_block_header(), new_tip_hash);
+        Ok(())
+    }
+
+    #[test]
+    fn test_shorter_branch_with_more_work_becomes_the_main_chain() -> Result<(), MessageError> {
+        let mut blockchain = BlockChain::new(Network::Testnet);
+
+        let base = block_after(blockchain.get_last_block_header(), 0)?;
+        let base_hash = base.get_header().get_header();
+        let first = block_after(base_hash.clone(), 1)?;
+        let first_hash = first.get_header().get_header();
+        let second = block_after(first_hash.clone(), 2)?;
+        let second_hash = second.get_header().get_header();
+        let tied = block_after(first_hash.clone(), 3)?;
+        let mut harder = block_after(base_hash.clone(), 4)?;
+        harder.block_header = BlockHeader::new(1, base_hash.clone(), vec![0; 32], 0, 0x1d00ffff, 4);
+        let harder_hash = harder.get_header().get_header();
+
+        blockchain.connect(base);
+        blockchain.connect(first);
+        blockchain.connect(second);
+        assert_eq!(
+            blockchain.get_chain_work(&second_hash),
+            Some(3 * BlockHeader::new(1, vec![], vec![], 0, 0x207fffff, 0).get_work())
+        );
+
+        // The first branch seen is kept when the work is tied
+        assert_eq!(blockchain.connect(tied), ChainUpdate::SideBranch);
+        assert_eq!(blockchain.best_tip(), &second_hash);
+
+        assert_eq!(
+            blockchain.connect(harder),
+            ChainUpdate::Reorganized {
+                disconnected: vec![second_hash, first_hash],
+                connected: vec![harder_hash.clone()],
+            }
+        );
+        assert_eq!(blockchain.best_tip(), &harder_hash);
+        assert_eq!(blockchain.get_last_block_header(), harder_hash);
+        Ok(())
+    }
+
+    #[test]
+    fn test_main_chain_is_restored_after_a_failed_reorganization() -> Result<(), MessageError> {
+        let mut blockchain = BlockChain::new(Network::Testnet);
+        blockchain.set_main_chain(&[Network::Testnet.get_genesis_header()], 0);
+
+        let first = block_after(blockchain.get_last_block_header(), 0)?;
+        let first_hash = first.get_header().get_header();
+        let tip = block_after(first_hash.clone(), 1)?;
+        let tip_hash = tip.get_header().get_header();
+        let competing = block_after(first_hash.clone(), 2)?;
+        let competing_hash = competing.get_header().get_header();
+        let new_tip = block_after(competing_hash.clone(), 3)?;
+        let new_tip_hash = new_tip.get_header().get_header();
+
+        blockchain.connect(first);
+        blockchain.connect(tip);
+        blockchain.connect(competing);
+        let old_tip = blockchain.get_tip_header().clone();
+
+        let disconnected = vec![tip_hash.clone()];
+        let connected = vec![competing_hash.clone(), new_tip_hash];
+        assert_eq!(
+            blockchain.connect(new_tip),
+            ChainUpdate::Reorganized {
+                disconnected: disconnected.clone(),
+                connected: connected.clone(),
+            }
+        );
+        blockchain.restore_main_chain(old_tip, &disconnected, &connected);
+
+        assert_eq!(blockchain.get_last_block_header(), tip_hash);
+        assert_eq!(blockchain.best_tip(), &tip_hash);
+        assert_eq!(blockchain.tip_height(), Some(2));
+        assert_eq!(blockchain.get_height(&competing_hash), None);
+        assert_eq!(
+            blockchain
+                .get_block_by_height(2)
+                .map(|block| block.get_header().get_header()),
+            Some(tip_hash)
+        );
+        assert!(blockchain.get_block(&competing_hash).is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_height_index_follows_the_main_chain() -> Result<(), MessageError> {
+        let mut blockchain = BlockChain::new(Network::Testnet);
+        assert_eq!(blockchain.tip_height(), None);
+        blockchain.set_main_chain(&[Network::Testnet.get_genesis_header()], 0);
+        assert_eq!(blockchain.tip_height(), Some(0));
+
+        let first = block_after(blockchain.get_last_block_header(), 0)?;
+        let first_hash = first.get_header().get_header();
+        let tip = block_after(first_hash.clone(), 1)?;
+        let tip_hash = tip.get_header().get_header();
+        let competing = block_after(first_hash.clone(), 2)?;
+        let competing_hash = competing.get_header().get_header();
+        let new_tip = block_after(competing_hash.clone(), 3)?;
+
+        blockchain.connect(first);
+        blockchain.connect(tip);
+        blockchain.connect(competing);
+        assert_eq!(blockchain.tip_height(), Some(2));
+        assert_eq!(blockchain.get_height(&tip_hash), Some(2));
+        assert_eq!(blockchain.get_height(&competing_hash), None);
+
+        blockchain.connect(new_tip);
+        assert_eq!(blockchain.tip_height(), Some(3));
+        assert_eq!(blockchain.get_height(&tip_hash), None);
+        assert_eq!(
+            blockchain
+                .get_block_by_height(2)
+                .map(|block| block.get_header().get_header()),
+            Some(competing_hash)
+        );
+        assert_eq!(blockchain.get_height(&first_hash), Some(1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_locator_steps_back_exponentially() -> Result<(), MessageError> {
+        assert_eq!(locator_positions(0), Vec::<usize>::new());
+        assert_eq!(locator_positions(3), vec![0, 1, 2]);
+        assert_eq!(
+            locator_positions(100),
+            vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 11, 15, 23, 39, 71, 99]
+        );
+
+        let mut blockchain = BlockChain::new(Network::Testnet);
+        let genesis_hash = blockchain.get_last_block_header();
+        assert_eq!(blockchain.build_locator(), vec![genesis_hash.clone()]);
+
+        let mut hashes = vec![];
+        for nonce in 0..12 {
+            let block = block_after(blockchain.get_last_block_header(), nonce)?;
+            hashes.push(block.get_header().get_header());
+            blockchain.connect(block);
+        }
+        hashes.reverse();
+
+        let locator = blockchain.build_locator();
+        assert_eq!(locator.len(), 11);
+        assert_eq!(locator[..10], hashes[..10]);
+        assert_eq!(locator[10], hashes[11]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_transactions_are_indexed_by_id() -> Result<(), MessageError> {
+        let mut blockchain = BlockChain::new(Network::Testnet);
+        let mut first = block_after(blockchain.get_last_block_header(), 0)?;
+        first.coinbase.lock_time = 1;
+        let first_hash = first.get_header().get_header();
+        let first_coinbase = first.get_coinbase().get_id();
+        let mut tip = block_after(first_hash.clone(), 1)?;
+        tip.coinbase.lock_time = 2;
+        let tip_coinbase = tip.get_coinbase().get_id();
+
+        blockchain.connect(first);
+        assert_eq!(blockchain.get_tx_location(&first_coinbase), None);
+
+        blockchain.enable_tx_index();
+        assert!(blockchain.has_tx_index());
+        assert_eq!(
+            blockchain.get_tx_location(&first_coinbase),
+            Some((&first_hash, 0))
+        );
+
+        blockchain.connect(tip);
+        assert_eq!(
+            blockchain
+                .get_transaction_block(&tip_coinbase)
+                .map(|header| header.get_header()),
+            Some(blockchain.get_last_block_header())
+        );
+        assert_eq!(blockchain.get_transaction_block(&vec![7; 32]), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_tips_of_every_branch_are_listed() -> Result<(), MessageError> {
+        let mut blockchain = BlockChain::new(Network::Testnet);
+        blockchain.set_main_chain(&[Network::Testnet.get_genesis_header()], 0);
+        let first = block_after(blockchain.get_last_block_header(), 0)?;
+        let first_hash = first.get_header().get_header();
+        let tip = block_after(first_hash.clone(), 1)?;
+        let tip_hash = tip.get_header().get_header();
+        let competing = block_after(first_hash.clone(), 2)?;
+        let competing_hash = competing.get_header().get_header();
+        let orphan = block_after(vec![7; 32], 3)?;
+        let orphan_hash = orphan.get_header().get_header();
+
+        blockchain.connect(first);
+        blockchain.connect(tip);
+        blockchain.connect(competing);
+        blockchain.connect(orphan);
+
+        let tips = blockchain.get_chain_tips();
+        assert_eq!(tips.len(), 3);
+        assert_eq!(tips[0].get_hash(), &tip_hash);
+        assert_eq!(tips[0].get_status(), ChainTipStatus::Active);
+        assert_eq!(tips[0].get_height(), Some(2));
+        assert_eq!(
+            tips[1],
+            ChainTip::new(
+                competing_hash,
+                Some(2),
+                1,
+                tips[0].get_chain_work(),
+                ChainTipStatus::ValidFork
+            )
+        );
+        assert_eq!(tips[2].get_hash(), &orphan_hash);
+        assert_eq!(tips[2].get_status(), ChainTipStatus::Stale);
+        Ok(())
     }
 }
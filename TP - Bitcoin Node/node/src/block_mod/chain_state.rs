@@ -0,0 +1,113 @@
+use super::{
+    blockchain::BlockChain, header_chain::HeaderChain, invalid_blocks::InvalidBlocks,
+    mempool::Mempool, utxo::UnspentTx,
+};
+use crate::{
+    network::{event_bus::EventBus, network_time::NetworkTime},
+    wallet_utils::event_log::{EventLog, NodeEvent},
+};
+use std::sync::{Arc, Mutex};
+
+/// Groups the shared structures holding the state of the chain, so they can be handed to the
+/// threads of the node as a whole.
+#[derive(Clone)]
+pub struct ChainState {
+    blockchain: Arc<Mutex<BlockChain>>,
+    utxo: Arc<Mutex<UnspentTx>>,
+    mempool: Arc<Mutex<Mempool>>,
+    header_chain: Arc<Mutex<HeaderChain>>,
+    network_time: Arc<NetworkTime>,
+    invalid_blocks: Arc<Mutex<InvalidBlocks>>,
+    wallet_events: Arc<Mutex<EventLog>>,
+    event_bus: EventBus,
+}
+
+impl ChainState {
+    /// Creates a new chain state from its shared structures.
+    ///
+    /// # Arguments
+    ///
+    /// * `blockchain` - The shared blockchain.
+    /// * `utxo` - The shared unspent transaction set.
+    /// * `mempool` - The shared mempool.
+    /// * `header_chain` - The shared header chain.
+    /// * `network_time` - The network-adjusted time, used to validate the timestamps of new blocks.
+    /// * `invalid_blocks` - The shared table of the blocks found invalid.
+    /// * `wallet_events` - The shared log of the events pushed to the wallet.
+    pub fn new(
+        blockchain: Arc<Mutex<BlockChain>>,
+        utxo: Arc<Mutex<UnspentTx>>,
+        mempool: Arc<Mutex<Mempool>>,
+        header_chain: Arc<Mutex<HeaderChain>>,
+        network_time: Arc<NetworkTime>,
+        invalid_blocks: Arc<Mutex<InvalidBlocks>>,
+        wallet_events: Arc<Mutex<EventLog>>,
+    ) -> ChainState {
+        ChainState {
+            blockchain,
+            utxo,
+            mempool,
+            header_chain,
+            network_time,
+            invalid_blocks,
+            wallet_events,
+            event_bus: EventBus::new(),
+        }
+    }
+
+    pub fn get_blockchain(&self) -> &Arc<Mutex<BlockChain>> {
+        &self.blockchain
+    }
+
+    pub fn get_utxo(&self) -> &Arc<Mutex<UnspentTx>> {
+        &self.utxo
+    }
+
+    pub fn get_mempool(&self) -> &Arc<Mutex<Mempool>> {
+        &self.mempool
+    }
+
+    pub fn get_header_chain(&self) -> &Arc<Mutex<HeaderChain>> {
+        &self.header_chain
+    }
+
+    pub fn get_network_time(&self) -> &Arc<NetworkTime> {
+        &self.network_time
+    }
+
+    pub fn get_invalid_blocks(&self) -> &Arc<Mutex<InvalidBlocks>> {
+        &self.invalid_blocks
+    }
+
+    pub fn get_wallet_events(&self) -> &Arc<Mutex<EventLog>> {
+        &self.wallet_events
+    }
+
+    /// Replaces the bus the events of the chain and the mempool are published to, such as the one
+    /// shared by the whole node.
+    pub fn set_event_bus(&mut self, event_bus: EventBus) {
+        self.event_bus = event_bus;
+    }
+
+    pub fn get_event_bus(&self) -> &EventBus {
+        &self.event_bus
+    }
+
+    /// Records an event in the log of the wallet events and publishes it to the subscribers of
+    /// the node.
+    ///
+    /// # Returns
+    ///
+    /// `false` if the log of the wallet events could not be locked.
+    pub fn publish(&self, event: NodeEvent) -> bool {
+        match self.wallet_events.lock() {
+            Ok(mut wallet_events) => {
+                wallet_events.push(event.clone());
+            }
+            Err(_) => return false,
+        }
+
+        self.event_bus.publish(event);
+        true
+    }
+}
@@ -0,0 +1,75 @@
+/// Represents the status of a tip of the blockchain, as reported by `getchaintips`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChainTipStatus {
+    /// The tip of the main chain.
+    Active,
+    /// The tip of a downloaded branch the main chain could be reorganized onto, if it gathered
+    /// more work.
+    ValidFork,
+    /// The tip of a branch that forks from the main chain deeper than `MAX_REORG_DEPTH` blocks, or
+    /// whose blocks are not all downloaded, so the main chain cannot be reorganized onto it.
+    Stale,
+}
+
+impl ChainTipStatus {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            ChainTipStatus::Active => "active",
+            ChainTipStatus::ValidFork => "valid-fork",
+            ChainTipStatus::Stale => "stale",
+        }
+    }
+}
+
+/// Represents a tip of the blockchain: a downloaded block no other downloaded block builds on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainTip {
+    hash: Vec<u8>,
+    height: Option<u32>,
+    branch_len: usize,
+    chain_work: u128,
+    status: ChainTipStatus,
+}
+
+impl ChainTip {
+    pub fn new(
+        hash: Vec<u8>,
+        height: Option<u32>,
+        branch_len: usize,
+        chain_work: u128,
+        status: ChainTipStatus,
+    ) -> ChainTip {
+        ChainTip {
+            hash,
+            height,
+            branch_len,
+            chain_work,
+            status,
+        }
+    }
+
+    pub fn get_hash(&self) -> &Vec<u8> {
+        &self.hash
+    }
+
+    /// Returns the height of the tip, if the height of the point its branch forks from the main
+    /// chain is known.
+    pub fn get_height(&self) -> Option<u32> {
+        self.height
+    }
+
+    /// Returns the amount of blocks of the branch of the tip that are not in the main chain. It
+    /// is 0 for the tip of the main chain.
+    pub fn get_branch_len(&self) -> usize {
+        self.branch_len
+    }
+
+    /// Returns the cumulative work of the tip since the first downloaded block.
+    pub fn get_chain_work(&self) -> u128 {
+        self.chain_work
+    }
+
+    pub fn get_status(&self) -> ChainTipStatus {
+        self.status
+    }
+}
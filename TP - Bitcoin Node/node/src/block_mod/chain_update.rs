@@ -0,0 +1,16 @@
+/// Describes how the main chain changed when a block was connected to the blockchain.
+#[derive(Debug, PartialEq)]
+pub enum ChainUpdate {
+    /// The block extended the tip of the main chain.
+    Extended,
+    /// The block was stored in a branch with less work than the main chain, or its parent is
+    /// unknown, so the main chain did not change.
+    SideBranch,
+    /// The block made a competing branch the one with most work.
+    Reorganized {
+        /// The hashes of the blocks disconnected from the old tip, the old tip first.
+        disconnected: Vec<Vec<u8>>,
+        /// The hashes of the blocks connected after the fork point, the new tip last.
+        connected: Vec<Vec<u8>>,
+    },
+}
@@ -1,11 +1,15 @@
+use crate::block_mod::block_constants::{WITNESS_COMMITMENT_HEADER, WITNESS_RESERVED_VALUE_SIZE};
+use crate::block_mod::script_interpreter::{decode_num, encode_num};
 use crate::block_mod::tx_in_coinbase::TxInCoinbase;
 use crate::block_mod::tx_out::TxOut;
 use crate::block_mod::witness::Witness;
 use crate::messages::compact_size::CompactSizeUInt;
+use crate::messages::message_constants::{MAX_TX_IN_COUNT, MAX_TX_OUT_COUNT};
 use crate::messages::message_error::MessageError;
 use crate::messages::read_from_bytes::{
     read_i32_from_bytes, read_u32_from_bytes, read_u8_from_bytes,
 };
+use crate::messages::serialization::{items_to_bytes_into, read_items, read_vec};
 use bitcoin_hashes::sha256d;
 use bitcoin_hashes::Hash;
 use std::io::Read;
@@ -33,38 +37,24 @@ impl Coinbase {
     /// A Result containing the constructed `Coinbase` if successful, otherwise a `MessageError`.
     pub fn from_bytes(stream: &mut dyn Read) -> Result<Coinbase, MessageError> {
         let version = read_i32_from_bytes(stream, true)?;
-        let mut tx_in_count = CompactSizeUInt::from_bytes(stream)?;
+        let mut tx_in_count = CompactSizeUInt::from_bytes_bounded(stream, MAX_TX_IN_COUNT)?;
 
         let is_segwit = tx_in_count.value() == 0;
         let mut flag = 0;
 
         if is_segwit {
             flag = read_u8_from_bytes(stream)?;
-            tx_in_count = CompactSizeUInt::from_bytes(stream)?;
+            tx_in_count = CompactSizeUInt::from_bytes_bounded(stream, MAX_TX_IN_COUNT)?;
         }
 
-        let mut tx_in_list: Vec<TxInCoinbase> = Vec::new();
+        let tx_in_list = read_items::<TxInCoinbase>(stream, &tx_in_count)?;
+        let (tx_out_count, tx_out_list) = read_vec::<TxOut>(stream, MAX_TX_OUT_COUNT)?;
 
-        for _i in 0..tx_in_count.value() {
-            tx_in_list.push(TxInCoinbase::from_bytes(stream)?);
-        }
-
-        let tx_out_count = CompactSizeUInt::from_bytes(stream)?;
-        let mut tx_out_list: Vec<TxOut> = Vec::new();
-
-        for _i in 0..tx_out_count.value() {
-            tx_out_list.push(TxOut::from_bytes(stream)?);
-        }
-
-        let mut witness = vec![];
-
-        if is_segwit {
-            for _ in 0..tx_in_count.value() {
-                witness.push(Witness::from_bytes(stream)?);
-            }
+        let witness = if is_segwit {
+            read_items::<Witness>(stream, &tx_in_count)?
         } else {
-            witness = vec![];
-        }
+            vec![]
+        };
 
         let lock_time = read_u32_from_bytes(stream, true)?;
         let coinbase = Coinbase {
@@ -88,25 +78,106 @@ impl Coinbase {
     /// # Returns
     /// A vector of bytes representing the `Coinbase`.
     pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes_with_witness(false)
+    }
+
+    /// Converts the `Coinbase` instance to bytes, with or without its witness, which holds the
+    /// reserved value of the witness commitment.
+    ///
+    /// # Arguments
+    /// * `witness` - Whether the witness is serialized, if the coinbase has one.
+    ///
+    /// # Returns
+    /// A vector of bytes representing the `Coinbase`.
+    pub fn to_bytes_with_witness(&self, witness: bool) -> Vec<u8> {
         let mut buff = Vec::new();
 
-        buff.extend(self.version.to_le_bytes());
-        buff.extend(self.tx_in_count.to_bytes());
+        self.to_bytes_with_witness_into(witness, &mut buff);
+        buff
+    }
 
-        for txin in self.tx_in_list.iter() {
-            //for each?
-            buff.extend(&txin.to_bytes());
-        }
+    /// Appends the byte representation of the `Coinbase` to the given buffer, without its witness.
+    pub fn to_bytes_into(&self, buff: &mut Vec<u8>) {
+        self.to_bytes_with_witness_into(false, buff);
+    }
 
-        buff.extend(self.tx_out_count.to_bytes());
+    /// Appends the byte representation of the `Coinbase` to the given buffer, with or without its
+    /// witness.
+    ///
+    /// # Arguments
+    /// * `witness` - Whether the witness is serialized, if the coinbase has one.
+    /// * `buff` - The buffer the bytes are appended to.
+    pub fn to_bytes_with_witness_into(&self, witness: bool, buff: &mut Vec<u8>) {
+        let segwit = witness && self.flag != 0;
 
-        for txout in self.tx_out_list.iter() {
-            buff.extend(&txout.to_bytes());
+        buff.extend(self.version.to_le_bytes());
+        if segwit {
+            buff.push(0x00);
+            buff.push(self.flag);
         }
+        self.tx_in_count.to_bytes_into(buff);
+        items_to_bytes_into(&self.tx_in_list, buff);
+        self.tx_out_count.to_bytes_into(buff);
+        items_to_bytes_into(&self.tx_out_list, buff);
 
+        if segwit {
+            items_to_bytes_into(&self.witness, buff);
+        }
         buff.extend(self.lock_time.to_le_bytes());
+    }
 
-        buff
+    /// Returns the witness commitment of the coinbase (BIP141): the hash pushed by the last of its
+    /// outputs whose script starts with the commitment header, if any.
+    pub fn get_witness_commitment(&self) -> Option<Vec<u8>> {
+        let commitment_size = WITNESS_COMMITMENT_HEADER.len() + 32;
+
+        self.tx_out_list.iter().rev().find_map(|tx_out| {
+            let pk_script = &tx_out.pk_script;
+            (pk_script.len() >= commitment_size
+                && pk_script.starts_with(&WITNESS_COMMITMENT_HEADER))
+            .then(|| pk_script[WITNESS_COMMITMENT_HEADER.len()..commitment_size].to_vec())
+        })
+    }
+
+    /// Returns the reserved value the witness commitment is computed with: the only item of the
+    /// witness of the coinbase, if it has a single item of `WITNESS_RESERVED_VALUE_SIZE` bytes.
+    pub fn get_witness_reserved_value(&self) -> Option<&Vec<u8>> {
+        match self.witness.as_slice() {
+            [witness] => match witness.stack_items.as_slice() {
+                [reserved_value] if reserved_value.len() == WITNESS_RESERVED_VALUE_SIZE => {
+                    Some(reserved_value)
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns the height of the block the coinbase belongs to, as encoded at the start of its
+    /// script (BIP34): either as `OP_0` or `OP_1` to `OP_16`, or as a minimal push of the height.
+    ///
+    /// # Returns
+    ///
+    /// The height, or `None` if the script does not start with a valid height, as is the case of
+    /// the coinbases of blocks older than BIP34.
+    pub fn height(&self) -> Option<u32> {
+        let script_sig = self.tx_in_list.first()?.script_sig();
+        let opcode = *script_sig.first()?;
+
+        match opcode {
+            0x00 => Some(0),
+            0x51..=0x60 => Some((opcode - 0x50) as u32),
+            0x01..=0x4b => {
+                let number = script_sig.get(1..1 + opcode as usize)?;
+                let height = decode_num(number).ok()?;
+
+                if encode_num(height) != number {
+                    return None;
+                }
+                u32::try_from(height).ok()
+            }
+            _ => None,
+        }
     }
 
     /// Computes the ID of the Coinbase transaction by hashing its serialized bytes.
@@ -0,0 +1,205 @@
+use super::{
+    block_constants::{
+        DIFFICULTY_ADJUSTMENT_INTERVAL, TARGET_SIZE, TARGET_SPACING, TARGET_TIMESPAN,
+    },
+    block_header::BlockHeader,
+};
+use crate::network::bitcoin_network::Network;
+use std::cmp::Ordering;
+
+/// Returns the nbits the header at the given height must have.
+///
+/// The target is retargeted every `DIFFICULTY_ADJUSTMENT_INTERVAL` blocks, scaling the target of
+/// the previous header by the time the last interval took, bounded to a factor of four either way.
+/// On testnet, a header more than twice the target spacing after its parent may be mined at the
+/// minimum difficulty, and the headers after it go back to the last difficulty that was not the
+/// minimum. Regtest never retargets.
+///
+/// # Arguments
+///
+/// * `network` - The network the header belongs to.
+/// * `height` - The height of the header, which must be above 0.
+/// * `time` - The timestamp of the header.
+/// * `get_header` - Returns the header of the chain at the given height, if it is known.
+///
+/// # Returns
+///
+/// The expected nbits, or `None` if the headers needed to compute them are not known.
+pub fn next_work_required<'a, F>(
+    network: Network,
+    height: u32,
+    time: u32,
+    get_header: F,
+) -> Option<u32>
+where
+    F: Fn(u32) -> Option<&'a BlockHeader>,
+{
+    let prev = get_header(height.checked_sub(1)?)?;
+    let pow_limit = network.get_pow_limit();
+
+    if network == Network::Regtest {
+        return Some(prev.get_nbits());
+    }
+
+    if !height.is_multiple_of(DIFFICULTY_ADJUSTMENT_INTERVAL) {
+        if network != Network::Testnet {
+            return Some(prev.get_nbits());
+        }
+        if time > prev.get_time() + TARGET_SPACING * 2 {
+            return Some(pow_limit);
+        }
+
+        let mut last_height = height - 1;
+        let mut last = prev;
+        while !last_height.is_multiple_of(DIFFICULTY_ADJUSTMENT_INTERVAL)
+            && last.get_nbits() == pow_limit
+        {
+            last_height -= 1;
+            last = get_header(last_height)?;
+        }
+        return Some(last.get_nbits());
+    }
+
+    let first = get_header(height - DIFFICULTY_ADJUSTMENT_INTERVAL)?;
+    let timespan = prev
+        .get_time()
+        .saturating_sub(first.get_time())
+        .clamp(TARGET_TIMESPAN / 4, TARGET_TIMESPAN * 4);
+
+    let mut target = prev.target();
+    mul_small(&mut target, timespan);
+    div_small(&mut target, TARGET_TIMESPAN);
+
+    let limit = decode_compact(pow_limit);
+    if compare(&target, &limit) == Ordering::Greater {
+        target = limit;
+    }
+    Some(BlockHeader::compact_from_target(&target))
+}
+
+/// Expands nbits into the target they encode, as little-endian bytes.
+pub fn decode_compact(nbits: u32) -> Vec<u8> {
+    let exponent = (nbits >> 24) as usize;
+    let mut mantissa = nbits & 0x007fffff;
+    let mut target = vec![0u8; TARGET_SIZE];
+
+    if exponent <= 3 {
+        mantissa >>= 8 * (3 - exponent);
+        target[..4].copy_from_slice(&mantissa.to_le_bytes());
+        return target;
+    }
+
+    for (index, byte) in mantissa.to_le_bytes()[..3].iter().enumerate() {
+        if let Some(position) = target.get_mut(exponent - 3 + index) {
+            *position = *byte;
+        }
+    }
+    target
+}
+
+/// Compresses a target, as little-endian bytes, into nbits.
+pub fn encode_compact(target: &[u8]) -> u32 {
+    let mut size = match target.iter().rposition(|byte| *byte != 0) {
+        Some(position) => position + 1,
+        None => return 0,
+    };
+
+    let mut mantissa = if size <= 3 {
+        let mut bytes = [0u8; 4];
+        bytes[..size].copy_from_slice(&target[..size]);
+        u32::from_le_bytes(bytes) << (8 * (3 - size))
+    } else {
+        u32::from_le_bytes([target[size - 3], target[size - 2], target[size - 1], 0])
+    };
+
+    if mantissa & 0x00800000 != 0 {
+        mantissa >>= 8;
+        size += 1;
+    }
+    ((size as u32) << 24) | mantissa
+}
+
+fn mul_small(target: &mut [u8], factor: u32) {
+    let mut carry = 0u64;
+
+    for byte in target.iter_mut() {
+        let value = *byte as u64 * factor as u64 + carry;
+        *byte = value as u8;
+        carry = value >> 8;
+    }
+}
+
+fn div_small(target: &mut [u8], divisor: u32) {
+    let mut remainder = 0u64;
+
+    for byte in target.iter_mut().rev() {
+        let value = (remainder << 8) | *byte as u64;
+        *byte = (value / divisor as u64) as u8;
+        remainder = value % divisor as u64;
+    }
+}
+
+fn compare(first: &[u8], second: &[u8]) -> Ordering {
+    first.iter().rev().cmp(second.iter().rev())
+}
+
+#[cfg(test)]
+mod difficulty_test {
+    use super::*;
+
+    fn header(time: u32, nbits: u32) -> BlockHeader {
+        BlockHeader::new(1, vec![0; 32], vec![0; 32], time, nbits, 0)
+    }
+
+    #[test]
+    fn test_compact_round_trip() {
+        for nbits in [0x1d00ffff, 0x1b0404cb, 0x207fffff, 0x1e0377ae, 0x03123456] {
+            assert_eq!(encode_compact(&decode_compact(nbits)), nbits);
+        }
+    }
+
+    #[test]
+    fn test_mainnet_retarget_after_interval() {
+        // Retarget at height 32256, from the headers at heights 30240 and 32255
+        let first = header(1261130161, 0x1d00ffff);
+        let prev = header(1262152739, 0x1d00ffff);
+        let get_header = |height| match height {
+            30240 => Some(&first),
+            32255 => Some(&prev),
+            _ => None,
+        };
+
+        assert_eq!(
+            next_work_required(Network::Mainnet, 32256, 1262153464, get_header),
+            Some(0x1d00d86a)
+        );
+        assert_eq!(
+            next_work_required(Network::Mainnet, 32200, 1262153464, |_| Some(&prev)),
+            Some(0x1d00ffff)
+        );
+    }
+
+    #[test]
+    fn test_testnet_min_difficulty_blocks() {
+        let retargeted = header(1000, 0x1c00ffff);
+        let min_difficulty = header(1300, 0x1d00ffff);
+        let get_header = |height| match height {
+            4032 => Some(&retargeted),
+            4033 => Some(&min_difficulty),
+            _ => None,
+        };
+
+        assert_eq!(
+            next_work_required(Network::Testnet, 4034, 1300 + 1201, get_header),
+            Some(0x1d00ffff)
+        );
+        assert_eq!(
+            next_work_required(Network::Testnet, 4034, 1300 + 600, get_header),
+            Some(0x1c00ffff)
+        );
+        assert_eq!(
+            next_work_required(Network::Regtest, 4034, 1300 + 600, get_header),
+            Some(0x1d00ffff)
+        );
+    }
+}
@@ -0,0 +1,435 @@
+use super::{
+    block_constants::MEDIAN_TIME_SPAN, block_header::BlockHeader, difficulty::next_work_required,
+};
+use crate::network::bitcoin_network::Network;
+use std::collections::HashMap;
+use std::sync::mpsc::Sender;
+
+/// Represents a header known by the node, either in the active chain or in a competing branch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeaderNode {
+    parent: Vec<u8>,
+    height: u32,
+    chain_work: u128,
+}
+
+impl HeaderNode {
+    /// Returns the hash of the parent of the header.
+    pub fn get_parent(&self) -> &Vec<u8> {
+        &self.parent
+    }
+
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the work of the header added to the work of its ancestors, since the first header
+    /// of the chain.
+    pub fn get_chain_work(&self) -> u128 {
+        self.chain_work
+    }
+}
+
+/// Represents the chain of block headers known by the node, indexed by height.
+///
+/// Besides the active chain, every known header is kept in a tree keyed by its hash, with the
+/// link to its parent and its cumulative work, so competing branches can coexist until one of
+/// them gathers the most work. The headers off the active chain are kept apart from it, and move
+/// into it when the chain is rewound to their fork point and they are appended again.
+///
+/// Components interested in new headers can subscribe to the chain, and every header appended to
+/// its tip is sent to them along with its height.
+#[derive(Debug)]
+pub struct HeaderChain {
+    headers: Vec<BlockHeader>,
+    start_height: u32,
+    nodes: HashMap<Vec<u8>, HeaderNode>,
+    branches: HashMap<Vec<u8>, BlockHeader>,
+    best_tip: Vec<u8>,
+    subscribers: Vec<Sender<(u32, BlockHeader)>>,
+}
+
+impl HeaderChain {
+    /// Creates a new header chain from the downloaded headers.
+    ///
+    /// # Arguments
+    ///
+    /// * `headers` - The downloaded headers.
+    /// * `start_height` - The height of the first downloaded header, which is 0 unless the sync
+    ///   started from a checkpoint.
+    pub fn new(headers: Vec<BlockHeader>, start_height: u32) -> HeaderChain {
+        let mut header_chain = HeaderChain {
+            headers: Vec::with_capacity(headers.len()),
+            start_height,
+            nodes: HashMap::with_capacity(headers.len()),
+            branches: HashMap::new(),
+            best_tip: Vec::new(),
+            subscribers: Vec::new(),
+        };
+
+        for header in headers {
+            header_chain.index(&header);
+            header_chain.headers.push(header);
+        }
+        header_chain
+    }
+
+    /// Appends a header to the tip of the chain and sends it to every subscriber.
+    ///
+    /// Headers that do not extend the tip but whose parent is known are kept as a competing
+    /// branch. Subscribers that are no longer listening are dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - The new block header.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the header extends the tip of the chain, `false` if it was ignored.
+    pub fn add(&mut self, header: BlockHeader) -> bool {
+        if let Some(tip) = self.headers.last() {
+            if tip.get_header() != *header.get_previuos_block_header() {
+                self.insert(header);
+                return false;
+            }
+        }
+
+        let hash = header.get_header();
+        if self.branches.remove(&hash).is_none() {
+            self.index(&header);
+        }
+
+        self.headers.push(header.clone());
+        let height = self.get_height();
+
+        self.subscribers
+            .retain(|subscriber| subscriber.send((height, header.clone())).is_ok());
+
+        true
+    }
+
+    /// Adds a header to the tree of known headers without appending it to the active chain.
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - The block header.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the header was added, `false` if it was already known or its parent is not.
+    pub fn insert(&mut self, header: BlockHeader) -> bool {
+        let hash = header.get_header();
+
+        if self.nodes.contains_key(&hash)
+            || !self.nodes.contains_key(header.get_previuos_block_header())
+        {
+            return false;
+        }
+
+        self.index(&header);
+        self.branches.insert(hash, header);
+        true
+    }
+
+    /// Removes the headers after the header with the given hash, so a competing branch can be
+    /// appended from it. The removed headers are kept as a competing branch.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash` - The hash of the header that becomes the tip of the chain.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the chain was rewound, `false` if the header does not belong to the chain.
+    pub fn rewind(&mut self, hash: &[u8]) -> bool {
+        let height = match self.get_height_of(hash) {
+            Some(height) => height,
+            None => return false,
+        };
+
+        let position = (height - self.start_height) as usize;
+        for header in self.headers.drain(position + 1..) {
+            self.branches.insert(header.get_header(), header);
+        }
+        true
+    }
+
+    /// Subscribes to the headers appended to the chain from now on.
+    ///
+    /// # Arguments
+    ///
+    /// * `subscriber` - The sender through which new headers and their heights are delivered.
+    pub fn subscribe(&mut self, subscriber: Sender<(u32, BlockHeader)>) {
+        self.subscribers.push(subscriber);
+    }
+
+    /// Returns the headers whose height is greater than or equal to the given one.
+    ///
+    /// Heights below the start height of the chain are not known, so the headers are returned from
+    /// the start height onwards.
+    pub fn get_headers_since(&self, height: u32) -> Vec<BlockHeader> {
+        self.headers
+            .iter()
+            .skip(height.saturating_sub(self.start_height) as usize)
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the height of the tip of the chain, the genesis block being at height 0.
+    pub fn get_height(&self) -> u32 {
+        (self.start_height + self.headers.len() as u32).saturating_sub(1)
+    }
+
+    /// Returns the height of the header with the given hash, if it belongs to the chain.
+    pub fn get_height_of(&self, hash: &[u8]) -> Option<u32> {
+        let height = self.nodes.get(hash)?.get_height();
+        let header = self
+            .headers
+            .get(height.checked_sub(self.start_height)? as usize)?;
+
+        (header.get_header() == hash).then_some(height)
+    }
+
+    /// Returns the node of the header with the given hash, whether it belongs to the chain or to a
+    /// competing branch.
+    pub fn get_node(&self, hash: &[u8]) -> Option<&HeaderNode> {
+        self.nodes.get(hash)
+    }
+
+    /// Returns the header with the given hash, whether it belongs to the chain or to a competing
+    /// branch.
+    pub fn get_header(&self, hash: &[u8]) -> Option<&BlockHeader> {
+        if let Some(header) = self.branches.get(hash) {
+            return Some(header);
+        }
+
+        let height = self.get_height_of(hash)?;
+        self.headers.get((height - self.start_height) as usize)
+    }
+
+    /// Returns the hash of the known header with most cumulative work, which is the tip of the
+    /// chain unless a competing branch gathered more work.
+    pub fn get_best_tip(&self) -> &Vec<u8> {
+        &self.best_tip
+    }
+
+    /// Returns the headers of the branch ending at the header with the given hash, from the first
+    /// header after its fork point with the chain, in ascending order of height.
+    ///
+    /// The branch is empty if the header belongs to the chain or is not known.
+    pub fn get_branch(&self, hash: &[u8]) -> Vec<BlockHeader> {
+        let mut branch = Vec::new();
+        let mut current = hash.to_vec();
+
+        while let Some(header) = self.branches.get(&current) {
+            current = header.get_previuos_block_header().clone();
+            branch.push(header.clone());
+        }
+
+        branch.reverse();
+        branch
+    }
+
+    /// Returns the median time past of the header of the chain at the given height: the median of
+    /// the timestamps of the last `MEDIAN_TIME_SPAN` headers up to it, or of as many as are known.
+    pub fn get_median_time_past(&self, height: u32) -> Option<u32> {
+        let end = height.checked_sub(self.start_height)? as usize + 1;
+        if end > self.headers.len() {
+            return None;
+        }
+
+        let mut times: Vec<u32> = self.headers[end.saturating_sub(MEDIAN_TIME_SPAN)..end]
+            .iter()
+            .map(|header| header.get_time())
+            .collect();
+        times.sort_unstable();
+        Some(times[times.len() / 2])
+    }
+
+    /// Returns the ancestor at the given height of the header with the given hash, whether the
+    /// header belongs to the chain or to a competing branch.
+    pub fn get_ancestor(&self, hash: &[u8], height: u32) -> Option<&BlockHeader> {
+        let mut current = hash;
+
+        loop {
+            let node = self.nodes.get(current)?;
+            if node.get_height() < height {
+                return None;
+            }
+            if self.get_height_of(current).is_some() {
+                return self
+                    .headers
+                    .get(height.checked_sub(self.start_height)? as usize);
+            }
+            if node.get_height() == height {
+                return self.branches.get(current);
+            }
+            current = node.get_parent();
+        }
+    }
+
+    /// Returns whether a header has the difficulty the retarget rules of the network require of a
+    /// header extending its parent.
+    ///
+    /// The difficulty can only be checked if the parent is known, along with the headers the
+    /// retarget depends on, so headers it cannot be checked for are considered valid.
+    pub fn has_required_nbits(&self, network: Network, header: &BlockHeader) -> bool {
+        let parent = header.get_previuos_block_header();
+        let height = match self.nodes.get(parent) {
+            Some(node) => node.get_height() + 1,
+            None => return true,
+        };
+
+        next_work_required(network, height, header.get_time(), |at| {
+            self.get_ancestor(parent, at)
+        })
+        .is_none_or(|nbits| nbits == header.get_nbits())
+    }
+
+    pub fn get_start_height(&self) -> u32 {
+        self.start_height
+    }
+
+    pub fn get_headers(&self) -> &Vec<BlockHeader> {
+        &self.headers
+    }
+
+    /// Records a header in the tree, computing its height and cumulative work from its parent.
+    /// A header whose parent is not known starts the tree, at the start height of the chain.
+    fn index(&mut self, header: &BlockHeader) {
+        let parent = header.get_previuos_block_header().clone();
+        let (height, parent_work) = match self.nodes.get(&parent) {
+            Some(node) => (node.get_height() + 1, node.get_chain_work()),
+            None => (self.start_height, 0),
+        };
+        let chain_work = parent_work.saturating_add(header.get_work());
+        let hash = header.get_header();
+
+        let is_best = match self.nodes.get(&self.best_tip) {
+            Some(best) => chain_work > best.get_chain_work(),
+            None => true,
+        };
+        if is_best {
+            self.best_tip = hash.clone();
+        }
+
+        self.nodes.insert(
+            hash,
+            HeaderNode {
+                parent,
+                height,
+                chain_work,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod header_chain_test {
+    use super::*;
+    use std::sync::mpsc;
+
+    fn header_after(previous: &BlockHeader, nonce: u32) -> BlockHeader {
+        BlockHeader::new(1, previous.get_header(), vec![0; 32], 0, 0, nonce)
+    }
+
+    #[test]
+    fn test_subscribers_receive_new_headers() {
+        let genesis = BlockHeader::new(1, vec![0; 32], vec![0; 32], 0, 0, 0);
+        let mut header_chain = HeaderChain::new(vec![genesis.clone()], 0);
+        let (tx, rx) = mpsc::channel();
+        header_chain.subscribe(tx);
+
+        let header = header_after(&genesis, 1);
+        assert!(header_chain.add(header.clone()));
+        assert!(!header_chain.add(header_after(&genesis, 2)));
+
+        assert_eq!(rx.try_recv().ok(), Some((1, header.clone())));
+        assert!(rx.try_recv().is_err());
+        assert_eq!(header_chain.get_headers_since(1), vec![header]);
+    }
+
+    #[test]
+    fn test_header_chain_starting_from_checkpoint() {
+        let first = BlockHeader::new(1, vec![1; 32], vec![0; 32], 0, 0, 0);
+        let second = header_after(&first, 1);
+        let header_chain = HeaderChain::new(vec![first, second.clone()], 547);
+
+        assert_eq!(header_chain.get_height(), 548);
+        assert_eq!(header_chain.get_headers_since(548), vec![second.clone()]);
+        assert_eq!(header_chain.get_headers_since(0).len(), 2);
+        assert_eq!(header_chain.get_height_of(&second.get_header()), Some(548));
+    }
+
+    #[test]
+    fn test_header_chain_rewind() {
+        let genesis = BlockHeader::new(1, vec![0; 32], vec![0; 32], 0, 0, 0);
+        let first = header_after(&genesis, 1);
+        let mut header_chain = HeaderChain::new(vec![genesis.clone(), first], 0);
+
+        assert!(!header_chain.rewind(&[1; 32]));
+        assert!(header_chain.rewind(&genesis.get_header()));
+        assert_eq!(header_chain.get_height(), 0);
+        assert!(header_chain.add(header_after(&genesis, 2)));
+    }
+
+    #[test]
+    fn test_competing_branches_coexist_until_one_has_most_work() {
+        let genesis = BlockHeader::new(1, vec![0; 32], vec![0; 32], 0, 0x1d00ffff, 0);
+        let first = BlockHeader::new(1, genesis.get_header(), vec![0; 32], 0, 0x1d00ffff, 1);
+        let mut header_chain = HeaderChain::new(vec![genesis.clone(), first.clone()], 0);
+
+        let side = BlockHeader::new(1, genesis.get_header(), vec![0; 32], 0, 0x1d00ffff, 2);
+        let side_tip = BlockHeader::new(1, side.get_header(), vec![0; 32], 0, 0x1d00ffff, 3);
+        assert!(header_chain.insert(side.clone()));
+        assert!(!header_chain.insert(side.clone()));
+        assert_eq!(*header_chain.get_best_tip(), first.get_header());
+
+        assert!(!header_chain.add(side_tip.clone()));
+        assert_eq!(*header_chain.get_best_tip(), side_tip.get_header());
+        assert_eq!(header_chain.get_height_of(&side_tip.get_header()), None);
+        assert_eq!(
+            header_chain
+                .get_node(&side_tip.get_header())
+                .map(HeaderNode::get_height),
+            Some(2)
+        );
+        assert_eq!(
+            header_chain.get_branch(&side_tip.get_header()),
+            vec![side.clone(), side_tip.clone()]
+        );
+
+        assert!(header_chain.rewind(&genesis.get_header()));
+        assert!(header_chain.add(side));
+        assert!(header_chain.add(side_tip.clone()));
+        assert_eq!(header_chain.get_height_of(&side_tip.get_header()), Some(2));
+        assert_eq!(
+            header_chain.get_branch(&first.get_header()),
+            vec![first.clone()]
+        );
+        assert_eq!(header_chain.get_header(&first.get_header()), Some(&first));
+    }
+
+    #[test]
+    fn test_headers_announced_after_sync_must_have_the_required_nbits() {
+        let genesis = Network::Mainnet.get_genesis_header();
+        let first = BlockHeader::new(1, genesis.get_header(), vec![0; 32], 1, 0x1d00ffff, 0);
+        let mut header_chain = HeaderChain::new(vec![genesis.clone(), first.clone()], 0);
+        let side = BlockHeader::new(1, genesis.get_header(), vec![0; 32], 2, 0x1d00ffff, 1);
+        assert!(header_chain.insert(side.clone()));
+
+        let valid = BlockHeader::new(1, first.get_header(), vec![0; 32], 2, 0x1d00ffff, 2);
+        let easier = BlockHeader::new(1, first.get_header(), vec![0; 32], 2, 0x207fffff, 3);
+        let side_easier = BlockHeader::new(1, side.get_header(), vec![0; 32], 3, 0x207fffff, 4);
+        let unknown = BlockHeader::new(1, vec![1; 32], vec![0; 32], 2, 0x207fffff, 5);
+
+        assert!(header_chain.has_required_nbits(Network::Mainnet, &valid));
+        assert!(!header_chain.has_required_nbits(Network::Mainnet, &easier));
+        assert!(!header_chain.has_required_nbits(Network::Mainnet, &side_easier));
+        assert!(header_chain.has_required_nbits(Network::Mainnet, &unknown));
+        assert_eq!(
+            header_chain.get_ancestor(&side.get_header(), 0),
+            Some(&genesis)
+        );
+    }
+}
@@ -0,0 +1,310 @@
+use crate::{
+    messages::read_from_bytes::{decode_hex, encode_hex},
+    network::network_error::NetworkError,
+};
+
+use std::{
+    collections::HashMap,
+    fs::{self, OpenOptions},
+    io::Write,
+};
+
+/// Represents the consensus rules a block can be rejected for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockRule {
+    /// The hash of the block does not satisfy its target.
+    HighHash,
+    /// The target of the block is not the one the retarget rules require.
+    BadDiffBits,
+    /// The merkle root of the header does not match the transactions of the block.
+    BadMerkleRoot,
+    /// The timestamp of the block is too far ahead of the network-adjusted time.
+    TimeTooNew,
+    /// The block holds the same transaction more than once.
+    DuplicateTx,
+    /// The block descends from a block that was found invalid.
+    BadPrevBlock,
+    /// The weight of the block exceeds `MAX_BLOCK_WEIGHT`.
+    BadBlockWeight,
+    /// The first transaction of the block is not a coinbase.
+    BadCoinbaseMissing,
+    /// A transaction other than the first one of the block is a coinbase.
+    BadCoinbaseMultiple,
+    /// A transaction of the block spends an output that does not exist or is already spent.
+    MissingOrSpentInputs,
+    /// The value of an output of a transaction of the block, or their total, is negative or exceeds
+    /// `MAX_MONEY`.
+    OutputValueOutOfRange,
+    /// The outputs spent by a transaction of the block are worth more than `MAX_MONEY`.
+    InputValuesOutOfRange,
+    /// The fees of the transactions of the block add up to more than `MAX_MONEY`.
+    AccumulatedFeeOutOfRange,
+    /// The outputs of a transaction of the block are worth more than its inputs.
+    NegativeFee,
+    /// The coinbase of the block claims more than the subsidy plus the fees of the block.
+    BadCoinbaseAmount,
+    /// The witness of the coinbase is not a single reserved value of 32 bytes.
+    BadWitnessNonceSize,
+    /// The witness commitment of the coinbase does not match the witness data of the block.
+    BadWitnessMerkleMatch,
+    /// The block holds witness data but its coinbase commits to none.
+    UnexpectedWitness,
+    /// The coinbase of the block does not start its script with the height of the block (BIP34).
+    BadCoinbaseHeight,
+}
+
+impl BlockRule {
+    /// Returns the name of the rule, as reported by Bitcoin Core.
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            BlockRule::HighHash => "high-hash",
+            BlockRule::BadDiffBits => "bad-diffbits",
+            BlockRule::BadMerkleRoot => "bad-txnmrklroot",
+            BlockRule::TimeTooNew => "time-too-new",
+            BlockRule::DuplicateTx => "bad-txns-duplicate",
+            BlockRule::BadPrevBlock => "bad-prevblk",
+            BlockRule::BadBlockWeight => "bad-blk-weight",
+            BlockRule::BadCoinbaseMissing => "bad-cb-missing",
+            BlockRule::BadCoinbaseMultiple => "bad-cb-multiple",
+            BlockRule::MissingOrSpentInputs => "bad-txns-inputs-missingorspent",
+            BlockRule::OutputValueOutOfRange => "bad-txns-vout-outofrange",
+            BlockRule::InputValuesOutOfRange => "bad-txns-inputvalues-outofrange",
+            BlockRule::AccumulatedFeeOutOfRange => "bad-txns-accumulated-fee-outofrange",
+            BlockRule::NegativeFee => "bad-txns-in-belowout",
+            BlockRule::BadCoinbaseAmount => "bad-cb-amount",
+            BlockRule::BadWitnessNonceSize => "bad-witness-nonce-size",
+            BlockRule::BadWitnessMerkleMatch => "bad-witness-merkle-match",
+            BlockRule::UnexpectedWitness => "unexpected-witness",
+            BlockRule::BadCoinbaseHeight => "bad-cb-height",
+        }
+    }
+
+    /// Returns the rule with the given name, if there is one.
+    pub fn from_name(name: &str) -> Option<BlockRule> {
+        [
+            BlockRule::HighHash,
+            BlockRule::BadDiffBits,
+            BlockRule::BadMerkleRoot,
+            BlockRule::TimeTooNew,
+            BlockRule::DuplicateTx,
+            BlockRule::BadPrevBlock,
+            BlockRule::BadBlockWeight,
+            BlockRule::BadCoinbaseMissing,
+            BlockRule::BadCoinbaseMultiple,
+            BlockRule::MissingOrSpentInputs,
+            BlockRule::OutputValueOutOfRange,
+            BlockRule::InputValuesOutOfRange,
+            BlockRule::AccumulatedFeeOutOfRange,
+            BlockRule::NegativeFee,
+            BlockRule::BadCoinbaseAmount,
+            BlockRule::BadWitnessNonceSize,
+            BlockRule::BadWitnessMerkleMatch,
+            BlockRule::UnexpectedWitness,
+            BlockRule::BadCoinbaseHeight,
+        ]
+        .into_iter()
+        .find(|rule| rule.get_name() == name)
+    }
+}
+
+/// Represents the reason a block was found invalid: the rule it violated and, if the violation
+/// was caused by one of its transactions, the id of that transaction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidBlock {
+    rule: BlockRule,
+    tx_id: Option<Vec<u8>>,
+}
+
+impl InvalidBlock {
+    pub fn new(rule: BlockRule, tx_id: Option<Vec<u8>>) -> InvalidBlock {
+        InvalidBlock { rule, tx_id }
+    }
+
+    pub fn get_rule(&self) -> BlockRule {
+        self.rule
+    }
+
+    pub fn get_tx_id(&self) -> Option<&Vec<u8>> {
+        self.tx_id.as_ref()
+    }
+}
+
+/// Returns the hexadecimal form of a hash, as displayed by block explorers.
+fn displayed_hex(hash: &[u8]) -> String {
+    let mut displayed = hash.to_vec();
+    displayed.reverse();
+    encode_hex(&displayed).unwrap_or_default()
+}
+
+/// Parses a hash displayed by block explorers.
+fn from_displayed_hex(hex: &str) -> Option<Vec<u8>> {
+    let mut hash = decode_hex(hex).ok()?;
+    hash.reverse();
+    (hash.len() == 32).then_some(hash)
+}
+
+/// Returns the line of the table file describing an invalid block.
+fn to_line(block_hash: &[u8], invalid_block: &InvalidBlock) -> String {
+    let tx_id = match invalid_block.get_tx_id() {
+        Some(tx_id) => displayed_hex(tx_id),
+        None => "-".to_string(),
+    };
+
+    format!(
+        "{} {} {}",
+        displayed_hex(block_hash),
+        invalid_block.get_rule().get_name(),
+        tx_id
+    )
+}
+
+/// Keeps the blocks that were found invalid, so they are rejected straight away if they are
+/// received again, until the operator explicitly asks to reconsider them.
+///
+/// The table is persisted to disk, one block per line holding its hash, the violated rule and the
+/// id of the offending transaction, or `-` if there is none.
+#[derive(Debug)]
+pub struct InvalidBlocks {
+    blocks: HashMap<Vec<u8>, InvalidBlock>,
+    file_path: String,
+}
+
+impl InvalidBlocks {
+    /// Creates the table of invalid blocks, loading the blocks persisted in the given file.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_path` - The path of the file where invalid blocks are persisted.
+    ///
+    /// # Returns
+    ///
+    /// The table of invalid blocks, or a `NetworkError` if the file exists but cannot be parsed.
+    pub fn new(file_path: &str) -> Result<InvalidBlocks, NetworkError> {
+        let mut blocks = HashMap::new();
+
+        if let Ok(content) = fs::read_to_string(file_path) {
+            for line in content.lines() {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+
+                let (hash, invalid_block) = match fields.as_slice() {
+                    [hash, rule, tx_id] => {
+                        let rule = BlockRule::from_name(rule).ok_or(NetworkError::InvalidBlock)?;
+                        let tx_id = match *tx_id {
+                            "-" => None,
+                            tx_id => {
+                                Some(from_displayed_hex(tx_id).ok_or(NetworkError::InvalidBlock)?)
+                            }
+                        };
+                        let hash = from_displayed_hex(hash).ok_or(NetworkError::InvalidBlock)?;
+                        (hash, InvalidBlock::new(rule, tx_id))
+                    }
+                    _ => return Err(NetworkError::InvalidBlock),
+                };
+                blocks.insert(hash, invalid_block);
+            }
+        }
+
+        Ok(InvalidBlocks {
+            blocks,
+            file_path: file_path.to_string(),
+        })
+    }
+
+    /// Records a block as invalid and persists it.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_hash` - The hash of the invalid block.
+    /// * `invalid_block` - The reason the block is invalid.
+    ///
+    /// # Returns
+    ///
+    /// A `NetworkError` if the block could not be persisted.
+    pub fn park(
+        &mut self,
+        block_hash: Vec<u8>,
+        invalid_block: InvalidBlock,
+    ) -> Result<(), NetworkError> {
+        let line = to_line(&block_hash, &invalid_block);
+        println!("Block has been marked as invalid: {}.", line);
+
+        if self.blocks.contains_key(&block_hash) {
+            return Ok(());
+        }
+        self.blocks.insert(block_hash, invalid_block);
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.file_path)
+            .map_err(|_| NetworkError::Broadcasting)?;
+        writeln!(file, "{}", line).map_err(|_| NetworkError::Broadcasting)
+    }
+
+    /// Removes a block from the table, so it is validated again the next time it is received.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_hash` - The hash of the block to reconsider.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the block was in the table, or a `NetworkError` if the table could not be
+    /// persisted.
+    pub fn reconsider(&mut self, block_hash: &Vec<u8>) -> Result<bool, NetworkError> {
+        if self.blocks.remove(block_hash).is_none() {
+            return Ok(false);
+        }
+
+        let content: String = self
+            .blocks
+            .iter()
+            .map(|(hash, invalid_block)| to_line(hash, invalid_block) + "\n")
+            .collect();
+
+        fs::write(&self.file_path, content).map_err(|_| NetworkError::Broadcasting)?;
+        Ok(true)
+    }
+
+    pub fn get(&self, block_hash: &Vec<u8>) -> Option<&InvalidBlock> {
+        self.blocks.get(block_hash)
+    }
+
+    pub fn contains(&self, block_hash: &Vec<u8>) -> bool {
+        self.blocks.contains_key(block_hash)
+    }
+
+    pub fn get_blocks(&self) -> &HashMap<Vec<u8>, InvalidBlock> {
+        &self.blocks
+    }
+}
+
+#[cfg(test)]
+mod invalid_blocks_test {
+    use super::*;
+
+    #[test]
+    fn test_invalid_blocks_are_persisted_until_reconsidered() -> Result<(), NetworkError> {
+        let path = "data/test_invalid_blocks.txt";
+        let _ = fs::remove_file(path);
+        let mut invalid_blocks = InvalidBlocks::new(path)?;
+
+        let invalid_block = InvalidBlock::new(BlockRule::DuplicateTx, Some(vec![2; 32]));
+        invalid_blocks.park(vec![1; 32], invalid_block.clone())?;
+        invalid_blocks.park(vec![3; 32], InvalidBlock::new(BlockRule::HighHash, None))?;
+
+        let mut reloaded = InvalidBlocks::new(path)?;
+        assert_eq!(reloaded.get(&vec![1; 32]), Some(&invalid_block));
+        assert!(reloaded.contains(&vec![3; 32]));
+
+        assert!(reloaded.reconsider(&vec![3; 32])?);
+        assert!(!reloaded.reconsider(&vec![3; 32])?);
+
+        let reloaded = InvalidBlocks::new(path)?;
+        let _ = fs::remove_file(path);
+
+        assert_eq!(reloaded.get_blocks().len(), 1);
+        assert!(!reloaded.contains(&vec![3; 32]));
+        Ok(())
+    }
+}
@@ -1,10 +1,55 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
 
-use super::{block::Block, transaction::Transaction};
+use super::{
+    block::Block,
+    mempool_snapshot::{MempoolDiff, MempoolSnapshot},
+    outpoint::Outpoint,
+    transaction::Transaction,
+    utxo::UnspentTx,
+};
 
-/// Represents a mempool of unconfirmed transactions.
+/// Represents the fee, virtual size and arrival time of a transaction of the mempool.
+#[derive(Debug, Clone, Copy)]
+pub struct MempoolEntry {
+    fee: Option<u64>,
+    vsize: usize,
+    entered: Instant,
+}
+
+impl MempoolEntry {
+    /// Returns the fee paid by the transaction, or `None` if it is not known.
+    pub fn get_fee(&self) -> Option<u64> {
+        self.fee
+    }
+
+    pub fn get_vsize(&self) -> usize {
+        self.vsize
+    }
+
+    /// Returns the fee rate of the transaction, in satoshis per kilobyte, taking an unknown fee as
+    /// no fee at all.
+    pub fn get_fee_rate(&self) -> u64 {
+        self.fee.unwrap_or(0) * 1000 / self.vsize.max(1) as u64
+    }
+
+    /// Returns how long ago the transaction entered the mempool.
+    pub fn get_age(&self) -> Duration {
+        self.entered.elapsed()
+    }
+}
+
+/// Represents a mempool of unconfirmed transactions, indexed by their txid and by their wtxid.
 pub struct Mempool {
     txs: HashMap<Vec<u8>, Transaction>,
+    entries: HashMap<Vec<u8>, MempoolEntry>,
+    wtxids: HashMap<Vec<u8>, Vec<u8>>,
+    /// The txid of the transaction spending each output spent within the mempool.
+    spenders: HashMap<(Vec<u8>, u32), Vec<u8>>,
+    /// The sum of the virtual sizes of the transactions in the mempool.
+    size: usize,
 }
 
 impl Mempool {
@@ -12,6 +57,10 @@ impl Mempool {
     pub fn new() -> Mempool {
         Mempool {
             txs: HashMap::new(),
+            entries: HashMap::new(),
+            wtxids: HashMap::new(),
+            spenders: HashMap::new(),
+            size: 0,
         }
     }
 
@@ -20,8 +69,110 @@ impl Mempool {
     /// # Arguments
     ///
     /// * `tx` - The transaction to be added to the mempool.
-    pub fn add(&mut self, tx: Transaction) {
-        self.txs.insert(tx.get_id(false), tx);
+    /// * `fee` - The fee paid by the transaction, if it is known.
+    pub fn add(&mut self, tx: Transaction, fee: Option<u64>) {
+        let tx_id = tx.get_id(false);
+        self.remove(&tx_id);
+
+        for tx_in in tx.get_tx_in_list() {
+            self.spenders
+                .insert(outpoint_key(tx_in.get_prev_output()), tx_id.clone());
+        }
+
+        let entry = MempoolEntry {
+            fee,
+            vsize: tx.vsize(),
+            entered: Instant::now(),
+        };
+        self.size += entry.vsize;
+        self.entries.insert(tx_id.clone(), entry);
+        self.wtxids.insert(tx.wtxid(), tx_id.clone());
+        self.txs.insert(tx_id, tx);
+    }
+
+    /// Removes the transactions that entered the mempool longer than `expiry` ago and then, while
+    /// the virtual sizes of the transactions add up to more than `max_size`, the one with the
+    /// lowest fee rate. The transactions spending the outputs of a removed transaction are removed
+    /// along with it.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_size` - The maximum sum of the virtual sizes of the transactions, in bytes.
+    /// * `expiry` - How long a transaction may stay in the mempool.
+    ///
+    /// # Returns
+    ///
+    /// The txids of the removed transactions.
+    pub fn trim(&mut self, max_size: usize, expiry: Duration) -> Vec<Vec<u8>> {
+        let expired: Vec<Vec<u8>> = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.get_age() >= expiry)
+            .map(|(tx_id, _)| tx_id.clone())
+            .collect();
+
+        let mut removed = vec![];
+        for tx_id in expired {
+            self.remove_with_descendants(&tx_id, &mut removed);
+        }
+
+        let mut by_fee_rate = self.get_by_fee_rate();
+        while self.size > max_size {
+            let Some(tx_id) = by_fee_rate.pop() else {
+                break;
+            };
+            self.remove_with_descendants(&tx_id, &mut removed);
+        }
+        removed
+    }
+
+    /// Returns the txids of the transactions in the mempool, from the highest fee rate to the
+    /// lowest.
+    pub fn get_by_fee_rate(&self) -> Vec<Vec<u8>> {
+        let mut tx_ids: Vec<(&Vec<u8>, u64)> = self
+            .entries
+            .iter()
+            .map(|(tx_id, entry)| (tx_id, entry.get_fee_rate()))
+            .collect();
+
+        tx_ids.sort_by(|first, second| second.1.cmp(&first.1).then(first.0.cmp(second.0)));
+        tx_ids.into_iter().map(|(tx_id, _)| tx_id.clone()).collect()
+    }
+
+    /// Removes a transaction from the mempool, leaving the ones spending its outputs.
+    ///
+    /// # Returns
+    ///
+    /// The removed transaction, or `None` if it was not in the mempool.
+    fn remove(&mut self, tx_id: &Vec<u8>) -> Option<Transaction> {
+        let tx = self.txs.remove(tx_id)?;
+
+        for tx_in in tx.get_tx_in_list() {
+            let key = outpoint_key(tx_in.get_prev_output());
+            if self.spenders.get(&key) == Some(tx_id) {
+                self.spenders.remove(&key);
+            }
+        }
+        if let Some(entry) = self.entries.remove(tx_id) {
+            self.size -= entry.vsize;
+        }
+        self.wtxids.remove(&tx.wtxid());
+        Some(tx)
+    }
+
+    /// Removes a transaction from the mempool along with the ones spending its outputs, adding
+    /// the txids of the removed transactions to `removed`.
+    fn remove_with_descendants(&mut self, tx_id: &Vec<u8>, removed: &mut Vec<Vec<u8>>) {
+        let Some(tx) = self.remove(tx_id) else {
+            return;
+        };
+
+        for index in 0..tx.get_tx_out_list().len() {
+            if let Some(spender) = self.spenders.get(&(tx_id.clone(), index as u32)).cloned() {
+                self.remove_with_descendants(&spender, removed);
+            }
+        }
+        removed.push(tx_id.clone());
     }
 
     /// Updates the mempool by removing transactions included in a given block.
@@ -31,10 +182,31 @@ impl Mempool {
     /// * `block` - A reference to the block containing transactions to be removed from the mempool.
     pub fn update(&mut self, block: &Block) {
         for tx in block.get_txn_list() {
-            self.txs.remove(&tx.get_id(false));
+            self.remove(&tx.get_id(false));
         }
     }
 
+    /// Returns the txid of the transaction of the mempool spending the given output, if any.
+    pub fn get_spender(&self, outpoint: &Outpoint) -> Option<&Vec<u8>> {
+        self.spenders.get(&outpoint_key(outpoint))
+    }
+
+    /// Returns the transaction with the given wtxid, if it is in the mempool.
+    pub fn get_by_wtxid(&self, wtxid: &Vec<u8>) -> Option<&Transaction> {
+        self.txs.get(self.wtxids.get(wtxid)?)
+    }
+
+    /// Returns the fee, virtual size and arrival time of the transaction with the given txid, if it
+    /// is in the mempool.
+    pub fn get_entry(&self, tx_id: &Vec<u8>) -> Option<&MempoolEntry> {
+        self.entries.get(tx_id)
+    }
+
+    /// Returns the sum of the virtual sizes of the transactions in the mempool, in bytes.
+    pub fn get_size(&self) -> usize {
+        self.size
+    }
+
     /// Returns the number of transactions in the mempool.
     ///
     /// # Returns
@@ -52,6 +224,51 @@ impl Mempool {
     pub fn get_txs(&self) -> &HashMap<Vec<u8>, Transaction> {
         &self.txs
     }
+
+    /// Takes a snapshot of the ids and fee rates of the transactions in the mempool, so later
+    /// changes can be obtained through `diff` instead of copying the whole mempool again.
+    ///
+    /// # Arguments
+    ///
+    /// * `utxo` - The unspent transaction set, used to compute the fee rates.
+    pub fn snapshot(&self, utxo: &UnspentTx) -> MempoolSnapshot {
+        MempoolSnapshot::new(
+            self.txs
+                .iter()
+                .map(|(tx_id, tx)| (tx_id.clone(), utxo.get_fee_rate(tx)))
+                .collect(),
+        )
+    }
+
+    /// Returns the transactions that entered or left the mempool since a snapshot was taken.
+    ///
+    /// Only the fee rates of the added transactions are computed.
+    ///
+    /// # Arguments
+    ///
+    /// * `prev_snapshot` - The snapshot the mempool is compared against.
+    /// * `utxo` - The unspent transaction set, used to compute the fee rates.
+    pub fn diff(&self, prev_snapshot: &MempoolSnapshot, utxo: &UnspentTx) -> MempoolDiff {
+        let added = self
+            .txs
+            .iter()
+            .filter(|(tx_id, _)| !prev_snapshot.contains(tx_id))
+            .map(|(tx_id, tx)| (tx_id.clone(), utxo.get_fee_rate(tx)))
+            .collect();
+
+        let removed = prev_snapshot
+            .get_fee_rates()
+            .keys()
+            .filter(|tx_id| !self.txs.contains_key(*tx_id))
+            .cloned()
+            .collect();
+
+        MempoolDiff::new(added, removed)
+    }
+}
+
+fn outpoint_key(outpoint: &Outpoint) -> (Vec<u8>, u32) {
+    (outpoint.get_tx_id().clone(), outpoint.get_index())
 }
 
 impl Default for Mempool {
@@ -59,3 +276,99 @@ impl Default for Mempool {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod mempool_test {
+    use super::*;
+    use crate::block_mod::{tx_in::TxIn, tx_out::TxOut};
+
+    fn transaction(prev_tx_id: Vec<u8>, value: i64) -> Transaction {
+        Transaction::new(
+            1,
+            vec![TxIn::new(prev_tx_id, 0, vec![], 0xffffffff)],
+            vec![TxOut::new(value, vec![0; 25])],
+            0,
+            false,
+        )
+    }
+
+    #[test]
+    fn test_mempool_diff_since_snapshot() {
+        let mut utxo = UnspentTx::new();
+        let funding = transaction(vec![0; 32], 10000);
+        utxo.update_transaction(&funding);
+
+        let first = transaction(vec![1; 32], 9000);
+        let second = transaction(funding.get_id(false), 9000);
+        let mut mempool = Mempool::new();
+        mempool.add(first.clone(), None);
+        let mut snapshot = mempool.snapshot(&utxo);
+
+        mempool.remove(&first.get_id(false));
+        mempool.add(second.clone(), utxo.get_fee(&second));
+        let diff = mempool.diff(&snapshot, &utxo);
+
+        assert_eq!(diff.get_removed(), &vec![first.get_id(false)]);
+        assert_eq!(
+            diff.get_added(),
+            &vec![(second.get_id(false), utxo.get_fee_rate(&second))]
+        );
+        assert!(utxo.get_fee_rate(&second).is_some());
+
+        snapshot.apply(&diff);
+        assert_eq!(snapshot, mempool.snapshot(&utxo));
+        assert!(mempool.diff(&snapshot, &utxo).is_empty());
+    }
+
+    #[test]
+    fn test_mempool_is_indexed_by_wtxid() {
+        let tx = transaction(vec![1; 32], 9000);
+        let mut mempool = Mempool::new();
+        mempool.add(tx.clone(), None);
+
+        assert_eq!(
+            mempool.get_by_wtxid(&tx.wtxid()).map(|tx| tx.get_id(false)),
+            Some(tx.get_id(false))
+        );
+        assert!(mempool.get_by_wtxid(&vec![2; 32]).is_none());
+    }
+
+    #[test]
+    fn test_mempool_evicts_the_lowest_fee_rates() {
+        let cheap = transaction(vec![1; 32], 9000);
+        let child = transaction(cheap.get_id(false), 8000);
+        let expensive = transaction(vec![2; 32], 9000);
+        let vsize = cheap.vsize();
+
+        let mut mempool = Mempool::new();
+        mempool.add(cheap.clone(), Some(100));
+        mempool.add(child.clone(), Some(5000));
+        mempool.add(expensive.clone(), Some(1000));
+        assert_eq!(mempool.get_size(), 3 * vsize);
+        assert_eq!(
+            mempool.get_by_fee_rate(),
+            vec![
+                child.get_id(false),
+                expensive.get_id(false),
+                cheap.get_id(false)
+            ]
+        );
+
+        // Evicting the cheapest transaction also evicts the one spending its output
+        let removed = mempool.trim(2 * vsize, Duration::from_secs(3600));
+        assert_eq!(removed, vec![child.get_id(false), cheap.get_id(false)]);
+        assert_eq!(mempool.get_size(), vsize);
+        assert!(mempool
+            .get_spender(child.get_tx_in_list()[0].get_prev_output())
+            .is_none());
+        assert!(mempool.get_by_wtxid(&cheap.wtxid()).is_none());
+
+        assert!(mempool.trim(vsize, Duration::from_secs(3600)).is_empty());
+        assert_eq!(
+            mempool.trim(vsize, Duration::ZERO),
+            vec![expensive.get_id(false)]
+        );
+        assert_eq!(mempool.cant_txs(), 0);
+        assert_eq!(mempool.get_size(), 0);
+    }
+}
@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+
+/// Represents the transactions held by the mempool at some point in time, along with their fee
+/// rates in satoshis per kilobyte, if they are known.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MempoolSnapshot {
+    fee_rates: HashMap<Vec<u8>, Option<u64>>,
+}
+
+/// Represents the changes of the mempool between two snapshots.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MempoolDiff {
+    added: Vec<(Vec<u8>, Option<u64>)>,
+    removed: Vec<Vec<u8>>,
+}
+
+impl MempoolSnapshot {
+    /// Creates a new snapshot from the fee rates of the transactions held by the mempool.
+    ///
+    /// # Arguments
+    ///
+    /// * `fee_rates` - The fee rate of every transaction, indexed by its id.
+    pub fn new(fee_rates: HashMap<Vec<u8>, Option<u64>>) -> MempoolSnapshot {
+        MempoolSnapshot { fee_rates }
+    }
+
+    /// Brings the snapshot up to date with the changes of the mempool.
+    ///
+    /// # Arguments
+    ///
+    /// * `diff` - The changes of the mempool since the snapshot was taken.
+    pub fn apply(&mut self, diff: &MempoolDiff) {
+        for tx_id in diff.get_removed() {
+            self.fee_rates.remove(tx_id);
+        }
+
+        for (tx_id, fee_rate) in diff.get_added() {
+            self.fee_rates.insert(tx_id.clone(), *fee_rate);
+        }
+    }
+
+    pub fn contains(&self, tx_id: &Vec<u8>) -> bool {
+        self.fee_rates.contains_key(tx_id)
+    }
+
+    pub fn get_fee_rates(&self) -> &HashMap<Vec<u8>, Option<u64>> {
+        &self.fee_rates
+    }
+
+    pub fn len(&self) -> usize {
+        self.fee_rates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fee_rates.is_empty()
+    }
+}
+
+impl MempoolDiff {
+    /// Creates a new diff of the mempool.
+    ///
+    /// # Arguments
+    ///
+    /// * `added` - The ids and fee rates of the transactions that entered the mempool.
+    /// * `removed` - The ids of the transactions that left the mempool.
+    pub fn new(added: Vec<(Vec<u8>, Option<u64>)>, removed: Vec<Vec<u8>>) -> MempoolDiff {
+        MempoolDiff { added, removed }
+    }
+
+    pub fn get_added(&self) -> &Vec<(Vec<u8>, Option<u64>)> {
+        &self.added
+    }
+
+    pub fn get_removed(&self) -> &Vec<Vec<u8>> {
+        &self.removed
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
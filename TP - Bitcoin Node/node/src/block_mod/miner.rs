@@ -0,0 +1,265 @@
+use super::block::{
+    calculate_merkle_root, calculate_witness_commitment, calculate_witness_root, Block,
+};
+use super::block_constants::*;
+use super::block_header::BlockHeader;
+use super::chain_state::ChainState;
+use super::chain_update::ChainUpdate;
+use super::coinbase::Coinbase;
+use super::transaction::Transaction;
+use super::tx_in_coinbase::TxInCoinbase;
+use super::tx_out::TxOut;
+use super::witness::Witness;
+use crate::messages::compact_size::CompactSizeUInt;
+use crate::messages::script::Script;
+use crate::network::bitcoin_network::Network;
+use crate::network::broadcasting::submit_block;
+use crate::network::network_error::NetworkError;
+use crate::network::peer_pool::PeerPool;
+use crate::settings_mod::settings::Settings;
+use chrono::Utc;
+use std::sync::{Arc, Mutex};
+
+/// Returns the subsidy of a block at the given height, which halves every halving interval of the
+/// network.
+pub fn block_subsidy(network: Network, height: u32) -> i64 {
+    let halvings = height / network.get_halving_interval();
+
+    if halvings >= 64 {
+        return 0;
+    }
+    INITIAL_BLOCK_SUBSIDY >> halvings
+}
+
+/// Encodes the height of a block as the minimal push the coinbase starts its script with (BIP34).
+fn height_script(height: u32) -> Script {
+    let mut read_bytes = height.to_le_bytes().to_vec();
+
+    while read_bytes.last() == Some(&0) {
+        read_bytes.pop();
+    }
+    if read_bytes.last().is_some_and(|byte| byte & 0x80 != 0) {
+        read_bytes.push(0);
+    }
+
+    Script {
+        number: height as u64,
+        bytes: read_bytes.len() as u8,
+        read_bytes,
+    }
+}
+
+/// Builds the coinbase of a block, paying the reward to the given script.
+///
+/// # Arguments
+///
+/// * `height` - The height of the block.
+/// * `reward` - The subsidy plus the fees of the transactions of the block.
+/// * `pk_script` - The script the reward is paid to.
+fn build_coinbase(height: u32, reward: i64, pk_script: Vec<u8>) -> Coinbase {
+    let height = height_script(height);
+    let tx_in = TxInCoinbase {
+        hash: vec![0; 32],
+        index: COINBASE_NULL_INDEX,
+        script_bytes: CompactSizeUInt::from_number(height.cant_bytes() as u64),
+        height,
+        coinbase_script: Vec::new(),
+        sequence: COINBASE_SEQUENCE,
+    };
+
+    Coinbase {
+        version: 1,
+        flag: 0,
+        tx_in_count: CompactSizeUInt::from_number(1),
+        tx_in_list: vec![tx_in],
+        tx_out_count: CompactSizeUInt::from_number(1),
+        tx_out_list: vec![TxOut::new(reward, pk_script)],
+        witness: Vec::new(),
+        lock_time: 0,
+    }
+}
+
+/// Adds the witness commitment of the given transactions to a coinbase (BIP141), along with the
+/// reserved value it is computed with as the witness of the coinbase.
+fn add_witness_commitment(coinbase: &mut Coinbase, txn_list: &[Transaction]) {
+    let reserved_value = vec![0; WITNESS_RESERVED_VALUE_SIZE];
+    let commitment =
+        calculate_witness_commitment(&calculate_witness_root(txn_list), &reserved_value);
+
+    coinbase.tx_out_list.push(TxOut::new(
+        0,
+        [WITNESS_COMMITMENT_HEADER.to_vec(), commitment].concat(),
+    ));
+    coinbase.tx_out_count = CompactSizeUInt::from_number(coinbase.tx_out_list.len() as u64);
+    coinbase.flag = 1;
+    coinbase.witness = vec![Witness::new(vec![reserved_value])];
+}
+
+/// Assembles a regtest block on top of the given parent and grinds its nonce until the header
+/// meets the target.
+///
+/// # Arguments
+///
+/// * `network` - The network the block is mined for.
+/// * `parent` - The header the block is built on.
+/// * `height` - The height of the block.
+/// * `txs` - The transactions of the block, besides the coinbase, along with their total fee.
+/// * `pk_script` - The script the reward is paid to.
+///
+/// # Returns
+///
+/// The mined block, or `None` if the network is not regtest, whose target is low enough to be
+/// met instantly, or no nonce meets the target.
+pub fn mine_block(
+    network: Network,
+    parent: &BlockHeader,
+    height: u32,
+    txs: (Vec<Transaction>, u64),
+    pk_script: Vec<u8>,
+) -> Option<Block> {
+    if network != Network::Regtest {
+        return None;
+    }
+
+    let (txn_list, fees) = txs;
+    let mut coinbase = build_coinbase(
+        height,
+        block_subsidy(network, height) + fees as i64,
+        pk_script,
+    );
+    if txn_list.iter().any(|tx| tx.is_segwit()) {
+        add_witness_commitment(&mut coinbase, &txn_list);
+    }
+
+    let mut txn_ids = vec![coinbase.get_id()];
+    txn_ids.extend(txn_list.iter().map(|tx| tx.get_id(false)));
+
+    let time = (Utc::now().timestamp() as u32).max(parent.get_time() + 1);
+    let mut block_header = BlockHeader::new(
+        MINED_BLOCK_VERSION,
+        parent.get_header(),
+        calculate_merkle_root(&mut txn_ids),
+        time,
+        network.get_genesis_header().get_nbits(),
+        0,
+    );
+
+    while !block_header.proof_of_work() {
+        block_header.nonce = block_header.nonce.checked_add(1)?;
+    }
+
+    Some(Block {
+        block_header,
+        txn_count: CompactSizeUInt::from_number(txn_list.len() as u64 + 1),
+        coinbase,
+        txn_list,
+    })
+}
+
+/// Mines a regtest block with the transactions of the mempool on top of the tip of the chain,
+/// and submits it through the same path as the blocks received from peers.
+///
+/// Only the transactions whose inputs are all confirmed are included, so the block never spends
+/// an output created later in it.
+///
+/// # Arguments
+///
+/// * `settings` - The network settings.
+/// * `chain_state` - The shared blockchain, UTXO set, mempool and header chain.
+/// * `peer_pool` - The shared pool of connections the block is announced to.
+/// * `pk_script` - The script the reward is paid to.
+///
+/// # Returns
+///
+/// How the main chain changed, or a NetworkError.
+///
+/// # Errors
+///
+/// Returns `NetworkError::Mining` if the node does not run on regtest or the chain is empty, or
+/// the errors of `submit_block`.
+pub fn generate_block(
+    settings: &Arc<Settings>,
+    chain_state: &ChainState,
+    peer_pool: &Arc<Mutex<PeerPool>>,
+    pk_script: Vec<u8>,
+) -> Result<ChainUpdate, NetworkError> {
+    let mut txn_list = Vec::new();
+    let mut fees = 0;
+
+    {
+        let locked_utxo = chain_state
+            .get_utxo()
+            .lock()
+            .map_err(|_| NetworkError::Broadcasting)?;
+        let locked_mempool = chain_state
+            .get_mempool()
+            .lock()
+            .map_err(|_| NetworkError::Broadcasting)?;
+
+        for tx in locked_mempool.get_txs().values() {
+            if let Some(fee) = locked_utxo.get_fee(tx) {
+                txn_list.push(tx.clone());
+                fees += fee;
+            }
+        }
+    }
+
+    let (parent, height) = {
+        let locked_header_chain = chain_state
+            .get_header_chain()
+            .lock()
+            .map_err(|_| NetworkError::Broadcasting)?;
+        let parent = locked_header_chain
+            .get_headers()
+            .last()
+            .cloned()
+            .ok_or(NetworkError::Mining)?;
+
+        (parent, locked_header_chain.get_height() + 1)
+    };
+
+    let block = mine_block(
+        settings.get_network(),
+        &parent,
+        height,
+        (txn_list, fees),
+        pk_script,
+    )
+    .ok_or(NetworkError::Mining)?;
+
+    println!("Mined block at height {}.", height);
+    submit_block(settings, chain_state, peer_pool, block)
+}
+
+#[cfg(test)]
+mod miner_test {
+    use super::*;
+    use crate::messages::message_error::MessageError;
+
+    #[test]
+    fn test_mined_regtest_block_meets_the_target() -> Result<(), MessageError> {
+        let genesis = Network::Regtest.get_genesis_header();
+        let block = mine_block(Network::Regtest, &genesis, 1, (vec![], 0), vec![0x51])
+            .ok_or(MessageError::InvalidInputSubmitBlock)?;
+
+        assert!(block.proof_of_work());
+        assert!(block.proof_of_inclusion());
+        assert_eq!(*block.get_previuos_block_header(), genesis.get_header());
+        assert_eq!(
+            block.get_coinbase().tx_out_list[0].get_value(),
+            INITIAL_BLOCK_SUBSIDY
+        );
+
+        let parsed = Block::from_bytes(&mut block.to_bytes().as_slice())?;
+        assert_eq!(parsed.get_coinbase().tx_in_list[0].height.value(), 1);
+        assert_eq!(parsed.get_txn_ids(), block.get_txn_ids());
+
+        assert!(mine_block(Network::Testnet, &genesis, 1, (vec![], 0), vec![0x51]).is_none());
+        assert_eq!(
+            block_subsidy(Network::Regtest, 300),
+            INITIAL_BLOCK_SUBSIDY / 4
+        );
+        assert_eq!(block_subsidy(Network::Testnet, 300), INITIAL_BLOCK_SUBSIDY);
+        Ok(())
+    }
+}
@@ -32,12 +32,16 @@ impl Outpoint {
     /// # Returns
     /// A vector of bytes representing the `Outpoint` instance.
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut buff = Vec::new();
+        let mut buff = Vec::with_capacity(self.tx_id.len() + 4);
 
+        self.to_bytes_into(&mut buff);
+        buff
+    }
+
+    /// Appends the byte representation of the `Outpoint` to the given buffer.
+    pub fn to_bytes_into(&self, buff: &mut Vec<u8>) {
         buff.extend(&self.tx_id);
         buff.extend(self.index.to_le_bytes());
-
-        buff
     }
 
     /// A reference to the transaction ID associated with the outpoint.
@@ -0,0 +1,96 @@
+use super::block_constants::*;
+
+/// Represents the relative lock time an input imposes on the transaction spending it, as defined
+/// in BIP68.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelativeLockTime {
+    /// Amount of blocks that must be mined on top of the block containing the spent output.
+    Blocks(u32),
+    /// Amount of seconds that must elapse since the block containing the spent output.
+    Time(u32),
+}
+
+impl RelativeLockTime {
+    /// Decodes the relative lock time of an input from its sequence number.
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - The version of the transaction the input belongs to.
+    /// * `sequence` - The sequence number of the input.
+    ///
+    /// # Returns
+    ///
+    /// The relative lock time of the input, or `None` if the transaction version predates BIP68 or
+    /// the input has its relative lock time disabled.
+    pub fn from_sequence(version: i32, sequence: u32) -> Option<RelativeLockTime> {
+        if version < BIP68_MIN_VERSION || sequence & SEQUENCE_LOCKTIME_DISABLE_FLAG != 0 {
+            return None;
+        }
+
+        let value = sequence & SEQUENCE_LOCKTIME_MASK;
+
+        if sequence & SEQUENCE_LOCKTIME_TYPE_FLAG != 0 {
+            return Some(RelativeLockTime::Time(
+                value << SEQUENCE_LOCKTIME_GRANULARITY,
+            ));
+        }
+        Some(RelativeLockTime::Blocks(value))
+    }
+
+    /// Checks whether the lock is satisfied for an output confirmed at the given height and time.
+    ///
+    /// # Arguments
+    ///
+    /// * `confirmation` - The height and time of the block containing the spent output, or `None`
+    ///   if the output is still unconfirmed.
+    /// * `tip_height` - The height of the tip of the chain.
+    /// * `tip_time` - The time of the tip of the chain.
+    ///
+    /// # Returns
+    ///
+    /// `true` if a transaction spending the output could be included in the next block.
+    pub fn is_satisfied(
+        &self,
+        confirmation: Option<(u32, u32)>,
+        tip_height: u32,
+        tip_time: u32,
+    ) -> bool {
+        match (self, confirmation) {
+            (RelativeLockTime::Blocks(blocks), Some((height, _))) => {
+                height + blocks <= tip_height + 1
+            }
+            (RelativeLockTime::Time(seconds), Some((_, time))) => time + seconds <= tip_time,
+            (RelativeLockTime::Blocks(0), None) | (RelativeLockTime::Time(0), None) => true,
+            (_, None) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod relative_lock_time_test {
+    use super::*;
+
+    #[test]
+    fn test_relative_lock_time_from_sequence() {
+        assert_eq!(RelativeLockTime::from_sequence(1, 10), None);
+        assert_eq!(RelativeLockTime::from_sequence(2, 0xfffffffd), None);
+        assert_eq!(
+            RelativeLockTime::from_sequence(2, 10),
+            Some(RelativeLockTime::Blocks(10))
+        );
+        assert_eq!(
+            RelativeLockTime::from_sequence(2, SEQUENCE_LOCKTIME_TYPE_FLAG | 2),
+            Some(RelativeLockTime::Time(1024))
+        );
+    }
+
+    #[test]
+    fn test_relative_lock_time_is_satisfied() {
+        let lock = RelativeLockTime::Blocks(10);
+
+        assert!(lock.is_satisfied(Some((100, 0)), 109, 0));
+        assert!(!lock.is_satisfied(Some((100, 0)), 108, 0));
+        assert!(!lock.is_satisfied(None, 109, 0));
+        assert!(RelativeLockTime::Blocks(0).is_satisfied(None, 0, 0));
+    }
+}
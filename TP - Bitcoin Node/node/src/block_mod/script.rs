@@ -1,7 +1,22 @@
+use super::{
+    address::{encode_base58_check, encode_segwit_address},
+    block_constants::{
+        MAX_SCRIPT_ELEMENT_SIZE, MAX_WITNESS_PROGRAM_SIZE, MIN_WITNESS_PROGRAM_SIZE, OPCODES,
+        OP_RETURN, TAPROOT_PROGRAM_SIZE, TAPROOT_VERSION,
+    },
+    script_command::ScriptCommand,
+    script_type::ScriptType,
+};
+use crate::messages::message_error::MessageError;
+use crate::network::bitcoin_network::Network;
+use std::fmt;
+
 /// Represents a script.
-#[derive(Debug, Clone)]
+///
+/// Each command is either an opcode or the data pushed by the script.
+#[derive(Debug, Clone, PartialEq)]
 pub struct Script {
-    cmds: Vec<Vec<u8>>,
+    cmds: Vec<ScriptCommand>,
 }
 
 impl Script {
@@ -14,7 +29,7 @@ impl Script {
     /// # Returns
     ///
     /// A new `Script` instance.
-    pub fn new(commands: Option<Vec<Vec<u8>>>) -> Script {
+    pub fn new(commands: Option<Vec<ScriptCommand>>) -> Script {
         match commands {
             Some(cmds) => Script { cmds },
             None => Script { cmds: Vec::new() },
@@ -30,25 +45,414 @@ impl Script {
         let mut buffer: Vec<u8> = Vec::new();
 
         for cmd in &self.cmds {
-            let length = cmd.len();
+            let data = match cmd {
+                ScriptCommand::Op(opcode) => {
+                    buffer.push(*opcode);
+                    continue;
+                }
+                ScriptCommand::Push(data) => data,
+            };
+            let length = data.len();
 
-            if length == 1 {
-                buffer.push(cmd[0]);
+            if length <= 75 {
+                buffer.push(length as u8);
+            } else if length <= 0xff {
+                buffer.push(76);
+                buffer.push(length as u8);
+            } else if length <= 0xffff {
+                buffer.push(77);
+                buffer.extend((length as u16).to_le_bytes());
             } else {
-                if length <= 75 {
-                    buffer.push(length as u8);
-                } else if length <= 0xff {
-                    buffer.push(76);
-                    buffer.push(length as u8);
-                } else if length <= 520 {
-                    buffer.push(77);
-                    let length = (length as u16).to_le_bytes();
-                    buffer.extend(length);
-                }
-                buffer.extend(cmd);
+                buffer.push(78);
+                buffer.extend((length as u32).to_le_bytes());
             }
+            buffer.extend(data);
         }
 
         buffer
     }
+
+    /// Parses the byte representation of a script.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The serialized script, without its length.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MessageError::InvalidScript` if a data push runs past the end of the script or
+    /// pushes more than `MAX_SCRIPT_ELEMENT_SIZE` bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Script, MessageError> {
+        let mut cmds = vec![];
+        let mut index = 0;
+
+        while index < bytes.len() {
+            let opcode = bytes[index];
+            index += 1;
+
+            let (length, size_bytes) = match opcode {
+                0x01..=0x4b => (opcode as usize, 0),
+                0x4c => (read_push_length(bytes, index, 1)?, 1),
+                0x4d => (read_push_length(bytes, index, 2)?, 2),
+                0x4e => (read_push_length(bytes, index, 4)?, 4),
+                _ => {
+                    cmds.push(ScriptCommand::Op(opcode));
+                    continue;
+                }
+            };
+            index += size_bytes;
+
+            if length > MAX_SCRIPT_ELEMENT_SIZE {
+                return Err(MessageError::InvalidScript);
+            }
+
+            let data = bytes
+                .get(index..index + length)
+                .ok_or(MessageError::InvalidScript)?;
+            cmds.push(ScriptCommand::Push(data.to_vec()));
+            index += length;
+        }
+
+        Ok(Script { cmds })
+    }
+
+    /// Parses a script from its human-readable assembly, such as
+    /// `OP_DUP OP_HASH160 <hex> OP_EQUALVERIFY OP_CHECKSIG`.
+    ///
+    /// Opcodes are written by name, or as `0x` followed by their hexadecimal value, while pushed
+    /// data is written in hexadecimal, optionally between angle brackets. An empty push is written
+    /// as `<>`.
+    ///
+    /// # Arguments
+    ///
+    /// * `asm` - The assembly of the script, its commands separated by whitespace.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MessageError::InvalidScript` if an opcode is unknown or some pushed data is not
+    /// valid hexadecimal or is longer than `MAX_SCRIPT_ELEMENT_SIZE` bytes.
+    pub fn from_asm(asm: &str) -> Result<Script, MessageError> {
+        let mut cmds = vec![];
+
+        for token in asm.split_whitespace() {
+            if token.starts_with("OP_") {
+                let opcode = OPCODES
+                    .iter()
+                    .find(|(_, name)| *name == token)
+                    .ok_or(MessageError::InvalidScript)?;
+                cmds.push(ScriptCommand::Op(opcode.0));
+            } else if let Some(opcode) = token.strip_prefix("0x") {
+                match hex::decode(opcode)?.as_slice() {
+                    [opcode] => cmds.push(ScriptCommand::Op(*opcode)),
+                    _ => return Err(MessageError::InvalidScript),
+                }
+            } else {
+                let data = token.trim_start_matches('<').trim_end_matches('>');
+                let data = hex::decode(data)?;
+                if data.len() > MAX_SCRIPT_ELEMENT_SIZE {
+                    return Err(MessageError::InvalidScript);
+                }
+                cmds.push(ScriptCommand::Push(data));
+            }
+        }
+
+        Ok(Script { cmds })
+    }
+
+    /// Returns the human-readable assembly of the script, in the format accepted by `from_asm`.
+    ///
+    /// Opcodes without a name are written as `0x` followed by their hexadecimal value.
+    pub fn to_asm(&self) -> String {
+        self.cmds
+            .iter()
+            .map(|cmd| match cmd {
+                ScriptCommand::Push(data) if data.is_empty() => "<>".to_string(),
+                ScriptCommand::Push(data) => hex::encode(data),
+                ScriptCommand::Op(opcode) => match OPCODES.iter().find(|(op, _)| op == opcode) {
+                    Some((_, name)) => name.to_string(),
+                    None => format!("0x{:02x}", opcode),
+                },
+            })
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    /// Returns the commands of the script.
+    pub fn get_cmds(&self) -> &Vec<ScriptCommand> {
+        &self.cmds
+    }
+
+    /// Returns the version and the program of the script, if it is a witness program: a version
+    /// opcode, `OP_0` or `OP_1` to `OP_16`, followed by a single push of 2 to 40 bytes (BIP141).
+    pub fn get_witness_program(&self) -> Option<(u8, &Vec<u8>)> {
+        let (version, program) = match self.cmds.as_slice() {
+            [ScriptCommand::Op(version), ScriptCommand::Push(program)] => (*version, program),
+            _ => return None,
+        };
+
+        let version = match version {
+            0x00 => 0,
+            0x51..=0x60 => version - 0x50,
+            _ => return None,
+        };
+        (MIN_WITNESS_PROGRAM_SIZE..=MAX_WITNESS_PROGRAM_SIZE)
+            .contains(&program.len())
+            .then_some((version, program))
+    }
+
+    /// Creates a null-data script, `OP_RETURN` followed by the push of the data, which marks its
+    /// output as provably unspendable.
+    pub fn op_return(data: Vec<u8>) -> Script {
+        let mut cmds = vec![ScriptCommand::Op(OP_RETURN)];
+        if !data.is_empty() {
+            cmds.push(ScriptCommand::Push(data));
+        }
+        Script { cmds }
+    }
+
+    /// Returns the data embedded in the script, if it is a null-data script: `OP_RETURN` followed
+    /// only by data pushes, whose contents are concatenated.
+    pub fn op_return_data(&self) -> Option<Vec<u8>> {
+        let (first, pushes) = self.cmds.split_first()?;
+        if *first != ScriptCommand::Op(OP_RETURN) {
+            return None;
+        }
+
+        let mut data = vec![];
+        for push in pushes {
+            match push {
+                ScriptCommand::Op(0x00) => {}
+                ScriptCommand::Op(_) => return None,
+                ScriptCommand::Push(bytes) => data.extend(bytes),
+            }
+        }
+        Some(data)
+    }
+
+    /// Classifies the script by the standard template it follows.
+    pub fn classify(&self) -> ScriptType {
+        if self.is_p2tr() {
+            return ScriptType::P2tr;
+        }
+        match self.get_witness_program() {
+            Some((0, program)) if program.len() == 20 => return ScriptType::P2wpkh,
+            Some((0, program)) if program.len() == 32 => return ScriptType::P2wsh,
+            _ => {}
+        }
+        if self.op_return_data().is_some() {
+            return ScriptType::OpReturn;
+        }
+
+        use ScriptCommand::{Op, Push};
+        match self.cmds.as_slice() {
+            [Push(pubkey), Op(0xac)] if matches!(pubkey.len(), 33 | 65) => ScriptType::P2pk,
+            [Op(0x76), Op(0xa9), Push(hash), Op(0x88), Op(0xac)] if hash.len() == 20 => {
+                ScriptType::P2pkh
+            }
+            [Op(0xa9), Push(hash), Op(0x87)] if hash.len() == 20 => ScriptType::P2sh,
+            _ => ScriptType::NonStandard,
+        }
+    }
+
+    /// Returns the address the script pays to on the given network, if its type has one: base58
+    /// for P2PKH and P2SH scripts, and bech32 or bech32m for witness programs.
+    pub fn to_address(&self, network: Network) -> Option<String> {
+        match (self.classify(), self.cmds.as_slice()) {
+            (ScriptType::P2pkh, [_, _, ScriptCommand::Push(hash), ..]) => {
+                Some(encode_base58_check(network.get_p2pkh_prefix(), hash))
+            }
+            (ScriptType::P2sh, [_, ScriptCommand::Push(hash), ..]) => {
+                Some(encode_base58_check(network.get_p2sh_prefix(), hash))
+            }
+            (ScriptType::P2wpkh | ScriptType::P2wsh | ScriptType::P2tr, _) => {
+                let (version, program) = self.get_witness_program()?;
+                Some(encode_segwit_address(
+                    network.get_bech32_hrp(),
+                    version,
+                    program,
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns whether the script pays to a taproot output key: a version 1 witness program of 32
+    /// bytes (BIP341).
+    pub fn is_p2tr(&self) -> bool {
+        matches!(
+            self.get_witness_program(),
+            Some((TAPROOT_VERSION, program)) if program.len() == TAPROOT_PROGRAM_SIZE
+        )
+    }
+}
+
+/// Reads the little-endian length of an `OP_PUSHDATA` push.
+fn read_push_length(bytes: &[u8], index: usize, size: usize) -> Result<usize, MessageError> {
+    let length = bytes
+        .get(index..index + size)
+        .ok_or(MessageError::InvalidScript)?;
+
+    Ok(length
+        .iter()
+        .rev()
+        .fold(0, |length, byte| (length << 8) | *byte as usize))
+}
+
+impl fmt::Display for Script {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_asm())
+    }
+}
+
+#[cfg(test)]
+mod script_test {
+    use super::*;
+
+    #[test]
+    fn test_p2pkh_script_asm_round_trip() -> Result<(), MessageError> {
+        let asm =
+            "OP_DUP OP_HASH160 c9bc003bf72ebdc53a9572f7ea792ef49a2858d7 OP_EQUALVERIFY OP_CHECKSIG";
+        let bytes = hex::decode("76a914c9bc003bf72ebdc53a9572f7ea792ef49a2858d788ac")?;
+
+        let script = Script::from_asm(asm)?;
+
+        assert_eq!(script.to_bytes(), bytes);
+        assert_eq!(Script::from_bytes(&bytes)?, script);
+        assert_eq!(script.to_asm(), asm);
+        assert_eq!(
+            Script::from_asm("OP_DUP OP_HASH160 <c9bc003bf72ebdc53a9572f7ea792ef49a2858d7> OP_EQUALVERIFY OP_CHECKSIG")?,
+            script
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_script_asm() {
+        assert!(Script::from_asm("OP_DUP OP_FOO").is_err());
+        assert!(Script::from_asm("OP_RETURN 0x0a0b").is_err());
+        assert!(Script::from_asm("OP_RETURN xyz").is_err());
+        assert!(Script::from_bytes(&[0x4c, 0x05, 0x01]).is_err());
+    }
+
+    #[test]
+    fn test_single_byte_pushes_round_trip() -> Result<(), MessageError> {
+        // OP_RETURN, a push of 0x76 and OP_DUP
+        let bytes = vec![0x6a, 0x01, 0x76, 0x76];
+
+        let script = Script::from_bytes(&bytes)?;
+
+        assert_eq!(
+            script.get_cmds(),
+            &vec![
+                ScriptCommand::Op(OP_RETURN),
+                ScriptCommand::Push(vec![0x76]),
+                ScriptCommand::Op(0x76)
+            ]
+        );
+        assert_eq!(script.to_bytes(), bytes);
+        assert_eq!(script.to_asm(), "OP_RETURN 76 OP_DUP");
+        assert_eq!(Script::from_asm(&script.to_asm())?, script);
+        assert_eq!(
+            Script::op_return(vec![0x0a]).op_return_data(),
+            Some(vec![0x0a])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_empty_pushes_round_trip() -> Result<(), MessageError> {
+        let script = Script::new(Some(vec![
+            ScriptCommand::Push(vec![]),
+            ScriptCommand::Op(0x87),
+        ]));
+
+        assert_eq!(script.to_asm(), "<> OP_EQUAL");
+        assert_eq!(Script::from_asm("<> OP_EQUAL")?, script);
+        assert_eq!(script.to_bytes(), vec![0x00, 0x87]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_witness_programs_are_classified_by_version() -> Result<(), MessageError> {
+        let p2wpkh = Script::from_bytes(&hex::decode(
+            "00149df71352136f51c3b344046409d70e89e02430cd",
+        )?)?;
+        let p2tr = Script::from_bytes(&hex::decode(
+            "5120000000c4a5cad46221b2a187905e5266362b99d5e91c6ce24d165dab93e86433",
+        )?)?;
+
+        assert_eq!(
+            p2wpkh.get_witness_program().map(|(version, _)| version),
+            Some(0)
+        );
+        assert!(!p2wpkh.is_p2tr());
+        assert_eq!(
+            p2tr.get_witness_program().map(|(version, _)| version),
+            Some(1)
+        );
+        assert!(p2tr.is_p2tr());
+        assert_eq!(
+            Script::from_asm("OP_1 0102")?.get_witness_program(),
+            Some((1, &vec![1, 2]))
+        );
+        assert!(Script::from_asm("OP_DUP 0102")?
+            .get_witness_program()
+            .is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_op_return_data_is_extracted() -> Result<(), MessageError> {
+        let script = Script::op_return(b"hello world".to_vec());
+        let parsed = Script::from_bytes(&script.to_bytes())?;
+
+        assert_eq!(parsed.op_return_data(), Some(b"hello world".to_vec()));
+        assert_eq!(
+            Script::from_asm("OP_RETURN 0102 OP_0 0304")?.op_return_data(),
+            Some(vec![1, 2, 3, 4])
+        );
+        assert_eq!(Script::op_return(vec![]).op_return_data(), Some(vec![]));
+        assert_eq!(Script::from_asm("OP_RETURN OP_DUP")?.op_return_data(), None);
+        assert_eq!(Script::from_asm("OP_DUP 0102")?.op_return_data(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_scripts_are_classified_and_converted_to_addresses() -> Result<(), MessageError> {
+        let p2pkh = Script::from_asm(
+            "OP_DUP OP_HASH160 751e76e8199196d454941c45d1b3a323f1433bd6 OP_EQUALVERIFY OP_CHECKSIG",
+        )?;
+        let p2wpkh = Script::from_asm("OP_0 751e76e8199196d454941c45d1b3a323f1433bd6")?;
+        let p2sh =
+            Script::from_asm("OP_HASH160 751e76e8199196d454941c45d1b3a323f1433bd6 OP_EQUAL")?;
+        let p2pk = Script::from_asm(
+            "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798 OP_CHECKSIG",
+        )?;
+
+        assert_eq!(p2pkh.classify(), ScriptType::P2pkh);
+        assert_eq!(
+            p2pkh.to_address(Network::Mainnet),
+            Some("1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH".to_string())
+        );
+        assert_eq!(p2wpkh.classify(), ScriptType::P2wpkh);
+        assert_eq!(
+            p2wpkh.to_address(Network::Testnet),
+            Some("tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx".to_string())
+        );
+        assert_eq!(p2sh.classify(), ScriptType::P2sh);
+        assert!(p2sh
+            .to_address(Network::Testnet)
+            .is_some_and(|address| address.starts_with('2')));
+        assert_eq!(p2pk.classify(), ScriptType::P2pk);
+        assert_eq!(p2pk.to_address(Network::Mainnet), None);
+        assert_eq!(
+            Script::op_return(vec![1, 2]).classify(),
+            ScriptType::OpReturn
+        );
+        assert_eq!(
+            Script::from_asm("OP_1 OP_ADD")?.classify(),
+            ScriptType::NonStandard
+        );
+        Ok(())
+    }
 }
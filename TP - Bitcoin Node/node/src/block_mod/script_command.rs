@@ -0,0 +1,9 @@
+/// Represents a command of a script, told apart by how it is serialized rather than by its length,
+/// so that single byte data pushes are not mistaken for opcodes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptCommand {
+    /// An opcode, including the small number opcodes `OP_0` and `OP_1` to `OP_16`.
+    Op(u8),
+    /// The data pushed by a direct push or an `OP_PUSHDATA` opcode.
+    Push(Vec<u8>),
+}
@@ -0,0 +1,30 @@
+/// The reasons a script can fail to evaluate.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ScriptError {
+    EvalFalse,
+    OpReturn,
+    ScriptSize,
+    PushSize,
+    OpCount,
+    StackSize,
+    PubkeyCount,
+    SigCount,
+    InvalidStackOperation,
+    InvalidAltstackOperation,
+    UnbalancedConditional,
+    BadOpcode,
+    DisabledOpcode,
+    NumOverflow,
+    Verify,
+    EqualVerify,
+    NumEqualVerify,
+    CheckSigVerify,
+    CheckMultiSigVerify,
+    SigPushOnly,
+    MalformedScript,
+    WitnessMalleated,
+    WitnessProgramMismatch,
+    WitnessMalleatedP2SH,
+    NegativeLockTime,
+    UnsatisfiedLockTime,
+}
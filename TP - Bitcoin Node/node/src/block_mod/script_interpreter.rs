@@ -0,0 +1,1128 @@
+use super::{
+    block_constants::{
+        BIP68_MIN_VERSION, DISABLED_OPCODES, LOCKTIME_THRESHOLD, MAX_LOCKTIME_NUM_SIZE,
+        MAX_OPS_PER_SCRIPT, MAX_PUBKEYS_PER_MULTISIG, MAX_SCRIPT_ELEMENT_SIZE, MAX_SCRIPT_NUM_SIZE,
+        MAX_SCRIPT_SIZE, MAX_STACK_SIZE, SEQUENCE_FINAL, SEQUENCE_LOCKTIME_DISABLE_FLAG,
+        SEQUENCE_LOCKTIME_MASK, SEQUENCE_LOCKTIME_TYPE_FLAG, SIGHASH_ALL,
+    },
+    script::Script,
+    script_command::ScriptCommand,
+    script_error::ScriptError,
+    transaction::Transaction,
+    tx_out::TxOut,
+};
+use bitcoin_hashes::{hash160, ripemd160, sha1, sha256, sha256d, Hash};
+use secp256k1::{ecdsa::Signature, Message, PublicKey, Secp256k1, VerifyOnly};
+
+/// Checks the signatures found by `OP_CHECKSIG` and `OP_CHECKMULTISIG` against the transaction
+/// being validated.
+pub trait SignatureChecker {
+    /// Returns whether the signature, followed by its hash type, signs the transaction with the key.
+    ///
+    /// # Arguments
+    ///
+    /// * `signature` - The DER-encoded signature followed by its hash type.
+    /// * `pubkey` - The serialized public key.
+    /// * `script_code` - The script the signature commits to.
+    fn check_sig(&self, signature: &[u8], pubkey: &[u8], script_code: &Script) -> bool;
+
+    /// Returns whether the transaction is locked until at least the given height or time, as
+    /// required by `OP_CHECKLOCKTIMEVERIFY` (BIP65).
+    fn check_lock_time(&self, _lock_time: i64) -> bool {
+        false
+    }
+
+    /// Returns whether the input is locked for at least the given relative lock time, as required
+    /// by `OP_CHECKSEQUENCEVERIFY` (BIP112).
+    fn check_sequence(&self, _sequence: i64) -> bool {
+        false
+    }
+}
+
+/// Checks the ECDSA signatures of an input of a transaction, either a legacy or a P2WPKH one.
+///
+/// Only `SIGHASH_ALL` signatures are supported, since it is the only hash type the signature hash
+/// of the transaction is computed for.
+pub struct TransactionSignatureChecker<'a> {
+    transaction: &'a Transaction,
+    index: usize,
+    /// The P2WPKH script being spent and the amounts of every input, if the input is a P2WPKH one.
+    witness: Option<(Vec<u8>, Vec<i64>)>,
+    secp: Secp256k1<VerifyOnly>,
+}
+
+impl<'a> TransactionSignatureChecker<'a> {
+    /// Creates a checker for the signatures of an input of the transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction` - The transaction spending the input.
+    /// * `index` - The index of the input within the transaction.
+    pub fn new(transaction: &'a Transaction, index: usize) -> TransactionSignatureChecker<'a> {
+        TransactionSignatureChecker {
+            transaction,
+            index,
+            witness: None,
+            secp: Secp256k1::verification_only(),
+        }
+    }
+
+    /// Creates a checker for the signatures of a P2WPKH input of the transaction, which commit to
+    /// the amounts being spent.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction` - The transaction spending the input.
+    /// * `index` - The index of the input within the transaction.
+    /// * `pk_script` - The P2WPKH script of the output being spent.
+    /// * `amounts` - The amount of every input of the transaction.
+    pub fn new_p2wpkh(
+        transaction: &'a Transaction,
+        index: usize,
+        pk_script: Vec<u8>,
+        amounts: Vec<i64>,
+    ) -> TransactionSignatureChecker<'a> {
+        TransactionSignatureChecker {
+            transaction,
+            index,
+            witness: Some((pk_script, amounts)),
+            secp: Secp256k1::verification_only(),
+        }
+    }
+}
+
+impl SignatureChecker for TransactionSignatureChecker<'_> {
+    fn check_sig(&self, signature: &[u8], pubkey: &[u8], script_code: &Script) -> bool {
+        let (hash_type, signature) = match signature.split_last() {
+            Some(split) => split,
+            None => return false,
+        };
+        if *hash_type != SIGHASH_ALL || self.index >= self.transaction.get_tx_in_list().len() {
+            return false;
+        }
+
+        let (mut signature, pubkey) = match (
+            Signature::from_der(signature),
+            PublicKey::from_slice(pubkey),
+        ) {
+            (Ok(signature), Ok(pubkey)) => (signature, pubkey),
+            _ => return false,
+        };
+        signature.normalize_s();
+
+        let signature_hash = match &self.witness {
+            Some((pk_script, amounts)) if amounts.len() > self.index => self
+                .transaction
+                .p2wpkh_signature_hash(self.index, pk_script.clone(), amounts.clone()),
+            Some(_) => return false,
+            None => self
+                .transaction
+                .p2pkh_signature_hash(self.index, &script_code.to_bytes()),
+        };
+        let message = Message::from_hashed_data::<sha256::Hash>(&signature_hash);
+
+        self.secp
+            .verify_ecdsa(&message, &signature, &pubkey)
+            .is_ok()
+    }
+
+    fn check_lock_time(&self, lock_time: i64) -> bool {
+        let tx_in = match self.transaction.get_tx_in_list().get(self.index) {
+            Some(tx_in) => tx_in,
+            None => return false,
+        };
+        let tx_lock_time = self.transaction.lock_time as i64;
+        let threshold = LOCKTIME_THRESHOLD as i64;
+
+        // Both lock times must be heights or both must be timestamps
+        if (lock_time < threshold) != (tx_lock_time < threshold) {
+            return false;
+        }
+        lock_time <= tx_lock_time && tx_in.get_sequence() != SEQUENCE_FINAL
+    }
+
+    fn check_sequence(&self, sequence: i64) -> bool {
+        let tx_sequence = match self.transaction.get_tx_in_list().get(self.index) {
+            Some(tx_in) => tx_in.get_sequence(),
+            None => return false,
+        };
+        if self.transaction.get_version() < BIP68_MIN_VERSION
+            || tx_sequence & SEQUENCE_LOCKTIME_DISABLE_FLAG != 0
+        {
+            return false;
+        }
+
+        let mask = (SEQUENCE_LOCKTIME_TYPE_FLAG | SEQUENCE_LOCKTIME_MASK) as i64;
+        let (sequence, tx_sequence) = (sequence & mask, tx_sequence as i64 & mask);
+        let type_flag = SEQUENCE_LOCKTIME_TYPE_FLAG as i64;
+
+        // Both relative lock times must be in blocks or both must be in time
+        if (sequence & type_flag) != (tx_sequence & type_flag) {
+            return false;
+        }
+        sequence <= tx_sequence
+    }
+}
+
+/// Evaluates the unlocking script of an input followed by the locking script of the output it
+/// spends.
+///
+/// The unlocking script may only push data. The stack it leaves is the initial stack of the
+/// locking script, which must leave a true value on top of the stack for the spend to be valid.
+///
+/// # Arguments
+///
+/// * `script_sig` - The unlocking script of the input.
+/// * `pk_script` - The locking script of the output being spent.
+/// * `checker` - Checks the signatures found by the locking script.
+///
+/// # Errors
+///
+/// Returns the `ScriptError` either script failed with, or `ScriptError::EvalFalse` if the
+/// locking script leaves a false value, or nothing, on top of the stack.
+pub fn eval(
+    script_sig: &Script,
+    pk_script: &Script,
+    checker: &dyn SignatureChecker,
+) -> Result<(), ScriptError> {
+    if !is_push_only(script_sig) {
+        return Err(ScriptError::SigPushOnly);
+    }
+
+    let mut stack = vec![];
+    execute(script_sig, &mut stack, checker)?;
+    execute(pk_script, &mut stack, checker)?;
+
+    match stack.last() {
+        Some(top) if cast_to_bool(top) => Ok(()),
+        _ => Err(ScriptError::EvalFalse),
+    }
+}
+
+/// Verifies that an input of a transaction is allowed to spend the output it refers to.
+///
+/// P2WPKH outputs are spent through the witness of the input, while every other output is spent
+/// through the unlocking script of the input. Witness programs other than P2WPKH are evaluated as
+/// legacy scripts, which accept any spend, as nodes that do not know their rules do.
+///
+/// P2SH outputs are additionally spent by the redeem script their hash commits to (BIP16), which
+/// is the last item pushed by the unlocking script and may itself be a P2WPKH witness program.
+///
+/// # Arguments
+///
+/// * `transaction` - The transaction spending the output.
+/// * `index` - The index of the input within the transaction.
+/// * `spent_outputs` - The outputs spent by every input of the transaction, in order.
+///
+/// # Errors
+///
+/// Returns the `ScriptError` the input failed with, or `ScriptError::MalformedScript` if a script
+/// cannot be parsed.
+pub fn verify_input(
+    transaction: &Transaction,
+    index: usize,
+    spent_outputs: &[TxOut],
+) -> Result<(), ScriptError> {
+    let tx_in = transaction
+        .get_tx_in_list()
+        .get(index)
+        .ok_or(ScriptError::InvalidStackOperation)?;
+    let pk_script = spent_outputs
+        .get(index)
+        .ok_or(ScriptError::InvalidStackOperation)?
+        .get_pk_script();
+    let script_sig = tx_in.get_signature_script();
+
+    if is_p2sh(&pk_script) {
+        return verify_p2sh(transaction, index, &script_sig, &pk_script, spent_outputs);
+    }
+    if !is_p2wpkh(&pk_script) {
+        let checker = TransactionSignatureChecker::new(transaction, index);
+        return eval(
+            &Script::from_bytes(&script_sig).map_err(|_| ScriptError::MalformedScript)?,
+            &Script::from_bytes(&pk_script).map_err(|_| ScriptError::MalformedScript)?,
+            &checker,
+        );
+    }
+
+    if !script_sig.is_empty() {
+        return Err(ScriptError::WitnessMalleated);
+    }
+    verify_p2wpkh(transaction, index, pk_script, spent_outputs)
+}
+
+/// Verifies an input spending a P2SH output: the unlocking script must push a redeem script
+/// matching the hash of the output, which is then run on the rest of the stack. A redeem script
+/// that is a P2WPKH witness program must be the only push of the unlocking script, and is spent
+/// through the witness of the input.
+fn verify_p2sh(
+    transaction: &Transaction,
+    index: usize,
+    script_sig: &[u8],
+    pk_script: &[u8],
+    spent_outputs: &[TxOut],
+) -> Result<(), ScriptError> {
+    let script_sig = Script::from_bytes(script_sig).map_err(|_| ScriptError::MalformedScript)?;
+    let pk_script = Script::from_bytes(pk_script).map_err(|_| ScriptError::MalformedScript)?;
+    if !is_push_only(&script_sig) {
+        return Err(ScriptError::SigPushOnly);
+    }
+
+    let checker = TransactionSignatureChecker::new(transaction, index);
+    let mut stack = vec![];
+    execute(&script_sig, &mut stack, &checker)?;
+    let mut redeem_stack = stack.clone();
+    execute(&pk_script, &mut stack, &checker)?;
+
+    match stack.last() {
+        Some(top) if cast_to_bool(top) => {}
+        _ => return Err(ScriptError::EvalFalse),
+    }
+
+    let redeem_script = pop(&mut redeem_stack)?;
+    if is_p2wpkh(&redeem_script) {
+        let pushes_only_redeem_script = matches!(
+            script_sig.get_cmds().as_slice(),
+            [ScriptCommand::Push(push)] if *push == redeem_script
+        );
+        if !pushes_only_redeem_script {
+            return Err(ScriptError::WitnessMalleatedP2SH);
+        }
+        return verify_p2wpkh(transaction, index, redeem_script, spent_outputs);
+    }
+
+    let redeem_script =
+        Script::from_bytes(&redeem_script).map_err(|_| ScriptError::MalformedScript)?;
+    execute(&redeem_script, &mut redeem_stack, &checker)?;
+
+    match redeem_stack.last() {
+        Some(top) if cast_to_bool(top) => Ok(()),
+        _ => Err(ScriptError::EvalFalse),
+    }
+}
+
+/// Verifies an input spending a P2WPKH witness program through its witness, which must hold a
+/// signature and the public key hashed by the program.
+fn verify_p2wpkh(
+    transaction: &Transaction,
+    index: usize,
+    pk_script: Vec<u8>,
+    spent_outputs: &[TxOut],
+) -> Result<(), ScriptError> {
+    let witness = match transaction.get_witness().get(index) {
+        Some(witness) if witness.stack_items.len() == 2 => witness.stack_items.clone(),
+        _ => return Err(ScriptError::WitnessProgramMismatch),
+    };
+
+    let amounts = spent_outputs
+        .iter()
+        .map(|tx_out| tx_out.get_value())
+        .collect();
+    let checker =
+        TransactionSignatureChecker::new_p2wpkh(transaction, index, pk_script.clone(), amounts);
+    let script_code = Script::new(Some(vec![
+        ScriptCommand::Op(0x76),
+        ScriptCommand::Op(0xa9),
+        ScriptCommand::Push(pk_script[2..].to_vec()),
+        ScriptCommand::Op(0x88),
+        ScriptCommand::Op(0xac),
+    ]));
+
+    let mut stack = witness;
+    execute(&script_code, &mut stack, &checker)?;
+
+    match stack.as_slice() {
+        [top] if cast_to_bool(top) => Ok(()),
+        _ => Err(ScriptError::EvalFalse),
+    }
+}
+
+/// Returns whether the script pays to the hash of a redeem script: `OP_HASH160 <20 bytes>
+/// OP_EQUAL`.
+pub fn is_p2sh(pk_script: &[u8]) -> bool {
+    pk_script.len() == 23 && pk_script[0] == 0xa9 && pk_script[1] == 0x14 && pk_script[22] == 0x87
+}
+
+/// Returns whether the script is a version 0 witness program holding a public key hash.
+fn is_p2wpkh(pk_script: &[u8]) -> bool {
+    pk_script.len() == 22 && pk_script[0] == 0x00 && pk_script[1] == 0x14
+}
+
+/// Returns whether every command of the script pushes data onto the stack.
+pub fn is_push_only(script: &Script) -> bool {
+    script
+        .get_cmds()
+        .iter()
+        .all(|cmd| !matches!(cmd, ScriptCommand::Op(opcode) if *opcode > 0x60))
+}
+
+/// Executes every command of the script on the given stack.
+fn execute(
+    script: &Script,
+    stack: &mut Vec<Vec<u8>>,
+    checker: &dyn SignatureChecker,
+) -> Result<(), ScriptError> {
+    if script.to_bytes().len() > MAX_SCRIPT_SIZE {
+        return Err(ScriptError::ScriptSize);
+    }
+
+    let cmds = script.get_cmds();
+    let mut altstack: Vec<Vec<u8>> = vec![];
+    let mut conditions: Vec<bool> = vec![];
+    let mut op_count = 0;
+    let mut code_start = 0;
+
+    for (position, cmd) in cmds.iter().enumerate() {
+        let executing = conditions.iter().all(|condition| *condition);
+
+        match cmd {
+            ScriptCommand::Push(data) => {
+                if data.len() > MAX_SCRIPT_ELEMENT_SIZE {
+                    return Err(ScriptError::PushSize);
+                }
+                if executing {
+                    stack.push(data.clone());
+                }
+            }
+            ScriptCommand::Op(opcode) => {
+                let opcode = *opcode;
+
+                if opcode > 0x60 {
+                    op_count += 1;
+                    if op_count > MAX_OPS_PER_SCRIPT {
+                        return Err(ScriptError::OpCount);
+                    }
+                }
+                if DISABLED_OPCODES.contains(&opcode) {
+                    return Err(ScriptError::DisabledOpcode);
+                }
+                if !executing && !(0x63..=0x68).contains(&opcode) {
+                    continue;
+                }
+
+                match opcode {
+                    // OP_0
+                    0x00 => stack.push(vec![]),
+                    // OP_1NEGATE, OP_1 - OP_16
+                    0x4f | 0x51..=0x60 => stack.push(encode_num(opcode as i64 - 0x50)),
+                    // OP_CHECKLOCKTIMEVERIFY
+                    0xb1 => {
+                        let lock_time = decode_sized_num(&peek(stack, 0)?, MAX_LOCKTIME_NUM_SIZE)?;
+                        if lock_time < 0 {
+                            return Err(ScriptError::NegativeLockTime);
+                        }
+                        if !checker.check_lock_time(lock_time) {
+                            return Err(ScriptError::UnsatisfiedLockTime);
+                        }
+                    }
+                    // OP_CHECKSEQUENCEVERIFY, a NOP if the disable flag of the sequence is set
+                    0xb2 => {
+                        let sequence = decode_sized_num(&peek(stack, 0)?, MAX_LOCKTIME_NUM_SIZE)?;
+                        if sequence < 0 {
+                            return Err(ScriptError::NegativeLockTime);
+                        }
+                        if sequence & SEQUENCE_LOCKTIME_DISABLE_FLAG as i64 == 0
+                            && !checker.check_sequence(sequence)
+                        {
+                            return Err(ScriptError::UnsatisfiedLockTime);
+                        }
+                    }
+                    // OP_NOP, OP_NOP1, OP_NOP4 - OP_NOP10
+                    0x61 | 0xb0 | 0xb3..=0xb9 => {}
+                    // OP_IF, OP_NOTIF
+                    0x63 | 0x64 => {
+                        let mut condition = false;
+                        if executing {
+                            condition = cast_to_bool(&pop(stack)?) == (opcode == 0x63);
+                        }
+                        conditions.push(condition);
+                    }
+                    // OP_ELSE
+                    0x67 => {
+                        let condition = conditions
+                            .last_mut()
+                            .ok_or(ScriptError::UnbalancedConditional)?;
+                        *condition = !*condition;
+                    }
+                    // OP_ENDIF
+                    0x68 => {
+                        conditions.pop().ok_or(ScriptError::UnbalancedConditional)?;
+                    }
+                    // OP_VERIFY
+                    0x69 => verify(stack, ScriptError::Verify)?,
+                    // OP_RETURN
+                    0x6a => return Err(ScriptError::OpReturn),
+                    // OP_TOALTSTACK
+                    0x6b => altstack.push(pop(stack)?),
+                    // OP_FROMALTSTACK
+                    0x6c => stack.push(
+                        altstack
+                            .pop()
+                            .ok_or(ScriptError::InvalidAltstackOperation)?,
+                    ),
+                    // OP_2DROP
+                    0x6d => {
+                        let length = require(stack, 2)?;
+                        stack.truncate(length - 2);
+                    }
+                    // OP_2DUP, OP_3DUP, OP_2OVER
+                    0x6e..=0x70 => {
+                        let (count, depth) = match opcode {
+                            0x6e => (2, 2),
+                            0x6f => (3, 3),
+                            _ => (2, 4),
+                        };
+                        let length = require(stack, depth)?;
+                        let items = stack[length - depth..length - depth + count].to_vec();
+                        stack.extend(items);
+                    }
+                    // OP_2ROT
+                    0x71 => {
+                        let length = require(stack, 6)?;
+                        stack[length - 6..].rotate_left(2);
+                    }
+                    // OP_2SWAP
+                    0x72 => {
+                        let length = require(stack, 4)?;
+                        stack[length - 4..].rotate_left(2);
+                    }
+                    // OP_IFDUP
+                    0x73 => {
+                        let top = peek(stack, 0)?;
+                        if cast_to_bool(&top) {
+                            stack.push(top);
+                        }
+                    }
+                    // OP_DEPTH
+                    0x74 => stack.push(encode_num(stack.len() as i64)),
+                    // OP_DROP
+                    0x75 => {
+                        pop(stack)?;
+                    }
+                    // OP_DUP
+                    0x76 => stack.push(peek(stack, 0)?),
+                    // OP_NIP
+                    0x77 => {
+                        let length = require(stack, 2)?;
+                        stack.remove(length - 2);
+                    }
+                    // OP_OVER
+                    0x78 => stack.push(peek(stack, 1)?),
+                    // OP_PICK, OP_ROLL
+                    0x79 | 0x7a => {
+                        let depth = pop_num(stack)?;
+                        if depth < 0 || depth as usize >= stack.len() {
+                            return Err(ScriptError::InvalidStackOperation);
+                        }
+
+                        let index = stack.len() - 1 - depth as usize;
+                        let item = match opcode {
+                            0x79 => stack[index].clone(),
+                            _ => stack.remove(index),
+                        };
+                        stack.push(item);
+                    }
+                    // OP_ROT
+                    0x7b => {
+                        let length = require(stack, 3)?;
+                        stack[length - 3..].rotate_left(1);
+                    }
+                    // OP_SWAP
+                    0x7c => {
+                        let length = require(stack, 2)?;
+                        stack.swap(length - 2, length - 1);
+                    }
+                    // OP_TUCK
+                    0x7d => {
+                        let length = require(stack, 2)?;
+                        let top = stack[length - 1].clone();
+                        stack.insert(length - 2, top);
+                    }
+                    // OP_SIZE
+                    0x82 => {
+                        let size = peek(stack, 0)?.len();
+                        stack.push(encode_num(size as i64));
+                    }
+                    // OP_EQUAL, OP_EQUALVERIFY
+                    0x87 | 0x88 => {
+                        let (first, second) = (pop(stack)?, pop(stack)?);
+                        stack.push(encode_bool(first == second));
+                        if opcode == 0x88 {
+                            verify(stack, ScriptError::EqualVerify)?;
+                        }
+                    }
+                    // OP_1ADD, OP_1SUB, OP_NEGATE, OP_ABS, OP_NOT, OP_0NOTEQUAL
+                    0x8b | 0x8c | 0x8f..=0x92 => {
+                        let value = pop_num(stack)?;
+                        let result = match opcode {
+                            0x8b => value + 1,
+                            0x8c => value - 1,
+                            0x8f => -value,
+                            0x90 => value.abs(),
+                            0x91 => (value == 0) as i64,
+                            _ => (value != 0) as i64,
+                        };
+                        stack.push(encode_num(result));
+                    }
+                    // OP_ADD, OP_SUB, OP_BOOLAND, OP_BOOLOR, OP_NUMEQUAL, OP_NUMEQUALVERIFY,
+                    // OP_NUMNOTEQUAL, OP_LESSTHAN, OP_GREATERTHAN, OP_LESSTHANOREQUAL,
+                    // OP_GREATERTHANOREQUAL, OP_MIN, OP_MAX
+                    0x93 | 0x94 | 0x9a..=0xa4 => {
+                        let second = pop_num(stack)?;
+                        let first = pop_num(stack)?;
+                        let result = match opcode {
+                            0x93 => first + second,
+                            0x94 => first - second,
+                            0x9a => (first != 0 && second != 0) as i64,
+                            0x9b => (first != 0 || second != 0) as i64,
+                            0x9c | 0x9d => (first == second) as i64,
+                            0x9e => (first != second) as i64,
+                            0x9f => (first < second) as i64,
+                            0xa0 => (first > second) as i64,
+                            0xa1 => (first <= second) as i64,
+                            0xa2 => (first >= second) as i64,
+                            0xa3 => first.min(second),
+                            _ => first.max(second),
+                        };
+                        stack.push(encode_num(result));
+                        if opcode == 0x9d {
+                            verify(stack, ScriptError::NumEqualVerify)?;
+                        }
+                    }
+                    // OP_WITHIN
+                    0xa5 => {
+                        let max = pop_num(stack)?;
+                        let min = pop_num(stack)?;
+                        let value = pop_num(stack)?;
+                        stack.push(encode_bool(min <= value && value < max));
+                    }
+                    // OP_RIPEMD160, OP_SHA1, OP_SHA256, OP_HASH160, OP_HASH256
+                    0xa6..=0xaa => {
+                        let item = pop(stack)?;
+                        let digest = match opcode {
+                            0xa6 => ripemd160::Hash::hash(&item).to_byte_array().to_vec(),
+                            0xa7 => sha1::Hash::hash(&item).to_byte_array().to_vec(),
+                            0xa8 => sha256::Hash::hash(&item).to_byte_array().to_vec(),
+                            0xa9 => hash160::Hash::hash(&item).to_byte_array().to_vec(),
+                            _ => sha256d::Hash::hash(&item).to_byte_array().to_vec(),
+                        };
+                        stack.push(digest);
+                    }
+                    // OP_CODESEPARATOR
+                    0xab => code_start = position + 1,
+                    // OP_CHECKSIG, OP_CHECKSIGVERIFY
+                    0xac | 0xad => {
+                        let pubkey = pop(stack)?;
+                        let signature = pop(stack)?;
+                        let script_code = script_code(&cmds[code_start..], &[&signature]);
+
+                        let valid = !signature.is_empty()
+                            && checker.check_sig(&signature, &pubkey, &script_code);
+                        stack.push(encode_bool(valid));
+                        if opcode == 0xad {
+                            verify(stack, ScriptError::CheckSigVerify)?;
+                        }
+                    }
+                    // OP_CHECKMULTISIG, OP_CHECKMULTISIGVERIFY
+                    0xae | 0xaf => {
+                        let key_count = pop_num(stack)?;
+                        if key_count < 0 || key_count as usize > MAX_PUBKEYS_PER_MULTISIG {
+                            return Err(ScriptError::PubkeyCount);
+                        }
+                        op_count += key_count as usize;
+                        if op_count > MAX_OPS_PER_SCRIPT {
+                            return Err(ScriptError::OpCount);
+                        }
+                        let pubkeys = pop_many(stack, key_count as usize)?;
+
+                        let signature_count = pop_num(stack)?;
+                        if signature_count < 0 || signature_count > key_count {
+                            return Err(ScriptError::SigCount);
+                        }
+                        let signatures = pop_many(stack, signature_count as usize)?;
+
+                        // The off-by-one bug of the original implementation pops an extra element.
+                        pop(stack)?;
+
+                        let signature_refs: Vec<&[u8]> = signatures
+                            .iter()
+                            .map(|signature| signature.as_slice())
+                            .collect();
+                        let script_code = script_code(&cmds[code_start..], &signature_refs);
+
+                        let valid = check_multisig(&signatures, &pubkeys, &script_code, checker);
+                        stack.push(encode_bool(valid));
+                        if opcode == 0xaf {
+                            verify(stack, ScriptError::CheckMultiSigVerify)?;
+                        }
+                    }
+                    _ => return Err(ScriptError::BadOpcode),
+                }
+            }
+        }
+
+        if stack.len() + altstack.len() > MAX_STACK_SIZE {
+            return Err(ScriptError::StackSize);
+        }
+    }
+
+    if !conditions.is_empty() {
+        return Err(ScriptError::UnbalancedConditional);
+    }
+
+    Ok(())
+}
+
+/// Matches the signatures against the keys in order, each key being tried at most once.
+fn check_multisig(
+    signatures: &[Vec<u8>],
+    pubkeys: &[Vec<u8>],
+    script_code: &Script,
+    checker: &dyn SignatureChecker,
+) -> bool {
+    let mut signatures = signatures.iter().peekable();
+    let mut pubkeys = pubkeys.iter();
+
+    while let Some(signature) = signatures.peek() {
+        let pubkey = match pubkeys.next() {
+            Some(pubkey) => pubkey,
+            None => return false,
+        };
+
+        if !signature.is_empty() && checker.check_sig(signature, pubkey, script_code) {
+            signatures.next();
+        }
+    }
+
+    true
+}
+
+/// Returns the commands the signatures commit to, without the signatures themselves.
+fn script_code(cmds: &[ScriptCommand], signatures: &[&[u8]]) -> Script {
+    let cmds = cmds
+        .iter()
+        .filter(|cmd| match cmd {
+            ScriptCommand::Push(data) => !signatures.contains(&data.as_slice()),
+            ScriptCommand::Op(_) => true,
+        })
+        .cloned()
+        .collect();
+
+    Script::new(Some(cmds))
+}
+
+/// Returns the length of the stack if it holds at least `count` items.
+fn require(stack: &[Vec<u8>], count: usize) -> Result<usize, ScriptError> {
+    if stack.len() < count {
+        return Err(ScriptError::InvalidStackOperation);
+    }
+
+    Ok(stack.len())
+}
+
+fn pop(stack: &mut Vec<Vec<u8>>) -> Result<Vec<u8>, ScriptError> {
+    stack.pop().ok_or(ScriptError::InvalidStackOperation)
+}
+
+/// Pops `count` items, the top of the stack first.
+fn pop_many(stack: &mut Vec<Vec<u8>>, count: usize) -> Result<Vec<Vec<u8>>, ScriptError> {
+    (0..count).map(|_| pop(stack)).collect()
+}
+
+fn pop_num(stack: &mut Vec<Vec<u8>>) -> Result<i64, ScriptError> {
+    decode_num(&pop(stack)?)
+}
+
+/// Returns a copy of the item `depth` positions below the top of the stack.
+fn peek(stack: &[Vec<u8>], depth: usize) -> Result<Vec<u8>, ScriptError> {
+    let length = require(stack, depth + 1)?;
+
+    Ok(stack[length - 1 - depth].clone())
+}
+
+/// Pops the top of the stack, failing with the given error if it is false.
+fn verify(stack: &mut Vec<Vec<u8>>, error: ScriptError) -> Result<(), ScriptError> {
+    if cast_to_bool(&pop(stack)?) {
+        return Ok(());
+    }
+
+    Err(error)
+}
+
+/// Returns whether the item is true: any non-zero value other than negative zero.
+pub fn cast_to_bool(item: &[u8]) -> bool {
+    match item.split_last() {
+        Some((last, rest)) => rest.iter().any(|byte| *byte != 0) || (*last & 0x7f) != 0,
+        None => false,
+    }
+}
+
+fn encode_bool(value: bool) -> Vec<u8> {
+    encode_num(value as i64)
+}
+
+/// Encodes a number in the little-endian sign-magnitude format of the stack, with zero encoded as
+/// an empty item.
+pub fn encode_num(value: i64) -> Vec<u8> {
+    let mut magnitude = value.unsigned_abs();
+    let mut bytes = vec![];
+
+    while magnitude > 0 {
+        bytes.push((magnitude & 0xff) as u8);
+        magnitude >>= 8;
+    }
+
+    if let Some(last) = bytes.last_mut() {
+        if *last & 0x80 != 0 {
+            bytes.push(if value < 0 { 0x80 } else { 0x00 });
+        } else if value < 0 {
+            *last |= 0x80;
+        }
+    }
+
+    bytes
+}
+
+/// Decodes a number of the stack, which may be at most `MAX_SCRIPT_NUM_SIZE` bytes long.
+///
+/// # Errors
+///
+/// Returns `ScriptError::NumOverflow` if the item is too long to be a number.
+pub fn decode_num(item: &[u8]) -> Result<i64, ScriptError> {
+    decode_sized_num(item, MAX_SCRIPT_NUM_SIZE)
+}
+
+/// Decodes a number of the stack which may be at most `max_size` bytes long, as the lock times of
+/// `OP_CHECKLOCKTIMEVERIFY` and `OP_CHECKSEQUENCEVERIFY` may be up to five bytes long.
+fn decode_sized_num(item: &[u8], max_size: usize) -> Result<i64, ScriptError> {
+    if item.len() > max_size {
+        return Err(ScriptError::NumOverflow);
+    }
+
+    let (last, _) = match item.split_last() {
+        Some(split) => split,
+        None => return Ok(0),
+    };
+
+    let magnitude = item.iter().enumerate().fold(0, |value, (index, byte)| {
+        let byte = if index == item.len() - 1 {
+            byte & 0x7f
+        } else {
+            *byte
+        };
+        value | (byte as i64) << (8 * index)
+    });
+
+    if last & 0x80 != 0 {
+        return Ok(-magnitude);
+    }
+    Ok(magnitude)
+}
+
+#[cfg(test)]
+mod script_interpreter_test {
+    use super::*;
+    use crate::block_mod::{tx_in::TxIn, tx_out::TxOut};
+    use secp256k1::SecretKey;
+
+    /// Accepts the signatures whose first byte matches the first byte of the key.
+    struct MockChecker;
+
+    impl SignatureChecker for MockChecker {
+        fn check_sig(&self, signature: &[u8], pubkey: &[u8], _: &Script) -> bool {
+            signature.first() == pubkey.first()
+        }
+    }
+
+    fn eval_asm(script_sig: &str, pk_script: &str) -> Result<(), ScriptError> {
+        let script_sig = Script::from_asm(script_sig).map_err(|_| ScriptError::BadOpcode)?;
+        let pk_script = Script::from_asm(pk_script).map_err(|_| ScriptError::BadOpcode)?;
+
+        eval(&script_sig, &pk_script, &MockChecker)
+    }
+
+    #[test]
+    fn test_p2pkh_spend_is_verified_against_the_transaction() -> Result<(), ScriptError> {
+        let secp = Secp256k1::new();
+        let private_key =
+            SecretKey::from_slice(&[0x11; 32]).map_err(|_| ScriptError::CheckSigVerify)?;
+        let pubkey = PublicKey::from_secret_key(&secp, &private_key)
+            .serialize()
+            .to_vec();
+        let pubkey_hash = hash160::Hash::hash(&pubkey).to_byte_array().to_vec();
+        let pk_script = Script::new(Some(vec![
+            ScriptCommand::Op(0x76),
+            ScriptCommand::Op(0xa9),
+            ScriptCommand::Push(pubkey_hash),
+            ScriptCommand::Op(0x88),
+            ScriptCommand::Op(0xac),
+        ]));
+
+        let mut transaction = Transaction::new(
+            1,
+            vec![TxIn::new(vec![0xab; 32], 0, vec![], 0xffffffff)],
+            vec![TxOut::new(5000, vec![0x51])],
+            0,
+            false,
+        );
+        let signature_hash = transaction.p2pkh_signature_hash(0, &pk_script.to_bytes());
+        let message = Message::from_hashed_data::<sha256::Hash>(&signature_hash);
+        let mut signature = secp
+            .sign_ecdsa(&message, &private_key)
+            .serialize_der()
+            .to_vec();
+        signature.push(SIGHASH_ALL);
+        let script_sig = Script::new(Some(vec![
+            ScriptCommand::Push(signature),
+            ScriptCommand::Push(pubkey),
+        ]));
+        transaction.set_signature(0, script_sig.to_bytes());
+
+        let checker = TransactionSignatureChecker::new(&transaction, 0);
+        eval(&script_sig, &pk_script, &checker)?;
+
+        let tampered = Transaction::new(
+            1,
+            transaction.get_tx_in_list().clone(),
+            vec![TxOut::new(6000, vec![0x51])],
+            0,
+            false,
+        );
+        let checker = TransactionSignatureChecker::new(&tampered, 0);
+        assert_eq!(
+            eval(&script_sig, &pk_script, &checker),
+            Err(ScriptError::EvalFalse)
+        );
+
+        let other_script = Script::new(Some(vec![
+            ScriptCommand::Op(0x76),
+            ScriptCommand::Op(0xa9),
+            ScriptCommand::Push(vec![0x00; 20]),
+            ScriptCommand::Op(0x88),
+            ScriptCommand::Op(0xac),
+        ]));
+        assert_eq!(
+            eval(&script_sig, &other_script, &checker),
+            Err(ScriptError::EqualVerify)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_arithmetic_and_flow_control() {
+        assert_eq!(eval_asm("OP_2 OP_3", "OP_ADD OP_5 OP_EQUAL"), Ok(()));
+        assert_eq!(eval_asm("OP_0", "OP_IF OP_0 OP_ELSE OP_1 OP_ENDIF"), Ok(()));
+        assert_eq!(
+            eval_asm(
+                "OP_1",
+                "OP_NOTIF OP_RETURN OP_ENDIF OP_7 OP_1NEGATE OP_SUB OP_8 OP_NUMEQUAL"
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            eval_asm(
+                "OP_1 OP_2 OP_3",
+                "OP_ROT OP_1 OP_EQUALVERIFY OP_DEPTH OP_2 OP_EQUAL"
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            eval_asm("OP_1", "OP_IF OP_1"),
+            Err(ScriptError::UnbalancedConditional)
+        );
+        assert_eq!(eval_asm("OP_1", "OP_RETURN"), Err(ScriptError::OpReturn));
+        assert_eq!(eval_asm("OP_1", "OP_DROP"), Err(ScriptError::EvalFalse));
+        assert_eq!(
+            eval_asm("", "OP_DUP"),
+            Err(ScriptError::InvalidStackOperation)
+        );
+        assert_eq!(
+            eval_asm("OP_1 OP_DUP", "OP_1"),
+            Err(ScriptError::SigPushOnly)
+        );
+        assert_eq!(
+            eval_asm("OP_0", "OP_IF 0x95 OP_ENDIF OP_1"),
+            Err(ScriptError::DisabledOpcode)
+        );
+    }
+
+    #[test]
+    fn test_single_byte_pushes_are_data() {
+        assert_eq!(eval_asm("76", "76 OP_EQUAL"), Ok(()));
+        assert_eq!(eval_asm("01", "OP_1 OP_EQUAL"), Ok(()));
+        assert_eq!(eval_asm("76", "OP_DUP OP_EQUAL"), Ok(()));
+        assert_eq!(eval_asm("76 76", "OP_EQUAL"), Ok(()));
+    }
+
+    #[test]
+    fn test_checkmultisig_matches_signatures_in_order() {
+        let multisig = "OP_2 020202 030303 040404 OP_3 OP_CHECKMULTISIG";
+
+        assert_eq!(eval_asm("OP_0 0201 0401", multisig), Ok(()));
+        assert_eq!(
+            eval_asm("OP_0 0401 0201", multisig),
+            Err(ScriptError::EvalFalse)
+        );
+        assert_eq!(
+            eval_asm("OP_0 0201 0501", multisig),
+            Err(ScriptError::EvalFalse)
+        );
+        assert_eq!(
+            eval_asm("0201 0401", multisig),
+            Err(ScriptError::InvalidStackOperation)
+        );
+    }
+
+    #[test]
+    fn test_numbers_round_trip() -> Result<(), ScriptError> {
+        for value in [0, 1, -1, 127, 128, -128, 255, 256, -32768, 0x7fffffff] {
+            assert_eq!(decode_num(&encode_num(value))?, value);
+        }
+        assert_eq!(encode_num(-1), vec![0x81]);
+        assert_eq!(encode_num(128), vec![0x80, 0x00]);
+        assert!(!cast_to_bool(&[0x00, 0x80]));
+        assert_eq!(decode_num(&[1, 2, 3, 4, 5]), Err(ScriptError::NumOverflow));
+        Ok(())
+    }
+
+    fn lock_time_tx(version: i32, sequence: u32, lock_time: u32) -> Transaction {
+        Transaction::new(
+            version,
+            vec![TxIn::new(vec![0xab; 32], 0, vec![], sequence)],
+            vec![TxOut::new(5000, vec![0x51])],
+            lock_time,
+            false,
+        )
+    }
+
+    fn eval_lock_time(pk_script: &str, transaction: &Transaction) -> Result<(), ScriptError> {
+        let pk_script = Script::from_asm(pk_script).map_err(|_| ScriptError::BadOpcode)?;
+        let checker = TransactionSignatureChecker::new(transaction, 0);
+
+        eval(&Script::new(None), &pk_script, &checker)
+    }
+
+    #[test]
+    fn test_checklocktimeverify_compares_the_lock_time_of_the_transaction() {
+        // Locked until height 500
+        let cltv = "f401 OP_CHECKLOCKTIMEVERIFY OP_DROP OP_1";
+
+        assert_eq!(eval_lock_time(cltv, &lock_time_tx(1, 0, 500)), Ok(()));
+        assert_eq!(
+            eval_lock_time(cltv, &lock_time_tx(1, 0, 499)),
+            Err(ScriptError::UnsatisfiedLockTime)
+        );
+        assert_eq!(
+            eval_lock_time(cltv, &lock_time_tx(1, 0, LOCKTIME_THRESHOLD)),
+            Err(ScriptError::UnsatisfiedLockTime)
+        );
+        assert_eq!(
+            eval_lock_time(cltv, &lock_time_tx(1, SEQUENCE_FINAL, 500)),
+            Err(ScriptError::UnsatisfiedLockTime)
+        );
+        assert_eq!(
+            eval_asm("", "OP_CHECKLOCKTIMEVERIFY"),
+            Err(ScriptError::InvalidStackOperation)
+        );
+        assert_eq!(
+            eval_asm("", "OP_1NEGATE OP_CHECKLOCKTIMEVERIFY"),
+            Err(ScriptError::NegativeLockTime)
+        );
+    }
+
+    #[test]
+    fn test_checksequenceverify_compares_the_sequence_of_the_input() {
+        // Locked for 300 blocks
+        let csv = "2c01 OP_CHECKSEQUENCEVERIFY OP_DROP OP_1";
+
+        assert_eq!(eval_lock_time(csv, &lock_time_tx(2, 300, 0)), Ok(()));
+        assert_eq!(
+            eval_lock_time(csv, &lock_time_tx(2, 299, 0)),
+            Err(ScriptError::UnsatisfiedLockTime)
+        );
+        assert_eq!(
+            eval_lock_time(csv, &lock_time_tx(1, 300, 0)),
+            Err(ScriptError::UnsatisfiedLockTime)
+        );
+        assert_eq!(
+            eval_lock_time(csv, &lock_time_tx(2, SEQUENCE_LOCKTIME_TYPE_FLAG | 300, 0)),
+            Err(ScriptError::UnsatisfiedLockTime)
+        );
+        // A sequence with the disable flag set, five bytes long, makes it a NOP
+        assert_eq!(
+            eval_asm("", "0000008000 OP_CHECKSEQUENCEVERIFY OP_DROP OP_1"),
+            Ok(())
+        );
+    }
+
+    fn p2sh_pk_script(redeem_script: &[u8]) -> Vec<u8> {
+        let script_hash = hash160::Hash::hash(redeem_script).to_byte_array().to_vec();
+
+        Script::new(Some(vec![
+            ScriptCommand::Op(0xa9),
+            ScriptCommand::Push(script_hash),
+            ScriptCommand::Op(0x87),
+        ]))
+        .to_bytes()
+    }
+
+    fn verify_p2sh_spend(
+        script_sig: Vec<ScriptCommand>,
+        pk_script: Vec<u8>,
+    ) -> Result<(), ScriptError> {
+        let mut transaction = lock_time_tx(1, SEQUENCE_FINAL, 0);
+        transaction.set_signature(0, Script::new(Some(script_sig)).to_bytes());
+
+        verify_input(&transaction, 0, &[TxOut::new(5000, pk_script)])
+    }
+
+    #[test]
+    fn test_p2sh_spends_run_the_redeem_script() -> Result<(), ScriptError> {
+        let redeem_script = Script::from_asm("OP_ADD OP_5 OP_EQUAL")
+            .map_err(|_| ScriptError::BadOpcode)?
+            .to_bytes();
+        let pk_script = p2sh_pk_script(&redeem_script);
+        assert!(is_p2sh(&pk_script));
+
+        let spend = |first: u8, second: u8| {
+            vec![
+                ScriptCommand::Op(first),
+                ScriptCommand::Op(second),
+                ScriptCommand::Push(redeem_script.clone()),
+            ]
+        };
+        assert_eq!(
+            verify_p2sh_spend(spend(0x52, 0x53), pk_script.clone()),
+            Ok(())
+        );
+        assert_eq!(
+            verify_p2sh_spend(spend(0x52, 0x52), pk_script.clone()),
+            Err(ScriptError::EvalFalse)
+        );
+        assert_eq!(
+            verify_p2sh_spend(spend(0x52, 0x53), p2sh_pk_script(&[0x51, 0x51])),
+            Err(ScriptError::EvalFalse)
+        );
+
+        // A wrapped witness program must be the only push of the unlocking script
+        let witness_program = [vec![0x00, 0x14], vec![0x07; 20]].concat();
+        assert_eq!(
+            verify_p2sh_spend(
+                vec![
+                    ScriptCommand::Op(0x51),
+                    ScriptCommand::Push(witness_program.clone())
+                ],
+                p2sh_pk_script(&witness_program)
+            ),
+            Err(ScriptError::WitnessMalleatedP2SH)
+        );
+        assert_eq!(
+            verify_p2sh_spend(
+                vec![ScriptCommand::Push(witness_program.clone())],
+                p2sh_pk_script(&witness_program)
+            ),
+            Err(ScriptError::WitnessProgramMismatch)
+        );
+        Ok(())
+    }
+}
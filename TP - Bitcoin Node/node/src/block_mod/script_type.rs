@@ -0,0 +1,37 @@
+/// Represents the standard templates an output script can follow, which tell how the output is
+/// spent and which address, if any, it pays to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptType {
+    /// Pays to a public key: `<pubkey> OP_CHECKSIG`.
+    P2pk,
+    /// Pays to the hash of a public key: `OP_DUP OP_HASH160 <hash> OP_EQUALVERIFY OP_CHECKSIG`.
+    P2pkh,
+    /// Pays to the hash of a redeem script: `OP_HASH160 <hash> OP_EQUAL` (BIP16).
+    P2sh,
+    /// A version 0 witness program holding the hash of a public key (BIP141).
+    P2wpkh,
+    /// A version 0 witness program holding the hash of a witness script (BIP141).
+    P2wsh,
+    /// A version 1 witness program holding a taproot output key (BIP341).
+    P2tr,
+    /// A provably unspendable output carrying data: `OP_RETURN` followed by data pushes.
+    OpReturn,
+    /// Any other script.
+    NonStandard,
+}
+
+impl ScriptType {
+    /// Returns the name of the type, as reported by Bitcoin Core.
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            ScriptType::P2pk => "pubkey",
+            ScriptType::P2pkh => "pubkeyhash",
+            ScriptType::P2sh => "scripthash",
+            ScriptType::P2wpkh => "witness_v0_keyhash",
+            ScriptType::P2wsh => "witness_v0_scripthash",
+            ScriptType::P2tr => "witness_v1_taproot",
+            ScriptType::OpReturn => "nulldata",
+            ScriptType::NonStandard => "nonstandard",
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use super::block_constants::{MAX_STANDARD_TX_WEIGHT, WITNESS_SCALE_FACTOR};
+
+/// Represents the sizes of a serialized transaction, telling apart the bytes of its witness
+/// (BIP141).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeBreakdown {
+    base_size: usize,
+    total_size: usize,
+}
+
+impl SizeBreakdown {
+    /// Creates a new size breakdown.
+    ///
+    /// # Arguments
+    ///
+    /// * `base_size` - The size of the transaction serialized without its witness.
+    /// * `total_size` - The size of the transaction serialized with its witness.
+    pub fn new(base_size: usize, total_size: usize) -> SizeBreakdown {
+        SizeBreakdown {
+            base_size,
+            total_size,
+        }
+    }
+
+    pub fn get_base_size(&self) -> usize {
+        self.base_size
+    }
+
+    pub fn get_total_size(&self) -> usize {
+        self.total_size
+    }
+
+    /// Returns the size of the witness, its marker and flag included.
+    pub fn get_witness_size(&self) -> usize {
+        self.total_size - self.base_size
+    }
+
+    /// Returns the weight of the transaction, where each byte outside the witness weighs four
+    /// units and each byte of the witness weighs one.
+    pub fn get_weight(&self) -> usize {
+        self.base_size * (WITNESS_SCALE_FACTOR - 1) + self.total_size
+    }
+
+    /// Returns the virtual size of the transaction, its weight divided by four and rounded up.
+    pub fn get_vsize(&self) -> usize {
+        self.get_weight().div_ceil(WITNESS_SCALE_FACTOR)
+    }
+
+    /// Returns whether the weight of the transaction is within the standard policy of the node.
+    pub fn is_standard(&self) -> bool {
+        self.get_weight() <= MAX_STANDARD_TX_WEIGHT
+    }
+}
+
+impl std::fmt::Display for SizeBreakdown {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} bytes ({} base, {} witness), {} weight units, {} vbytes",
+            self.total_size,
+            self.base_size,
+            self.get_witness_size(),
+            self.get_weight(),
+            self.get_vsize()
+        )
+    }
+}
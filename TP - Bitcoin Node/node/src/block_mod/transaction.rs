@@ -1,11 +1,15 @@
+use crate::block_mod::block_constants::{LOCKTIME_THRESHOLD, MAX_MONEY, SEQUENCE_FINAL};
+use crate::block_mod::size_breakdown::SizeBreakdown;
 use crate::block_mod::tx_in::TxIn;
 use crate::block_mod::tx_out::TxOut;
 use crate::block_mod::witness::Witness;
 use crate::messages::compact_size::CompactSizeUInt;
+use crate::messages::message_constants::{MAX_TX_IN_COUNT, MAX_TX_OUT_COUNT};
 use crate::messages::message_error::MessageError;
 use crate::messages::read_from_bytes::{
     read_i32_from_bytes, read_u32_from_bytes, read_u8_from_bytes,
 };
+use crate::messages::serialization::{items_to_bytes_into, read_items, read_vec};
 use bitcoin_hashes::Hash;
 use bitcoin_hashes::{sha256, sha256d};
 use std::io::Read;
@@ -58,37 +62,23 @@ impl Transaction {
     /// A `Result` containing the parsed `Transaction` instance or a `MessageError` if parsing fails.
     pub fn from_bytes(stream: &mut dyn Read) -> Result<Transaction, MessageError> {
         let version = read_i32_from_bytes(stream, true)?;
-        let mut tx_in_count = CompactSizeUInt::from_bytes(stream)?;
+        let mut tx_in_count = CompactSizeUInt::from_bytes_bounded(stream, MAX_TX_IN_COUNT)?;
         let mut flag = 0;
 
         let is_segwit = tx_in_count.value() == 0;
 
         if is_segwit {
             flag = read_u8_from_bytes(stream)?;
-            tx_in_count = CompactSizeUInt::from_bytes(stream)?;
+            tx_in_count = CompactSizeUInt::from_bytes_bounded(stream, MAX_TX_IN_COUNT)?;
         }
 
-        let mut tx_in_list: Vec<TxIn> = Vec::new();
-
-        for _i in 0..tx_in_count.value() {
-            tx_in_list.push(TxIn::from_bytes(stream)?);
-        }
-
-        let tx_out_count = CompactSizeUInt::from_bytes(stream)?;
-        let mut tx_out_list: Vec<TxOut> = Vec::new();
-
-        for _i in 0..tx_out_count.value() {
-            tx_out_list.push(TxOut::from_bytes(stream)?);
-        }
-        let mut witness = vec![];
-
-        if is_segwit {
-            for _ in 0..tx_in_count.value() {
-                witness.push(Witness::from_bytes(stream)?);
-            }
+        let tx_in_list = read_items::<TxIn>(stream, &tx_in_count)?;
+        let (tx_out_count, tx_out_list) = read_vec::<TxOut>(stream, MAX_TX_OUT_COUNT)?;
+        let witness = if is_segwit {
+            read_items::<Witness>(stream, &tx_in_count)?
         } else {
-            witness = vec![];
-        }
+            vec![]
+        };
 
         let lock_time = read_u32_from_bytes(stream, true)?;
 
@@ -109,34 +99,36 @@ impl Transaction {
     /// # Returns
     /// A vector of bytes representing the `Transaction` instance.
     pub fn to_bytes(&self, segwit: bool) -> Vec<u8> {
-        let mut buff = self.version.to_le_bytes().to_vec();
+        let mut buff = Vec::new();
+
+        self.to_bytes_into(segwit, &mut buff);
+        buff
+    }
+
+    /// Appends the byte representation of the `Transaction` to the given buffer, so the
+    /// transactions of a block can be serialized without allocating a vector for each of them.
+    ///
+    /// # Arguments
+    /// * `segwit` - Whether the marker, flag and witness data of segwit transactions are included.
+    /// * `buff` - The buffer the bytes are appended to.
+    pub fn to_bytes_into(&self, segwit: bool, buff: &mut Vec<u8>) {
+        buff.extend(self.version.to_le_bytes());
 
         if segwit && self.is_segwit() {
             buff.push(0x00);
             buff.push(self.flag);
         }
 
-        buff.extend(self.tx_in_count.to_bytes());
-
-        for txin in self.tx_in_list.iter() {
-            buff.extend(&txin.to_bytes());
-        }
-
-        buff.extend(self.tx_out_count.to_bytes());
-
-        for txout in self.tx_out_list.iter() {
-            buff.extend(&txout.to_bytes());
-        }
+        self.tx_in_count.to_bytes_into(buff);
+        items_to_bytes_into(&self.tx_in_list, buff);
+        self.tx_out_count.to_bytes_into(buff);
+        items_to_bytes_into(&self.tx_out_list, buff);
 
         if segwit && self.is_segwit() {
-            for witness in self.witness.clone() {
-                buff.extend(witness.to_bytes());
-            }
+            items_to_bytes_into(&self.witness, buff);
         }
 
         buff.extend(self.lock_time.to_le_bytes());
-
-        buff
     }
 
     ///Calculates the transaction ID by hashing the serialized bytes of the `BlockHeader`.
@@ -149,11 +141,123 @@ impl Transaction {
             .to_vec()
     }
 
+    /// Returns the witness transaction ID (BIP141), which commits to the witness data as well. For
+    /// transactions without witness data, it is the same as the transaction ID.
+    pub fn wtxid(&self) -> Vec<u8> {
+        self.get_id(true)
+    }
+
+    /// Breaks the size of the transaction down into its base and witness bytes, from which its
+    /// weight and virtual size are derived.
+    pub fn size_breakdown(&self) -> SizeBreakdown {
+        let mut buffer = Vec::new();
+
+        self.to_bytes_into(false, &mut buffer);
+        let base_size = buffer.len();
+
+        buffer.clear();
+        self.to_bytes_into(true, &mut buffer);
+        SizeBreakdown::new(base_size, buffer.len())
+    }
+
+    /// Returns the weight of the transaction (BIP141), counting its witness bytes once and the
+    /// rest of its bytes four times.
+    pub fn weight(&self) -> usize {
+        self.size_breakdown().get_weight()
+    }
+
+    /// Returns the virtual size of the transaction, its weight divided by four and rounded up.
+    pub fn vsize(&self) -> usize {
+        self.size_breakdown().get_vsize()
+    }
+
+    /// Computes the fee paid by the transaction, as the value of its inputs minus the value of its
+    /// outputs.
+    ///
+    /// # Arguments
+    ///
+    /// * `input_values` - The value of the output spent by each input, in the order of the inputs.
+    ///
+    /// # Returns
+    ///
+    /// The fee in satoshis, or `None` if there is not a value for every input, the inputs or the
+    /// outputs are worth a value outside `0..=MAX_MONEY`, or the outputs are worth more than the
+    /// inputs.
+    pub fn fee(&self, input_values: &[i64]) -> Option<u64> {
+        if input_values.len() != self.tx_in_list.len() {
+            return None;
+        }
+
+        let input_value = input_values
+            .iter()
+            .try_fold(0, |total, value| add_money(total, *value))?;
+        let output_value = self.output_value()?;
+
+        u64::try_from(input_value - output_value).ok()
+    }
+
+    /// Computes the value of the outputs of the transaction.
+    ///
+    /// # Returns
+    ///
+    /// The value in satoshis, or `None` if the value of an output, or their total, is negative or
+    /// exceeds `MAX_MONEY`.
+    pub fn output_value(&self) -> Option<i64> {
+        self.tx_out_list
+            .iter()
+            .try_fold(0, |total, tx_out| add_money(total, tx_out.get_value()))
+    }
+
+    /// Computes the fee rate of the transaction, in satoshis per kilobyte of virtual size, so the
+    /// witness bytes are discounted.
+    ///
+    /// # Arguments
+    ///
+    /// * `input_values` - The value of the output spent by each input, in the order of the inputs.
+    ///
+    /// # Returns
+    ///
+    /// The fee rate, or `None` if the fee cannot be computed.
+    pub fn fee_rate(&self, input_values: &[i64]) -> Option<u64> {
+        Some(self.fee(input_values)? * 1000 / self.vsize() as u64)
+    }
+
     /// Returns a reference to the list of transaction inputs.
     pub fn get_tx_in_list(&self) -> &Vec<TxIn> {
         &self.tx_in_list
     }
 
+    pub fn get_version(&self) -> i32 {
+        self.version
+    }
+
+    /// Returns whether the lock time of the transaction allows it to be included in a block.
+    ///
+    /// Lock times below `LOCKTIME_THRESHOLD` are heights, and the rest are timestamps compared
+    /// against the median time past. Transactions whose inputs all have a final sequence number
+    /// are final regardless of their lock time.
+    ///
+    /// # Arguments
+    ///
+    /// * `height` - The height of the block the transaction would be included in.
+    /// * `median_time_past` - The median time past of the parent of that block.
+    pub fn is_final(&self, height: u32, median_time_past: u32) -> bool {
+        if self.lock_time == 0 {
+            return true;
+        }
+
+        let limit = if self.lock_time < LOCKTIME_THRESHOLD {
+            height
+        } else {
+            median_time_past
+        };
+        self.lock_time < limit
+            || self
+                .tx_in_list
+                .iter()
+                .all(|tx_in| tx_in.get_sequence() == SEQUENCE_FINAL)
+    }
+
     /// Returns a reference to the list of transaction outputs.
     pub fn get_tx_out_list(&self) -> &Vec<TxOut> {
         &self.tx_out_list
@@ -186,37 +290,26 @@ impl Transaction {
     /// # Returns
     ///
     /// A vector of bytes representing the computed signature hash.
-    pub fn p2pkh_signature_hash(&mut self, index: usize, pk_script: &[u8]) -> Vec<u8> {
-        let mut buffer = self.version.to_le_bytes().to_vec();
-        let mut aux_txin: TxIn;
+    pub fn p2pkh_signature_hash(&self, index: usize, pk_script: &[u8]) -> Vec<u8> {
+        let mut buffer = Vec::with_capacity(
+            pk_script.len() + 49 * self.tx_in_list.len() + 43 * self.tx_out_list.len() + 26,
+        );
 
-        buffer.extend(self.tx_in_count.to_bytes());
+        buffer.extend(self.version.to_le_bytes());
+        self.tx_in_count.to_bytes_into(&mut buffer);
 
         for (i, txin) in self.tx_in_list.iter().enumerate() {
-            if i == index {
-                aux_txin = TxIn::new(
-                    txin.get_prev_output().get_tx_id().clone(),
-                    txin.get_prev_output().get_index(),
-                    pk_script.to_vec(),
-                    txin.get_sequence(),
-                );
-            } else {
-                aux_txin = TxIn::new(
-                    txin.get_prev_output().get_tx_id().clone(),
-                    txin.get_prev_output().get_index(),
-                    vec![],
-                    txin.get_sequence(),
-                );
-            }
-            buffer.extend(&aux_txin.to_bytes());
-        }
-
-        buffer.extend(self.tx_out_count.to_bytes());
+            let script: &[u8] = if i == index { pk_script } else { &[] };
 
-        for txout in self.tx_out_list.iter() {
-            buffer.extend(txout.to_bytes());
+            txin.get_prev_output().to_bytes_into(&mut buffer);
+            CompactSizeUInt::from_number(script.len() as u64).to_bytes_into(&mut buffer);
+            buffer.extend(script);
+            buffer.extend(txin.get_sequence().to_le_bytes());
         }
 
+        self.tx_out_count.to_bytes_into(&mut buffer);
+        items_to_bytes_into(&self.tx_out_list, &mut buffer);
+
         buffer.extend(self.lock_time.to_le_bytes());
         buffer.extend((1_u32).to_le_bytes());
 
@@ -246,29 +339,31 @@ impl Transaction {
     ) -> Vec<u8> {
         let txins = self.get_tx_in_list();
 
-        let mut signature = self.version.to_le_bytes().to_vec();
-        let mut previous_outpoints = vec![];
-        let mut previous_sequences = vec![];
+        let mut signature = Vec::with_capacity(182);
+        // Reused for the outpoints, the sequences and the outputs, which are hashed on their own
+        let mut scratch = Vec::with_capacity(36 * txins.len());
+
+        signature.extend(self.version.to_le_bytes());
 
         for txin in txins.iter() {
-            previous_outpoints.extend(txin.get_prev_output().to_bytes());
-            previous_sequences.extend(txin.get_sequence().to_le_bytes());
+            txin.get_prev_output().to_bytes_into(&mut scratch);
         }
+        signature.extend(sha256d::Hash::hash(&scratch).to_byte_array());
 
-        signature.extend(sha256d::Hash::hash(&previous_outpoints).to_byte_array());
-        signature.extend(sha256d::Hash::hash(&previous_sequences).to_byte_array());
-        signature.extend(txins[index].get_prev_output().to_bytes());
+        scratch.clear();
+        for txin in txins.iter() {
+            scratch.extend(txin.get_sequence().to_le_bytes());
+        }
+        signature.extend(sha256d::Hash::hash(&scratch).to_byte_array());
+
+        txins[index].get_prev_output().to_bytes_into(&mut signature);
         signature.extend(get_script_code(&pk_script[2..]));
         signature.extend(amount_list[index].to_le_bytes());
-        signature.extend([0xff, 0xff, 0xff, 0xff]);
-
-        let mut outputs = vec![];
-
-        for txout in self.get_tx_out_list().iter() {
-            outputs.extend(txout.to_bytes());
-        }
+        signature.extend(txins[index].get_sequence().to_le_bytes());
 
-        signature.extend(sha256d::Hash::hash(&outputs).to_byte_array());
+        scratch.clear();
+        items_to_bytes_into(self.get_tx_out_list(), &mut scratch);
+        signature.extend(sha256d::Hash::hash(&scratch).to_byte_array());
         signature.extend(self.lock_time.to_le_bytes());
         signature.extend(1_u32.to_le_bytes());
 
@@ -290,6 +385,18 @@ impl Transaction {
     }
 }
 
+/// Adds a value to a total of satoshis, as long as both the value and the resulting total are in
+/// the range `0..=MAX_MONEY`, so a negative or huge value cannot overflow or lower the total.
+pub fn add_money(total: i64, value: i64) -> Option<i64> {
+    if !(0..=MAX_MONEY).contains(&value) {
+        return None;
+    }
+
+    total
+        .checked_add(value)
+        .filter(|total| (0..=MAX_MONEY).contains(total))
+}
+
 fn get_script_code(pubkey_hash: &[u8]) -> Vec<u8> {
     let mut buffer = vec![0x19, 0x76, 0xa9, 0x14];
     buffer.extend(pubkey_hash);
@@ -329,8 +436,12 @@ impl std::fmt::Display for Transaction {
 
 #[cfg(test)]
 mod block_test {
-    use crate::block_mod::transaction::Transaction;
+    use crate::block_mod::block_constants::MAX_MONEY;
+    use crate::block_mod::transaction::{add_money, Transaction};
+    use crate::block_mod::tx_in::TxIn;
+    use crate::block_mod::tx_out::TxOut;
     use crate::messages::message_error::MessageError;
+    use bitcoin_hashes::{sha256, Hash};
     use hex::decode;
 
     #[test]
@@ -347,4 +458,86 @@ mod block_test {
 
         Ok(())
     }
+
+    #[test]
+    fn test_p2wpkh_signature_hash_commits_to_the_sequence_of_the_input() -> Result<(), MessageError>
+    {
+        // P2SH-P2WPKH example of BIP143, whose input has a sequence of 0xfffffffe
+        let data = decode("0100000001db6b1b20aa0fd7b23880be2ecbd4a98130974cf4748fb66092ac4d3ceb1a54770100000000feffffff02b8b4eb0b000000001976a914a457b684d7f0d539a46a45bbc043f35b59d0d96388ac0008af2f000000001976a914fd270b1ee6abcaea97fea7ad0402e8bd8ad6d77c88ac92040000")?;
+        let transaction = Transaction::from_bytes(&mut data.as_slice())?;
+        let pk_script = decode("001479091972186c449eb1ded22b78e40d009bdf0089")?;
+
+        let signature_hash = transaction.p2wpkh_signature_hash(0, pk_script, vec![1_000_000_000]);
+
+        assert_eq!(
+            sha256::Hash::hash(&signature_hash).to_byte_array().to_vec(),
+            decode("64f3b0f4dd2bb3aa1ce8566d220cc74dda9df97d8490cc81d89d735c92e59fb6")?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_segwit_tx_size_breakdown() -> Result<(), MessageError> {
+        let data = decode("020000000001011216d10ae3afe6119529c0a01abe7833641e0e9d37eb880ae5547cfb7c6c7bca0000000000fdffffff0246b31b00000000001976a914c9bc003bf72ebdc53a9572f7ea792ef49a2858d788ac731f2001020000001976a914d617966c3f29cfe50f7d9278dd3e460e3f084b7b88ac02473044022059570681a773748425ddd56156f6af3a0a781a33ae3c42c74fafd6cc2bd0acbc02200c4512c250f88653fae4d73e0cab419fa2ead01d6ba1c54edee69e15c1618638012103e7d8e9b09533ae390d0db3ad53cc050a54f89a987094bffac260f25912885b834b2c2500")?;
+        let transaction = Transaction::from_bytes(&mut &data[..])?;
+
+        let size_breakdown = transaction.size_breakdown();
+
+        assert_eq!(size_breakdown.get_total_size(), 228);
+        assert_eq!(size_breakdown.get_base_size(), 119);
+        assert_eq!(size_breakdown.get_witness_size(), 109);
+        assert_eq!(size_breakdown.get_weight(), 585);
+        assert_eq!(size_breakdown.get_vsize(), 147);
+        assert!(size_breakdown.is_standard());
+
+        assert_eq!(transaction.weight(), 585);
+        assert_eq!(transaction.vsize(), 147);
+        let output_value: i64 = transaction
+            .get_tx_out_list()
+            .iter()
+            .map(|tx_out| tx_out.get_value())
+            .sum();
+        assert_eq!(transaction.fee(&[output_value + 147]), Some(147));
+        assert_eq!(transaction.fee_rate(&[output_value + 147]), Some(1000));
+        assert_eq!(transaction.fee(&[output_value - 1]), None);
+        assert_eq!(transaction.fee(&[]), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fee_of_out_of_range_outputs_is_unknown() {
+        let tx_in = TxIn::new(vec![0; 32], 0, vec![], 0xffffffff);
+        let negative = Transaction::new(
+            1,
+            vec![tx_in.clone()],
+            vec![TxOut::new(-1_000_000_000, vec![]), TxOut::new(1000, vec![])],
+            0,
+            false,
+        );
+        assert_eq!(negative.output_value(), None);
+        assert_eq!(negative.fee(&[2000]), None);
+
+        let overflowing = Transaction::new(
+            1,
+            vec![tx_in],
+            vec![TxOut::new(i64::MAX, vec![]), TxOut::new(i64::MAX, vec![])],
+            0,
+            false,
+        );
+        assert_eq!(overflowing.output_value(), None);
+        assert_eq!(overflowing.fee(&[MAX_MONEY]), None);
+        assert_eq!(add_money(MAX_MONEY, 1), None);
+        assert_eq!(add_money(MAX_MONEY - 1, 1), Some(MAX_MONEY));
+    }
+
+    #[test]
+    fn test_oversized_input_counts_are_rejected_before_parsing_inputs() {
+        let mut data = vec![2u8, 0, 0, 0, 0xff];
+        data.extend(u64::MAX.to_le_bytes());
+
+        assert!(matches!(
+            Transaction::from_bytes(&mut &data[..]),
+            Err(MessageError::TooManyElements)
+        ));
+    }
 }
@@ -1,6 +1,7 @@
-use super::outpoint::Outpoint;
+use super::{outpoint::Outpoint, relative_lock_time::RelativeLockTime};
 use crate::messages::{
     compact_size::CompactSizeUInt,
+    message_constants::MAX_PAYLOAD_SIZE,
     message_error::MessageError,
     read_from_bytes::{read_u32_from_bytes, read_vec_from_bytes},
 };
@@ -39,7 +40,7 @@ impl TxIn {
     /// - `Err(MessageError)` if an error occurs during parsing.
     pub fn from_bytes(stream: &mut dyn Read) -> Result<TxIn, MessageError> {
         let previous_output = Outpoint::from_bytes(stream)?;
-        let script_bytes = CompactSizeUInt::from_bytes(stream)?;
+        let script_bytes = CompactSizeUInt::from_bytes_bounded(stream, MAX_PAYLOAD_SIZE as u64)?;
         let script = read_vec_from_bytes(stream, script_bytes.value() as usize)?;
         let sequence = read_u32_from_bytes(stream, true)?;
 
@@ -57,14 +58,18 @@ impl TxIn {
     ///
     /// A `Vec<u8>` containing the byte representation of the `TxIn`.
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut buff = Vec::new();
+        let mut buff = Vec::with_capacity(49 + self.script.len());
 
-        buff.extend(self.previous_output.to_bytes());
-        buff.extend(self.script_bytes.to_bytes());
+        self.to_bytes_into(&mut buff);
+        buff
+    }
+
+    /// Appends the byte representation of the `TxIn` to the given buffer.
+    pub fn to_bytes_into(&self, buff: &mut Vec<u8>) {
+        self.previous_output.to_bytes_into(buff);
+        self.script_bytes.to_bytes_into(buff);
         buff.extend(&self.script);
         buff.extend(self.sequence.to_le_bytes());
-
-        buff
     }
 
     /// Returns a reference to the previous output being spent by this input.
@@ -81,6 +86,15 @@ impl TxIn {
         self.sequence
     }
 
+    /// Returns the relative lock time the input imposes, as defined in BIP68.
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - The version of the transaction the input belongs to.
+    pub fn get_relative_lock_time(&self, version: i32) -> Option<RelativeLockTime> {
+        RelativeLockTime::from_sequence(version, self.sequence)
+    }
+
     pub fn get_signature_script(&self) -> Vec<u8> {
         self.script.clone()
     }
@@ -1,4 +1,5 @@
 use crate::messages::compact_size::CompactSizeUInt;
+use crate::messages::message_constants::MAX_PAYLOAD_SIZE;
 use crate::messages::message_error::MessageError;
 use crate::messages::read_from_bytes::{read_u32_from_bytes, read_vec_from_bytes};
 use crate::messages::script::Script;
@@ -28,14 +29,15 @@ impl TxInCoinbase {
 
         let index = read_u32_from_bytes(stream, true)?;
 
-        let script_bytes = CompactSizeUInt::from_bytes(stream)?;
+        let script_bytes = CompactSizeUInt::from_bytes_bounded(stream, MAX_PAYLOAD_SIZE as u64)?;
 
         let height = Script::from_bytes(stream)?;
 
-        let coinbase_script = read_vec_from_bytes(
-            stream,
-            (script_bytes.value() - height.cant_bytes() as u64) as usize,
-        )?;
+        let coinbase_script_bytes = script_bytes
+            .value()
+            .checked_sub(height.cant_bytes() as u64)
+            .ok_or(MessageError::ReadFromBytes)?;
+        let coinbase_script = read_vec_from_bytes(stream, coinbase_script_bytes as usize)?;
 
         let sequence = read_u32_from_bytes(stream, true)?;
 
@@ -56,17 +58,28 @@ impl TxInCoinbase {
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut buff = Vec::new();
 
+        self.to_bytes_into(&mut buff);
+        buff
+    }
+
+    /// Appends the byte representation of the `TxInCoinbase` to the given buffer.
+    pub fn to_bytes_into(&self, buff: &mut Vec<u8>) {
         buff.extend(&self.hash);
         buff.extend(self.index.to_le_bytes());
-        buff.extend(self.script_bytes.to_bytes());
+        self.script_bytes.to_bytes_into(buff);
         buff.extend(self.height.to_bytes());
         buff.extend(&self.coinbase_script);
         buff.extend(self.sequence.to_le_bytes());
-
-        buff
     }
 
     pub fn script(&self) -> Vec<u8> {
         self.coinbase_script.clone()
     }
+
+    /// Returns the whole script of the input: the height push followed by the rest of the script.
+    pub fn script_sig(&self) -> Vec<u8> {
+        let mut script_sig = self.height.to_bytes();
+        script_sig.extend(&self.coinbase_script);
+        script_sig
+    }
 }
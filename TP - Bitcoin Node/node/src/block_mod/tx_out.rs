@@ -1,5 +1,6 @@
 use crate::messages::{
     compact_size::CompactSizeUInt,
+    message_constants::MAX_PAYLOAD_SIZE,
     message_error::MessageError,
     read_from_bytes::{read_i64_from_bytes, read_vec_from_bytes},
 };
@@ -33,7 +34,7 @@ impl TxOut {
     /// - `Err(MessageError)` if an error occurs during parsing.
     pub fn from_bytes(stream: &mut dyn Read) -> Result<TxOut, MessageError> {
         let value = read_i64_from_bytes(stream, true)?;
-        let pk_script_bytes = CompactSizeUInt::from_bytes(stream)?;
+        let pk_script_bytes = CompactSizeUInt::from_bytes_bounded(stream, MAX_PAYLOAD_SIZE as u64)?;
         let pk_script = read_vec_from_bytes(stream, pk_script_bytes.value() as usize)?;
 
         Ok(TxOut {
@@ -49,13 +50,17 @@ impl TxOut {
     ///
     /// A `Vec<u8>` containing the byte representation of the `TxOut`.
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut buff = Vec::new();
+        let mut buff = Vec::with_capacity(17 + self.pk_script.len());
 
+        self.to_bytes_into(&mut buff);
+        buff
+    }
+
+    /// Appends the byte representation of the `TxOut` to the given buffer.
+    pub fn to_bytes_into(&self, buff: &mut Vec<u8>) {
         buff.extend(self.value.to_le_bytes());
-        buff.extend(self.pk_script_bytes.to_bytes());
+        self.pk_script_bytes.to_bytes_into(buff);
         buff.extend(&self.pk_script);
-
-        buff
     }
 
     /// Returns a reference to the value of the transaction output.
@@ -0,0 +1,350 @@
+use super::{
+    mempool::Mempool, script_interpreter::verify_input, transaction::Transaction, utxo::UnspentTx,
+};
+use crate::messages::message_constants::{REJECT_DUPLICATE, REJECT_INVALID};
+use std::collections::{HashMap, HashSet};
+
+/// Represents the rules a transaction can be refused entry to the mempool for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxRule {
+    /// The transaction is already in the mempool.
+    AlreadyInMempool,
+    /// The transaction spends the same output more than once.
+    DuplicateInputs,
+    /// An output spent by the transaction is not in the UTXO set.
+    MissingInputs,
+    /// An output spent by the transaction was created by a coinbase that has not matured yet.
+    PrematureCoinbaseSpend,
+    /// An output spent by the transaction is already spent by a transaction of the mempool.
+    MempoolConflict,
+    /// The value of an output of the transaction, or their total, is negative or exceeds
+    /// `MAX_MONEY`.
+    OutputValueOutOfRange,
+    /// The outputs of the transaction are worth more than its inputs.
+    NegativeFee,
+    /// The script of an input does not satisfy the output it spends.
+    ScriptVerifyFailed,
+    /// The lock time of the transaction has not been reached.
+    NonFinal,
+    /// The relative lock time of an input has not elapsed (BIP68).
+    NonBip68Final,
+}
+
+impl TxRule {
+    /// Returns the name of the rule, as reported by Bitcoin Core.
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            TxRule::AlreadyInMempool => "txn-already-in-mempool",
+            TxRule::DuplicateInputs => "bad-txns-inputs-duplicate",
+            TxRule::MissingInputs => "bad-txns-inputs-missingorspent",
+            TxRule::PrematureCoinbaseSpend => "bad-txns-premature-spend-of-coinbase",
+            TxRule::MempoolConflict => "txn-mempool-conflict",
+            TxRule::OutputValueOutOfRange => "bad-txns-vout-outofrange",
+            TxRule::NegativeFee => "bad-txns-in-belowout",
+            TxRule::ScriptVerifyFailed => "mandatory-script-verify-flag-failed",
+            TxRule::NonFinal => "non-final",
+            TxRule::NonBip68Final => "non-BIP68-final",
+        }
+    }
+
+    /// Returns the code of the "reject" message sent for transactions violating the rule.
+    pub fn get_reject_code(&self) -> u8 {
+        match self {
+            TxRule::AlreadyInMempool | TxRule::MempoolConflict => REJECT_DUPLICATE,
+            _ => REJECT_INVALID,
+        }
+    }
+}
+
+/// Represents the state of the chain the lock times of a transaction are checked against: the
+/// height and median time past of its tip, and when the outputs spent by the transaction were
+/// confirmed.
+#[derive(Debug, Clone, Default)]
+pub struct LockContext {
+    tip_height: u32,
+    median_time_past: u32,
+    confirmations: HashMap<Vec<u8>, (u32, u32)>,
+}
+
+impl LockContext {
+    pub fn new(tip_height: u32, median_time_past: u32) -> LockContext {
+        LockContext {
+            tip_height,
+            median_time_past,
+            confirmations: HashMap::new(),
+        }
+    }
+
+    /// Records the confirmation of a transaction, at the given height and with the median time
+    /// past of the parent of the block confirming it, as BIP68 measures time from it.
+    pub fn add_confirmation(&mut self, tx_id: Vec<u8>, height: u32, median_time_past: u32) {
+        self.confirmations.insert(tx_id, (height, median_time_past));
+    }
+
+    /// Returns whether the transaction could be included in the next block, both by its lock time
+    /// and by the relative lock times of its inputs.
+    fn check_locks(&self, tx: &Transaction) -> Result<(), TxRule> {
+        if !tx.is_final(self.tip_height + 1, self.median_time_past) {
+            return Err(TxRule::NonFinal);
+        }
+
+        for tx_in in tx.get_tx_in_list() {
+            let lock = match tx_in.get_relative_lock_time(tx.get_version()) {
+                Some(lock) => lock,
+                None => continue,
+            };
+            let confirmation = self
+                .confirmations
+                .get(tx_in.get_prev_output().get_tx_id())
+                .copied();
+
+            if !lock.is_satisfied(confirmation, self.tip_height, self.median_time_past) {
+                return Err(TxRule::NonBip68Final);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Validates a transaction before it is admitted to the mempool.
+///
+/// The value of every output, and their total, must be in the range `0..=MAX_MONEY`. Every output
+/// it spends must be in the UTXO set, not be spent by another transaction of the mempool and, if
+/// it was created by a coinbase, have matured. Its lock time, and the relative lock times of its
+/// inputs, must allow it into the next block. The script of every input must
+/// verify and its inputs must be worth at least as much as its outputs.
+///
+/// # Arguments
+///
+/// * `tx` - The transaction to validate.
+/// * `utxo` - The unspent transaction set.
+/// * `mempool` - The mempool the transaction would be added to.
+/// * `lock_context` - The state of the chain the lock times are checked against.
+///
+/// # Returns
+///
+/// The fee paid by the transaction, or the first `TxRule` it violates.
+pub fn validate_transaction(
+    tx: &Transaction,
+    utxo: &UnspentTx,
+    mempool: &Mempool,
+    lock_context: &LockContext,
+) -> Result<u64, TxRule> {
+    if mempool.get_txs().contains_key(&tx.get_id(false)) {
+        return Err(TxRule::AlreadyInMempool);
+    }
+    if tx.output_value().is_none() {
+        return Err(TxRule::OutputValueOutOfRange);
+    }
+
+    let mut outpoints = HashSet::new();
+    let mut spent_outputs = vec![];
+
+    for tx_in in tx.get_tx_in_list() {
+        let outpoint = tx_in.get_prev_output();
+
+        if !outpoints.insert((outpoint.get_tx_id().clone(), outpoint.get_index())) {
+            return Err(TxRule::DuplicateInputs);
+        }
+        if mempool.get_spender(outpoint).is_some() {
+            return Err(TxRule::MempoolConflict);
+        }
+        spent_outputs.push(utxo.get_tx_out(outpoint).ok_or(TxRule::MissingInputs)?);
+        if !utxo.is_mature(outpoint) {
+            return Err(TxRule::PrematureCoinbaseSpend);
+        }
+    }
+
+    lock_context.check_locks(tx)?;
+
+    let input_values: Vec<i64> = spent_outputs
+        .iter()
+        .map(|tx_out| tx_out.get_value())
+        .collect();
+    let fee = tx.fee(&input_values).ok_or(TxRule::NegativeFee)?;
+
+    for index in 0..tx.get_tx_in_list().len() {
+        if verify_input(tx, index, &spent_outputs).is_err() {
+            return Err(TxRule::ScriptVerifyFailed);
+        }
+    }
+
+    Ok(fee)
+}
+
+#[cfg(test)]
+mod tx_validation_test {
+    use super::*;
+    use crate::block_mod::{
+        block::{test_block_bytes, Block},
+        block_constants::COINBASE_MATURITY,
+        tx_in::TxIn,
+        tx_out::TxOut,
+    };
+    use crate::messages::message_error::MessageError;
+
+    fn transaction(prev_tx_id: Vec<u8>, script_sig: Vec<u8>, value: i64) -> Transaction {
+        Transaction::new(
+            1,
+            vec![TxIn::new(prev_tx_id, 0, script_sig, 0xffffffff)],
+            vec![TxOut::new(value, vec![0x51])],
+            0,
+            false,
+        )
+    }
+
+    #[test]
+    fn test_transactions_are_validated_before_entering_the_mempool() {
+        let mut utxo = UnspentTx::new();
+        // Locked by OP_1 OP_EQUAL, spent by pushing OP_1
+        let funding = Transaction::new(
+            1,
+            vec![TxIn::new(vec![0; 32], 0, vec![], 0xffffffff)],
+            vec![TxOut::new(10000, vec![0x51, 0x87])],
+            0,
+            false,
+        );
+        utxo.update_transaction(&funding);
+        let mut mempool = Mempool::new();
+        let context = LockContext::default();
+
+        let spending = transaction(funding.get_id(false), vec![0x51], 9000);
+        assert_eq!(
+            validate_transaction(&spending, &utxo, &mempool, &context),
+            Ok(1000)
+        );
+
+        let wrong_script = transaction(funding.get_id(false), vec![0x52], 9000);
+        assert_eq!(
+            validate_transaction(&wrong_script, &utxo, &mempool, &context),
+            Err(TxRule::ScriptVerifyFailed)
+        );
+
+        let overspending = transaction(funding.get_id(false), vec![0x51], 11000);
+        assert_eq!(
+            validate_transaction(&overspending, &utxo, &mempool, &context),
+            Err(TxRule::NegativeFee)
+        );
+
+        let missing = transaction(vec![1; 32], vec![0x51], 9000);
+        assert_eq!(
+            validate_transaction(&missing, &utxo, &mempool, &context),
+            Err(TxRule::MissingInputs)
+        );
+
+        mempool.add(spending.clone(), Some(1000));
+        assert_eq!(
+            validate_transaction(&spending, &utxo, &mempool, &context),
+            Err(TxRule::AlreadyInMempool)
+        );
+
+        let conflicting = transaction(funding.get_id(false), vec![0x51], 8000);
+        assert_eq!(
+            validate_transaction(&conflicting, &utxo, &mempool, &context),
+            Err(TxRule::MempoolConflict)
+        );
+    }
+
+    #[test]
+    fn test_coinbase_outputs_are_not_spent_before_maturing() -> Result<(), MessageError> {
+        let mut utxo = UnspentTx::new();
+        let mempool = Mempool::new();
+        let context = LockContext::default();
+        let block = Block::from_bytes(&mut test_block_bytes().as_slice())?;
+        let value = block.get_coinbase().tx_out_list[0].get_value();
+        let spending = transaction(block.get_coinbase().get_id(), vec![], value);
+
+        utxo.update(&block, 1);
+        assert_eq!(
+            validate_transaction(&spending, &utxo, &mempool, &context),
+            Err(TxRule::PrematureCoinbaseSpend)
+        );
+
+        for height in 2..COINBASE_MATURITY + 1 {
+            let mut next = Block::from_bytes(&mut test_block_bytes().as_slice())?;
+            next.block_header.nonce = height;
+            next.coinbase.lock_time = height;
+            utxo.update(&next, height);
+        }
+        assert_ne!(
+            validate_transaction(&spending, &utxo, &mempool, &context),
+            Err(TxRule::PrematureCoinbaseSpend)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_lock_times_must_be_reached() {
+        let mut utxo = UnspentTx::new();
+        let mempool = Mempool::new();
+        let funding = transaction(vec![0; 32], vec![], 10000);
+        utxo.update_transaction(&funding);
+        let mut context = LockContext::new(99, 1_600_000_000);
+
+        let mut locked = transaction(funding.get_id(false), vec![], 9000);
+        locked.tx_in_list[0].sequence = 0;
+        locked.lock_time = 99;
+        assert_eq!(
+            validate_transaction(&locked, &utxo, &mempool, &context),
+            Ok(1000)
+        );
+
+        locked.lock_time = 100;
+        assert_eq!(
+            validate_transaction(&locked, &utxo, &mempool, &context),
+            Err(TxRule::NonFinal)
+        );
+        locked.lock_time = 1_600_000_000;
+        assert_eq!(
+            validate_transaction(&locked, &utxo, &mempool, &context),
+            Err(TxRule::NonFinal)
+        );
+
+        // Locked for 10 blocks after the confirmation of the funding transaction
+        let mut relative = transaction(funding.get_id(false), vec![], 9000);
+        relative.version = 2;
+        relative.tx_in_list[0].sequence = 10;
+        assert_eq!(
+            validate_transaction(&relative, &utxo, &mempool, &context),
+            Err(TxRule::NonBip68Final)
+        );
+
+        context.add_confirmation(funding.get_id(false), 91, 1_500_000_000);
+        assert_eq!(
+            validate_transaction(&relative, &utxo, &mempool, &context),
+            Err(TxRule::NonBip68Final)
+        );
+        context.add_confirmation(funding.get_id(false), 90, 1_500_000_000);
+        assert_eq!(
+            validate_transaction(&relative, &utxo, &mempool, &context),
+            Ok(1000)
+        );
+    }
+
+    #[test]
+    fn test_out_of_range_output_values_are_rejected() {
+        let mut utxo = UnspentTx::new();
+        let mempool = Mempool::new();
+        let context = LockContext::default();
+        let funding = transaction(vec![0; 32], vec![], 10000);
+        utxo.update_transaction(&funding);
+
+        let mut negative = transaction(funding.get_id(false), vec![], 9000);
+        negative
+            .tx_out_list
+            .push(TxOut::new(-1_000_000_000, vec![0x51]));
+        assert_eq!(
+            validate_transaction(&negative, &utxo, &mempool, &context),
+            Err(TxRule::OutputValueOutOfRange)
+        );
+
+        let mut overflowing = transaction(funding.get_id(false), vec![], i64::MAX);
+        overflowing
+            .tx_out_list
+            .push(TxOut::new(i64::MAX, vec![0x51]));
+        assert_eq!(
+            validate_transaction(&overflowing, &utxo, &mempool, &context),
+            Err(TxRule::OutputValueOutOfRange)
+        );
+    }
+}
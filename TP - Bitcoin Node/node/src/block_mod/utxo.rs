@@ -1,17 +1,153 @@
+use super::block_constants::{COINBASE_MATURITY, MAX_REORG_DEPTH};
+use super::block_undo::BlockUndo;
 use super::outpoint::Outpoint;
 use super::transaction::Transaction;
 use crate::block_mod::block::Block;
 use crate::block_mod::tx_in::TxIn;
 use crate::block_mod::tx_out::TxOut;
-use std::collections::HashMap;
+use crate::messages::{
+    compact_size::CompactSizeUInt,
+    message_error::MessageError,
+    read_from_bytes::{read_u32_from_bytes, read_u8_from_bytes, read_vec_from_bytes},
+};
+use bitcoin_hashes::{sha256, sha256d, Hash};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::Read;
+use std::mem::size_of;
+
+/// The key of an unspent output in the set: the ID of its transaction and its index within it.
+///
+/// It is kept inline, instead of as the heap-allocated ID of an `Outpoint`, to keep the set small.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OutpointKey {
+    tx_id: [u8; 32],
+    index: u32,
+}
+
+impl OutpointKey {
+    pub fn new(tx_id: &[u8], index: u32) -> OutpointKey {
+        OutpointKey {
+            tx_id: tx_id.try_into().unwrap_or_default(),
+            index,
+        }
+    }
+
+    pub fn get_tx_id(&self) -> &[u8; 32] {
+        &self.tx_id
+    }
+
+    pub fn get_index(&self) -> u32 {
+        self.index
+    }
+}
+
+impl From<&Outpoint> for OutpointKey {
+    fn from(outpoint: &Outpoint) -> OutpointKey {
+        OutpointKey::new(outpoint.get_tx_id(), outpoint.get_index())
+    }
+}
+
+/// Returns the hash the unspent outputs locked by a script are indexed by: the SHA-256 of the
+/// script, as used by the Electrum protocol.
+pub fn script_hash(pk_script: &[u8]) -> [u8; 32] {
+    sha256::Hash::hash(pk_script).to_byte_array()
+}
+
+/// An unspent output as kept in the set: its value and its script, without the length prefix and
+/// the spare capacity a `TxOut` carries, and the height of its block if it was created by a
+/// coinbase.
+#[derive(Debug, Clone)]
+pub struct Coin {
+    value: i64,
+    pk_script: Box<[u8]>,
+    coinbase_height: Option<u32>,
+}
+
+impl Coin {
+    /// Creates the coin of an output created by the coinbase of the block at the given height.
+    pub fn from_coinbase(tx_out: &TxOut, height: u32) -> Coin {
+        Coin {
+            coinbase_height: Some(height),
+            ..Coin::from(tx_out)
+        }
+    }
+
+    pub fn get_value(&self) -> i64 {
+        self.value
+    }
+
+    pub fn get_pk_script(&self) -> &[u8] {
+        &self.pk_script
+    }
+
+    /// Returns the height of the block whose coinbase created the coin, or `None` if it was not
+    /// created by a coinbase.
+    pub fn get_coinbase_height(&self) -> Option<u32> {
+        self.coinbase_height
+    }
+
+    /// Rebuilds the output the coin was created from.
+    pub fn to_tx_out(&self) -> TxOut {
+        TxOut::new(self.value, self.pk_script.to_vec())
+    }
+
+    /// Appends the coin to the buffer as its output followed, if it was created by a coinbase, by
+    /// the height of its block.
+    pub fn to_bytes_into(&self, bytes: &mut Vec<u8>) {
+        self.to_tx_out().to_bytes_into(bytes);
+
+        match self.coinbase_height {
+            Some(height) => {
+                bytes.push(1);
+                bytes.extend(height.to_le_bytes());
+            }
+            None => bytes.push(0),
+        }
+    }
+
+    /// Parses a coin serialized by `to_bytes_into`.
+    ///
+    /// # Returns
+    ///
+    /// The coin, or a `MessageError` if the bytes are truncated or malformed.
+    pub fn from_bytes(stream: &mut dyn Read) -> Result<Coin, MessageError> {
+        let tx_out = TxOut::from_bytes(stream)?;
+
+        match read_u8_from_bytes(stream)? {
+            0 => Ok(Coin::from(&tx_out)),
+            _ => Ok(Coin::from_coinbase(
+                &tx_out,
+                read_u32_from_bytes(stream, true)?,
+            )),
+        }
+    }
+}
+
+impl From<&TxOut> for Coin {
+    fn from(tx_out: &TxOut) -> Coin {
+        Coin {
+            value: tx_out.get_value(),
+            pk_script: tx_out.get_pk_script().into_boxed_slice(),
+            coinbase_height: None,
+        }
+    }
+}
 
 /// Represents a collection of unspent transaction outputs (UTXOs).
 #[derive(Debug)]
 pub struct UnspentTx {
-    /// The mapping of hashed transaction IDs to a map of output indices to `TxOut` objects.
-    /// The outer `HashMap` uses the hashed transaction ID as the key, while the inner `HashMap`
-    /// uses the transaction output's index as the key and the corresponding `TxOut` object as the value.
-    utxo: HashMap<Vec<u8>, HashMap<u32, TxOut>>,
+    /// The unspent outputs, keyed by their outpoint.
+    utxo: HashMap<OutpointKey, Coin>,
+    /// The outpoints of the unspent outputs, keyed by the hash of the script locking them.
+    script_index: HashMap<[u8; 32], HashSet<OutpointKey>>,
+    /// The coins spent by the last `MAX_REORG_DEPTH` connected blocks, keyed by block hash, so
+    /// those blocks can be disconnected if the chain is reorganized.
+    undo_data: HashMap<Vec<u8>, Vec<(Outpoint, Coin)>>,
+    undo_order: VecDeque<Vec<u8>>,
+    /// The hash of the last block connected to the set, if any.
+    tip: Option<Vec<u8>>,
+    /// The height of the last block connected to the set, if any.
+    height: Option<u32>,
 }
 
 impl UnspentTx {
@@ -23,18 +159,113 @@ impl UnspentTx {
     pub fn new() -> UnspentTx {
         UnspentTx {
             utxo: HashMap::new(),
+            script_index: HashMap::new(),
+            undo_data: HashMap::new(),
+            undo_order: VecDeque::new(),
+            tip: None,
+            height: None,
         }
     }
 
     /// Updates the `UnspentTx` object by processing a new block.
     ///
+    /// The outputs created by its coinbase are tagged with the height of the block, so they are
+    /// not spent before they mature. The coins spent by the block are kept as its undo data, along
+    /// with the height of the coinbases that created them, so the block can be disconnected later
+    /// on. Only the undo data of the last `MAX_REORG_DEPTH`
+    /// blocks is kept.
+    ///
     /// # Arguments
     ///
     /// * `new_block` - A reference to the `Block` object representing the new block to be processed.
-    pub fn update(&mut self, new_block: &Block) {
+    /// * `height` - The height of the block.
+    pub fn update(&mut self, new_block: &Block, height: u32) {
+        let mut spent = vec![];
+        let coinbase_id = new_block.get_coinbase().get_id();
+
+        for (index, tx_out) in new_block.get_coinbase().tx_out_list.iter().enumerate() {
+            self.add_coin(
+                OutpointKey::new(&coinbase_id, index as u32),
+                Coin::from_coinbase(tx_out, height),
+            );
+        }
+
         for transaction in new_block.get_txn_list() {
-            self.update_transaction(transaction);
+            spent.extend(self.update_transaction(transaction));
+        }
+
+        let block_hash = new_block.get_header().get_header();
+        self.tip = Some(block_hash.clone());
+        self.height = Some(height);
+        self.add_undo(BlockUndo::new(block_hash, spent));
+    }
+
+    /// Keeps the undo data of a block as that of the last connected block, such as the undo data
+    /// persisted by a previous run, dropping the undo data of the oldest block once more than
+    /// `MAX_REORG_DEPTH` blocks have it.
+    pub fn add_undo(&mut self, undo: BlockUndo) {
+        let (block_hash, spent) = undo.into_parts();
+
+        if self.undo_data.insert(block_hash.clone(), spent).is_none() {
+            self.undo_order.push_back(block_hash);
+        }
+
+        if self.undo_order.len() > MAX_REORG_DEPTH {
+            if let Some(oldest) = self.undo_order.pop_front() {
+                self.undo_data.remove(&oldest);
+            }
+        }
+    }
+
+    /// Reverts the changes a block made to the `UnspentTx` object, removing the outputs it created
+    /// and restoring the ones it spent.
+    ///
+    /// # Arguments
+    ///
+    /// * `block` - A reference to the `Block` object to be disconnected, which must be the last
+    ///   connected block.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the block was disconnected, or `false` if its undo data is no longer kept.
+    pub fn disconnect(&mut self, block: &Block) -> bool {
+        let block_hash = block.get_header().get_header();
+        let spent = match self.undo_data.remove(&block_hash) {
+            Some(spent) => spent,
+            None => return false,
+        };
+        self.undo_order.retain(|hash| *hash != block_hash);
+        self.tip = Some(block.get_header().get_previuos_block_header().clone());
+        self.height = self.height.and_then(|height| height.checked_sub(1));
+
+        let coinbase_id = block.get_coinbase().get_id();
+        for index in 0..block.get_coinbase().tx_out_list.len() {
+            self.remove_coin(&OutpointKey::new(&coinbase_id, index as u32));
+        }
+        let mut created = HashSet::from([coinbase_id]);
+
+        for transaction in block.get_txn_list() {
+            let tx_id = transaction.get_id(false);
+
+            for index in 0..transaction.get_tx_out_list().len() {
+                self.remove_coin(&OutpointKey::new(&tx_id, index as u32));
+            }
+            created.insert(tx_id);
+        }
+
+        // Outputs created and spent within the block itself are not restored
+        for (outpoint, coin) in spent {
+            if !created.contains(outpoint.get_tx_id()) {
+                self.add_coin(OutpointKey::from(&outpoint), coin);
+            }
         }
+        true
+    }
+
+    /// Returns the coins spent by a block, if it is one of the last `MAX_REORG_DEPTH` connected
+    /// blocks.
+    pub fn get_undo_data(&self, block_hash: &Vec<u8>) -> Option<&Vec<(Outpoint, Coin)>> {
+        self.undo_data.get(block_hash)
     }
 
     /// Updates the `UnspentTx` object by processing a new transaction.
@@ -42,9 +273,17 @@ impl UnspentTx {
     /// # Arguments
     ///
     /// * `new_tx` - A reference to the `Transaction` object representing the new transaction to be processed.
-    pub fn update_transaction(&mut self, new_tx: &Transaction) {
+    ///
+    /// # Returns
+    ///
+    /// The coins spent by the transaction that belonged to the set.
+    pub fn update_transaction(&mut self, new_tx: &Transaction) -> Vec<(Outpoint, Coin)> {
+        let mut spent = vec![];
+
         for tx_in in new_tx.get_tx_in_list() {
-            self.remove_tx_out(tx_in);
+            if let Some(coin) = self.remove_tx_out(tx_in) {
+                spent.push((tx_in.get_prev_output().clone(), coin));
+            }
         }
 
         let new_tx_id = new_tx.get_id(false);
@@ -52,6 +291,7 @@ impl UnspentTx {
         for (index, tx_out) in new_tx.get_tx_out_list().iter().enumerate() {
             self.add_tx_out(tx_out, &new_tx_id, index);
         }
+        spent
     }
 
     /// Removes a transaction output from the `UnspentTx` object based on the provided `TxIn`.
@@ -59,20 +299,12 @@ impl UnspentTx {
     /// # Arguments
     ///
     /// * `new_tx_in` - A reference to the `TxIn` object representing the transaction input that spends the output.
-    fn remove_tx_out(&mut self, new_tx_in: &TxIn) {
-        let tx_id = new_tx_in.get_prev_output().get_tx_id();
-        let index = new_tx_in.get_prev_output().get_index();
-
-        let transaction_outputs = match self.utxo.get_mut(tx_id) {
-            Some(transaction) => transaction,
-            None => {
-                return;
-            }
-        };
-
-        if transaction_outputs.remove(&index).is_some() && transaction_outputs.values().len() == 0 {
-            self.utxo.remove(tx_id);
-        }
+    ///
+    /// # Returns
+    ///
+    /// The coin of the removed output, or `None` if it was not unspent.
+    fn remove_tx_out(&mut self, new_tx_in: &TxIn) -> Option<Coin> {
+        self.remove_coin(&OutpointKey::from(new_tx_in.get_prev_output()))
     }
 
     /// Adds a transaction output to the `UnspentTx` object.
@@ -83,43 +315,497 @@ impl UnspentTx {
     /// * `tx_id` - The hashed ID of the transaction to which the output belongs.
     /// * `index` - The index of the output within the transaction.
     fn add_tx_out(&mut self, new_tx_out: &TxOut, tx_id: &[u8], index: usize) {
-        //corroborar el tipo de tx_id
-        let transation_outputs = self.utxo.entry(tx_id.to_vec()).or_insert(HashMap::new());
+        self.add_coin(
+            OutpointKey::new(tx_id, index as u32),
+            Coin::from(new_tx_out),
+        );
+    }
 
-        transation_outputs
-            .entry(index as u32)
-            .or_insert(new_tx_out.clone());
+    /// Adds a coin to the set, unless its outpoint is already unspent, indexing it by the hash of
+    /// its script.
+    fn add_coin(&mut self, key: OutpointKey, coin: Coin) {
+        if self.utxo.contains_key(&key) {
+            return;
+        }
+        self.script_index
+            .entry(script_hash(coin.get_pk_script()))
+            .or_default()
+            .insert(key);
+        self.utxo.insert(key, coin);
     }
 
-    /// Returns the total number of transactions in the `UnspentTx` object.
+    /// Removes a coin from the set and from the index of its script.
+    fn remove_coin(&mut self, key: &OutpointKey) -> Option<Coin> {
+        let coin = self.utxo.remove(key)?;
+        let hash = script_hash(coin.get_pk_script());
+
+        if let Some(outpoints) = self.script_index.get_mut(&hash) {
+            outpoints.remove(key);
+            if outpoints.is_empty() {
+                self.script_index.remove(&hash);
+            }
+        }
+        Some(coin)
+    }
+
+    /// Returns the total number of unspent outputs in the `UnspentTx` object.
     ///
     /// # Returns
     ///
-    /// The total number of transactions.
+    /// The total number of unspent outputs.
     pub fn tx_count(&self) -> usize {
-        let mut cant = 0;
+        self.utxo.len()
+    }
+
+    pub fn contains_key(&self, output: &Outpoint) -> bool {
+        self.utxo.contains_key(&OutpointKey::from(output))
+    }
+
+    /// Returns whether the output the outpoint refers to can be spent by the next block: outputs
+    /// created by a coinbase can only be spent once `COINBASE_MATURITY` blocks have been built on
+    /// top of theirs. Outputs not in the set are not considered immature.
+    pub fn is_mature(&self, outpoint: &Outpoint) -> bool {
+        let coinbase_height = match self
+            .utxo
+            .get(&OutpointKey::from(outpoint))
+            .and_then(|coin| coin.get_coinbase_height())
+        {
+            Some(coinbase_height) => coinbase_height,
+            None => return true,
+        };
+        let spend_height = self.height.map_or(0, |height| height + 1);
+
+        spend_height.saturating_sub(coinbase_height) >= COINBASE_MATURITY
+    }
+
+    /// Estimates the memory taken by the unspent outputs, in bytes: the slots of the map, whether
+    /// used or not, the scripts they point to and the index of their scripts.
+    pub fn memory_usage(&self) -> usize {
+        let scripts: usize = self
+            .utxo
+            .values()
+            .map(|coin| coin.get_pk_script().len())
+            .sum();
+        let index: usize = self
+            .script_index
+            .values()
+            .map(|outpoints| outpoints.capacity() * size_of::<OutpointKey>())
+            .sum();
+
+        self.utxo.capacity() * (size_of::<OutpointKey>() + size_of::<Coin>())
+            + scripts
+            + self.script_index.capacity() * (32 + size_of::<HashSet<OutpointKey>>())
+            + index
+    }
+
+    /// Returns the fee rate of a transaction, in satoshis per kilobyte of virtual size.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - The transaction whose fee rate is computed.
+    ///
+    /// # Returns
+    ///
+    /// The fee rate, or `None` if some output spent by the transaction is not unspent, in which
+    /// case its fee cannot be known.
+    pub fn get_fee_rate(&self, tx: &Transaction) -> Option<u64> {
+        tx.fee_rate(&self.get_input_values(tx)?)
+    }
+
+    /// Computes the fee paid by a transaction, as the value of its inputs minus the value of its
+    /// outputs.
+    ///
+    /// # Returns
+    ///
+    /// The fee in satoshis, or `None` if an input is not in the UTXO set or the outputs are worth
+    /// more than the inputs.
+    pub fn get_fee(&self, tx: &Transaction) -> Option<u64> {
+        tx.fee(&self.get_input_values(tx)?)
+    }
+
+    /// Returns the value of the output spent by each input of a transaction, or `None` if an input
+    /// is not in the UTXO set.
+    fn get_input_values(&self, tx: &Transaction) -> Option<Vec<i64>> {
+        tx.get_tx_in_list()
+            .iter()
+            .map(|tx_in| {
+                self.utxo
+                    .get(&OutpointKey::from(tx_in.get_prev_output()))
+                    .map(|coin| coin.get_value())
+            })
+            .collect()
+    }
+
+    /// Returns the unspent output the outpoint refers to, if it is in the set.
+    pub fn get_tx_out(&self, outpoint: &Outpoint) -> Option<TxOut> {
+        self.utxo
+            .get(&OutpointKey::from(outpoint))
+            .map(|coin| coin.to_tx_out())
+    }
+
+    /// Returns the unspent outputs locked by the script whose hash is given, along with their
+    /// outpoints.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash` - The hash of the script, as returned by `script_hash`.
+    pub fn get_by_script_hash(&self, hash: &[u8; 32]) -> Vec<(OutpointKey, &Coin)> {
+        self.script_index
+            .get(hash)
+            .map(|outpoints| {
+                outpoints
+                    .iter()
+                    .filter_map(|key| self.utxo.get(key).map(|coin| (*key, coin)))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns the unspent outputs locked by the given script, along with their outpoints.
+    pub fn get_by_script(&self, pk_script: &[u8]) -> Vec<(OutpointKey, &Coin)> {
+        self.get_by_script_hash(&script_hash(pk_script))
+    }
+
+    pub fn get_utxo(&self) -> &HashMap<OutpointKey, Coin> {
+        &self.utxo
+    }
+
+    /// Returns the hash of the last block connected to the set, if any.
+    pub fn get_tip(&self) -> Option<&Vec<u8>> {
+        self.tip.as_ref()
+    }
+
+    /// Returns the height of the last block connected to the set, if any.
+    pub fn get_height(&self) -> Option<u32> {
+        self.height
+    }
+
+    /// Serializes the set so it can be persisted, along with the hash and height of the last block
+    /// connected to it. The undo data of the last blocks is persisted on its own, alongside the
+    /// blocks.
+    ///
+    /// # Returns
+    ///
+    /// The tip hash, or zeros if no block was connected, and its height, followed by the count of
+    /// unspent outputs and each outpoint with its output and, for coinbase outputs, the height of
+    /// their block.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.tip.clone().unwrap_or(vec![0; 32]);
+        bytes.extend(self.height.unwrap_or(0).to_le_bytes());
 
-        for transaction in self.utxo.iter() {
-            cant += transaction.1.iter().len();
+        CompactSizeUInt::from_number(self.tx_count() as u64).to_bytes_into(&mut bytes);
+        for (key, coin) in self.utxo.iter() {
+            coin_to_bytes_into(key, coin, &mut bytes);
         }
-        cant
+        bytes
     }
 
-    pub fn contains_key(&self, output: &Outpoint) -> bool {
-        if let Some(outputs) = self.utxo.get(output.get_tx_id()) {
-            println!("true");
-            return outputs.contains_key(&output.get_index());
+    /// Returns the hash of the contents of the set, which does not depend on the order its outputs
+    /// were added in: the double SHA-256 of every outpoint, sorted, followed by its output and, for
+    /// coinbase outputs, the height of their block, as serialized by `to_bytes`.
+    pub fn content_hash(&self) -> Vec<u8> {
+        let mut keys: Vec<&OutpointKey> = self.utxo.keys().collect();
+        keys.sort_by_key(|key| (key.get_tx_id(), key.get_index()));
+
+        let mut bytes = vec![];
+        for key in keys {
+            if let Some(coin) = self.utxo.get(key) {
+                coin_to_bytes_into(key, coin, &mut bytes);
+            }
         }
-        false
+        sha256d::Hash::hash(&bytes).to_byte_array().to_vec()
     }
 
-    pub fn get_utxo(&self) -> &HashMap<Vec<u8>, HashMap<u32, TxOut>> {
-        &self.utxo
+    /// Parses a set serialized by `to_bytes`.
+    ///
+    /// # Returns
+    ///
+    /// The set, or a `MessageError` if the bytes are truncated or malformed.
+    pub fn from_bytes(stream: &mut dyn Read) -> Result<UnspentTx, MessageError> {
+        let mut utxo = UnspentTx::new();
+        let tip = read_vec_from_bytes(stream, 32)?;
+        let height = read_u32_from_bytes(stream, true)?;
+        if tip.iter().any(|byte| *byte != 0) {
+            utxo.tip = Some(tip);
+            utxo.height = Some(height);
+        }
+
+        for _ in 0..CompactSizeUInt::from_bytes(stream)?.value() {
+            let key = OutpointKey::from(&Outpoint::from_bytes(stream)?);
+            utxo.add_coin(key, Coin::from_bytes(stream)?);
+        }
+        Ok(utxo)
     }
 }
 
+/// Appends an unspent output to the buffer as its outpoint, followed by its output and, if it was
+/// created by a coinbase, the height of its block.
+fn coin_to_bytes_into(key: &OutpointKey, coin: &Coin, bytes: &mut Vec<u8>) {
+    bytes.extend(key.get_tx_id());
+    bytes.extend(key.get_index().to_le_bytes());
+    coin.to_bytes_into(bytes);
+}
+
 impl Default for UnspentTx {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod utxo_test {
+    use super::*;
+    use crate::block_mod::block::test_block_bytes;
+    use crate::messages::message_error::MessageError;
+
+    #[test]
+    fn test_fee_rate_of_transaction() {
+        let mut utxo = UnspentTx::new();
+        let funding = Transaction::new(
+            1,
+            vec![TxIn::new(vec![0; 32], 0, vec![], 0xffffffff)],
+            vec![TxOut::new(10000, vec![0; 25])],
+            0,
+            false,
+        );
+        utxo.update_transaction(&funding);
+
+        let spending = Transaction::new(
+            1,
+            vec![TxIn::new(funding.get_id(false), 0, vec![], 0xffffffff)],
+            vec![TxOut::new(9000, vec![0; 25])],
+            0,
+            false,
+        );
+        let vsize = spending.vsize() as u64;
+        let unknown = Transaction::new(
+            1,
+            vec![TxIn::new(vec![1; 32], 0, vec![], 0xffffffff)],
+            vec![TxOut::new(9000, vec![0; 25])],
+            0,
+            false,
+        );
+
+        assert_eq!(utxo.get_fee_rate(&spending), Some(1000 * 1000 / vsize));
+        assert_eq!(utxo.get_fee_rate(&unknown), None);
+    }
+
+    #[test]
+    fn test_disconnected_block_is_reverted() -> Result<(), MessageError> {
+        let mut utxo = UnspentTx::new();
+        let funding = Transaction::new(
+            1,
+            vec![TxIn::new(vec![0; 32], 0, vec![], 0xffffffff)],
+            vec![TxOut::new(10000, vec![0; 25])],
+            0,
+            false,
+        );
+        let spending = Transaction::new(
+            1,
+            vec![TxIn::new(funding.get_id(false), 0, vec![], 0xffffffff)],
+            vec![TxOut::new(9000, vec![0; 25])],
+            0,
+            false,
+        );
+
+        let mut first = Block::from_bytes(&mut test_block_bytes().as_slice())?;
+        first.txn_list = vec![funding.clone()];
+        let mut second = Block::from_bytes(&mut test_block_bytes().as_slice())?;
+        second.block_header.nonce = 1;
+        second.txn_list = vec![spending.clone()];
+
+        utxo.update(&first, 1);
+        utxo.update(&second, 2);
+        assert!(!utxo.contains_key(&Outpoint::new(funding.get_id(false), 0)));
+
+        assert!(utxo.disconnect(&second));
+        assert!(!utxo.disconnect(&second));
+        assert!(utxo.contains_key(&Outpoint::new(funding.get_id(false), 0)));
+        assert!(!utxo.contains_key(&Outpoint::new(spending.get_id(false), 0)));
+        assert_eq!(
+            utxo.get_tip(),
+            Some(second.get_header().get_previuos_block_header())
+        );
+        assert_eq!(utxo.get_height(), Some(1));
+        assert!(!utxo.contains_key(&Outpoint::new(second.get_coinbase().get_id(), 0)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_disconnected_block_restores_the_height_of_spent_coinbases() -> Result<(), MessageError>
+    {
+        let mut utxo = UnspentTx::new();
+        let first = Block::from_bytes(&mut test_block_bytes().as_slice())?;
+        let coinbase_output = Outpoint::new(first.get_coinbase().get_id(), 0);
+        let spending = Transaction::new(
+            1,
+            vec![TxIn::new(
+                coinbase_output.get_tx_id().clone(),
+                0,
+                vec![],
+                0xffffffff,
+            )],
+            vec![TxOut::new(9000, vec![0; 25])],
+            0,
+            false,
+        );
+
+        let mut second = Block::from_bytes(&mut test_block_bytes().as_slice())?;
+        second.block_header.nonce = 1;
+        second.coinbase.lock_time = 1;
+        second.txn_list = vec![spending];
+
+        utxo.update(&first, 1);
+        utxo.update(&second, 2);
+        assert!(!utxo.contains_key(&coinbase_output));
+
+        assert!(utxo.disconnect(&second));
+        assert_eq!(
+            utxo.get_utxo()
+                .get(&OutpointKey::from(&coinbase_output))
+                .and_then(|coin| coin.get_coinbase_height()),
+            Some(1)
+        );
+        assert!(!utxo.is_mature(&coinbase_output));
+        Ok(())
+    }
+
+    #[test]
+    fn test_persisted_set_is_restored() -> Result<(), MessageError> {
+        let mut utxo = UnspentTx::new();
+        let funding = Transaction::new(
+            1,
+            vec![TxIn::new(vec![0; 32], 0, vec![], 0xffffffff)],
+            vec![
+                TxOut::new(10000, vec![0; 25]),
+                TxOut::new(5000, vec![1; 22]),
+            ],
+            0,
+            false,
+        );
+        let spending = Transaction::new(
+            1,
+            vec![TxIn::new(funding.get_id(false), 0, vec![], 0xffffffff)],
+            vec![TxOut::new(9000, vec![0; 25])],
+            0,
+            false,
+        );
+
+        let mut first = Block::from_bytes(&mut test_block_bytes().as_slice())?;
+        first.txn_list = vec![funding.clone()];
+        let mut second = Block::from_bytes(&mut test_block_bytes().as_slice())?;
+        second.block_header.nonce = 1;
+        second.txn_list = vec![spending.clone()];
+        utxo.update(&first, 1);
+        utxo.update(&second, 2);
+
+        let mut restored = UnspentTx::from_bytes(&mut utxo.to_bytes().as_slice())?;
+        assert_eq!(restored.get_tip(), Some(&second.get_header().get_header()));
+        assert_eq!(restored.get_height(), Some(2));
+        assert_eq!(restored.tx_count(), utxo.tx_count());
+        assert_eq!(
+            restored
+                .get_utxo()
+                .get(&OutpointKey::new(&first.get_coinbase().get_id(), 0))
+                .and_then(|coin| coin.get_coinbase_height()),
+            Some(1)
+        );
+        assert_eq!(
+            restored
+                .get_tx_out(&Outpoint::new(funding.get_id(false), 1))
+                .map(|tx_out| tx_out.to_bytes()),
+            Some(TxOut::new(5000, vec![1; 22]).to_bytes())
+        );
+
+        // The undo data is persisted apart, so it must be added back to disconnect the last blocks
+        assert!(!restored.disconnect(&second));
+        let undo = utxo
+            .get_undo_data(&second.get_header().get_header())
+            .cloned();
+        restored.add_undo(BlockUndo::new(
+            second.get_header().get_header(),
+            undo.unwrap_or_default(),
+        ));
+        assert!(restored.disconnect(&second));
+        assert!(restored.contains_key(&Outpoint::new(funding.get_id(false), 0)));
+        assert!(
+            UnspentTx::from_bytes(&mut UnspentTx::new().to_bytes().as_slice())?
+                .get_tip()
+                .is_none()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_coinbase_outputs_mature_after_a_hundred_blocks() -> Result<(), MessageError> {
+        let mut utxo = UnspentTx::new();
+        let block = Block::from_bytes(&mut test_block_bytes().as_slice())?;
+        let coinbase_output = Outpoint::new(block.get_coinbase().get_id(), 0);
+        let funding = Transaction::new(
+            1,
+            vec![TxIn::new(vec![0; 32], 0, vec![], 0xffffffff)],
+            vec![TxOut::new(10000, vec![0; 25])],
+            0,
+            false,
+        );
+        utxo.update(&block, 500);
+        utxo.update_transaction(&funding);
+
+        assert!(utxo.contains_key(&coinbase_output));
+        assert!(!utxo.is_mature(&coinbase_output));
+        assert!(utxo.is_mature(&Outpoint::new(funding.get_id(false), 0)));
+
+        utxo.height = Some(500 + COINBASE_MATURITY - 2);
+        assert!(!utxo.is_mature(&coinbase_output));
+        utxo.height = Some(500 + COINBASE_MATURITY - 1);
+        assert!(utxo.is_mature(&coinbase_output));
+        Ok(())
+    }
+
+    #[test]
+    fn test_outputs_are_indexed_by_script() -> Result<(), MessageError> {
+        let mut utxo = UnspentTx::new();
+        let funding = Transaction::new(
+            1,
+            vec![TxIn::new(vec![0; 32], 0, vec![], 0xffffffff)],
+            vec![
+                TxOut::new(10000, vec![0; 25]),
+                TxOut::new(5000, vec![1; 22]),
+                TxOut::new(2500, vec![0; 25]),
+            ],
+            0,
+            false,
+        );
+        let spending = Transaction::new(
+            1,
+            vec![TxIn::new(funding.get_id(false), 0, vec![], 0xffffffff)],
+            vec![TxOut::new(9000, vec![1; 22])],
+            0,
+            false,
+        );
+        let mut block = Block::from_bytes(&mut test_block_bytes().as_slice())?;
+        block.txn_list = vec![spending.clone()];
+
+        utxo.update_transaction(&funding);
+        assert_eq!(utxo.get_by_script(&[0; 25]).len(), 2);
+        assert_eq!(utxo.get_by_script(&[1; 22]).len(), 1);
+
+        utxo.update(&block, 1);
+        let outpoints: Vec<OutpointKey> = utxo
+            .get_by_script(&[0; 25])
+            .iter()
+            .map(|(key, _)| *key)
+            .collect();
+        assert_eq!(outpoints, vec![OutpointKey::new(&funding.get_id(false), 2)]);
+        assert_eq!(utxo.get_by_script_hash(&script_hash(&[1; 22])).len(), 2);
+
+        assert!(utxo.disconnect(&block));
+        assert_eq!(utxo.get_by_script(&[0; 25]).len(), 2);
+        assert_eq!(utxo.get_by_script(&[1; 22]).len(), 1);
+        assert!(utxo.get_by_script(&[2; 22]).is_empty());
+
+        let restored = UnspentTx::from_bytes(&mut utxo.to_bytes().as_slice())?;
+        assert_eq!(restored.get_by_script(&[0; 25]).len(), 2);
+        Ok(())
+    }
+}
@@ -1,4 +1,5 @@
 use crate::messages::compact_size::CompactSizeUInt;
+use crate::messages::message_constants::MAX_PAYLOAD_SIZE;
 use crate::messages::message_error::MessageError;
 use std::io::Read;
 use std::vec;
@@ -40,11 +41,12 @@ impl Witness {
     ///
     /// * `stream` - A mutable reference to a type that implements the `Read` trait.
     pub fn from_bytes(stream: &mut dyn Read) -> Result<Witness, MessageError> {
-        let stack_item_count = CompactSizeUInt::from_bytes(stream)?;
+        let stack_item_count =
+            CompactSizeUInt::from_bytes_bounded(stream, MAX_PAYLOAD_SIZE as u64)?;
         let mut stack_items = vec![];
 
         for _ in 0..stack_item_count.value() {
-            let item_length = CompactSizeUInt::from_bytes(stream)?;
+            let item_length = CompactSizeUInt::from_bytes_bounded(stream, MAX_PAYLOAD_SIZE as u64)?;
 
             let mut item = vec![0u8; item_length.value().try_into()?];
 
@@ -70,14 +72,19 @@ impl Witness {
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut buffer = vec![];
 
-        buffer.extend(self.stack_item_count.to_bytes());
+        self.to_bytes_into(&mut buffer);
+        buffer
+    }
+
+    /// Appends the byte representation of the `Witness` to the given buffer, without copying its
+    /// stack items first.
+    pub fn to_bytes_into(&self, buffer: &mut Vec<u8>) {
+        self.stack_item_count.to_bytes_into(buffer);
 
-        for item in self.stack_items.clone() {
-            buffer.extend(CompactSizeUInt::from_number(item.len() as u64).to_bytes());
+        for item in self.stack_items.iter() {
+            CompactSizeUInt::from_number(item.len() as u64).to_bytes_into(buffer);
             buffer.extend(item);
         }
-
-        buffer
     }
 
     /// Retrieves the public key from the Witness.
@@ -1,21 +1,383 @@
 use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, File, OpenOptions},
+    io::{BufReader, Read, Seek, Write},
     sync::{mpsc::Receiver, Arc, Mutex},
     thread::{self, JoinHandle},
 };
 
-use crate::block_mod::{block::Block, blockchain::BlockChain, utxo::UnspentTx};
+use crate::{
+    block_mod::{
+        block::Block, block_constants::MAX_REORG_DEPTH, block_filter::BlockFilter,
+        block_header::BlockHeader, block_undo::BlockUndo, blockchain::BlockChain,
+        header_chain::HeaderChain, utxo::UnspentTx,
+    },
+    messages::{
+        compact_size::CompactSizeUInt,
+        message_error::MessageError,
+        read_from_bytes::{decode_hex, read_vec_from_bytes},
+    },
+    network::{
+        bitcoin_network::Network,
+        network_constants::{SNAPSHOT_MAGIC, UTXO_FLUSH_INTERVAL},
+        network_error::NetworkError,
+    },
+};
+
+/// Opens the file where downloaded blocks are persisted, creating it if it does not exist.
+fn open_blocks_file(blocks_file_path: &str) -> Result<File, NetworkError> {
+    OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(blocks_file_path)
+        .map_err(|_| NetworkError::BlockDownload)
+}
+
+/// Builds the compact filter of a block that was just connected to the UTXO set, from the outputs
+/// it spent.
+fn add_filter(blockchain: &mut BlockChain, utxo: &UnspentTx, block_hash: &Vec<u8>) {
+    let spent = utxo.get_undo_data(block_hash).map(Vec::as_slice);
+    blockchain.add_filter(block_hash, spent.unwrap_or_default());
+}
+
+/// Writes the undo data a block left in the UTXO set when it was connected to the undo file.
+fn write_undo(file: &mut File, utxo: &UnspentTx, block_hash: &Vec<u8>) -> Result<(), NetworkError> {
+    if let Some(spent) = utxo.get_undo_data(block_hash) {
+        file.write_all(&BlockUndo::new(block_hash.clone(), spent.clone()).to_bytes())?;
+    }
+    Ok(())
+}
+
+/// Reads the undo data persisted alongside the blocks, discarding the record that was being
+/// written if the node died in the middle of it.
+///
+/// # Arguments
+///
+/// * `undo_file_path` - The path of the file where the undo data of the blocks is persisted.
+/// * `wanted` - The hashes of the blocks whose undo data is kept.
+///
+/// # Returns
+///
+/// The undo data of the wanted blocks keyed by block hash, or a `NetworkError` if the file cannot
+/// be opened.
+fn read_undo(
+    undo_file_path: &str,
+    wanted: &HashSet<Vec<u8>>,
+) -> Result<HashMap<Vec<u8>, BlockUndo>, NetworkError> {
+    let file = open_blocks_file(undo_file_path)?;
+    let mut reader = BufReader::new(file.try_clone()?);
+    let mut records = HashMap::new();
+    let mut valid_length = 0;
+
+    while let Ok(undo) = BlockUndo::from_bytes(&mut reader) {
+        valid_length = reader.stream_position()?;
+        if wanted.contains(undo.get_block_hash()) {
+            records.insert(undo.get_block_hash().clone(), undo);
+        }
+    }
+
+    file.set_len(valid_length)?;
+    Ok(records)
+}
+
+/// Writes the UTXO set to its file, followed by the compact filters of the blocks connected to it,
+/// since building them again would require the outputs those blocks spent.
+///
+/// The set is written to a temporary file that then replaces the UTXO file, so the UTXO file is
+/// never left half-written.
+fn write_utxo(
+    utxo_file_path: &str,
+    utxo: &UnspentTx,
+    blockchain: &BlockChain,
+) -> Result<(), NetworkError> {
+    let temp_file_path = format!("{}.tmp", utxo_file_path);
+    let mut bytes = utxo.to_bytes();
+
+    bytes.extend(CompactSizeUInt::from_number(blockchain.get_filters().len() as u64).to_bytes());
+    for (block_hash, filter) in blockchain.get_filters() {
+        let filter_bytes = filter.to_bytes();
+
+        bytes.extend(block_hash);
+        bytes.extend(CompactSizeUInt::from_number(filter_bytes.len() as u64).to_bytes());
+        bytes.extend(filter_bytes);
+    }
+
+    let mut file = File::create(&temp_file_path)?;
+    file.write_all(&bytes)?;
+    file.sync_all()?;
+    fs::rename(temp_file_path, utxo_file_path)?;
+    Ok(())
+}
+
+/// Reads the UTXO set persisted by a previous run, along with the compact filters of the blocks
+/// connected to it.
+///
+/// # Returns
+///
+/// The set and the filters keyed by block hash, or a `MessageError` if the file does not exist or
+/// is malformed.
+fn read_utxo(
+    utxo_file_path: &str,
+) -> Result<(UnspentTx, HashMap<Vec<u8>, BlockFilter>), MessageError> {
+    let file = File::open(utxo_file_path).map_err(|_| MessageError::ReadFromBytes)?;
+    let mut reader = BufReader::new(file);
+    let utxo = UnspentTx::from_bytes(&mut reader)?;
+    let mut filters = HashMap::new();
+
+    for _ in 0..CompactSizeUInt::from_bytes(&mut reader)?.value() {
+        let block_hash = read_vec_from_bytes(&mut reader, 32)?;
+        let length = CompactSizeUInt::from_bytes(&mut reader)?.value() as usize;
+        let filter = read_vec_from_bytes(&mut reader, length)?;
+        filters.insert(block_hash, BlockFilter::from_bytes(filter)?);
+    }
+
+    if reader.read(&mut [0])? != 0 {
+        return Err(MessageError::ReadFromBytes);
+    }
+    Ok((utxo, filters))
+}
+
+/// Loads the blocks persisted by previous runs into the blockchain and the UTXO set.
+///
+/// If the UTXO set persisted by a previous run is connected to one of the loaded blocks, it is
+/// restored along with the undo data of the last `MAX_REORG_DEPTH` blocks connected to it, and
+/// only the blocks loaded after that one are connected to it. Otherwise, the set is rebuilt from
+/// every loaded block.
+///
+/// If the node died while a block was being written, the incomplete block is discarded so new
+/// blocks are appended right after the last complete one.
+///
+/// # Arguments
+///
+/// * `blocks_file_path` - The path of the file where downloaded blocks are persisted.
+/// * `utxo_file_path` - The path of the file where the UTXO set is persisted.
+/// * `undo_file_path` - The path of the file where the undo data of the blocks is persisted.
+/// * `headers` - The downloaded headers, in chain order, which give the height of the blocks.
+/// * `start_height` - The height of the first header.
+/// * `blockchain` - The shared blockchain.
+/// * `utxo` - The shared unspent transaction set.
+///
+/// # Returns
+///
+/// The hashes of the loaded blocks, or a `NetworkError` if the file cannot be opened or the shared
+/// structures cannot be locked.
+pub fn load_blocks(
+    blocks_file_path: &str,
+    utxo_file_path: &str,
+    undo_file_path: &str,
+    headers: &[BlockHeader],
+    start_height: u32,
+    blockchain: &Arc<Mutex<BlockChain>>,
+    utxo: &Arc<Mutex<UnspentTx>>,
+) -> Result<HashSet<Vec<u8>>, NetworkError> {
+    let file = open_blocks_file(blocks_file_path)?;
+    let mut reader = BufReader::new(file.try_clone()?);
+    let mut downloaded = HashSet::new();
+    let mut loaded = vec![];
+    let mut valid_length = 0;
+
+    let mut locked_utxo = utxo.lock().map_err(|_| NetworkError::BlockDownload)?;
+    let mut locked_blockchain = blockchain.lock().map_err(|_| NetworkError::BlockDownload)?;
+
+    while let Ok(block) = Block::from_bytes(&mut reader) {
+        valid_length = reader.stream_position()?;
+        let block_hash = block.get_header().get_header();
+        downloaded.insert(block_hash.clone());
+        loaded.push(block_hash);
+        locked_blockchain.add(block);
+    }
+
+    let mut restored = 0;
+    if let Ok((persisted, filters)) = read_utxo(utxo_file_path) {
+        let tip_position = persisted
+            .get_tip()
+            .and_then(|tip| loaded.iter().position(|block_hash| block_hash == tip));
+
+        if let Some(position) = tip_position {
+            *locked_utxo = persisted;
+            for (block_hash, filter) in filters {
+                locked_blockchain.insert_filter(block_hash, filter);
+            }
+            restored = position + 1;
+        }
+    }
+
+    let undo_window = &loaded[restored.saturating_sub(MAX_REORG_DEPTH)..restored];
+    let mut undo_records = read_undo(undo_file_path, &undo_window.iter().cloned().collect())?;
+    for block_hash in undo_window {
+        if let Some(undo) = undo_records.remove(block_hash) {
+            locked_utxo.add_undo(undo);
+        }
+    }
 
+    let heights: HashMap<Vec<u8>, u32> = headers
+        .iter()
+        .zip(start_height..)
+        .map(|(header, height)| (header.get_header(), height))
+        .filter(|(block_hash, _)| downloaded.contains(block_hash))
+        .collect();
+
+    for block_hash in &loaded[restored..] {
+        if let Some(block) = locked_blockchain.get_block(block_hash) {
+            let height = height_of(&heights, &locked_utxo, block_hash);
+            locked_utxo.update(block, height);
+        }
+        add_filter(&mut locked_blockchain, &locked_utxo, block_hash);
+    }
+
+    println!(
+        "The UTXO set holds {} unspent outputs, taking about {} KB...",
+        locked_utxo.tx_count(),
+        locked_utxo.memory_usage() / 1024
+    );
+    drop(locked_blockchain);
+    drop(locked_utxo);
+
+    file.set_len(valid_length)?;
+
+    println!(
+        "{} blocks are already downloaded, {} of them restored from the UTXO set...",
+        downloaded.len(),
+        restored
+    );
+    Ok(downloaded)
+}
+
+/// Indexes the hashes of the headers by their height.
+///
+/// # Arguments
+///
+/// * `headers` - The headers, in chain order.
+/// * `start_height` - The height of the first header.
+pub fn index_heights(headers: &[BlockHeader], start_height: u32) -> HashMap<Vec<u8>, u32> {
+    headers
+        .iter()
+        .zip(start_height..)
+        .map(|(header, height)| (header.get_header(), height))
+        .collect()
+}
+
+/// Returns the height of a block from the indexed heights or, if its header is not indexed, as the
+/// height following the last block connected to the UTXO set.
+fn height_of(heights: &HashMap<Vec<u8>, u32>, utxo: &UnspentTx, block_hash: &Vec<u8>) -> u32 {
+    match heights.get(block_hash) {
+        Some(height) => *height,
+        None => utxo.get_height().map_or(0, |height| height + 1),
+    }
+}
+
+/// Finds the tip of the persisted blocks: the last header of the run of consecutive headers whose
+/// blocks are stored, starting at the first stored one.
+///
+/// # Returns
+///
+/// The position of the first stored header and the position of the tip within the headers, or
+/// `None` if no block is stored.
+fn find_stored_tip(headers: &[BlockHeader], blockchain: &BlockChain) -> Option<(usize, usize)> {
+    let is_stored = |header: &BlockHeader| blockchain.get_block(&header.get_header()).is_some();
+    let first = headers.iter().position(is_stored)?;
+    let run = headers[first..]
+        .iter()
+        .take_while(|header| is_stored(header))
+        .count();
+
+    Some((first, first + run - 1))
+}
+
+/// Makes the tip of the persisted blocks the tip of the blockchain, so the blocks received from
+/// the network are connected on top of it, and indexes the persisted blocks up to it by height.
+///
+/// # Arguments
+///
+/// * `headers` - The downloaded headers, in chain order.
+/// * `start_height` - The height of the first header.
+/// * `blockchain` - The shared blockchain, holding the persisted blocks.
+///
+/// # Returns
+///
+/// The position of the first header after the tip, from which blocks must be downloaded, or a
+/// `NetworkError` if the blockchain cannot be locked.
+pub fn restore_tip(
+    headers: &[BlockHeader],
+    start_height: u32,
+    blockchain: &Arc<Mutex<BlockChain>>,
+) -> Result<usize, NetworkError> {
+    let mut locked_blockchain = blockchain.lock().map_err(|_| NetworkError::BlockDownload)?;
+
+    let (first, tip) = match find_stored_tip(headers, &locked_blockchain) {
+        Some(positions) => positions,
+        None => return Ok(0),
+    };
+    locked_blockchain.set_main_chain(&headers[first..=tip], start_height + first as u32);
+    Ok(tip + 1)
+}
+
+/// Spawns the thread that adds the downloaded blocks to the blockchain and the UTXO set, and
+/// persists them so the download can be resumed if the node stops.
+///
+/// The undo data every block leaves in the UTXO set is appended to the undo file as the block is
+/// connected, and the UTXO set is flushed to its file every `UTXO_FLUSH_INTERVAL` blocks, so a
+/// restart only connects the blocks downloaded since the last flush.
+///
+/// # Arguments
+///
+/// * `blockchain` - The shared blockchain.
+/// * `utxo` - The shared unspent transaction set.
+/// * `rx` - The receiver through which downloaded blocks arrive.
+/// * `heights` - The heights of the blocks to download, indexed by their hash.
+/// * `blocks_file_path` - The path of the file where downloaded blocks are persisted.
+/// * `utxo_file_path` - The path of the file where the UTXO set is persisted.
+/// * `undo_file_path` - The path of the file where the undo data of the blocks is persisted.
 pub fn download_blocks(
     blockchain: Arc<Mutex<BlockChain>>,
     utxo: Arc<Mutex<UnspentTx>>,
     rx: Receiver<Block>,
+    heights: HashMap<Vec<u8>, u32>,
+    blocks_file_path: &str,
+    utxo_file_path: &str,
+    undo_file_path: &str,
 ) -> JoinHandle<()> {
+    let blocks_file_path = blocks_file_path.to_string();
+    let utxo_file_path = utxo_file_path.to_string();
+    let undo_file_path = undo_file_path.to_string();
+
     thread::spawn(move || {
+        let mut file = match open_blocks_file(&blocks_file_path) {
+            Ok(file) => Some(file),
+            Err(_) => {
+                println!("Failed to open blocks file, downloaded blocks will not be saved.");
+                None
+            }
+        };
+        let mut undo_file = match open_blocks_file(&undo_file_path) {
+            Ok(undo_file) => Some(undo_file),
+            Err(_) => {
+                println!("Failed to open undo file, undo data will not be saved.");
+                None
+            }
+        };
+
         while let Ok(block) = rx.recv() {
+            if let Some(blocks_file) = file.as_mut() {
+                if blocks_file.write_all(&block.to_bytes()).is_err() {
+                    println!("Failed to save downloaded block.");
+                }
+            }
+
             if let Ok(mut locked_utxo) = utxo.lock() {
                 if let Ok(mut locked_blockchain) = blockchain.lock() {
-                    locked_utxo.update(&block);
+                    let block_hash = block.get_header().get_header();
+                    let height = height_of(&heights, &locked_utxo, &block_hash);
+                    locked_utxo.update(&block, height);
                     locked_blockchain.add(block);
+                    add_filter(&mut locked_blockchain, &locked_utxo, &block_hash);
+
+                    if let Some(undo_file) = undo_file.as_mut() {
+                        if write_undo(undo_file, &locked_utxo, &block_hash).is_err() {
+                            println!("Failed to save the undo data of a downloaded block.");
+                        }
+                    }
 
                     if locked_blockchain.cant_blocks() % 1000 == 0 {
                         println!(
@@ -24,6 +386,12 @@ pub fn download_blocks(
                         );
                     }
 
+                    if locked_blockchain.cant_blocks() % UTXO_FLUSH_INTERVAL == 0
+                        && write_utxo(&utxo_file_path, &locked_utxo, &locked_blockchain).is_err()
+                    {
+                        println!("Failed to flush the UTXO set.");
+                    }
+
                     drop(locked_blockchain);
                 }
                 drop(locked_utxo);
@@ -31,3 +399,469 @@ pub fn download_blocks(
         }
     })
 }
+
+/// Persists every block of the blockchain in the order of the header chain, including the blocks
+/// received after the download, so the next start loads them and rebuilds the UTXO set from them.
+///
+/// The blocks are written to a temporary file that then replaces the blocks file, so the blocks
+/// file is never left half-written.
+///
+/// # Arguments
+///
+/// * `blocks_file_path` - The path of the file where blocks are persisted.
+/// * `blockchain` - The shared blockchain.
+/// * `header_chain` - The shared header chain, which gives the order of the blocks.
+///
+/// # Returns
+///
+/// The amount of blocks persisted, or a `NetworkError` if the file cannot be written or the shared
+/// structures cannot be locked.
+pub fn save_blocks(
+    blocks_file_path: &str,
+    blockchain: &Arc<Mutex<BlockChain>>,
+    header_chain: &Arc<Mutex<HeaderChain>>,
+) -> Result<usize, NetworkError> {
+    let temp_file_path = format!("{}.tmp", blocks_file_path);
+    let mut file = File::create(&temp_file_path)?;
+    let mut saved = 0;
+
+    let locked_blockchain = blockchain.lock().map_err(|_| NetworkError::BlockDownload)?;
+    let locked_header_chain = header_chain
+        .lock()
+        .map_err(|_| NetworkError::BlockDownload)?;
+
+    for header in locked_header_chain.get_headers() {
+        if let Some(block) = locked_blockchain.get_block(&header.get_header()) {
+            file.write_all(&block.to_bytes())?;
+            saved += 1;
+        }
+    }
+
+    drop(locked_header_chain);
+    drop(locked_blockchain);
+
+    file.sync_all()?;
+    fs::rename(temp_file_path, blocks_file_path)?;
+    Ok(saved)
+}
+
+/// Persists the UTXO set, so the next start restores it instead of connecting every block again.
+///
+/// # Arguments
+///
+/// * `utxo_file_path` - The path of the file where the UTXO set is persisted.
+/// * `utxo` - The shared unspent transaction set.
+/// * `blockchain` - The shared blockchain, holding the compact filters persisted along with the set.
+///
+/// # Returns
+///
+/// The amount of unspent outputs persisted, or a `NetworkError` if the file cannot be written or
+/// the shared structures cannot be locked.
+pub fn save_utxo(
+    utxo_file_path: &str,
+    utxo: &Arc<Mutex<UnspentTx>>,
+    blockchain: &Arc<Mutex<BlockChain>>,
+) -> Result<usize, NetworkError> {
+    let locked_utxo = utxo.lock().map_err(|_| NetworkError::BlockDownload)?;
+    let locked_blockchain = blockchain.lock().map_err(|_| NetworkError::BlockDownload)?;
+
+    write_utxo(utxo_file_path, &locked_utxo, &locked_blockchain)?;
+    Ok(locked_utxo.tx_count())
+}
+
+/// Persists the undo data kept in the UTXO set, in the order of the header chain, so the next start
+/// can still disconnect the last blocks connected to the set.
+///
+/// The undo data is written to a temporary file that then replaces the undo file, so the undo file
+/// is never left half-written.
+///
+/// # Arguments
+///
+/// * `undo_file_path` - The path of the file where the undo data of the blocks is persisted.
+/// * `utxo` - The shared unspent transaction set.
+/// * `header_chain` - The shared header chain, which gives the order of the blocks.
+///
+/// # Returns
+///
+/// The amount of blocks whose undo data was persisted, or a `NetworkError` if the file cannot be
+/// written or the shared structures cannot be locked.
+pub fn save_undo(
+    undo_file_path: &str,
+    utxo: &Arc<Mutex<UnspentTx>>,
+    header_chain: &Arc<Mutex<HeaderChain>>,
+) -> Result<usize, NetworkError> {
+    let temp_file_path = format!("{}.tmp", undo_file_path);
+    let mut file = File::create(&temp_file_path)?;
+    let mut saved = 0;
+
+    let locked_utxo = utxo.lock().map_err(|_| NetworkError::BlockDownload)?;
+    let locked_header_chain = header_chain
+        .lock()
+        .map_err(|_| NetworkError::BlockDownload)?;
+
+    for header in locked_header_chain.get_headers() {
+        let block_hash = header.get_header();
+
+        if locked_utxo.get_undo_data(&block_hash).is_some() {
+            write_undo(&mut file, &locked_utxo, &block_hash)?;
+            saved += 1;
+        }
+    }
+
+    drop(locked_header_chain);
+    drop(locked_utxo);
+
+    file.sync_all()?;
+    fs::rename(temp_file_path, undo_file_path)?;
+    Ok(saved)
+}
+
+/// Dumps the UTXO set to a snapshot file other nodes of the network can be bootstrapped from: the
+/// snapshot magic and the start string of the network, followed by the set along with the hash and
+/// height of its tip. The undo data of the set is left out.
+///
+/// # Arguments
+///
+/// * `snapshot_file_path` - The path of the snapshot file.
+/// * `utxo` - The shared unspent transaction set.
+/// * `network` - The network the set belongs to.
+///
+/// # Returns
+///
+/// The height of the tip of the set and the content hash of the set, which must be added to the
+/// snapshots of the network for nodes to load it, or a `NetworkError` if the set has no tip, the
+/// file cannot be written or the set cannot be locked.
+pub fn dump_snapshot(
+    snapshot_file_path: &str,
+    utxo: &Arc<Mutex<UnspentTx>>,
+    network: Network,
+) -> Result<(u32, Vec<u8>), NetworkError> {
+    let temp_file_path = format!("{}.tmp", snapshot_file_path);
+    let locked_utxo = utxo.lock().map_err(|_| NetworkError::BlockDownload)?;
+    let height = locked_utxo
+        .get_height()
+        .ok_or(NetworkError::InvalidSnapshot)?;
+
+    let mut bytes = SNAPSHOT_MAGIC.to_vec();
+    bytes.extend(network.get_start_string());
+    bytes.extend(locked_utxo.to_bytes());
+    let content_hash = locked_utxo.content_hash();
+    drop(locked_utxo);
+
+    let mut file = File::create(&temp_file_path)?;
+    file.write_all(&bytes)?;
+    file.sync_all()?;
+    fs::rename(temp_file_path, snapshot_file_path)?;
+    Ok((height, content_hash))
+}
+
+/// Reads a snapshot dumped by `dump_snapshot`.
+///
+/// # Errors
+///
+/// Returns `NetworkError::WrongNetwork` if the snapshot belongs to another network, or
+/// `NetworkError::InvalidSnapshot` if the file does not exist, is malformed or the set has no tip.
+fn read_snapshot(snapshot_file_path: &str, network: Network) -> Result<UnspentTx, NetworkError> {
+    let file = File::open(snapshot_file_path).map_err(|_| NetworkError::InvalidSnapshot)?;
+    let mut reader = BufReader::new(file);
+    let invalid = |_| NetworkError::InvalidSnapshot;
+
+    let magic = read_vec_from_bytes(&mut reader, SNAPSHOT_MAGIC.len()).map_err(invalid)?;
+    let start_string = read_vec_from_bytes(&mut reader, 4).map_err(invalid)?;
+    if magic != SNAPSHOT_MAGIC {
+        return Err(NetworkError::InvalidSnapshot);
+    }
+    if start_string != network.get_start_string() {
+        return Err(NetworkError::WrongNetwork);
+    }
+
+    let utxo = UnspentTx::from_bytes(&mut reader).map_err(invalid)?;
+    if utxo.get_tip().is_none() || reader.read(&mut [0])? != 0 {
+        return Err(NetworkError::InvalidSnapshot);
+    }
+    Ok(utxo)
+}
+
+/// Checks a snapshot against the trusted snapshot hashes and the downloaded headers: a trusted
+/// hash must be known for the height of its tip and match its content hash, and its tip must be
+/// the header at that height.
+///
+/// # Returns
+///
+/// The position of the tip of the snapshot within the headers, or
+/// `NetworkError::InvalidSnapshot` if the snapshot is not trusted.
+fn check_snapshot(
+    utxo: &UnspentTx,
+    snapshot_hashes: &[(u32, &str)],
+    headers: &[BlockHeader],
+    start_height: u32,
+) -> Result<usize, NetworkError> {
+    let height = utxo.get_height().ok_or(NetworkError::InvalidSnapshot)?;
+    let (_, hash) = snapshot_hashes
+        .iter()
+        .find(|(snapshot_height, _)| *snapshot_height == height)
+        .ok_or(NetworkError::InvalidSnapshot)?;
+
+    if decode_hex(hash).map_err(|_| NetworkError::InvalidSnapshot)? != utxo.content_hash() {
+        return Err(NetworkError::InvalidSnapshot);
+    }
+
+    let position = height
+        .checked_sub(start_height)
+        .ok_or(NetworkError::InvalidSnapshot)? as usize;
+    match headers.get(position) {
+        Some(header) if Some(&header.get_header()) == utxo.get_tip() => Ok(position),
+        _ => Err(NetworkError::InvalidSnapshot),
+    }
+}
+
+/// Bootstraps the UTXO set from a snapshot dumped by `dump_snapshot`, so only the blocks after its
+/// tip are downloaded and connected. The snapshot is only loaded if its content hash is one of the
+/// snapshot hashes of the network and its tip belongs to the downloaded headers.
+///
+/// The blocks below the tip are not downloaded, so the transactions they hold are not known.
+///
+/// # Arguments
+///
+/// * `snapshot_file_path` - The path of the snapshot file.
+/// * `network` - The network the node belongs to.
+/// * `headers` - The downloaded headers, in chain order.
+/// * `start_height` - The height of the first header.
+/// * `utxo` - The shared unspent transaction set, which is replaced by the snapshot.
+///
+/// # Returns
+///
+/// The position of the first header after the tip of the snapshot, from which blocks must be
+/// downloaded, or a `NetworkError` if the snapshot cannot be read or is not trusted.
+pub fn load_snapshot(
+    snapshot_file_path: &str,
+    network: Network,
+    headers: &[BlockHeader],
+    start_height: u32,
+    utxo: &Arc<Mutex<UnspentTx>>,
+) -> Result<usize, NetworkError> {
+    let snapshot = read_snapshot(snapshot_file_path, network)?;
+    let position = check_snapshot(
+        &snapshot,
+        network.get_snapshot_hashes(),
+        headers,
+        start_height,
+    )?;
+
+    let mut locked_utxo = utxo.lock().map_err(|_| NetworkError::BlockDownload)?;
+    *locked_utxo = snapshot;
+    Ok(position + 1)
+}
+
+#[cfg(test)]
+mod block_saver_test {
+    use super::*;
+    use crate::block_mod::{
+        block::test_block_bytes, outpoint::Outpoint, transaction::Transaction, tx_out::TxOut,
+    };
+    use crate::messages::read_from_bytes::encode_hex;
+    use std::{fs, sync::mpsc};
+
+    #[test]
+    fn test_saved_blocks_are_loaded() -> Result<(), NetworkError> {
+        let path = "data/test_blocks.bin";
+        let _ = fs::remove_file(path);
+        let bytes = test_block_bytes();
+        let block = Block::from_bytes(&mut bytes.as_slice())?;
+        let hash = block.get_header().get_header();
+
+        let (tx, rx) = mpsc::channel();
+        let handle = download_blocks(
+            Arc::new(Mutex::new(BlockChain::new(Network::Testnet))),
+            Arc::new(Mutex::new(UnspentTx::new())),
+            rx,
+            HashMap::new(),
+            path,
+            "data/test_blocks_utxo.bin",
+            "data/test_blocks_undo.bin",
+        );
+        tx.send(block).map_err(|_| NetworkError::BlockDownload)?;
+        drop(tx);
+        handle.join().map_err(|_| NetworkError::BlockDownload)?;
+
+        // Simulates a block that was being written when the node stopped
+        let mut file = open_blocks_file(path)?;
+        file.write_all(&bytes[..10])?;
+
+        let blockchain = Arc::new(Mutex::new(BlockChain::new(Network::Testnet)));
+        let downloaded = load_blocks(
+            path,
+            "data/test_blocks_utxo.bin",
+            "data/test_blocks_undo.bin",
+            &[],
+            0,
+            &blockchain,
+            &Arc::new(Mutex::new(UnspentTx::new())),
+        )?;
+        let length = fs::metadata(path)?.len();
+        let _ = fs::remove_file(path);
+        let _ = fs::remove_file("data/test_blocks_undo.bin");
+
+        assert!(downloaded.contains(&hash));
+        assert_eq!(length, bytes.len() as u64);
+        Ok(())
+    }
+
+    #[test]
+    fn test_blocks_are_saved_in_chain_order() -> Result<(), NetworkError> {
+        let path = "data/test_saved_blocks.bin";
+        let bytes = test_block_bytes();
+        let block = Block::from_bytes(&mut bytes.as_slice())?;
+        let header_chain = HeaderChain::new(vec![block.get_header().clone()], 0);
+
+        let mut blockchain = BlockChain::new(Network::Testnet);
+        blockchain.add(block);
+        let saved = save_blocks(
+            path,
+            &Arc::new(Mutex::new(blockchain)),
+            &Arc::new(Mutex::new(header_chain)),
+        )?;
+        let saved_bytes = fs::read(path)?;
+        let _ = fs::remove_file(path);
+
+        assert_eq!(saved, 1);
+        assert_eq!(saved_bytes, bytes);
+        Ok(())
+    }
+
+    #[test]
+    fn test_persisted_utxo_is_restored() -> Result<(), NetworkError> {
+        let blocks_path = "data/test_restored_blocks.bin";
+        let utxo_path = "data/test_restored_utxo.bin";
+        let undo_path = "data/test_restored_undo.bin";
+        let block = Block::from_bytes(&mut test_block_bytes().as_slice())?;
+        let hash = block.get_header().get_header();
+        fs::write(blocks_path, block.to_bytes())?;
+
+        // An output no block created proves the set was restored instead of rebuilt
+        let extra = Transaction::new(1, vec![], vec![TxOut::new(1000, vec![0x51])], 0, false);
+        let mut persisted = UnspentTx::new();
+        persisted.update(&block, 0);
+        persisted.update_transaction(&extra);
+        let mut blockchain = BlockChain::new(Network::Testnet);
+        blockchain.add(block);
+        add_filter(&mut blockchain, &persisted, &hash);
+        write_utxo(utxo_path, &persisted, &blockchain)?;
+
+        // Simulates an undo record that was being written when the node stopped
+        let mut undo_file = File::create(undo_path)?;
+        write_undo(&mut undo_file, &persisted, &hash)?;
+        let undo_length = fs::metadata(undo_path)?.len();
+        undo_file.write_all(&hash[..10])?;
+
+        let blockchain = Arc::new(Mutex::new(BlockChain::new(Network::Testnet)));
+        let utxo = Arc::new(Mutex::new(UnspentTx::new()));
+        let loaded = load_blocks(
+            blocks_path,
+            utxo_path,
+            undo_path,
+            &[],
+            0,
+            &blockchain,
+            &utxo,
+        );
+        let restored_undo_length = fs::metadata(undo_path)?.len();
+        let _ = fs::remove_file(blocks_path);
+        let _ = fs::remove_file(utxo_path);
+        let _ = fs::remove_file(undo_path);
+        loaded?;
+
+        let locked_utxo = utxo.lock().map_err(|_| NetworkError::BlockDownload)?;
+        let locked_blockchain = blockchain.lock().map_err(|_| NetworkError::BlockDownload)?;
+        assert_eq!(locked_utxo.get_tip(), Some(&hash));
+        assert_eq!(locked_utxo.tx_count(), persisted.tx_count());
+        assert!(locked_utxo.contains_key(&Outpoint::new(extra.get_id(false), 0)));
+        assert!(locked_blockchain.get_filter(&hash).is_some());
+        assert!(locked_utxo.get_undo_data(&hash).is_some());
+        assert_eq!(restored_undo_length, undo_length);
+        Ok(())
+    }
+
+    #[test]
+    fn test_stored_tip_is_the_end_of_the_first_run() -> Result<(), NetworkError> {
+        let mut blockchain = BlockChain::new(Network::Testnet);
+        let mut headers = vec![];
+
+        for nonce in 0..6 {
+            let mut block = Block::from_bytes(&mut test_block_bytes().as_slice())?;
+            block.block_header.nonce = nonce;
+            headers.push(block.get_header().clone());
+
+            // The first block is not stored, and neither is the one after the run
+            if nonce != 0 && nonce != 3 {
+                blockchain.add(block);
+            }
+        }
+
+        assert_eq!(find_stored_tip(&headers, &blockchain), Some((1, 2)));
+        assert_eq!(find_stored_tip(&headers[..1], &blockchain), None);
+
+        let blockchain = Arc::new(Mutex::new(blockchain));
+        assert_eq!(restore_tip(&headers, 100, &blockchain)?, 3);
+        let locked_blockchain = blockchain.lock().map_err(|_| NetworkError::BlockDownload)?;
+        assert_eq!(
+            locked_blockchain.get_last_block_header(),
+            headers[2].get_header()
+        );
+        assert_eq!(locked_blockchain.tip_height(), Some(102));
+        assert_eq!(
+            locked_blockchain.get_height(&headers[1].get_header()),
+            Some(101)
+        );
+        assert!(locked_blockchain.get_block_by_height(100).is_none());
+        assert!(locked_blockchain
+            .get_height(&headers[4].get_header())
+            .is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_snapshot_is_only_loaded_if_trusted() -> Result<(), NetworkError> {
+        let snapshot_path = "data/test_snapshot.bin";
+        let mut headers = vec![];
+        let mut utxo = UnspentTx::new();
+
+        for nonce in 0..3 {
+            let mut block = Block::from_bytes(&mut test_block_bytes().as_slice())?;
+            block.block_header.nonce = nonce;
+            block.coinbase.lock_time = nonce;
+            headers.push(block.get_header().clone());
+            utxo.update(&block, 100 + nonce);
+        }
+        let utxo = Arc::new(Mutex::new(utxo));
+
+        let dumped = dump_snapshot(snapshot_path, &utxo, Network::Regtest);
+        let read = read_snapshot(snapshot_path, Network::Regtest);
+        let wrong_network = read_snapshot(snapshot_path, Network::Testnet);
+        let _ = fs::remove_file(snapshot_path);
+        let (height, content_hash) = dumped?;
+        let snapshot = read?;
+        assert!(matches!(wrong_network, Err(NetworkError::WrongNetwork)));
+
+        assert_eq!(height, 102);
+        assert_eq!(snapshot.content_hash(), content_hash);
+        assert_eq!(snapshot.tx_count(), 3);
+        assert_eq!(snapshot.get_tip(), Some(&headers[2].get_header()));
+
+        let hex = encode_hex(&content_hash).map_err(|_| NetworkError::InvalidSnapshot)?;
+        assert_eq!(check_snapshot(&snapshot, &[(102, &hex)], &headers, 100)?, 2);
+        assert!(check_snapshot(&snapshot, &[(101, &hex)], &headers, 100).is_err());
+        assert!(check_snapshot(&snapshot, &[(102, &hex)], &headers, 101).is_err());
+
+        let mut tampered = UnspentTx::from_bytes(&mut snapshot.to_bytes().as_slice())?;
+        tampered.update_transaction(&Transaction::new(
+            1,
+            vec![],
+            vec![TxOut::new(1000, vec![0x51])],
+            0,
+            false,
+        ));
+        assert!(check_snapshot(&tampered, &[(102, &hex)], &headers, 100).is_err());
+        Ok(())
+    }
+}
@@ -1,28 +1,149 @@
 use std::{
-    net::{SocketAddr, TcpListener, TcpStream},
+    collections::HashMap,
+    io::ErrorKind,
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream},
     sync::{
         mpsc::{self, Receiver, Sender},
         Arc, Mutex,
     },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
 };
 
 use node::{
     block_mod::{
-        block::Block, block_header::BlockHeader, blockchain::BlockChain, mempool::Mempool,
+        block::Block, block_header::BlockHeader, blockchain::BlockChain, chain_state::ChainState,
+        header_chain::HeaderChain, invalid_blocks::InvalidBlocks, mempool::Mempool,
         utxo::UnspentTx,
     },
-    block_saver::download_blocks,
+    block_saver::{
+        download_blocks, dump_snapshot, index_heights, load_blocks, load_snapshot, restore_tip,
+        save_blocks, save_undo, save_utxo,
+    },
+    messages::read_from_bytes::encode_hex,
     network::{
-        block_download::{block_download, take_streams},
+        addr_manager::AddrManager,
+        anchors::Anchors,
+        block_download::block_download,
         broadcasting::broadcasting,
+        event_bus::EventBus,
         handshake::handshake,
-        headers_download::headers_download,
+        headers_download::{get_start_height, headers_download},
+        health_monitor::monitor_health,
+        message_capture::MessageCapture,
+        network_constants::{
+            ANCHORS_FILE_PATH, BANNED_PEERS_FILE_PATH, BLOCKS_FILE_PATH, INVALID_BLOCKS_FILE_PATH,
+            PEER_HEIGHTS_FILE_PATH, UNDO_FILE_PATH, UTXO_FILE_PATH, WALLET_ACCEPT_POLL_MILLIS,
+            WALLET_EVENTS_CAPACITY,
+        },
+        network_time::NetworkTime,
+        peer_features::PeerFeatures,
+        peer_heights::PeerHeights,
+        peer_pool::PeerPool,
+        peer_scoring::PeerScores,
+        rate_limiter::BandwidthLimits,
+        rebroadcast::{rebroadcast_wallet_txs, WalletTxs},
+        shutdown::ShutdownCoordinator,
+        sync_monitor::monitor_sync,
+        sync_progress::SyncProgress,
     },
     settings_mod::{settings::Settings, settings_error::SettingError},
-    wallet_utils::update_wallet::update_wallet,
+    wallet_utils::{event_log::EventLog, update_wallet::update_wallet},
 };
 
+/// Waits for a wallet to connect to the listener, giving up if a shutdown is requested
+/// meanwhile.
+fn accept_wallet(listener: &TcpListener, shutdown: &ShutdownCoordinator) -> Option<TcpStream> {
+    if let Err(err) = listener.set_nonblocking(true) {
+        println!(
+            "Attempt to configure listener for wallet has failed: {}.",
+            err
+        );
+        return None;
+    }
+
+    while !shutdown.is_requested() {
+        match listener.accept() {
+            Ok((wallet, _)) => match wallet.set_nonblocking(false) {
+                Ok(()) => return Some(wallet),
+                Err(err) => println!(
+                    "Attempt to configure wallet connection has failed: {}.",
+                    err
+                ),
+            },
+            Err(err) if err.kind() == ErrorKind::WouldBlock => {
+                thread::sleep(Duration::from_millis(WALLET_ACCEPT_POLL_MILLIS));
+            }
+            Err(err) => {
+                println!(
+                    "Attempt to establish connection with wallet has failed: {}.",
+                    err
+                );
+                return None;
+            }
+        }
+    }
+    None
+}
+
+/// Accepts wallet connections until a shutdown is requested, serving each of them in its own
+/// thread, so several wallets, or a wallet reconnecting, are served at the same time. Every thread
+/// shares the chain state, the peer pool and the transactions broadcast for the wallets.
+///
+/// # Returns
+///
+/// The handles of the threads still serving wallets, to be joined once the shutdown is requested.
+fn serve_wallets(
+    listener: &TcpListener,
+    shutdown: &ShutdownCoordinator,
+    chain_state: &ChainState,
+    settings: &Arc<Settings>,
+    peer_pool: &Arc<Mutex<PeerPool>>,
+    wallet_txs: &Arc<Mutex<WalletTxs>>,
+    start_time: Instant,
+) -> Vec<JoinHandle<()>> {
+    let mut handles_wallets: Vec<JoinHandle<()>> = vec![];
+
+    while let Some(wallet) = accept_wallet(listener, shutdown) {
+        if shutdown.register(&wallet).is_err() {
+            println!("Attempt to register wallet connection for shutdown has failed.");
+        }
+        if let Ok(address) = wallet.peer_addr() {
+            println!("Wallet connected from {}.", address);
+        }
+
+        let chain_state = chain_state.clone();
+        let settings = settings.clone();
+        let peer_pool = peer_pool.clone();
+        let wallet_txs = wallet_txs.clone();
+        let shutdown = shutdown.clone();
+        handles_wallets.retain(|handle| !handle.is_finished());
+        handles_wallets.push(thread::spawn(move || {
+            let connection = wallet.try_clone();
+
+            if let Err(err) = update_wallet(
+                wallet,
+                chain_state,
+                settings,
+                peer_pool,
+                wallet_txs,
+                start_time,
+            ) {
+                if !shutdown.is_requested() {
+                    println!("Attempt to communicate with wallet has failed: {:?}.", err);
+                }
+            }
+            // The events pushed to the wallet hold the connection open otherwise
+            if let Ok(connection) = connection {
+                let _ = connection.shutdown(Shutdown::Both);
+            }
+        }));
+    }
+    handles_wallets
+}
+
 fn main() {
+    let start_time = Instant::now();
     let args: Vec<String> = std::env::args().collect();
 
     if args.len() != 2 {
@@ -38,6 +159,15 @@ fn main() {
         }
     };
 
+    let shutdown = ShutdownCoordinator::new();
+    if let Err(err) = shutdown.install_handler() {
+        println!(
+            "Attempt to install the shutdown handler has failed: {:?}.",
+            err
+        );
+        return;
+    }
+
     let listener: TcpListener = match TcpListener::bind("127.0.0.1:8000") {
         Ok(listener) => listener,
         Err(err) => {
@@ -46,39 +176,199 @@ fn main() {
         }
     };
 
-    let mut streams: Vec<TcpStream> = match handshake(&settings) {
-        Ok(streams) => streams,
+    let invalid_blocks = match InvalidBlocks::new(INVALID_BLOCKS_FILE_PATH) {
+        Ok(invalid_blocks) => invalid_blocks,
+        Err(err) => {
+            println!("Attempt to load invalid blocks has failed: {:?}.", err);
+            return;
+        }
+    };
+
+    let peer_scores = match PeerScores::new(BANNED_PEERS_FILE_PATH) {
+        Ok(peer_scores) => peer_scores,
+        Err(err) => {
+            println!("Attempt to load banned peers has failed: {:?}.", err);
+            return;
+        }
+    };
+
+    let mut peer_heights = match PeerHeights::from_file(PEER_HEIGHTS_FILE_PATH) {
+        Ok(peer_heights) => peer_heights,
         Err(err) => {
             println!(
-                "Attempt to establish handshake with other nodes has failed: {:?}",
+                "Attempt to load the heights of the peers has failed: {:?}.",
                 err
             );
             return;
         }
     };
 
-    let headers: Vec<BlockHeader> = match headers_download(&settings, &mut streams) {
-        Ok(headers) => headers,
+    let anchors = match Anchors::from_file(ANCHORS_FILE_PATH) {
+        Ok(anchors) => anchors,
         Err(err) => {
-            println!("Attempt to download block headers has failed: {:?}", err);
+            println!("Attempt to load the anchor peers has failed: {:?}.", err);
             return;
         }
     };
 
+    // The peers of a connect_only run are not meant to be reused as anchors by a regular one.
+    let save_anchors = settings.get_connect_only().is_empty();
+    let snapshot_dump = settings
+        .get_dump_snapshot()
+        .map(|path| (path.clone(), settings.get_network()));
+
+    let mut network_time = NetworkTime::new();
+    let mut peer_features: HashMap<SocketAddr, PeerFeatures> = HashMap::new();
+    let mut addr_manager = AddrManager::new();
+
+    let mut streams: Vec<(SocketAddr, TcpStream)> = match handshake(
+        &settings,
+        &peer_scores,
+        anchors.get_addrs(),
+        &mut network_time,
+        &mut peer_heights,
+        &mut peer_features,
+        &mut addr_manager,
+    ) {
+        Ok(streams) => streams,
+        Err(err) => {
+            println!(
+                "Attempt to establish handshake with other nodes has failed: {:?}",
+                err
+            );
+            return;
+        }
+    };
+
+    let sync_progress = Arc::new(Mutex::new(SyncProgress::new()));
+
+    let headers: Vec<BlockHeader> =
+        match headers_download(&settings, &mut streams, &network_time, &sync_progress) {
+            Ok(headers) => headers,
+            Err(err) => {
+                println!("Attempt to download block headers has failed: {:?}", err);
+                return;
+            }
+        };
+
     let (tx, rx): (Sender<Block>, Receiver<Block>) = mpsc::channel();
 
-    let blockchain: Arc<Mutex<BlockChain>> = Arc::new(Mutex::new(BlockChain::new()));
+    let mut chain = BlockChain::new(settings.get_network());
+    if settings.get_txindex() {
+        chain.enable_tx_index();
+    }
+    let blockchain: Arc<Mutex<BlockChain>> = Arc::new(Mutex::new(chain));
     let utxo: Arc<Mutex<UnspentTx>> = Arc::new(Mutex::new(UnspentTx::new()));
     let mempool: Arc<Mutex<Mempool>> = Arc::new(Mutex::new(Mempool::new()));
-    let mut streams: Vec<Arc<Mutex<TcpStream>>> = streams
-        .into_iter()
-        .map(|streams| Arc::new(Mutex::new(streams)))
-        .collect();
+    let peer_scores: Arc<Mutex<PeerScores>> = Arc::new(Mutex::new(peer_scores));
+    let event_bus = EventBus::new();
+    let mut peer_pool = PeerPool::new(settings.get_max_outbound());
+    peer_pool.set_event_bus(event_bus.clone());
+    peer_pool.set_peer_heights(peer_heights);
+    peer_pool.set_anchors(anchors);
+    peer_pool.set_addr_manager(addr_manager);
+    peer_pool.set_bandwidth_limits(BandwidthLimits::new(
+        settings.get_max_upload_rate(),
+        settings.get_max_download_rate(),
+    ));
+    if let Some(path) = settings.get_capture_file() {
+        match MessageCapture::open(path) {
+            Ok(capture) => peer_pool.set_message_capture(capture),
+            Err(err) => println!("Attempt to open the message capture has failed: {:?}.", err),
+        }
+    }
+
+    for (addr, stream) in streams {
+        if let Err(err) = peer_pool.add_peer(addr, stream) {
+            println!("Attempt to add peer to the pool has failed: {:?}.", err);
+        } else if let Some(features) = peer_features.remove(&addr) {
+            peer_pool.set_peer_features(addr, features);
+        }
+    }
+
+    for (_, writer) in peer_pool.iter() {
+        if shutdown.register(writer.get_socket()).is_err() {
+            println!("Attempt to register peer connection for shutdown has failed.");
+        }
+    }
+
+    let peer_pool: Arc<Mutex<PeerPool>> = Arc::new(Mutex::new(peer_pool));
     let settings = Arc::new(settings);
 
-    let block_download_thread = download_blocks(blockchain.clone(), utxo.clone(), rx);
+    let start_height = match get_start_height(&headers, settings.get_network()) {
+        Ok(start_height) => start_height,
+        Err(err) => {
+            println!(
+                "Attempt to get the height of the headers has failed: {:?}.",
+                err
+            );
+            return;
+        }
+    };
+
+    let downloaded = match load_blocks(
+        BLOCKS_FILE_PATH,
+        UTXO_FILE_PATH,
+        UNDO_FILE_PATH,
+        &headers,
+        start_height,
+        &blockchain,
+        &utxo,
+    ) {
+        Ok(downloaded) => downloaded,
+        Err(err) => {
+            println!("Attempt to load downloaded blocks has failed: {:?}.", err);
+            return;
+        }
+    };
+
+    // Only the blocks after the tip of the persisted ones are requested
+    let mut download_from = match restore_tip(&headers, start_height, &blockchain) {
+        Ok(download_from) => download_from,
+        Err(err) => {
+            println!(
+                "Attempt to restore the tip of the blockchain has failed: {:?}.",
+                err
+            );
+            return;
+        }
+    };
+
+    // A fresh node bootstrapped from a snapshot only requests the blocks after its tip
+    let fresh = utxo
+        .lock()
+        .map(|locked_utxo| locked_utxo.get_tip().is_none());
+    if let (Some(path), Ok(true)) = (settings.get_load_snapshot(), fresh) {
+        match load_snapshot(path, settings.get_network(), &headers, start_height, &utxo) {
+            Ok(snapshot_from) => {
+                println!("The UTXO set has been loaded from the snapshot.");
+                download_from = download_from.max(snapshot_from);
+            }
+            Err(err) => println!("Attempt to load the UTXO snapshot has failed: {:?}.", err),
+        }
+    }
+
+    let block_download_thread = download_blocks(
+        blockchain.clone(),
+        utxo.clone(),
+        rx,
+        index_heights(
+            &headers[download_from..],
+            start_height + download_from as u32,
+        ),
+        BLOCKS_FILE_PATH,
+        UTXO_FILE_PATH,
+        UNDO_FILE_PATH,
+    );
 
-    if let Err(err) = block_download(settings.clone(), &streams, &headers, tx) {
+    if let Err(err) = block_download(
+        settings.clone(),
+        &peer_pool,
+        &headers[download_from..],
+        &downloaded,
+        tx,
+        &sync_progress,
+    ) {
         println!("Attempt to download blocks has failed: {:?}.", err);
         return;
     }
@@ -95,6 +385,15 @@ fn main() {
                 println!("Attempt to lock blockchain has failed.");
                 return;
             }
+
+            // Blocks arrive out of order, so the tip is found again once all of them arrived
+            if let Err(err) = restore_tip(&headers, start_height, &blockchain) {
+                println!(
+                    "Attempt to restore the tip of the blockchain has failed: {:?}.",
+                    err
+                );
+                return;
+            }
         }
         Err(_) => {
             println!("Attempt to join block download threads has failed.");
@@ -102,16 +401,25 @@ fn main() {
         }
     }
 
-    let cant_streams = streams.len();
-
-    let streams_tx_broadcast = take_streams(&mut streams, cant_streams / 2);
+    let header_chain: Arc<Mutex<HeaderChain>> =
+        Arc::new(Mutex::new(HeaderChain::new(headers, start_height)));
+    let mut chain_state = ChainState::new(
+        blockchain,
+        utxo,
+        mempool,
+        header_chain,
+        Arc::new(network_time),
+        Arc::new(Mutex::new(invalid_blocks)),
+        Arc::new(Mutex::new(EventLog::new(WALLET_EVENTS_CAPACITY))),
+    );
+    chain_state.set_event_bus(event_bus);
 
-    let handles_broadcasting = match broadcasting(
+    let mut handles_broadcasting = match broadcasting(
         settings.clone(),
-        &mut streams,
-        blockchain.clone(),
-        utxo.clone(),
-        mempool.clone(),
+        &peer_pool,
+        chain_state.clone(),
+        peer_scores.clone(),
+        shutdown.clone(),
     ) {
         Ok(handle_broadcasting) => handle_broadcasting,
         Err(_) => {
@@ -119,32 +427,95 @@ fn main() {
             return;
         }
     };
+    handles_broadcasting.push(monitor_sync(
+        settings.clone(),
+        peer_pool.clone(),
+        chain_state.get_header_chain().clone(),
+        shutdown.clone(),
+    ));
+    handles_broadcasting.push(monitor_health(
+        settings.clone(),
+        peer_pool.clone(),
+        chain_state.clone(),
+        peer_scores,
+        shutdown.clone(),
+    ));
 
-    let (wallet, _addr): (TcpStream, SocketAddr) = match listener.accept() {
-        Ok((wallet, _addr)) => (wallet, _addr),
-        Err(err) => {
-            println!(
-                "Attempt to establish connection with wallet has failed: {}.",
-                err
-            );
-            return;
-        }
-    };
+    let wallet_txs: Arc<Mutex<WalletTxs>> = Arc::new(Mutex::new(WalletTxs::new()));
+    handles_broadcasting.push(rebroadcast_wallet_txs(
+        settings.clone(),
+        wallet_txs.clone(),
+        peer_pool.clone(),
+        chain_state.clone(),
+        shutdown.clone(),
+    ));
 
-    if let Err(err) = update_wallet(
-        wallet,
-        blockchain,
-        utxo,
-        mempool,
-        settings,
-        streams_tx_broadcast,
+    for handle_wallet in serve_wallets(
+        &listener,
+        &shutdown,
+        &chain_state,
+        &settings,
+        &peer_pool,
+        &wallet_txs,
+        start_time,
     ) {
-        println!("Attempt to communicate with wallet has failed: {:?}.", err);
-    };
+        if handle_wallet.join().is_err() {
+            println!("Attempt to join wallet threads has failed.");
+        }
+    }
 
     for handle_broadcasting in handles_broadcasting {
         if handle_broadcasting.join().is_err() {
             println!("Attempt to join broadcast threads has failed.");
         }
     }
+
+    match save_blocks(
+        BLOCKS_FILE_PATH,
+        chain_state.get_blockchain(),
+        chain_state.get_header_chain(),
+    ) {
+        Ok(saved) => println!("{} blocks have been saved.", saved),
+        Err(err) => println!("Attempt to save the blocks has failed: {:?}.", err),
+    }
+
+    match save_utxo(
+        UTXO_FILE_PATH,
+        chain_state.get_utxo(),
+        chain_state.get_blockchain(),
+    ) {
+        Ok(saved) => println!("{} unspent outputs have been saved.", saved),
+        Err(err) => println!("Attempt to save the UTXO set has failed: {:?}.", err),
+    }
+
+    match save_undo(
+        UNDO_FILE_PATH,
+        chain_state.get_utxo(),
+        chain_state.get_header_chain(),
+    ) {
+        Ok(saved) => println!("The undo data of {} blocks has been saved.", saved),
+        Err(err) => println!("Attempt to save the undo data has failed: {:?}.", err),
+    }
+
+    if let Some((path, network)) = snapshot_dump {
+        match dump_snapshot(&path, chain_state.get_utxo(), network) {
+            Ok((height, content_hash)) => println!(
+                "The UTXO snapshot at height {} has been dumped, with content hash {}.",
+                height,
+                encode_hex(&content_hash).unwrap_or_default()
+            ),
+            Err(err) => println!("Attempt to dump the UTXO snapshot has failed: {:?}.", err),
+        }
+    }
+
+    if let Ok(locked_peer_pool) = peer_pool.lock() {
+        if locked_peer_pool.get_peer_heights().save().is_err() {
+            println!("Attempt to save the heights of the peers has failed.");
+        }
+        if save_anchors && locked_peer_pool.get_anchors().save().is_err() {
+            println!("Attempt to save the anchor peers has failed.");
+        }
+    }
+
+    println!("The node has stopped.");
 }
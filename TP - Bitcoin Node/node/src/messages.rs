@@ -3,23 +3,45 @@
 //! # Modules
 //!
 //! - [`addr`](addr) - Is the response to the "GetAddress" message, and it contains IP addresses of other peers.
+//! - [`block_message`](block_message) - Implements the `block` message for sending a full block to peers.
+//! - [`command`](command) - Defines the commands of the messages along with their 12-byte encoding.
 //! - [`compact_size`](compact_size) - Provides utilities for working with values of variable length byte size.
+//! - [`fee_filter`](fee_filter) - Implements the `feefilter` message for announcing the minimum fee rate of relayed transactions.
+//! - [`filter_add`](filter_add) - Implements the `filteradd` message for adding an element to a loaded bloom filter.
+//! - [`filter_clear`](filter_clear) - Implements the `filterclear` message for removing a loaded bloom filter.
+//! - [`filter_load`](filter_load) - Implements the `filterload` message for loading a bloom filter of relevant transactions.
+//! - [`get_blocks`](get_blocks) - Implements the `getblocks` message for requesting the inventory of the blocks after a locator.
 //! - [`get_data`](get_data) - Implements the `getdata` message for requesting different types of data from peers.
 //! - [`get_headers`](get_headers) - Implements the `getheaders` message for requesting block headers from peers.
 //! - [`header`](header) - Defines the structure and operations related to block headers.
 //! - [`headers`](headers) - Implements the `headers` message for sending block headers to peers.
 //! - [`inventory`](inventory) - Defines the structure and operations related to inventory items.
 //! - [`ip`](ip) - Provides IP address handling utilities.
+//! - [`mempool`](mempool) - Implements the `mempool` message for requesting the transactions held in a peer's mempool.
+//! - [`merkle_block`](merkle_block) - Implements the `merkleblock` message for sending filtered blocks to SPV clients.
+//! - [`message`](message) - Defines the `Message` trait shared by every message, to serialize and deserialize them generically.
 //! - [`message_constants`](message_constants) - Defines constants related to Bitcoin protocol messages.
 //! - [`message_error`](message_error) - Implements error handling for Bitcoin protocol messages.
+//! - [`not_found`](not_found) - Implements the `notfound` message for reporting the requested data a peer does not have.
 //! - [`ping`](ping) - Implements the `ping` message for network connection testing.
 //! - [`pong`](pong) - Implements the `pong` message as a response to `ping` messages.
 //! - [`read_from_bytes`](read_from_bytes) - Provides utilities for reading data from byte buffers.
+//! - [`reject`](reject) - Implements the `reject` message for reporting the messages rejected by a peer.
 //! - [`script`](script) - Defines the structure and operations related to Bitcoin script.
+//! - [`serialization`](serialization) - Defines the `ToBytes` and `FromBytes` traits shared by blocks, transactions and messages, along with generic helpers for vectors of them.
+//! - [`send_headers`](send_headers) - Implements the `sendheaders` message for requesting block announcements through headers.
 //! - [`version`](version) - Implements the `version` message for protocol version negotiation.
+//! - [`wtxid_relay`](wtxid_relay) - Implements the `wtxidrelay` message for negotiating the announcement of transactions by wtxid.
 
 pub mod addr;
+pub mod block_message;
+pub mod command;
 pub mod compact_size;
+pub mod fee_filter;
+pub mod filter_add;
+pub mod filter_clear;
+pub mod filter_load;
+pub mod get_blocks;
 pub mod get_data;
 pub mod get_headers;
 pub mod header;
@@ -27,11 +49,19 @@ pub mod headers;
 pub mod inv;
 pub mod inventory;
 pub mod ip;
+pub mod mempool;
+pub mod merkle_block;
+pub mod message;
 pub mod message_constants;
 pub mod message_error;
+pub mod not_found;
 pub mod ping;
 pub mod pong;
 pub mod read_from_bytes;
+pub mod reject;
 pub mod script;
+pub mod send_headers;
+pub mod serialization;
 pub mod tx;
 pub mod version;
+pub mod wtxid_relay;
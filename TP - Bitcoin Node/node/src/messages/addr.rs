@@ -1,11 +1,9 @@
 use super::compact_size::CompactSizeUInt;
 use super::header::MessageHeader;
 use super::ip::Ip;
-use super::message_constants::{ADDR_COMMAND, HEADER_BYTES_SIZE};
+use super::message_constants::{ADDR_COMMAND, HEADER_BYTES_SIZE, MAX_ADDR_COUNT};
 use super::message_error::MessageError;
-use super::read_from_bytes::{
-    read_ipv6_from_bytes, read_u16_from_bytes, read_u32_from_bytes, read_u64_from_bytes,
-};
+use super::serialization::{items_to_bytes, read_vec};
 use bitcoin_hashes::sha256d;
 use bitcoin_hashes::Hash;
 use std::io::Read;
@@ -65,17 +63,7 @@ impl Addr {
             return Err(MessageError::InvalidInputAddr);
         }
 
-        let ip_address_count = CompactSizeUInt::from_bytes(stream)?;
-        let mut ip_address_list: Vec<Ip> = Vec::new();
-
-        for _i in 0..ip_address_count.value() {
-            let time = read_u32_from_bytes(stream, true)?;
-            let services = read_u64_from_bytes(stream, true)?;
-            let ip_address = read_ipv6_from_bytes(stream)?;
-            let port = read_u16_from_bytes(stream, false)?;
-
-            ip_address_list.push(Ip::new(time, services, ip_address, port));
-        }
+        let (ip_address_count, ip_address_list) = read_vec::<Ip>(stream, MAX_ADDR_COUNT)?;
 
         Ok(Addr {
             header,
@@ -84,6 +72,10 @@ impl Addr {
         })
     }
 
+    pub fn get_ip_address_list(&self) -> &Vec<Ip> {
+        &self.ip_address_list
+    }
+
     /// Serializes the `Addr` object into a byte stream.
     ///
     /// # Returns
@@ -93,9 +85,7 @@ impl Addr {
         let mut buff = self.header.to_bytes();
 
         buff.extend(self.ip_address_count.to_bytes());
-        self.ip_address_list
-            .iter()
-            .for_each(|ip| buff.extend(ip.to_bytes()));
+        buff.extend(items_to_bytes(&self.ip_address_list));
         buff
     }
 }
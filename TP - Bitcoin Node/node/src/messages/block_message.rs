@@ -0,0 +1,71 @@
+use super::{header::MessageHeader, message_constants::BLOCK_COMMAND};
+use crate::block_mod::block::Block;
+use bitcoin_hashes::sha256d;
+use bitcoin_hashes::Hash;
+
+/// Represents a block message, sent to peers that requested a full block through `getdata`.
+#[derive(Debug)]
+pub struct BlockMessage {
+    header: MessageHeader,
+    payload: Vec<u8>,
+}
+
+impl BlockMessage {
+    /// Creates a new `BlockMessage` with the given start string and block.
+    ///
+    /// # Arguments
+    ///
+    /// * `start_string` - The start string of the message.
+    /// * `block` - The block to send.
+    /// * `witness` - Whether the witness data of the transactions of the block is sent.
+    pub fn new(start_string: Vec<u8>, block: &Block, witness: bool) -> BlockMessage {
+        let mut block_message = BlockMessage {
+            header: MessageHeader::new(start_string, BLOCK_COMMAND.to_string()),
+            payload: block.to_bytes_with_witness(witness),
+        };
+
+        let checksum = sha256d::Hash::hash(&block_message.payload).to_byte_array()[..4].to_vec();
+
+        block_message
+            .header
+            .update_payload(block_message.payload.len() as u32, checksum);
+
+        block_message
+    }
+
+    /// Converts the `BlockMessage` object to its byte representation.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buff = self.header.to_bytes();
+        buff.extend(&self.payload);
+
+        buff
+    }
+}
+
+#[cfg(test)]
+mod block_message_test {
+    use super::*;
+    use crate::block_mod::block::test_block_bytes;
+    use crate::messages::message_constants::HEADER_BYTES_SIZE;
+    use crate::messages::message_error::MessageError;
+
+    #[test]
+    fn test_block_message_holds_the_block() -> Result<(), MessageError> {
+        let block = Block::from_bytes(&mut test_block_bytes().as_slice())?;
+        let bytes = BlockMessage::new(vec![11, 17, 9, 7], &block, false).to_bytes();
+        let mut stream = bytes.as_slice();
+
+        let header = MessageHeader::from_bytes(&mut stream)?;
+
+        assert_eq!(header.get_command_name(), BLOCK_COMMAND);
+        assert_eq!(
+            bytes.len() - HEADER_BYTES_SIZE,
+            header.get_payload_size() as usize
+        );
+        assert_eq!(
+            Block::from_bytes(&mut stream)?.get_header().get_header(),
+            block.get_header().get_header()
+        );
+        Ok(())
+    }
+}
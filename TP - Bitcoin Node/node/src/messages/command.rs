@@ -0,0 +1,252 @@
+use super::{
+    message_constants::*,
+    message_error::MessageError,
+    read_from_bytes::{fill_command, read_string_from_bytes},
+};
+use std::{fmt, io::Read};
+
+/// The command of a message, either of the Bitcoin protocol or of the protocol spoken between the
+/// node and the wallet, both of which encode it as a name padded with nulls to 12 bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Command {
+    //Bitcoin protocol
+    Version,
+    Verack,
+    Addr,
+    GetAddr,
+    GetHeaders,
+    GetBlocks,
+    Headers,
+    Ping,
+    Pong,
+    GetData,
+    Block,
+    SendHeaders,
+    Inv,
+    Tx,
+    FeeFilter,
+    FilterLoad,
+    FilterAdd,
+    FilterClear,
+    MerkleBlock,
+    Mempool,
+    Reject,
+    NotFound,
+    WtxidRelay,
+
+    //wallet protocol
+    WalletVersion,
+    WalletAuth,
+    AuthResult,
+    Request,
+    Response,
+    HeadersSince,
+    WalletHeaders,
+    GetTx,
+    TxInfo,
+    NotFoundWallet,
+    GetFilters,
+    Filters,
+    GetBlock,
+    RawBlock,
+    SubmitBlock,
+    BlockStatus,
+    GetInvalid,
+    InvalidBlocks,
+    Reconsider,
+    Reconsidered,
+    Resume,
+    Events,
+    GetPeerInfo,
+    PeerInfo,
+    GetNodeInfo,
+    NodeInfo,
+    Subscribe,
+    Notify,
+    SendRawTx,
+    TxStatus,
+    GetBalance,
+    Balance,
+    GetUtxos,
+    Utxos,
+}
+
+const COMMANDS: [Command; 57] = [
+    Command::Version,
+    Command::Verack,
+    Command::Addr,
+    Command::GetAddr,
+    Command::GetHeaders,
+    Command::GetBlocks,
+    Command::Headers,
+    Command::Ping,
+    Command::Pong,
+    Command::GetData,
+    Command::Block,
+    Command::SendHeaders,
+    Command::Inv,
+    Command::Tx,
+    Command::FeeFilter,
+    Command::FilterLoad,
+    Command::FilterAdd,
+    Command::FilterClear,
+    Command::MerkleBlock,
+    Command::Mempool,
+    Command::Reject,
+    Command::NotFound,
+    Command::WtxidRelay,
+    Command::WalletVersion,
+    Command::WalletAuth,
+    Command::AuthResult,
+    Command::Request,
+    Command::Response,
+    Command::HeadersSince,
+    Command::WalletHeaders,
+    Command::GetTx,
+    Command::TxInfo,
+    Command::NotFoundWallet,
+    Command::GetFilters,
+    Command::Filters,
+    Command::GetBlock,
+    Command::RawBlock,
+    Command::SubmitBlock,
+    Command::BlockStatus,
+    Command::GetInvalid,
+    Command::InvalidBlocks,
+    Command::Reconsider,
+    Command::Reconsidered,
+    Command::Resume,
+    Command::Events,
+    Command::GetPeerInfo,
+    Command::PeerInfo,
+    Command::GetNodeInfo,
+    Command::NodeInfo,
+    Command::Subscribe,
+    Command::Notify,
+    Command::SendRawTx,
+    Command::TxStatus,
+    Command::GetBalance,
+    Command::Balance,
+    Command::GetUtxos,
+    Command::Utxos,
+];
+
+impl Command {
+    /// Returns the name of the command, without the padding.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::Version => VERSION_COMMAND,
+            Command::Verack => VERACK_COMMAND,
+            Command::Addr => ADDR_COMMAND,
+            Command::GetAddr => GET_ADDR_COMMAND,
+            Command::GetHeaders => GET_HEADERS_COMMAND,
+            Command::GetBlocks => GET_BLOCKS_COMMAND,
+            Command::Headers => HEADERS_COMMAND,
+            Command::Ping => PING_COMMAND,
+            Command::Pong => PONG_COMMAND,
+            Command::GetData => GET_DATA_COMMAND,
+            Command::Block => BLOCK_COMMAND,
+            Command::SendHeaders => SEND_HEADERS_COMMAND,
+            Command::Inv => INV_COMMAND,
+            Command::Tx => TX_COMMAND,
+            Command::FeeFilter => FEE_FILTER_COMMAND,
+            Command::FilterLoad => FILTER_LOAD_COMMAND,
+            Command::FilterAdd => FILTER_ADD_COMMAND,
+            Command::FilterClear => FILTER_CLEAR_COMMAND,
+            Command::MerkleBlock => MERKLE_BLOCK_COMMAND,
+            Command::Mempool => MEMPOOL_COMMAND,
+            Command::Reject => REJECT_COMMAND,
+            Command::NotFound => NOT_FOUND_COMMAND,
+            Command::WtxidRelay => WTXID_RELAY_COMMAND,
+            Command::WalletVersion => "wallet_ver",
+            Command::WalletAuth => "wallet_auth",
+            Command::AuthResult => "auth_result",
+            Command::Request => "request",
+            Command::Response => "response",
+            Command::HeadersSince => "hdrs_since",
+            Command::WalletHeaders => "wallet_hdrs",
+            Command::GetTx => "get_tx",
+            Command::TxInfo => "tx_info",
+            Command::NotFoundWallet => "not_found",
+            Command::GetFilters => "get_filters",
+            Command::Filters => "filters",
+            Command::GetBlock => "get_block",
+            Command::RawBlock => "raw_block",
+            Command::SubmitBlock => "submitblock",
+            Command::BlockStatus => "block_status",
+            Command::GetInvalid => "get_invalid",
+            Command::InvalidBlocks => "invalid_blks",
+            Command::Reconsider => "reconsider",
+            Command::Reconsidered => "reconsidered",
+            Command::Resume => "resume",
+            Command::Events => "events",
+            Command::GetPeerInfo => "getpeerinfo",
+            Command::PeerInfo => "peer_info",
+            Command::GetNodeInfo => "getnodeinfo",
+            Command::NodeInfo => "node_info",
+            Command::Subscribe => "subscribe",
+            Command::Notify => "notify",
+            Command::SendRawTx => "sendrawtx",
+            Command::TxStatus => "tx_status",
+            Command::GetBalance => "getbalance",
+            Command::Balance => "balance",
+            Command::GetUtxos => "getutxos",
+            Command::Utxos => "utxos",
+        }
+    }
+
+    /// Returns the command with the given name, or `None` if the name is unknown.
+    pub fn from_name(name: &str) -> Option<Command> {
+        COMMANDS
+            .iter()
+            .find(|command| command.name() == name)
+            .copied()
+    }
+
+    /// Reads the 12-byte encoding of a command from the stream.
+    ///
+    /// # Returns
+    ///
+    /// The command, or `MessageError::UnknownCommand` if the name read is not a known command.
+    pub fn from_bytes(stream: &mut dyn Read) -> Result<Command, MessageError> {
+        let name = read_string_from_bytes(stream, 12)?;
+
+        Command::from_name(&name).ok_or(MessageError::UnknownCommand)
+    }
+
+    /// Returns the 12-byte encoding of the command: its name padded with nulls.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        fill_command(self.name()).into_bytes()
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[cfg(test)]
+mod command_test {
+    use super::*;
+
+    #[test]
+    fn test_commands_round_trip_through_their_wire_encoding() -> Result<(), MessageError> {
+        for command in COMMANDS {
+            let bytes = command.to_bytes();
+            assert_eq!(bytes.len(), 12);
+            assert_eq!(Command::from_bytes(&mut bytes.as_slice())?, command);
+        }
+
+        assert_eq!(
+            Command::from_name("sendheaders"),
+            Some(Command::SendHeaders)
+        );
+        assert_eq!(Command::from_name("sendheader"), None);
+        assert!(matches!(
+            Command::from_bytes(&mut [0u8; 12].as_slice()),
+            Err(MessageError::UnknownCommand)
+        ));
+        Ok(())
+    }
+}
@@ -25,6 +25,9 @@ impl CompactSizeUInt {
 
     /// Reads a `CompactSizeUInt` object from a byte stream.
     ///
+    /// Values must be encoded in the shortest form that holds them, as the protocol requires, so
+    /// every value has a single encoding.
+    ///
     /// # Arguments
     ///
     /// * `stream` - A mutable reference to a byte stream implementing the `Read` trait.
@@ -32,21 +35,47 @@ impl CompactSizeUInt {
     /// # Returns
     ///
     /// A `Result` containing the parsed `CompactSizeUInt` object on success, or a `MessageError` on failure.
+    /// Values encoded in a longer form than needed are rejected with
+    /// `MessageError::NonCanonicalCompactSize`.
     pub fn from_bytes(stream: &mut dyn Read) -> Result<CompactSizeUInt, MessageError> {
-        let value = read_u8_from_bytes(stream)?;
-
-        if value <= BYTE_SIZE as u8 {
-            return Ok(CompactSizeUInt::from_number(value as u64));
-        } else if value == TWO_BYTE_SIZE {
-            let value16 = read_u16_from_bytes(stream, true)?;
-            return Ok(CompactSizeUInt::from_number(value16 as u64));
-        } else if value == FOUR_BYTE_SIZE {
-            let value32 = read_u32_from_bytes(stream, true)?;
-            return Ok(CompactSizeUInt::from_number(value32 as u64));
+        let prefix = read_u8_from_bytes(stream)?;
+
+        let (value, min_value) = match prefix {
+            TWO_BYTE_SIZE => (read_u16_from_bytes(stream, true)? as u64, BYTE_SIZE + 1),
+            FOUR_BYTE_SIZE => (read_u32_from_bytes(stream, true)? as u64, 0x10000),
+            EIGHT_BYTE_SIZE => (read_u64_from_bytes(stream, true)?, 0x100000000),
+            _ => return Ok(CompactSizeUInt::from_number(prefix as u64)),
+        };
+
+        if value < min_value {
+            return Err(MessageError::NonCanonicalCompactSize);
         }
+        Ok(CompactSizeUInt::from_number(value))
+    }
 
-        let value64 = read_u64_from_bytes(stream, true)?;
-        Ok(CompactSizeUInt::from_number(value64))
+    /// Reads a `CompactSizeUInt` object from a byte stream, checking that its value does not exceed
+    /// the given maximum, so the counts and lengths sent by a peer cannot make the node allocate
+    /// without bound.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - A mutable reference to a byte stream implementing the `Read` trait.
+    /// * `max` - The maximum value accepted.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `CompactSizeUInt` object on success, or
+    /// `MessageError::TooManyElements` if its value exceeds the maximum.
+    pub fn from_bytes_bounded(
+        stream: &mut dyn Read,
+        max: u64,
+    ) -> Result<CompactSizeUInt, MessageError> {
+        let compact_size = CompactSizeUInt::from_bytes(stream)?;
+
+        if compact_size.value() > max {
+            return Err(MessageError::TooManyElements);
+        }
+        Ok(compact_size)
     }
 
     /// Checks the size category of the `CompactSizeUInt` object.
@@ -80,10 +109,17 @@ impl CompactSizeUInt {
     /// If the size is `FOUR_BYTE_SIZE`, it includes a size prefix followed by a 32-bit value in little-endian format.
     /// If the size is `EIGHT_BYTE_SIZE`, it includes a size prefix followed by a 64-bit value in little-endian format.
     pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = Vec::with_capacity(9);
+
+        self.to_bytes_into(&mut buffer);
+        buffer
+    }
+
+    /// Appends the byte representation of the `CompactSizeUInt` object to the given buffer.
+    pub fn to_bytes_into(&self, buffer: &mut Vec<u8>) {
         let size = self.check_size();
-        let mut buffer = Vec::new();
 
-        buffer.extend(&size.to_le_bytes());
+        buffer.push(size);
 
         if size == TWO_BYTE_SIZE {
             let value16 = (self.value as u16).to_le_bytes();
@@ -95,7 +131,6 @@ impl CompactSizeUInt {
             let value64 = self.value.to_le_bytes();
             buffer.extend(&value64);
         }
-        buffer
     }
 
     /// Returns the value of the `CompactSizeUInt` object.
@@ -152,4 +187,59 @@ mod test_compact_size {
         assert_eq!(number_env, number_recv);
         Ok(())
     }
+
+    #[test]
+    fn test_bounded_compact_size_rejects_values_over_the_maximum() -> Result<(), MessageError> {
+        let number_env_bytes = CompactSizeUInt::from_number(2001).to_bytes();
+
+        let number_recv =
+            CompactSizeUInt::from_bytes_bounded(&mut number_env_bytes.as_slice(), 2001)?;
+        assert_eq!(number_recv.value(), 2001);
+
+        assert!(matches!(
+            CompactSizeUInt::from_bytes_bounded(&mut number_env_bytes.as_slice(), 2000),
+            Err(MessageError::TooManyElements)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_compact_sizes_round_trip_at_every_boundary() -> Result<(), MessageError> {
+        let cases: [(u64, usize); 8] = [
+            (0, 1),
+            (252, 1),
+            (253, 3),
+            (0xffff, 3),
+            (0x10000, 5),
+            (0xffffffff, 5),
+            (0x100000000, 9),
+            (u64::MAX, 9),
+        ];
+
+        for (value, len) in cases {
+            let bytes = CompactSizeUInt::from_number(value).to_bytes();
+            assert_eq!(bytes.len(), len);
+            assert_eq!(
+                CompactSizeUInt::from_bytes(&mut bytes.as_slice())?.value(),
+                value
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_non_minimal_compact_sizes_are_rejected() {
+        let non_canonical: [&[u8]; 3] = [
+            &[0xfd, 0xfc, 0x00],
+            &[0xfe, 0xff, 0xff, 0x00, 0x00],
+            &[0xff, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00],
+        ];
+
+        for mut bytes in non_canonical {
+            assert!(matches!(
+                CompactSizeUInt::from_bytes(&mut bytes),
+                Err(MessageError::NonCanonicalCompactSize)
+            ));
+        }
+    }
 }
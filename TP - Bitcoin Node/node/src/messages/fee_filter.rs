@@ -0,0 +1,101 @@
+use super::header::MessageHeader;
+use super::message_constants::{FEE_FILTER_COMMAND, HEADER_BYTES_SIZE};
+use super::message_error::MessageError;
+use super::read_from_bytes::read_u64_from_bytes;
+use bitcoin_hashes::sha256d;
+use bitcoin_hashes::Hash;
+use std::io::Read;
+
+/// Represents a FeeFilter message in the Bitcoin protocol (BIP133).
+///
+/// A peer sending it asks not to be relayed transactions whose fee rate, in satoshis per
+/// kilobyte, is lower than the one of the message.
+#[derive(Debug, PartialEq)]
+pub struct FeeFilter {
+    header: MessageHeader,
+    fee_rate: u64,
+}
+
+impl FeeFilter {
+    /// Creates a new FeeFilter message with the specified start string and fee rate.
+    ///
+    /// # Arguments
+    ///
+    /// * `start_string`: The start string of the message.
+    /// * `fee_rate`: The minimum fee rate, in satoshis per kilobyte, of the transactions to relay.
+    ///
+    /// # Returns
+    ///
+    /// A new `FeeFilter` message.
+    pub fn new(start_string: Vec<u8>, fee_rate: u64) -> FeeFilter {
+        let header = MessageHeader::new(start_string, FEE_FILTER_COMMAND.to_string());
+
+        let mut fee_filter = FeeFilter { header, fee_rate };
+
+        let stream: Vec<u8> = fee_filter.to_bytes();
+        let payload_size = stream.len() - HEADER_BYTES_SIZE;
+        let checksum =
+            sha256d::Hash::hash(&stream[HEADER_BYTES_SIZE..]).to_byte_array()[..4].to_vec();
+
+        fee_filter
+            .header
+            .update_payload(payload_size as u32, checksum);
+        fee_filter
+    }
+
+    /// Parses a FeeFilter message from the provided message header and byte stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `header`: The message header.
+    /// * `stream`: The byte stream to read the message from.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the parsed `FeeFilter` message or an error if parsing fails.
+    pub fn from_bytes(
+        header: MessageHeader,
+        stream: &mut dyn Read,
+    ) -> Result<FeeFilter, MessageError> {
+        if header.get_command_name() != FEE_FILTER_COMMAND {
+            return Err(MessageError::InvalidInputFeeFilter);
+        }
+
+        let fee_rate = read_u64_from_bytes(stream, true)?;
+        Ok(FeeFilter { header, fee_rate })
+    }
+
+    /// Serializes the `FeeFilter` message into a byte vector.
+    ///
+    /// # Returns
+    ///
+    /// A byte vector representing the serialized `FeeFilter` message.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.header.to_bytes();
+        buffer.extend(self.fee_rate.to_le_bytes());
+        buffer
+    }
+
+    /// Returns the minimum fee rate, in satoshis per kilobyte, of the transactions to relay.
+    pub fn get_fee_rate(&self) -> u64 {
+        self.fee_rate
+    }
+}
+
+#[cfg(test)]
+mod fee_filter_test {
+    use super::*;
+
+    #[test]
+    fn test_new_fee_filter_from_bytes() -> Result<(), MessageError> {
+        let fee_filter = FeeFilter::new(vec![11, 17, 9, 7], 1000);
+        let bytes = fee_filter.to_bytes();
+        let mut stream = bytes.as_slice();
+
+        let header = MessageHeader::from_bytes(&mut stream)?;
+
+        assert_eq!(header.get_payload_size(), 8);
+        assert_eq!(FeeFilter::from_bytes(header, &mut stream)?, fee_filter);
+        Ok(())
+    }
+}
@@ -0,0 +1,117 @@
+use super::compact_size::CompactSizeUInt;
+use super::header::MessageHeader;
+use super::message_constants::{FILTER_ADD_COMMAND, HEADER_BYTES_SIZE, MAX_FILTER_ADD_SIZE};
+use super::message_error::MessageError;
+use super::read_from_bytes::read_vec_from_bytes;
+use bitcoin_hashes::sha256d;
+use bitcoin_hashes::Hash;
+use std::io::Read;
+
+/// Represents a FilterAdd message in the Bitcoin protocol (BIP37).
+///
+/// A peer sending it asks to add an element to the bloom filter it previously loaded.
+#[derive(Debug, PartialEq)]
+pub struct FilterAdd {
+    header: MessageHeader,
+    element_bytes: CompactSizeUInt,
+    element: Vec<u8>,
+}
+
+impl FilterAdd {
+    /// Creates a new FilterAdd message with the specified start string and element.
+    ///
+    /// # Arguments
+    ///
+    /// * `start_string`: The start string of the message.
+    /// * `element`: The element to add to the bloom filter.
+    ///
+    /// # Returns
+    ///
+    /// A new `FilterAdd` message.
+    pub fn new(start_string: Vec<u8>, element: Vec<u8>) -> FilterAdd {
+        let header = MessageHeader::new(start_string, FILTER_ADD_COMMAND.to_string());
+
+        let mut filter_add = FilterAdd {
+            header,
+            element_bytes: CompactSizeUInt::from_number(element.len() as u64),
+            element,
+        };
+
+        let stream: Vec<u8> = filter_add.to_bytes();
+        let payload_size = stream.len() - HEADER_BYTES_SIZE;
+        let checksum =
+            sha256d::Hash::hash(&stream[HEADER_BYTES_SIZE..]).to_byte_array()[..4].to_vec();
+
+        filter_add
+            .header
+            .update_payload(payload_size as u32, checksum);
+        filter_add
+    }
+
+    /// Parses a FilterAdd message from the provided message header and byte stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `header`: The message header.
+    /// * `stream`: The byte stream to read the message from.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the parsed `FilterAdd` message or an error if parsing fails, or if the
+    /// element is bigger than the protocol allows.
+    pub fn from_bytes(
+        header: MessageHeader,
+        stream: &mut dyn Read,
+    ) -> Result<FilterAdd, MessageError> {
+        if header.get_command_name() != FILTER_ADD_COMMAND {
+            return Err(MessageError::InvalidInputFilterAdd);
+        }
+
+        let element_bytes = CompactSizeUInt::from_bytes(stream)?;
+
+        if element_bytes.value() > MAX_FILTER_ADD_SIZE as u64 {
+            return Err(MessageError::InvalidInputFilterAdd);
+        }
+
+        let element = read_vec_from_bytes(stream, element_bytes.value() as usize)?;
+
+        Ok(FilterAdd {
+            header,
+            element_bytes,
+            element,
+        })
+    }
+
+    /// Serializes the `FilterAdd` message into a byte vector.
+    ///
+    /// # Returns
+    ///
+    /// A byte vector representing the serialized `FilterAdd` message.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.header.to_bytes();
+        buffer.extend(self.element_bytes.to_bytes());
+        buffer.extend(&self.element);
+        buffer
+    }
+
+    pub fn get_element(&self) -> &Vec<u8> {
+        &self.element
+    }
+}
+
+#[cfg(test)]
+mod filter_add_test {
+    use super::*;
+
+    #[test]
+    fn test_new_filter_add_from_bytes() -> Result<(), MessageError> {
+        let filter_add = FilterAdd::new(vec![11, 17, 9, 7], vec![7; 20]);
+        let bytes = filter_add.to_bytes();
+        let mut stream = bytes.as_slice();
+
+        let header = MessageHeader::from_bytes(&mut stream)?;
+
+        assert_eq!(FilterAdd::from_bytes(header, &mut stream)?, filter_add);
+        Ok(())
+    }
+}
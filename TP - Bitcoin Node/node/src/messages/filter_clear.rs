@@ -0,0 +1,58 @@
+use super::header::MessageHeader;
+use super::message_constants::{FILTER_CLEAR_COMMAND, PAYLOAD_EMPTY_MSG};
+use super::message_error::MessageError;
+
+/// Represents a FilterClear message in the Bitcoin protocol (BIP37).
+///
+/// A peer sending it asks to remove the bloom filter it previously loaded, so every transaction is
+/// relayed to it again. The message has no payload.
+#[derive(Debug, PartialEq)]
+pub struct FilterClear {
+    header: MessageHeader,
+}
+
+impl FilterClear {
+    /// Creates a new FilterClear message with the specified start string.
+    ///
+    /// # Arguments
+    ///
+    /// * `start_string`: The start string of the message.
+    ///
+    /// # Returns
+    ///
+    /// A new `FilterClear` message.
+    pub fn new(start_string: Vec<u8>) -> FilterClear {
+        FilterClear {
+            header: MessageHeader::new(start_string, FILTER_CLEAR_COMMAND.to_string()),
+        }
+    }
+
+    /// Parses a FilterClear message from the provided message header.
+    ///
+    /// # Arguments
+    ///
+    /// * `header`: The message header.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the parsed `FilterClear` message or an error if the header does not
+    /// belong to an empty FilterClear message.
+    pub fn from_header(header: MessageHeader) -> Result<FilterClear, MessageError> {
+        if header.get_command_name() != FILTER_CLEAR_COMMAND
+            || header.get_payload_size() != PAYLOAD_EMPTY_MSG
+        {
+            return Err(MessageError::InvalidInputFilterClear);
+        }
+
+        Ok(FilterClear { header })
+    }
+
+    /// Serializes the `FilterClear` message into a byte vector.
+    ///
+    /// # Returns
+    ///
+    /// A byte vector representing the serialized `FilterClear` message.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.header.to_bytes()
+    }
+}
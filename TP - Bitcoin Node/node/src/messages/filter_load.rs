@@ -0,0 +1,172 @@
+use super::compact_size::CompactSizeUInt;
+use super::header::MessageHeader;
+use super::message_constants::{
+    FILTER_LOAD_COMMAND, HEADER_BYTES_SIZE, MAX_BLOOM_FILTER_SIZE, MAX_BLOOM_HASH_FUNCS,
+};
+use super::message_error::MessageError;
+use super::read_from_bytes::{read_u32_from_bytes, read_u8_from_bytes, read_vec_from_bytes};
+use bitcoin_hashes::sha256d;
+use bitcoin_hashes::Hash;
+use std::io::Read;
+
+/// Represents a FilterLoad message in the Bitcoin protocol (BIP37).
+///
+/// A peer sending it asks to be relayed only the transactions matching the bloom filter of the
+/// message, and to be answered filtered blocks through `merkleblock` messages.
+#[derive(Debug, PartialEq)]
+pub struct FilterLoad {
+    header: MessageHeader,
+    filter_bytes: CompactSizeUInt,
+    filter: Vec<u8>,
+    hash_funcs: u32,
+    tweak: u32,
+    flags: u8,
+}
+
+impl FilterLoad {
+    /// Creates a new FilterLoad message with the specified start string and bloom filter.
+    ///
+    /// # Arguments
+    ///
+    /// * `start_string`: The start string of the message.
+    /// * `filter`: The bit field of the bloom filter.
+    /// * `hash_funcs`: The number of hash functions of the bloom filter.
+    /// * `tweak`: The value added to the seed of every hash function.
+    /// * `flags`: How the filter is updated when an output matches it.
+    ///
+    /// # Returns
+    ///
+    /// A new `FilterLoad` message.
+    pub fn new(
+        start_string: Vec<u8>,
+        filter: Vec<u8>,
+        hash_funcs: u32,
+        tweak: u32,
+        flags: u8,
+    ) -> FilterLoad {
+        let header = MessageHeader::new(start_string, FILTER_LOAD_COMMAND.to_string());
+
+        let mut filter_load = FilterLoad {
+            header,
+            filter_bytes: CompactSizeUInt::from_number(filter.len() as u64),
+            filter,
+            hash_funcs,
+            tweak,
+            flags,
+        };
+
+        let stream: Vec<u8> = filter_load.to_bytes();
+        let payload_size = stream.len() - HEADER_BYTES_SIZE;
+        let checksum =
+            sha256d::Hash::hash(&stream[HEADER_BYTES_SIZE..]).to_byte_array()[..4].to_vec();
+
+        filter_load
+            .header
+            .update_payload(payload_size as u32, checksum);
+        filter_load
+    }
+
+    /// Parses a FilterLoad message from the provided message header and byte stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `header`: The message header.
+    /// * `stream`: The byte stream to read the message from.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the parsed `FilterLoad` message or an error if parsing fails, or if the
+    /// filter is bigger or uses more hash functions than the protocol allows.
+    pub fn from_bytes(
+        header: MessageHeader,
+        stream: &mut dyn Read,
+    ) -> Result<FilterLoad, MessageError> {
+        if header.get_command_name() != FILTER_LOAD_COMMAND {
+            return Err(MessageError::InvalidInputFilterLoad);
+        }
+
+        let filter_bytes = CompactSizeUInt::from_bytes(stream)?;
+
+        if filter_bytes.value() > MAX_BLOOM_FILTER_SIZE as u64 {
+            return Err(MessageError::InvalidInputFilterLoad);
+        }
+
+        let filter = read_vec_from_bytes(stream, filter_bytes.value() as usize)?;
+        let hash_funcs = read_u32_from_bytes(stream, true)?;
+        let tweak = read_u32_from_bytes(stream, true)?;
+        let flags = read_u8_from_bytes(stream)?;
+
+        if hash_funcs > MAX_BLOOM_HASH_FUNCS {
+            return Err(MessageError::InvalidInputFilterLoad);
+        }
+
+        Ok(FilterLoad {
+            header,
+            filter_bytes,
+            filter,
+            hash_funcs,
+            tweak,
+            flags,
+        })
+    }
+
+    /// Serializes the `FilterLoad` message into a byte vector.
+    ///
+    /// # Returns
+    ///
+    /// A byte vector representing the serialized `FilterLoad` message.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.header.to_bytes();
+        buffer.extend(self.filter_bytes.to_bytes());
+        buffer.extend(&self.filter);
+        buffer.extend(self.hash_funcs.to_le_bytes());
+        buffer.extend(self.tweak.to_le_bytes());
+        buffer.push(self.flags);
+        buffer
+    }
+
+    pub fn get_filter(&self) -> &Vec<u8> {
+        &self.filter
+    }
+
+    pub fn get_hash_funcs(&self) -> u32 {
+        self.hash_funcs
+    }
+
+    pub fn get_tweak(&self) -> u32 {
+        self.tweak
+    }
+
+    pub fn get_flags(&self) -> u8 {
+        self.flags
+    }
+}
+
+#[cfg(test)]
+mod filter_load_test {
+    use super::*;
+
+    #[test]
+    fn test_new_filter_load_from_bytes() -> Result<(), MessageError> {
+        let filter_load = FilterLoad::new(vec![11, 17, 9, 7], vec![0xb5, 0x0f], 11, 0, 1);
+        let bytes = filter_load.to_bytes();
+        let mut stream = bytes.as_slice();
+
+        let header = MessageHeader::from_bytes(&mut stream)?;
+
+        assert_eq!(FilterLoad::from_bytes(header, &mut stream)?, filter_load);
+        Ok(())
+    }
+
+    #[test]
+    fn test_filter_load_with_too_many_hash_funcs_is_rejected() -> Result<(), MessageError> {
+        let filter_load = FilterLoad::new(vec![11, 17, 9, 7], vec![0; 2], 51, 0, 0);
+        let bytes = filter_load.to_bytes();
+        let mut stream = bytes.as_slice();
+
+        let header = MessageHeader::from_bytes(&mut stream)?;
+
+        assert!(FilterLoad::from_bytes(header, &mut stream).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,140 @@
+use super::compact_size::CompactSizeUInt;
+use super::header::MessageHeader;
+use super::message_constants::{GET_BLOCKS_COMMAND, HEADER_BYTES_SIZE, MAX_LOCATOR_COUNT};
+use super::message_error::MessageError;
+use super::read_from_bytes::{read_i32_from_bytes, read_vec_from_bytes};
+use bitcoin_hashes::sha256d;
+use bitcoin_hashes::Hash;
+use std::io::Read;
+
+/// Represents the `getblocks` message in the Bitcoin protocol.
+///
+/// It is answered with an `inv` message announcing the blocks after the first hash of the
+/// locator that belongs to the main chain of the receiving node, up to the stopping hash.
+#[derive(Debug, PartialEq)]
+pub struct GetBlocks {
+    header: MessageHeader,
+    version: i32,
+    hash_count: CompactSizeUInt,
+    locator: Vec<u8>,
+    stopping_hash: Vec<u8>,
+}
+
+impl GetBlocks {
+    /// Creates a new `GetBlocks` message with the specified parameters.
+    ///
+    /// # Arguments
+    ///
+    /// * `start_string` - The start string of the message.
+    /// * `version` - The version number.
+    /// * `locator` - The hashes of the block locator, from the tip of the chain backwards.
+    /// * `stopping_hash` - The hash of the block to stop at.
+    ///
+    /// # Returns
+    ///
+    /// A new `GetBlocks` message.
+    pub fn new(
+        start_string: Vec<u8>,
+        version: i32,
+        locator: Vec<Vec<u8>>,
+        stopping_hash: Vec<u8>,
+    ) -> GetBlocks {
+        let header = MessageHeader::new(start_string, GET_BLOCKS_COMMAND.to_string());
+
+        let mut get_blocks = GetBlocks {
+            header,
+            version,
+            hash_count: CompactSizeUInt::from_number(locator.len() as u64),
+            locator: locator.concat(),
+            stopping_hash,
+        };
+
+        let stream: Vec<u8> = get_blocks.to_bytes();
+        let payload_size = stream.len() - HEADER_BYTES_SIZE;
+        let checksum =
+            sha256d::Hash::hash(&stream[HEADER_BYTES_SIZE..]).to_byte_array()[..4].to_vec();
+
+        get_blocks
+            .header
+            .update_payload(payload_size as u32, checksum);
+        get_blocks
+    }
+
+    /// Creates a `GetBlocks` message by decoding the raw bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - The message header.
+    /// * `stream` - The byte stream to decode.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the decoded `GetBlocks` message or an error.
+    pub fn from_bytes(
+        header: MessageHeader,
+        stream: &mut dyn Read,
+    ) -> Result<GetBlocks, MessageError> {
+        if header.get_command_name() != GET_BLOCKS_COMMAND {
+            return Err(MessageError::InvalidInputGetBlocks);
+        }
+
+        let version = read_i32_from_bytes(stream, true)?;
+        let hash_count = CompactSizeUInt::from_bytes_bounded(stream, MAX_LOCATOR_COUNT)?;
+        let locator = read_vec_from_bytes(stream, (hash_count.value() * 32) as usize)?;
+        let stopping_hash = read_vec_from_bytes(stream, 32)?;
+
+        Ok(GetBlocks {
+            header,
+            version,
+            hash_count,
+            locator,
+            stopping_hash,
+        })
+    }
+
+    /// Converts the `GetBlocks` message to its raw byte representation.
+    ///
+    /// # Returns
+    ///
+    /// The byte representation of the `GetBlocks` message.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.header.to_bytes();
+
+        buffer.extend(self.version.to_le_bytes());
+        buffer.extend(self.hash_count.to_bytes());
+        buffer.extend(&self.locator);
+        buffer.extend(&self.stopping_hash);
+
+        buffer
+    }
+
+    /// Returns the hashes of the block locator, from the tip of the chain backwards.
+    pub fn get_locator(&self) -> Vec<Vec<u8>> {
+        self.locator.chunks(32).map(<[u8]>::to_vec).collect()
+    }
+
+    pub fn get_stopping_hash(&self) -> &Vec<u8> {
+        &self.stopping_hash
+    }
+}
+
+#[cfg(test)]
+mod get_blocks_test {
+    use super::*;
+
+    #[test]
+    fn test_new_get_blocks_from_bytes() -> Result<(), MessageError> {
+        let locator = vec![vec![1u8; 32], vec![2u8; 32], vec![3u8; 32]];
+        let get_blocks = GetBlocks::new(vec![11, 17, 9, 7], 70016, locator.clone(), vec![0; 32]);
+        let bytes = get_blocks.to_bytes();
+        let mut stream = bytes.as_slice();
+
+        let header = MessageHeader::from_bytes(&mut stream)?;
+        let get_blocks_recv = GetBlocks::from_bytes(header, &mut stream)?;
+
+        assert_eq!(get_blocks, get_blocks_recv);
+        assert_eq!(get_blocks_recv.get_locator(), locator);
+        assert_eq!(*get_blocks_recv.get_stopping_hash(), vec![0; 32]);
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
 use super::inventory::Inventory;
-use super::message_constants::HEADER_BYTES_SIZE;
+use super::message_constants::{HEADER_BYTES_SIZE, MAX_INV_COUNT};
 use super::message_error::MessageError;
+use super::serialization::{items_to_bytes, read_vec};
 use crate::messages::compact_size::CompactSizeUInt;
 use crate::messages::header::MessageHeader;
 use crate::messages::message_constants::GET_DATA_COMMAND;
@@ -24,15 +25,23 @@ impl GetData {
     ///
     /// * `start_string` - The start string of the message.
     /// * `inventory_list` - The list of inventory items.
+    /// * `witness` - Whether the witness data of transactions and blocks is requested as well,
+    ///   which must only be done with peers that serve it.
     ///
     /// # Returns
     ///
     /// A new `GetData` message.
-    pub fn new(start_string: Vec<u8>, mut inventory_list: Vec<Inventory>) -> GetData {
+    pub fn new(
+        start_string: Vec<u8>,
+        mut inventory_list: Vec<Inventory>,
+        witness: bool,
+    ) -> GetData {
         let header = MessageHeader::new(start_string, GET_DATA_COMMAND.to_string());
 
-        for inventory in inventory_list.iter_mut() {
-            inventory.update_to_segwit();
+        if witness {
+            for inventory in inventory_list.iter_mut() {
+                inventory.update_to_segwit();
+            }
         }
 
         let mut get_data = GetData {
@@ -73,12 +82,7 @@ impl GetData {
             return Err(MessageError::InvalidInputGetData);
         }
 
-        let count = CompactSizeUInt::from_bytes(stream)?;
-        let mut inventory_list = Vec::new();
-
-        for _i in 0..count.value() {
-            inventory_list.push(Inventory::from_bytes(stream)?);
-        }
+        let (count, inventory_list) = read_vec::<Inventory>(stream, MAX_INV_COUNT as u64)?;
         Ok(GetData {
             header,
             count,
@@ -96,12 +100,15 @@ impl GetData {
 
         buffer.extend(self.count.to_bytes());
 
-        for inv in self.inventory_list.iter() {
-            buffer.extend(inv.to_bytes())
-        }
+        buffer.extend(items_to_bytes(&self.inventory_list));
 
         buffer
     }
+
+    /// Returns a clone of the list of inventory items.
+    pub fn get_inventories(&self) -> Vec<Inventory> {
+        self.inventory_list.clone()
+    }
 }
 
 #[cfg(test)]
@@ -116,7 +123,7 @@ mod get_data_test {
 
         let inventory_list = vec![inv1, inv2];
 
-        let get_data_env = GetData::new(start_string, inventory_list);
+        let get_data_env = GetData::new(start_string, inventory_list, true);
 
         let get_data_env_bytes = get_data_env.to_bytes();
 
@@ -1,6 +1,6 @@
 use super::compact_size::CompactSizeUInt;
 use super::header::MessageHeader;
-use super::message_constants::{GET_HEADERS_COMMAND, HEADER_BYTES_SIZE};
+use super::message_constants::{GET_HEADERS_COMMAND, HEADER_BYTES_SIZE, MAX_LOCATOR_COUNT};
 use super::message_error::MessageError;
 use super::read_from_bytes::{read_i32_from_bytes, read_vec_from_bytes};
 use bitcoin_hashes::sha256d;
@@ -13,7 +13,7 @@ pub struct GetHeaders {
     header: MessageHeader,
     version: i32,
     hash_count: CompactSizeUInt,
-    last_block_header: Vec<u8>,
+    locator: Vec<u8>,
     stopping_hash: Vec<u8>,
 }
 
@@ -24,7 +24,7 @@ impl GetHeaders {
     ///
     /// * `start_string` - The start string of the message.
     /// * `version` - The version number.
-    /// * `last_block_header` - The hash of the last known block header.
+    /// * `locator` - The hashes of the block locator, from the tip of the chain backwards.
     /// * `stopping_hash` - The hash of the block to stop at.
     ///
     /// # Returns
@@ -33,7 +33,7 @@ impl GetHeaders {
     pub fn new(
         start_string: Vec<u8>,
         version: i32,
-        last_block_header: Vec<u8>,
+        locator: Vec<Vec<u8>>,
         stopping_hash: Vec<u8>,
     ) -> GetHeaders {
         let header = MessageHeader::new(start_string, GET_HEADERS_COMMAND.to_string());
@@ -41,8 +41,8 @@ impl GetHeaders {
         let mut get_headers = GetHeaders {
             header,
             version,
-            hash_count: CompactSizeUInt::from_number(1),
-            last_block_header,
+            hash_count: CompactSizeUInt::from_number(locator.len() as u64),
+            locator: locator.concat(),
             stopping_hash,
         };
 
@@ -76,15 +76,15 @@ impl GetHeaders {
         }
 
         let version = read_i32_from_bytes(stream, true)?;
-        let hash_count = CompactSizeUInt::from_bytes(stream)?;
-        let last_block_header = read_vec_from_bytes(stream, (hash_count.value() * 32) as usize)?;
+        let hash_count = CompactSizeUInt::from_bytes_bounded(stream, MAX_LOCATOR_COUNT)?;
+        let locator = read_vec_from_bytes(stream, (hash_count.value() * 32) as usize)?;
         let stopping_hash = read_vec_from_bytes(stream, 32)?;
 
         Ok(GetHeaders {
             header,
             version,
             hash_count,
-            last_block_header,
+            locator,
             stopping_hash,
         })
     }
@@ -99,11 +99,20 @@ impl GetHeaders {
 
         buffer.extend(self.version.to_le_bytes());
         buffer.extend(self.hash_count.to_bytes());
-        buffer.extend(&self.last_block_header);
+        buffer.extend(&self.locator);
         buffer.extend(&self.stopping_hash);
 
         buffer
     }
+
+    /// Returns the hashes of the block locator, from the tip of the chain backwards.
+    pub fn get_locator(&self) -> Vec<Vec<u8>> {
+        self.locator.chunks(32).map(<[u8]>::to_vec).collect()
+    }
+
+    pub fn get_stopping_hash(&self) -> &Vec<u8> {
+        &self.stopping_hash
+    }
 }
 
 #[cfg(test)]
@@ -113,10 +122,10 @@ mod get_data_test {
     fn test_new_get_headers_from_bytes() -> Result<(), MessageError> {
         let start_string = vec![11u8, 17, 9, 7];
         let version = 70016 as i32;
-        let last_block_header = [0u8; 32].to_vec();
+        let locator = vec![vec![1u8; 32], vec![2u8; 32]];
         let stopping_hash = [0; 32].to_vec();
 
-        let get_headers = GetHeaders::new(start_string, version, last_block_header, stopping_hash);
+        let get_headers = GetHeaders::new(start_string, version, locator.clone(), stopping_hash);
         let get_headers_bytes = get_headers.to_bytes();
         let mut stream = get_headers_bytes.as_slice();
 
@@ -124,6 +133,7 @@ mod get_data_test {
         let get_header_recv = GetHeaders::from_bytes(header, &mut stream)?;
 
         assert_eq!(get_headers, get_header_recv);
+        assert_eq!(get_header_recv.get_locator(), locator);
         Ok(())
     }
 }
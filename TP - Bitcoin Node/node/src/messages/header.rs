@@ -1,10 +1,13 @@
 use super::read_from_bytes::fill_command;
 use super::{
+    command::Command,
     message_error::MessageError,
     read_from_bytes::{read_string_from_bytes, read_u32_from_bytes, read_vec_from_bytes},
 };
 use crate::messages::message_constants::CHECKSUM_EMPTY_MSG;
+use crate::messages::message_constants::MAX_PAYLOAD_SIZE;
 use crate::messages::message_constants::PAYLOAD_EMPTY_MSG;
+use bitcoin_hashes::{sha256d, Hash};
 use std::io::Read;
 
 /// Represents the header of a Bitcoin protocol message.
@@ -41,13 +44,18 @@ impl MessageHeader {
     /// # Returns
     ///
     /// * `Result<MessageHeader, MessageError>` - The parsed `MessageHeader` if successful, or an error
-    ///   if the stream does not contain a valid header.
+    ///   if the stream does not contain a valid header. Headers announcing a payload larger than
+    ///   `MAX_PAYLOAD_SIZE` are rejected with `MessageError::PayloadTooLarge`.
     pub fn from_bytes(stream: &mut dyn Read) -> Result<MessageHeader, MessageError> {
         let start_string = read_vec_from_bytes(stream, 4)?;
         let command_name = read_string_from_bytes(stream, 12)?;
         let payload_size = read_u32_from_bytes(stream, true)?;
         let checksum = read_vec_from_bytes(stream, 4)?;
 
+        if payload_size > MAX_PAYLOAD_SIZE {
+            return Err(MessageError::PayloadTooLarge);
+        }
+
         Ok(MessageHeader {
             start_string,
             command_name,
@@ -56,6 +64,26 @@ impl MessageHeader {
         })
     }
 
+    /// Reads the payload of the message the header belongs to, checking it against the checksum
+    /// of the header, so a corrupted payload never reaches the parsers.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - The byte stream to read the payload from, right after the header.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<u8>, MessageError>` - The payload if its checksum matches, or
+    ///   `MessageError::InvalidChecksum` if it does not.
+    pub fn read_payload(&self, stream: &mut dyn Read) -> Result<Vec<u8>, MessageError> {
+        let payload = read_vec_from_bytes(stream, self.payload_size as usize)?;
+
+        if sha256d::Hash::hash(&payload)[..4] != self.checksum[..] {
+            return Err(MessageError::InvalidChecksum);
+        }
+        Ok(payload)
+    }
+
     /// Converts the `MessageHeader` to a byte representation.
     ///
     /// The byte representation follows the Bitcoin protocol message header format.
@@ -103,6 +131,15 @@ impl MessageHeader {
         &self.command_name
     }
 
+    /// Returns the command of the `MessageHeader`.
+    ///
+    /// # Returns
+    ///
+    /// The command, or `None` if the command name is unknown.
+    pub fn get_command(&self) -> Option<Command> {
+        Command::from_name(&self.command_name)
+    }
+
     /// Returns the payload size of the `MessageHeader`.
     ///
     /// # Returns
@@ -125,7 +162,11 @@ impl MessageHeader {
 #[cfg(test)]
 mod header_test {
     use super::MessageHeader;
-    use crate::messages::{message_constants::VERACK_COMMAND, message_error::MessageError};
+    use crate::messages::{
+        message_constants::{MAX_PAYLOAD_SIZE, VERACK_COMMAND},
+        message_error::MessageError,
+        ping::Ping,
+    };
 
     #[test]
     fn test_new_header_from_bytes() -> Result<(), MessageError> {
@@ -146,4 +187,33 @@ mod header_test {
         assert_eq!(header_env, header_recv);
         Ok(())
     }
+
+    #[test]
+    fn test_payload_is_checked_against_the_checksum() -> Result<(), MessageError> {
+        let mut ping_bytes = Ping::new(vec![11u8, 17, 9, 7], 42).to_bytes();
+
+        let header = MessageHeader::from_bytes(&mut ping_bytes.as_slice())?;
+        assert_eq!(
+            header.read_payload(&mut &ping_bytes[24..])?,
+            ping_bytes[24..]
+        );
+
+        ping_bytes[30] ^= 1;
+        assert!(matches!(
+            header.read_payload(&mut &ping_bytes[24..]),
+            Err(MessageError::InvalidChecksum)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_headers_of_oversized_payloads_are_rejected() {
+        let mut header = MessageHeader::new(vec![11u8, 17, 9, 7], VERACK_COMMAND.to_string());
+        header.update_payload(MAX_PAYLOAD_SIZE + 1, vec![0u8; 4]);
+
+        assert!(matches!(
+            MessageHeader::from_bytes(&mut header.to_bytes().as_slice()),
+            Err(MessageError::PayloadTooLarge)
+        ));
+    }
 }
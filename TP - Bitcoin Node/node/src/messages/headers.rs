@@ -1,6 +1,6 @@
 use super::super::block_mod::block_header::BlockHeader;
 use super::header::MessageHeader;
-use super::message_constants::{HEADERS_COMMAND, HEADER_BYTES_SIZE};
+use super::message_constants::{HEADERS_COMMAND, HEADER_BYTES_SIZE, MAX_HEADERS_COUNT};
 use super::{compact_size::CompactSizeUInt, message_error::MessageError};
 use bitcoin_hashes::sha256d;
 use bitcoin_hashes::Hash;
@@ -60,7 +60,7 @@ impl Headers {
         if header.get_command_name() != HEADERS_COMMAND {
             return Err(MessageError::InvalidInputHeaders);
         }
-        let count = CompactSizeUInt::from_bytes(stream)?;
+        let count = CompactSizeUInt::from_bytes_bounded(stream, MAX_HEADERS_COUNT)?;
         let mut headers: Vec<BlockHeader> = Vec::new();
 
         for _i in 0..count.value() {
@@ -1,33 +1,64 @@
 use std::io::Read;
 
 use super::{
-    compact_size::CompactSizeUInt, header::MessageHeader, message_constants::INV_COMMAND,
+    compact_size::CompactSizeUInt,
+    header::MessageHeader,
+    message_constants::{HEADER_BYTES_SIZE, INV_COMMAND, MAX_INV_COUNT},
     message_error::MessageError,
+    serialization::{items_to_bytes, read_vec},
 };
 use crate::messages::inventory::Inventory;
+use bitcoin_hashes::{sha256d, Hash};
 
 /// Represents an inventory message.
 #[derive(Debug)]
 pub struct Inv {
-    _header: MessageHeader,
-    _count: CompactSizeUInt,
+    header: MessageHeader,
+    count: CompactSizeUInt,
     inventory_list: Vec<Inventory>,
     data_type: u32,
 }
 
 impl Inv {
+    /// Creates a new inventory message announcing the given inventory items.
+    ///
+    /// # Arguments
+    ///
+    /// * `start_string` - The start string of the message.
+    /// * `inventory_list` - The list of inventory items.
+    ///
+    /// # Returns
+    ///
+    /// A new `Inv` message.
+    pub fn new(start_string: Vec<u8>, inventory_list: Vec<Inventory>) -> Inv {
+        let data_type = match inventory_list.last() {
+            Some(inventory) => inventory.get_type(),
+            None => 0,
+        };
+
+        let mut inv = Inv {
+            header: MessageHeader::new(start_string, INV_COMMAND.to_string()),
+            count: CompactSizeUInt::from_number(inventory_list.len() as u64),
+            inventory_list,
+            data_type,
+        };
+
+        let stream: Vec<u8> = inv.to_bytes();
+        let payload_size = stream.len() - HEADER_BYTES_SIZE;
+        let checksum =
+            sha256d::Hash::hash(&stream[HEADER_BYTES_SIZE..]).to_byte_array()[..4].to_vec();
+
+        inv.header.update_payload(payload_size as u32, checksum);
+        inv
+    }
+
     /// Parses an inventory message from the provided byte stream.
     pub fn from_bytes(header: MessageHeader, stream: &mut dyn Read) -> Result<Inv, MessageError> {
         if header.get_command_name() != INV_COMMAND {
             return Err(MessageError::InvalidInputInv);
         }
 
-        let count = CompactSizeUInt::from_bytes(stream)?;
-        let mut inventory_list = Vec::new();
-
-        for _ in 0..count.value() {
-            inventory_list.push(Inventory::from_bytes(stream)?)
-        }
+        let (count, inventory_list) = read_vec::<Inventory>(stream, MAX_INV_COUNT as u64)?;
 
         let data_type = inventory_list
             .last()
@@ -35,13 +66,22 @@ impl Inv {
             .get_type();
 
         Ok(Inv {
-            _header: header,
-            _count: count,
+            header,
+            count,
             inventory_list,
             data_type,
         })
     }
 
+    /// Serializes the inventory message into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.header.to_bytes();
+        buffer.extend(self.count.to_bytes());
+
+        buffer.extend(items_to_bytes(&self.inventory_list));
+        buffer
+    }
+
     /// Returns a clone of the list of inventory items.
     pub fn get_inventories(&self) -> Vec<Inventory> {
         self.inventory_list.clone()
@@ -52,3 +92,26 @@ impl Inv {
         self.data_type
     }
 }
+
+#[cfg(test)]
+mod inv_test {
+    use super::*;
+
+    #[test]
+    fn test_new_inv_from_bytes() -> Result<(), MessageError> {
+        let inventories = vec![
+            Inventory::new(2, vec![1; 32]),
+            Inventory::new(2, vec![2; 32]),
+        ];
+        let bytes = Inv::new(vec![11, 17, 9, 7], inventories).to_bytes();
+        let mut stream = bytes.as_slice();
+
+        let header = MessageHeader::from_bytes(&mut stream)?;
+        let inv = Inv::from_bytes(header, &mut stream)?;
+
+        assert_eq!(inv.get_inventories().len(), 2);
+        assert_eq!(inv.get_inventories()[1].get_data(), vec![2; 32]);
+        assert_eq!(inv.get_type(), 2);
+        Ok(())
+    }
+}
@@ -1,7 +1,22 @@
 use super::message_error::MessageError;
 use super::read_from_bytes::*;
 use std::io::Read;
-use std::net::Ipv6Addr;
+use std::net::{IpAddr, Ipv6Addr, SocketAddr};
+
+/// Converts an IP address to the 16 bytes form used by the network messages, where IPv4 addresses
+/// are mapped to IPv6.
+pub fn to_network_ip(ip: IpAddr) -> Ipv6Addr {
+    match ip {
+        IpAddr::V4(ip) => ip.to_ipv6_mapped(),
+        IpAddr::V6(ip) => ip,
+    }
+}
+
+/// Converts an IP address read from a network message back to its canonical form, so IPv4-mapped
+/// addresses are handled as IPv4 addresses.
+pub fn to_socket_addr(ip: Ipv6Addr, port: u16) -> SocketAddr {
+    SocketAddr::new(IpAddr::V6(ip).to_canonical(), port)
+}
 
 /// Represents an IP address with associated information.
 #[derive(Debug, PartialEq)]
@@ -23,6 +38,17 @@ impl Ip {
         }
     }
 
+    /// Creates a new `Ip` instance from the socket address of a peer.
+    pub fn from_socket_addr(time: u32, services: u64, addr: &SocketAddr) -> Ip {
+        Ip::new(time, services, to_network_ip(addr.ip()), addr.port())
+    }
+
+    /// Returns the socket address the `Ip` instance points to, IPv4 addresses in their canonical
+    /// form.
+    pub fn get_socket_addr(&self) -> SocketAddr {
+        to_socket_addr(self.ip, self.port)
+    }
+
     /// Parses a byte stream and constructs an `Ip` instance from it.
     ///
     /// # Arguments
@@ -102,4 +128,25 @@ mod ip_test {
 
         assert_eq!(ip_bytes.len(), 30);
     }
+
+    #[test]
+    fn test_ip_socket_addr() -> Result<(), MessageError> {
+        let ipv4: SocketAddr = "127.0.0.1:18333"
+            .parse()
+            .map_err(|_| MessageError::ReadFromBytes)?;
+        let ipv6: SocketAddr = "[2001:db8::1]:18333"
+            .parse()
+            .map_err(|_| MessageError::ReadFromBytes)?;
+
+        for addr in [ipv4, ipv6] {
+            let bytes = Ip::from_socket_addr(0, 0, &addr).to_bytes();
+            let ip = Ip::from_bytes(&mut bytes.as_slice())?;
+
+            assert_eq!(ip.get_socket_addr(), addr);
+        }
+
+        let bytes = Ip::from_socket_addr(0, 0, &ipv4).to_bytes();
+        assert_eq!(bytes[22..28], [0xff, 0xff, 127, 0, 0, 1]);
+        Ok(())
+    }
 }
@@ -0,0 +1,74 @@
+use super::header::MessageHeader;
+use super::message_constants::{MEMPOOL_COMMAND, PAYLOAD_EMPTY_MSG};
+use super::message_error::MessageError;
+
+/// Represents a Mempool message in the Bitcoin protocol (BIP35).
+///
+/// A peer sending it asks to be announced, through `inv` messages, the transactions held in the
+/// mempool of the node. The message has no payload.
+#[derive(Debug, PartialEq)]
+pub struct Mempool {
+    header: MessageHeader,
+}
+
+impl Mempool {
+    /// Creates a new Mempool message with the specified start string.
+    ///
+    /// # Arguments
+    ///
+    /// * `start_string`: The start string of the message.
+    ///
+    /// # Returns
+    ///
+    /// A new `Mempool` message.
+    pub fn new(start_string: Vec<u8>) -> Mempool {
+        Mempool {
+            header: MessageHeader::new(start_string, MEMPOOL_COMMAND.to_string()),
+        }
+    }
+
+    /// Parses a Mempool message from the provided message header.
+    ///
+    /// # Arguments
+    ///
+    /// * `header`: The message header.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the parsed `Mempool` message or an error if the header does not belong
+    /// to an empty Mempool message.
+    pub fn from_header(header: MessageHeader) -> Result<Mempool, MessageError> {
+        if header.get_command_name() != MEMPOOL_COMMAND
+            || header.get_payload_size() != PAYLOAD_EMPTY_MSG
+        {
+            return Err(MessageError::InvalidInputMempool);
+        }
+
+        Ok(Mempool { header })
+    }
+
+    /// Serializes the `Mempool` message into a byte vector.
+    ///
+    /// # Returns
+    ///
+    /// A byte vector representing the serialized `Mempool` message.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.header.to_bytes()
+    }
+}
+
+#[cfg(test)]
+mod mempool_test {
+    use super::*;
+
+    #[test]
+    fn test_new_mempool_from_bytes() -> Result<(), MessageError> {
+        let mempool = Mempool::new(vec![11, 17, 9, 7]);
+        let bytes = mempool.to_bytes();
+
+        let header = MessageHeader::from_bytes(&mut bytes.as_slice())?;
+
+        assert_eq!(Mempool::from_header(header)?, mempool);
+        Ok(())
+    }
+}
@@ -0,0 +1,325 @@
+use super::compact_size::CompactSizeUInt;
+use super::header::MessageHeader;
+use super::message_constants::{
+    HEADER_BYTES_SIZE, MAX_BLOCK_TX_COUNT, MAX_PAYLOAD_SIZE, MERKLE_BLOCK_COMMAND,
+};
+use super::message_error::MessageError;
+use super::read_from_bytes::{read_u32_from_bytes, read_vec_from_bytes};
+use crate::block_mod::{block::Block, block_header::BlockHeader};
+use crate::proof_of_inclusion_mod::proof_of_inclusion::compute_hash;
+use bitcoin_hashes::sha256d;
+use bitcoin_hashes::Hash;
+use std::io::Read;
+
+/// Represents a MerkleBlock message in the Bitcoin protocol (BIP37).
+///
+/// It is the answer to a `getdata` message requesting a filtered block. It holds the header of the
+/// block and a partial merkle tree proving which transactions of the block matched the bloom filter
+/// of the peer.
+#[derive(Debug, PartialEq)]
+pub struct MerkleBlock {
+    header: MessageHeader,
+    block_header: BlockHeader,
+    total_transactions: u32,
+    hash_count: CompactSizeUInt,
+    hashes: Vec<Vec<u8>>,
+    flag_byte_count: CompactSizeUInt,
+    flags: Vec<u8>,
+}
+
+/// Returns the amount of nodes of the merkle tree at the given height, the leaves being at height 0.
+fn tree_width(total_transactions: u32, height: u32) -> u32 {
+    (total_transactions + (1 << height) - 1) >> height
+}
+
+/// Returns the height of the root of the merkle tree.
+fn tree_height(total_transactions: u32) -> u32 {
+    let mut height = 0;
+
+    while tree_width(total_transactions, height) > 1 {
+        height += 1;
+    }
+    height
+}
+
+/// Computes the hash of a node of the merkle tree.
+fn calculate_hash(height: u32, position: u32, txids: &[Vec<u8>]) -> Vec<u8> {
+    if height == 0 {
+        return txids[position as usize].clone();
+    }
+
+    let left = calculate_hash(height - 1, position * 2, txids);
+    let right = if position * 2 + 1 < tree_width(txids.len() as u32, height - 1) {
+        calculate_hash(height - 1, position * 2 + 1, txids)
+    } else {
+        left.clone()
+    };
+
+    compute_hash(left, right)
+}
+
+/// Traverses the merkle tree depth-first, appending a flag bit for every node visited and the hashes
+/// of the nodes whose subtree does not need to be descended.
+fn build_partial_tree(
+    height: u32,
+    position: u32,
+    txids: &[Vec<u8>],
+    matches: &[bool],
+    hashes: &mut Vec<Vec<u8>>,
+    bits: &mut Vec<bool>,
+) {
+    let start = (position << height) as usize;
+    let end = (((position + 1) << height) as usize).min(txids.len());
+    let parent_of_match = matches[start..end].iter().any(|matched| *matched);
+
+    bits.push(parent_of_match);
+
+    if height == 0 || !parent_of_match {
+        hashes.push(calculate_hash(height, position, txids));
+        return;
+    }
+
+    build_partial_tree(height - 1, position * 2, txids, matches, hashes, bits);
+
+    if position * 2 + 1 < tree_width(txids.len() as u32, height - 1) {
+        build_partial_tree(height - 1, position * 2 + 1, txids, matches, hashes, bits);
+    }
+}
+
+/// Traverses the partial merkle tree in the same order it was built, computing the hash of every
+/// node visited and collecting the ids of the matched transactions.
+fn extract_partial_tree(
+    total_transactions: u32,
+    (height, position): (u32, u32),
+    (hashes, bits): (&[Vec<u8>], &[bool]),
+    (hashes_used, bits_used): (&mut usize, &mut usize),
+    matched: &mut Vec<Vec<u8>>,
+) -> Option<Vec<u8>> {
+    let parent_of_match = *bits.get(*bits_used)?;
+    *bits_used += 1;
+
+    if height == 0 || !parent_of_match {
+        let hash = hashes.get(*hashes_used)?.clone();
+        *hashes_used += 1;
+
+        if height == 0 && parent_of_match {
+            matched.push(hash.clone());
+        }
+        return Some(hash);
+    }
+
+    let left = extract_partial_tree(
+        total_transactions,
+        (height - 1, position * 2),
+        (hashes, bits),
+        (hashes_used, bits_used),
+        matched,
+    )?;
+    let right = if position * 2 + 1 < tree_width(total_transactions, height - 1) {
+        extract_partial_tree(
+            total_transactions,
+            (height - 1, position * 2 + 1),
+            (hashes, bits),
+            (hashes_used, bits_used),
+            matched,
+        )?
+    } else {
+        left.clone()
+    };
+
+    Some(compute_hash(left, right))
+}
+
+impl MerkleBlock {
+    /// Creates a new MerkleBlock message for the given block and its matched transactions.
+    ///
+    /// # Arguments
+    ///
+    /// * `start_string`: The start string of the message.
+    /// * `block`: The filtered block.
+    /// * `matches`: Whether each transaction of the block, coinbase included, matched the filter.
+    ///
+    /// # Returns
+    ///
+    /// A new `MerkleBlock` message.
+    pub fn new(start_string: Vec<u8>, block: &Block, matches: &[bool]) -> MerkleBlock {
+        let header = MessageHeader::new(start_string, MERKLE_BLOCK_COMMAND.to_string());
+        let txids = block.get_txn_ids();
+        let total_transactions = txids.len() as u32;
+
+        let mut hashes = Vec::new();
+        let mut bits = Vec::new();
+        build_partial_tree(
+            tree_height(total_transactions),
+            0,
+            &txids,
+            matches,
+            &mut hashes,
+            &mut bits,
+        );
+
+        let mut flags = vec![0u8; bits.len().div_ceil(8)];
+        for (index, bit) in bits.iter().enumerate() {
+            flags[index / 8] |= (*bit as u8) << (index % 8);
+        }
+
+        let mut merkle_block = MerkleBlock {
+            header,
+            block_header: block.get_header().clone(),
+            total_transactions,
+            hash_count: CompactSizeUInt::from_number(hashes.len() as u64),
+            hashes,
+            flag_byte_count: CompactSizeUInt::from_number(flags.len() as u64),
+            flags,
+        };
+
+        let stream: Vec<u8> = merkle_block.to_bytes();
+        let payload_size = stream.len() - HEADER_BYTES_SIZE;
+        let checksum =
+            sha256d::Hash::hash(&stream[HEADER_BYTES_SIZE..]).to_byte_array()[..4].to_vec();
+
+        merkle_block
+            .header
+            .update_payload(payload_size as u32, checksum);
+        merkle_block
+    }
+
+    /// Parses a MerkleBlock message from the provided message header and byte stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `header`: The message header.
+    /// * `stream`: The byte stream to read the message from.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the parsed `MerkleBlock` message or an error if parsing fails.
+    pub fn from_bytes(
+        header: MessageHeader,
+        stream: &mut dyn Read,
+    ) -> Result<MerkleBlock, MessageError> {
+        if header.get_command_name() != MERKLE_BLOCK_COMMAND {
+            return Err(MessageError::InvalidInputMerkleBlock);
+        }
+
+        let block_header = BlockHeader::from_bytes(stream)?;
+        let total_transactions = read_u32_from_bytes(stream, true)?;
+        let hash_count = CompactSizeUInt::from_bytes_bounded(stream, MAX_BLOCK_TX_COUNT)?;
+        let mut hashes = Vec::new();
+
+        for _ in 0..hash_count.value() {
+            hashes.push(read_vec_from_bytes(stream, 32)?);
+        }
+
+        let flag_byte_count = CompactSizeUInt::from_bytes_bounded(stream, MAX_PAYLOAD_SIZE as u64)?;
+        let flags = read_vec_from_bytes(stream, flag_byte_count.value() as usize)?;
+
+        Ok(MerkleBlock {
+            header,
+            block_header,
+            total_transactions,
+            hash_count,
+            hashes,
+            flag_byte_count,
+            flags,
+        })
+    }
+
+    /// Serializes the `MerkleBlock` message into a byte vector.
+    ///
+    /// # Returns
+    ///
+    /// A byte vector representing the serialized `MerkleBlock` message.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.header.to_bytes();
+        buffer.extend(self.block_header.to_bytes());
+        buffer.extend(self.total_transactions.to_le_bytes());
+        buffer.extend(self.hash_count.to_bytes());
+
+        for hash in self.hashes.iter() {
+            buffer.extend(hash);
+        }
+
+        buffer.extend(self.flag_byte_count.to_bytes());
+        buffer.extend(&self.flags);
+        buffer
+    }
+
+    /// Extracts the ids of the matched transactions from the partial merkle tree.
+    ///
+    /// # Returns
+    ///
+    /// The ids of the matched transactions, or `None` if the partial merkle tree is malformed or
+    /// its root does not match the merkle root of the block header.
+    pub fn get_matched_txids(&self) -> Option<Vec<Vec<u8>>> {
+        if self.total_transactions == 0 {
+            return None;
+        }
+
+        let bits: Vec<bool> = (0..self.flags.len() * 8)
+            .map(|index| self.flags[index / 8] & (1 << (index % 8)) != 0)
+            .collect();
+        let mut matched = Vec::new();
+        let (mut hashes_used, mut bits_used) = (0, 0);
+
+        let root = extract_partial_tree(
+            self.total_transactions,
+            (tree_height(self.total_transactions), 0),
+            (&self.hashes, &bits),
+            (&mut hashes_used, &mut bits_used),
+            &mut matched,
+        )?;
+
+        if root != *self.block_header.get_merkle_root() || hashes_used != self.hashes.len() {
+            return None;
+        }
+        Some(matched)
+    }
+
+    pub fn get_block_header(&self) -> &BlockHeader {
+        &self.block_header
+    }
+}
+
+#[cfg(test)]
+mod merkle_block_test {
+    use super::*;
+    use crate::block_mod::block::test_block_bytes;
+
+    #[test]
+    fn test_new_merkle_block_from_bytes() -> Result<(), MessageError> {
+        let bytes = test_block_bytes();
+        let block = Block::from_bytes(&mut bytes.as_slice())?;
+        let merkle_block = MerkleBlock::new(vec![11, 17, 9, 7], &block, &[true]);
+        let bytes = merkle_block.to_bytes();
+        let mut stream = bytes.as_slice();
+
+        let header = MessageHeader::from_bytes(&mut stream)?;
+
+        assert_eq!(MerkleBlock::from_bytes(header, &mut stream)?, merkle_block);
+        Ok(())
+    }
+
+    #[test]
+    fn test_partial_merkle_tree_proves_matched_txids() {
+        let txids: Vec<Vec<u8>> = (0..5).map(|i| vec![i; 32]).collect();
+        let matches = [false, true, false, false, true];
+        let root = calculate_hash(tree_height(5), 0, &txids);
+
+        let mut hashes = Vec::new();
+        let mut bits = Vec::new();
+        build_partial_tree(tree_height(5), 0, &txids, &matches, &mut hashes, &mut bits);
+
+        let mut matched = Vec::new();
+        let extracted = extract_partial_tree(
+            5,
+            (tree_height(5), 0),
+            (&hashes, &bits),
+            (&mut 0, &mut 0),
+            &mut matched,
+        );
+
+        assert_eq!(extracted, Some(root));
+        assert_eq!(matched, vec![txids[1].clone(), txids[4].clone()]);
+    }
+}
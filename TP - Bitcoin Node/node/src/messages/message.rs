@@ -0,0 +1,185 @@
+use super::{
+    addr::Addr,
+    command::Command,
+    fee_filter::FeeFilter,
+    filter_add::FilterAdd,
+    filter_clear::FilterClear,
+    filter_load::FilterLoad,
+    get_blocks::GetBlocks,
+    get_data::GetData,
+    get_headers::GetHeaders,
+    header::MessageHeader,
+    headers::Headers,
+    inv::Inv,
+    mempool::Mempool,
+    merkle_block::MerkleBlock,
+    message_error::MessageError,
+    not_found::NotFound,
+    ping::Ping,
+    pong::Pong,
+    reject::Reject,
+    send_headers::SendHeaders,
+    serialization::{FromBytes, ToBytes},
+    tx::Tx,
+    version::Version,
+    wtxid_relay::WtxidRelay,
+};
+use crate::messages::block_message::BlockMessage;
+use std::io::Read;
+
+/// A message of the Bitcoin protocol, identified by the command of its header.
+///
+/// Every message can be serialized along with its header, and deserialized from the header read
+/// off a stream followed by its payload, so messages can be handled without knowing their type.
+pub trait Message: Sized {
+    /// The command of the header of the message.
+    const COMMAND: Command;
+
+    /// Serializes the message, header included.
+    fn serialize(&self) -> Vec<u8>;
+
+    /// Deserializes the message from its header and the payload that follows it in the stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `MessageError` if the header does not belong to the message or the payload is
+    /// malformed.
+    fn deserialize(header: MessageHeader, stream: &mut dyn Read) -> Result<Self, MessageError>;
+}
+
+/// Implements `ToBytes` and `FromBytes` for a message, which is read along with its header.
+macro_rules! impl_message_bytes {
+    ($message:ty) => {
+        impl ToBytes for $message {
+            fn to_bytes(&self) -> Vec<u8> {
+                self.serialize()
+            }
+        }
+
+        impl FromBytes for $message {
+            fn from_bytes(stream: &mut dyn Read) -> Result<Self, MessageError> {
+                read_message(stream)
+            }
+        }
+    };
+}
+
+/// Implements `Message` for messages that carry a payload, through their `from_bytes` and
+/// `to_bytes` functions.
+macro_rules! impl_message {
+    ($message:ty, $command:expr) => {
+        impl_message_bytes!($message);
+
+        impl Message for $message {
+            const COMMAND: Command = $command;
+
+            fn serialize(&self) -> Vec<u8> {
+                self.to_bytes()
+            }
+
+            fn deserialize(
+                header: MessageHeader,
+                stream: &mut dyn Read,
+            ) -> Result<Self, MessageError> {
+                <$message>::from_bytes(header, stream)
+            }
+        }
+    };
+}
+
+/// Implements `Message` for messages made of a header alone, through their `from_header` and
+/// `to_bytes` functions.
+macro_rules! impl_empty_message {
+    ($message:ty, $command:expr) => {
+        impl_message_bytes!($message);
+
+        impl Message for $message {
+            const COMMAND: Command = $command;
+
+            fn serialize(&self) -> Vec<u8> {
+                self.to_bytes()
+            }
+
+            fn deserialize(header: MessageHeader, _: &mut dyn Read) -> Result<Self, MessageError> {
+                <$message>::from_header(header)
+            }
+        }
+    };
+}
+
+impl_message!(Addr, Command::Addr);
+impl_message!(FeeFilter, Command::FeeFilter);
+impl_message!(FilterAdd, Command::FilterAdd);
+impl_message!(FilterLoad, Command::FilterLoad);
+impl_message!(GetBlocks, Command::GetBlocks);
+impl_message!(GetData, Command::GetData);
+impl_message!(GetHeaders, Command::GetHeaders);
+impl_message!(Headers, Command::Headers);
+impl_message!(Inv, Command::Inv);
+impl_message!(MerkleBlock, Command::MerkleBlock);
+impl_message!(NotFound, Command::NotFound);
+impl_message!(Ping, Command::Ping);
+impl_message!(Pong, Command::Pong);
+impl_message!(Reject, Command::Reject);
+impl_message!(Version, Command::Version);
+
+impl_empty_message!(FilterClear, Command::FilterClear);
+impl_empty_message!(Mempool, Command::Mempool);
+impl_empty_message!(SendHeaders, Command::SendHeaders);
+impl_empty_message!(WtxidRelay, Command::WtxidRelay);
+
+/// The `tx` and `block` messages are only ever sent, since received transactions and blocks are
+/// parsed straight from their payload.
+impl ToBytes for Tx {
+    fn to_bytes(&self) -> Vec<u8> {
+        Tx::to_bytes(self)
+    }
+}
+
+impl ToBytes for BlockMessage {
+    fn to_bytes(&self) -> Vec<u8> {
+        BlockMessage::to_bytes(self)
+    }
+}
+
+/// Reads a message of the expected type from a stream, header included.
+///
+/// # Errors
+///
+/// Returns a `MessageError` if the stream does not hold a well-formed message of the expected
+/// type.
+pub fn read_message<M: Message>(stream: &mut dyn Read) -> Result<M, MessageError> {
+    let header = MessageHeader::from_bytes(stream)?;
+
+    M::deserialize(header, stream)
+}
+
+#[cfg(test)]
+mod message_test {
+    use super::*;
+
+    fn round_trip<M: Message + std::fmt::Debug + PartialEq>(
+        message: M,
+    ) -> Result<(), MessageError> {
+        let bytes = message.serialize();
+        let header = MessageHeader::from_bytes(&mut bytes.as_slice())?;
+        assert_eq!(header.get_command(), Some(M::COMMAND));
+
+        assert_eq!(read_message::<M>(&mut bytes.as_slice())?, message);
+        Ok(())
+    }
+
+    #[test]
+    fn test_messages_round_trip_through_the_trait() -> Result<(), MessageError> {
+        let start_string = vec![11u8, 17, 9, 7];
+
+        round_trip(Ping::new(start_string.clone(), 7))?;
+        round_trip(Pong::new(start_string.clone(), 7))?;
+        round_trip(SendHeaders::new(start_string.clone()))?;
+        round_trip(FeeFilter::new(start_string.clone(), 1000))?;
+
+        let ping_bytes = Ping::new(start_string, 7).serialize();
+        assert!(read_message::<Pong>(&mut ping_bytes.as_slice()).is_err());
+        Ok(())
+    }
+}
@@ -3,6 +3,7 @@ pub const VERACK_COMMAND: &str = "verack";
 pub const ADDR_COMMAND: &str = "addr";
 pub const GET_ADDR_COMMAND: &str = "getaddr";
 pub const GET_HEADERS_COMMAND: &str = "getheaders";
+pub const GET_BLOCKS_COMMAND: &str = "getblocks";
 pub const HEADERS_COMMAND: &str = "headers";
 pub const PONG_COMMAND: &str = "pong";
 pub const PING_COMMAND: &str = "ping";
@@ -11,10 +12,18 @@ pub const BLOCK_COMMAND: &str = "block";
 pub const SEND_HEADERS_COMMAND: &str = "sendheaders";
 pub const INV_COMMAND: &str = "inv";
 pub const TX_COMMAND: &str = "tx";
+pub const FEE_FILTER_COMMAND: &str = "feefilter";
 pub const FILTER_LOAD_COMMAND: &str = "filterload";
+pub const FILTER_ADD_COMMAND: &str = "filteradd";
+pub const FILTER_CLEAR_COMMAND: &str = "filterclear";
 pub const MERKLE_BLOCK_COMMAND: &str = "merkleblock";
+pub const MEMPOOL_COMMAND: &str = "mempool";
+pub const REJECT_COMMAND: &str = "reject";
+pub const NOT_FOUND_COMMAND: &str = "notfound";
+pub const WTXID_RELAY_COMMAND: &str = "wtxidrelay";
 
 pub const HEADER_BYTES_SIZE: usize = 24;
+pub const MAX_PAYLOAD_SIZE: u32 = 4_000_000;
 
 pub const PAYLOAD_EMPTY_MSG: u32 = 0;
 pub const CHECKSUM_EMPTY_MSG: [u8; 4] = [93, 246, 224, 226];
@@ -28,3 +37,31 @@ pub const MSG_TX: u32 = 1;
 pub const MSG_BLOCK: u32 = 2;
 pub const MSG_WITNESS_TX: u32 = 0x40000001;
 pub const MSG_WITNESS_BLOCK: u32 = 0x40000002;
+
+pub const MAX_BLOOM_FILTER_SIZE: usize = 36000;
+pub const MAX_BLOOM_HASH_FUNCS: u32 = 50;
+pub const MAX_FILTER_ADD_SIZE: usize = 520;
+
+pub const MAX_INV_COUNT: usize = 50000;
+pub const MAX_GET_BLOCKS_COUNT: usize = 500;
+pub const MAX_HEADERS_COUNT: u64 = 2000;
+pub const MAX_ADDR_COUNT: u64 = 1000;
+pub const MAX_LOCATOR_COUNT: u64 = 101;
+pub const MAX_USER_AGENT_SIZE: u64 = 256;
+
+pub const MIN_TX_SIZE: u64 = 60;
+pub const MIN_TX_IN_SIZE: u64 = 41;
+pub const MIN_TX_OUT_SIZE: u64 = 9;
+pub const MAX_BLOCK_TX_COUNT: u64 = MAX_PAYLOAD_SIZE as u64 / MIN_TX_SIZE;
+pub const MAX_TX_IN_COUNT: u64 = MAX_PAYLOAD_SIZE as u64 / MIN_TX_IN_SIZE;
+pub const MAX_TX_OUT_COUNT: u64 = MAX_PAYLOAD_SIZE as u64 / MIN_TX_OUT_SIZE;
+
+pub const REJECT_MALFORMED: u8 = 0x01;
+pub const REJECT_INVALID: u8 = 0x10;
+pub const REJECT_OBSOLETE: u8 = 0x11;
+pub const REJECT_DUPLICATE: u8 = 0x12;
+pub const REJECT_NONSTANDARD: u8 = 0x40;
+pub const REJECT_DUST: u8 = 0x41;
+pub const REJECT_INSUFFICIENT_FEE: u8 = 0x42;
+pub const REJECT_CHECKPOINT: u8 = 0x43;
+pub const REJECT_DATA_SIZE: usize = 32;
@@ -1,5 +1,5 @@
 use hex::FromHexError;
-use std::num::TryFromIntError;
+use std::{io::ErrorKind, num::TryFromIntError};
 
 #[derive(Debug)]
 pub enum MessageError {
@@ -7,18 +7,50 @@ pub enum MessageError {
     InvalidInputPing,
     InvalidInputAddr,
     InvalidInputGetData,
+    InvalidInputGetBlocks,
     InvalidInputHeaders,
     InvalidInputInv,
     InvalidInputPong,
     InvalidInputVersion,
+    InvalidInputSendHeaders,
+    InvalidInputFeeFilter,
+    InvalidInputFilterLoad,
+    InvalidInputFilterAdd,
+    InvalidInputFilterClear,
+    InvalidInputMerkleBlock,
+    InvalidInputMempool,
+    InvalidInputSubmitBlock,
+    InvalidInputSendRawTx,
+    InvalidInputReject,
+    InvalidInputNotFound,
+    InvalidInputWtxidRelay,
     InvalidBlockCommitment,
+    InvalidScript,
+    InvalidChecksum,
+    PayloadTooLarge,
+    TooManyElements,
+    NonCanonicalCompactSize,
+    UnknownCommand,
     DecodeHex,
     TryInto,
+    Timeout,
 }
 
 impl From<std::io::Error> for MessageError {
-    fn from(_: std::io::Error) -> MessageError {
-        MessageError::ReadFromBytes
+    fn from(err: std::io::Error) -> MessageError {
+        match err.kind() {
+            ErrorKind::WouldBlock | ErrorKind::TimedOut => MessageError::Timeout,
+            _ => MessageError::ReadFromBytes,
+        }
+    }
+}
+
+impl From<bincode::Error> for MessageError {
+    fn from(err: bincode::Error) -> MessageError {
+        match *err {
+            bincode::ErrorKind::SizeLimit => MessageError::PayloadTooLarge,
+            _ => MessageError::ReadFromBytes,
+        }
     }
 }
 
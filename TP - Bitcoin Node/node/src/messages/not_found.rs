@@ -0,0 +1,103 @@
+use std::io::Read;
+
+use super::{
+    compact_size::CompactSizeUInt,
+    header::MessageHeader,
+    message_constants::{HEADER_BYTES_SIZE, MAX_INV_COUNT, NOT_FOUND_COMMAND},
+    message_error::MessageError,
+    serialization::{items_to_bytes, read_vec},
+};
+use crate::messages::inventory::Inventory;
+use bitcoin_hashes::{sha256d, Hash};
+
+/// Represents a notfound message, sent in answer to a `getdata` message to report the requested
+/// inventory items the peer does not have.
+#[derive(Debug)]
+pub struct NotFound {
+    header: MessageHeader,
+    count: CompactSizeUInt,
+    inventory_list: Vec<Inventory>,
+}
+
+impl NotFound {
+    /// Creates a new notfound message reporting the given inventory items.
+    ///
+    /// # Arguments
+    ///
+    /// * `start_string` - The start string of the message.
+    /// * `inventory_list` - The list of inventory items that were not found.
+    ///
+    /// # Returns
+    ///
+    /// A new `NotFound` message.
+    pub fn new(start_string: Vec<u8>, inventory_list: Vec<Inventory>) -> NotFound {
+        let mut not_found = NotFound {
+            header: MessageHeader::new(start_string, NOT_FOUND_COMMAND.to_string()),
+            count: CompactSizeUInt::from_number(inventory_list.len() as u64),
+            inventory_list,
+        };
+
+        let stream: Vec<u8> = not_found.to_bytes();
+        let payload_size = stream.len() - HEADER_BYTES_SIZE;
+        let checksum =
+            sha256d::Hash::hash(&stream[HEADER_BYTES_SIZE..]).to_byte_array()[..4].to_vec();
+
+        not_found
+            .header
+            .update_payload(payload_size as u32, checksum);
+        not_found
+    }
+
+    /// Parses a notfound message from the provided byte stream.
+    pub fn from_bytes(
+        header: MessageHeader,
+        stream: &mut dyn Read,
+    ) -> Result<NotFound, MessageError> {
+        if header.get_command_name() != NOT_FOUND_COMMAND {
+            return Err(MessageError::InvalidInputNotFound);
+        }
+
+        let (count, inventory_list) = read_vec::<Inventory>(stream, MAX_INV_COUNT as u64)?;
+
+        Ok(NotFound {
+            header,
+            count,
+            inventory_list,
+        })
+    }
+
+    /// Serializes the notfound message into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.header.to_bytes();
+        buffer.extend(self.count.to_bytes());
+
+        buffer.extend(items_to_bytes(&self.inventory_list));
+        buffer
+    }
+
+    /// Returns a clone of the list of inventory items that were not found.
+    pub fn get_inventories(&self) -> Vec<Inventory> {
+        self.inventory_list.clone()
+    }
+}
+
+#[cfg(test)]
+mod not_found_test {
+    use super::*;
+
+    #[test]
+    fn test_new_not_found_from_bytes() -> Result<(), MessageError> {
+        let inventories = vec![
+            Inventory::new(2, vec![1; 32]),
+            Inventory::new(1, vec![2; 32]),
+        ];
+        let bytes = NotFound::new(vec![11, 17, 9, 7], inventories.clone()).to_bytes();
+        let mut stream = bytes.as_slice();
+
+        let header = MessageHeader::from_bytes(&mut stream)?;
+        let not_found = NotFound::from_bytes(header, &mut stream)?;
+
+        assert_eq!(not_found.get_inventories(), inventories);
+        Ok(())
+    }
+}
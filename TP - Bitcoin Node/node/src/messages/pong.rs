@@ -69,6 +69,10 @@ impl Pong {
         buffer.extend(self.nonce.to_le_bytes());
         buffer
     }
+
+    pub fn get_nonce(&self) -> u64 {
+        self.nonce
+    }
 }
 
 #[cfg(test)]
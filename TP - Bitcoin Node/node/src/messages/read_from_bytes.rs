@@ -259,7 +259,7 @@ pub fn read_vec_from_bytes(stream: &mut dyn Read, size: usize) -> Result<Vec<u8>
         Ok(()) => {}
         Err(error) => {
             println!("Error: {:?}", error);
-            return Err(MessageError::ReadFromBytes);
+            return Err(MessageError::from(error));
         }
     }
 
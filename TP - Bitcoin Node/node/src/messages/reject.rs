@@ -0,0 +1,195 @@
+use super::compact_size::CompactSizeUInt;
+use super::header::MessageHeader;
+use super::message_constants::{HEADER_BYTES_SIZE, REJECT_COMMAND, REJECT_DATA_SIZE};
+use super::message_error::MessageError;
+use super::read_from_bytes::{read_string_from_bytes, read_u8_from_bytes, read_vec_from_bytes};
+use bitcoin_hashes::sha256d;
+use bitcoin_hashes::Hash;
+use std::io::Read;
+
+/// Represents a Reject message in the Bitcoin protocol (BIP61).
+///
+/// It is sent to a peer to report that one of its messages was rejected, along with the command
+/// of the rejected message, a code and a human readable reason. When a transaction or a block is
+/// rejected, the data holds its hash.
+#[derive(Debug, PartialEq)]
+pub struct Reject {
+    header: MessageHeader,
+    message: String,
+    code: u8,
+    reason: String,
+    data: Vec<u8>,
+}
+
+impl Reject {
+    /// Creates a new Reject message with the specified start string and rejection details.
+    ///
+    /// # Arguments
+    ///
+    /// * `start_string`: The start string of the message.
+    /// * `message`: The command of the rejected message.
+    /// * `code`: The code of the rejection.
+    /// * `reason`: The reason of the rejection.
+    /// * `data`: The hash of the rejected transaction or block, or empty for other messages.
+    ///
+    /// # Returns
+    ///
+    /// A new `Reject` message.
+    pub fn new(
+        start_string: Vec<u8>,
+        message: &str,
+        code: u8,
+        reason: &str,
+        data: Vec<u8>,
+    ) -> Reject {
+        let header = MessageHeader::new(start_string, REJECT_COMMAND.to_string());
+
+        let mut reject = Reject {
+            header,
+            message: message.to_string(),
+            code,
+            reason: reason.to_string(),
+            data,
+        };
+
+        let stream: Vec<u8> = reject.to_bytes();
+        let payload_size = stream.len() - HEADER_BYTES_SIZE;
+        let checksum =
+            sha256d::Hash::hash(&stream[HEADER_BYTES_SIZE..]).to_byte_array()[..4].to_vec();
+
+        reject.header.update_payload(payload_size as u32, checksum);
+        reject
+    }
+
+    /// Parses a Reject message from the provided message header and byte stream.
+    ///
+    /// The data is only read if the payload has room for a hash after the reason. The lengths of the
+    /// command and the reason are bounded by the size of the payload, so a peer cannot make the
+    /// node allocate without bound.
+    ///
+    /// # Arguments
+    ///
+    /// * `header`: The message header.
+    /// * `stream`: The byte stream to read the message from.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the parsed `Reject` message or an error if parsing fails.
+    pub fn from_bytes(
+        header: MessageHeader,
+        stream: &mut dyn Read,
+    ) -> Result<Reject, MessageError> {
+        if header.get_command_name() != REJECT_COMMAND {
+            return Err(MessageError::InvalidInputReject);
+        }
+
+        let payload = read_vec_from_bytes(stream, header.get_payload_size() as usize)?;
+        let payload_size = payload.len() as u64;
+        let mut payload = payload.as_slice();
+
+        let message_size = CompactSizeUInt::from_bytes_bounded(&mut payload, payload_size)?;
+        let message = read_string_from_bytes(&mut payload, message_size.value() as usize)?;
+        let code = read_u8_from_bytes(&mut payload)?;
+        let reason_size = CompactSizeUInt::from_bytes_bounded(&mut payload, payload_size)?;
+        let reason = read_string_from_bytes(&mut payload, reason_size.value() as usize)?;
+
+        let data = if payload.len() >= REJECT_DATA_SIZE {
+            read_vec_from_bytes(&mut payload, REJECT_DATA_SIZE)?
+        } else {
+            vec![]
+        };
+
+        Ok(Reject {
+            header,
+            message,
+            code,
+            reason,
+            data,
+        })
+    }
+
+    /// Serializes the `Reject` message into a byte vector.
+    ///
+    /// # Returns
+    ///
+    /// A byte vector representing the serialized `Reject` message.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.header.to_bytes();
+        buffer.extend(CompactSizeUInt::from_number(self.message.len() as u64).to_bytes());
+        buffer.extend(self.message.as_bytes());
+        buffer.push(self.code);
+        buffer.extend(CompactSizeUInt::from_number(self.reason.len() as u64).to_bytes());
+        buffer.extend(self.reason.as_bytes());
+        buffer.extend(&self.data);
+        buffer
+    }
+
+    /// Returns the command of the rejected message.
+    pub fn get_message(&self) -> &String {
+        &self.message
+    }
+
+    pub fn get_code(&self) -> u8 {
+        self.code
+    }
+
+    pub fn get_reason(&self) -> &String {
+        &self.reason
+    }
+
+    /// Returns the hash of the rejected transaction or block, or an empty vector if there is none.
+    pub fn get_data(&self) -> &Vec<u8> {
+        &self.data
+    }
+}
+
+#[cfg(test)]
+mod reject_test {
+    use super::*;
+    use crate::messages::message_constants::{REJECT_INVALID, REJECT_MALFORMED};
+
+    #[test]
+    fn test_new_reject_from_bytes() -> Result<(), MessageError> {
+        let reject = Reject::new(
+            vec![11, 17, 9, 7],
+            "block",
+            REJECT_INVALID,
+            "high-hash",
+            vec![7; 32],
+        );
+        let bytes = reject.to_bytes();
+        let mut stream = bytes.as_slice();
+
+        let header = MessageHeader::from_bytes(&mut stream)?;
+
+        assert_eq!(header.get_payload_size(), 1 + 5 + 1 + 1 + 9 + 32);
+        assert_eq!(Reject::from_bytes(header, &mut stream)?, reject);
+        Ok(())
+    }
+
+    #[test]
+    fn test_reject_without_data_from_bytes() -> Result<(), MessageError> {
+        let reject = Reject::new(vec![11, 17, 9, 7], "ping", REJECT_MALFORMED, "", vec![]);
+        let bytes = reject.to_bytes();
+        let mut stream = bytes.as_slice();
+
+        let header = MessageHeader::from_bytes(&mut stream)?;
+        let read_reject = Reject::from_bytes(header, &mut stream)?;
+
+        assert!(read_reject.get_data().is_empty());
+        assert_eq!(read_reject.get_message(), "ping");
+        Ok(())
+    }
+
+    #[test]
+    fn test_reject_with_oversized_lengths_is_refused() {
+        let mut header = MessageHeader::new(vec![11, 17, 9, 7], REJECT_COMMAND.to_string());
+        header.update_payload(10, vec![0; 4]);
+        let payload = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f, 0x00];
+
+        assert!(matches!(
+            Reject::from_bytes(header, &mut payload.as_slice()),
+            Err(MessageError::TooManyElements)
+        ));
+    }
+}
@@ -0,0 +1,74 @@
+use super::header::MessageHeader;
+use super::message_constants::{PAYLOAD_EMPTY_MSG, SEND_HEADERS_COMMAND};
+use super::message_error::MessageError;
+
+/// Represents a SendHeaders message in the Bitcoin protocol (BIP130).
+///
+/// A peer sending it asks to be announced new blocks through `headers` messages instead of `inv`
+/// messages. The message has no payload.
+#[derive(Debug, PartialEq)]
+pub struct SendHeaders {
+    header: MessageHeader,
+}
+
+impl SendHeaders {
+    /// Creates a new SendHeaders message with the specified start string.
+    ///
+    /// # Arguments
+    ///
+    /// * `start_string`: The start string of the message.
+    ///
+    /// # Returns
+    ///
+    /// A new `SendHeaders` message.
+    pub fn new(start_string: Vec<u8>) -> SendHeaders {
+        SendHeaders {
+            header: MessageHeader::new(start_string, SEND_HEADERS_COMMAND.to_string()),
+        }
+    }
+
+    /// Parses a SendHeaders message from the provided message header.
+    ///
+    /// # Arguments
+    ///
+    /// * `header`: The message header.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the parsed `SendHeaders` message or an error if the header does not
+    /// belong to an empty SendHeaders message.
+    pub fn from_header(header: MessageHeader) -> Result<SendHeaders, MessageError> {
+        if header.get_command_name() != SEND_HEADERS_COMMAND
+            || header.get_payload_size() != PAYLOAD_EMPTY_MSG
+        {
+            return Err(MessageError::InvalidInputSendHeaders);
+        }
+
+        Ok(SendHeaders { header })
+    }
+
+    /// Serializes the `SendHeaders` message into a byte vector.
+    ///
+    /// # Returns
+    ///
+    /// A byte vector representing the serialized `SendHeaders` message.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.header.to_bytes()
+    }
+}
+
+#[cfg(test)]
+mod send_headers_test {
+    use super::*;
+
+    #[test]
+    fn test_new_send_headers_from_bytes() -> Result<(), MessageError> {
+        let send_headers = SendHeaders::new(vec![11, 17, 9, 7]);
+        let bytes = send_headers.to_bytes();
+
+        let header = MessageHeader::from_bytes(&mut bytes.as_slice())?;
+
+        assert_eq!(SendHeaders::from_header(header)?, send_headers);
+        Ok(())
+    }
+}
@@ -0,0 +1,212 @@
+use super::{
+    compact_size::CompactSizeUInt, inventory::Inventory, ip::Ip, message_error::MessageError,
+};
+use crate::block_mod::{
+    block::Block, block_header::BlockHeader, coinbase::Coinbase, outpoint::Outpoint,
+    transaction::Transaction, tx_in::TxIn, tx_in_coinbase::TxInCoinbase, tx_out::TxOut,
+    witness::Witness,
+};
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+use std::io::Read;
+
+/// A value that can be serialized into the bytes it is sent through the network or stored with.
+pub trait ToBytes {
+    /// Serializes the value into a byte vector.
+    fn to_bytes(&self) -> Vec<u8>;
+
+    /// Appends the serialized value to the given buffer, so many values can be serialized into a
+    /// single allocation.
+    fn to_bytes_into(&self, buffer: &mut Vec<u8>) {
+        buffer.extend(self.to_bytes());
+    }
+}
+
+/// A value that can be deserialized from a byte stream.
+pub trait FromBytes: Sized {
+    /// Deserializes the value from the stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `MessageError` if the stream does not hold a well-formed value.
+    fn from_bytes(stream: &mut dyn Read) -> Result<Self, MessageError>;
+}
+
+/// Implements `ToBytes` and `FromBytes` through the `to_bytes` and `from_bytes` functions of the
+/// type, along with its `to_bytes_into` function for the types listed after `into:`.
+macro_rules! impl_bytes {
+    ($($value:ty),*; into: $($into:ty),*) => {
+        impl_bytes!($($value),*);
+
+        $(
+            impl ToBytes for $into {
+                fn to_bytes(&self) -> Vec<u8> {
+                    <$into>::to_bytes(self)
+                }
+
+                fn to_bytes_into(&self, buffer: &mut Vec<u8>) {
+                    <$into>::to_bytes_into(self, buffer)
+                }
+            }
+
+            impl FromBytes for $into {
+                fn from_bytes(stream: &mut dyn Read) -> Result<Self, MessageError> {
+                    <$into>::from_bytes(stream)
+                }
+            }
+        )*
+    };
+    ($($value:ty),*) => {
+        $(
+            impl ToBytes for $value {
+                fn to_bytes(&self) -> Vec<u8> {
+                    <$value>::to_bytes(self)
+                }
+            }
+
+            impl FromBytes for $value {
+                fn from_bytes(stream: &mut dyn Read) -> Result<Self, MessageError> {
+                    <$value>::from_bytes(stream)
+                }
+            }
+        )*
+    };
+}
+
+impl_bytes!(
+    Inventory,
+    Ip;
+    into:
+    Block,
+    BlockHeader,
+    Coinbase,
+    CompactSizeUInt,
+    Outpoint,
+    TxIn,
+    TxInCoinbase,
+    TxOut,
+    Witness
+);
+
+/// Transactions are serialized along with their witness data, as they are sent through the
+/// network.
+impl ToBytes for Transaction {
+    fn to_bytes(&self) -> Vec<u8> {
+        Transaction::to_bytes(self, true)
+    }
+
+    fn to_bytes_into(&self, buffer: &mut Vec<u8>) {
+        Transaction::to_bytes_into(self, true, buffer)
+    }
+}
+
+impl FromBytes for Transaction {
+    fn from_bytes(stream: &mut dyn Read) -> Result<Self, MessageError> {
+        Transaction::from_bytes(stream)
+    }
+}
+
+/// Implements `Serialize` and `Deserialize` through `ToBytes` and `FromBytes`, so the types can be
+/// part of the messages encoded with serde while keeping the serialization they have in the
+/// network.
+macro_rules! impl_serde {
+    ($($value:ty),*) => {
+        $(
+            impl Serialize for $value {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    ToBytes::to_bytes(self).serialize(serializer)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $value {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let bytes = Vec::<u8>::deserialize(deserializer)?;
+
+                    <$value as FromBytes>::from_bytes(&mut bytes.as_slice())
+                        .map_err(|err| D::Error::custom(format!("{:?}", err)))
+                }
+            }
+        )*
+    };
+}
+
+impl_serde!(Transaction, TxOut);
+
+/// Reads as many items as the given count from the stream.
+///
+/// # Errors
+///
+/// Returns a `MessageError` if any of the items is malformed.
+pub fn read_items<T: FromBytes>(
+    stream: &mut dyn Read,
+    count: &CompactSizeUInt,
+) -> Result<Vec<T>, MessageError> {
+    let mut items = Vec::new();
+
+    for _ in 0..count.value() {
+        items.push(T::from_bytes(stream)?);
+    }
+    Ok(items)
+}
+
+/// Reads a vector of items prefixed by its length, of at most `max` items.
+///
+/// # Returns
+///
+/// The length of the vector along with its items, or `MessageError::TooManyElements` if the
+/// vector holds more than `max` items.
+pub fn read_vec<T: FromBytes>(
+    stream: &mut dyn Read,
+    max: u64,
+) -> Result<(CompactSizeUInt, Vec<T>), MessageError> {
+    let count = CompactSizeUInt::from_bytes_bounded(stream, max)?;
+    let items = read_items(stream, &count)?;
+
+    Ok((count, items))
+}
+
+/// Serializes the given items one after the other, without their length.
+pub fn items_to_bytes<T: ToBytes>(items: &[T]) -> Vec<u8> {
+    let mut buffer = Vec::new();
+
+    items_to_bytes_into(items, &mut buffer);
+    buffer
+}
+
+/// Appends the given items to the buffer one after the other, without their length.
+pub fn items_to_bytes_into<T: ToBytes>(items: &[T], buffer: &mut Vec<u8>) {
+    for item in items {
+        item.to_bytes_into(buffer);
+    }
+}
+
+/// Serializes the given items prefixed by their length.
+pub fn vec_to_bytes<T: ToBytes>(items: &[T]) -> Vec<u8> {
+    let mut buffer = CompactSizeUInt::from_number(items.len() as u64).to_bytes();
+
+    items_to_bytes_into(items, &mut buffer);
+    buffer
+}
+
+#[cfg(test)]
+mod serialization_test {
+    use super::*;
+
+    #[test]
+    fn test_length_prefixed_vectors_round_trip() -> Result<(), MessageError> {
+        let tx_outs = vec![
+            TxOut::new(1000, vec![0x51]),
+            TxOut::new(2500, vec![0x00, 0x14, 0xab]),
+        ];
+
+        let bytes = vec_to_bytes(&tx_outs);
+        let (count, read): (CompactSizeUInt, Vec<TxOut>) = read_vec(&mut bytes.as_slice(), 2)?;
+
+        assert_eq!(count.value(), 2);
+        assert_eq!(items_to_bytes(&read), items_to_bytes(&tx_outs));
+        assert!(matches!(
+            read_vec::<TxOut>(&mut bytes.as_slice(), 1),
+            Err(MessageError::TooManyElements)
+        ));
+        Ok(())
+    }
+}
@@ -11,16 +11,24 @@ use bitcoin_hashes::Hash;
 pub struct Tx {
     header: MessageHeader,
     pub transaction: Transaction,
+    witness: bool,
 }
 
 impl Tx {
     /// Creates a new `Tx` with the given start string and transaction.
     pub fn new(start_string: Vec<u8>, transaction: Transaction) -> Tx {
+        Tx::with_witness(start_string, transaction, true)
+    }
+
+    /// Creates a new `Tx` with the given start string and transaction, holding its witness data
+    /// only if it was requested through a `MSG_WITNESS_TX` inventory.
+    pub fn with_witness(start_string: Vec<u8>, transaction: Transaction, witness: bool) -> Tx {
         let header = MessageHeader::new(start_string, TX_COMMAND.to_string());
 
         let mut tx = Tx {
             header,
             transaction,
+            witness,
         };
 
         let stream: Vec<u8> = tx.to_bytes();
@@ -38,7 +46,7 @@ impl Tx {
     /// Converts the `Tx` object to its byte representation.
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut buff = self.header.to_bytes();
-        buff.extend(self.transaction.to_bytes(self.transaction.is_segwit()));
+        buff.extend(self.transaction.to_bytes(self.witness));
 
         buff
     }
@@ -1,6 +1,8 @@
 use super::compact_size::CompactSizeUInt;
 use super::header::MessageHeader;
+use super::ip::{to_network_ip, to_socket_addr};
 use super::message_constants::HEADER_BYTES_SIZE;
+use super::message_constants::MAX_USER_AGENT_SIZE;
 use super::message_constants::VERSION_COMMAND;
 use super::message_error::MessageError;
 use super::read_from_bytes::*;
@@ -9,7 +11,7 @@ use bitcoin_hashes::sha256d;
 use bitcoin_hashes::Hash;
 use chrono::Utc;
 use std::io::Read;
-use std::net::Ipv6Addr;
+use std::net::{Ipv6Addr, SocketAddr};
 
 /// Version Message is one of the messages exchanged during the handshake process in the Bitcoin protocol.
 /// This message is used for two nodes to introduce themselves and agree on connection details such as:
@@ -39,7 +41,13 @@ pub struct Version {
 }
 
 impl Version {
-    pub fn new(ip: Ipv6Addr, settings: &Settings) -> Version {
+    /// Creates the version message sent to a peer.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr_recv` - The address of the peer, either IPv4 or IPv6.
+    /// * `settings` - The node settings, holding the address of the node.
+    pub fn new(addr_recv: &SocketAddr, settings: &Settings) -> Version {
         let header = MessageHeader::new(settings.get_start_string(), VERSION_COMMAND.to_string());
 
         let mut version = Version {
@@ -48,10 +56,10 @@ impl Version {
             services: settings.get_services(),
             timestamp: Utc::now().timestamp(),
             addr_recv_services: settings.get_services(),
-            addr_recv_ip: ip,
-            addr_recv_port: settings.get_port(),
+            addr_recv_ip: to_network_ip(addr_recv.ip()),
+            addr_recv_port: addr_recv.port(),
             addr_trans_services: settings.get_services(),
-            addr_trans_ip: settings.get_ip(),
+            addr_trans_ip: to_network_ip(settings.get_ip()),
             addr_trans_port: settings.get_port(),
             nonce: rand::random(),
             user_agent_bytes: CompactSizeUInt::from_number(settings.get_user_agent().len() as u64),
@@ -90,13 +98,13 @@ impl Version {
         let addr_trans_ip = read_ipv6_from_bytes(stream)?;
         let addr_trans_port = read_u16_from_bytes(stream, false)?;
         let nonce = read_u64_from_bytes(stream, true)?;
-        let user_agent_bytes = CompactSizeUInt::from_bytes(stream)?;
+        let user_agent_bytes = CompactSizeUInt::from_bytes_bounded(stream, MAX_USER_AGENT_SIZE)?;
         let user_agent = read_string_from_bytes(stream, user_agent_bytes.value() as usize)?;
         let start_height = read_i32_from_bytes(stream, true)?;
 
         let relay = match read_u8_from_bytes(stream) {
-            Ok(value) => value == 0,
-            Err(_) => false,
+            Ok(value) => value != 0,
+            Err(_) => true,
         };
 
         Ok(Version {
@@ -136,11 +144,7 @@ impl Version {
         buff.extend(self.user_agent.as_bytes());
         buff.extend(self.start_height.to_le_bytes());
 
-        if self.relay {
-            buff.extend([0u8]);
-        } else {
-            buff.extend([1u8]);
-        }
+        buff.extend([self.relay as u8]);
 
         buff
     }
@@ -151,20 +155,51 @@ impl Version {
     pub fn get_services(&self) -> u64 {
         self.services
     }
+    pub fn get_timestamp(&self) -> i64 {
+        self.timestamp
+    }
+    pub fn get_start_height(&self) -> i32 {
+        self.start_height
+    }
+    pub fn get_user_agent(&self) -> &String {
+        &self.user_agent
+    }
+    /// Returns the random nonce of the message, which lets a node detect that it connected to
+    /// itself.
+    pub fn get_nonce(&self) -> u64 {
+        self.nonce
+    }
+    /// Returns whether the transmitting node wants transactions to be announced to it. Peers that
+    /// omit the field want them (BIP37).
+    pub fn get_relay(&self) -> bool {
+        self.relay
+    }
+
+    /// Returns the address of the receiving node, as seen by the transmitting node.
+    pub fn get_addr_recv(&self) -> SocketAddr {
+        to_socket_addr(self.addr_recv_ip, self.addr_recv_port)
+    }
+
+    /// Returns the address of the transmitting node.
+    pub fn get_addr_trans(&self) -> SocketAddr {
+        to_socket_addr(self.addr_trans_ip, self.addr_trans_port)
+    }
 }
 
 #[cfg(test)]
 mod version_test {
     use super::*;
+    use std::fs;
 
     #[test]
     fn test_new_version_from_bytes() -> Result<(), MessageError> {
         let ip_recv = Ipv6Addr::new(2, 2, 2, 2, 2, 2, 2, 2);
+        let addr_recv = SocketAddr::new(ip_recv.into(), 18444);
 
         let settings =
             Settings::from_file("settings/nodo.conf").map_err(|_| MessageError::ReadFromBytes)?;
 
-        let version_env = Version::new(ip_recv, &settings);
+        let version_env = Version::new(&addr_recv, &settings);
 
         let verison_env_bytes = version_env.to_bytes();
 
@@ -174,6 +209,40 @@ mod version_test {
         let version_recv = Version::from_bytes(header, &mut stream)?;
 
         assert_eq!(version_env, version_recv);
+        assert_eq!(version_recv.get_addr_recv(), addr_recv);
+        assert_eq!(
+            version_recv.get_addr_trans(),
+            SocketAddr::new(settings.get_ip().to_canonical(), settings.get_port())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_version_identifies_the_node_as_configured() -> Result<(), MessageError> {
+        let path = "data/test_version.conf";
+        let content = fs::read_to_string("settings/nodo.conf")?
+            .lines()
+            .filter(|line| !line.starts_with("user_agent") && !line.starts_with("relay"))
+            .map(|line| format!("{}\n", line))
+            .collect::<String>()
+            + "user_agent=/test:1.0/\nrelay=false\n";
+        fs::write(path, content)?;
+
+        let settings = Settings::from_file(path);
+        fs::remove_file(path)?;
+        let settings = settings.map_err(|_| MessageError::ReadFromBytes)?;
+
+        let addr_recv = SocketAddr::from(([127, 0, 0, 1], 18444));
+        let bytes = Version::new(&addr_recv, &settings).to_bytes();
+        assert_eq!(bytes.last(), Some(&0));
+
+        let mut stream = bytes.as_slice();
+        let header = MessageHeader::from_bytes(&mut stream)?;
+        let version = Version::from_bytes(header, &mut stream)?;
+
+        assert_eq!(version.get_user_agent(), "/test:1.0/");
+        assert_eq!(version.get_services(), (1 << 3) | (1 << 10));
+        assert!(!version.get_relay());
         Ok(())
     }
 }
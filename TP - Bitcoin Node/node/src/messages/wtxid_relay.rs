@@ -0,0 +1,75 @@
+use super::header::MessageHeader;
+use super::message_constants::{PAYLOAD_EMPTY_MSG, WTXID_RELAY_COMMAND};
+use super::message_error::MessageError;
+
+/// Represents a WtxidRelay message in the Bitcoin protocol (BIP339).
+///
+/// It is sent between the `version` and `verack` messages of the handshake. Once both peers sent
+/// it, transactions are announced and requested by their wtxid instead of their txid. The message
+/// has no payload.
+#[derive(Debug, PartialEq)]
+pub struct WtxidRelay {
+    header: MessageHeader,
+}
+
+impl WtxidRelay {
+    /// Creates a new WtxidRelay message with the specified start string.
+    ///
+    /// # Arguments
+    ///
+    /// * `start_string`: The start string of the message.
+    ///
+    /// # Returns
+    ///
+    /// A new `WtxidRelay` message.
+    pub fn new(start_string: Vec<u8>) -> WtxidRelay {
+        WtxidRelay {
+            header: MessageHeader::new(start_string, WTXID_RELAY_COMMAND.to_string()),
+        }
+    }
+
+    /// Parses a WtxidRelay message from the provided message header.
+    ///
+    /// # Arguments
+    ///
+    /// * `header`: The message header.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the parsed `WtxidRelay` message or an error if the header does not
+    /// belong to an empty WtxidRelay message.
+    pub fn from_header(header: MessageHeader) -> Result<WtxidRelay, MessageError> {
+        if header.get_command_name() != WTXID_RELAY_COMMAND
+            || header.get_payload_size() != PAYLOAD_EMPTY_MSG
+        {
+            return Err(MessageError::InvalidInputWtxidRelay);
+        }
+
+        Ok(WtxidRelay { header })
+    }
+
+    /// Serializes the `WtxidRelay` message into a byte vector.
+    ///
+    /// # Returns
+    ///
+    /// A byte vector representing the serialized `WtxidRelay` message.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.header.to_bytes()
+    }
+}
+
+#[cfg(test)]
+mod wtxid_relay_test {
+    use super::*;
+
+    #[test]
+    fn test_new_wtxid_relay_from_bytes() -> Result<(), MessageError> {
+        let wtxid_relay = WtxidRelay::new(vec![11, 17, 9, 7]);
+        let bytes = wtxid_relay.to_bytes();
+
+        let header = MessageHeader::from_bytes(&mut bytes.as_slice())?;
+
+        assert_eq!(WtxidRelay::from_header(header)?, wtxid_relay);
+        Ok(())
+    }
+}
@@ -2,16 +2,64 @@
 //!
 //! # Modules
 //!
+//! - [`addr_manager`](addr_manager) - Keeps the addresses of the peers the node may connect to later on.
+//! - [`anchors`](anchors) - Persists the best-performing outbound peers to connect to them first on the next start.
+//! - [`bitcoin_network`](bitcoin_network) - Defines the supported networks and the parameters that differ between them.
 //! - [`block_download`](block_download) - Implements block download functionality for syncing with the Bitcoin network.
+//! - [`bloom_filter`](bloom_filter) - Matches transactions against the bloom filters loaded by SPV peers.
 //! - [`broadcasting`](broadcasting) - Listens for incoming new headers to update the blockchain.
+//! - [`event_bus`](event_bus) - Delivers the events of the node to the components subscribed to them.
 //! - [`handshake`](handshake) - Implements the handshake protocol for establishing connections with peers.
+//! - [`health_monitor`](health_monitor) - Evicts unhealthy peers and replaces them with fresh ones.
 //! - [`headers_download`](headers_download) - Implements headers download functionality for syncing block headers with the Bitcoin network.
+//! - [`keepalive`](keepalive) - Pings the peers periodically and drops the ones that stop answering.
+//! - [`known_txs`](known_txs) - Keeps the bounded set of transactions each peer is known to have.
+//! - [`message_capture`](message_capture) - Records the messages exchanged with the peers to a capture file in trace mode.
+//! - [`message_dispatcher`](message_dispatcher) - Maps the commands of the received messages to their handlers.
 //! - [`network_constants`](network_constants) - Defines constants related to the Bitcoin network.
 //! - [`network_error`](network_error) - Implements error handling for network-related operations.
+//! - [`network_time`](network_time) - Computes the network-adjusted time from the clocks of the peers.
+//! - [`peer_features`](peer_features) - Keeps the protocol version and services negotiated with each peer.
+//! - [`peer_heights`](peer_heights) - Keeps the best heights advertised by the peers and detects stalled syncs.
+//! - [`peer_pool`](peer_pool) - Keeps track of the live outbound connections shared across the node.
+//! - [`peer_scoring`](peer_scoring) - Scores peer misbehavior and keeps the list of banned peers.
+//! - [`peer_stats`](peer_stats) - Counts the messages each peer sent and when it was last heard from.
+//! - [`peer_stream`](peer_stream) - Splits each connection into a reading side and a writing thread fed by a queue.
+//! - [`rate_limiter`](rate_limiter) - Throttles the bytes sent to and received from the peers with token buckets.
+//! - [`rebroadcast`](rebroadcast) - Broadcasts again the wallet transactions that are not confirmed yet.
+//! - [`shutdown`](shutdown) - Coordinates the graceful shutdown of the node and its threads.
+//! - [`socks5`](socks5) - Connects to peers and resolves DNS seeds through a SOCKS5 proxy.
+//! - [`sync_monitor`](sync_monitor) - Watches the sync of the node and rotates peers when it stalls.
+//! - [`sync_progress`](sync_progress) - Keeps the progress of the initial block download.
+//! - [`work_queue`](work_queue) - Shares the pending block batches between the block download threads.
 
+pub mod addr_manager;
+pub mod anchors;
+pub mod bitcoin_network;
 pub mod block_download;
+pub mod bloom_filter;
 pub mod broadcasting;
+pub mod event_bus;
 pub mod handshake;
 pub mod headers_download;
+pub mod health_monitor;
+pub mod keepalive;
+pub mod known_txs;
+pub mod message_capture;
+pub mod message_dispatcher;
 pub mod network_constants;
 pub mod network_error;
+pub mod network_time;
+pub mod peer_features;
+pub mod peer_heights;
+pub mod peer_pool;
+pub mod peer_scoring;
+pub mod peer_stats;
+pub mod peer_stream;
+pub mod rate_limiter;
+pub mod rebroadcast;
+pub mod shutdown;
+pub mod socks5;
+pub mod sync_monitor;
+pub mod sync_progress;
+pub mod work_queue;
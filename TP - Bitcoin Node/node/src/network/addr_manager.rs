@@ -0,0 +1,85 @@
+use super::network_constants::MAX_ADDR_MANAGER_SIZE;
+
+use std::{collections::VecDeque, net::SocketAddr};
+
+/// Keeps the addresses of the peers the node may connect to later on: the ones discovered through
+/// the DNS seed that were not needed at start, and the ones advertised by the peers through `addr`
+/// messages.
+///
+/// At most `MAX_ADDR_MANAGER_SIZE` addresses are kept, the oldest ones being forgotten first.
+#[derive(Debug, Default)]
+pub struct AddrManager {
+    addrs: VecDeque<SocketAddr>,
+}
+
+impl AddrManager {
+    /// Creates an address manager without addresses.
+    pub fn new() -> AddrManager {
+        AddrManager::default()
+    }
+
+    /// Adds an address, unless it is already known.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address of the peer.
+    pub fn add(&mut self, addr: SocketAddr) {
+        if self.addrs.contains(&addr) {
+            return;
+        }
+
+        if self.addrs.len() >= MAX_ADDR_MANAGER_SIZE {
+            self.addrs.pop_front();
+        }
+        self.addrs.push_back(addr);
+    }
+
+    /// Takes the most recently added address that is not excluded, so the same peer is not tried
+    /// twice.
+    ///
+    /// # Arguments
+    ///
+    /// * `excluded` - The addresses that must not be returned, such as the connected peers.
+    ///
+    /// # Returns
+    ///
+    /// The address, or `None` if there is no address left besides the excluded ones.
+    pub fn take_fresh(&mut self, excluded: &[SocketAddr]) -> Option<SocketAddr> {
+        let position = self
+            .addrs
+            .iter()
+            .rposition(|addr| !excluded.contains(addr))?;
+
+        self.addrs.remove(position)
+    }
+
+    pub fn len(&self) -> usize {
+        self.addrs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.addrs.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod addr_manager_test {
+    use super::*;
+
+    #[test]
+    fn test_fresh_addresses_skip_excluded_and_known_ones() {
+        let mut addr_manager = AddrManager::new();
+        let first = SocketAddr::from(([127, 0, 0, 1], 18333));
+        let second = SocketAddr::from(([127, 0, 0, 2], 18333));
+
+        addr_manager.add(first);
+        addr_manager.add(second);
+        addr_manager.add(first);
+        assert_eq!(addr_manager.len(), 2);
+
+        assert_eq!(addr_manager.take_fresh(&[second]), Some(first));
+        assert_eq!(addr_manager.take_fresh(&[second]), None);
+        assert_eq!(addr_manager.take_fresh(&[]), Some(second));
+        assert!(addr_manager.is_empty());
+    }
+}
@@ -0,0 +1,113 @@
+use super::{network_constants::MAX_ANCHORS, network_error::NetworkError};
+
+use std::{fs, net::SocketAddr, str::FromStr};
+
+/// Keeps the addresses of the best-performing outbound peers, which the node connects to first on
+/// its next start (anchor connections).
+///
+/// Reconnecting to peers that were known to be honest and responsive makes it harder for an
+/// attacker to surround the node with its own peers after a restart, and speeds the reconnection
+/// up. The anchors are persisted to disk, one address per line.
+#[derive(Debug)]
+pub struct Anchors {
+    addrs: Vec<SocketAddr>,
+    file_path: Option<String>,
+}
+
+impl Anchors {
+    /// Creates an empty set of anchors that is not persisted.
+    pub fn new() -> Anchors {
+        Anchors {
+            addrs: Vec::new(),
+            file_path: None,
+        }
+    }
+
+    /// Creates the anchors, loading the ones of the last run from the given file.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_path` - The path of the file where the anchors are persisted.
+    ///
+    /// # Returns
+    ///
+    /// The anchors, or a `NetworkError` if the file exists but cannot be parsed.
+    pub fn from_file(file_path: &str) -> Result<Anchors, NetworkError> {
+        let mut anchors = Anchors::new();
+        anchors.file_path = Some(file_path.to_string());
+
+        if let Ok(content) = fs::read_to_string(file_path) {
+            let addrs = content
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| SocketAddr::from_str(line.trim()).map_err(|_| NetworkError::HandShake))
+                .collect::<Result<Vec<SocketAddr>, NetworkError>>()?;
+            anchors.set(addrs);
+        }
+
+        Ok(anchors)
+    }
+
+    /// Replaces the anchors with the given addresses, keeping the first `MAX_ANCHORS` of them.
+    ///
+    /// # Arguments
+    ///
+    /// * `addrs` - The addresses of the peers, from the best-performing one.
+    pub fn set(&mut self, mut addrs: Vec<SocketAddr>) {
+        addrs.truncate(MAX_ANCHORS);
+        self.addrs = addrs;
+    }
+
+    /// Writes the anchors to the file they were loaded from, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `NetworkError` if the file cannot be written.
+    pub fn save(&self) -> Result<(), NetworkError> {
+        let file_path = match &self.file_path {
+            Some(file_path) => file_path,
+            None => return Ok(()),
+        };
+
+        let content: String = self
+            .addrs
+            .iter()
+            .map(|addr| format!("{}\n", addr))
+            .collect();
+
+        fs::write(file_path, content).map_err(|_| NetworkError::Broadcasting)
+    }
+
+    pub fn get_addrs(&self) -> &Vec<SocketAddr> {
+        &self.addrs
+    }
+}
+
+impl Default for Anchors {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod anchors_test {
+    use super::*;
+
+    #[test]
+    fn test_anchors_are_persisted_across_runs() -> Result<(), NetworkError> {
+        let file_path = "data/test_anchors.txt";
+        let addrs: Vec<SocketAddr> = (1..=3)
+            .map(|i| SocketAddr::from(([127, 0, 0, i], 18333)))
+            .collect();
+
+        let mut anchors = Anchors::from_file(file_path)?;
+        anchors.set(addrs.clone());
+        anchors.save()?;
+
+        let loaded = Anchors::from_file(file_path);
+        fs::remove_file(file_path).map_err(|_| NetworkError::Broadcasting)?;
+
+        assert_eq!(loaded?.get_addrs(), &addrs[..MAX_ANCHORS].to_vec());
+        Ok(())
+    }
+}
@@ -0,0 +1,264 @@
+use super::network_constants::*;
+use crate::block_mod::block_constants::{
+    BIP34_HEIGHT, HALVING_INTERVAL, MAINNET_BIP34_HEIGHT, REGTEST_HALVING_INTERVAL,
+    TESTNET_BIP34_HEIGHT,
+};
+use crate::block_mod::block_header::BlockHeader;
+use crate::messages::read_from_bytes::decode_hex;
+
+/// Represents the Bitcoin network the node runs on, along with the parameters that differ between
+/// networks, so switching networks only takes the `network` field of the settings.
+///
+/// # Variants
+///
+/// - `Mainnet`: The main Bitcoin network.
+/// - `Testnet`: The public test network (testnet3).
+/// - `Regtest`: The local regression test network, whose blocks can be mined instantly.
+/// - `Signet`: The default signet, whose blocks are signed by a known set of keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Network {
+    Mainnet,
+    #[default]
+    Testnet,
+    Regtest,
+    Signet,
+}
+
+impl Network {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Network::Mainnet => MAINNET,
+            Network::Testnet => TESTNET,
+            Network::Regtest => REGTEST,
+            Network::Signet => SIGNET,
+        }
+    }
+
+    /// Returns the network with the given name, if there is one.
+    pub fn from_name(name: &str) -> Option<Network> {
+        Network::all()
+            .into_iter()
+            .find(|network| network.get_name() == name)
+    }
+
+    /// Returns the network identified by the given start string, if there is one.
+    pub fn from_start_string(start_string: &[u8]) -> Option<Network> {
+        Network::all()
+            .into_iter()
+            .find(|network| network.get_start_string() == start_string)
+    }
+
+    /// Returns the start string (magic bytes) every message of the network begins with.
+    pub fn get_start_string(&self) -> Vec<u8> {
+        match self {
+            Network::Mainnet => MAINNET_START_STRING,
+            Network::Testnet => TESTNET_START_STRING,
+            Network::Regtest => REGTEST_START_STRING,
+            Network::Signet => SIGNET_START_STRING,
+        }
+        .to_vec()
+    }
+
+    pub fn get_default_port(&self) -> u16 {
+        match self {
+            Network::Mainnet => MAINNET_PORT,
+            Network::Testnet => TESTNET_PORT,
+            Network::Regtest => REGTEST_PORT,
+            Network::Signet => SIGNET_PORT,
+        }
+    }
+
+    /// Returns the DNS seed peers are discovered from. Regtest has no seeds, so the local host is
+    /// used instead.
+    pub fn get_dns_seed(&self) -> &'static str {
+        match self {
+            Network::Mainnet => MAINNET_DNS_SEED,
+            Network::Testnet => TESTNET_DNS_SEED,
+            Network::Regtest => REGTEST_DNS_SEED,
+            Network::Signet => SIGNET_DNS_SEED,
+        }
+    }
+
+    /// Returns the human-readable part of the bech32 addresses of the network.
+    pub fn get_bech32_hrp(&self) -> &'static str {
+        match self {
+            Network::Mainnet => MAINNET_BECH32_HRP,
+            Network::Testnet => TESTNET_BECH32_HRP,
+            Network::Regtest => REGTEST_BECH32_HRP,
+            Network::Signet => SIGNET_BECH32_HRP,
+        }
+    }
+
+    /// Returns the version byte of the base58 addresses paying to a public key hash. Every test
+    /// network shares the testnet prefix.
+    pub fn get_p2pkh_prefix(&self) -> u8 {
+        match self {
+            Network::Mainnet => MAINNET_P2PKH_PREFIX,
+            _ => TESTNET_P2PKH_PREFIX,
+        }
+    }
+
+    /// Returns the version byte of the base58 addresses paying to a script hash.
+    pub fn get_p2sh_prefix(&self) -> u8 {
+        match self {
+            Network::Mainnet => MAINNET_P2SH_PREFIX,
+            _ => TESTNET_P2SH_PREFIX,
+        }
+    }
+
+    /// Returns the header of the genesis block of the network.
+    pub fn get_genesis_header(&self) -> BlockHeader {
+        let (time, n_bits, nonce) = match self {
+            Network::Mainnet => (
+                MAINNET_GENESIS_TIME,
+                MAINNET_GENESIS_NBITS,
+                MAINNET_GENESIS_NONCE,
+            ),
+            Network::Testnet => (
+                TESTNET_GENESIS_TIME,
+                TESTNET_GENESIS_NBITS,
+                TESTNET_GENESIS_NONCE,
+            ),
+            Network::Regtest => (
+                REGTEST_GENESIS_TIME,
+                REGTEST_GENESIS_NBITS,
+                REGTEST_GENESIS_NONCE,
+            ),
+            Network::Signet => (
+                SIGNET_GENESIS_TIME,
+                SIGNET_GENESIS_NBITS,
+                SIGNET_GENESIS_NONCE,
+            ),
+        };
+
+        BlockHeader::new(
+            GENESIS_VERSION,
+            GENESIS_PREVIOUS_BLOCK_HEADER_HASH.to_vec(),
+            GENESIS_MERKLE_ROOT_HASH.to_vec(),
+            time,
+            n_bits,
+            nonce,
+        )
+    }
+
+    /// Returns the hash of the genesis block of the network, in internal byte order.
+    pub fn get_genesis_hash(&self) -> Vec<u8> {
+        let hash = match self {
+            Network::Mainnet => MAINNET_GENESIS_HASH,
+            Network::Testnet => TESTNET_GENESIS_HASH,
+            Network::Regtest => REGTEST_GENESIS_HASH,
+            Network::Signet => SIGNET_GENESIS_HASH,
+        };
+        let mut hash = decode_hex(hash).unwrap_or_default();
+        hash.reverse();
+        hash
+    }
+
+    /// Returns the nbits of the easiest target a header of the network may have, which is the
+    /// target of its genesis block.
+    pub fn get_pow_limit(&self) -> u32 {
+        self.get_genesis_header().get_nbits()
+    }
+
+    /// Returns the number of blocks after which the subsidy of the network halves.
+    pub fn get_halving_interval(&self) -> u32 {
+        match self {
+            Network::Regtest => REGTEST_HALVING_INTERVAL,
+            _ => HALVING_INTERVAL,
+        }
+    }
+
+    /// Returns the height from which the coinbase of every block of the network must start its
+    /// script with the height of the block (BIP34).
+    pub fn get_bip34_height(&self) -> u32 {
+        match self {
+            Network::Mainnet => MAINNET_BIP34_HEIGHT,
+            Network::Testnet => TESTNET_BIP34_HEIGHT,
+            _ => BIP34_HEIGHT,
+        }
+    }
+
+    /// Returns the checkpoints of the network, as their height and their hash as displayed by
+    /// block explorers. Only testnet has checkpoints, so the other networks always sync from the
+    /// genesis block.
+    pub fn get_checkpoints(&self) -> &'static [(u32, &'static str)] {
+        match self {
+            Network::Testnet => &TESTNET_CHECKPOINTS,
+            _ => &[],
+        }
+    }
+
+    /// Returns the UTXO snapshots a node of the network can be bootstrapped from, as the height of
+    /// their tip and the content hash of their set, as hex. No snapshot is trusted by default: the
+    /// hash printed when a snapshot is dumped must be added here for nodes to load it.
+    pub fn get_snapshot_hashes(&self) -> &'static [(u32, &'static str)] {
+        match self {
+            Network::Mainnet => &MAINNET_SNAPSHOTS,
+            Network::Testnet => &TESTNET_SNAPSHOTS,
+            Network::Regtest => &REGTEST_SNAPSHOTS,
+            Network::Signet => &SIGNET_SNAPSHOTS,
+        }
+    }
+
+    fn all() -> [Network; 4] {
+        [
+            Network::Mainnet,
+            Network::Testnet,
+            Network::Regtest,
+            Network::Signet,
+        ]
+    }
+}
+
+#[cfg(test)]
+mod bitcoin_network_test {
+    use super::*;
+    use crate::messages::read_from_bytes::encode_hex;
+
+    fn displayed_hash(network: Network) -> String {
+        let mut hash = network.get_genesis_header().get_header();
+        hash.reverse();
+        encode_hex(&hash).unwrap_or_default()
+    }
+
+    #[test]
+    fn test_genesis_headers_match_the_known_hashes() {
+        assert_eq!(
+            displayed_hash(Network::Mainnet),
+            "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f"
+        );
+        assert_eq!(
+            displayed_hash(Network::Testnet),
+            "000000000933ea01ad0ee984209779baaec3ced90fa3f408719526f8d77f4943"
+        );
+        assert_eq!(
+            displayed_hash(Network::Regtest),
+            "0f9188f13cb7b2c71f2a335e3a4fc328bf5beb436012afca590b1a11466e2206"
+        );
+        assert_eq!(
+            displayed_hash(Network::Signet),
+            "00000008819873e925422c1ff0f99f7cc9bbb232af63a077a480a3633bee1ef6"
+        );
+    }
+
+    #[test]
+    fn test_genesis_headers_hash_to_the_embedded_hashes() {
+        for network in Network::all() {
+            assert_eq!(
+                network.get_genesis_header().get_header(),
+                network.get_genesis_hash()
+            );
+        }
+    }
+
+    #[test]
+    fn test_network_from_name_and_start_string() {
+        assert_eq!(Network::from_name("regtest"), Some(Network::Regtest));
+        assert_eq!(Network::from_name("litecoin"), None);
+        assert_eq!(
+            Network::from_start_string(&[0xf9, 0xbe, 0xb4, 0xd9]),
+            Some(Network::Mainnet)
+        );
+        assert_eq!(Network::default().get_default_port(), 18333);
+    }
+}
@@ -1,58 +1,174 @@
 use super::{
     headers_download::handle_other_message,
-    network_constants::{DATE_LIMIT, MSG_BLOCK_DATA_TYPE},
+    network_constants::{
+        BLOCK_BATCH_SIZE, BLOCK_REQUEST_TIMEOUT_SECS, DATE_LIMIT, MSG_BLOCK_DATA_TYPE,
+        WORK_QUEUE_POLL_MILLIS,
+    },
+    work_queue::WorkQueue,
 };
 use crate::{
     block_mod::{block::Block, block_header::BlockHeader},
     messages::{
-        get_data::GetData, header::MessageHeader, inventory::Inventory,
-        message_constants::BLOCK_COMMAND,
+        command::Command, get_data::GetData, header::MessageHeader, inventory::Inventory,
+        not_found::NotFound,
+    },
+    network::{
+        network_constants::DATE_FORMAT, network_error::NetworkError, peer_pool::PeerPool,
+        peer_stream::PeerStream, sync_progress::SyncProgress,
     },
-    network::{network_constants::DATE_FORMAT, network_error::NetworkError},
     settings_mod::settings::Settings,
 };
 use chrono::DateTime;
 use chrono::Utc;
 use std::{
+    collections::HashSet,
     io::Write,
-    net::TcpStream,
+    net::SocketAddr,
     sync::mpsc::Sender,
-    sync::{Arc, Mutex, MutexGuard},
+    sync::{Arc, Mutex},
     thread::{self, JoinHandle},
+    time::Duration,
 };
 
-/// Manage a block download error by adding inventories to a shared collection.
+/// Takes the next batch of the shared work queue the peer may download.
+///
+/// If there are no such batches but other peers still have batches in flight, waits until one
+/// of them is handed back or every batch has been downloaded.
+///
+/// # Arguments
 ///
-/// This function is used to handle a block download error scenario. It takes a shared
-/// collection of inventories along with a vector of new inventories to be added.
-/// The function locks the shared collection, extends it with the new inventories,
-/// and then releases the lock.
+/// - `work_queue`: The shared queue of block batches.
+/// - `addr`: The address of the peer the batch is taken for.
+///
+/// # Returns
+///
+/// The next batch to download, or `None` if every batch has been downloaded or the batches left
+/// are blocks the peer reported as not found.
+fn take_batch(work_queue: &Arc<Mutex<WorkQueue>>, addr: &SocketAddr) -> Option<Vec<Inventory>> {
+    loop {
+        let mut locked_queue = work_queue.lock().ok()?;
+
+        if let Some(batch) = locked_queue.take(addr) {
+            return Some(batch);
+        }
+
+        if locked_queue.is_done() || locked_queue.is_idle() {
+            return None;
+        }
+
+        drop(locked_queue);
+        thread::sleep(Duration::from_millis(WORK_QUEUE_POLL_MILLIS));
+    }
+}
+
+/// Requests a batch of blocks to a peer and sends the received blocks to the block saver.
 ///
 /// # Arguments
 ///
-/// - `shared_inventories`: A reference-counted smart pointer to a mutex-protected vector of `Inventory` items.
-/// - `invs`: A vector of `Inventory` items to be added to the shared collection.
-fn manage_block_download_error(
-    shared_inventories: &Arc<Mutex<Vec<Inventory>>>,
-    invs: &[Inventory],
-) {
-    let mut locked_inv = match shared_inventories.lock() {
-        Ok(locked_inv) => locked_inv,
-        Err(_) => return,
-    };
-
-    locked_inv.extend(invs.to_vec());
-
-    drop(locked_inv);
+/// - `stream`: The connection to the peer, whose requests are queued to its writing thread.
+/// - `start_string`: The start string of the network.
+/// - `batch`: The inventories of the blocks to request.
+/// - `witness`: Whether the peer serves the witness data of the blocks.
+/// - `tx`: The sender through which downloaded blocks are handed to the block saver.
+///
+/// # Returns
+///
+/// The inventories the peer answered with `notfound` once every other block of the batch was
+/// downloaded, or the inventories still missing if the peer stalled, timed out or sent an invalid
+/// message.
+fn download_batch(
+    stream: &mut PeerStream,
+    start_string: Vec<u8>,
+    batch: Vec<Inventory>,
+    witness: bool,
+    tx: &Sender<Block>,
+    progress: &Arc<Mutex<SyncProgress>>,
+) -> Result<Vec<Inventory>, Vec<Inventory>> {
+    let get_data = GetData::new(start_string.clone(), batch.clone(), witness);
+
+    if stream.write_all(&get_data.to_bytes()).is_err() {
+        return Err(batch);
+    }
+
+    let mut missing = batch;
+    let mut not_found = vec![];
+
+    while !missing.is_empty() {
+        stream.discard_payload();
+        let header = match MessageHeader::from_bytes(stream) {
+            Ok(header) => header,
+            Err(_) => return Err(missing),
+        };
+
+        if stream.receive_payload(&header).is_err() {
+            return Err(missing);
+        }
+
+        if header.get_command() == Some(Command::NotFound) {
+            let inventories = match NotFound::from_bytes(header, stream) {
+                Ok(message) => message.get_inventories(),
+                Err(_) => return Err(missing),
+            };
+
+            let (found, lacking): (Vec<Inventory>, Vec<Inventory>) =
+                missing.into_iter().partition(|inv| {
+                    inventories
+                        .iter()
+                        .all(|not_found| not_found.get_data() != inv.get_data())
+                });
+            missing = found;
+            not_found.extend(lacking);
+            continue;
+        }
+
+        if header.get_command() != Some(Command::Block) {
+            if handle_other_message(stream, header, start_string.clone()).is_err() {
+                return Err(missing);
+            }
+            continue;
+        }
+
+        let block_size = header.get_payload_size();
+        let block = match Block::from_bytes(stream) {
+            Ok(block) => block,
+            Err(_) => return Err(missing),
+        };
+
+        let hash = block.get_header().get_header();
+        let position = match missing.iter().position(|inv| inv.get_data() == hash) {
+            Some(position) => position,
+            None => continue,
+        };
+
+        if tx.send(block).is_err() {
+            return Err(missing);
+        }
+        missing.remove(position);
+
+        if let Ok(mut locked_progress) = progress.lock() {
+            locked_progress.add_block(block_size as u64);
+        }
+    }
+
+    Ok(not_found)
 }
 
 /// Downloads blocks from a network using multiple TCP streams and filters them based on date and merkle tree validation.
 ///
+/// Blocks are requested in batches taken from a shared work queue, so idle peers steal the work
+/// left. A peer that does not answer a request within `BLOCK_REQUEST_TIMEOUT_SECS` is considered
+/// stalled: the blocks it did not send are handed back to the queue and it is dropped from the pool.
+/// Blocks a peer answers with `notfound` are handed back to be downloaded from other peers, and
+/// the download fails if no peer has them.
+///
 /// # Arguments
 /// * `settings` - A reference to the network settings.
-/// * `streams` - A mutable vector of TCP streams used for communication.
+/// * `peer_pool` - The shared pool of connections used for communication.
 /// * `headers` - A vector of block headers to download.
-/// * `utxo_set` - A mutable reference to the unspent transaction output set.
+/// * `downloaded` - The hashes of the blocks downloaded by previous runs, which are skipped.
+/// * `tx` - The sender through which downloaded blocks are handed to the block saver.
+/// * `progress` - The shared sync progress, updated with every block downloaded and logged after
+///   every batch.
 ///
 /// # Returns
 /// A Result containing a HashMap of block headers to their corresponding blocks if successful,
@@ -63,9 +179,11 @@ fn manage_block_download_error(
 /// if there's an error parsing the date, or if there are issues with network communication.
 pub fn block_download(
     settings: Arc<Settings>,
-    streams: &Vec<Arc<Mutex<TcpStream>>>,
+    peer_pool: &Arc<Mutex<PeerPool>>,
     headers: &[BlockHeader],
+    downloaded: &HashSet<Vec<u8>>,
     tx: Sender<Block>,
+    progress: &Arc<Mutex<SyncProgress>>,
 ) -> Result<(), NetworkError> {
     let date_time = DateTime::parse_from_str(DATE_LIMIT, DATE_FORMAT)
         .map_err(|_| NetworkError::BlockDownload)?
@@ -75,98 +193,102 @@ pub fn block_download(
     let block_headers: Vec<&BlockHeader> = headers
         .iter()
         .filter(|block_header| block_header.get_time() > date_time as u32)
+        .filter(|block_header| !downloaded.contains(&block_header.get_header()))
         .collect();
 
     println!("Blocks to be downloaded: {}", block_headers.len());
+    progress
+        .lock()
+        .map_err(|_| NetworkError::BlockDownload)?
+        .start_blocks(block_headers.len());
 
     let inventories: Vec<Inventory> = block_headers
         .iter()
         .map(|block_header| Inventory::new(MSG_BLOCK_DATA_TYPE, block_header.get_header()))
         .collect();
 
-    let shared_inventories = Arc::new(Mutex::new(inventories));
+    let work_queue = Arc::new(Mutex::new(WorkQueue::new(inventories, BLOCK_BATCH_SIZE)));
     let mut threads: Vec<JoinHandle<()>> = vec![];
 
-    for stream in streams {
-        let shared_stream = stream.clone();
+    let locked_peer_pool = peer_pool.lock().map_err(|_| NetworkError::BlockDownload)?;
+    let peers: Vec<_> = locked_peer_pool
+        .iter()
+        .map(|(addr, writer)| {
+            (
+                *addr,
+                writer.clone(),
+                locked_peer_pool.supports_witness(addr),
+            )
+        })
+        .collect();
+    drop(locked_peer_pool);
+
+    for (addr, writer, witness) in peers {
         let shared_settings = settings.clone();
         let shared_tx = tx.clone();
-        let shared_inv = shared_inventories.clone();
+        let shared_queue = work_queue.clone();
+        let shared_pool = peer_pool.clone();
+        let shared_progress = progress.clone();
 
         let thread = thread::spawn(move || {
-            let mut locked_stream = match shared_stream.lock() {
-                Ok(locked_stream) => locked_stream,
-                Err(_) => return,
-            };
-
-            'thread_loop: loop {
-                let mut locked_inv = match shared_inv.lock() {
-                    Ok(locked_inv) => locked_inv,
-                    Err(_) => continue,
-                };
+            let mut stream = writer.reader();
 
-                let mut get_data_size = 100;
-
-                if locked_inv.is_empty() {
-                    drop(locked_inv);
-                    break;
-                } else if locked_inv.len() < 100 {
-                    get_data_size = locked_inv.len();
-                }
-
-                let inv = take_invs(&mut locked_inv, get_data_size);
-
-                drop(locked_inv);
+            let timeout = Duration::from_secs(BLOCK_REQUEST_TIMEOUT_SECS);
+            if stream.set_read_timeout(Some(timeout)).is_err() {
+                return;
+            }
 
-                let get_data = GetData::new(shared_settings.get_start_string(), inv.clone());
+            while let Some(batch) = take_batch(&shared_queue, &addr) {
+                let result = download_batch(
+                    &mut stream,
+                    shared_settings.get_start_string(),
+                    batch,
+                    witness,
+                    &shared_tx,
+                    &shared_progress,
+                );
 
-                if locked_stream.write_all(&get_data.to_bytes()).is_err() {
-                    println!("Failed to send Get Data message. Trying again...");
-                    manage_block_download_error(&shared_inv, &inv);
-                    continue 'thread_loop;
-                }
+                let mut locked_queue = match shared_queue.lock() {
+                    Ok(locked_queue) => locked_queue,
+                    Err(_) => return,
+                };
 
-                for _ in 0..get_data_size {
-                    loop {
-                        let header = match MessageHeader::from_bytes(&mut *locked_stream) {
-                            Ok(header) => header,
-                            Err(_) => {
-                                manage_block_download_error(&shared_inv, &inv);
-                                break 'thread_loop;
-                            }
-                        };
-
-                        if header.get_command_name() == BLOCK_COMMAND {
-                            break;
-                        }
+                let not_found = match result {
+                    Ok(not_found) => not_found,
+                    Err(missing) => {
+                        println!(
+                            "Peer {} stalled, {} blocks were handed back to other peers.",
+                            addr,
+                            missing.len()
+                        );
+                        locked_queue.requeue(missing);
+                        drop(locked_queue);
 
-                        if handle_other_message(
-                            &mut locked_stream,
-                            header,
-                            shared_settings.get_start_string(),
-                        )
-                        .map_err(|_| NetworkError::BlockDownload)
-                        .is_err()
-                        {
-                            manage_block_download_error(&shared_inv, &inv);
-                            break 'thread_loop;
+                        if let Ok(mut locked_pool) = shared_pool.lock() {
+                            locked_pool.remove(&addr);
                         }
+                        stream.shutdown();
+                        return;
                     }
+                };
 
-                    let block = match Block::from_bytes(&mut *locked_stream) {
-                        Ok(block) => block,
-                        Err(_) => {
-                            manage_block_download_error(&shared_inv, &inv);
-                            break 'thread_loop;
-                        }
-                    };
+                locked_queue.complete();
+                if !not_found.is_empty() {
+                    println!(
+                        "Peer {} did not find {} blocks, they were handed to other peers.",
+                        addr,
+                        not_found.len()
+                    );
+                    locked_queue.hand_off(not_found, addr);
+                }
+                drop(locked_queue);
 
-                    if shared_tx.send(block).is_err() {
-                        manage_block_download_error(&shared_inv, &inv);
-                        break 'thread_loop;
-                    }
+                if let Ok(locked_progress) = shared_progress.lock() {
+                    println!("{}", locked_progress);
                 }
             }
+
+            let _ = stream.set_read_timeout(Some(shared_settings.get_read_timeout()));
         });
 
         threads.push(thread);
@@ -176,38 +298,14 @@ pub fn block_download(
         handle.join().map_err(|_| NetworkError::BlockDownload)?;
     }
 
-    Ok(())
-}
-
-pub fn take_invs(inventories: &mut MutexGuard<Vec<Inventory>>, amount: usize) -> Vec<Inventory> {
-    let mut new_list = Vec::new();
+    let locked_queue = work_queue.lock().map_err(|_| NetworkError::BlockDownload)?;
 
-    for _ in 0..amount {
-        match inventories.pop() {
-            Some(inv) => new_list.push(inv),
-            None => {
-                break;
-            }
-        };
+    if !locked_queue.is_done() {
+        println!(
+            "{} batches of blocks could not be downloaded from any peer.",
+            locked_queue.get_pending_count()
+        );
+        return Err(NetworkError::BlockDownload);
     }
-
-    new_list
-}
-
-pub fn take_streams(
-    streams: &mut Vec<Arc<Mutex<TcpStream>>>,
-    amount: usize,
-) -> Vec<Arc<Mutex<TcpStream>>> {
-    let mut new_list = Vec::new();
-
-    for _ in 0..amount {
-        match streams.pop() {
-            Some(stream) => new_list.push(stream),
-            None => {
-                break;
-            }
-        };
-    }
-
-    new_list
+    Ok(())
 }
@@ -0,0 +1,272 @@
+use super::network_constants::{
+    BLOOM_SEED_MULTIPLIER, BLOOM_UPDATE_ALL, BLOOM_UPDATE_P2PUBKEY_ONLY, OP_CHECKMULTISIG,
+    OP_CHECKSIG, OP_PUSHDATA1, OP_PUSHDATA2, OP_PUSHDATA4,
+};
+use crate::block_mod::{block::Block, transaction::Transaction, tx_out::TxOut};
+use crate::messages::filter_load::FilterLoad;
+
+/// Represents the bloom filter loaded by a peer through a `filterload` message (BIP37).
+///
+/// Transactions match the filter if their id, an outpoint they spend, or a data push of one of
+/// their scripts is in the filter. Depending on its flags, the filter is updated with the outpoints
+/// of the matched outputs, so the transactions spending them match as well.
+#[derive(Debug)]
+pub struct BloomFilter {
+    filter: Vec<u8>,
+    hash_funcs: u32,
+    tweak: u32,
+    flags: u8,
+}
+
+/// Computes the 32-bit MurmurHash3 of the given data.
+fn murmur3(seed: u32, data: &[u8]) -> u32 {
+    let c1: u32 = 0xcc9e2d51;
+    let c2: u32 = 0x1b873593;
+    let mut hash = seed;
+
+    let blocks = data.chunks_exact(4);
+    let tail = blocks.remainder();
+
+    for block in blocks {
+        let mut k = u32::from_le_bytes([block[0], block[1], block[2], block[3]]);
+        k = k.wrapping_mul(c1).rotate_left(15).wrapping_mul(c2);
+
+        hash ^= k;
+        hash = hash
+            .rotate_left(13)
+            .wrapping_mul(5)
+            .wrapping_add(0xe6546b64);
+    }
+
+    let mut k: u32 = 0;
+    for (index, byte) in tail.iter().enumerate() {
+        k ^= (*byte as u32) << (8 * index);
+    }
+    if !tail.is_empty() {
+        hash ^= k.wrapping_mul(c1).rotate_left(15).wrapping_mul(c2);
+    }
+
+    hash ^= data.len() as u32;
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x85ebca6b);
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(0xc2b2ae35);
+    hash ^ (hash >> 16)
+}
+
+/// Returns the data pushed by a script, ignoring its opcodes.
+///
+/// Parsing stops at the first push that goes past the end of the script.
+fn script_data_pushes(script: &[u8]) -> Vec<&[u8]> {
+    let mut pushes = Vec::new();
+    let mut index = 0;
+
+    while index < script.len() {
+        let opcode = script[index];
+        index += 1;
+
+        let (size, size_bytes) = match opcode {
+            1..=75 => (opcode as usize, 0),
+            OP_PUSHDATA1 => (*script.get(index).unwrap_or(&0) as usize, 1),
+            OP_PUSHDATA2 => match script.get(index..index + 2) {
+                Some(bytes) => (u16::from_le_bytes([bytes[0], bytes[1]]) as usize, 2),
+                None => break,
+            },
+            OP_PUSHDATA4 => match script.get(index..index + 4) {
+                Some(bytes) => (
+                    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize,
+                    4,
+                ),
+                None => break,
+            },
+            _ => continue,
+        };
+
+        index += size_bytes;
+        match script.get(index..index + size) {
+            Some(push) => pushes.push(push),
+            None => break,
+        }
+        index += size;
+    }
+
+    pushes
+}
+
+/// Returns whether a script pays to a public key or is a bare multisig.
+fn is_pay_to_pubkey(script: &[u8]) -> bool {
+    match script.last() {
+        Some(&OP_CHECKSIG) => matches!(script.len(), 35 | 67) && matches!(script[0], 33 | 65),
+        Some(&OP_CHECKMULTISIG) => true,
+        _ => false,
+    }
+}
+
+/// Serializes an outpoint, as it is inserted in and looked up from the filter.
+fn outpoint_bytes(tx_id: &[u8], index: u32) -> Vec<u8> {
+    let mut bytes = tx_id.to_vec();
+    bytes.extend(index.to_le_bytes());
+    bytes
+}
+
+impl BloomFilter {
+    /// Creates a bloom filter from the one sent by a peer.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter_load` - The `filterload` message sent by the peer.
+    pub fn new(filter_load: &FilterLoad) -> BloomFilter {
+        BloomFilter {
+            filter: filter_load.get_filter().clone(),
+            hash_funcs: filter_load.get_hash_funcs(),
+            tweak: filter_load.get_tweak(),
+            flags: filter_load.get_flags(),
+        }
+    }
+
+    /// Returns the index of the bit of the filter set by the given hash function.
+    fn bit_index(&self, hash_num: u32, data: &[u8]) -> usize {
+        let seed = hash_num
+            .wrapping_mul(BLOOM_SEED_MULTIPLIER)
+            .wrapping_add(self.tweak);
+
+        murmur3(seed, data) as usize % (self.filter.len() * 8)
+    }
+
+    /// Adds an element to the filter.
+    pub fn insert(&mut self, data: &[u8]) {
+        if self.filter.is_empty() {
+            return;
+        }
+
+        for hash_num in 0..self.hash_funcs {
+            let index = self.bit_index(hash_num, data);
+            self.filter[index / 8] |= 1 << (index % 8);
+        }
+    }
+
+    /// Returns whether an element may be in the filter.
+    pub fn contains(&self, data: &[u8]) -> bool {
+        if self.filter.is_empty() {
+            return false;
+        }
+
+        (0..self.hash_funcs).all(|hash_num| {
+            let index = self.bit_index(hash_num, data);
+            self.filter[index / 8] & (1 << (index % 8)) != 0
+        })
+    }
+
+    /// Checks the id and the outputs of a transaction against the filter, inserting the outpoints
+    /// of the matched outputs if the flags of the filter ask for it.
+    fn matches_outputs(&mut self, tx_id: &[u8], tx_out_list: &[TxOut]) -> bool {
+        let mut matched = self.contains(tx_id);
+
+        for (index, tx_out) in tx_out_list.iter().enumerate() {
+            let pk_script = tx_out.get_pk_script();
+
+            if !script_data_pushes(&pk_script)
+                .iter()
+                .any(|push| self.contains(push))
+            {
+                continue;
+            }
+
+            matched = true;
+            if self.flags == BLOOM_UPDATE_ALL
+                || (self.flags == BLOOM_UPDATE_P2PUBKEY_ONLY && is_pay_to_pubkey(&pk_script))
+            {
+                self.insert(&outpoint_bytes(tx_id, index as u32));
+            }
+        }
+
+        matched
+    }
+
+    /// Returns whether a transaction matches the filter, updating the filter with the outputs it
+    /// matched.
+    pub fn is_relevant(&mut self, tx: &Transaction) -> bool {
+        if self.matches_outputs(&tx.get_id(false), tx.get_tx_out_list()) {
+            return true;
+        }
+
+        tx.get_tx_in_list().iter().any(|tx_in| {
+            let outpoint = tx_in.get_prev_output();
+
+            self.contains(&outpoint.to_bytes())
+                || script_data_pushes(&tx_in.get_signature_script())
+                    .iter()
+                    .any(|push| self.contains(push))
+        })
+    }
+
+    /// Returns whether each transaction of a block, coinbase included, matches the filter.
+    pub fn match_block(&mut self, block: &Block) -> Vec<bool> {
+        let coinbase = block.get_coinbase();
+        let mut matches = vec![self.matches_outputs(&coinbase.get_id(), &coinbase.tx_out_list)];
+
+        for tx in block.get_txn_list() {
+            matches.push(self.is_relevant(tx));
+        }
+
+        matches
+    }
+}
+
+#[cfg(test)]
+mod bloom_filter_test {
+    use super::*;
+    use crate::block_mod::tx_in::TxIn;
+
+    fn empty_filter(flags: u8) -> BloomFilter {
+        BloomFilter::new(&FilterLoad::new(
+            vec![11, 17, 9, 7],
+            vec![0; 32],
+            5,
+            0,
+            flags,
+        ))
+    }
+
+    #[test]
+    fn test_murmur3_test_vectors() {
+        assert_eq!(murmur3(0, &[]), 0);
+        assert_eq!(murmur3(0xfba4c795, &[]), 0x6a396f08);
+        assert_eq!(murmur3(0, &[0x00]), 0x514e28b7);
+        assert_eq!(murmur3(0, &[0x21, 0x43, 0x65, 0x87]), 0xf55b516b);
+    }
+
+    #[test]
+    fn test_bloom_filter_matches_spending_transaction() {
+        let pubkey_hash = vec![7; 20];
+        let mut pk_script = vec![0x76, 0xa9, 20];
+        pk_script.extend(&pubkey_hash);
+        pk_script.extend([0x88, 0xac]);
+
+        let funding = Transaction::new(
+            1,
+            vec![TxIn::new(vec![0; 32], 0, vec![], 0xffffffff)],
+            vec![TxOut::new(1000, pk_script)],
+            0,
+            false,
+        );
+        let spending = Transaction::new(
+            1,
+            vec![TxIn::new(funding.get_id(false), 0, vec![], 0xffffffff)],
+            vec![TxOut::new(900, vec![])],
+            0,
+            false,
+        );
+
+        let mut filter = empty_filter(BLOOM_UPDATE_ALL);
+        filter.insert(&pubkey_hash);
+        assert!(!filter.is_relevant(&spending));
+        assert!(filter.is_relevant(&funding));
+        assert!(filter.is_relevant(&spending));
+
+        let mut filter = empty_filter(0);
+        filter.insert(&pubkey_hash);
+        assert!(filter.is_relevant(&funding));
+        assert!(!filter.is_relevant(&spending));
+    }
+}
@@ -1,185 +1,1475 @@
-use super::network_constants::MSG_BLOCK_DATA_TYPE;
+use super::bitcoin_network::Network;
+use super::bloom_filter::BloomFilter;
+use super::keepalive::keep_alive;
+use super::message_dispatcher::MessageDispatcher;
+use super::network_constants::{
+    MSG_BLOCK_DATA_TYPE, MSG_FILTERED_BLOCK_DATA_TYPE, MSG_TX_DATA_TYPE, MSG_WTX_DATA_TYPE,
+    STOPPING_HASH,
+};
 use super::network_error::NetworkError;
+use super::peer_pool::PeerPool;
+use super::peer_scoring::{Misbehavior, PeerScores};
+use super::peer_stream::{PeerStream, PeerWriter};
+use super::shutdown::ShutdownCoordinator;
+use crate::block_mod::block_validation::{
+    check_block_spends, check_block_structure, check_coinbase_height,
+};
 use crate::block_mod::blockchain::BlockChain;
+use crate::block_mod::chain_state::ChainState;
+use crate::block_mod::chain_update::ChainUpdate;
+use crate::block_mod::header_chain::HeaderChain;
+use crate::block_mod::invalid_blocks::{BlockRule, InvalidBlock};
 use crate::block_mod::mempool::Mempool;
+use crate::block_mod::miner::block_subsidy;
 use crate::block_mod::transaction::Transaction;
+use crate::block_mod::tx_validation::{validate_transaction, LockContext, TxRule};
+use crate::block_mod::utxo::UnspentTx;
+use crate::messages::addr::Addr;
+use crate::messages::block_message::BlockMessage;
+use crate::messages::command::Command;
+use crate::messages::fee_filter::FeeFilter;
+use crate::messages::filter_add::FilterAdd;
+use crate::messages::filter_clear::FilterClear;
+use crate::messages::filter_load::FilterLoad;
 use crate::messages::inv::Inv;
-use crate::messages::message_constants::{INV_COMMAND, TX_COMMAND};
+use crate::messages::mempool::Mempool as MempoolMessage;
+use crate::messages::merkle_block::MerkleBlock;
+use crate::messages::message_constants::{
+    MAX_GET_BLOCKS_COUNT, MAX_INV_COUNT, MSG_WITNESS_BLOCK, MSG_WITNESS_TX, REJECT_INVALID,
+    REJECT_NONSTANDARD, TX_COMMAND,
+};
+use crate::messages::not_found::NotFound;
+use crate::messages::read_from_bytes::encode_hex;
+use crate::messages::reject::Reject;
+use crate::messages::send_headers::SendHeaders;
 use crate::messages::tx::Tx;
+use crate::wallet_utils::event_log::NodeEvent;
 use crate::{
-    block_mod::{block::Block, utxo::UnspentTx},
+    block_mod::{block::Block, block_header::BlockHeader},
     messages::{
-        get_data::GetData,
-        header::MessageHeader,
-        headers::Headers,
-        inventory::Inventory,
-        message_constants::{BLOCK_COMMAND, HEADERS_COMMAND, PING_COMMAND},
-        ping::Ping,
+        get_blocks::GetBlocks, get_data::GetData, get_headers::GetHeaders, header::MessageHeader,
+        headers::Headers, inventory::Inventory, message_constants::BLOCK_COMMAND, ping::Ping,
         pong::Pong,
     },
     settings_mod::settings::Settings,
 };
-use std::sync::{Arc, Mutex};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, OnceLock};
 use std::thread::{self, JoinHandle};
 use std::{
-    io::{Read, Write},
-    net::TcpStream,
+    io::Write,
+    net::{IpAddr, SocketAddr},
 };
 
 /// Manages the "tx" command received over the network.
 ///
-/// This function reads a transaction from the provided `stream` and adds it to the mempool.
+/// This function reads a transaction from the provided `stream` and adds it to the mempool, unless
+/// its weight exceeds the standard policy of the node or it fails the validation of the mempool,
+/// in which case a "reject" message holding the rule it violates is sent back to the peer. The new
+/// transaction is recorded in the log of events pushed to the wallet, and announced to the peers
+/// that do not know it yet.
 ///
 /// # Arguments
-/// * `stream` - A mutable reference to a TcpStream used for network communication.
-/// * `mempool` - An Arc-wrapped Mutex for accessing and modifying the mempool.
+/// * `stream` - A mutable reference to the PeerStream used for network communication.
+/// * `settings` - The network settings.
+/// * `chain_state` - The shared state of the chain, holding the mempool and the wallet events.
+/// * `requested` - The hashes of the data requested to the peer.
+/// * `peer` - The shared peer pool and the address of the peer, which is marked as knowing the
+///   transaction.
 ///
 /// # Returns
 /// * `Result<(), NetworkError>` - A result indicating success or an error of type NetworkError.
 ///
 /// # Errors
 /// The function can return a NetworkError in the following cases:
-/// * If there is an error while reading the transaction from the stream.
-/// * If there is an error acquiring the lock on the mempool.
-/// * If there is an error while adding the transaction to the mempool.
+/// * If the transaction cannot be read from the stream, or the peer stopped sending it.
+/// * If the transaction was never requested to the peer.
+/// * If the "reject" message for a non-standard or invalid transaction cannot be sent.
+/// * If there is an error acquiring the lock on the UTXO set, the mempool, the wallet events or
+///   the peer pool.
 fn manage_tx_command(
-    stream: &mut TcpStream,
-    mempool: &Arc<Mutex<Mempool>>,
+    stream: &mut PeerStream,
+    settings: &Arc<Settings>,
+    chain_state: &ChainState,
+    requested: &mut HashSet<Vec<u8>>,
+    peer: (&Arc<Mutex<PeerPool>>, &SocketAddr),
+) -> Result<(), NetworkError> {
+    let tx = Transaction::from_bytes(stream).map_err(NetworkError::from_read_error)?;
+    let (peer_pool, addr) = peer;
+
+    peer_pool
+        .lock()
+        .map_err(|_| NetworkError::Broadcasting)?
+        .mark_known_tx(addr, tx.get_id(false));
+
+    if !requested.remove(&tx.get_id(false)) && !requested.remove(&tx.wtxid()) {
+        return Err(NetworkError::UnsolicitedData);
+    }
+
+    let tx_id = tx.get_id(false);
+    let fee_rate = match accept_to_mempool(settings, chain_state, &tx)? {
+        MempoolAcceptance::Accepted(fee_rate) => fee_rate,
+        MempoolAcceptance::NonStandard => {
+            println!("Non-standard transaction received, its weight exceeds the standard policy.");
+            return send_reject(
+                stream,
+                settings,
+                TX_COMMAND,
+                (REJECT_NONSTANDARD, "tx-size"),
+                tx_id,
+            );
+        }
+        MempoolAcceptance::Invalid(rule) => {
+            println!("Invalid transaction received: {}.", rule.get_name());
+            return send_reject(
+                stream,
+                settings,
+                TX_COMMAND,
+                (rule.get_reject_code(), rule.get_name()),
+                tx_id,
+            );
+        }
+        MempoolAcceptance::Evicted => return Ok(()),
+    };
+
+    if !chain_state.publish(NodeEvent::NewTx(tx_id)) {
+        return Err(NetworkError::Broadcasting);
+    }
+
+    println!("New transaction has been succesfully received.");
+
+    broadcast_new_txn(settings, &tx, peer_pool, fee_rate)
+}
+
+/// Represents the outcome of offering a transaction to the mempool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MempoolAcceptance {
+    /// The transaction entered the mempool, paying the given fee rate.
+    Accepted(Option<u64>),
+    /// The weight of the transaction exceeds the standard policy.
+    NonStandard,
+    /// The transaction violates one of the rules of the mempool.
+    Invalid(TxRule),
+    /// The transaction was valid, but was evicted right away as it pays the lowest fee rate of a
+    /// full mempool.
+    Evicted,
+}
+
+/// Offers a transaction to the mempool, whether it was received from a peer or created by the
+/// wallet.
+///
+/// The transaction must meet the standard weight policy and pass `validate_transaction`, lock
+/// times included, before being added. The mempool is then trimmed down to the size set in the
+/// settings, which may evict the transaction itself.
+///
+/// # Arguments
+///
+/// * `settings` - The node settings, holding the size and expiry of the mempool.
+/// * `chain_state` - The shared blockchain, UTXO set, mempool and header chain.
+/// * `tx` - The transaction to add.
+///
+/// # Returns
+///
+/// The `MempoolAcceptance` of the transaction, or `NetworkError::Broadcasting` if the shared
+/// structures cannot be locked.
+pub fn accept_to_mempool(
+    settings: &Settings,
+    chain_state: &ChainState,
+    tx: &Transaction,
+) -> Result<MempoolAcceptance, NetworkError> {
+    let lock_context = lock_context(tx, chain_state)?;
+    let locked_utxo = chain_state
+        .get_utxo()
+        .lock()
+        .map_err(|_| NetworkError::Broadcasting)?;
+    let mut locked_mempool = chain_state
+        .get_mempool()
+        .lock()
+        .map_err(|_| NetworkError::Broadcasting)?;
+
+    Ok(add_to_mempool(
+        settings,
+        (&locked_utxo, &mut locked_mempool),
+        &lock_context,
+        tx,
+    ))
+}
+
+/// Validates a transaction against the locked UTXO set and mempool and adds it to the mempool,
+/// trimming it afterwards, as `accept_to_mempool` does once it holds the locks.
+fn add_to_mempool(
+    settings: &Settings,
+    (utxo, mempool): (&UnspentTx, &mut Mempool),
+    lock_context: &LockContext,
+    tx: &Transaction,
+) -> MempoolAcceptance {
+    if !tx.size_breakdown().is_standard() {
+        return MempoolAcceptance::NonStandard;
+    }
+
+    let fee = match validate_transaction(tx, utxo, mempool, lock_context) {
+        Ok(fee) => fee,
+        Err(rule) => return MempoolAcceptance::Invalid(rule),
+    };
+
+    mempool.add(tx.clone(), Some(fee));
+    let evicted = mempool.trim(
+        settings.get_max_mempool_size(),
+        settings.get_mempool_expiry(),
+    );
+    if !evicted.is_empty() {
+        println!(
+            "{} transactions have been evicted from the mempool.",
+            evicted.len()
+        );
+    }
+    if evicted.contains(&tx.get_id(false)) {
+        return MempoolAcceptance::Evicted;
+    }
+    MempoolAcceptance::Accepted(utxo.get_fee_rate(tx))
+}
+
+/// Gathers the state of the chain the lock times of the transaction are checked against: the
+/// height and median time past of the tip, and the confirmation of the outputs spent by the inputs
+/// that have a relative lock time.
+///
+/// # Errors
+///
+/// Returns `NetworkError::Broadcasting` if the lock on the blockchain or the header chain cannot be
+/// acquired.
+pub fn lock_context(
+    tx: &Transaction,
+    chain_state: &ChainState,
+) -> Result<LockContext, NetworkError> {
+    let locked_blockchain = chain_state
+        .get_blockchain()
+        .lock()
+        .map_err(|_| NetworkError::Broadcasting)?;
+    let locked_header_chain = chain_state
+        .get_header_chain()
+        .lock()
+        .map_err(|_| NetworkError::Broadcasting)?;
+
+    Ok(build_lock_context(
+        tx,
+        &locked_blockchain,
+        &locked_header_chain,
+    ))
+}
+
+/// Gathers the state of the chain the lock times of the transaction are checked against from the
+/// locked blockchain and header chain, as `lock_context` does once it holds the locks.
+fn build_lock_context(
+    tx: &Transaction,
+    blockchain: &BlockChain,
+    header_chain: &HeaderChain,
+) -> LockContext {
+    let tip_height = header_chain.get_height();
+    let mut lock_context = LockContext::new(
+        tip_height,
+        header_chain
+            .get_median_time_past(tip_height)
+            .unwrap_or_default(),
+    );
+
+    for tx_in in tx.get_tx_in_list() {
+        if tx_in.get_relative_lock_time(tx.get_version()).is_none() {
+            continue;
+        }
+
+        let tx_id = tx_in.get_prev_output().get_tx_id();
+        let height = blockchain
+            .get_transaction_block(tx_id)
+            .and_then(|header| header_chain.get_height_of(&header.get_header()));
+        if let Some(height) = height {
+            let median_time_past = header_chain
+                .get_median_time_past(height.saturating_sub(1))
+                .unwrap_or_default();
+            lock_context.add_confirmation(tx_id.clone(), height, median_time_past);
+        }
+    }
+    lock_context
+}
+
+/// Manages the "block" command received over the network.
+///
+/// This function reads a block from the provided `stream` and performs the necessary operations
+/// to update the blockchain, UTXO set, and mempool if the block is valid, reorganizing the chain
+/// if the block makes a competing branch the one with most work. Blocks stored in a side branch
+/// are not announced to other peers, and invalid blocks are answered with a "reject" message
+/// holding the rule they violate.
+///
+/// # Arguments
+/// * `stream` - A mutable reference to the PeerStream used for network communication.
+/// * `settings` - The network settings.
+/// * `chain_state` - The shared blockchain, UTXO set, mempool and header chain.
+/// * `requested` - The hashes of the data requested to the peer.
+/// * `peer` - The shared peer pool and the address of the peer the block comes from.
+///
+/// # Returns
+/// * `Result<(), NetworkError>` - A result indicating success or an error of type NetworkError.
+///
+/// # Errors
+/// The function can return a NetworkError in the following cases:
+/// * If the block cannot be read from the stream.
+/// * If the block was never requested to the peer, nor reassigned to it after another peer did
+///   not find it.
+/// * If the block was already found invalid, or fails the validation, in which case it is recorded
+///   in the table of invalid blocks.
+/// * If there is an error acquiring the lock on the blockchain, UTXO set, mempool or peer pool.
+pub fn manage_block_command(
+    stream: &mut PeerStream,
+    settings: &Arc<Settings>,
+    chain_state: &ChainState,
+    requested: &mut HashSet<Vec<u8>>,
+    peer: (&Arc<Mutex<PeerPool>>, &SocketAddr),
+) -> Result<(), NetworkError> {
+    let block = Block::from_bytes(stream).map_err(NetworkError::from_read_error)?;
+    let block_hash = block.get_header().get_header();
+    let (peer_pool, origin) = peer;
+
+    if !requested.remove(&block_hash)
+        && !peer_pool
+            .lock()
+            .map_err(|_| NetworkError::Broadcasting)?
+            .take_reassigned(origin, &block_hash)
+    {
+        return Err(NetworkError::UnsolicitedData);
+    }
+
+    if let Err(error) = check_block(settings.get_network(), chain_state, &block) {
+        reject_block(stream, settings, chain_state, block_hash)?;
+        return Err(error);
+    }
+
+    let block_header = block.get_header().clone();
+    let chain_update = match connect_block(settings, chain_state, block) {
+        Err(NetworkError::InvalidBlock) => {
+            reject_block(stream, settings, chain_state, block_hash)?;
+            return Err(NetworkError::InvalidBlock);
+        }
+        chain_update => chain_update?,
+    };
+
+    if chain_update == ChainUpdate::SideBranch {
+        println!("New block has been stored in a side branch.");
+        return Ok(());
+    }
+
+    println!("New block has been succesfully received.");
+
+    announce_block(settings, &block_header, peer_pool, Some(origin))
+}
+
+/// Validates a block, returning the rule it violates and the offending transaction, if any.
+///
+/// A block is invalid if it descends from an invalid block, fails the proof-of-work or
+/// proof-of-inclusion validation, its timestamp is too far ahead of the network-adjusted time, it
+/// holds the same transaction twice, it exceeds the weight limit, its coinbase is missing or
+/// repeated or it does not commit to its witness data. Its spends are validated once it is
+/// connected to the main chain, since only then the UTXO set reflects its parent.
+///
+/// The difficulty of the block must be the one required of a block extending its parent, which
+/// the caller checks against the header chain and passes as `has_required_nbits`.
+fn validate_block(
+    chain_state: &ChainState,
+    block: &Block,
+    (parent_invalid, has_required_nbits): (bool, bool),
+) -> Result<(), InvalidBlock> {
+    if parent_invalid {
+        return Err(InvalidBlock::new(BlockRule::BadPrevBlock, None));
+    }
+    if !has_required_nbits {
+        return Err(InvalidBlock::new(BlockRule::BadDiffBits, None));
+    }
+    if !block.proof_of_work() {
+        return Err(InvalidBlock::new(BlockRule::HighHash, None));
+    }
+    if !chain_state
+        .get_network_time()
+        .is_header_time_valid(block.get_header())
+    {
+        return Err(InvalidBlock::new(BlockRule::TimeTooNew, None));
+    }
+
+    let mut tx_ids = HashSet::new();
+    for tx_id in block.get_txn_ids() {
+        if !tx_ids.insert(tx_id.clone()) {
+            return Err(InvalidBlock::new(BlockRule::DuplicateTx, Some(tx_id)));
+        }
+    }
+
+    if !block.proof_of_inclusion() {
+        return Err(InvalidBlock::new(BlockRule::BadMerkleRoot, None));
+    }
+
+    check_block_structure(block)
+}
+
+/// Validates the spends of a block against the UTXO set of its parent, and the height its
+/// coinbase encodes against the height it is connected at.
+///
+/// The UTXO set only holds every unspent output if the blocks were downloaded from the genesis
+/// block on, otherwise the outputs missing from it are assumed to exist.
+fn validate_block_spends(
+    settings: &Arc<Settings>,
+    locked_utxo: &UnspentTx,
+    block: &Block,
+    height: u32,
+    complete: bool,
+) -> Result<(), InvalidBlock> {
+    check_coinbase_height(block, settings.get_network(), height)?;
+
+    check_block_spends(
+        block,
+        locked_utxo,
+        block_subsidy(settings.get_network(), height),
+        complete,
+    )
+}
+
+/// Checks that a block is not in the table of invalid blocks and passes the validation, recording
+/// it in the table otherwise.
+///
+/// Blocks in the table are rejected without being validated again, until the operator asks to
+/// reconsider them.
+///
+/// # Errors
+/// Returns `NetworkError::InvalidBlock` if the block is invalid, or a NetworkError if the table
+/// cannot be locked or persisted.
+fn check_block(
+    network: Network,
+    chain_state: &ChainState,
+    block: &Block,
 ) -> Result<(), NetworkError> {
-    let tx = Transaction::from_bytes(stream).map_err(|_| NetworkError::Broadcasting)?;
-    mempool
+    let mut locked_invalid_blocks = chain_state
+        .get_invalid_blocks()
+        .lock()
+        .map_err(|_| NetworkError::Broadcasting)?;
+    let block_hash = block.get_header().get_header();
+
+    if locked_invalid_blocks.contains(&block_hash) {
+        println!("Block previously found invalid has been received again.");
+        return Err(NetworkError::InvalidBlock);
+    }
+
+    let parent_invalid = locked_invalid_blocks.contains(block.get_previuos_block_header());
+    let has_required_nbits = chain_state
+        .get_header_chain()
         .lock()
         .map_err(|_| NetworkError::Broadcasting)?
-        .add(tx);
+        .has_required_nbits(network, block.get_header());
+
+    if let Err(invalid_block) =
+        validate_block(chain_state, block, (parent_invalid, has_required_nbits))
+    {
+        locked_invalid_blocks.park(block_hash, invalid_block)?;
+        return Err(NetworkError::InvalidBlock);
+    }
+    Ok(())
+}
+
+/// Sends a "reject" message for a block found invalid, giving the rule it violates as the reason.
+///
+/// # Errors
+/// Returns a NetworkError if the table of invalid blocks cannot be locked or the message cannot be
+/// sent.
+fn reject_block(
+    stream: &mut PeerStream,
+    settings: &Arc<Settings>,
+    chain_state: &ChainState,
+    block_hash: Vec<u8>,
+) -> Result<(), NetworkError> {
+    let reason = chain_state
+        .get_invalid_blocks()
+        .lock()
+        .map_err(|_| NetworkError::Broadcasting)?
+        .get(&block_hash)
+        .map(|invalid_block| invalid_block.get_rule().get_name())
+        .unwrap_or_default();
+
+    send_reject(
+        stream,
+        settings,
+        BLOCK_COMMAND,
+        (REJECT_INVALID, reason),
+        block_hash,
+    )
+}
+
+/// Sends a "reject" message to the peer, reporting that one of its messages was dropped.
+///
+/// # Arguments
+/// * `stream` - A mutable reference to the PeerStream used for network communication.
+/// * `settings` - The network settings.
+/// * `message` - The command of the rejected message.
+/// * `(code, reason)` - The code and the reason of the rejection.
+/// * `data` - The hash of the rejected transaction or block.
+///
+/// # Errors
+/// Returns `NetworkError::Broadcasting` if the message cannot be sent.
+fn send_reject(
+    stream: &mut PeerStream,
+    settings: &Arc<Settings>,
+    message: &str,
+    (code, reason): (u8, &str),
+    data: Vec<u8>,
+) -> Result<(), NetworkError> {
+    let reject = Reject::new(settings.get_start_string(), message, code, reason, data);
+    stream
+        .write_all(&reject.to_bytes())
+        .map_err(|_| NetworkError::Broadcasting)?;
+    println!(
+        "Message Reject sent for {} with reason: {}",
+        message, reason
+    );
+    Ok(())
+}
+
+/// Submits a block that did not come from a peer, such as one mined by an external miner.
+///
+/// The block goes through the same validation as the blocks received from peers, and if it is
+/// valid it is connected to the blockchain and announced to every peer of the pool, unless it was
+/// stored in a side branch.
+///
+/// # Arguments
+/// * `settings` - The network settings.
+/// * `chain_state` - The shared blockchain, UTXO set, mempool and header chain.
+/// * `peer_pool` - The shared pool of connections the block is announced to.
+/// * `block` - The submitted block.
+///
+/// # Returns
+/// * `Result<ChainUpdate, NetworkError>` - How the main chain changed, or an error of type
+///   NetworkError.
+///
+/// # Errors
+/// The function can return a NetworkError in the following cases:
+/// * If the block was already found invalid, or fails the validation, in which case it is recorded
+///   in the table of invalid blocks.
+/// * If there is an error acquiring the lock on the blockchain, UTXO set, mempool, header chain or
+///   peer pool.
+pub fn submit_block(
+    settings: &Arc<Settings>,
+    chain_state: &ChainState,
+    peer_pool: &Arc<Mutex<PeerPool>>,
+    block: Block,
+) -> Result<ChainUpdate, NetworkError> {
+    check_block(settings.get_network(), chain_state, &block)?;
+
+    let block_header = block.get_header().clone();
+    let chain_update = connect_block(settings, chain_state, block)?;
+
+    if chain_update != ChainUpdate::SideBranch {
+        announce_block(settings, &block_header, peer_pool, None)?;
+    }
+
+    Ok(chain_update)
+}
+
+/// Connects a block to the blockchain and updates the UTXO set, the mempool and the header chain
+/// accordingly.
+///
+/// If the block makes a competing branch the one with most work, the blocks of the old branch are
+/// disconnected from the UTXO set before the blocks of the new branch are connected. Once the new
+/// branch is connected, the transactions of the old one are offered to the mempool again, and the
+/// ones that are no longer valid on top of the new tip are dropped.
+///
+/// Every block connected to the main chain has its spends validated against the UTXO set of its
+/// parent first. If a block fails the validation, the old main chain is restored, and the block
+/// and the blocks of the new branch building on it are recorded in the table of invalid blocks.
+/// The old main chain is also restored if one of its blocks cannot be disconnected.
+///
+/// The new tip is recorded in the log of events pushed to the wallet, as a new block or as a
+/// reorganization.
+///
+/// # Arguments
+/// * `settings` - The network settings.
+/// * `chain_state` - The shared blockchain, UTXO set, mempool and header chain.
+/// * `block` - The block to connect.
+///
+/// # Returns
+/// * `Result<ChainUpdate, NetworkError>` - How the main chain changed, or an error of type
+///   NetworkError.
+///
+/// # Errors
+/// The function can return a NetworkError in the following cases:
+/// * If a block connected to the main chain fails the validation of its spends, in which case
+///   `NetworkError::InvalidBlock` is returned.
+/// * If there is an error acquiring the lock on the blockchain, UTXO set, mempool, header chain,
+///   table of invalid blocks or wallet events, or if a block cannot be disconnected.
+fn connect_block(
+    settings: &Arc<Settings>,
+    chain_state: &ChainState,
+    block: Block,
+) -> Result<ChainUpdate, NetworkError> {
+    let mut locked_utxo = chain_state
+        .get_utxo()
+        .lock()
+        .map_err(|_| NetworkError::Broadcasting)?;
+    let mut locked_mempool = chain_state
+        .get_mempool()
+        .lock()
+        .map_err(|_| NetworkError::Broadcasting)?;
+    let mut locked_blockchain = chain_state
+        .get_blockchain()
+        .lock()
+        .map_err(|_| NetworkError::Broadcasting)?;
+    let mut locked_header_chain = chain_state
+        .get_header_chain()
+        .lock()
+        .map_err(|_| NetworkError::Broadcasting)?;
+
+    let complete = locked_header_chain.get_start_height() == 0
+        && locked_header_chain
+            .get_headers()
+            .get(1)
+            .is_none_or(|header| locked_blockchain.get_block(&header.get_header()).is_some());
+    let old_tip = locked_blockchain.get_tip_header().clone();
+
+    let block_hash = block.get_header().get_header();
+    let chain_update = locked_blockchain.connect(block);
+
+    let (disconnected, connected) = match &chain_update {
+        ChainUpdate::Extended => (vec![], vec![block_hash]),
+        ChainUpdate::SideBranch => (vec![], vec![]),
+        ChainUpdate::Reorganized {
+            disconnected,
+            connected,
+        } => (disconnected.clone(), connected.clone()),
+    };
+
+    for (undone, hash) in disconnected.iter().enumerate() {
+        let disconnected_block = locked_blockchain.get_block(hash);
+
+        if !disconnected_block.is_some_and(|block| locked_utxo.disconnect(block)) {
+            println!("Block could not be disconnected, its undo data is no longer kept.");
+            restore_main_chain(
+                (&mut locked_utxo, &mut locked_mempool),
+                (&mut locked_blockchain, &mut locked_header_chain),
+                old_tip,
+                (&disconnected, undone),
+                (&connected, 0),
+            )?;
+            return Err(NetworkError::Broadcasting);
+        }
+    }
+
+    if let Some(fork_point) = disconnected
+        .last()
+        .and_then(|hash| locked_blockchain.get_block(hash))
+    {
+        locked_header_chain.rewind(fork_point.get_previuos_block_header());
+    }
+
+    for (position, hash) in connected.iter().enumerate() {
+        let block = locked_blockchain
+            .get_block(hash)
+            .ok_or(NetworkError::Broadcasting)?;
+
+        let height = match locked_blockchain.get_height(hash) {
+            Some(height) => height,
+            None => locked_utxo.get_height().map_or(0, |height| height + 1),
+        };
+        if let Err(invalid_block) =
+            validate_block_spends(settings, &locked_utxo, block, height, complete)
+        {
+            restore_main_chain(
+                (&mut locked_utxo, &mut locked_mempool),
+                (&mut locked_blockchain, &mut locked_header_chain),
+                old_tip,
+                (&disconnected, disconnected.len()),
+                (&connected, position),
+            )?;
+            drop(locked_header_chain);
+            drop(locked_blockchain);
+            drop(locked_mempool);
+            drop(locked_utxo);
+
+            let mut locked_invalid_blocks = chain_state
+                .get_invalid_blocks()
+                .lock()
+                .map_err(|_| NetworkError::Broadcasting)?;
+            locked_invalid_blocks.park(hash.clone(), invalid_block)?;
+            for descendant in connected[position + 1..].iter() {
+                locked_invalid_blocks.park(
+                    descendant.clone(),
+                    InvalidBlock::new(BlockRule::BadPrevBlock, None),
+                )?;
+            }
+            return Err(NetworkError::InvalidBlock);
+        }
+
+        locked_utxo.update(block, height);
+        locked_mempool.update(block);
+        locked_header_chain.add(block.get_header().clone());
+    }
+
+    for hash in connected.iter() {
+        let spent = locked_utxo.get_undo_data(hash).map(Vec::as_slice);
+        locked_blockchain.add_filter(hash, spent.unwrap_or_default());
+    }
+
+    for hash in disconnected.iter().rev() {
+        let txn_list = match locked_blockchain.get_block(hash) {
+            Some(block) => block.get_txn_list().clone(),
+            None => continue,
+        };
+        for tx in txn_list.iter() {
+            let lock_context = build_lock_context(tx, &locked_blockchain, &locked_header_chain);
+            add_to_mempool(
+                settings,
+                (&locked_utxo, &mut locked_mempool),
+                &lock_context,
+                tx,
+            );
+        }
+    }
+
+    if !disconnected.is_empty() {
+        println!(
+            "Chain reorganized: {} blocks disconnected and {} blocks connected.",
+            disconnected.len(),
+            connected.len()
+        );
+    }
+
+    let tip = locked_header_chain
+        .get_headers()
+        .last()
+        .map(|header| (header.get_header(), locked_header_chain.get_height()));
+
+    drop(locked_header_chain);
+    drop(locked_blockchain);
+    drop(locked_mempool);
+    drop(locked_utxo);
+
+    let event = match (&chain_update, tip) {
+        (ChainUpdate::Extended, Some((hash, height))) => Some(NodeEvent::NewBlock(hash, height)),
+        (ChainUpdate::Reorganized { .. }, Some((hash, height))) => {
+            Some(NodeEvent::Reorg(hash, height))
+        }
+        _ => None,
+    };
+    if let Some(event) = event {
+        if !chain_state.publish(event) {
+            return Err(NetworkError::Broadcasting);
+        }
+    }
+
+    Ok(chain_update)
+}
+
+/// Restores the main chain a block was connected on top of, once a block of the new branch fails
+/// the validation of its spends.
+///
+/// The blocks of the new branch connected so far are disconnected from the UTXO set, and the blocks
+/// of the old branch disconnected so far are connected again, removing their transactions from the
+/// mempool.
+///
+/// # Arguments
+/// * `(locked_utxo, locked_mempool)` - The locked UTXO set and mempool.
+/// * `(locked_blockchain, locked_header_chain)` - The locked blockchain and header chain.
+/// * `old_tip` - The header of the tip of the main chain before the block was connected.
+/// * `(disconnected, undone)` - The blocks of the old branch, the old tip first, and how many of
+///   them were disconnected from the UTXO set.
+/// * `(connected, position)` - The blocks of the new branch, the new tip last, and the position of
+///   the block that failed the validation.
+///
+/// # Errors
+/// Returns `NetworkError::Broadcasting` if a block of the new branch cannot be disconnected or a
+/// block of either branch is missing.
+fn restore_main_chain(
+    (locked_utxo, locked_mempool): (&mut UnspentTx, &mut Mempool),
+    (locked_blockchain, locked_header_chain): (&mut BlockChain, &mut HeaderChain),
+    old_tip: BlockHeader,
+    (disconnected, undone): (&[Vec<u8>], usize),
+    (connected, position): (&[Vec<u8>], usize),
+) -> Result<(), NetworkError> {
+    for hash in connected[..position].iter().rev() {
+        let block = locked_blockchain
+            .get_block(hash)
+            .ok_or(NetworkError::Broadcasting)?;
+
+        if !locked_utxo.disconnect(block) {
+            return Err(NetworkError::Broadcasting);
+        }
+    }
+
+    locked_blockchain.restore_main_chain(old_tip, disconnected, connected);
+    if let Some(fork_point) = connected
+        .first()
+        .and_then(|hash| locked_blockchain.get_block(hash))
+    {
+        locked_header_chain.rewind(fork_point.get_previuos_block_header());
+    }
+
+    for (index, hash) in disconnected.iter().enumerate().rev() {
+        let block = locked_blockchain
+            .get_block(hash)
+            .ok_or(NetworkError::Broadcasting)?;
+
+        if index < undone {
+            let height = match locked_blockchain.get_height(hash) {
+                Some(height) => height,
+                None => locked_utxo.get_height().map_or(0, |height| height + 1),
+            };
+            locked_utxo.update(block, height);
+            locked_mempool.update(block);
+        }
+        locked_header_chain.add(block.get_header().clone());
+    }
+
+    println!("Main chain has been restored, the new branch could not be connected.");
+    Ok(())
+}
+
+/// Announces a new block to every peer of the pool but the one it came from.
+///
+/// Peers that sent a `sendheaders` message are announced the block through a `headers` message,
+/// while the rest are announced it through an `inv` message.
+///
+/// # Arguments
+/// * `settings` - The network settings.
+/// * `block_header` - The header of the new block.
+/// * `peer_pool` - The shared pool of connections the block is announced to.
+/// * `origin` - The address of the peer the block comes from, if it came from a peer.
+///
+/// # Returns
+/// * `Result<(), NetworkError>` - A result indicating success or an error of type NetworkError.
+///
+/// # Errors
+/// The function can return a NetworkError if there is an error acquiring the lock on the peer
+/// pool.
+fn announce_block(
+    settings: &Arc<Settings>,
+    block_header: &BlockHeader,
+    peer_pool: &Arc<Mutex<PeerPool>>,
+    origin: Option<&SocketAddr>,
+) -> Result<(), NetworkError> {
+    let headers = Headers::new(settings.get_start_string(), vec![block_header.clone()]).to_bytes();
+    let inv = Inv::new(
+        settings.get_start_string(),
+        vec![Inventory::new(
+            MSG_BLOCK_DATA_TYPE,
+            block_header.get_header(),
+        )],
+    )
+    .to_bytes();
+
+    let locked_peer_pool = peer_pool.lock().map_err(|_| NetworkError::Broadcasting)?;
+    let peers: Vec<_> = locked_peer_pool
+        .iter()
+        .filter(|(addr, _)| Some(addr) != origin)
+        .map(|(addr, writer)| (locked_peer_pool.prefers_headers(addr), writer.clone()))
+        .collect();
+    drop(locked_peer_pool);
+
+    for (prefers_headers, writer) in peers {
+        let announcement = if prefers_headers { &headers } else { &inv };
+
+        if let Err(error) = writer.send(announcement.clone()) {
+            println!("Error when attempting to announce new block: {:?}", error);
+        }
+    }
+
+    Ok(())
+}
+
+/// Manages the "inv" command received over the network.
+///
+/// This function reads an Inv message from the provided `stream`, extracts the inventory,
+/// and sends a GetData message requesting the corresponding data.
+///
+/// # Arguments
+/// * `header` - The MessageHeader of the received message.
+/// * `settings` - An Arc-wrapped reference to the network settings.
+/// * `stream` - A mutable reference to the PeerStream used for network communication.
+/// * `requested` - The hashes of the data requested to the peer.
+/// * `peer` - The shared peer pool and the address of the peer, which is marked as knowing the
+///   announced transactions.
+///
+/// # Returns
+/// * `Result<(), NetworkError>` - A result indicating success or an error of type NetworkError.
+///
+/// # Errors
+/// The function can return a NetworkError in the following cases:
+/// * If there is an error while reading the Inv message from the stream.
+/// * If there is no inventory available in the Inv message.
+/// * If there is an error while creating and sending the GetData message.
+/// * If there is an error while writing to the stream.
+fn manage_inv_command(
+    header: MessageHeader,
+    settings: &Arc<Settings>,
+    stream: &mut PeerStream,
+    requested: &mut HashSet<Vec<u8>>,
+    peer: (&Arc<Mutex<PeerPool>>, &SocketAddr),
+) -> Result<(), NetworkError> {
+    let inv = Inv::from_bytes(header, stream).map_err(NetworkError::from_read_error)?;
+    let (peer_pool, addr) = peer;
+
+    let mut locked_peer_pool = peer_pool.lock().map_err(|_| NetworkError::Broadcasting)?;
+    for inventory in inv.get_inventories() {
+        if inventory.get_type() == MSG_TX_DATA_TYPE {
+            locked_peer_pool.mark_known_tx(addr, inventory.get_data());
+        }
+    }
+
+    let witness = locked_peer_pool.supports_witness(addr);
+    drop(locked_peer_pool);
+
+    let inventory = inv
+        .get_inventories()
+        .pop()
+        .ok_or(NetworkError::Broadcasting)?;
+    requested.insert(inventory.get_data());
+    let get_data = GetData::new(settings.get_start_string(), vec![inventory], witness);
+    stream
+        .write_all(&get_data.to_bytes())
+        .map_err(|_| NetworkError::Broadcasting)?;
+
+    Ok(())
+}
+
+/// Handles the notfound command received from the network, sent by a peer that does not have
+/// some of the data requested to it.
+///
+/// The data is no longer expected from the peer. The blocks are requested to another peer, so they
+/// are not silently lost, unless they had already been reassigned to this peer after another one
+/// did not find them.
+///
+/// # Arguments
+///
+/// * `header` - The message header.
+/// * `settings` - The network settings.
+/// * `stream` - The TCP stream for communication.
+/// * `requested` - The hashes of the data requested to the peer.
+/// * `peer` - The shared peer pool and the address of the peer the message comes from.
+///
+/// # Returns
+///
+/// An empty result if successful, or a `NetworkError` if the message cannot be read, the peer
+/// pool cannot be locked or the blocks cannot be requested to the other peer.
+fn manage_not_found_command(
+    header: MessageHeader,
+    settings: &Arc<Settings>,
+    stream: &mut PeerStream,
+    requested: &mut HashSet<Vec<u8>>,
+    peer: (&Arc<Mutex<PeerPool>>, &SocketAddr),
+) -> Result<(), NetworkError> {
+    let not_found = NotFound::from_bytes(header, stream).map_err(NetworkError::from_read_error)?;
+    let (peer_pool, addr) = peer;
+
+    let mut locked_peer_pool = peer_pool.lock().map_err(|_| NetworkError::Broadcasting)?;
+    let mut block_hashes = vec![];
+
+    for inventory in not_found.get_inventories() {
+        let is_block = matches!(
+            inventory.get_type(),
+            MSG_BLOCK_DATA_TYPE | MSG_WITNESS_BLOCK
+        );
+
+        if requested.remove(&inventory.get_data()) && is_block {
+            block_hashes.push(inventory.get_data());
+        } else if is_block && locked_peer_pool.take_reassigned(addr, &inventory.get_data()) {
+            println!("Reassigned block was not found by peer {} either.", addr);
+        }
+    }
+
+    if block_hashes.is_empty() {
+        return Ok(());
+    }
+
+    let (other, writer) = match locked_peer_pool.reassign_blocks(addr, block_hashes.clone()) {
+        Some(other) => other,
+        None => {
+            println!(
+                "{} blocks not found by peer {} have no other peer to be requested to.",
+                block_hashes.len(),
+                addr
+            );
+            return Ok(());
+        }
+    };
+    let witness = locked_peer_pool.supports_witness(&other);
+    drop(locked_peer_pool);
+
+    println!(
+        "{} blocks not found by peer {} were requested to peer {}.",
+        block_hashes.len(),
+        addr,
+        other
+    );
+    let inventories = block_hashes
+        .into_iter()
+        .map(|block_hash| Inventory::new(MSG_BLOCK_DATA_TYPE, block_hash))
+        .collect();
+
+    writer.send(GetData::new(settings.get_start_string(), inventories, witness).to_bytes())
+}
+
+/// Handles the headers command received from the network.
+///
+/// Peers that were sent a `sendheaders` message announce new blocks through `headers` messages,
+/// so every announced header with a valid proof of work and timestamp that is not yet in the
+/// header chain is requested. The headers are added to the tree of known headers, so those of a
+/// competing branch are kept until the branch wins or loses. A header whose difficulty is not the
+/// one the retarget rules require is ignored, along with the headers after it. If the announced
+/// headers do not connect to any known header, the missing ones are requested through a
+/// `getheaders` message holding the locator of the blockchain.
+///
+/// # Arguments
+///
+/// * `header` - The message header.
+/// * `settings` - The network settings.
+/// * `stream` - The TCP stream for communication.
+/// * `chain_state` - The shared blockchain, UTXO set, mempool and header chain.
+/// * `requested` - The hashes of the data requested to the peer.
+/// * `peer` - The shared peer pool and the address of the peer the message comes from, whose
+///   advertised height is updated with the announced headers.
+///
+/// # Returns
+///
+/// An empty result if successful, or a `NetworkError` if an error occurs.
+fn manage_headers_command(
+    header: MessageHeader,
+    settings: &Arc<Settings>,
+    stream: &mut PeerStream,
+    chain_state: &ChainState,
+    requested: &mut HashSet<Vec<u8>>,
+    peer: (&Arc<Mutex<PeerPool>>, &SocketAddr),
+) -> Result<(), NetworkError> {
+    let new_headers = Headers::from_bytes(header, stream).map_err(NetworkError::from_read_error)?;
+
+    let mut locked_header_chain = chain_state
+        .get_header_chain()
+        .lock()
+        .map_err(|_| NetworkError::Broadcasting)?;
+
+    if let Some(first) = new_headers.get_headers().first() {
+        if let Some(height) = locked_header_chain.get_height_of(first.get_previuos_block_header()) {
+            let (peer_pool, addr) = peer;
+            peer_pool
+                .lock()
+                .map_err(|_| NetworkError::Broadcasting)?
+                .get_peer_heights_mut()
+                .record(*addr, height + new_headers.get_headers().len() as u32);
+        }
+    }
+    let mut announced: Vec<BlockHeader> = new_headers
+        .get_headers()
+        .into_iter()
+        .filter(|block_header| block_header.proof_of_work())
+        .filter(|block_header| {
+            chain_state
+                .get_network_time()
+                .is_header_time_valid(block_header)
+        })
+        .filter(|block_header| {
+            locked_header_chain
+                .get_height_of(&block_header.get_header())
+                .is_none()
+        })
+        .collect();
+
+    let unconnected = announced.first().is_some_and(|first| {
+        locked_header_chain
+            .get_node(first.get_previuos_block_header())
+            .is_none()
+    });
+
+    let mut inserted = 0;
+    for block_header in announced.iter() {
+        if !locked_header_chain.has_required_nbits(settings.get_network(), block_header) {
+            println!("Header announced with the wrong difficulty, it and the rest are ignored.");
+            break;
+        }
+        locked_header_chain.insert(block_header.clone());
+        inserted += 1;
+    }
+    announced.truncate(inserted);
+    drop(locked_header_chain);
+
+    if unconnected {
+        let locator = chain_state
+            .get_blockchain()
+            .lock()
+            .map_err(|_| NetworkError::Broadcasting)?
+            .build_locator();
+        let get_headers = GetHeaders::new(
+            settings.get_start_string(),
+            settings.get_protocol_version(),
+            locator,
+            STOPPING_HASH.to_vec(),
+        );
+
+        stream
+            .write_all(&get_headers.to_bytes())
+            .map_err(|_| NetworkError::Broadcasting)?;
+    }
+
+    let inv: Vec<Inventory> = announced
+        .iter()
+        .map(|block_header| Inventory::new(MSG_BLOCK_DATA_TYPE, block_header.get_header()))
+        .collect();
+
+    if inv.is_empty() {
+        return Ok(());
+    }
+
+    for inventory in inv.iter() {
+        requested.insert(inventory.get_data());
+    }
+
+    let (peer_pool, addr) = peer;
+    let witness = peer_pool
+        .lock()
+        .map_err(|_| NetworkError::Broadcasting)?
+        .supports_witness(addr);
+
+    let get_data = GetData::new(settings.get_start_string(), inv, witness);
+
+    stream
+        .write_all(&get_data.to_bytes())
+        .map_err(|_| NetworkError::Broadcasting)?;
+    Ok(())
+}
+
+/// Handles the getdata command received from the network.
+///
+/// Every block requested is answered with a `block` message, holding the witness data of its
+/// transactions if it was requested through a `MSG_WITNESS_BLOCK` inventory, so peers can
+/// download the blocks the node announced. Likewise, every transaction of the mempool requested,
+/// by txid or by wtxid (`MSG_WTX`), is answered with a `tx` message, and the peer is marked as
+/// knowing it. Transactions requested by wtxid always hold their witness data. Every filtered
+/// block requested is answered with a `merkleblock` message built from the bloom filter loaded by
+/// the peer, followed by a `tx` message for every matched transaction. The blocks and transactions
+/// the node does not have are reported through a `notfound` message. Filtered blocks requested by
+/// peers without a bloom filter and any other type of data are ignored.
+///
+/// # Arguments
+///
+/// * `header` - The message header.
+/// * `settings` - The network settings.
+/// * `stream` - The TCP stream for communication.
+/// * `chain_state` - The shared blockchain, UTXO set, mempool and header chain.
+/// * `peer` - The shared peer pool and the address of the peer the message comes from.
+///
+/// # Returns
+///
+/// An empty result if successful, or a `NetworkError` if an error occurs.
+fn manage_get_data_command(
+    header: MessageHeader,
+    settings: &Arc<Settings>,
+    stream: &mut PeerStream,
+    chain_state: &ChainState,
+    peer: (&Arc<Mutex<PeerPool>>, &SocketAddr),
+) -> Result<(), NetworkError> {
+    let get_data = GetData::from_bytes(header, stream).map_err(NetworkError::from_read_error)?;
+    let (peer_pool, addr) = peer;
+    let mut answer: Vec<u8> = Vec::new();
+
+    let locked_mempool = chain_state
+        .get_mempool()
+        .lock()
+        .map_err(|_| NetworkError::Broadcasting)?;
+    let locked_blockchain = chain_state
+        .get_blockchain()
+        .lock()
+        .map_err(|_| NetworkError::Broadcasting)?;
+    let mut locked_peer_pool = peer_pool.lock().map_err(|_| NetworkError::Broadcasting)?;
+    let mut not_found = vec![];
+
+    for inventory in get_data.get_inventories() {
+        if matches!(
+            inventory.get_type(),
+            MSG_TX_DATA_TYPE | MSG_WITNESS_TX | MSG_WTX_DATA_TYPE
+        ) {
+            let tx = match inventory.get_type() {
+                MSG_WTX_DATA_TYPE => locked_mempool.get_by_wtxid(&inventory.get_data()),
+                _ => locked_mempool.get_txs().get(&inventory.get_data()),
+            };
+
+            match tx {
+                Some(tx) => {
+                    locked_peer_pool.mark_known_tx(addr, tx.get_id(false));
+                    answer.extend(
+                        Tx::with_witness(
+                            settings.get_start_string(),
+                            tx.clone(),
+                            inventory.get_type() != MSG_TX_DATA_TYPE,
+                        )
+                        .to_bytes(),
+                    );
+                }
+                None => not_found.push(inventory),
+            }
+            continue;
+        }
+
+        if matches!(
+            inventory.get_type(),
+            MSG_BLOCK_DATA_TYPE | MSG_WITNESS_BLOCK
+        ) {
+            match locked_blockchain.get_block(&inventory.get_data()) {
+                Some(block) => answer.extend(
+                    BlockMessage::new(
+                        settings.get_start_string(),
+                        block,
+                        inventory.get_type() == MSG_WITNESS_BLOCK,
+                    )
+                    .to_bytes(),
+                ),
+                None => not_found.push(inventory),
+            }
+            continue;
+        }
+
+        if inventory.get_type() != MSG_FILTERED_BLOCK_DATA_TYPE {
+            continue;
+        }
+
+        let block = match locked_blockchain.get_block(&inventory.get_data()) {
+            Some(block) => block,
+            None => continue,
+        };
+        let matches = match locked_peer_pool.match_block(addr, block) {
+            Some(matches) => matches,
+            None => continue,
+        };
+
+        answer.extend(MerkleBlock::new(settings.get_start_string(), block, &matches).to_bytes());
+
+        if matches[0] {
+            let coinbase = block.get_coinbase().to_bytes();
+            let tx = Transaction::from_bytes(&mut coinbase.as_slice())
+                .map_err(|_| NetworkError::Broadcasting)?;
+            answer.extend(Tx::new(settings.get_start_string(), tx).to_bytes());
+        }
+
+        for (tx, matched) in block.get_txn_list().iter().zip(matches.iter().skip(1)) {
+            if *matched {
+                locked_peer_pool.mark_known_tx(addr, tx.get_id(false));
+                answer.extend(Tx::new(settings.get_start_string(), tx.clone()).to_bytes());
+            }
+        }
+    }
 
-    println!("New transaction has been succesfully received.");
+    drop(locked_peer_pool);
+    drop(locked_blockchain);
+    drop(locked_mempool);
+
+    if !not_found.is_empty() {
+        answer.extend(NotFound::new(settings.get_start_string(), not_found).to_bytes());
+    }
 
+    stream
+        .write_all(&answer)
+        .map_err(|_| NetworkError::Broadcasting)?;
     Ok(())
 }
 
-/// Manages the "block" command received over the network.
-///
-/// This function reads a block from the provided `stream` and performs the necessary operations
-/// to update the blockchain, UTXO set, and mempool if the block is valid.
+/// Handles the filterload, filteradd and filterclear commands received from the network, which
+/// manage the bloom filter of the peer.
 ///
 /// # Arguments
-/// * `stream` - A mutable reference to a TcpStream used for network communication.
-/// * `blockchain` - An Arc-wrapped Mutex for accessing and modifying the blockchain.
-/// * `utxo` - An Arc-wrapped Mutex for accessing and modifying the UTXO set.
-/// * `mempool` - An Arc-wrapped Mutex for accessing and modifying the mempool.
+///
+/// * `header` - The message header.
+/// * `stream` - The TCP stream for communication.
+/// * `peer` - The shared peer pool and the address of the peer the message comes from.
 ///
 /// # Returns
-/// * `Result<(), NetworkError>` - A result indicating success or an error of type NetworkError.
+///
+/// An empty result if successful, or a `NetworkError` if an error occurs.
 ///
 /// # Errors
-/// The function can return a NetworkError in the following cases:
-/// * If there is an error while reading the block from the stream.
-/// * If the block fails the proof-of-work or proof-of-inclusion validation.
-/// * If there is an error acquiring the lock on the blockchain, UTXO set, or mempool.
-/// * If there is an error while updating the blockchain, UTXO set, or mempool.
-pub fn manage_block_command(
-    stream: &mut TcpStream,
-    blockchain: &Arc<Mutex<BlockChain>>,
-    utxo: &Arc<Mutex<UnspentTx>>,
-    mempool: &Arc<Mutex<Mempool>>,
+///
+/// Returns `NetworkError::MalformedMessage` if the message cannot be read, or if the peer adds an
+/// element to a bloom filter it never loaded.
+fn manage_filter_command(
+    header: MessageHeader,
+    stream: &mut PeerStream,
+    peer: (&Arc<Mutex<PeerPool>>, &SocketAddr),
 ) -> Result<(), NetworkError> {
-    let block = Block::from_bytes(stream).map_err(|_| NetworkError::Broadcasting)?;
+    let (peer_pool, addr) = peer;
 
-    if block.proof_of_work() && block.proof_of_inclusion() {
-        utxo.lock()
-            .map_err(|_| NetworkError::Broadcasting)?
-            .update(&block);
-        mempool
-            .lock()
-            .map_err(|_| NetworkError::Broadcasting)?
-            .update(&block);
-        blockchain
-            .lock()
-            .map_err(|_| NetworkError::Broadcasting)?
-            .add(block);
-    }
+    match header.get_command() {
+        Some(Command::FilterLoad) => {
+            let filter_load =
+                FilterLoad::from_bytes(header, stream).map_err(NetworkError::from_read_error)?;
 
-    println!("New block has been succesfully received.");
+            peer_pool
+                .lock()
+                .map_err(|_| NetworkError::Broadcasting)?
+                .set_bloom_filter(addr, BloomFilter::new(&filter_load));
+        }
+        Some(Command::FilterAdd) => {
+            let filter_add =
+                FilterAdd::from_bytes(header, stream).map_err(NetworkError::from_read_error)?;
+
+            if !peer_pool
+                .lock()
+                .map_err(|_| NetworkError::Broadcasting)?
+                .add_to_bloom_filter(addr, filter_add.get_element())
+            {
+                return Err(NetworkError::MalformedMessage);
+            }
+        }
+        _ => {
+            FilterClear::from_header(header).map_err(|_| NetworkError::MalformedMessage)?;
+
+            peer_pool
+                .lock()
+                .map_err(|_| NetworkError::Broadcasting)?
+                .clear_bloom_filter(addr);
+        }
+    }
 
     Ok(())
 }
 
-/// Manages the "inv" command received over the network.
+/// Handles the mempool command received from the network (BIP35).
 ///
-/// This function reads an Inv message from the provided `stream`, extracts the inventory,
-/// and sends a GetData message requesting the corresponding data.
+/// Every transaction of the mempool that passes the fee filter and the bloom filter of the peer is
+/// announced to it through `inv` messages of at most `MAX_INV_COUNT` inventories, and marked as
+/// known by the peer.
 ///
 /// # Arguments
-/// * `header` - The MessageHeader of the received message.
-/// * `settings` - An Arc-wrapped reference to the network settings.
-/// * `stream` - A mutable reference to a TcpStream used for network communication.
+///
+/// * `header` - The message header.
+/// * `settings` - The network settings.
+/// * `stream` - The TCP stream for communication.
+/// * `chain_state` - The shared blockchain, UTXO set, mempool and header chain.
+/// * `peer` - The shared peer pool and the address of the peer the message comes from.
 ///
 /// # Returns
-/// * `Result<(), NetworkError>` - A result indicating success or an error of type NetworkError.
 ///
-/// # Errors
-/// The function can return a NetworkError in the following cases:
-/// * If there is an error while reading the Inv message from the stream.
-/// * If there is no inventory available in the Inv message.
-/// * If there is an error while creating and sending the GetData message.
-/// * If there is an error while writing to the stream.
-fn manage_inv_command(
+/// An empty result if successful, or a `NetworkError` if an error occurs.
+fn manage_mempool_command(
     header: MessageHeader,
     settings: &Arc<Settings>,
-    stream: &mut TcpStream,
+    stream: &mut PeerStream,
+    chain_state: &ChainState,
+    peer: (&Arc<Mutex<PeerPool>>, &SocketAddr),
 ) -> Result<(), NetworkError> {
-    let inv = Inv::from_bytes(header, stream)?;
+    MempoolMessage::from_header(header).map_err(|_| NetworkError::MalformedMessage)?;
+    let (peer_pool, addr) = peer;
 
-    let inventory = inv
-        .get_inventories()
-        .pop()
-        .ok_or(NetworkError::Broadcasting)?;
-    let get_data = GetData::new(settings.get_start_string(), vec![inventory]);
-    stream
-        .write_all(&get_data.to_bytes())
+    let locked_utxo = chain_state
+        .get_utxo()
+        .lock()
+        .map_err(|_| NetworkError::Broadcasting)?;
+    let locked_mempool = chain_state
+        .get_mempool()
+        .lock()
         .map_err(|_| NetworkError::Broadcasting)?;
+    let txs: Vec<(Transaction, Option<u64>)> = locked_mempool
+        .get_txs()
+        .values()
+        .map(|tx| (tx.clone(), locked_utxo.get_fee_rate(tx)))
+        .collect();
+    drop(locked_mempool);
+    drop(locked_utxo);
+
+    let mut locked_peer_pool = peer_pool.lock().map_err(|_| NetworkError::Broadcasting)?;
+    let mut inventories = vec![];
+
+    for (tx, fee_rate) in txs {
+        if !locked_peer_pool.accepts_fee_rate(addr, fee_rate)
+            || !locked_peer_pool.is_tx_relevant(addr, &tx)
+        {
+            continue;
+        }
 
+        locked_peer_pool.mark_known_tx(addr, tx.get_id(false));
+        inventories.push(Inventory::new(MSG_TX_DATA_TYPE, tx.get_id(false)));
+    }
+    drop(locked_peer_pool);
+
+    println!(
+        "Message Mempool received, {} transactions announced.",
+        inventories.len()
+    );
+
+    for chunk in inventories.chunks(MAX_INV_COUNT) {
+        let inv = Inv::new(settings.get_start_string(), chunk.to_vec());
+        stream
+            .write_all(&inv.to_bytes())
+            .map_err(|_| NetworkError::Broadcasting)?;
+    }
     Ok(())
 }
 
-/// Handles the headers command received from the network.
+/// Handles the getblocks command received from the network.
+///
+/// The blocks of the main chain after the first hash of the locator that belongs to it, or after
+/// the first known header if none does, are announced to the peer through an `inv` message of at
+/// most `MAX_GET_BLOCKS_COUNT` inventories. The announcement stops at the stopping hash, and at
+/// the first block that was not downloaded.
 ///
 /// # Arguments
 ///
 /// * `header` - The message header.
 /// * `settings` - The network settings.
 /// * `stream` - The TCP stream for communication.
-/// * `blocks` - The map of blocks.
-/// * `utxo_set` - The unspent transaction set.
+/// * `chain_state` - The shared blockchain, UTXO set, mempool and header chain.
 ///
 /// # Returns
 ///
 /// An empty result if successful, or a `NetworkError` if an error occurs.
-fn manage_headers_command(
+fn manage_get_blocks_command(
     header: MessageHeader,
     settings: &Arc<Settings>,
-    stream: &mut TcpStream,
+    stream: &mut PeerStream,
+    chain_state: &ChainState,
 ) -> Result<(), NetworkError> {
-    let new_headers = Headers::from_bytes(header, stream)?;
+    let get_blocks =
+        GetBlocks::from_bytes(header, stream).map_err(NetworkError::from_read_error)?;
 
-    let block_header = new_headers
-        .get_headers()
-        .pop()
-        .ok_or(NetworkError::Broadcasting)?;
+    let locked_blockchain = chain_state
+        .get_blockchain()
+        .lock()
+        .map_err(|_| NetworkError::Broadcasting)?;
+    let locked_header_chain = chain_state
+        .get_header_chain()
+        .lock()
+        .map_err(|_| NetworkError::Broadcasting)?;
 
-    if block_header.proof_of_work() {
-        let inv = vec![Inventory::new(
-            MSG_BLOCK_DATA_TYPE,
-            block_header.get_header(),
-        )];
+    let start_height = locked_header_chain.get_start_height();
+    let fork_height = get_blocks
+        .get_locator()
+        .iter()
+        .find_map(|hash| locked_header_chain.get_height_of(hash))
+        .unwrap_or(start_height);
 
-        let get_data = GetData::new(settings.get_start_string(), inv);
+    let mut inventories = vec![];
+    for block_header in locked_header_chain
+        .get_headers()
+        .iter()
+        .skip((fork_height + 1 - start_height) as usize)
+        .take(MAX_GET_BLOCKS_COUNT)
+    {
+        let block_hash = block_header.get_header();
+        if locked_blockchain.get_block(&block_hash).is_none() {
+            break;
+        }
 
-        stream
-            .write_all(&get_data.to_bytes())
-            .map_err(|_| NetworkError::Broadcasting)?;
+        let is_stopping_hash = block_hash == *get_blocks.get_stopping_hash();
+        inventories.push(Inventory::new(MSG_BLOCK_DATA_TYPE, block_hash));
+        if is_stopping_hash {
+            break;
+        }
     }
-    Ok(())
+    drop(locked_header_chain);
+    drop(locked_blockchain);
+
+    if inventories.is_empty() {
+        return Ok(());
+    }
+
+    let inv = Inv::new(settings.get_start_string(), inventories);
+    stream
+        .write_all(&inv.to_bytes())
+        .map_err(|_| NetworkError::Broadcasting)
 }
 
 /// Handles the ping command received from the network.
@@ -196,7 +1486,7 @@ fn manage_headers_command(
 fn manage_ping_command(
     header: MessageHeader,
     settings: &Arc<Settings>,
-    stream: &mut TcpStream,
+    stream: &mut PeerStream,
 ) -> Result<(), NetworkError> {
     let ping = Ping::from_bytes(header, stream).map_err(|_| NetworkError::Broadcasting)?;
     println!("Message Ping received with nonce: {}", ping.get_nonce());
@@ -208,119 +1498,461 @@ fn manage_ping_command(
     Ok(())
 }
 
+/// Handles the pong command received from the network, which answers a ping sent by the node and
+/// gives the round-trip latency of the peer.
+///
+/// # Arguments
+///
+/// * `header` - The message header.
+/// * `stream` - The TCP stream for communication.
+/// * `peer` - The shared peer pool and the address of the peer the message comes from.
+///
+/// # Returns
+///
+/// An empty result if successful, or a `NetworkError` if an error occurs.
+fn manage_pong_command(
+    header: MessageHeader,
+    stream: &mut PeerStream,
+    peer: (&Arc<Mutex<PeerPool>>, &SocketAddr),
+) -> Result<(), NetworkError> {
+    let pong = Pong::from_bytes(header, stream).map_err(NetworkError::from_read_error)?;
+    let (peer_pool, addr) = peer;
+
+    let latency = peer_pool
+        .lock()
+        .map_err(|_| NetworkError::Broadcasting)?
+        .get_pings_mut()
+        .complete(addr, pong.get_nonce());
+
+    if let Some(latency) = latency {
+        println!(
+            "Peer {} answered a ping in {} ms.",
+            addr,
+            latency.as_millis()
+        );
+    }
+    Ok(())
+}
+
+/// Handles a `sendheaders` message, after which new blocks are announced to the peer through
+/// `headers` messages instead of `inv` ones.
+fn manage_send_headers_command(
+    header: MessageHeader,
+    peer: (&Arc<Mutex<PeerPool>>, &SocketAddr),
+) -> Result<(), NetworkError> {
+    SendHeaders::from_header(header).map_err(|_| NetworkError::MalformedMessage)?;
+
+    let (peer_pool, addr) = peer;
+    peer_pool
+        .lock()
+        .map_err(|_| NetworkError::Broadcasting)?
+        .mark_prefers_headers(addr);
+    Ok(())
+}
+
+/// Handles a `feefilter` message, recording the minimum fee rate of the transactions announced to
+/// the peer.
+fn manage_fee_filter_command(
+    header: MessageHeader,
+    stream: &mut PeerStream,
+    peer: (&Arc<Mutex<PeerPool>>, &SocketAddr),
+) -> Result<(), NetworkError> {
+    let fee_filter =
+        FeeFilter::from_bytes(header, stream).map_err(NetworkError::from_read_error)?;
+
+    let (peer_pool, addr) = peer;
+    peer_pool
+        .lock()
+        .map_err(|_| NetworkError::Broadcasting)?
+        .set_fee_filter(addr, fee_filter.get_fee_rate());
+    Ok(())
+}
+
+/// Handles an `addr` message, keeping the advertised addresses in the address manager of the pool.
+fn manage_addr_command(
+    header: MessageHeader,
+    stream: &mut PeerStream,
+    peer_pool: &Arc<Mutex<PeerPool>>,
+) -> Result<(), NetworkError> {
+    let addr_message = Addr::from_bytes(header, stream).map_err(NetworkError::from_read_error)?;
+
+    let mut locked_peer_pool = peer_pool.lock().map_err(|_| NetworkError::Broadcasting)?;
+
+    for ip in addr_message.get_ip_address_list() {
+        locked_peer_pool
+            .get_addr_manager_mut()
+            .add(ip.get_socket_addr());
+    }
+    Ok(())
+}
+
+/// Handles a `reject` message, logging the message the peer rejected and why.
+fn manage_reject_command(
+    header: MessageHeader,
+    stream: &mut PeerStream,
+) -> Result<(), NetworkError> {
+    let reject = Reject::from_bytes(header, stream).map_err(NetworkError::from_read_error)?;
+
+    println!(
+        "Message Reject received for {} with code {:#04x}, reason: {}, data: {}",
+        reject.get_message(),
+        reject.get_code(),
+        reject.get_reason(),
+        encode_hex(reject.get_data()).unwrap_or_default()
+    );
+    Ok(())
+}
+
+/// Returns the dispatcher holding the handlers of every message the node understands, which is
+/// built the first time it is needed.
+pub fn message_dispatcher() -> &'static MessageDispatcher {
+    static DISPATCHER: OnceLock<MessageDispatcher> = OnceLock::new();
+
+    DISPATCHER.get_or_init(|| {
+        let mut dispatcher = MessageDispatcher::new();
+
+        dispatcher.register_message::<Ping>(|header, settings, stream, _, _, _| {
+            manage_ping_command(header, settings, stream)
+        });
+        dispatcher.register_message::<Pong>(|header, _, stream, _, _, peer| {
+            manage_pong_command(header, stream, peer)
+        });
+        dispatcher.register_message::<Headers>(manage_headers_command);
+        dispatcher.register_message::<Inv>(|header, settings, stream, _, requested, peer| {
+            manage_inv_command(header, settings, stream, requested, peer)
+        });
+        dispatcher.register(
+            Command::Tx,
+            |_, settings, stream, chain_state, requested, peer| {
+                manage_tx_command(stream, settings, chain_state, requested, peer)
+            },
+        );
+        dispatcher.register_message::<SendHeaders>(|header, _, _, _, _, peer| {
+            manage_send_headers_command(header, peer)
+        });
+        dispatcher.register_message::<FeeFilter>(|header, _, stream, _, _, peer| {
+            manage_fee_filter_command(header, stream, peer)
+        });
+        dispatcher.register_message::<FilterLoad>(|header, _, stream, _, _, peer| {
+            manage_filter_command(header, stream, peer)
+        });
+        dispatcher.register_message::<FilterAdd>(|header, _, stream, _, _, peer| {
+            manage_filter_command(header, stream, peer)
+        });
+        dispatcher.register_message::<FilterClear>(|header, _, stream, _, _, peer| {
+            manage_filter_command(header, stream, peer)
+        });
+        dispatcher.register_message::<Addr>(|header, _, stream, _, _, (peer_pool, _)| {
+            manage_addr_command(header, stream, peer_pool)
+        });
+        dispatcher.register_message::<GetData>(|header, settings, stream, chain_state, _, peer| {
+            manage_get_data_command(header, settings, stream, chain_state, peer)
+        });
+        dispatcher.register_message::<GetBlocks>(|header, settings, stream, chain_state, _, _| {
+            manage_get_blocks_command(header, settings, stream, chain_state)
+        });
+        dispatcher.register_message::<MempoolMessage>(
+            |header, settings, stream, chain_state, _, peer| {
+                manage_mempool_command(header, settings, stream, chain_state, peer)
+            },
+        );
+        dispatcher.register_message::<NotFound>(|header, settings, stream, _, requested, peer| {
+            manage_not_found_command(header, settings, stream, requested, peer)
+        });
+        dispatcher.register_message::<Reject>(|header, _, stream, _, _, _| {
+            manage_reject_command(header, stream)
+        });
+        dispatcher.register(
+            Command::Block,
+            |_, settings, stream, chain_state, requested, peer| {
+                manage_block_command(stream, settings, chain_state, requested, peer)
+            },
+        );
+
+        dispatcher
+    })
+}
+
 /// Handles incoming messages based on their command type.
 ///
+/// Messages whose start string is not the one of the configured network are rejected before their
+/// payload is read. Otherwise, the payload is received and checked against its checksum first, and
+/// the message is then handed over to the handler registered for its command in the
+/// `message_dispatcher`. Whatever the handler leaves unread of the payload is discarded, so the
+/// next message is read from its header on.
+///
 /// # Arguments
 ///
 /// * `header` - The message header.
 /// * `settings` - The network settings.
 /// * `stream` - The TCP stream for communication.
-/// * `blocks` - The hashmap storing blocks.
-/// * `utxo_set` - The unspent transaction set.
+/// * `chain_state` - The shared blockchain, UTXO set, mempool and header chain.
+/// * `requested` - The hashes of the data requested to the peer.
+/// * `peer` - The shared peer pool and the address of the peer the message comes from.
 ///
 /// # Returns
 ///
-/// An empty result if successful, or a `NetworkError` if an error occurs.
+/// An empty result if successful, `NetworkError::WrongNetwork` if the message belongs to another
+/// network, or another `NetworkError` if an error occurs.
 pub fn handle_messages(
     header: MessageHeader,
     settings: &Arc<Settings>,
-    stream: &mut TcpStream,
-    blockchain: &Arc<Mutex<BlockChain>>,
-    utxo: &Arc<Mutex<UnspentTx>>,
-    mempool: &Arc<Mutex<Mempool>>,
+    stream: &mut PeerStream,
+    chain_state: &ChainState,
+    requested: &mut HashSet<Vec<u8>>,
+    peer: (&Arc<Mutex<PeerPool>>, &SocketAddr),
 ) -> Result<(), NetworkError> {
-    let command_name: &str = header.get_command_name().as_str();
+    if *header.get_start_string() != settings.get_start_string() {
+        println!(
+            "Peer {} sent a message of another network, with start string {:?}.",
+            peer.1,
+            header.get_start_string()
+        );
+        return Err(NetworkError::WrongNetwork);
+    }
 
-    match command_name {
-        PING_COMMAND => {
-            manage_ping_command(header, settings, stream)?;
-        }
-        HEADERS_COMMAND => {
-            manage_headers_command(header, settings, stream)?;
-        }
-        INV_COMMAND => {
-            manage_inv_command(header, settings, stream)?;
-        }
-        TX_COMMAND => {
-            manage_tx_command(stream, mempool)?;
-        }
-        BLOCK_COMMAND => {
-            manage_block_command(stream, blockchain, utxo, mempool)?;
+    stream.receive_payload(&header)?;
+
+    let result =
+        message_dispatcher().dispatch(header, settings, stream, chain_state, requested, peer);
+    stream.discard_payload();
+    result
+}
+
+/// Penalizes a peer for misbehaving.
+///
+/// # Arguments
+///
+/// * `peer_scores` - The shared peer scores.
+/// * `ip` - The IP address of the peer.
+/// * `misbehavior` - The misbehavior the peer incurred in.
+///
+/// # Returns
+///
+/// `true` if the peer has been banned and must be disconnected.
+fn penalize_peer(
+    peer_scores: &Arc<Mutex<PeerScores>>,
+    ip: IpAddr,
+    misbehavior: Misbehavior,
+) -> bool {
+    match peer_scores.lock() {
+        Ok(mut locked_scores) => match locked_scores.misbehaving(ip, misbehavior) {
+            Ok(banned) => banned,
+            Err(err) => {
+                println!("{:?}", err);
+                true
+            }
+        },
+        Err(_) => false,
+    }
+}
+
+/// Copies the misbehavior score of a peer to its statistics in the peer pool.
+///
+/// # Arguments
+///
+/// * `peer_scores` - The shared peer scores.
+/// * `peer_pool` - The shared pool of connections.
+/// * `addr` - The address of the peer.
+fn record_misbehavior(
+    peer_scores: &Arc<Mutex<PeerScores>>,
+    peer_pool: &Arc<Mutex<PeerPool>>,
+    addr: &SocketAddr,
+) {
+    let score = match peer_scores.lock() {
+        Ok(locked_scores) => locked_scores.get_score(&addr.ip()),
+        Err(_) => return,
+    };
+
+    if let Ok(mut locked_peer_pool) = peer_pool.lock() {
+        locked_peer_pool.set_misbehavior(addr, score);
+    }
+}
+
+/// Marks a peer as stale once it stops answering within the read timeout, so the thread listening
+/// to it can stop instead of waiting for it forever.
+///
+/// # Arguments
+///
+/// * `peer_pool` - The shared pool of connections.
+/// * `addr` - The address of the stale peer.
+fn mark_peer_stale(peer_pool: &Arc<Mutex<PeerPool>>, addr: &SocketAddr) {
+    println!(
+        "Peer {} did not answer in time, it is now considered stale.",
+        addr
+    );
+
+    if let Ok(mut locked_peer_pool) = peer_pool.lock() {
+        locked_peer_pool.mark_stale(addr);
+    }
+}
+
+/// Listens to a peer of the pool from its own thread, handling every message it sends.
+///
+/// The peer is penalized when it misbehaves, and disconnected once it gets banned. It is removed
+/// from the pool once it disconnects, is marked as stale, or a shutdown is requested.
+///
+/// # Arguments
+///
+/// * `addr` - The address of the peer.
+/// * `writer` - The writing half of the connection to the peer.
+/// * `settings` - The network settings.
+/// * `peer_pool` - The shared pool of connections the peer belongs to.
+/// * `chain_state` - The shared blockchain, UTXO set, mempool and header chain.
+/// * `peer_scores` - The shared peer scores.
+/// * `shutdown` - The shutdown coordinator of the node.
+///
+/// # Returns
+///
+/// The handle of the listening thread, or `None` if the connection cannot be read from.
+pub fn listen_to_peer(
+    addr: SocketAddr,
+    writer: &PeerWriter,
+    settings: &Arc<Settings>,
+    peer_pool: &Arc<Mutex<PeerPool>>,
+    chain_state: &ChainState,
+    peer_scores: &Arc<Mutex<PeerScores>>,
+    shutdown: &ShutdownCoordinator,
+) -> Option<JoinHandle<()>> {
+    let mut reader = writer.reader();
+    let shared_peer_pool = peer_pool.clone();
+    let shared_settings = settings.clone();
+    let shared_chain_state = chain_state.clone();
+    let shared_peer_scores = peer_scores.clone();
+    let shared_shutdown = shutdown.clone();
+
+    Some(thread::spawn(move || {
+        let mut requested: HashSet<Vec<u8>> = HashSet::new();
+
+        while !shared_shutdown.is_requested() {
+            let header = match MessageHeader::from_bytes(&mut reader) {
+                Ok(header) => header,
+                Err(_) if shared_shutdown.is_requested() => break,
+                Err(err) => {
+                    if let NetworkError::StalePeer = NetworkError::from_read_error(err) {
+                        mark_peer_stale(&shared_peer_pool, &addr);
+                    }
+                    break;
+                }
+            };
+
+            if let Ok(mut locked_peer_pool) = shared_peer_pool.lock() {
+                locked_peer_pool.record_message(&addr, header.get_command_name());
+            }
+
+            if let Err(err) = handle_messages(
+                header,
+                &shared_settings,
+                &mut reader,
+                &shared_chain_state,
+                &mut requested,
+                (&shared_peer_pool, &addr),
+            ) {
+                if shared_shutdown.is_requested() {
+                    break;
+                }
+                println!("{:?}", err);
+
+                if let NetworkError::StalePeer = err {
+                    mark_peer_stale(&shared_peer_pool, &addr);
+                    break;
+                }
+
+                let misbehavior = match Misbehavior::from_error(&err) {
+                    Some(misbehavior) => misbehavior,
+                    None => break,
+                };
+
+                let banned = penalize_peer(&shared_peer_scores, addr.ip(), misbehavior);
+                record_misbehavior(&shared_peer_scores, &shared_peer_pool, &addr);
+
+                if banned {
+                    reader.shutdown();
+                    break;
+                }
+
+                if misbehavior.leaves_stream_unreadable() {
+                    break;
+                }
+            }
         }
-        _ => {
-            stream
-                .read_exact(&mut vec![0u8; header.get_payload_size() as usize])
-                .map_err(|_| NetworkError::Broadcasting)?;
+
+        if let Ok(mut locked_peer_pool) = shared_peer_pool.lock() {
+            locked_peer_pool.remove(&addr);
+            drop(locked_peer_pool);
         }
-    };
-    Ok(())
+    }))
 }
 
-/// Performs broadcasting of messages to multiple TCP streams.
+/// Performs broadcasting of messages to every connection of the peer pool.
+///
+/// Every peer is listened to from its own thread. Peers that misbehave are penalized, and
+/// disconnected once they get banned. Peers are removed from the pool once they disconnect, or
+/// once they are marked as stale for not sending anything within the read timeout or not answering
+/// the pings of the keepalive thread, which is spawned along with the listening threads. Every
+/// thread finishes once a shutdown is requested.
 ///
 /// # Arguments
 ///
 /// * `settings` - The network settings.
-/// * `streams` - The vector of TCP streams to broadcast messages to.
-/// * `blocks` - The hashmap storing blocks.
-/// * `utxo_set` - The unspent transaction set.
+/// * `peer_pool` - The shared pool of connections to listen to.
+/// * `chain_state` - The shared blockchain, UTXO set, mempool and header chain.
+/// * `peer_scores` - The shared peer scores.
+/// * `shutdown` - The shutdown coordinator of the node, which shuts the connections down.
 ///
 /// # Returns
 ///
-/// An empty result if successful, or a `NetworkError` if an error occurs.
+/// The handles of the listening threads if successful, or a `NetworkError` if an error occurs.
 pub fn broadcasting(
     settings: Arc<Settings>,
-    streams: &mut Vec<Arc<Mutex<TcpStream>>>,
-    blockchain: Arc<Mutex<BlockChain>>,
-    utxo: Arc<Mutex<UnspentTx>>,
-    mempool: Arc<Mutex<Mempool>>,
+    peer_pool: &Arc<Mutex<PeerPool>>,
+    chain_state: ChainState,
+    peer_scores: Arc<Mutex<PeerScores>>,
+    shutdown: ShutdownCoordinator,
 ) -> Result<Vec<JoinHandle<()>>, NetworkError> {
     println!("Broadcasting has begun.\n");
 
     let mut handles_broadcasting = vec![];
+    let peers: Vec<_> = peer_pool
+        .lock()
+        .map_err(|_| NetworkError::Broadcasting)?
+        .iter()
+        .cloned()
+        .collect();
 
-    for stream in streams {
-        let shared_stream = stream.clone();
-        let shared_settings = settings.clone();
-        let shared_blockchain = blockchain.clone();
-        let shared_utxo = utxo.clone();
-        let shared_mempool = mempool.clone();
-
-        let handle_broadcasting = thread::spawn(move || {
-            if let Ok(mut locked_stream) = shared_stream.lock() {
-                loop {
-                    if let Ok(header) = MessageHeader::from_bytes(&mut *locked_stream) {
-                        if let Err(err) = handle_messages(
-                            header,
-                            &shared_settings,
-                            &mut locked_stream,
-                            &shared_blockchain,
-                            &shared_utxo,
-                            &shared_mempool,
-                        ) {
-                            println!("{:?}", err);
-                            return;
-                        }
-                    } else {
-                        return;
-                    }
-                }
-            }
-        });
-
-        handles_broadcasting.push(handle_broadcasting);
+    for (addr, writer) in peers {
+        if let Some(handle_broadcasting) = listen_to_peer(
+            addr,
+            &writer,
+            &settings,
+            peer_pool,
+            &chain_state,
+            &peer_scores,
+            &shutdown,
+        ) {
+            handles_broadcasting.push(handle_broadcasting);
+        }
     }
 
+    handles_broadcasting.push(keep_alive(settings, peer_pool.clone(), shutdown));
+
     Ok(handles_broadcasting)
 }
 
-/// Broadcasts a new transaction to a list of network streams.
+/// Broadcasts a new transaction to every connection of the peer pool.
 ///
-/// This function sends the provided `broadcast_tx_msg` to each stream in the `streams` list.
+/// This function announces the transaction through an `inv` message to each stream in the peer
+/// pool, except to the peers already known to have the transaction, so it is never relayed back
+/// to the peer it came from, and to the peers whose fee filter or bloom filter the transaction
+/// does not pass. Peers that negotiated wtxid relay (BIP339) are announced its wtxid, and the rest
+/// its txid. Every peer it is announced to is marked as knowing it, and requests it through a
+/// `getdata` message answered from the mempool, so the transaction must already be in it.
 ///
 /// # Arguments
-/// * `broadcast_tx_msg` - The Tx message to broadcast.
-/// * `streams` - A reference to a vector of Arc-wrapped Mutex-wrapped TcpStream objects representing network streams.
+/// * `settings` - The network settings.
+/// * `tx` - The transaction to broadcast.
+/// * `peer_pool` - The shared pool of connections to broadcast the transaction to.
+/// * `fee_rate` - The fee rate of the transaction in satoshis per kilobyte, if it is known.
 ///
 /// # Returns
 /// * `Result<(), NetworkError>` - A result indicating success or an error of type NetworkError.
@@ -329,39 +1961,59 @@ pub fn broadcasting(
 /// The function can return a NetworkError in the following case:
 /// * If there is an error while writing to a network stream.
 pub fn broadcast_new_txn(
-    broadcast_tx_msg: Tx,
-    streams: &Vec<Arc<Mutex<TcpStream>>>,
+    settings: &Arc<Settings>,
+    tx: &Transaction,
+    peer_pool: &Arc<Mutex<PeerPool>>,
+    fee_rate: Option<u64>,
 ) -> Result<(), NetworkError> {
-    let tx = broadcast_tx_msg.to_bytes();
     let mut count = 0;
 
-    println!("Tx broadcast message\n:{:?}", broadcast_tx_msg);
-    println!(
-        "Tx to be broadcasted:\n: {:?}\n\n",
-        broadcast_tx_msg.transaction
-    );
-    println!(
-        "Is Tx segwit: {:?}\n\n",
-        broadcast_tx_msg.transaction.is_segwit()
-    );
+    println!("Tx to be broadcasted:\n: {:?}\n\n", tx);
+    println!("Is Tx segwit: {:?}\n\n", tx.is_segwit());
 
-    for stream in streams {
-        if let Ok(mut locked_stream) = stream.lock() {
-            match locked_stream.write_all(&tx) {
-                Ok(_) => count += 1,
-                Err(error) => {
-                    println!(
-                        "Error when attempting to broadcast created transaction: {}",
-                        error
-                    )
-                }
-            }
+    let tx_id = tx.get_id(false);
+    let inv = Inv::new(
+        settings.get_start_string(),
+        vec![Inventory::new(MSG_TX_DATA_TYPE, tx_id.clone())],
+    )
+    .to_bytes();
+    let wtx_inv = Inv::new(
+        settings.get_start_string(),
+        vec![Inventory::new(MSG_WTX_DATA_TYPE, tx.wtxid())],
+    )
+    .to_bytes();
+    let mut locked_peer_pool = peer_pool.lock().map_err(|_| NetworkError::Broadcasting)?;
+    let candidates: Vec<_> = locked_peer_pool
+        .iter()
+        .filter(|(addr, _)| !locked_peer_pool.knows_tx(addr, &tx_id))
+        .filter(|(addr, _)| locked_peer_pool.accepts_fee_rate(addr, fee_rate))
+        .cloned()
+        .collect();
+
+    let mut peers = Vec::new();
+    for (addr, writer) in candidates {
+        if locked_peer_pool.is_tx_relevant(&addr, tx) {
+            locked_peer_pool.mark_known_tx(&addr, tx_id.clone());
+            peers.push((locked_peer_pool.relays_wtxid(&addr), writer));
+        }
+    }
+    drop(locked_peer_pool);
 
-            drop(locked_stream);
+    for (relays_wtxid, writer) in peers {
+        let announcement = if relays_wtxid { &wtx_inv } else { &inv };
+
+        match writer.send(announcement.clone()) {
+            Ok(_) => count += 1,
+            Err(error) => {
+                println!(
+                    "Error when attempting to broadcast created transaction: {:?}",
+                    error
+                )
+            }
         }
     }
 
-    println!("Succesfully broadcasted new transaction to {} nodes", count);
+    println!("Succesfully announced new transaction to {} nodes", count);
 
     Ok(())
 }
@@ -0,0 +1,85 @@
+use crate::wallet_utils::event_log::NodeEvent;
+
+use std::sync::{
+    mpsc::{self, Receiver, Sender},
+    Arc, Mutex,
+};
+
+/// Delivers the events of the node (new blocks, new mempool transactions, reorganizations, and
+/// peers connecting or disconnecting) to every subscriber, so the wallet server and other tools
+/// can react to them without polling the shared state of the node.
+///
+/// The bus is a handle: its clones share the same subscribers, so it can be handed to the peer
+/// pool and the chain state alike. Subscribers that dropped their receiver are forgotten the next
+/// time an event is published.
+#[derive(Debug, Clone, Default)]
+pub struct EventBus {
+    subscribers: Arc<Mutex<Vec<Sender<NodeEvent>>>>,
+}
+
+impl EventBus {
+    /// Creates a bus without subscribers.
+    pub fn new() -> EventBus {
+        EventBus::default()
+    }
+
+    /// Subscribes to the events of the node.
+    ///
+    /// # Returns
+    ///
+    /// The receiving end of the events published from now on.
+    pub fn subscribe(&self) -> Receiver<NodeEvent> {
+        let (sender, receiver) = mpsc::channel();
+
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.push(sender);
+        }
+        receiver
+    }
+
+    /// Sends an event to every subscriber, dropping the ones that stopped listening.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The event to publish.
+    pub fn publish(&self, event: NodeEvent) {
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.retain(|subscriber| subscriber.send(event.clone()).is_ok());
+        }
+    }
+
+    /// Returns the amount of subscribers that were listening when the last event was published.
+    pub fn get_subscriber_count(&self) -> usize {
+        self.subscribers
+            .lock()
+            .map(|subscribers| subscribers.len())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod event_bus_test {
+    use super::*;
+    use std::net::SocketAddr;
+
+    #[test]
+    fn test_events_reach_every_live_subscriber() {
+        let event_bus = EventBus::new();
+        let first = event_bus.subscribe();
+        let second = event_bus.clone().subscribe();
+        drop(event_bus.subscribe());
+
+        let addr = SocketAddr::from(([127, 0, 0, 1], 18333));
+        event_bus.publish(NodeEvent::PeerConnected(addr));
+        event_bus.publish(NodeEvent::NewBlock(vec![1; 32], 10));
+
+        for receiver in [first, second] {
+            assert_eq!(receiver.try_recv(), Ok(NodeEvent::PeerConnected(addr)));
+            assert_eq!(
+                receiver.try_recv(),
+                Ok(NodeEvent::NewBlock(vec![1; 32], 10))
+            );
+        }
+        assert_eq!(event_bus.get_subscriber_count(), 2);
+    }
+}
@@ -1,25 +1,36 @@
 use super::{
     super::messages::{header::MessageHeader, version::Version},
+    addr_manager::AddrManager,
     network_constants::{
-        DIG_COMMAND, DURATION_TIMEOUT_MILLIS, SERVICES_ACEPTED, SHORT_ARG, VERSION_ACEPTED,
+        DIG_COMMAND, DNS_RECORD_TYPES, DURATION_TIMEOUT_MILLIS, PROXY_RESOLVE_ATTEMPTS, SHORT_ARG,
     },
     network_error::NetworkError,
+    network_time::NetworkTime,
+    peer_features::PeerFeatures,
+    peer_heights::PeerHeights,
+    peer_scoring::PeerScores,
+    socks5::{self, SocksTarget},
 };
 
 use crate::{
-    messages::message_constants::{SEND_HEADERS_COMMAND, VERACK_COMMAND},
+    messages::{
+        command::Command, fee_filter::FeeFilter, message_constants::VERACK_COMMAND,
+        send_headers::SendHeaders, wtxid_relay::WtxidRelay,
+    },
     settings_mod::settings::Settings,
 };
 
+use chrono::Utc;
 use std::{
-    io::Write,
-    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream},
-    process::Command,
+    collections::HashMap,
+    io::{Read, Write},
+    net::{IpAddr, SocketAddr, TcpStream},
+    process,
     str::FromStr,
     time::Duration,
 };
 
-/// Performs peer discovery by querying a DNS seed for IPv6 addresses.
+/// Performs peer discovery by querying a DNS seed for both IPv4 and IPv6 addresses.
 ///
 /// # Arguments
 ///
@@ -27,116 +38,354 @@ use std::{
 ///
 /// # Returns
 ///
-/// A vector of discovered IPv6 addresses, or a `NetworkError` if an error occurs.
-fn peer_discovery(dns_seed: &String) -> Result<Vec<Ipv6Addr>, NetworkError> {
-    let dns_output: std::process::Output = Command::new(DIG_COMMAND)
-        .arg(SHORT_ARG)
-        .arg(dns_seed)
-        .output()?;
-    let dns_output: String = String::from_utf8(dns_output.stdout)?;
+/// A vector of discovered addresses, or a `NetworkError` if an error occurs.
+fn peer_discovery(dns_seed: &String) -> Result<Vec<IpAddr>, NetworkError> {
+    let mut ips: Vec<IpAddr> = Vec::new();
 
-    let peers: Vec<&str> = dns_output.lines().collect();
+    for record_type in DNS_RECORD_TYPES {
+        let dns_output: std::process::Output = process::Command::new(DIG_COMMAND)
+            .arg(SHORT_ARG)
+            .arg(dns_seed)
+            .arg(record_type)
+            .output()?;
+        let dns_output: String = String::from_utf8(dns_output.stdout)?;
 
-    let ips: Vec<Ipv6Addr> = peers
-        .iter()
-        .filter_map(|ip_string| match Ipv4Addr::from_str(ip_string) {
-            Ok(ip) => Some(ip.to_ipv6_mapped()),
-            Err(_) => None,
-        })
-        .collect();
+        ips.extend(
+            dns_output
+                .lines()
+                .filter_map(|ip_string| IpAddr::from_str(ip_string).ok()),
+        );
+    }
 
     Ok(ips)
 }
 
-/// Checks if a given version is compatible with the accepted versions.
+/// Performs peer discovery by resolving a DNS seed through a SOCKS5 proxy, so no DNS query leaks
+/// outside of it.
+///
+/// Since the proxy answers a single address per query, the seed is resolved up to
+/// `PROXY_RESOLVE_ATTEMPTS` times, keeping the distinct addresses.
 ///
 /// # Arguments
 ///
-/// * `version` - The version to check.
+/// * `proxy` - The address of the proxy.
+/// * `dns_seed` - The DNS seed to query.
 ///
 /// # Returns
 ///
-/// `true` if the version is compatible, `false` otherwise.
-fn is_version_compatible(version: &Version) -> bool {
-    version.get_version() == VERSION_ACEPTED && version.get_services() == SERVICES_ACEPTED
+/// A vector of discovered addresses, or a `NetworkError` if the proxy could not resolve the seed.
+fn proxied_peer_discovery(proxy: &SocketAddr, dns_seed: &str) -> Result<Vec<IpAddr>, NetworkError> {
+    let mut ips: Vec<IpAddr> = Vec::new();
+
+    for _ in 0..PROXY_RESOLVE_ATTEMPTS {
+        let ip = socks5::resolve(proxy, dns_seed)?.to_canonical();
+
+        if !ips.contains(&ip) {
+            ips.push(ip);
+        }
+    }
+
+    Ok(ips)
 }
 
-/// Performs the handshake with peer nodes.
+/// Opens a connection to a peer, through the proxy of the settings if there is one.
+fn connect_to_peer(settings: &Settings, socket: &SocketAddr) -> Result<TcpStream, NetworkError> {
+    match settings.get_proxy() {
+        Some(proxy) => socks5::connect(&proxy, &SocksTarget::Ip(socket.ip()), socket.port()),
+        None => Ok(TcpStream::connect_timeout(
+            socket,
+            Duration::from_millis(DURATION_TIMEOUT_MILLIS),
+        )?),
+    }
+}
+
+/// Reads the messages a peer sends until its `verack`, recording whether it sent a `wtxidrelay`
+/// message (BIP339). Other messages sent before the `verack` are skipped.
+///
+/// # Arguments
+///
+/// * `stream` - The connection to the peer.
+/// * `features` - The features of the peer, which are updated if it agreed to wtxid relay.
+///
+/// # Returns
+///
+/// An empty result once the `verack` is received, or a `NetworkError` if a message cannot be
+/// read.
+fn receive_verack(stream: &mut TcpStream, features: &mut PeerFeatures) -> Result<(), NetworkError> {
+    loop {
+        let header = MessageHeader::from_bytes(stream).map_err(|_| NetworkError::HandShake)?;
+
+        match header.get_command() {
+            Some(Command::Verack) => return Ok(()),
+            Some(Command::WtxidRelay) => {
+                WtxidRelay::from_header(header).map_err(|_| NetworkError::HandShake)?;
+                features.mark_wtxid_relay();
+            }
+            _ => {
+                let mut payload = vec![0u8; header.get_payload_size() as usize];
+                stream.read_exact(&mut payload)?;
+            }
+        }
+    }
+}
+
+/// Selects the addresses of the peers to connect to, in order.
+///
+/// If the settings hold a `connect_only` list, those are the only addresses returned, and neither
+/// the anchors nor the DNS seed are used. Otherwise, the anchors come first, followed by the peers
+/// discovered through the DNS seed.
+///
+/// # Returns
+///
+/// The addresses of the peers, or a `NetworkError` if the DNS seed cannot be queried and there
+/// are no anchors.
+fn select_sockets(
+    settings: &Settings,
+    anchors: &[SocketAddr],
+) -> Result<Vec<SocketAddr>, NetworkError> {
+    if !settings.get_connect_only().is_empty() {
+        return Ok(settings.get_connect_only().clone());
+    }
+
+    let discovered = match settings.get_proxy() {
+        Some(proxy) => proxied_peer_discovery(&proxy, settings.get_dns_seed()),
+        None => peer_discovery(settings.get_dns_seed()),
+    };
+    let ips: Vec<IpAddr> = match discovered {
+        Ok(ips) => ips,
+        Err(err) if anchors.is_empty() => return Err(err),
+        Err(_) => vec![],
+    };
+
+    Ok(anchors
+        .iter()
+        .copied()
+        .chain(
+            ips.into_iter()
+                .map(|ip| SocketAddr::new(ip, settings.get_port()))
+                .filter(|socket| !anchors.contains(socket)),
+        )
+        .collect())
+}
+
+/// Connects to a single peer and performs the handshake with it.
 ///
 /// # Arguments
 ///
 /// * `settings` - The network settings.
+/// * `socket` - The address of the peer.
 ///
 /// # Returns
 ///
-/// A vector of established TCP streams to the peer nodes.
-pub fn handshake(settings: &Settings) -> Result<Vec<TcpStream>, NetworkError> {
-    println!("Node handshake has begun...");
-    let ips: Vec<Ipv6Addr> = peer_discovery(settings.get_dns_seed())?;
+/// The stream connected to the peer, with the read and write timeouts of the settings set, along
+/// with the version message of the peer and the features negotiated with it, or a `NetworkError`
+/// if the peer cannot be reached, does not complete the handshake, or does not serve the
+/// blockchain or speaks a too old protocol. If the version of the peer carries the nonce of the
+/// version sent, the node connected to itself, so the connection is dropped with
+/// `NetworkError::SelfConnection`.
+pub fn connect_and_handshake(
+    settings: &Settings,
+    socket: &SocketAddr,
+) -> Result<(TcpStream, Version, PeerFeatures), NetworkError> {
+    // Se crea nuestro version
+    let version = Version::new(socket, settings);
+    //Se establece la conexion
+    let mut stream = connect_to_peer(settings, socket)?;
 
-    let mut streams: Vec<TcpStream> = Vec::new();
+    stream.set_read_timeout(Some(settings.get_read_timeout()))?;
+    stream.set_write_timeout(Some(settings.get_write_timeout()))?;
 
-    for ip in ips {
-        // Se crea nuestro version
-        let version = Version::new(ip, settings);
-        //Se establece la conexion
-        let socket = SocketAddr::new(IpAddr::V6(ip), settings.get_port());
+    //Se envia nuestro version
+    stream.write_all(&version.to_bytes())?;
 
-        let mut stream = match TcpStream::connect_timeout(
-            &socket,
-            Duration::from_millis(DURATION_TIMEOUT_MILLIS),
-        ) {
-            Ok(s) => s,
-            Err(_) => continue,
-        };
+    //Se recibe el version del peer
+    let header_version =
+        MessageHeader::from_bytes(&mut stream).map_err(|_| NetworkError::HandShake)?;
 
-        //Se envia nuestro version
-        stream.write_all(&version.to_bytes())?;
+    if *header_version.get_start_string() != settings.get_start_string() {
+        println!(
+            "Connection to {} has been dropped, it belongs to another network.",
+            socket
+        );
+        return Err(NetworkError::WrongNetwork);
+    }
+    let version_peer =
+        Version::from_bytes(header_version, &mut stream).map_err(|_| NetworkError::HandShake)?;
 
-        //Se recibe el version del peer
-        let header_version = match MessageHeader::from_bytes(&mut stream) {
-            Ok(header) => header,
-            Err(_) => continue,
-        };
+    if version_peer.get_nonce() == version.get_nonce() {
+        println!(
+            "Connection to {} has been dropped, it leads to this node.",
+            socket
+        );
+        return Err(NetworkError::SelfConnection);
+    }
 
-        let version_peer = match Version::from_bytes(header_version, &mut stream) {
-            Ok(version) => version,
-            Err(_) => continue,
-        };
+    let mut features = PeerFeatures::new(settings.get_protocol_version(), &version_peer);
+    if !features.is_supported() {
+        return Err(NetworkError::HandShake);
+    }
 
-        if !is_version_compatible(&version_peer) || (version_peer.get_services() & 8 == 0) {
-            continue;
-        }
+    if features.supports_wtxid_relay() {
+        let wtxid_relay = WtxidRelay::new(settings.get_start_string());
 
-        //Se crea nuestro verack
-        let verack = MessageHeader::new(settings.get_start_string(), VERACK_COMMAND.to_string());
+        stream.write_all(&wtxid_relay.to_bytes())?;
+    }
 
-        //Se envia nuestro verack
-        stream.write_all(&verack.to_bytes())?;
+    //Se crea nuestro verack
+    let verack = MessageHeader::new(settings.get_start_string(), VERACK_COMMAND.to_string());
 
-        //Se recibe el verack del peer
-        match MessageHeader::from_bytes(&mut stream) {
-            Ok(header) => header,
-            Err(_) => break,
-        };
+    //Se envia nuestro verack
+    stream.write_all(&verack.to_bytes())?;
 
-        let send_headers = MessageHeader::new(
-            settings.get_start_string(),
-            SEND_HEADERS_COMMAND.to_string(),
-        );
+    //Se recibe el verack del peer
+    receive_verack(&mut stream, &mut features)?;
+
+    if features.supports_send_headers() {
+        let send_headers = SendHeaders::new(settings.get_start_string());
 
         stream.write_all(&send_headers.to_bytes())?;
+    }
+
+    if features.supports_fee_filter() {
+        let fee_filter = FeeFilter::new(settings.get_start_string(), settings.get_min_relay_fee());
 
-        streams.push(stream);
+        stream.write_all(&fee_filter.to_bytes())?;
+    }
+
+    Ok((stream, version_peer, features))
+}
+
+/// Performs the handshake with peer nodes.
+///
+/// # Arguments
+///
+/// * `settings` - The network settings.
+/// * `peer_scores` - The peer scores, used to skip banned peers.
+/// * `anchors` - The addresses of the peers to connect to first.
+/// * `network_time` - The network time, which collects the timestamps of the peers' version
+///   messages.
+/// * `peer_heights` - The peer heights, which collect the start heights of the peers' version
+///   messages.
+/// * `peer_features` - The protocol version negotiated with each peer, the services it advertised
+///   and whether it agreed to wtxid relay. Peers that do not serve the blockchain or speak a too
+///   old protocol are skipped, and `wtxidrelay`, `sendheaders` and `feefilter` messages are only
+///   sent to the peers that understand them.
+/// * `addr_manager` - The address manager, which keeps the addresses that were not connected to
+///   so the connection health monitor can replace evicted peers with them.
+///
+/// If the settings hold a proxy, both the DNS seed and the peers are reached through it.
+///
+/// The anchors, the best-performing peers of the last run, are connected to before the peers
+/// discovered through the DNS seed, and are enough to start if the seed cannot be queried. If the
+/// settings hold a `connect_only` list, the node connects exclusively to those peers instead.
+///
+/// # Returns
+///
+/// A vector of established TCP streams to the peer nodes, along with the addresses of the peers,
+/// which are not the addresses the streams are connected to when a proxy is used. Every stream
+/// has the read and write timeouts of the settings set, so a hung peer cannot block a reading or
+/// writing thread forever.
+pub fn handshake(
+    settings: &Settings,
+    peer_scores: &PeerScores,
+    anchors: &[SocketAddr],
+    network_time: &mut NetworkTime,
+    peer_heights: &mut PeerHeights,
+    peer_features: &mut HashMap<SocketAddr, PeerFeatures>,
+    addr_manager: &mut AddrManager,
+) -> Result<Vec<(SocketAddr, TcpStream)>, NetworkError> {
+    println!("Node handshake has begun...");
+    let sockets = select_sockets(settings, anchors)?;
+
+    let mut streams: Vec<(SocketAddr, TcpStream)> = Vec::new();
+
+    for socket in sockets {
+        if peer_scores.is_banned(&socket.ip()) {
+            continue;
+        }
+
+        // One connection more than the maximum is kept, since the headers download consumes it.
+        if streams.len() > settings.get_max_outbound() {
+            // Connect-only nodes never connect to other peers.
+            if settings.get_connect_only().is_empty() {
+                addr_manager.add(socket);
+            }
+            continue;
+        }
+
+        let (stream, version_peer, features) = match connect_and_handshake(settings, &socket) {
+            Ok(connection) => connection,
+            Err(_) => continue,
+        };
+
+        network_time.add_sample(version_peer.get_timestamp(), Utc::now().timestamp());
+        peer_features.insert(socket, features);
+        peer_heights.record(socket, version_peer.get_start_height().max(0) as u32);
+        streams.push((socket, stream));
     }
     println!(
         "\nConnection has been succesfully established with {} nodes.",
         streams.len()
     );
 
-    if let Some(last) = streams.last() {
+    if peer_heights.save().is_err() {
+        println!("Attempt to save the heights of the peers has failed.");
+    }
+
+    if let Some((_, last)) = streams.last() {
         println!("\nStream to be used: {:?}\n", last)
     }
 
     Ok(streams)
 }
+
+#[cfg(test)]
+mod handshake_test {
+    use super::*;
+    use std::{fs, net::TcpListener, thread};
+
+    #[test]
+    fn test_connect_only_peers_replace_discovery_and_anchors() -> Result<(), NetworkError> {
+        let path = "data/test_connect_only.conf";
+        let mut content = fs::read_to_string("settings/nodo.conf")?;
+        content.push_str("connect_only=127.0.0.1:18444, 127.0.0.2:18444\n");
+        fs::write(path, content)?;
+
+        let settings = Settings::from_file(path);
+        fs::remove_file(path)?;
+        let settings = settings.map_err(|_| NetworkError::HandShake)?;
+
+        let anchors = [SocketAddr::from(([127, 0, 0, 3], 18333))];
+
+        assert_eq!(
+            select_sockets(&settings, &anchors)?,
+            vec![
+                SocketAddr::from(([127, 0, 0, 1], 18444)),
+                SocketAddr::from(([127, 0, 0, 2], 18444)),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_connections_to_the_node_itself_are_dropped() -> Result<(), NetworkError> {
+        let settings =
+            Settings::from_file("settings/nodo.conf").map_err(|_| NetworkError::HandShake)?;
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+
+        let echo = thread::spawn(move || -> Result<(), NetworkError> {
+            let (mut stream, _) = listener.accept()?;
+            let header = MessageHeader::from_bytes(&mut stream)?;
+            let version = Version::from_bytes(header, &mut stream)?;
+            stream.write_all(&version.to_bytes())?;
+            Ok(())
+        });
+
+        assert!(matches!(
+            connect_and_handshake(&settings, &addr),
+            Err(NetworkError::SelfConnection)
+        ));
+        echo.join().map_err(|_| NetworkError::HandShake)?
+    }
+}
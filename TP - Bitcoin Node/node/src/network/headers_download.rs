@@ -1,41 +1,43 @@
-use super::{network_constants::*, network_error::NetworkError};
+use super::{
+    bitcoin_network::Network, network_constants::*, network_error::NetworkError,
+    network_time::NetworkTime, sync_progress::SyncProgress,
+};
 
 use crate::{
-    block_mod::block_header::BlockHeader,
+    block_mod::{
+        block_header::BlockHeader, blockchain::locator_positions, difficulty::next_work_required,
+    },
     messages::{
-        get_headers::GetHeaders,
-        header::MessageHeader,
-        headers::Headers,
-        message_constants::{HEADERS_COMMAND, PING_COMMAND},
-        ping::Ping,
-        pong::Pong,
+        command::Command, get_headers::GetHeaders, header::MessageHeader, headers::Headers,
+        message_constants::MAX_HEADERS_COUNT, ping::Ping, pong::Pong, read_from_bytes::decode_hex,
     },
     settings_mod::settings::Settings,
 };
 
 use std::{
     fs::{self, File, OpenOptions},
-    io::{Read, Write},
-    net::TcpStream,
+    io::{BufReader, Read, Write},
+    net::{SocketAddr, TcpStream},
+    sync::{Arc, Mutex},
 };
 
 /// Handles non-specific messages received from the peer.
 ///
 /// # Arguments
 ///
-/// * `stream` - The TCP stream to the peer.
+/// * `stream` - The connection to the peer, either the TCP stream itself or its `PeerStream`.
 /// * `header` - The message header.
 /// * `settings` - The network settings.
 ///
 /// # Returns
 ///
 /// Result indicating success or failure.
-pub fn handle_other_message(
-    stream: &mut TcpStream,
+pub fn handle_other_message<S: Read + Write>(
+    stream: &mut S,
     header: MessageHeader,
     start_string: Vec<u8>,
 ) -> Result<(), NetworkError> {
-    if header.get_command_name() == PING_COMMAND {
+    if header.get_command() == Some(Command::Ping) {
         let ping = Ping::from_bytes(header, stream).map_err(|_| NetworkError::HeaderDownload)?;
         let pong = Pong::new(start_string, ping.get_nonce());
         stream
@@ -49,12 +51,107 @@ pub fn handle_other_message(
     Ok(())
 }
 
-/// Validates a list of block headers and adds them to the header list if they pass the proof of work.
+/// Decodes the hash of a checkpoint into the byte order used by the block headers.
+///
+/// # Arguments
+///
+/// * `hash` - The hash of the checkpoint, as displayed by block explorers.
+///
+/// # Returns
+///
+/// The hash in internal byte order, or a `NetworkError` if the hash is not valid hex.
+fn checkpoint_hash(hash: &str) -> Result<Vec<u8>, NetworkError> {
+    let mut hash = decode_hex(hash).map_err(|_| NetworkError::HeaderDownload)?;
+    hash.reverse();
+    Ok(hash)
+}
+
+/// Checks that a header matches the checkpoint at its height, if there is one.
+///
+/// # Arguments
+///
+/// * `network` - The network whose checkpoints are checked.
+/// * `height` - The height of the header.
+/// * `header` - The header to check.
+///
+/// # Returns
+///
+/// Result indicating whether the header passes through the checkpoints or not.
+fn check_checkpoint(
+    network: Network,
+    height: u32,
+    header: &BlockHeader,
+) -> Result<(), NetworkError> {
+    for (checkpoint_height, hash) in network.get_checkpoints() {
+        if *checkpoint_height == height && checkpoint_hash(hash)? != header.get_header() {
+            return Err(NetworkError::CheckpointMismatch);
+        }
+    }
+    Ok(())
+}
+
+/// Returns the latest checkpoint of the network as its height and its hash in internal byte
+/// order.
+fn latest_checkpoint(network: Network) -> Result<(u32, Vec<u8>), NetworkError> {
+    let (height, hash) = network
+        .get_checkpoints()
+        .iter()
+        .max_by_key(|(height, _)| *height)
+        .ok_or(NetworkError::HeaderDownload)?;
+    Ok((*height, checkpoint_hash(hash)?))
+}
+
+/// Returns the height of the first header of a downloaded header list.
+///
+/// Lists starting with the genesis block header start at height 0, as long as it is the genesis
+/// block of the network, while lists synced from a checkpoint start right after it.
+///
+/// # Arguments
+///
+/// * `headers` - The downloaded headers.
+/// * `network` - The network the headers belong to.
+///
+/// # Returns
+///
+/// The height of the first header, `NetworkError::GenesisMismatch` if the list starts with the
+/// genesis block of another network, or `NetworkError::CheckpointMismatch` if the list starts
+/// after a block that is not a checkpoint.
+pub fn get_start_height(headers: &[BlockHeader], network: Network) -> Result<u32, NetworkError> {
+    let first = match headers.first() {
+        Some(first) => first,
+        None => return Ok(0),
+    };
+
+    if *first.get_previuos_block_header() == GENESIS_PREVIOUS_BLOCK_HEADER_HASH.to_vec() {
+        if first.get_header() != network.get_genesis_hash() {
+            return Err(NetworkError::GenesisMismatch);
+        }
+        return Ok(0);
+    }
+
+    for (height, hash) in network.get_checkpoints() {
+        if checkpoint_hash(hash)? == *first.get_previuos_block_header() {
+            return Ok(height + 1);
+        }
+    }
+    Err(NetworkError::CheckpointMismatch)
+}
+
+/// Validates a list of block headers and adds them to the header list if they pass the proof of
+/// work, extend the last header of the list, match the checkpoints, have the difficulty required by
+/// the retarget rules and are not too far ahead of the network-adjusted time.
+///
+/// The difficulty is not checked while the headers it depends on have not been downloaded, as
+/// happens right after syncing from a checkpoint.
 ///
 /// # Arguments
 ///
 /// * `headers` - The list of block headers to validate.
 /// * `header_list` - The list to store the valid block headers.
+/// * `previous_hash` - The hash of the block the headers must extend.
+/// * `start_height` - The height of the first header of `header_list`.
+/// * `network` - The network whose checkpoints the headers must match.
+/// * `network_time` - The network-adjusted time.
 /// * `file` - The file to write the valid block headers to.
 ///
 /// # Returns
@@ -63,20 +160,61 @@ pub fn handle_other_message(
 fn validate_headers(
     headers: Vec<BlockHeader>,
     header_list: &mut Vec<BlockHeader>,
+    mut previous_hash: Vec<u8>,
+    start_height: u32,
+    network: Network,
+    network_time: &NetworkTime,
     file: &mut File,
 ) -> Result<(), NetworkError> {
     for h in headers {
-        if h.proof_of_work() {
-            file.write_all(&h.to_bytes())
-                .map_err(|_| NetworkError::HeaderDownload)?;
-            header_list.push(h);
-        } else {
+        if !h.proof_of_work()
+            || *h.get_previuos_block_header() != previous_hash
+            || !network_time.is_header_time_valid(&h)
+        {
             return Err(NetworkError::HeaderDownload);
         }
+        let height = start_height + header_list.len() as u32;
+        check_checkpoint(network, height, &h)?;
+
+        let required = next_work_required(network, height, h.get_time(), |at| {
+            header_list.get(at.checked_sub(start_height)? as usize)
+        });
+        if required.is_some_and(|nbits| nbits != h.get_nbits()) {
+            println!("Header at height {} has the wrong difficulty", height);
+            return Err(NetworkError::BadDifficulty);
+        }
+
+        file.write_all(&h.to_bytes())
+            .map_err(|_| NetworkError::HeaderDownload)?;
+        previous_hash = h.get_header();
+        header_list.push(h);
     }
     Ok(())
 }
 
+/// Builds the block locator of the downloaded headers, so the peer can find the point where its
+/// chain forks from them.
+///
+/// # Arguments
+///
+/// * `header_list` - The downloaded headers.
+/// * `start` - The hash of the block the sync started from, either a checkpoint or the genesis
+///   block, which is the only hash of the locator while no header has been downloaded.
+///
+/// # Returns
+///
+/// The hashes of the locator, from the last downloaded header backwards.
+fn build_locator(header_list: &[BlockHeader], start: &[u8]) -> Vec<Vec<u8>> {
+    if header_list.is_empty() {
+        return vec![start.to_vec()];
+    }
+
+    locator_positions(header_list.len())
+        .into_iter()
+        .map(|position| header_list[header_list.len() - 1 - position].get_header())
+        .collect()
+}
+
 /// Checks if a file is empty.
 ///
 /// # Arguments
@@ -99,30 +237,36 @@ fn is_file_empty(file_path: &str) -> bool {
 /// # Arguments
 ///
 /// * `file` - The file to load the headers from.
+/// * `network` - The network whose genesis block header an empty file starts with.
+/// * `checkpoint_sync` - Whether an empty file should be synced from the latest checkpoint instead
+///   of from the genesis block.
 ///
 /// # Returns
 ///
 /// Returns a vector of block headers if successful, or a `NetworkError` if an error occurs.
-pub fn load_headers(file: &mut File) -> Result<Vec<BlockHeader>, NetworkError> {
+pub fn load_headers(
+    file: &mut File,
+    network: Network,
+    checkpoint_sync: bool,
+) -> Result<Vec<BlockHeader>, NetworkError> {
     let mut block_headers: Vec<BlockHeader> = Vec::new();
 
     if is_file_empty(HEADERS_FILE_PATH) {
-        let genesis = BlockHeader::new(
-            GENESIS_VERSION,
-            GENESIS_PREVIOUS_BLOCK_HEADER_HASH.to_vec(),
-            GENESIS_MERKLE_ROOT_HASH.to_vec(),
-            GENESIS_TIME,
-            GENESIS_NBITS,
-            GENESIS_NONCE,
-        );
+        if checkpoint_sync && !network.get_checkpoints().is_empty() {
+            return Ok(block_headers);
+        }
+
+        let genesis = network.get_genesis_header();
         file.write_all(&genesis.to_bytes())
             .map_err(|_| NetworkError::HeaderDownload)?;
         block_headers.push(genesis);
         return Ok(block_headers);
     }
 
+    let mut reader = BufReader::new(file);
+
     loop {
-        let block_header = match BlockHeader::from_bytes(file) {
+        let block_header = match BlockHeader::from_bytes(&mut reader) {
             Ok(header) => header,
             Err(_) => return Ok(block_headers),
         };
@@ -153,41 +297,58 @@ fn open_headers_file() -> Result<File, NetworkError> {
 /// # Arguments
 ///
 /// * `settings` - The network settings.
-/// * `streams` - The TCP streams to communicate with peers.
+/// * `streams` - The TCP streams to communicate with peers, along with their addresses.
+/// * `network_time` - The network-adjusted time, used to reject headers too far in the future.
+/// * `progress` - The shared sync progress, updated with the height of every batch of headers.
 ///
 /// # Returns
 ///
 /// Returns a vector of block headers if successful, or a `NetworkError` if an error occurs.
 pub fn headers_download(
     settings: &Settings,
-    streams: &mut Vec<TcpStream>,
+    streams: &mut Vec<(SocketAddr, TcpStream)>,
+    network_time: &NetworkTime,
+    progress: &Arc<Mutex<SyncProgress>>,
 ) -> Result<Vec<BlockHeader>, NetworkError> {
     println!("Block header download has begun.");
-    let mut stream = streams.pop().ok_or(NetworkError::HeaderDownload)?;
+    let (_, mut stream) = streams.pop().ok_or(NetworkError::HeaderDownload)?;
 
     // Open the file in read-write mode
     let mut file = open_headers_file()?;
 
-    let mut header_list: Vec<BlockHeader> = load_headers(&mut file)?;
+    let mut header_list: Vec<BlockHeader> = load_headers(
+        &mut file,
+        settings.get_network(),
+        settings.get_checkpoint_sync(),
+    )?;
     let mut count = 0;
 
     println!("{} headers are already downloaded...", header_list.len());
 
+    let (start_height, start) = if header_list.is_empty() {
+        let (height, hash) = latest_checkpoint(settings.get_network())?;
+        println!("Syncing headers from checkpoint at height {}...", height);
+        (height + 1, hash)
+    } else {
+        (
+            get_start_height(&header_list, settings.get_network())?,
+            settings.get_network().get_genesis_hash(),
+        )
+    };
+
     loop {
         count += 2000;
         if count % 10000 == 0 {
             println!("Headers downloaded so far: {}...", count);
         }
 
-        let last_header = header_list
-            .last()
-            .ok_or(NetworkError::HeaderDownload)?
-            .get_header();
+        let locator = build_locator(&header_list, &start);
+        let last_header = locator[0].clone();
 
         let get_headers = GetHeaders::new(
             settings.get_start_string(),
             settings.get_protocol_version(),
-            last_header.clone(),
+            locator,
             STOPPING_HASH.to_vec(),
         );
 
@@ -198,16 +359,34 @@ pub fn headers_download(
         let mut header =
             MessageHeader::from_bytes(&mut stream).map_err(|_| NetworkError::HeaderDownload)?;
 
-        while header.get_command_name() != HEADERS_COMMAND {
+        while header.get_command() != Some(Command::Headers) {
             handle_other_message(&mut stream, header.clone(), settings.get_start_string())?;
             header =
                 MessageHeader::from_bytes(&mut stream).map_err(|_| NetworkError::HeaderDownload)?;
         }
 
-        let headers =
-            Headers::from_bytes(header, &mut stream).map_err(|_| NetworkError::HeaderDownload)?;
+        let payload = header
+            .read_payload(&mut stream)
+            .map_err(|_| NetworkError::HeaderDownload)?;
+        let headers = Headers::from_bytes(header, &mut payload.as_slice())
+            .map_err(|_| NetworkError::HeaderDownload)?;
 
-        validate_headers(headers.get_headers(), &mut header_list, &mut file)?;
+        validate_headers(
+            headers.get_headers(),
+            &mut header_list,
+            last_header,
+            start_height,
+            settings.get_network(),
+            network_time,
+            &mut file,
+        )?;
+
+        progress
+            .lock()
+            .map_err(|_| NetworkError::HeaderDownload)?
+            .set_headers_height(
+                (start_height as usize + header_list.len()).saturating_sub(1) as u32,
+            );
 
         if headers.get_count() != MAX_HEADERS_COUNT {
             break;
@@ -224,8 +403,7 @@ pub fn headers_download(
 
 #[cfg(test)]
 mod test_header_download {
-    use crate::network::headers_download::BlockHeader;
-    use crate::network::network_constants::*;
+    use crate::network::headers_download::*;
     use crate::network::network_error::NetworkError;
     use std::fs::OpenOptions;
     use std::io::prelude::*;
@@ -241,14 +419,7 @@ mod test_header_download {
             .open(file_path)
             .expect("Failed to open the file");
 
-        let genesis = BlockHeader::new(
-            GENESIS_VERSION,
-            GENESIS_PREVIOUS_BLOCK_HEADER_HASH.to_vec(),
-            GENESIS_MERKLE_ROOT_HASH.to_vec(),
-            GENESIS_TIME,
-            GENESIS_NBITS,
-            GENESIS_NONCE,
-        );
+        let genesis = Network::Testnet.get_genesis_header();
 
         file.write_all(&genesis.to_bytes())
             .map_err(|_| NetworkError::HeaderDownload)?;
@@ -262,4 +433,33 @@ mod test_header_download {
 
         Ok(())
     }
+
+    #[test]
+    fn test_start_height_from_checkpoint() -> Result<(), NetworkError> {
+        let (height, hash) = latest_checkpoint(Network::Testnet)?;
+        let header = BlockHeader::new(1, hash, vec![0; 32], 0, 0, 0);
+        let genesis = Network::Testnet.get_genesis_header();
+
+        assert_eq!(get_start_height(&[header], Network::Testnet)?, height + 1);
+        assert_eq!(
+            get_start_height(std::slice::from_ref(&genesis), Network::Testnet)?,
+            0
+        );
+        assert!(matches!(
+            get_start_height(&[genesis], Network::Mainnet),
+            Err(NetworkError::GenesisMismatch)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_header_not_matching_checkpoint_is_rejected() {
+        let header = BlockHeader::new(1, vec![0; 32], vec![0; 32], 0, 0, 0);
+
+        assert!(check_checkpoint(Network::Testnet, 1, &header).is_ok());
+        assert!(matches!(
+            check_checkpoint(Network::Testnet, TESTNET_CHECKPOINTS[0].0, &header),
+            Err(NetworkError::CheckpointMismatch)
+        ));
+    }
 }
@@ -0,0 +1,218 @@
+use super::{
+    broadcasting::listen_to_peer,
+    handshake::connect_and_handshake,
+    network_constants::{HEALTH_CHECK_INTERVAL_SECS, MAX_PEER_LATENCY_MILLIS, USELESS_PEER_SECS},
+    peer_pool::PeerPool,
+    peer_scoring::PeerScores,
+    shutdown::ShutdownCoordinator,
+};
+use crate::{block_mod::chain_state::ChainState, settings_mod::settings::Settings};
+
+use std::{
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// Picks the least healthy of the given peers, if any of them is unhealthy.
+///
+/// Peers that did not announce a block in `USELESS_PEER_SECS` are the first to go, the one that
+/// went the longest without announcing one being the worst. Otherwise, the peer whose last ping
+/// took the longest to be answered is picked, as long as it took more than
+/// `MAX_PEER_LATENCY_MILLIS`.
+///
+/// # Arguments
+///
+/// * `peers` - The address of each peer, along with the seconds elapsed since it last announced a
+///   block and the latency of its last ping, if it answered one.
+fn worst_peer(peers: &[(SocketAddr, u64, Option<Duration>)]) -> Option<SocketAddr> {
+    let useless = peers
+        .iter()
+        .filter(|(_, secs_without_blocks, _)| *secs_without_blocks >= USELESS_PEER_SECS)
+        .max_by_key(|(_, secs_without_blocks, _)| *secs_without_blocks);
+
+    if let Some((addr, _, _)) = useless {
+        return Some(*addr);
+    }
+
+    peers
+        .iter()
+        .filter_map(|(addr, _, latency)| latency.map(|latency| (addr, latency)))
+        .filter(|(_, latency)| latency.as_millis() > MAX_PEER_LATENCY_MILLIS)
+        .max_by_key(|(_, latency)| *latency)
+        .map(|(addr, _)| *addr)
+}
+
+/// Returns the peer of the pool to evict, as long as it is not the only peer left.
+fn peer_to_evict(peer_pool: &PeerPool) -> Option<SocketAddr> {
+    if peer_pool.len() <= 1 {
+        return None;
+    }
+
+    let peers: Vec<(SocketAddr, u64, Option<Duration>)> = peer_pool
+        .get_addrs()
+        .into_iter()
+        .filter_map(|addr| {
+            let secs_without_blocks = peer_pool.get_stats(&addr)?.get_secs_without_blocks();
+            Some((
+                addr,
+                secs_without_blocks,
+                peer_pool.get_pings().get_latency(&addr),
+            ))
+        })
+        .collect();
+
+    worst_peer(&peers)
+}
+
+/// Takes a fresh address from the address manager of the pool, skipping the banned peers.
+fn take_fresh_addr(
+    peer_pool: &Arc<Mutex<PeerPool>>,
+    peer_scores: &Arc<Mutex<PeerScores>>,
+) -> Option<SocketAddr> {
+    loop {
+        let addr = {
+            let mut locked_peer_pool = peer_pool.lock().ok()?;
+            let connected = locked_peer_pool.get_addrs();
+            locked_peer_pool
+                .get_addr_manager_mut()
+                .take_fresh(&connected)?
+        };
+
+        if !peer_scores.lock().ok()?.is_banned(&addr.ip()) {
+            return Some(addr);
+        }
+    }
+}
+
+/// Spawns the thread that keeps the outbound connections of the node healthy.
+///
+/// Every `HEALTH_CHECK_INTERVAL_SECS`, the peers are evaluated by how long they went without
+/// announcing a block and by the latency of their pings. The worst unhealthy peer is evicted once
+/// a connection to a fresh address of the address manager has been established, so the pool never
+/// shrinks because of the eviction. Free slots of the pool are filled the same way. Nodes that only
+/// connect to the peers of a `connect_only` list never replace them. The thread finishes once a
+/// shutdown is requested, after the threads listening to the new peers finish.
+///
+/// # Arguments
+///
+/// * `settings` - The network settings.
+/// * `peer_pool` - The shared pool of connections, holding the address manager.
+/// * `chain_state` - The shared blockchain, UTXO set, mempool and header chain the new peers are
+///   listened to with.
+/// * `peer_scores` - The shared peer scores, used to skip banned peers.
+/// * `shutdown` - The shutdown coordinator of the node.
+pub fn monitor_health(
+    settings: Arc<Settings>,
+    peer_pool: Arc<Mutex<PeerPool>>,
+    chain_state: ChainState,
+    peer_scores: Arc<Mutex<PeerScores>>,
+    shutdown: ShutdownCoordinator,
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        let mut handles = vec![];
+
+        while shutdown.sleep(Duration::from_secs(HEALTH_CHECK_INTERVAL_SECS)) {
+            if !settings.get_connect_only().is_empty() {
+                break;
+            }
+
+            let worst = match peer_pool.lock() {
+                Ok(locked_peer_pool) => {
+                    let worst = peer_to_evict(&locked_peer_pool);
+                    if worst.is_none() && locked_peer_pool.is_full() {
+                        continue;
+                    }
+                    worst
+                }
+                Err(_) => break,
+            };
+
+            let addr = match take_fresh_addr(&peer_pool, &peer_scores) {
+                Some(addr) => addr,
+                None => continue,
+            };
+
+            let (stream, version, features) = match connect_and_handshake(&settings, &addr) {
+                Ok(connection) => connection,
+                Err(_) => continue,
+            };
+
+            let mut locked_peer_pool = match peer_pool.lock() {
+                Ok(locked_peer_pool) => locked_peer_pool,
+                Err(_) => break,
+            };
+
+            if let Some(worst) = worst {
+                if locked_peer_pool.mark_stale(&worst) {
+                    println!("Peer {} has been evicted for being unhealthy.", worst);
+                }
+            }
+
+            if let Err(err) = locked_peer_pool.add_peer(addr, stream) {
+                println!("Attempt to add peer to the pool has failed: {:?}.", err);
+                continue;
+            }
+            locked_peer_pool.set_peer_features(addr, features);
+            locked_peer_pool
+                .get_peer_heights_mut()
+                .record(addr, version.get_start_height().max(0) as u32);
+
+            let writer = locked_peer_pool
+                .iter()
+                .find(|(peer, _)| *peer == addr)
+                .map(|(_, writer)| writer.clone());
+            drop(locked_peer_pool);
+
+            let writer = match writer {
+                Some(writer) => writer,
+                None => continue,
+            };
+
+            if shutdown.register(writer.get_socket()).is_err() {
+                println!("Attempt to register peer connection for shutdown has failed.");
+            }
+
+            println!("Connection has been established with peer {}.", addr);
+            handles.extend(listen_to_peer(
+                addr,
+                &writer,
+                &settings,
+                &peer_pool,
+                &chain_state,
+                &peer_scores,
+                &shutdown,
+            ));
+        }
+
+        for handle in handles {
+            if handle.join().is_err() {
+                println!("Attempt to join broadcast threads has failed.");
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod health_monitor_test {
+    use super::*;
+
+    #[test]
+    fn test_useless_peers_are_evicted_before_slow_ones() {
+        let fast = SocketAddr::from(([127, 0, 0, 1], 18333));
+        let slow = SocketAddr::from(([127, 0, 0, 2], 18333));
+        let useless = SocketAddr::from(([127, 0, 0, 3], 18333));
+
+        let mut peers = vec![
+            (fast, 10, Some(Duration::from_millis(100))),
+            (slow, 10, Some(Duration::from_secs(10))),
+        ];
+        assert_eq!(worst_peer(&peers), Some(slow));
+
+        peers.push((useless, USELESS_PEER_SECS, None));
+        assert_eq!(worst_peer(&peers), Some(useless));
+
+        assert_eq!(worst_peer(&peers[..1]), None);
+    }
+}
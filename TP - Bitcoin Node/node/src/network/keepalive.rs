@@ -0,0 +1,178 @@
+use super::{
+    network_constants::{PING_INTERVAL_SECS, PING_TIMEOUT_SECS},
+    peer_pool::PeerPool,
+    shutdown::ShutdownCoordinator,
+};
+use crate::{messages::ping::Ping, settings_mod::settings::Settings};
+
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// Keeps the pings sent to the peers that were not answered yet, along with the round-trip
+/// latency measured with the last `pong` each peer sent.
+#[derive(Debug, Default)]
+pub struct PingTracker {
+    pending: HashMap<SocketAddr, (u64, Instant)>,
+    latencies: HashMap<SocketAddr, Duration>,
+}
+
+impl PingTracker {
+    /// Creates a tracker without pings.
+    pub fn new() -> PingTracker {
+        PingTracker {
+            pending: HashMap::new(),
+            latencies: HashMap::new(),
+        }
+    }
+
+    /// Records that a ping has just been sent to a peer.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address of the peer.
+    /// * `nonce` - The nonce of the ping, which the peer must send back.
+    pub fn start(&mut self, addr: SocketAddr, nonce: u64) {
+        self.pending.insert(addr, (nonce, Instant::now()));
+    }
+
+    /// Completes the ping sent to a peer with the `pong` it answered.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address of the peer.
+    /// * `nonce` - The nonce of the `pong`.
+    ///
+    /// # Returns
+    ///
+    /// The round-trip latency of the ping, or `None` if no ping with that nonce was sent to the
+    /// peer.
+    pub fn complete(&mut self, addr: &SocketAddr, nonce: u64) -> Option<Duration> {
+        match self.pending.get(addr) {
+            Some((pending_nonce, _)) if *pending_nonce == nonce => {}
+            _ => return None,
+        }
+
+        let (_, sent) = self.pending.remove(addr)?;
+        let latency = sent.elapsed();
+        self.latencies.insert(*addr, latency);
+        Some(latency)
+    }
+
+    /// Returns the peers that did not answer their ping within `timeout`.
+    pub fn timed_out(&self, timeout: Duration) -> Vec<SocketAddr> {
+        self.pending
+            .iter()
+            .filter(|(_, (_, sent))| sent.elapsed() >= timeout)
+            .map(|(addr, _)| *addr)
+            .collect()
+    }
+
+    /// Returns whether a ping sent to the peer is waiting to be answered.
+    pub fn is_pending(&self, addr: &SocketAddr) -> bool {
+        self.pending.contains_key(addr)
+    }
+
+    /// Forgets the pings and latency of a peer, such as when it disconnects.
+    pub fn remove(&mut self, addr: &SocketAddr) {
+        self.pending.remove(addr);
+        self.latencies.remove(addr);
+    }
+
+    pub fn get_latency(&self, addr: &SocketAddr) -> Option<Duration> {
+        self.latencies.get(addr).copied()
+    }
+}
+
+/// Spawns the thread that keeps the connections to the peers alive.
+///
+/// Every `PING_INTERVAL_SECS`, a `ping` with a random nonce is sent to each peer that already
+/// answered its previous one, and the peers that did not answer within `PING_TIMEOUT_SECS` are
+/// marked as stale, so dead connections do not accumulate in the pool. The anchors of the pool are
+/// then picked again from the latencies measured so far. The thread finishes once the pool is
+/// empty or a shutdown is requested.
+///
+/// # Arguments
+///
+/// * `settings` - The node settings.
+/// * `peer_pool` - The shared pool of connections.
+/// * `shutdown` - The shutdown coordinator of the node.
+pub fn keep_alive(
+    settings: Arc<Settings>,
+    peer_pool: Arc<Mutex<PeerPool>>,
+    shutdown: ShutdownCoordinator,
+) -> JoinHandle<()> {
+    thread::spawn(move || loop {
+        if !shutdown.sleep(Duration::from_secs(PING_INTERVAL_SECS)) {
+            return;
+        }
+
+        let mut locked_peer_pool = match peer_pool.lock() {
+            Ok(locked_peer_pool) => locked_peer_pool,
+            Err(_) => return,
+        };
+
+        if locked_peer_pool.is_empty() {
+            return;
+        }
+
+        let timeout = Duration::from_secs(PING_TIMEOUT_SECS);
+        for addr in locked_peer_pool.get_pings().timed_out(timeout) {
+            println!(
+                "Peer {} did not answer a ping in {} seconds, it is now considered stale.",
+                addr, PING_TIMEOUT_SECS
+            );
+            locked_peer_pool.mark_stale(&addr);
+        }
+        locked_peer_pool.update_anchors();
+
+        let peers: Vec<_> = locked_peer_pool
+            .iter()
+            .filter(|(addr, _)| !locked_peer_pool.get_pings().is_pending(addr))
+            .cloned()
+            .collect();
+
+        let mut pings = vec![];
+        for (addr, writer) in peers {
+            let nonce: u64 = rand::random();
+            locked_peer_pool.get_pings_mut().start(addr, nonce);
+            pings.push((addr, writer, nonce));
+        }
+        drop(locked_peer_pool);
+
+        for (addr, writer, nonce) in pings {
+            let ping = Ping::new(settings.get_start_string(), nonce);
+
+            if writer.send(ping.to_bytes()).is_err() {
+                println!("Attempt to send a ping to peer {} has failed.", addr);
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod keepalive_test {
+    use super::*;
+
+    #[test]
+    fn test_pings_are_completed_with_matching_nonce() {
+        let addr = SocketAddr::from(([127, 0, 0, 1], 18333));
+        let mut pings = PingTracker::new();
+        pings.start(addr, 7);
+
+        assert!(pings.is_pending(&addr));
+        assert!(pings.complete(&addr, 8).is_none());
+        assert_eq!(pings.timed_out(Duration::ZERO), vec![addr]);
+        assert!(pings.timed_out(Duration::from_secs(60)).is_empty());
+
+        let latency = pings.complete(&addr, 7);
+        assert!(latency.is_some());
+        assert_eq!(pings.get_latency(&addr), latency);
+        assert!(!pings.is_pending(&addr));
+        assert!(pings.timed_out(Duration::ZERO).is_empty());
+    }
+}
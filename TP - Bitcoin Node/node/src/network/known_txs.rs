@@ -0,0 +1,90 @@
+use std::collections::{HashSet, VecDeque};
+
+/// Represents the transactions a peer is known to have, either because it announced or sent them
+/// to the node, or because the node sent them to it.
+///
+/// The set is bounded: once it holds `capacity` transactions, the oldest ones are forgotten first.
+#[derive(Debug)]
+pub struct KnownTxs {
+    ids: HashSet<Vec<u8>>,
+    order: VecDeque<Vec<u8>>,
+    capacity: usize,
+}
+
+impl KnownTxs {
+    /// Creates an empty set of known transactions.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The maximum amount of transactions remembered.
+    pub fn new(capacity: usize) -> KnownTxs {
+        KnownTxs {
+            ids: HashSet::new(),
+            order: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    /// Marks a transaction as known, forgetting the oldest one if the set is full.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx_id` - The id of the transaction.
+    pub fn insert(&mut self, tx_id: Vec<u8>) {
+        if self.capacity == 0 || self.ids.contains(&tx_id) {
+            return;
+        }
+
+        if self.order.len() >= self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.ids.remove(&oldest);
+            }
+        }
+
+        self.ids.insert(tx_id.clone());
+        self.order.push_back(tx_id);
+    }
+
+    /// Forgets a transaction, so it is considered unknown again.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx_id` - The id of the transaction.
+    pub fn remove(&mut self, tx_id: &Vec<u8>) {
+        if self.ids.remove(tx_id) {
+            self.order.retain(|id| id != tx_id);
+        }
+    }
+
+    pub fn contains(&self, tx_id: &Vec<u8>) -> bool {
+        self.ids.contains(tx_id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod known_txs_test {
+    use super::*;
+
+    #[test]
+    fn test_known_txs_forgets_oldest() {
+        let mut known_txs = KnownTxs::new(2);
+
+        known_txs.insert(vec![1]);
+        known_txs.insert(vec![2]);
+        known_txs.insert(vec![2]);
+        known_txs.insert(vec![3]);
+
+        assert_eq!(known_txs.len(), 2);
+        assert!(!known_txs.contains(&vec![1]));
+        assert!(known_txs.contains(&vec![2]));
+        assert!(known_txs.contains(&vec![3]));
+    }
+}
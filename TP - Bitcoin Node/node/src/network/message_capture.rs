@@ -0,0 +1,245 @@
+use super::network_error::NetworkError;
+use crate::messages::{
+    header::MessageHeader,
+    read_from_bytes::{decode_hex, encode_hex},
+};
+
+use chrono::Utc;
+use std::{
+    fs::{self, File, OpenOptions},
+    io::Write,
+    net::SocketAddr,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+/// Whether a captured message was received from or sent to a peer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Inbound,
+    Outbound,
+}
+
+impl Direction {
+    fn name(&self) -> &'static str {
+        match self {
+            Direction::Inbound => "in",
+            Direction::Outbound => "out",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Direction> {
+        match name {
+            "in" => Some(Direction::Inbound),
+            "out" => Some(Direction::Outbound),
+            _ => None,
+        }
+    }
+}
+
+/// A message exchanged with a peer, as recorded in a capture file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapturedMessage {
+    timestamp: i64,
+    direction: Direction,
+    peer: SocketAddr,
+    command: String,
+    payload: Vec<u8>,
+}
+
+impl CapturedMessage {
+    /// Returns the time the message was captured at, in milliseconds since the Unix epoch.
+    pub fn get_timestamp(&self) -> i64 {
+        self.timestamp
+    }
+    pub fn get_direction(&self) -> Direction {
+        self.direction
+    }
+    pub fn get_peer(&self) -> SocketAddr {
+        self.peer
+    }
+    pub fn get_command(&self) -> &String {
+        &self.command
+    }
+    pub fn get_size(&self) -> usize {
+        self.payload.len()
+    }
+    pub fn get_payload(&self) -> &Vec<u8> {
+        &self.payload
+    }
+}
+
+/// Records every message exchanged with the peers to a capture file, one message per line: the
+/// timestamp in milliseconds, the direction, the peer, the command, the size of the payload and the
+/// payload in hex, separated by tabs.
+///
+/// The capture is shared by every connection of the pool, so its clones append to the same file.
+#[derive(Debug, Clone)]
+pub struct MessageCapture {
+    file: Arc<Mutex<File>>,
+}
+
+impl MessageCapture {
+    /// Opens the capture file, appending to it if it already exists.
+    ///
+    /// # Errors
+    ///
+    /// Returns `NetworkError::Capture` if the file cannot be opened.
+    pub fn open(path: &str) -> Result<MessageCapture, NetworkError> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|_| NetworkError::Capture)?;
+
+        Ok(MessageCapture {
+            file: Arc::new(Mutex::new(file)),
+        })
+    }
+
+    /// Records a message received from a peer.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer` - The address of the peer.
+    /// * `header` - The header of the message.
+    /// * `payload` - The payload of the message.
+    pub fn record_received(&self, peer: &SocketAddr, header: &MessageHeader, payload: &[u8]) {
+        self.record(Direction::Inbound, peer, header.get_command_name(), payload);
+    }
+
+    /// Records the messages sent to a peer, which may be queued several at once.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer` - The address of the peer.
+    /// * `bytes` - The messages sent, one after the other.
+    pub fn record_sent(&self, peer: &SocketAddr, bytes: &[u8]) {
+        let mut stream = bytes;
+
+        while !stream.is_empty() {
+            let header = match MessageHeader::from_bytes(&mut stream) {
+                Ok(header) => header,
+                Err(_) => {
+                    self.record(Direction::Outbound, peer, "unknown", stream);
+                    return;
+                }
+            };
+
+            let size = (header.get_payload_size() as usize).min(stream.len());
+            let (payload, rest) = stream.split_at(size);
+            self.record(
+                Direction::Outbound,
+                peer,
+                header.get_command_name(),
+                payload,
+            );
+            stream = rest;
+        }
+    }
+
+    fn record(&self, direction: Direction, peer: &SocketAddr, command: &str, payload: &[u8]) {
+        let line = format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\n",
+            Utc::now().timestamp_millis(),
+            direction.name(),
+            peer,
+            command,
+            payload.len(),
+            encode_hex(payload).unwrap_or_default()
+        );
+
+        let recorded = match self.file.lock() {
+            Ok(mut file) => file.write_all(line.as_bytes()).is_ok(),
+            Err(_) => false,
+        };
+        if !recorded {
+            println!("Attempt to capture a message of peer {} has failed.", peer);
+        }
+    }
+}
+
+/// Reads the messages recorded in a capture file, in the order they were captured.
+///
+/// # Errors
+///
+/// Returns `NetworkError::Capture` if the file cannot be read or holds a malformed line.
+pub fn read_capture(path: &str) -> Result<Vec<CapturedMessage>, NetworkError> {
+    let content = fs::read_to_string(path).map_err(|_| NetworkError::Capture)?;
+
+    content
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(parse_line)
+        .collect()
+}
+
+fn parse_line(line: &str) -> Result<CapturedMessage, NetworkError> {
+    let fields: Vec<&str> = line.split('\t').collect();
+
+    if fields.len() != 6 {
+        return Err(NetworkError::Capture);
+    }
+
+    let payload = decode_hex(fields[5]).map_err(|_| NetworkError::Capture)?;
+    if fields[4].parse::<usize>().ok() != Some(payload.len()) {
+        return Err(NetworkError::Capture);
+    }
+
+    Ok(CapturedMessage {
+        timestamp: fields[0].parse().map_err(|_| NetworkError::Capture)?,
+        direction: Direction::from_name(fields[1]).ok_or(NetworkError::Capture)?,
+        peer: SocketAddr::from_str(fields[2]).map_err(|_| NetworkError::Capture)?,
+        command: fields[3].to_string(),
+        payload,
+    })
+}
+
+#[cfg(test)]
+mod message_capture_test {
+    use super::*;
+    use crate::messages::{ping::Ping, pong::Pong};
+
+    #[test]
+    fn test_messages_sent_at_once_are_captured_one_by_one() -> Result<(), NetworkError> {
+        let path = "data/test_capture.txt";
+        let _ = fs::remove_file(path);
+        let peer = SocketAddr::from_str("10.0.0.1:18333").map_err(|_| NetworkError::Capture)?;
+        let start_string = vec![0x0b, 0x11, 0x09, 0x07];
+        let capture = MessageCapture::open(path)?;
+
+        let mut sent = Ping::new(start_string.clone(), 7).to_bytes();
+        sent.extend(Pong::new(start_string.clone(), 8).to_bytes());
+        capture.record_sent(&peer, &sent);
+
+        let pong = Pong::new(start_string, 7).to_bytes();
+        let header =
+            MessageHeader::from_bytes(&mut pong.as_slice()).map_err(|_| NetworkError::Capture)?;
+        capture.record_received(&peer, &header, &pong[24..]);
+
+        let captured = read_capture(path)?;
+        let _ = fs::remove_file(path);
+
+        let summary: Vec<(Direction, &str, usize)> = captured
+            .iter()
+            .map(|message| {
+                (
+                    message.get_direction(),
+                    message.get_command().as_str(),
+                    message.get_size(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (Direction::Outbound, "ping", 8),
+                (Direction::Outbound, "pong", 8),
+                (Direction::Inbound, "pong", 8),
+            ]
+        );
+        assert_eq!(captured[0].get_payload(), &7u64.to_le_bytes().to_vec());
+        assert!(captured.iter().all(|message| message.get_peer() == peer));
+        Ok(())
+    }
+}
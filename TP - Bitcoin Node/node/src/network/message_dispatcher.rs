@@ -0,0 +1,116 @@
+use super::{network_error::NetworkError, peer_pool::PeerPool, peer_stream::PeerStream};
+use crate::{
+    block_mod::chain_state::ChainState,
+    messages::{command::Command, header::MessageHeader, message::Message},
+    settings_mod::settings::Settings,
+};
+
+use std::{
+    collections::{HashMap, HashSet},
+    io::Read,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+/// Handles a message received from a peer, whose payload follows its header in the stream.
+///
+/// Handlers receive the header of the message, the network settings, the stream of the peer, the
+/// shared chain state, the hashes of the data requested to the peer, and the shared peer pool
+/// along with the address of the peer the message comes from.
+pub type MessageHandler = fn(
+    MessageHeader,
+    &Arc<Settings>,
+    &mut PeerStream,
+    &ChainState,
+    &mut HashSet<Vec<u8>>,
+    (&Arc<Mutex<PeerPool>>, &SocketAddr),
+) -> Result<(), NetworkError>;
+
+/// Maps the commands of the messages the node understands to their handlers, so new messages are
+/// supported by registering a handler instead of touching the loop that listens to the peers.
+///
+/// Messages whose command has no handler are skipped.
+#[derive(Debug, Default)]
+pub struct MessageDispatcher {
+    handlers: HashMap<Command, MessageHandler>,
+}
+
+impl MessageDispatcher {
+    /// Creates a dispatcher without handlers.
+    pub fn new() -> MessageDispatcher {
+        MessageDispatcher::default()
+    }
+
+    /// Registers the handler of a command, replacing the previous one.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The command of the messages to handle.
+    /// * `handler` - The handler of the messages.
+    pub fn register(&mut self, command: Command, handler: MessageHandler) {
+        self.handlers.insert(command, handler);
+    }
+
+    /// Registers the handler of the messages of the given type.
+    pub fn register_message<M: Message>(&mut self, handler: MessageHandler) {
+        self.register(M::COMMAND, handler);
+    }
+
+    pub fn is_registered(&self, command: Command) -> bool {
+        self.handlers.contains_key(&command)
+    }
+
+    /// Hands a message over to the handler of its command, or skips its payload if there is none.
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - The header of the message.
+    /// * `settings` - The network settings.
+    /// * `stream` - The stream of the peer, right after the header.
+    /// * `chain_state` - The shared blockchain, UTXO set, mempool and header chain.
+    /// * `requested` - The hashes of the data requested to the peer.
+    /// * `peer` - The shared peer pool and the address of the peer the message comes from.
+    ///
+    /// # Returns
+    ///
+    /// The result of the handler, or a `NetworkError` if the payload of an unknown message cannot
+    /// be skipped.
+    pub fn dispatch(
+        &self,
+        header: MessageHeader,
+        settings: &Arc<Settings>,
+        stream: &mut PeerStream,
+        chain_state: &ChainState,
+        requested: &mut HashSet<Vec<u8>>,
+        peer: (&Arc<Mutex<PeerPool>>, &SocketAddr),
+    ) -> Result<(), NetworkError> {
+        match header
+            .get_command()
+            .and_then(|command| self.handlers.get(&command))
+        {
+            Some(handler) => handler(header, settings, stream, chain_state, requested, peer),
+            None => stream
+                .read_exact(&mut vec![0u8; header.get_payload_size() as usize])
+                .map_err(|_| NetworkError::Broadcasting),
+        }
+    }
+}
+
+#[cfg(test)]
+mod message_dispatcher_test {
+    use super::*;
+    use crate::messages::ping::Ping;
+
+    #[test]
+    fn test_handlers_are_registered_by_command() {
+        let mut dispatcher = MessageDispatcher::new();
+        dispatcher.register_message::<Ping>(|_, _, _, _, _, _| Ok(()));
+        dispatcher.register(Command::Tx, |_, _, _, _, _, _| {
+            Err(NetworkError::Broadcasting)
+        });
+
+        assert!(dispatcher.is_registered(Command::Ping));
+        assert!(dispatcher.is_registered(Command::Tx));
+        assert!(!dispatcher.is_registered(Command::Pong));
+    }
+}
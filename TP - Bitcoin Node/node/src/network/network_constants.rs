@@ -1,9 +1,18 @@
 //hand shake
 pub const DURATION_TIMEOUT_MILLIS: u64 = 1000;
-pub const VERSION_ACEPTED: i32 = 70016;
-pub const SERVICES_ACEPTED: u64 = 1033;
+pub const MIN_PEER_PROTO_VERSION: i32 = 70001;
+pub const ADDR_TIME_VERSION: i32 = 31402;
+pub const SEND_HEADERS_VERSION: i32 = 70012;
+pub const FEE_FILTER_VERSION: i32 = 70013;
+pub const SHORT_IDS_BLOCKS_VERSION: i32 = 70014;
+pub const WTXID_RELAY_VERSION: i32 = 70016;
+pub const NODE_NETWORK: u64 = 1;
+pub const NODE_BLOOM: u64 = 1 << 2;
+pub const NODE_WITNESS: u64 = 1 << 3;
+pub const NODE_NETWORK_LIMITED: u64 = 1 << 10;
 pub const DIG_COMMAND: &str = "dig";
 pub const SHORT_ARG: &str = "+short";
+pub const DNS_RECORD_TYPES: [&str; 2] = ["A", "AAAA"];
 
 //headers download
 pub const GENESIS_VERSION: i32 = 1;
@@ -12,18 +21,185 @@ pub const GENESIS_MERKLE_ROOT_HASH: [u8; 32] = [
     59, 163, 237, 253, 122, 123, 18, 178, 122, 199, 44, 62, 103, 118, 143, 97, 127, 200, 27, 195,
     136, 138, 81, 50, 58, 159, 184, 170, 75, 30, 94, 74,
 ];
-pub const GENESIS_TIME: u32 = 1296688602;
-pub const GENESIS_NBITS: u32 = 486604799;
-pub const GENESIS_NONCE: u32 = 414098458;
 pub const STOPPING_HASH: [u8; 32] = [0; 32];
 
+//networks
+pub const MAINNET: &str = "mainnet";
+pub const TESTNET: &str = "testnet";
+pub const REGTEST: &str = "regtest";
+pub const SIGNET: &str = "signet";
+pub const MAINNET_START_STRING: [u8; 4] = [0xf9, 0xbe, 0xb4, 0xd9];
+pub const TESTNET_START_STRING: [u8; 4] = [0x0b, 0x11, 0x09, 0x07];
+pub const REGTEST_START_STRING: [u8; 4] = [0xfa, 0xbf, 0xb5, 0xda];
+pub const SIGNET_START_STRING: [u8; 4] = [0x0a, 0x03, 0xcf, 0x40];
+pub const MAINNET_PORT: u16 = 8333;
+pub const TESTNET_PORT: u16 = 18333;
+pub const REGTEST_PORT: u16 = 18444;
+pub const SIGNET_PORT: u16 = 38333;
+pub const MAINNET_DNS_SEED: &str = "seed.bitcoin.sipa.be";
+pub const TESTNET_DNS_SEED: &str = "seed.testnet.bitcoin.sprovoost.nl";
+pub const REGTEST_DNS_SEED: &str = "localhost";
+pub const SIGNET_DNS_SEED: &str = "seed.signet.bitcoin.sprovoost.nl";
+pub const MAINNET_BECH32_HRP: &str = "bc";
+pub const TESTNET_BECH32_HRP: &str = "tb";
+pub const REGTEST_BECH32_HRP: &str = "bcrt";
+pub const SIGNET_BECH32_HRP: &str = "tb";
+pub const MAINNET_P2PKH_PREFIX: u8 = 0x00;
+pub const MAINNET_P2SH_PREFIX: u8 = 0x05;
+pub const TESTNET_P2PKH_PREFIX: u8 = 0x6f;
+pub const TESTNET_P2SH_PREFIX: u8 = 0xc4;
+pub const MAINNET_GENESIS_TIME: u32 = 1231006505;
+pub const MAINNET_GENESIS_NBITS: u32 = 0x1d00ffff;
+pub const MAINNET_GENESIS_NONCE: u32 = 2083236893;
+pub const TESTNET_GENESIS_TIME: u32 = 1296688602;
+pub const TESTNET_GENESIS_NBITS: u32 = 0x1d00ffff;
+pub const TESTNET_GENESIS_NONCE: u32 = 414098458;
+pub const REGTEST_GENESIS_TIME: u32 = 1296688602;
+pub const REGTEST_GENESIS_NBITS: u32 = 0x207fffff;
+pub const REGTEST_GENESIS_NONCE: u32 = 2;
+pub const SIGNET_GENESIS_TIME: u32 = 1598918400;
+pub const SIGNET_GENESIS_NBITS: u32 = 0x1e0377ae;
+pub const SIGNET_GENESIS_NONCE: u32 = 52613770;
+
+//genesis block hashes (as displayed by block explorers)
+pub const MAINNET_GENESIS_HASH: &str =
+    "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f";
+pub const TESTNET_GENESIS_HASH: &str =
+    "000000000933ea01ad0ee984209779baaec3ced90fa3f408719526f8d77f4943";
+pub const REGTEST_GENESIS_HASH: &str =
+    "0f9188f13cb7b2c71f2a335e3a4fc328bf5beb436012afca590b1a11466e2206";
+pub const SIGNET_GENESIS_HASH: &str =
+    "00000008819873e925422c1ff0f99f7cc9bbb232af63a077a480a3633bee1ef6";
+
+//checkpoints (height, block hash as displayed by block explorers)
+pub const TESTNET_CHECKPOINTS: [(u32, &str); 6] = [
+    (
+        546,
+        "000000002a936ca763904c3c35fce2f3556c559c0214345d31b1bcebf76acb70",
+    ),
+    (
+        21111,
+        "0000000023b3a96d3484e5abb3755c413e7d41500f8e2a5c3f0dd01299cd8ef8",
+    ),
+    (
+        330776,
+        "000000002104c8c45e99a8853285a3b592602a3ccde2b832481da85e9e4ba182",
+    ),
+    (
+        581885,
+        "00000000007f6655f22f98e72ed80d8b06dc761d5da09df0fa1dc4be4f861eb6",
+    ),
+    (
+        770112,
+        "00000000025e930139bac5c6c31a403776da130831ab85be56578f3fa75369bb",
+    ),
+    (
+        834624,
+        "00000000002b980fcd729daaa248fd9316a5200e9b367f4ff2c42453e84201ca",
+    ),
+];
+
+//socks5 proxy
+pub const SOCKS_TIMEOUT_SECS: u64 = 30;
+pub const SOCKS5_VERSION: u8 = 5;
+pub const SOCKS5_NO_AUTH: u8 = 0;
+pub const SOCKS5_CONNECT: u8 = 1;
+pub const SOCKS5_RESOLVE: u8 = 0xf0;
+pub const SOCKS5_SUCCEEDED: u8 = 0;
+pub const SOCKS5_ADDR_IPV4: u8 = 1;
+pub const SOCKS5_ADDR_DOMAIN: u8 = 3;
+pub const SOCKS5_ADDR_IPV6: u8 = 4;
+pub const PROXY_RESOLVE_ATTEMPTS: usize = 16;
+
 //block download
+pub const READ_BUFFER_SIZE: usize = 64 * 1024;
 pub const MAX_BLOCKS_GET_DATA: usize = 50000;
-pub const MAX_HEADERS_COUNT: u64 = 2000;
 pub const MSG_BLOCK_DATA_TYPE: u32 = 2;
 pub const MSG_TX_DATA_TYPE: u32 = 1;
+pub const BLOCK_BATCH_SIZE: usize = 100;
+pub const BLOCK_REQUEST_TIMEOUT_SECS: u64 = 30;
+pub const WORK_QUEUE_POLL_MILLIS: u64 = 100;
+pub const MAX_KNOWN_TXS_PER_PEER: usize = 5000;
+pub const MSG_FILTERED_BLOCK_DATA_TYPE: u32 = 3;
+pub const MSG_WTX_DATA_TYPE: u32 = 5;
+pub const UTXO_FLUSH_INTERVAL: usize = 1000;
+
+//bloom filters
+pub const BLOOM_SEED_MULTIPLIER: u32 = 0xfba4c795;
+pub const BLOOM_UPDATE_ALL: u8 = 1;
+pub const BLOOM_UPDATE_P2PUBKEY_ONLY: u8 = 2;
+pub const OP_PUSHDATA1: u8 = 0x4c;
+pub const OP_PUSHDATA2: u8 = 0x4d;
+pub const OP_PUSHDATA4: u8 = 0x4e;
+pub const OP_CHECKSIG: u8 = 0xac;
+pub const OP_CHECKMULTISIG: u8 = 0xae;
+
+pub const MIN_TIME_SAMPLES: usize = 5;
+pub const MAX_TIME_SAMPLES: usize = 200;
+pub const TIME_OFFSET_WARNING_SECS: i64 = 5 * 60;
+pub const MAX_TIME_ADJUSTMENT_SECS: i64 = 70 * 60;
+pub const MAX_FUTURE_BLOCK_TIME_SECS: i64 = 2 * 60 * 60;
 
 pub const DATE_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%:z";
 pub const DATE_LIMIT: &str = "2023-06-25T00:00:00-00:00";
 
+pub const DATA_DIRECTORY: &str = "data";
 pub const HEADERS_FILE_PATH: &str = "data/headers.bin";
+pub const BLOCKS_FILE_PATH: &str = "data/blocks.bin";
+pub const UTXO_FILE_PATH: &str = "data/utxo.bin";
+pub const UNDO_FILE_PATH: &str = "data/undo.bin";
+pub const INVALID_BLOCKS_FILE_PATH: &str = "data/invalid_blocks.txt";
+
+//utxo snapshots
+pub const SNAPSHOT_MAGIC: [u8; 5] = *b"utxo\xff";
+pub const MAINNET_SNAPSHOTS: [(u32, &str); 0] = [];
+pub const TESTNET_SNAPSHOTS: [(u32, &str); 0] = [];
+pub const REGTEST_SNAPSHOTS: [(u32, &str); 0] = [];
+pub const SIGNET_SNAPSHOTS: [(u32, &str); 0] = [];
+
+//peer scoring
+pub const BANNED_PEERS_FILE_PATH: &str = "data/banned_peers.txt";
+pub const BAN_THRESHOLD: u32 = 100;
+pub const INVALID_BLOCK_PENALTY: u32 = 100;
+pub const MALFORMED_MESSAGE_PENALTY: u32 = 20;
+pub const UNSOLICITED_DATA_PENALTY: u32 = 10;
+pub const WRONG_NETWORK_PENALTY: u32 = 100;
+
+//sync stall detection
+pub const PEER_HEIGHTS_FILE_PATH: &str = "data/peer_heights.txt";
+pub const STALL_HEIGHT_GAP: u32 = 144;
+pub const STALL_CHECK_INTERVAL_SECS: u64 = 60;
+
+//anchor connections
+pub const ANCHORS_FILE_PATH: &str = "data/anchors.txt";
+pub const MAX_ANCHORS: usize = 2;
+
+//keepalive pings
+pub const PING_INTERVAL_SECS: u64 = 2 * 60;
+pub const PING_TIMEOUT_SECS: u64 = 20 * 60;
+
+//address manager
+pub const MAX_ADDR_MANAGER_SIZE: usize = 1000;
+
+//connection health
+pub const HEALTH_CHECK_INTERVAL_SECS: u64 = 5 * 60;
+pub const MAX_PEER_LATENCY_MILLIS: u128 = 5000;
+pub const USELESS_PEER_SECS: u64 = 30 * 60;
+
+//shutdown
+pub const SHUTDOWN_POLL_MILLIS: u64 = 200;
+pub const WALLET_ACCEPT_POLL_MILLIS: u64 = 200;
+
+//wallet protocol
+pub const WALLET_PROTOCOL_VERSION: u32 = 3;
+pub const MAX_WALLET_FRAME_SIZE: u32 = 32 * 1024 * 1024;
+pub const MAX_WALLET_AUTH_TOKEN_SIZE: u64 = 256;
+
+//wallet transactions rebroadcast
+pub const REBROADCAST_EXPIRY_SECS: u64 = 14 * 24 * 60 * 60;
+
+//wallet events
+pub const WALLET_EVENTS_CAPACITY: usize = 1000;
+
+//wallet light mode
+pub const MAX_FILTERS_PER_MESSAGE: usize = 1000;
@@ -1,16 +1,39 @@
 use super::super::messages::message_error::MessageError;
-use std::{
-    net::TcpStream,
-    string::FromUtf8Error,
-    sync::{MutexGuard, PoisonError},
-};
+use std::string::FromUtf8Error;
 
 #[derive(Debug)]
 pub enum NetworkError {
     HandShake,
+    SelfConnection,
     HeaderDownload,
     BlockDownload,
     Broadcasting,
+    InvalidBlock,
+    MalformedMessage,
+    WrongNetwork,
+    UnsolicitedData,
+    PeerPoolFull,
+    CheckpointMismatch,
+    GenesisMismatch,
+    BadDifficulty,
+    StalePeer,
+    SignalHandler,
+    Mining,
+    Capture,
+    InvalidSnapshot,
+}
+
+impl NetworkError {
+    /// Classifies an error found while reading a message from a peer.
+    ///
+    /// A peer that did not send anything before the read timeout expired is stale, while any other
+    /// error means the peer sent a malformed message.
+    pub fn from_read_error(err: MessageError) -> NetworkError {
+        match err {
+            MessageError::Timeout => NetworkError::StalePeer,
+            _ => NetworkError::MalformedMessage,
+        }
+    }
 }
 
 impl From<std::io::Error> for NetworkError {
@@ -26,13 +49,10 @@ impl From<FromUtf8Error> for NetworkError {
 }
 
 impl From<MessageError> for NetworkError {
-    fn from(_: MessageError) -> NetworkError {
-        NetworkError::HandShake
-    }
-}
-
-impl From<PoisonError<MutexGuard<'_, TcpStream>>> for NetworkError {
-    fn from(_: PoisonError<MutexGuard<'_, TcpStream>>) -> NetworkError {
-        NetworkError::Broadcasting
+    fn from(err: MessageError) -> NetworkError {
+        match err {
+            MessageError::Timeout => NetworkError::StalePeer,
+            _ => NetworkError::HandShake,
+        }
     }
 }
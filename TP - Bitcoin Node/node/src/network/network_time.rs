@@ -0,0 +1,132 @@
+use super::network_constants::{
+    MAX_FUTURE_BLOCK_TIME_SECS, MAX_TIME_ADJUSTMENT_SECS, MAX_TIME_SAMPLES, MIN_TIME_SAMPLES,
+    TIME_OFFSET_WARNING_SECS,
+};
+use crate::block_mod::block_header::BlockHeader;
+use chrono::Utc;
+
+/// Keeps the offsets between the clocks of the peers and the local clock, taken from the
+/// timestamps of their version messages, and computes the network-adjusted time from them.
+///
+/// As in Bitcoin Core, the adjusted time is the local time plus the median of the offsets, as long
+/// as there are enough samples and the median is not larger than `MAX_TIME_ADJUSTMENT_SECS`.
+#[derive(Debug, Default)]
+pub struct NetworkTime {
+    offsets: Vec<i64>,
+    offset: i64,
+}
+
+impl NetworkTime {
+    pub fn new() -> NetworkTime {
+        NetworkTime::default()
+    }
+
+    /// Adds the offset between the clock of a peer and the local clock, and updates the offset of
+    /// the network-adjusted time.
+    ///
+    /// A warning is printed whenever the median offset exceeds `TIME_OFFSET_WARNING_SECS`, since
+    /// it likely means the local clock is wrong. The timestamp of the peer is not trusted, so the
+    /// offset saturates instead of overflowing.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer_time` - The timestamp of the version message of the peer.
+    /// * `local_time` - The local timestamp when the version message was received.
+    pub fn add_sample(&mut self, peer_time: i64, local_time: i64) {
+        if self.offsets.len() >= MAX_TIME_SAMPLES {
+            return;
+        }
+        self.offsets.push(peer_time.saturating_sub(local_time));
+
+        if self.offsets.len() < MIN_TIME_SAMPLES {
+            return;
+        }
+
+        let mut sorted = self.offsets.clone();
+        sorted.sort();
+        let median = sorted[sorted.len() / 2];
+
+        if median.saturating_abs() > TIME_OFFSET_WARNING_SECS {
+            println!(
+                "\n********************************************************************\n\
+                WARNING: the clocks of the peers differ from the local clock by {} seconds.\n\
+                Please check that your computer's date and time are correct.\n\
+                ********************************************************************\n",
+                median
+            );
+        }
+
+        self.offset = if median.saturating_abs() <= MAX_TIME_ADJUSTMENT_SECS {
+            median
+        } else {
+            0
+        };
+    }
+
+    pub fn get_offset(&self) -> i64 {
+        self.offset
+    }
+
+    /// Returns the current network-adjusted time.
+    pub fn get_adjusted_time(&self) -> i64 {
+        Utc::now().timestamp() + self.offset
+    }
+
+    /// Returns whether the timestamp of a header is not too far ahead of the network-adjusted time.
+    pub fn is_header_time_valid(&self, header: &BlockHeader) -> bool {
+        header.get_time() as i64 <= self.get_adjusted_time() + MAX_FUTURE_BLOCK_TIME_SECS
+    }
+}
+
+#[cfg(test)]
+mod network_time_test {
+    use super::*;
+
+    #[test]
+    fn test_offset_is_the_median_of_the_samples() {
+        let mut network_time = NetworkTime::new();
+
+        for offset in [10, -5, 30, 20] {
+            network_time.add_sample(1000 + offset, 1000);
+        }
+        assert_eq!(network_time.get_offset(), 0);
+
+        network_time.add_sample(1015, 1000);
+        assert_eq!(network_time.get_offset(), 15);
+    }
+
+    #[test]
+    fn test_large_offsets_are_not_applied() {
+        let mut network_time = NetworkTime::new();
+
+        for _ in 0..MIN_TIME_SAMPLES {
+            network_time.add_sample(MAX_TIME_ADJUSTMENT_SECS + 1, 0);
+        }
+
+        assert_eq!(network_time.get_offset(), 0);
+    }
+
+    #[test]
+    fn test_extreme_peer_times_do_not_overflow() {
+        let mut network_time = NetworkTime::new();
+
+        for _ in 0..MIN_TIME_SAMPLES {
+            network_time.add_sample(i64::MIN, 1000);
+        }
+        assert_eq!(network_time.get_offset(), 0);
+
+        network_time.add_sample(i64::MAX, -1000);
+        assert_eq!(network_time.get_offset(), 0);
+    }
+
+    #[test]
+    fn test_header_too_far_in_the_future() {
+        let network_time = NetworkTime::new();
+        let now = Utc::now().timestamp() as u32;
+        let header = BlockHeader::new(1, vec![0; 32], vec![0; 32], now, 0, 0);
+        let future_header = BlockHeader::new(1, vec![0; 32], vec![0; 32], now + 3 * 3600, 0, 0);
+
+        assert!(network_time.is_header_time_valid(&header));
+        assert!(!network_time.is_header_time_valid(&future_header));
+    }
+}
@@ -0,0 +1,151 @@
+use super::network_constants::{
+    ADDR_TIME_VERSION, FEE_FILTER_VERSION, MIN_PEER_PROTO_VERSION, NODE_NETWORK, NODE_WITNESS,
+    SEND_HEADERS_VERSION, SHORT_IDS_BLOCKS_VERSION, WTXID_RELAY_VERSION,
+};
+use crate::messages::version::Version;
+
+/// Represents what a peer supports, according to the version message it sent during the
+/// handshake: the protocol version negotiated with it, the lowest of both versions, the services
+/// it advertised, and whether it agreed to announce transactions by wtxid.
+///
+/// Features are only used with the peers that advertise them, instead of assuming every peer
+/// supports everything the node does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PeerFeatures {
+    version: i32,
+    services: u64,
+    wtxid_relay: bool,
+}
+
+impl PeerFeatures {
+    /// Creates the features of a peer from the version message it sent.
+    ///
+    /// # Arguments
+    ///
+    /// * `protocol_version` - The protocol version of the node.
+    /// * `version` - The version message of the peer.
+    pub fn new(protocol_version: i32, version: &Version) -> PeerFeatures {
+        PeerFeatures {
+            version: protocol_version.min(version.get_version()),
+            services: version.get_services(),
+            wtxid_relay: false,
+        }
+    }
+
+    /// Returns whether the node can work with the peer: it must speak at least
+    /// `MIN_PEER_PROTO_VERSION` and serve the whole blockchain.
+    pub fn is_supported(&self) -> bool {
+        self.version >= MIN_PEER_PROTO_VERSION && self.services & NODE_NETWORK != 0
+    }
+
+    /// Returns whether the peer serves witness data (BIP144).
+    pub fn supports_witness(&self) -> bool {
+        self.services & NODE_WITNESS != 0
+    }
+
+    /// Returns whether the peer understands `sendheaders` messages (BIP130).
+    pub fn supports_send_headers(&self) -> bool {
+        self.version >= SEND_HEADERS_VERSION
+    }
+
+    /// Returns whether the peer understands `feefilter` messages (BIP133).
+    pub fn supports_fee_filter(&self) -> bool {
+        self.version >= FEE_FILTER_VERSION
+    }
+
+    /// Returns whether compact blocks (BIP152) can be exchanged with the peer, which requires
+    /// witness support as well.
+    pub fn supports_compact_blocks(&self) -> bool {
+        self.version >= SHORT_IDS_BLOCKS_VERSION && self.supports_witness()
+    }
+
+    /// Returns whether wtxid relay (BIP339) can be negotiated with the peer, in which case a
+    /// `wtxidrelay` message is sent to it before the `verack`.
+    pub fn supports_wtxid_relay(&self) -> bool {
+        self.version >= WTXID_RELAY_VERSION
+    }
+
+    /// Records that the peer sent a `wtxidrelay` message during the handshake. It is ignored if
+    /// wtxid relay cannot be negotiated with the peer.
+    pub fn mark_wtxid_relay(&mut self) {
+        self.wtxid_relay = self.supports_wtxid_relay();
+    }
+
+    /// Returns whether transactions are announced to and requested from the peer by wtxid, which
+    /// requires both sides to have sent a `wtxidrelay` message.
+    pub fn relays_wtxid(&self) -> bool {
+        self.wtxid_relay
+    }
+
+    /// Returns whether addresses can be relayed to the peer, which requires the timestamps of the
+    /// `addr` messages.
+    pub fn supports_addr_relay(&self) -> bool {
+        self.version >= ADDR_TIME_VERSION
+    }
+
+    pub fn get_version(&self) -> i32 {
+        self.version
+    }
+
+    pub fn get_services(&self) -> u64 {
+        self.services
+    }
+}
+
+#[cfg(test)]
+mod peer_features_test {
+    use super::*;
+    use crate::messages::{header::MessageHeader, message_error::MessageError};
+    use crate::settings_mod::settings::Settings;
+    use std::net::SocketAddr;
+
+    fn peer_version(version: i32, services: u64) -> Result<Version, MessageError> {
+        let settings =
+            Settings::from_file("settings/nodo.conf").map_err(|_| MessageError::ReadFromBytes)?;
+        let addr = SocketAddr::from(([127, 0, 0, 1], 18333));
+        let mut bytes = Version::new(&addr, &settings).to_bytes();
+        bytes[24..28].copy_from_slice(&version.to_le_bytes());
+        bytes[28..36].copy_from_slice(&services.to_le_bytes());
+
+        let mut stream = bytes.as_slice();
+        let header = MessageHeader::from_bytes(&mut stream)?;
+        Version::from_bytes(header, &mut stream)
+    }
+
+    #[test]
+    fn test_features_are_gated_on_the_peer_version() -> Result<(), MessageError> {
+        let features = PeerFeatures::new(70016, &peer_version(70012, NODE_NETWORK)?);
+
+        assert!(features.is_supported());
+        assert_eq!(features.get_version(), 70012);
+        assert!(features.supports_send_headers());
+        assert!(!features.supports_fee_filter());
+        assert!(!features.supports_compact_blocks());
+        assert!(!features.supports_witness());
+
+        let features = PeerFeatures::new(70015, &peer_version(70016, NODE_NETWORK | NODE_WITNESS)?);
+
+        assert_eq!(features.get_version(), 70015);
+        assert!(features.supports_compact_blocks());
+        assert!(features.supports_addr_relay());
+
+        let features = PeerFeatures::new(70016, &peer_version(70016, NODE_WITNESS)?);
+        assert!(!features.is_supported());
+        Ok(())
+    }
+
+    #[test]
+    fn test_wtxid_relay_requires_both_sides() -> Result<(), MessageError> {
+        let mut features = PeerFeatures::new(70015, &peer_version(70016, NODE_NETWORK)?);
+        features.mark_wtxid_relay();
+        assert!(!features.relays_wtxid());
+
+        let mut features = PeerFeatures::new(70016, &peer_version(70016, NODE_NETWORK)?);
+        assert!(features.supports_wtxid_relay());
+        assert!(!features.relays_wtxid());
+
+        features.mark_wtxid_relay();
+        assert!(features.relays_wtxid());
+        Ok(())
+    }
+}
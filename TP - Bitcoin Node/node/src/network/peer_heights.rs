@@ -0,0 +1,190 @@
+use super::{network_constants::STALL_HEIGHT_GAP, network_error::NetworkError};
+
+use std::{
+    collections::HashMap,
+    fs,
+    net::SocketAddr,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+/// Keeps the last best height advertised by each peer, through its version message or the headers
+/// it announced, and detects when the sync of the node stalls behind them.
+///
+/// Heights are persisted to disk, one address and height per line, so the last known heights of
+/// the peers are remembered across runs.
+#[derive(Debug)]
+pub struct PeerHeights {
+    heights: HashMap<SocketAddr, u32>,
+    file_path: Option<String>,
+    last_height: u32,
+    last_progress: Instant,
+    stalled: bool,
+}
+
+impl PeerHeights {
+    /// Creates an empty set of peer heights that is not persisted.
+    pub fn new() -> PeerHeights {
+        PeerHeights {
+            heights: HashMap::new(),
+            file_path: None,
+            last_height: 0,
+            last_progress: Instant::now(),
+            stalled: false,
+        }
+    }
+
+    /// Creates the peer heights, loading the last known heights from the given file.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_path` - The path of the file where the heights are persisted.
+    ///
+    /// # Returns
+    ///
+    /// The peer heights, or a `NetworkError` if the file exists but cannot be parsed.
+    pub fn from_file(file_path: &str) -> Result<PeerHeights, NetworkError> {
+        let mut peer_heights = PeerHeights::new();
+        peer_heights.file_path = Some(file_path.to_string());
+
+        if let Ok(content) = fs::read_to_string(file_path) {
+            for line in content.lines() {
+                let (addr, height) = line.split_once(' ').ok_or(NetworkError::HandShake)?;
+                let addr = SocketAddr::from_str(addr).map_err(|_| NetworkError::HandShake)?;
+                let height = height.trim().parse().map_err(|_| NetworkError::HandShake)?;
+                peer_heights.heights.insert(addr, height);
+            }
+        }
+
+        Ok(peer_heights)
+    }
+
+    /// Records the best height advertised by a peer. Heights lower than the last one advertised
+    /// by the same peer are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address of the peer.
+    /// * `height` - The height advertised by the peer.
+    pub fn record(&mut self, addr: SocketAddr, height: u32) {
+        let best_height = self.heights.entry(addr).or_insert(height);
+        *best_height = height.max(*best_height);
+    }
+
+    /// Writes the known heights to the file they were loaded from, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `NetworkError` if the file cannot be written.
+    pub fn save(&self) -> Result<(), NetworkError> {
+        let file_path = match &self.file_path {
+            Some(file_path) => file_path,
+            None => return Ok(()),
+        };
+
+        let content: String = self
+            .heights
+            .iter()
+            .map(|(addr, height)| format!("{} {}\n", addr, height))
+            .collect();
+
+        fs::write(file_path, content).map_err(|_| NetworkError::Broadcasting)
+    }
+
+    /// Returns the last best height advertised by a peer, if it ever advertised one.
+    pub fn get_height(&self, addr: &SocketAddr) -> Option<u32> {
+        self.heights.get(addr).copied()
+    }
+
+    /// Compares the height of the node against the heights advertised by the given peers.
+    ///
+    /// The sync is considered stalled when every peer with a known height claims to be at least
+    /// `STALL_HEIGHT_GAP` blocks ahead of the node, and the height of the node has not grown for
+    /// the given timeout.
+    ///
+    /// # Arguments
+    ///
+    /// * `height` - The current height of the node.
+    /// * `peers` - The addresses of the connected peers.
+    /// * `timeout` - How long the height of the node may stay the same while behind its peers.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the sync has just been detected as stalled. The timeout is restarted whenever a
+    /// stall is detected, so stalls are reported at most once per timeout.
+    pub fn check_stall(&mut self, height: u32, peers: &[SocketAddr], timeout: Duration) -> bool {
+        let claimed: Vec<u32> = peers
+            .iter()
+            .filter_map(|addr| self.get_height(addr))
+            .collect();
+        let behind = !claimed.is_empty()
+            && claimed
+                .iter()
+                .all(|claimed| *claimed >= height.saturating_add(STALL_HEIGHT_GAP));
+
+        if height > self.last_height || !behind {
+            self.last_height = height;
+            self.last_progress = Instant::now();
+            self.stalled = false;
+            return false;
+        }
+
+        if self.last_progress.elapsed() < timeout {
+            return false;
+        }
+
+        self.last_progress = Instant::now();
+        self.stalled = true;
+        true
+    }
+
+    /// Returns whether the last check found the sync of the node stalled.
+    pub fn is_stalled(&self) -> bool {
+        self.stalled
+    }
+}
+
+impl Default for PeerHeights {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod peer_heights_test {
+    use super::*;
+
+    #[test]
+    fn test_peer_heights_are_persisted() -> Result<(), NetworkError> {
+        let path = "data/test_peer_heights.txt";
+        let _ = fs::remove_file(path);
+        let addr = SocketAddr::from_str("10.0.0.1:18333").map_err(|_| NetworkError::HandShake)?;
+
+        let mut peer_heights = PeerHeights::from_file(path)?;
+        peer_heights.record(addr, 2500000);
+        peer_heights.record(addr, 2400000);
+        peer_heights.save()?;
+
+        let reloaded = PeerHeights::from_file(path)?;
+        let _ = fs::remove_file(path);
+
+        assert_eq!(reloaded.get_height(&addr), Some(2500000));
+        Ok(())
+    }
+
+    #[test]
+    fn test_sync_stalls_behind_every_peer() -> Result<(), NetworkError> {
+        let ahead = SocketAddr::from_str("10.0.0.1:18333").map_err(|_| NetworkError::HandShake)?;
+        let synced = SocketAddr::from_str("10.0.0.2:18333").map_err(|_| NetworkError::HandShake)?;
+        let mut peer_heights = PeerHeights::new();
+        peer_heights.record(ahead, 1000 + STALL_HEIGHT_GAP);
+        peer_heights.record(synced, 1000);
+
+        assert!(!peer_heights.check_stall(1000, &[ahead, synced], Duration::ZERO));
+        assert!(peer_heights.check_stall(1000, &[ahead], Duration::ZERO));
+        assert!(peer_heights.is_stalled());
+        assert!(!peer_heights.check_stall(1001, &[ahead], Duration::ZERO));
+        assert!(!peer_heights.is_stalled());
+        Ok(())
+    }
+}
@@ -0,0 +1,657 @@
+use super::{
+    addr_manager::AddrManager, anchors::Anchors, bloom_filter::BloomFilter, event_bus::EventBus,
+    keepalive::PingTracker, known_txs::KnownTxs, message_capture::MessageCapture,
+    network_constants::MAX_KNOWN_TXS_PER_PEER, network_error::NetworkError,
+    peer_features::PeerFeatures, peer_heights::PeerHeights, peer_stats::PeerStats,
+    peer_stream::PeerWriter, rate_limiter::BandwidthLimits,
+};
+use crate::{
+    block_mod::{block::Block, transaction::Transaction},
+    wallet_utils::{event_log::NodeEvent, peer_info::PeerDetails},
+};
+
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    net::{SocketAddr, TcpStream},
+};
+
+/// Keeps track of the live outbound connections of the node.
+///
+/// The pool is meant to be shared as an `Arc<Mutex<PeerPool>>` between broadcasting, block download
+/// and transaction relay, so every component works over the same set of peers. Each connection is
+/// held through its `PeerWriter`, so messages are queued to the writing thread of the peer instead
+/// of locking its connection, and readers get their own copy of the connection from it.
+///
+/// The pool also remembers the transactions each peer is known to have, so they are never relayed
+/// back to the peer they came from, which peers asked to be announced new blocks through
+/// `headers` messages (BIP130), the fee filters (BIP133) and bloom filters (BIP37) the peers
+/// sent, the best heights the peers advertised, and the protocol version and services negotiated
+/// with each peer, the pings sent to the peers along with their latency, the blocks requested
+/// to a peer because another one did not find them, and the best-performing peers, which are
+/// connected to first on the next start, along with the messages each peer sent and its
+/// misbehavior score, and the addresses of the peers the node may connect to later on. Every
+/// connection added to the pool is
+/// throttled by the bandwidth limits of the pool and, in trace mode, has its messages recorded to
+/// the message capture of the pool, and every peer added to or removed from the pool is published
+/// to the event bus of the node.
+#[derive(Debug)]
+pub struct PeerPool {
+    peers: Vec<(SocketAddr, PeerWriter)>,
+    known_txs: HashMap<SocketAddr, KnownTxs>,
+    headers_announced: HashSet<SocketAddr>,
+    fee_filters: HashMap<SocketAddr, u64>,
+    bloom_filters: HashMap<SocketAddr, BloomFilter>,
+    peer_heights: PeerHeights,
+    features: HashMap<SocketAddr, PeerFeatures>,
+    pings: PingTracker,
+    reassigned: HashMap<SocketAddr, HashSet<Vec<u8>>>,
+    stats: HashMap<SocketAddr, PeerStats>,
+    anchors: Anchors,
+    addr_manager: AddrManager,
+    limits: BandwidthLimits,
+    capture: Option<MessageCapture>,
+    event_bus: EventBus,
+    max_outbound: usize,
+}
+
+impl PeerPool {
+    /// Creates an empty pool.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_outbound` - The maximum amount of outbound connections the pool may hold.
+    pub fn new(max_outbound: usize) -> PeerPool {
+        PeerPool {
+            peers: Vec::new(),
+            known_txs: HashMap::new(),
+            headers_announced: HashSet::new(),
+            fee_filters: HashMap::new(),
+            bloom_filters: HashMap::new(),
+            peer_heights: PeerHeights::new(),
+            features: HashMap::new(),
+            pings: PingTracker::new(),
+            reassigned: HashMap::new(),
+            stats: HashMap::new(),
+            anchors: Anchors::new(),
+            addr_manager: AddrManager::new(),
+            limits: BandwidthLimits::default(),
+            capture: None,
+            event_bus: EventBus::new(),
+            max_outbound,
+        }
+    }
+
+    /// Adds a connection to the pool.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - The connection to the peer.
+    ///
+    /// # Errors
+    ///
+    /// Returns `NetworkError::PeerPoolFull` if the pool already holds `max_outbound` connections, or
+    /// `NetworkError::HandShake` if the peer address of the connection cannot be retrieved.
+    pub fn add(&mut self, stream: TcpStream) -> Result<(), NetworkError> {
+        let addr = stream.peer_addr()?;
+        self.add_peer(
+            SocketAddr::new(addr.ip().to_canonical(), addr.port()),
+            stream,
+        )
+    }
+
+    /// Adds a connection to the pool under the given peer address, which is not the address the
+    /// connection is bound to when the peer is reached through a proxy.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address of the peer.
+    /// * `stream` - The connection to the peer.
+    ///
+    /// # Errors
+    ///
+    /// Returns `NetworkError::PeerPoolFull` if the pool already holds `max_outbound` connections,
+    /// or `NetworkError::HandShake` if the writing thread of the connection cannot be spawned.
+    pub fn add_peer(&mut self, addr: SocketAddr, stream: TcpStream) -> Result<(), NetworkError> {
+        if self.is_full() {
+            return Err(NetworkError::PeerPoolFull);
+        }
+
+        let mut writer = PeerWriter::new(stream, self.limits.clone())?;
+        if let Some(capture) = &self.capture {
+            writer.set_capture(capture.clone(), addr);
+        }
+
+        self.peers.push((addr, writer));
+        self.stats.insert(addr, PeerStats::new());
+        self.event_bus.publish(NodeEvent::PeerConnected(addr));
+        Ok(())
+    }
+
+    /// Removes a connection from the pool.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address of the peer to remove.
+    ///
+    /// # Returns
+    ///
+    /// The writer of the removed connection, or `None` if there was no peer with the given address.
+    pub fn remove(&mut self, addr: &SocketAddr) -> Option<PeerWriter> {
+        let position = self.peers.iter().position(|(peer, _)| peer == addr)?;
+        self.known_txs.remove(addr);
+        self.headers_announced.remove(addr);
+        self.fee_filters.remove(addr);
+        self.bloom_filters.remove(addr);
+        self.features.remove(addr);
+        self.pings.remove(addr);
+        self.reassigned.remove(addr);
+        self.stats.remove(addr);
+        self.event_bus.publish(NodeEvent::PeerDisconnected(*addr));
+        Some(self.peers.remove(position).1)
+    }
+
+    /// Marks a peer as stale, removing it from the pool and shutting its connection down so threads
+    /// blocked writing to it are released.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address of the stale peer.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the peer belonged to the pool.
+    pub fn mark_stale(&mut self, addr: &SocketAddr) -> bool {
+        match self.remove(addr) {
+            Some(writer) => {
+                writer.shutdown();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Marks a transaction as known by a peer, because the peer announced or sent it, or because it
+    /// was sent to the peer.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address of the peer.
+    /// * `tx_id` - The id of the transaction.
+    pub fn mark_known_tx(&mut self, addr: &SocketAddr, tx_id: Vec<u8>) {
+        self.known_txs
+            .entry(*addr)
+            .or_insert_with(|| KnownTxs::new(MAX_KNOWN_TXS_PER_PEER))
+            .insert(tx_id);
+    }
+
+    /// Forgets that the peers know a transaction, so it is sent again to every one of them the next
+    /// time it is broadcast.
+    pub fn forget_tx(&mut self, tx_id: &Vec<u8>) {
+        for known_txs in self.known_txs.values_mut() {
+            known_txs.remove(tx_id);
+        }
+    }
+
+    /// Returns whether a peer is known to have a transaction.
+    pub fn knows_tx(&self, addr: &SocketAddr, tx_id: &Vec<u8>) -> bool {
+        match self.known_txs.get(addr) {
+            Some(known_txs) => known_txs.contains(tx_id),
+            None => false,
+        }
+    }
+
+    /// Picks the peer to request the blocks another peer answered with `notfound`, preferring the
+    /// one that advertised the best height, and records the blocks as requested to it.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address of the peer that did not find the blocks.
+    /// * `block_hashes` - The hashes of the blocks.
+    ///
+    /// # Returns
+    ///
+    /// The address and writer of the chosen peer, or `None` if there is no other peer.
+    pub fn reassign_blocks(
+        &mut self,
+        addr: &SocketAddr,
+        block_hashes: Vec<Vec<u8>>,
+    ) -> Option<(SocketAddr, PeerWriter)> {
+        let (peer, writer) = self
+            .peers
+            .iter()
+            .filter(|(peer, _)| peer != addr)
+            .max_by_key(|(peer, _)| self.peer_heights.get_height(peer))?
+            .clone();
+
+        self.reassigned
+            .entry(peer)
+            .or_default()
+            .extend(block_hashes);
+        Some((peer, writer))
+    }
+
+    /// Takes a block out of the blocks reassigned to a peer.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the block had been reassigned to the peer.
+    pub fn take_reassigned(&mut self, addr: &SocketAddr, block_hash: &Vec<u8>) -> bool {
+        match self.reassigned.get_mut(addr) {
+            Some(block_hashes) => block_hashes.remove(block_hash),
+            None => false,
+        }
+    }
+
+    /// Records that a peer sent a `sendheaders` message, so new blocks are announced to it through
+    /// `headers` messages.
+    pub fn mark_prefers_headers(&mut self, addr: &SocketAddr) {
+        self.headers_announced.insert(*addr);
+    }
+
+    pub fn prefers_headers(&self, addr: &SocketAddr) -> bool {
+        self.headers_announced.contains(addr)
+    }
+
+    /// Records the fee filter sent by a peer through a `feefilter` message.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address of the peer.
+    /// * `fee_rate` - The minimum fee rate, in satoshis per kilobyte, of the transactions the peer
+    ///   wants to be relayed.
+    pub fn set_fee_filter(&mut self, addr: &SocketAddr, fee_rate: u64) {
+        self.fee_filters.insert(*addr, fee_rate);
+    }
+
+    /// Returns whether a transaction with the given fee rate passes the fee filter of a peer.
+    ///
+    /// Peers that did not send a fee filter accept every transaction, and so does every peer when
+    /// the fee rate of the transaction is unknown.
+    pub fn accepts_fee_rate(&self, addr: &SocketAddr, fee_rate: Option<u64>) -> bool {
+        match (self.fee_filters.get(addr), fee_rate) {
+            (Some(fee_filter), Some(fee_rate)) => fee_rate >= *fee_filter,
+            _ => true,
+        }
+    }
+
+    /// Records the bloom filter loaded by a peer through a `filterload` message, replacing the one
+    /// it previously loaded.
+    pub fn set_bloom_filter(&mut self, addr: &SocketAddr, bloom_filter: BloomFilter) {
+        self.bloom_filters.insert(*addr, bloom_filter);
+    }
+
+    /// Adds an element to the bloom filter loaded by a peer.
+    ///
+    /// # Returns
+    ///
+    /// `false` if the peer did not load a bloom filter.
+    pub fn add_to_bloom_filter(&mut self, addr: &SocketAddr, element: &[u8]) -> bool {
+        match self.bloom_filters.get_mut(addr) {
+            Some(bloom_filter) => {
+                bloom_filter.insert(element);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes the bloom filter loaded by a peer.
+    pub fn clear_bloom_filter(&mut self, addr: &SocketAddr) {
+        self.bloom_filters.remove(addr);
+    }
+
+    /// Returns whether a transaction must be relayed to a peer according to its bloom filter.
+    ///
+    /// Peers that did not load a bloom filter are relayed every transaction.
+    pub fn is_tx_relevant(&mut self, addr: &SocketAddr, tx: &Transaction) -> bool {
+        match self.bloom_filters.get_mut(addr) {
+            Some(bloom_filter) => bloom_filter.is_relevant(tx),
+            None => true,
+        }
+    }
+
+    /// Matches the transactions of a block against the bloom filter loaded by a peer.
+    ///
+    /// # Returns
+    ///
+    /// Whether each transaction of the block, coinbase included, matched the filter, or `None` if
+    /// the peer did not load a bloom filter.
+    pub fn match_block(&mut self, addr: &SocketAddr, block: &Block) -> Option<Vec<bool>> {
+        Some(self.bloom_filters.get_mut(addr)?.match_block(block))
+    }
+
+    /// Replaces the heights advertised by the peers, such as the ones collected by the handshake.
+    pub fn set_peer_heights(&mut self, peer_heights: PeerHeights) {
+        self.peer_heights = peer_heights;
+    }
+
+    /// Records the protocol version negotiated with a peer and the services it advertised.
+    /// Sets the bandwidth limits of the connections added to the pool from now on.
+    pub fn set_anchors(&mut self, anchors: Anchors) {
+        self.anchors = anchors;
+    }
+
+    /// Picks the best-performing peers of the pool as the anchors: the ones with the lowest ping
+    /// latency, and among them the ones that advertised the best height. Peers that never
+    /// answered a ping come last. The anchors are left untouched if the pool is empty.
+    pub fn update_anchors(&mut self) {
+        if self.peers.is_empty() {
+            return;
+        }
+
+        let mut addrs = self.get_addrs();
+        addrs.sort_by_key(|addr| {
+            let latency = self.pings.get_latency(addr);
+            (
+                latency.is_none(),
+                latency,
+                Reverse(self.peer_heights.get_height(addr)),
+            )
+        });
+        self.anchors.set(addrs);
+    }
+
+    pub fn get_anchors(&self) -> &Anchors {
+        &self.anchors
+    }
+
+    /// Replaces the addresses the node may connect to later on, such as the ones left over by the
+    /// handshake.
+    pub fn set_addr_manager(&mut self, addr_manager: AddrManager) {
+        self.addr_manager = addr_manager;
+    }
+
+    pub fn get_addr_manager_mut(&mut self) -> &mut AddrManager {
+        &mut self.addr_manager
+    }
+
+    pub fn set_bandwidth_limits(&mut self, limits: BandwidthLimits) {
+        self.limits = limits;
+    }
+
+    /// Records the messages of the connections added to the pool from now on to the given
+    /// capture.
+    pub fn set_message_capture(&mut self, capture: MessageCapture) {
+        self.capture = Some(capture);
+    }
+
+    /// Replaces the event bus the peers added to and removed from the pool are published to, such
+    /// as the one shared by the whole node.
+    pub fn set_event_bus(&mut self, event_bus: EventBus) {
+        self.event_bus = event_bus;
+    }
+
+    /// Returns the bytes sent to and received from a peer, or `None` if it is not in the pool.
+    pub fn get_byte_counters(&self, addr: &SocketAddr) -> Option<(u64, u64)> {
+        self.peers
+            .iter()
+            .find(|(peer, _)| peer == addr)
+            .map(|(_, writer)| {
+                let counters = writer.get_counters();
+                (counters.get_sent(), counters.get_received())
+            })
+    }
+
+    /// Records a message received from a peer.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address of the peer.
+    /// * `command` - The command of the message.
+    pub fn record_message(&mut self, addr: &SocketAddr, command: &str) {
+        self.stats.entry(*addr).or_default().record_message(command);
+    }
+
+    /// Records the misbehavior score a peer reached.
+    pub fn set_misbehavior(&mut self, addr: &SocketAddr, misbehavior: u32) {
+        self.stats
+            .entry(*addr)
+            .or_default()
+            .set_misbehavior(misbehavior);
+    }
+
+    /// Returns the activity counters of a peer, or `None` if it never sent anything.
+    pub fn get_stats(&self, addr: &SocketAddr) -> Option<&PeerStats> {
+        self.stats.get(addr)
+    }
+
+    /// Returns the statistics of every peer of the pool: the bytes sent to and received from it,
+    /// when it was last heard from, its ping latency, its misbehavior score and the messages it
+    /// sent by command.
+    pub fn get_peer_info(&self) -> Vec<PeerDetails> {
+        self.peers
+            .iter()
+            .map(|(addr, writer)| {
+                let counters = writer.get_counters();
+                let stats = self.stats.get(addr).cloned().unwrap_or_default();
+
+                PeerDetails::new(
+                    addr.to_string(),
+                    (counters.get_sent(), counters.get_received()),
+                    stats.get_last_seen(),
+                    self.pings
+                        .get_latency(addr)
+                        .map(|latency| latency.as_millis() as u64),
+                    stats.get_misbehavior(),
+                    stats.get_messages(),
+                )
+            })
+            .collect()
+    }
+
+    pub fn set_peer_features(&mut self, addr: SocketAddr, features: PeerFeatures) {
+        self.features.insert(addr, features);
+    }
+
+    pub fn get_peer_features(&self, addr: &SocketAddr) -> Option<PeerFeatures> {
+        self.features.get(addr).copied()
+    }
+
+    /// Returns whether a peer serves witness data, which is assumed not to be the case if its
+    /// features are unknown.
+    pub fn supports_witness(&self, addr: &SocketAddr) -> bool {
+        self.features
+            .get(addr)
+            .is_some_and(|features| features.supports_witness())
+    }
+
+    /// Returns whether transactions are announced to and requested from a peer by wtxid (BIP339),
+    /// which is assumed not to be the case if its features are unknown.
+    pub fn relays_wtxid(&self, addr: &SocketAddr) -> bool {
+        self.features
+            .get(addr)
+            .is_some_and(|features| features.relays_wtxid())
+    }
+
+    pub fn get_pings(&self) -> &PingTracker {
+        &self.pings
+    }
+
+    pub fn get_pings_mut(&mut self) -> &mut PingTracker {
+        &mut self.pings
+    }
+
+    /// Returns the heights advertised by the peers. Heights are kept after peers disconnect.
+    pub fn get_peer_heights(&self) -> &PeerHeights {
+        &self.peer_heights
+    }
+
+    pub fn get_peer_heights_mut(&mut self) -> &mut PeerHeights {
+        &mut self.peer_heights
+    }
+
+    /// Returns the addresses of the peers of the pool.
+    pub fn get_addrs(&self) -> Vec<SocketAddr> {
+        self.peers.iter().map(|(addr, _)| *addr).collect()
+    }
+
+    /// Returns an iterator over the addresses and connection writers of the pool.
+    pub fn iter(&self) -> impl Iterator<Item = &(SocketAddr, PeerWriter)> {
+        self.peers.iter()
+    }
+
+    /// Returns a copy of the connection writers of the pool, so they can be used once the lock on
+    /// the pool has been released.
+    pub fn get_writers(&self) -> Vec<PeerWriter> {
+        self.peers
+            .iter()
+            .map(|(_, writer)| writer.clone())
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.peers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.peers.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.peers.len() >= self.max_outbound
+    }
+
+    pub fn get_max_outbound(&self) -> usize {
+        self.max_outbound
+    }
+}
+
+#[cfg(test)]
+mod peer_pool_test {
+    use super::*;
+    use crate::messages::header::MessageHeader;
+    use std::{net::TcpListener, time::Duration};
+
+    fn connect(listener: &TcpListener) -> Result<TcpStream, NetworkError> {
+        Ok(TcpStream::connect(listener.local_addr()?)?)
+    }
+
+    #[test]
+    fn test_peer_pool_enforces_max_outbound() -> Result<(), NetworkError> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let mut peer_pool = PeerPool::new(2);
+
+        peer_pool.add(connect(&listener)?)?;
+        peer_pool.add(connect(&listener)?)?;
+
+        assert!(peer_pool.is_full());
+        assert!(matches!(
+            peer_pool.add(connect(&listener)?),
+            Err(NetworkError::PeerPoolFull)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_peer_pool_remove() -> Result<(), NetworkError> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let mut peer_pool = PeerPool::new(2);
+
+        peer_pool.add(connect(&listener)?)?;
+        let addr = peer_pool.iter().next().ok_or(NetworkError::HandShake)?.0;
+
+        assert!(peer_pool.remove(&addr).is_some());
+        assert!(peer_pool.remove(&addr).is_none());
+        assert!(peer_pool.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_peer_pool_publishes_peer_events() -> Result<(), NetworkError> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let event_bus = EventBus::new();
+        let events = event_bus.subscribe();
+        let mut peer_pool = PeerPool::new(1);
+        peer_pool.set_event_bus(event_bus);
+
+        peer_pool.add(connect(&listener)?)?;
+        let addr = peer_pool.iter().next().ok_or(NetworkError::HandShake)?.0;
+        peer_pool.remove(&addr);
+
+        assert_eq!(events.try_recv(), Ok(NodeEvent::PeerConnected(addr)));
+        assert_eq!(events.try_recv(), Ok(NodeEvent::PeerDisconnected(addr)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_peer_pool_known_txs() -> Result<(), NetworkError> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let mut peer_pool = PeerPool::new(1);
+
+        peer_pool.add(connect(&listener)?)?;
+        let addr = peer_pool.iter().next().ok_or(NetworkError::HandShake)?.0;
+        peer_pool.mark_known_tx(&addr, vec![1; 32]);
+
+        assert!(peer_pool.knows_tx(&addr, &vec![1; 32]));
+        assert!(!peer_pool.knows_tx(&addr, &vec![2; 32]));
+
+        peer_pool.remove(&addr);
+        assert!(!peer_pool.knows_tx(&addr, &vec![1; 32]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_peer_pool_reassigns_blocks_to_another_peer() -> Result<(), NetworkError> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let mut peer_pool = PeerPool::new(2);
+
+        let addrs = [
+            SocketAddr::from(([127, 0, 0, 1], 18333)),
+            SocketAddr::from(([127, 0, 0, 2], 18333)),
+        ];
+        peer_pool.add_peer(addrs[0], connect(&listener)?)?;
+        peer_pool.add_peer(addrs[1], connect(&listener)?)?;
+
+        let (peer, _) = peer_pool
+            .reassign_blocks(&addrs[0], vec![vec![1; 32]])
+            .ok_or(NetworkError::HandShake)?;
+
+        assert_eq!(peer, addrs[1]);
+        assert!(!peer_pool.take_reassigned(&addrs[0], &vec![1; 32]));
+        assert!(peer_pool.take_reassigned(&addrs[1], &vec![1; 32]));
+        assert!(!peer_pool.take_reassigned(&addrs[1], &vec![1; 32]));
+
+        peer_pool.remove(&addrs[0]);
+        assert!(peer_pool.reassign_blocks(&addrs[1], vec![]).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_peer_pool_fee_filters() -> Result<(), NetworkError> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let mut peer_pool = PeerPool::new(1);
+
+        peer_pool.add(connect(&listener)?)?;
+        let addr = peer_pool.iter().next().ok_or(NetworkError::HandShake)?.0;
+
+        assert!(peer_pool.accepts_fee_rate(&addr, Some(0)));
+        peer_pool.set_fee_filter(&addr, 1000);
+
+        assert!(peer_pool.accepts_fee_rate(&addr, Some(1000)));
+        assert!(!peer_pool.accepts_fee_rate(&addr, Some(999)));
+        assert!(peer_pool.accepts_fee_rate(&addr, None));
+        Ok(())
+    }
+
+    #[test]
+    fn test_silent_peer_is_marked_stale() -> Result<(), NetworkError> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let mut peer_pool = PeerPool::new(1);
+
+        let mut stream = connect(&listener)?;
+        stream.set_read_timeout(Some(Duration::from_millis(10)))?;
+        peer_pool.add(stream.try_clone()?)?;
+        let addr = stream.peer_addr()?;
+
+        let err = match MessageHeader::from_bytes(&mut stream) {
+            Ok(_) => return Err(NetworkError::HandShake),
+            Err(err) => NetworkError::from_read_error(err),
+        };
+
+        assert!(matches!(err, NetworkError::StalePeer));
+        assert!(peer_pool.mark_stale(&addr));
+        assert!(peer_pool.is_empty());
+        Ok(())
+    }
+}
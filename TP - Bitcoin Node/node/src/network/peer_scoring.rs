@@ -0,0 +1,200 @@
+use super::{network_constants::*, network_error::NetworkError};
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, OpenOptions},
+    io::Write,
+    net::IpAddr,
+    str::FromStr,
+};
+
+/// Represents the kinds of misbehavior a peer can be penalized for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Misbehavior {
+    InvalidBlock,
+    MalformedMessage,
+    UnsolicitedData,
+    WrongNetwork,
+}
+
+impl Misbehavior {
+    /// Returns the score penalty associated with the misbehavior.
+    pub fn get_penalty(&self) -> u32 {
+        match self {
+            Misbehavior::InvalidBlock => INVALID_BLOCK_PENALTY,
+            Misbehavior::MalformedMessage => MALFORMED_MESSAGE_PENALTY,
+            Misbehavior::UnsolicitedData => UNSOLICITED_DATA_PENALTY,
+            Misbehavior::WrongNetwork => WRONG_NETWORK_PENALTY,
+        }
+    }
+
+    /// Maps a network error to the misbehavior it reveals, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - The error returned while handling a peer's message.
+    ///
+    /// # Returns
+    ///
+    /// The misbehavior that caused the error, or `None` if the error is not the peer's fault.
+    pub fn from_error(error: &NetworkError) -> Option<Misbehavior> {
+        match error {
+            NetworkError::InvalidBlock => Some(Misbehavior::InvalidBlock),
+            NetworkError::MalformedMessage => Some(Misbehavior::MalformedMessage),
+            NetworkError::UnsolicitedData => Some(Misbehavior::UnsolicitedData),
+            NetworkError::WrongNetwork => Some(Misbehavior::WrongNetwork),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the connection cannot be read from anymore after the misbehavior, since the
+    /// rest of the offending message was left unread.
+    pub fn leaves_stream_unreadable(&self) -> bool {
+        matches!(
+            self,
+            Misbehavior::MalformedMessage | Misbehavior::WrongNetwork
+        )
+    }
+}
+
+/// Keeps track of the misbehavior score of every peer and of the peers that have been banned.
+///
+/// Banned peers are persisted to disk, one IP address per line, so they are skipped by the
+/// handshake on the following runs.
+#[derive(Debug)]
+pub struct PeerScores {
+    scores: HashMap<IpAddr, u32>,
+    banned: HashSet<IpAddr>,
+    ban_file_path: String,
+}
+
+impl PeerScores {
+    /// Creates the peer scores, loading the banned peers from the given file.
+    ///
+    /// # Arguments
+    ///
+    /// * `ban_file_path` - The path of the file where banned peers are persisted.
+    ///
+    /// # Returns
+    ///
+    /// The peer scores, or a `NetworkError` if the ban list exists but cannot be read.
+    pub fn new(ban_file_path: &str) -> Result<PeerScores, NetworkError> {
+        let mut banned = HashSet::new();
+
+        if let Ok(content) = fs::read_to_string(ban_file_path) {
+            for line in content.lines() {
+                let ip = IpAddr::from_str(line.trim()).map_err(|_| NetworkError::HandShake)?;
+                banned.insert(ip.to_canonical());
+            }
+        }
+
+        Ok(PeerScores {
+            scores: HashMap::new(),
+            banned,
+            ban_file_path: ban_file_path.to_string(),
+        })
+    }
+
+    /// Penalizes a peer for misbehaving, banning it once its score reaches the ban threshold.
+    ///
+    /// # Arguments
+    ///
+    /// * `ip` - The IP address of the peer.
+    /// * `misbehavior` - The misbehavior the peer incurred in.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the peer is banned and must be disconnected, or a `NetworkError` if the ban
+    /// could not be persisted.
+    pub fn misbehaving(
+        &mut self,
+        ip: IpAddr,
+        misbehavior: Misbehavior,
+    ) -> Result<bool, NetworkError> {
+        let ip = ip.to_canonical();
+        if self.banned.contains(&ip) {
+            return Ok(true);
+        }
+
+        let score = self.scores.entry(ip).or_insert(0);
+        *score += misbehavior.get_penalty();
+
+        println!(
+            "Peer {} misbehaved ({:?}), score is now {}.",
+            ip, misbehavior, score
+        );
+
+        if *score < BAN_THRESHOLD {
+            return Ok(false);
+        }
+
+        self.ban(ip)?;
+        Ok(true)
+    }
+
+    /// Bans a peer and appends it to the ban list file.
+    fn ban(&mut self, ip: IpAddr) -> Result<(), NetworkError> {
+        self.banned.insert(ip);
+        self.scores.remove(&ip);
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.ban_file_path)
+            .map_err(|_| NetworkError::Broadcasting)?;
+        writeln!(file, "{}", ip).map_err(|_| NetworkError::Broadcasting)?;
+
+        println!("Peer {} has been banned.", ip);
+        Ok(())
+    }
+
+    /// Returns whether a peer is banned, IPv4-mapped addresses being handled as IPv4 addresses.
+    pub fn is_banned(&self, ip: &IpAddr) -> bool {
+        self.banned.contains(&ip.to_canonical())
+    }
+
+    pub fn get_score(&self, ip: &IpAddr) -> u32 {
+        *self.scores.get(&ip.to_canonical()).unwrap_or(&0)
+    }
+}
+
+#[cfg(test)]
+mod peer_scoring_test {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn test_peer_is_banned_after_reaching_threshold() -> Result<(), NetworkError> {
+        let path = "data/test_banned_peers.txt";
+        let _ = fs::remove_file(path);
+        let ip = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        let mut peer_scores = PeerScores::new(path)?;
+
+        assert!(!peer_scores.misbehaving(ip, Misbehavior::MalformedMessage)?);
+        assert_eq!(peer_scores.get_score(&ip), MALFORMED_MESSAGE_PENALTY);
+        assert!(peer_scores.misbehaving(ip, Misbehavior::InvalidBlock)?);
+        assert!(peer_scores.is_banned(&ip));
+
+        let reloaded_scores = PeerScores::new(path)?;
+        let _ = fs::remove_file(path);
+
+        assert!(reloaded_scores.is_banned(&ip));
+        assert!(reloaded_scores.is_banned(&IpAddr::V6(Ipv4Addr::new(10, 0, 0, 1).to_ipv6_mapped())));
+        Ok(())
+    }
+
+    #[test]
+    fn test_peer_on_another_network_is_banned() -> Result<(), NetworkError> {
+        let path = "data/test_wrong_network_peers.txt";
+        let _ = fs::remove_file(path);
+        let ip = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+        let mut peer_scores = PeerScores::new(path)?;
+
+        let misbehavior = Misbehavior::from_error(&NetworkError::WrongNetwork);
+        assert_eq!(misbehavior, Some(Misbehavior::WrongNetwork));
+        assert!(peer_scores.misbehaving(ip, Misbehavior::WrongNetwork)?);
+
+        let _ = fs::remove_file(path);
+        Ok(())
+    }
+}
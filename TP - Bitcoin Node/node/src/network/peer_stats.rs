@@ -0,0 +1,108 @@
+use crate::messages::command::Command;
+use chrono::Utc;
+use std::collections::HashMap;
+
+/// Counters of the activity of a peer: the messages it sent by command, when it was last heard
+/// from, when it last announced a block, and its misbehavior score.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeerStats {
+    messages: HashMap<String, u64>,
+    connected_at: u64,
+    last_seen: u64,
+    last_block_at: u64,
+    misbehavior: u32,
+}
+
+impl PeerStats {
+    /// Creates the counters of a peer that just connected and has not sent anything yet.
+    pub fn new() -> PeerStats {
+        PeerStats {
+            messages: HashMap::new(),
+            connected_at: now(),
+            last_seen: 0,
+            last_block_at: 0,
+            misbehavior: 0,
+        }
+    }
+
+    /// Records a message received from the peer, which is now the last time it was heard from.
+    /// `headers` and `block` messages are block announcements.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The command of the message.
+    pub fn record_message(&mut self, command: &str) {
+        *self.messages.entry(command.to_string()).or_insert(0) += 1;
+        self.last_seen = now();
+
+        if matches!(
+            Command::from_name(command),
+            Some(Command::Headers | Command::Block)
+        ) {
+            self.last_block_at = self.last_seen;
+        }
+    }
+
+    pub fn set_misbehavior(&mut self, misbehavior: u32) {
+        self.misbehavior = misbehavior;
+    }
+
+    /// Returns the amount of messages received by command, sorted by command.
+    pub fn get_messages(&self) -> Vec<(String, u64)> {
+        let mut messages: Vec<(String, u64)> = self
+            .messages
+            .iter()
+            .map(|(command, count)| (command.clone(), *count))
+            .collect();
+        messages.sort();
+        messages
+    }
+
+    /// Returns the unix timestamp of the last message received from the peer, or 0 if it never
+    /// sent one.
+    pub fn get_last_seen(&self) -> u64 {
+        self.last_seen
+    }
+
+    pub fn get_misbehavior(&self) -> u32 {
+        self.misbehavior
+    }
+
+    /// Returns the seconds elapsed since the peer last announced a block, or since it connected if
+    /// it never did.
+    pub fn get_secs_without_blocks(&self) -> u64 {
+        now().saturating_sub(self.last_block_at.max(self.connected_at))
+    }
+}
+
+impl Default for PeerStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn now() -> u64 {
+    Utc::now().timestamp().max(0) as u64
+}
+
+#[cfg(test)]
+mod peer_stats_test {
+    use super::*;
+
+    #[test]
+    fn test_peer_stats_count_messages_by_command() {
+        let mut peer_stats = PeerStats::new();
+        assert_eq!(peer_stats.get_last_seen(), 0);
+
+        peer_stats.record_message("ping");
+        peer_stats.record_message("inv");
+        peer_stats.record_message("ping");
+
+        assert_eq!(
+            peer_stats.get_messages(),
+            vec![("inv".to_string(), 1), ("ping".to_string(), 2)]
+        );
+        assert!(peer_stats.get_last_seen() > 0);
+        assert!(peer_stats.get_secs_without_blocks() <= 1);
+    }
+}
@@ -0,0 +1,360 @@
+use super::{
+    message_capture::MessageCapture, network_constants::READ_BUFFER_SIZE,
+    network_error::NetworkError, rate_limiter::BandwidthLimits,
+};
+use crate::messages::header::MessageHeader;
+
+use std::{
+    io::{self, BufReader, Cursor, Read, Write},
+    net::{Shutdown, SocketAddr, TcpStream},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{self, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+/// Counts the bytes sent to and received from a peer.
+#[derive(Debug, Clone, Default)]
+pub struct ByteCounters {
+    sent: Arc<AtomicU64>,
+    received: Arc<AtomicU64>,
+}
+
+impl ByteCounters {
+    pub fn add_sent(&self, bytes: usize) {
+        self.sent.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub fn add_received(&self, bytes: usize) {
+        self.received.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub fn get_sent(&self) -> u64 {
+        self.sent.load(Ordering::Relaxed)
+    }
+
+    pub fn get_received(&self) -> u64 {
+        self.received.load(Ordering::Relaxed)
+    }
+}
+
+/// Writes the messages sent to a peer from a thread of its own, fed through a queue.
+///
+/// Every thread that talks to the peer holds a clone of the writer and queues whole messages, so
+/// messages are never interleaved and no thread has to lock the connection to send them. The
+/// writing thread finishes once every clone has been dropped, or once a message cannot be written,
+/// in which case the connection is shut down so the threads reading from it are released.
+///
+/// Both the writing thread and the streams read from the writer are throttled by the bandwidth
+/// limits of the node, and count the bytes they transfer. The streams read from the writer share
+/// a single read buffer, so the bytes buffered by one of them are not lost when it is dropped.
+/// In trace mode, the messages sent through the writer and received through its streams are
+/// recorded to a capture.
+#[derive(Debug, Clone)]
+pub struct PeerWriter {
+    sender: Sender<Vec<u8>>,
+    socket: Arc<TcpStream>,
+    reader: Arc<Mutex<BufReader<MeteredSocket>>>,
+    counters: ByteCounters,
+    capture: Option<(MessageCapture, SocketAddr)>,
+}
+
+impl PeerWriter {
+    /// Spawns the writing thread of a connection.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - The connection to the peer.
+    /// * `limits` - The upload and download limits shared by every connection.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `NetworkError` if the connection cannot be cloned for the writing thread or the
+    /// read buffer.
+    pub fn new(stream: TcpStream, limits: BandwidthLimits) -> Result<PeerWriter, NetworkError> {
+        let mut writer = stream.try_clone()?;
+        let (sender, receiver) = mpsc::channel::<Vec<u8>>();
+        let counters = ByteCounters::default();
+        let upload = limits.get_upload().clone();
+        let sent = counters.clone();
+        let socket = MeteredSocket {
+            socket: stream.try_clone()?,
+            limits,
+            counters: counters.clone(),
+        };
+
+        thread::spawn(move || {
+            for message in receiver {
+                upload.throttle(message.len());
+                sent.add_sent(message.len());
+
+                if writer.write_all(&message).is_err() {
+                    let _ = writer.shutdown(Shutdown::Both);
+                    break;
+                }
+            }
+        });
+
+        Ok(PeerWriter {
+            sender,
+            socket: Arc::new(stream),
+            reader: Arc::new(Mutex::new(BufReader::with_capacity(
+                READ_BUFFER_SIZE,
+                socket,
+            ))),
+            counters,
+            capture: None,
+        })
+    }
+
+    /// Records the messages exchanged through the writer and its streams from now on to the given
+    /// capture, under the given peer address.
+    pub fn set_capture(&mut self, capture: MessageCapture, addr: SocketAddr) {
+        self.capture = Some((capture, addr));
+    }
+
+    /// Queues a message to be written to the peer.
+    ///
+    /// # Errors
+    ///
+    /// Returns `NetworkError::Broadcasting` if the writing thread already finished.
+    pub fn send(&self, message: Vec<u8>) -> Result<(), NetworkError> {
+        if let Some((capture, addr)) = &self.capture {
+            capture.record_sent(addr, &message);
+        }
+
+        self.sender
+            .send(message)
+            .map_err(|_| NetworkError::Broadcasting)
+    }
+
+    /// Creates a stream to read the messages of the peer from, which writes through this writer.
+    pub fn reader(&self) -> PeerStream {
+        PeerStream {
+            reader: self.reader.clone(),
+            writer: self.clone(),
+            payload: Cursor::new(Vec::new()),
+        }
+    }
+
+    /// Shuts the connection down, releasing the threads reading from it.
+    pub fn shutdown(&self) {
+        let _ = self.socket.shutdown(Shutdown::Both);
+    }
+
+    pub fn get_socket(&self) -> &TcpStream {
+        &self.socket
+    }
+
+    pub fn get_counters(&self) -> &ByteCounters {
+        &self.counters
+    }
+}
+
+impl Write for PeerWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.send(buf.to_vec())
+            .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The socket of a peer as read by a `PeerStream`, throttled by the download limit of the node
+/// and counting the bytes it receives.
+#[derive(Debug)]
+struct MeteredSocket {
+    socket: TcpStream,
+    limits: BandwidthLimits,
+    counters: ByteCounters,
+}
+
+impl Read for MeteredSocket {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let bytes = self.socket.read(buf)?;
+
+        self.limits.get_download().throttle(bytes);
+        self.counters.add_received(bytes);
+        Ok(bytes)
+    }
+}
+
+/// The connection to a peer as seen by a thread that reads its messages: reads go through a
+/// buffer of `READ_BUFFER_SIZE` bytes, so parsing a message field by field does not take a read
+/// from the socket per field, while writes are queued to the writing thread of the peer.
+///
+/// The payload of a message can be received ahead of its parsing, so its checksum is verified
+/// first; reads are then served from the received payload until it is consumed.
+#[derive(Debug)]
+pub struct PeerStream {
+    reader: Arc<Mutex<BufReader<MeteredSocket>>>,
+    writer: PeerWriter,
+    payload: Cursor<Vec<u8>>,
+}
+
+impl PeerStream {
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> Result<(), NetworkError> {
+        Ok(self.writer.socket.set_read_timeout(timeout)?)
+    }
+
+    /// Shuts the connection down, releasing every thread reading from it.
+    pub fn shutdown(&self) {
+        self.writer.shutdown();
+    }
+
+    /// Receives the payload of the message of the given header, discarding whatever was left of
+    /// the previous one.
+    ///
+    /// # Errors
+    ///
+    /// Returns `NetworkError::MalformedMessage` if the payload does not match the checksum of the
+    /// header, or `NetworkError::StalePeer` if the peer did not send it in time.
+    pub fn receive_payload(&mut self, header: &MessageHeader) -> Result<(), NetworkError> {
+        self.discard_payload();
+
+        let payload = header
+            .read_payload(self)
+            .map_err(NetworkError::from_read_error)?;
+
+        if let Some((capture, addr)) = &self.writer.capture {
+            capture.record_received(addr, header, &payload);
+        }
+        self.payload = Cursor::new(payload);
+        Ok(())
+    }
+
+    /// Discards whatever the handler of the last message left unread of its payload, so the next
+    /// header is read from the connection rather than from the leftover bytes.
+    pub fn discard_payload(&mut self) {
+        self.payload = Cursor::new(Vec::new());
+    }
+
+    pub fn get_writer(&self) -> &PeerWriter {
+        &self.writer
+    }
+}
+
+impl Read for PeerStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if (self.payload.position() as usize) < self.payload.get_ref().len() {
+            return self.payload.read(buf);
+        }
+
+        self.reader
+            .lock()
+            .map_err(|_| io::Error::from(io::ErrorKind::Other))?
+            .read(buf)
+    }
+}
+
+impl Write for PeerStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod peer_stream_test {
+    use super::*;
+    use crate::messages::{
+        message_constants::{HEADER_BYTES_SIZE, PING_COMMAND},
+        ping::Ping,
+    };
+    use bitcoin_hashes::{sha256d, Hash};
+    use std::net::TcpListener;
+
+    #[test]
+    fn test_messages_are_written_in_order_from_several_writers() -> Result<(), NetworkError> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let writer = PeerWriter::new(
+            TcpStream::connect(listener.local_addr()?)?,
+            BandwidthLimits::default(),
+        )?;
+        let (mut peer, _) = listener.accept()?;
+
+        let mut stream = writer.reader();
+        stream.write_all(&[1, 2])?;
+        writer.send(vec![3])?;
+        writer.clone().write_all(&[4])?;
+
+        let mut buffer = [0u8; 4];
+        peer.read_exact(&mut buffer)?;
+        assert_eq!(buffer, [1, 2, 3, 4]);
+
+        peer.write_all(&[5])?;
+        let mut buffer = [0u8; 1];
+        stream.read_exact(&mut buffer)?;
+        assert_eq!(buffer, [5]);
+
+        assert_eq!(writer.get_counters().get_sent(), 4);
+        assert_eq!(writer.get_counters().get_received(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_bytes_buffered_by_a_dropped_stream_are_read_by_the_next_one() -> Result<(), NetworkError>
+    {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let writer = PeerWriter::new(
+            TcpStream::connect(listener.local_addr()?)?,
+            BandwidthLimits::default(),
+        )?;
+        let (mut peer, _) = listener.accept()?;
+
+        peer.write_all(&[1, 2, 3, 4])?;
+
+        let mut buffer = [0u8; 1];
+        writer.reader().read_exact(&mut buffer)?;
+        assert_eq!(buffer, [1]);
+
+        let mut buffer = [0u8; 3];
+        writer.reader().read_exact(&mut buffer)?;
+        assert_eq!(buffer, [2, 3, 4]);
+        assert_eq!(writer.get_counters().get_received(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_payload_left_unread_is_not_taken_for_the_next_header() -> Result<(), NetworkError> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let writer = PeerWriter::new(
+            TcpStream::connect(listener.local_addr()?)?,
+            BandwidthLimits::default(),
+        )?;
+        let (mut peer, _) = listener.accept()?;
+        let start_string = vec![0x0b, 0x11, 0x09, 0x07];
+
+        // A ping with three trailing bytes its parser does not read
+        let payload = [1u64.to_le_bytes().as_slice(), &[7, 7, 7]].concat();
+        let mut header = MessageHeader::new(start_string.clone(), PING_COMMAND.to_string());
+        header.update_payload(
+            payload.len() as u32,
+            sha256d::Hash::hash(&payload).to_byte_array()[..4].to_vec(),
+        );
+        peer.write_all(&[header.to_bytes(), payload].concat())?;
+        peer.write_all(&Ping::new(start_string, 2).to_bytes())?;
+
+        let mut stream = writer.reader();
+        for nonce in 1..=2 {
+            let header = MessageHeader::from_bytes(&mut stream)?;
+            stream.receive_payload(&header)?;
+            assert_eq!(Ping::from_bytes(header, &mut stream)?.get_nonce(), nonce);
+            stream.discard_payload();
+        }
+        assert_eq!(
+            writer.get_counters().get_received() as usize,
+            2 * HEADER_BYTES_SIZE + 19
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,136 @@
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Limits the amount of bytes transferred per second, allowing bursts of up to one second worth
+/// of bytes.
+///
+/// Transfers larger than the bytes available are let through, leaving the bucket in debt: the
+/// transfer has to wait until the debt is paid back at the configured rate.
+#[derive(Debug)]
+pub struct TokenBucket {
+    rate: u64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// Creates a full bucket.
+    ///
+    /// # Arguments
+    ///
+    /// * `rate` - The maximum amount of bytes per second.
+    pub fn new(rate: u64) -> TokenBucket {
+        TokenBucket {
+            rate,
+            tokens: rate as f64,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Takes the given amount of bytes from the bucket.
+    ///
+    /// # Returns
+    ///
+    /// The time to wait before the bytes can be transferred, which is zero if the bucket had
+    /// enough bytes available.
+    pub fn take(&mut self, bytes: u64) -> Duration {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate as f64).min(self.rate as f64);
+        self.last_refill = now;
+
+        self.tokens -= bytes as f64;
+        if self.tokens >= 0.0 {
+            return Duration::ZERO;
+        }
+        Duration::from_secs_f64(-self.tokens / self.rate as f64)
+    }
+}
+
+/// A token bucket shared by every connection it limits, or no limit at all.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimiter {
+    bucket: Option<Arc<Mutex<TokenBucket>>>,
+}
+
+impl RateLimiter {
+    /// Creates a limiter of the given amount of bytes per second, with 0 meaning no limit.
+    pub fn new(rate: u64) -> RateLimiter {
+        RateLimiter {
+            bucket: (rate > 0).then(|| Arc::new(Mutex::new(TokenBucket::new(rate)))),
+        }
+    }
+
+    /// Blocks the calling thread until the given amount of bytes can be transferred.
+    pub fn throttle(&self, bytes: usize) {
+        let wait = match &self.bucket {
+            Some(bucket) => match bucket.lock() {
+                Ok(mut locked_bucket) => locked_bucket.take(bytes as u64),
+                Err(_) => return,
+            },
+            None => return,
+        };
+
+        if !wait.is_zero() {
+            thread::sleep(wait);
+        }
+    }
+
+    pub fn is_limited(&self) -> bool {
+        self.bucket.is_some()
+    }
+}
+
+/// The upload and download limits shared by every connection to the peers.
+#[derive(Debug, Clone, Default)]
+pub struct BandwidthLimits {
+    upload: RateLimiter,
+    download: RateLimiter,
+}
+
+impl BandwidthLimits {
+    /// Creates the limits of the node.
+    ///
+    /// # Arguments
+    ///
+    /// * `upload_rate` - The maximum amount of bytes per second sent to the peers, 0 for no limit.
+    /// * `download_rate` - The maximum amount of bytes per second received from the peers, 0 for
+    ///   no limit.
+    pub fn new(upload_rate: u64, download_rate: u64) -> BandwidthLimits {
+        BandwidthLimits {
+            upload: RateLimiter::new(upload_rate),
+            download: RateLimiter::new(download_rate),
+        }
+    }
+
+    pub fn get_upload(&self) -> &RateLimiter {
+        &self.upload
+    }
+
+    pub fn get_download(&self) -> &RateLimiter {
+        &self.download
+    }
+}
+
+#[cfg(test)]
+mod rate_limiter_test {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket_makes_transfers_over_the_rate_wait() {
+        let mut bucket = TokenBucket::new(1000);
+
+        assert_eq!(bucket.take(500), Duration::ZERO);
+        let wait = bucket.take(1000);
+        assert!(wait > Duration::from_millis(400) && wait <= Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_rate_limiter_without_rate_is_unlimited() {
+        assert!(!RateLimiter::new(0).is_limited());
+        assert!(RateLimiter::new(1).is_limited());
+    }
+}
@@ -0,0 +1,259 @@
+use super::{
+    broadcasting::{accept_to_mempool, broadcast_new_txn, MempoolAcceptance},
+    network_constants::REBROADCAST_EXPIRY_SECS,
+    peer_pool::PeerPool,
+    shutdown::ShutdownCoordinator,
+};
+use crate::{
+    block_mod::{blockchain::BlockChain, chain_state::ChainState, transaction::Transaction},
+    messages::read_from_bytes::encode_hex,
+    settings_mod::settings::Settings,
+};
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// Represents a transaction created by the wallet that is not confirmed yet.
+#[derive(Debug)]
+struct PendingTx {
+    tx: Transaction,
+    first_broadcast: Instant,
+    last_broadcast: Instant,
+}
+
+/// Keeps the transactions broadcast on behalf of the wallet until they are confirmed or abandoned,
+/// so they can be broadcast again if the network forgets them.
+#[derive(Debug, Default)]
+pub struct WalletTxs {
+    pending: HashMap<Vec<u8>, PendingTx>,
+}
+
+impl WalletTxs {
+    /// Creates an empty set of wallet transactions.
+    pub fn new() -> WalletTxs {
+        WalletTxs {
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Starts tracking a transaction that has just been broadcast.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - The transaction created by the wallet.
+    pub fn track(&mut self, tx: Transaction) {
+        let now = Instant::now();
+
+        self.pending.entry(tx.get_id(false)).or_insert(PendingTx {
+            tx,
+            first_broadcast: now,
+            last_broadcast: now,
+        });
+    }
+
+    /// Stops tracking the transactions included in the blockchain and the ones first broadcast
+    /// longer than `expiry` ago, which are abandoned.
+    ///
+    /// # Arguments
+    ///
+    /// * `blockchain` - The blockchain where confirmed transactions are looked for.
+    /// * `expiry` - How long a transaction is broadcast again before it is abandoned.
+    ///
+    /// # Returns
+    ///
+    /// The ids of the abandoned transactions.
+    pub fn remove_settled(&mut self, blockchain: &BlockChain, expiry: Duration) -> Vec<Vec<u8>> {
+        self.pending
+            .retain(|tx_id, _| blockchain.get_transaction_block(tx_id).is_none());
+
+        let abandoned: Vec<Vec<u8>> = self
+            .pending
+            .iter()
+            .filter(|(_, pending)| pending.first_broadcast.elapsed() >= expiry)
+            .map(|(tx_id, _)| tx_id.clone())
+            .collect();
+
+        for tx_id in abandoned.iter() {
+            self.pending.remove(tx_id);
+        }
+        abandoned
+    }
+
+    /// Returns the transactions last broadcast at least `interval` ago, marking them as broadcast
+    /// now.
+    pub fn take_due(&mut self, interval: Duration) -> Vec<Transaction> {
+        let now = Instant::now();
+
+        self.pending
+            .values_mut()
+            .filter(|pending| now.duration_since(pending.last_broadcast) >= interval)
+            .map(|pending| {
+                pending.last_broadcast = now;
+                pending.tx.clone()
+            })
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+/// Spawns the thread that broadcasts again the wallet transactions that are still unconfirmed.
+///
+/// Every `rebroadcast_interval_secs`, the transactions already included in the blockchain or
+/// first broadcast more than `REBROADCAST_EXPIRY_SECS` ago stop being tracked, and the rest are
+/// announced again to every peer, even to the ones the node already announced them to. The ones
+/// evicted from the mempool are offered to it again first, and are skipped if they are no longer
+/// valid. The thread finishes once the pool is empty or a shutdown is requested.
+///
+/// # Arguments
+///
+/// * `settings` - The node settings, holding the rebroadcast interval.
+/// * `wallet_txs` - The shared wallet transactions waiting to be confirmed.
+/// * `peer_pool` - The shared pool of connections.
+/// * `chain_state` - The shared blockchain, UTXO set, mempool and header chain.
+/// * `shutdown` - The shutdown coordinator of the node.
+pub fn rebroadcast_wallet_txs(
+    settings: Arc<Settings>,
+    wallet_txs: Arc<Mutex<WalletTxs>>,
+    peer_pool: Arc<Mutex<PeerPool>>,
+    chain_state: ChainState,
+    shutdown: ShutdownCoordinator,
+) -> JoinHandle<()> {
+    thread::spawn(move || loop {
+        let interval = settings.get_rebroadcast_interval();
+        if !shutdown.sleep(interval) {
+            return;
+        }
+
+        match peer_pool.lock() {
+            Ok(locked_peer_pool) if !locked_peer_pool.is_empty() => drop(locked_peer_pool),
+            _ => return,
+        }
+
+        let mut locked_wallet_txs = match wallet_txs.lock() {
+            Ok(locked_wallet_txs) => locked_wallet_txs,
+            Err(_) => return,
+        };
+
+        let abandoned = match chain_state.get_blockchain().lock() {
+            Ok(locked_blockchain) => locked_wallet_txs.remove_settled(
+                &locked_blockchain,
+                Duration::from_secs(REBROADCAST_EXPIRY_SECS),
+            ),
+            Err(_) => return,
+        };
+
+        for tx_id in abandoned {
+            let mut displayed_id = tx_id;
+            displayed_id.reverse();
+            println!(
+                "Transaction {} was abandoned after not being confirmed.",
+                encode_hex(&displayed_id).unwrap_or_default()
+            );
+        }
+
+        let due = locked_wallet_txs.take_due(interval);
+        drop(locked_wallet_txs);
+
+        for tx in due {
+            let in_mempool = match chain_state.get_mempool().lock() {
+                Ok(locked_mempool) => locked_mempool.get_txs().contains_key(&tx.get_id(false)),
+                Err(_) => return,
+            };
+
+            let fee_rate = if in_mempool {
+                match chain_state.get_utxo().lock() {
+                    Ok(locked_utxo) => locked_utxo.get_fee_rate(&tx),
+                    Err(_) => return,
+                }
+            } else {
+                match accept_to_mempool(&settings, &chain_state, &tx) {
+                    Ok(MempoolAcceptance::Accepted(fee_rate)) => fee_rate,
+                    Ok(_) => {
+                        println!("Wallet transaction can no longer enter the mempool.");
+                        continue;
+                    }
+                    Err(_) => return,
+                }
+            };
+
+            match peer_pool.lock() {
+                Ok(mut locked_peer_pool) => locked_peer_pool.forget_tx(&tx.get_id(false)),
+                Err(_) => return,
+            }
+
+            if broadcast_new_txn(&settings, &tx, &peer_pool, fee_rate).is_err() {
+                println!("Attempt to rebroadcast wallet transaction has failed.");
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod rebroadcast_test {
+    use super::*;
+    use crate::{
+        block_mod::{
+            block::{test_block_bytes, Block},
+            tx_in::TxIn,
+            tx_out::TxOut,
+        },
+        messages::message_error::MessageError,
+        network::bitcoin_network::Network,
+    };
+
+    fn wallet_tx() -> Transaction {
+        Transaction::new(
+            1,
+            vec![TxIn::new(vec![1; 32], 0, vec![], 0xffffffff)],
+            vec![TxOut::new(9000, vec![0; 25])],
+            0,
+            false,
+        )
+    }
+
+    #[test]
+    fn test_confirmed_wallet_txs_are_not_rebroadcast() -> Result<(), MessageError> {
+        let tx = wallet_tx();
+        let mut wallet_txs = WalletTxs::new();
+        wallet_txs.track(tx.clone());
+        wallet_txs.track(tx.clone());
+
+        assert_eq!(wallet_txs.len(), 1);
+        assert_eq!(wallet_txs.take_due(Duration::ZERO).len(), 1);
+        assert!(wallet_txs.take_due(Duration::from_secs(60)).is_empty());
+
+        let mut block = Block::from_bytes(&mut test_block_bytes().as_slice())?;
+        block.txn_list = vec![tx];
+        let mut blockchain = BlockChain::new(Network::Testnet);
+        blockchain.add(block);
+        let abandoned = wallet_txs.remove_settled(&blockchain, Duration::from_secs(60));
+
+        assert!(abandoned.is_empty());
+        assert!(wallet_txs.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_expired_wallet_txs_are_abandoned() {
+        let tx = wallet_tx();
+        let mut wallet_txs = WalletTxs::new();
+        wallet_txs.track(tx.clone());
+
+        let abandoned =
+            wallet_txs.remove_settled(&BlockChain::new(Network::Testnet), Duration::ZERO);
+
+        assert_eq!(abandoned, vec![tx.get_id(false)]);
+        assert!(wallet_txs.is_empty());
+    }
+}
@@ -0,0 +1,132 @@
+use super::{network_constants::SHUTDOWN_POLL_MILLIS, network_error::NetworkError};
+
+use std::{
+    net::{Shutdown, TcpStream},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Coordinates the shutdown of the node.
+///
+/// The coordinator is meant to be cloned into every long-running thread: all clones share the
+/// same cancellation flag. Once a shutdown is requested, such as by pressing Ctrl-C, the flag is
+/// set and every registered connection is shut down, so the threads blocked reading from them are
+/// released and every thread can finish on its own and be joined.
+#[derive(Debug, Clone, Default)]
+pub struct ShutdownCoordinator {
+    requested: Arc<AtomicBool>,
+    streams: Arc<Mutex<Vec<TcpStream>>>,
+}
+
+impl ShutdownCoordinator {
+    /// Creates a coordinator with no shutdown requested.
+    pub fn new() -> ShutdownCoordinator {
+        ShutdownCoordinator {
+            requested: Arc::new(AtomicBool::new(false)),
+            streams: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Installs the Ctrl-C handler of the process, which requests a shutdown.
+    ///
+    /// # Errors
+    ///
+    /// Returns `NetworkError::SignalHandler` if a handler was already installed.
+    pub fn install_handler(&self) -> Result<(), NetworkError> {
+        let shutdown = self.clone();
+
+        ctrlc::set_handler(move || {
+            println!("\nShutdown requested, the node is stopping...");
+            shutdown.request();
+        })
+        .map_err(|_| NetworkError::SignalHandler)
+    }
+
+    /// Requests a shutdown, shutting down every registered connection.
+    pub fn request(&self) {
+        self.requested.store(true, Ordering::SeqCst);
+
+        if let Ok(locked_streams) = self.streams.lock() {
+            for stream in locked_streams.iter() {
+                let _ = stream.shutdown(Shutdown::Both);
+            }
+        }
+    }
+
+    /// Registers a connection to be shut down once a shutdown is requested. If one was already
+    /// requested, the connection is shut down right away.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - The connection, which is cloned so the caller keeps using it.
+    pub fn register(&self, stream: &TcpStream) -> Result<(), NetworkError> {
+        let stream = stream.try_clone()?;
+
+        if self.is_requested() {
+            let _ = stream.shutdown(Shutdown::Both);
+            return Ok(());
+        }
+
+        self.streams
+            .lock()
+            .map_err(|_| NetworkError::Broadcasting)?
+            .push(stream);
+        Ok(())
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.requested.load(Ordering::SeqCst)
+    }
+
+    /// Sleeps for the given duration, waking up early if a shutdown is requested meanwhile.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the whole duration elapsed, `false` if a shutdown was requested.
+    pub fn sleep(&self, duration: Duration) -> bool {
+        let start = Instant::now();
+        let poll = Duration::from_millis(SHUTDOWN_POLL_MILLIS);
+
+        while !self.is_requested() {
+            let elapsed = start.elapsed();
+            if elapsed >= duration {
+                return true;
+            }
+            thread::sleep(poll.min(duration - elapsed));
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod shutdown_test {
+    use super::*;
+    use std::{io::Read, net::TcpListener};
+
+    #[test]
+    fn test_shutdown_releases_threads() -> Result<(), NetworkError> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let stream = TcpStream::connect(listener.local_addr()?)?;
+        let shutdown = ShutdownCoordinator::new();
+        shutdown.register(&stream)?;
+
+        assert!(shutdown.sleep(Duration::ZERO));
+
+        let sleeper = shutdown.clone();
+        let handle = thread::spawn(move || sleeper.sleep(Duration::from_secs(60)));
+        let mut reader = stream.try_clone()?;
+        let reading = thread::spawn(move || reader.read(&mut [0u8; 1]));
+
+        shutdown.request();
+
+        assert!(!handle.join().map_err(|_| NetworkError::Broadcasting)?);
+        let read = reading.join().map_err(|_| NetworkError::Broadcasting)?;
+        assert!(matches!(read, Ok(0) | Err(_)));
+        assert!(shutdown.is_requested());
+        Ok(())
+    }
+}
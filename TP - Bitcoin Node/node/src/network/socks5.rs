@@ -0,0 +1,234 @@
+use super::{
+    network_constants::{
+        SOCKS5_ADDR_DOMAIN, SOCKS5_ADDR_IPV4, SOCKS5_ADDR_IPV6, SOCKS5_CONNECT, SOCKS5_NO_AUTH,
+        SOCKS5_RESOLVE, SOCKS5_SUCCEEDED, SOCKS5_VERSION, SOCKS_TIMEOUT_SECS,
+    },
+    network_error::NetworkError,
+};
+
+use std::{
+    io::{Read, Write},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream},
+    time::Duration,
+};
+
+/// Represents the destination of a request sent to a SOCKS5 proxy.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SocksTarget {
+    /// An IP address, which is sent as an IPv4 address if it is an IPv4-mapped IPv6 address.
+    Ip(IpAddr),
+    /// A domain name, resolved by the proxy.
+    Domain(String),
+}
+
+impl SocksTarget {
+    /// Serializes the address type and the address of the target, as expected by the proxy.
+    fn to_bytes(&self) -> Result<Vec<u8>, NetworkError> {
+        let ip = match self {
+            SocksTarget::Ip(IpAddr::V6(ip)) => match ip.to_ipv4_mapped() {
+                Some(ipv4) => IpAddr::V4(ipv4),
+                None => IpAddr::V6(*ip),
+            },
+            SocksTarget::Ip(ip) => *ip,
+            SocksTarget::Domain(domain) => {
+                let length = u8::try_from(domain.len()).map_err(|_| NetworkError::HandShake)?;
+                let mut buffer = vec![SOCKS5_ADDR_DOMAIN, length];
+                buffer.extend(domain.as_bytes());
+                return Ok(buffer);
+            }
+        };
+
+        let mut buffer = vec![];
+        match ip {
+            IpAddr::V4(ip) => {
+                buffer.push(SOCKS5_ADDR_IPV4);
+                buffer.extend(ip.octets());
+            }
+            IpAddr::V6(ip) => {
+                buffer.push(SOCKS5_ADDR_IPV6);
+                buffer.extend(ip.octets());
+            }
+        }
+        Ok(buffer)
+    }
+}
+
+/// Opens a connection to the proxy and negotiates a session without authentication.
+fn open_session(proxy: &SocketAddr) -> Result<TcpStream, NetworkError> {
+    let timeout = Duration::from_secs(SOCKS_TIMEOUT_SECS);
+    let mut stream = TcpStream::connect_timeout(proxy, timeout)?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+
+    stream.write_all(&[SOCKS5_VERSION, 1, SOCKS5_NO_AUTH])?;
+
+    let mut answer = [0u8; 2];
+    stream.read_exact(&mut answer)?;
+
+    if answer != [SOCKS5_VERSION, SOCKS5_NO_AUTH] {
+        return Err(NetworkError::HandShake);
+    }
+    Ok(stream)
+}
+
+/// Sends a request to the proxy and reads its reply.
+///
+/// # Returns
+///
+/// The address bound by the proxy, or a `NetworkError` if the proxy refused the request.
+fn send_request(
+    stream: &mut TcpStream,
+    command: u8,
+    target: &SocksTarget,
+    port: u16,
+) -> Result<SocketAddr, NetworkError> {
+    let mut request = vec![SOCKS5_VERSION, command, 0];
+    request.extend(target.to_bytes()?);
+    request.extend(port.to_be_bytes());
+    stream.write_all(&request)?;
+
+    let mut reply = [0u8; 4];
+    stream.read_exact(&mut reply)?;
+
+    if reply[0] != SOCKS5_VERSION || reply[1] != SOCKS5_SUCCEEDED {
+        return Err(NetworkError::HandShake);
+    }
+
+    let ip = match reply[3] {
+        SOCKS5_ADDR_IPV4 => {
+            let mut octets = [0u8; 4];
+            stream.read_exact(&mut octets)?;
+            IpAddr::V4(Ipv4Addr::from(octets))
+        }
+        SOCKS5_ADDR_IPV6 => {
+            let mut octets = [0u8; 16];
+            stream.read_exact(&mut octets)?;
+            IpAddr::V6(Ipv6Addr::from(octets))
+        }
+        _ => return Err(NetworkError::HandShake),
+    };
+
+    let mut port = [0u8; 2];
+    stream.read_exact(&mut port)?;
+
+    Ok(SocketAddr::new(ip, u16::from_be_bytes(port)))
+}
+
+/// Connects to a target through a SOCKS5 proxy, such as Tor.
+///
+/// # Arguments
+///
+/// * `proxy` - The address of the proxy.
+/// * `target` - The address the proxy connects to.
+/// * `port` - The port the proxy connects to.
+///
+/// # Returns
+///
+/// The connection to the target, or a `NetworkError` if the proxy cannot be reached or refused to
+/// connect.
+pub fn connect(
+    proxy: &SocketAddr,
+    target: &SocksTarget,
+    port: u16,
+) -> Result<TcpStream, NetworkError> {
+    let mut stream = open_session(proxy)?;
+    send_request(&mut stream, SOCKS5_CONNECT, target, port)?;
+
+    Ok(stream)
+}
+
+/// Resolves a domain name through a SOCKS5 proxy that supports the RESOLVE extension of Tor, so
+/// DNS queries never leave the proxy.
+///
+/// # Arguments
+///
+/// * `proxy` - The address of the proxy.
+/// * `domain` - The domain name to resolve.
+///
+/// # Returns
+///
+/// The address the domain resolves to, or a `NetworkError` if the proxy cannot be reached or
+/// could not resolve it.
+pub fn resolve(proxy: &SocketAddr, domain: &str) -> Result<IpAddr, NetworkError> {
+    let mut stream = open_session(proxy)?;
+    let target = SocksTarget::Domain(domain.to_string());
+
+    Ok(send_request(&mut stream, SOCKS5_RESOLVE, &target, 0)?.ip())
+}
+
+#[cfg(test)]
+mod socks5_test {
+    use super::*;
+    use std::{net::TcpListener, thread};
+
+    /// Answers a single session of a proxy, returning the request it received.
+    fn answer_session(
+        listener: TcpListener,
+        request_length: usize,
+        reply: Vec<u8>,
+    ) -> thread::JoinHandle<Vec<u8>> {
+        thread::spawn(move || {
+            let (mut stream, _) = match listener.accept() {
+                Ok(connection) => connection,
+                Err(_) => return vec![],
+            };
+
+            let mut greeting = [0u8; 3];
+            let mut request = vec![0u8; request_length];
+            if stream.read_exact(&mut greeting).is_err()
+                || stream.write_all(&[SOCKS5_VERSION, SOCKS5_NO_AUTH]).is_err()
+                || stream.read_exact(&mut request).is_err()
+                || stream.write_all(&reply).is_err()
+            {
+                return vec![];
+            }
+            request
+        })
+    }
+
+    #[test]
+    fn test_connect_through_proxy() -> Result<(), NetworkError> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let proxy = listener.local_addr()?;
+        let reply = vec![5, 0, 0, 1, 0, 0, 0, 0, 0, 0];
+        let handle = answer_session(listener, 10, reply);
+
+        let target = SocksTarget::Ip(IpAddr::V6(Ipv4Addr::new(1, 2, 3, 4).to_ipv6_mapped()));
+        connect(&proxy, &target, 18333)?;
+        let request = handle.join().map_err(|_| NetworkError::HandShake)?;
+
+        assert_eq!(request, vec![5, 1, 0, 1, 1, 2, 3, 4, 0x47, 0x9d]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_through_proxy() -> Result<(), NetworkError> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let proxy = listener.local_addr()?;
+        let reply = vec![5, 0, 0, 1, 5, 6, 7, 8, 0, 0];
+        let handle = answer_session(listener, 11, reply);
+
+        let ip = resolve(&proxy, "seed")?;
+        let request = handle.join().map_err(|_| NetworkError::HandShake)?;
+
+        assert_eq!(ip, IpAddr::V4(Ipv4Addr::new(5, 6, 7, 8)));
+        assert_eq!(
+            request,
+            vec![5, 0xf0, 0, 3, 4, b's', b'e', b'e', b'd', 0, 0]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_refused_connection() -> Result<(), NetworkError> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let proxy = listener.local_addr()?;
+        let reply = vec![5, 5, 0, 1, 0, 0, 0, 0, 0, 0];
+        let handle = answer_session(listener, 10, reply);
+
+        let target = SocksTarget::Ip(IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4)));
+        assert!(connect(&proxy, &target, 18333).is_err());
+        let _ = handle.join();
+        Ok(())
+    }
+}
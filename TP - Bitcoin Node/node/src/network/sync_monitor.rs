@@ -0,0 +1,93 @@
+use super::{
+    network_constants::STALL_CHECK_INTERVAL_SECS, peer_pool::PeerPool,
+    shutdown::ShutdownCoordinator,
+};
+use crate::{block_mod::header_chain::HeaderChain, settings_mod::settings::Settings};
+
+use std::{
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// Returns the peer claiming the highest height, which is rotated out of the pool when the sync
+/// stalls, as long as it is not the only peer left.
+fn peer_to_rotate(peer_pool: &PeerPool) -> Option<SocketAddr> {
+    if peer_pool.len() <= 1 {
+        return None;
+    }
+
+    peer_pool
+        .get_addrs()
+        .into_iter()
+        .max_by_key(|addr| peer_pool.get_peer_heights().get_height(addr))
+}
+
+/// Spawns the thread that watches the sync of the node.
+///
+/// Every `STALL_CHECK_INTERVAL_SECS`, the height of the header chain is compared against the
+/// heights advertised by the connected peers, which are persisted on every check. When the sync
+/// stalls, a warning is printed and the peer claiming the highest height is dropped from the pool,
+/// so the remaining peers take the sync over. The thread finishes once the pool is empty or a
+/// shutdown is requested.
+///
+/// # Arguments
+///
+/// * `settings` - The node settings, holding the stall timeout.
+/// * `peer_pool` - The shared pool of connections.
+/// * `header_chain` - The shared header chain, whose height is the sync progress of the node.
+/// * `shutdown` - The shutdown coordinator of the node.
+pub fn monitor_sync(
+    settings: Arc<Settings>,
+    peer_pool: Arc<Mutex<PeerPool>>,
+    header_chain: Arc<Mutex<HeaderChain>>,
+    shutdown: ShutdownCoordinator,
+) -> JoinHandle<()> {
+    thread::spawn(move || loop {
+        if !shutdown.sleep(Duration::from_secs(STALL_CHECK_INTERVAL_SECS)) {
+            return;
+        }
+
+        let height = match header_chain.lock() {
+            Ok(locked_header_chain) => locked_header_chain.get_height(),
+            Err(_) => return,
+        };
+
+        let mut locked_peer_pool = match peer_pool.lock() {
+            Ok(locked_peer_pool) => locked_peer_pool,
+            Err(_) => return,
+        };
+
+        if locked_peer_pool.is_empty() {
+            return;
+        }
+
+        let peers = locked_peer_pool.get_addrs();
+        let stalled = locked_peer_pool.get_peer_heights_mut().check_stall(
+            height,
+            &peers,
+            settings.get_sync_stall_timeout(),
+        );
+
+        if locked_peer_pool.get_peer_heights().save().is_err() {
+            println!("Attempt to save the heights of the peers has failed.");
+        }
+
+        if !stalled {
+            continue;
+        }
+
+        println!(
+            "Sync stalled: every peer claims to be far ahead of height {}, but no new headers arrived in {} seconds.",
+            height,
+            settings.get_sync_stall_timeout().as_secs()
+        );
+
+        if let Some(addr) = peer_to_rotate(&locked_peer_pool) {
+            println!("Peer {} has been rotated out of the pool.", addr);
+            locked_peer_pool.mark_stale(&addr);
+        }
+        drop(locked_peer_pool);
+    })
+}
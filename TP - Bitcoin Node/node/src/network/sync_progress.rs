@@ -0,0 +1,156 @@
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+/// Keeps the progress of the initial block download, so it can be shown instead of a silent wait.
+///
+/// The progress is meant to be shared as an `Arc<Mutex<SyncProgress>>`: the headers download
+/// updates the height of the header chain, and the block download threads the amount of blocks
+/// and bytes downloaded. The download rate and the estimated time left are computed from the time
+/// elapsed since the block download started.
+#[derive(Debug)]
+pub struct SyncProgress {
+    headers_height: u32,
+    blocks_downloaded: usize,
+    blocks_total: usize,
+    bytes_downloaded: u64,
+    blocks_started: Option<Instant>,
+}
+
+impl SyncProgress {
+    /// Creates the progress of a sync that has not started yet.
+    pub fn new() -> SyncProgress {
+        SyncProgress {
+            headers_height: 0,
+            blocks_downloaded: 0,
+            blocks_total: 0,
+            bytes_downloaded: 0,
+            blocks_started: None,
+        }
+    }
+
+    /// Records the height of the last header downloaded.
+    pub fn set_headers_height(&mut self, headers_height: u32) {
+        self.headers_height = headers_height;
+    }
+
+    /// Starts the block download, which has to download the given amount of blocks.
+    pub fn start_blocks(&mut self, blocks_total: usize) {
+        self.blocks_downloaded = 0;
+        self.blocks_total = blocks_total;
+        self.bytes_downloaded = 0;
+        self.blocks_started = Some(Instant::now());
+    }
+
+    /// Records a downloaded block.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The size of the block.
+    pub fn add_block(&mut self, bytes: u64) {
+        self.blocks_downloaded += 1;
+        self.bytes_downloaded += bytes;
+    }
+
+    pub fn get_headers_height(&self) -> u32 {
+        self.headers_height
+    }
+
+    pub fn get_blocks_downloaded(&self) -> usize {
+        self.blocks_downloaded
+    }
+
+    pub fn get_blocks_total(&self) -> usize {
+        self.blocks_total
+    }
+
+    /// Returns the amount of bytes per second downloaded since the block download started.
+    pub fn get_bytes_per_sec(&self) -> u64 {
+        let elapsed = match self.blocks_started {
+            Some(started) => started.elapsed().as_secs_f64(),
+            None => return 0,
+        };
+
+        if elapsed <= 0.0 {
+            return 0;
+        }
+        (self.bytes_downloaded as f64 / elapsed) as u64
+    }
+
+    /// Returns the estimated time left to download the remaining blocks at the rate they were
+    /// downloaded so far, or `None` if no block has been downloaded yet.
+    pub fn get_eta(&self) -> Option<Duration> {
+        let started = self.blocks_started?;
+        if self.blocks_downloaded == 0 {
+            return None;
+        }
+
+        let remaining = self.blocks_total.saturating_sub(self.blocks_downloaded);
+        Some(
+            started
+                .elapsed()
+                .mul_f64(remaining as f64 / self.blocks_downloaded as f64),
+        )
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.blocks_started.is_some() && self.blocks_downloaded >= self.blocks_total
+    }
+}
+
+impl Default for SyncProgress {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for SyncProgress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Headers height: {}", self.headers_height)?;
+
+        if self.blocks_started.is_none() {
+            return Ok(());
+        }
+
+        write!(
+            f,
+            ", blocks: {}/{}, {} kB/s",
+            self.blocks_downloaded,
+            self.blocks_total,
+            self.get_bytes_per_sec() / 1000
+        )?;
+
+        match self.get_eta() {
+            Some(eta) => write!(f, ", ETA: {}s", eta.as_secs()),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod sync_progress_test {
+    use super::*;
+
+    #[test]
+    fn test_sync_progress_counts_downloaded_blocks() {
+        let mut progress = SyncProgress::new();
+        progress.set_headers_height(2_500_000);
+        assert_eq!(progress.get_eta(), None);
+        assert!(!progress.is_done());
+
+        progress.start_blocks(2);
+        assert_eq!(progress.get_eta(), None);
+
+        progress.add_block(1000);
+        assert_eq!(progress.get_blocks_downloaded(), 1);
+        assert!(progress.get_eta().is_some());
+
+        progress.add_block(1000);
+        assert_eq!(progress.get_eta(), Some(Duration::ZERO));
+        assert!(progress.is_done());
+        assert!(progress
+            .to_string()
+            .starts_with("Headers height: 2500000, blocks: 2/2"));
+    }
+}
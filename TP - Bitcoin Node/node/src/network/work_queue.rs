@@ -0,0 +1,165 @@
+use crate::messages::inventory::Inventory;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    net::SocketAddr,
+};
+
+/// Represents the queue of block batches shared by the block download threads.
+///
+/// Idle peer threads take the next pending batch, so faster peers end up downloading more
+/// batches. Batches taken by peers that stall are handed back to the queue, so other peers can
+/// download them. Blocks a peer answered with `notfound` are handed back as well, and never given
+/// to that peer again.
+#[derive(Debug)]
+pub struct WorkQueue {
+    pending: VecDeque<Vec<Inventory>>,
+    in_flight: usize,
+    not_found: HashMap<Vec<u8>, HashSet<SocketAddr>>,
+}
+
+impl WorkQueue {
+    /// Creates a new work queue by splitting the inventories into batches.
+    ///
+    /// # Arguments
+    ///
+    /// * `inventories` - The inventories of the blocks to download.
+    /// * `batch_size` - The amount of inventories requested to a peer at once.
+    pub fn new(inventories: Vec<Inventory>, batch_size: usize) -> WorkQueue {
+        WorkQueue {
+            pending: inventories
+                .chunks(batch_size.max(1))
+                .map(|batch| batch.to_vec())
+                .collect(),
+            in_flight: 0,
+            not_found: HashMap::new(),
+        }
+    }
+
+    /// Takes the next pending batch the given peer did not report as not found, marking it as in
+    /// flight.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer` - The address of the peer the batch is taken for.
+    ///
+    /// # Returns
+    ///
+    /// The next batch, or `None` if there are no pending batches the peer may download.
+    pub fn take(&mut self, peer: &SocketAddr) -> Option<Vec<Inventory>> {
+        let position = self.pending.iter().position(|batch| {
+            batch.iter().all(|inventory| {
+                self.not_found
+                    .get(&inventory.get_data())
+                    .is_none_or(|peers| !peers.contains(peer))
+            })
+        })?;
+
+        let batch = self.pending.remove(position)?;
+        self.in_flight += 1;
+        Some(batch)
+    }
+
+    /// Marks an in flight batch as completely downloaded.
+    pub fn complete(&mut self) {
+        self.in_flight = self.in_flight.saturating_sub(1);
+    }
+
+    /// Hands back the part of an in flight batch that could not be downloaded.
+    ///
+    /// # Arguments
+    ///
+    /// * `batch` - The inventories that are still missing.
+    pub fn requeue(&mut self, batch: Vec<Inventory>) {
+        self.in_flight = self.in_flight.saturating_sub(1);
+
+        if !batch.is_empty() {
+            self.pending.push_front(batch);
+        }
+    }
+
+    /// Hands back the blocks a peer answered with `notfound`, so they are downloaded from other
+    /// peers. The batch they belonged to must have been completed or requeued beforehand.
+    ///
+    /// # Arguments
+    ///
+    /// * `batch` - The inventories the peer does not have.
+    /// * `peer` - The address of the peer, which will not be given these blocks again.
+    pub fn hand_off(&mut self, batch: Vec<Inventory>, peer: SocketAddr) {
+        if batch.is_empty() {
+            return;
+        }
+
+        for inventory in batch.iter() {
+            self.not_found
+                .entry(inventory.get_data())
+                .or_default()
+                .insert(peer);
+        }
+        self.pending.push_back(batch);
+    }
+
+    /// Returns whether no batch is in flight, in which case the pending batches a peer cannot take
+    /// will never be downloaded by it.
+    pub fn is_idle(&self) -> bool {
+        self.in_flight == 0
+    }
+
+    /// Returns whether every batch has been downloaded.
+    ///
+    /// The queue may be momentarily empty while other peers still have batches in flight, which
+    /// could be handed back if those peers stall.
+    pub fn is_done(&self) -> bool {
+        self.pending.is_empty() && self.in_flight == 0
+    }
+
+    pub fn get_pending_count(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+#[cfg(test)]
+mod work_queue_test {
+    use super::*;
+
+    #[test]
+    fn test_stalled_batches_are_requeued() {
+        let inventories = (0..5).map(|i| Inventory::new(2, vec![i; 32])).collect();
+        let mut work_queue = WorkQueue::new(inventories, 2);
+
+        assert_eq!(work_queue.get_pending_count(), 3);
+
+        let peer = SocketAddr::from(([127, 0, 0, 1], 18333));
+        let stalled = work_queue.take(&peer).unwrap_or_default();
+        let completed = work_queue.take(&peer).unwrap_or_default();
+        work_queue.complete();
+        work_queue.requeue(stalled[1..].to_vec());
+
+        assert_eq!(completed.len(), 2);
+        assert_eq!(work_queue.take(&peer), Some(stalled[1..].to_vec()));
+        assert_eq!(work_queue.take(&peer).map(|batch| batch.len()), Some(1));
+        assert!(!work_queue.is_done());
+
+        work_queue.complete();
+        work_queue.complete();
+        assert!(work_queue.is_done());
+    }
+
+    #[test]
+    fn test_not_found_blocks_are_handed_to_other_peers() {
+        let inventories = (0..2).map(|i| Inventory::new(2, vec![i; 32])).collect();
+        let mut work_queue = WorkQueue::new(inventories, 2);
+        let first_peer = SocketAddr::from(([127, 0, 0, 1], 18333));
+        let second_peer = SocketAddr::from(([127, 0, 0, 2], 18333));
+
+        let batch = work_queue.take(&first_peer).unwrap_or_default();
+        work_queue.complete();
+        work_queue.hand_off(batch[1..].to_vec(), first_peer);
+
+        assert!(work_queue.is_idle());
+        assert_eq!(work_queue.take(&first_peer), None);
+        assert_eq!(work_queue.take(&second_peer), Some(batch[1..].to_vec()));
+
+        work_queue.complete();
+        assert!(work_queue.is_done());
+    }
+}
@@ -1,7 +1,7 @@
 use super::proof_of_inclusion_error::ProofOfInclusionError;
 use crate::{
-    block_mod::blockchain::BlockChain, messages::read_from_bytes::fill_command,
-    wallet_utils::merkle_block::MerkleBlock,
+    block_mod::blockchain::BlockChain,
+    wallet_utils::{merkle_block::MerkleBlock, wallet_message::WalletResponse},
 };
 use bitcoin_hashes::{sha256d, Hash};
 use std::collections::HashSet;
@@ -129,8 +129,16 @@ fn calculate_merkle_root(txn_list: &mut Vec<Vec<u8>>, levels: &mut Vec<Vec<Vec<u
 }
 
 fn not_found(stream: &mut dyn Write) -> Result<(), ProofOfInclusionError> {
-    let not_found = "not_found".to_string();
-    let buffer = fill_command(&not_found).as_bytes().to_vec();
+    write_response(stream, WalletResponse::NotFound)
+}
+
+fn write_response(
+    stream: &mut dyn Write,
+    response: WalletResponse,
+) -> Result<(), ProofOfInclusionError> {
+    let buffer = response
+        .to_bytes()
+        .map_err(|_| ProofOfInclusionError::WriteError)?;
     stream
         .write(&buffer)
         .map_err(|_| ProofOfInclusionError::WriteError)?;
@@ -195,11 +203,8 @@ pub fn send_proof(
     println!("antes del calculate merkle proof\n");
     let proof = calculate_merkle_proof(&levels, txid_path(i, &levels), merkle_root);
     println!("Proof: {:?}\n", proof);
-    stream
-        .write(&proof.to_bytes())
-        .map_err(|_| ProofOfInclusionError::WriteError)?;
     drop(blockchain);
-    Ok(())
+    write_response(stream, WalletResponse::MerkleBlock(proof))
 }
 
 #[cfg(test)]
@@ -1,23 +1,47 @@
 use crate::messages::read_from_bytes::decode_hex;
+use crate::network::bitcoin_network::Network;
 use crate::settings_mod::settings_constants::*;
 use crate::settings_mod::settings_error::SettingError;
 use std::collections::HashMap;
-use std::net::Ipv6Addr;
+use std::net::{IpAddr, SocketAddr};
 use std::str::FromStr;
+use std::time::Duration;
 use std::{env, fs};
 
 /// Configuration settings for network communication.
+///
+/// The DNS seed, port and start string default to the ones of the configured network, so
+/// switching networks only takes the `network` field.
 #[derive(Debug)]
 pub struct Settings {
+    network: Network,
     dns_seed: String,
     protocol_version: i32,
     services: u64,
     port: u16,
-    ip: Ipv6Addr,
+    ip: IpAddr,
     user_agent: String,
     start_height: i32,
     relay: bool,
     start_string: Vec<u8>,
+    max_outbound: usize,
+    checkpoint_sync: bool,
+    read_timeout_secs: u64,
+    write_timeout_secs: u64,
+    min_relay_fee: u64,
+    sync_stall_timeout_secs: u64,
+    rebroadcast_interval_secs: u64,
+    proxy: Option<SocketAddr>,
+    max_upload_kbps: u64,
+    max_download_kbps: u64,
+    max_mempool_mb: usize,
+    mempool_expiry_hours: u64,
+    connect_only: Vec<SocketAddr>,
+    wallet_auth_token: Option<String>,
+    capture_file: Option<String>,
+    txindex: bool,
+    load_snapshot: Option<String>,
+    dump_snapshot: Option<String>,
 }
 
 impl Settings {
@@ -80,11 +104,12 @@ impl Settings {
         let file = fs::read_to_string(path)?;
 
         for line in file.lines() {
-            let token: Vec<&str> = line.split(EQUAL).collect();
+            let token: Vec<&str> = line.splitn(2, EQUAL).collect();
 
             if matches!(
                 token[0],
-                DNS_SEED
+                NETWORK
+                    | DNS_SEED
                     | PROCOCOL_VERSION
                     | SERVICES
                     | PORT
@@ -93,6 +118,24 @@ impl Settings {
                     | START_HEIGHT
                     | RELAY
                     | START_STRING
+                    | MAX_OUTBOUND
+                    | CHECKPOINT_SYNC
+                    | READ_TIMEOUT_SECS
+                    | WRITE_TIMEOUT_SECS
+                    | MIN_RELAY_FEE
+                    | SYNC_STALL_TIMEOUT_SECS
+                    | REBROADCAST_INTERVAL_SECS
+                    | PROXY
+                    | MAX_UPLOAD_KBPS
+                    | MAX_DOWNLOAD_KBPS
+                    | MAX_MEMPOOL_MB
+                    | MEMPOOL_EXPIRY_HOURS
+                    | CONNECT_ONLY
+                    | WALLET_AUTH_TOKEN
+                    | CAPTURE_FILE
+                    | TXINDEX
+                    | LOAD_SNAPSHOT
+                    | DUMP_SNAPSHOT
             ) {
                 parser_config.insert(token[0].to_string(), token[1].to_string());
             } else {
@@ -102,70 +145,258 @@ impl Settings {
 
         //println!("Archivo en hash map: {:?}", parser_config);
 
+        let network = match parser_config.get(NETWORK) {
+            Some(network) => Network::from_name(network).ok_or(SettingError::FieldNotFound)?,
+            None => Network::default(),
+        };
+
         Ok(Settings {
-            dns_seed: parser_config
-                .get(DNS_SEED)
-                .ok_or(SettingError::FieldNotFound)?
-                .to_string(),
+            network,
+            dns_seed: match parser_config.get(DNS_SEED) {
+                Some(dns_seed) => dns_seed.to_string(),
+                None => network.get_dns_seed().to_string(),
+            },
             protocol_version: i32::from_str(
                 parser_config
                     .get(PROCOCOL_VERSION)
                     .ok_or(SettingError::FieldNotFound)?,
             )?,
-            services: parser_config
-                .get(SERVICES)
-                .ok_or(SettingError::FieldNotFound)?
-                .parse()?,
-            port: parser_config
-                .get(PORT)
-                .ok_or(SettingError::FieldNotFound)?
-                .parse()?,
-            ip: Ipv6Addr::from_str(parser_config.get(IP).ok_or(SettingError::FieldNotFound)?)?,
-            user_agent: parser_config
-                .get(USER_AGENT)
-                .ok_or(SettingError::FieldNotFound)?
-                .to_string(),
+            services: match parser_config.get(SERVICES) {
+                Some(services) => parse_services(services)?,
+                None => DEFAULT_SERVICES,
+            },
+            port: match parser_config.get(PORT) {
+                Some(port) => port.parse()?,
+                None => network.get_default_port(),
+            },
+            ip: IpAddr::from_str(parser_config.get(IP).ok_or(SettingError::FieldNotFound)?)?,
+            user_agent: match parser_config.get(USER_AGENT) {
+                Some(user_agent) => user_agent.trim().to_string(),
+                None => DEFAULT_USER_AGENT.to_string(),
+            },
             start_height: parser_config
                 .get(START_HEIGHT)
                 .ok_or(SettingError::FieldNotFound)?
                 .parse()?,
-            relay: parser_config
-                .get(RELAY)
-                .ok_or(SettingError::FieldNotFound)?
-                .parse()?,
-            start_string: decode_hex(
-                parser_config
-                    .get(START_STRING)
-                    .ok_or(SettingError::FieldNotFound)?,
-            )?,
+            relay: match parser_config.get(RELAY) {
+                Some(relay) => relay.parse()?,
+                None => DEFAULT_RELAY,
+            },
+            start_string: match parser_config.get(START_STRING) {
+                Some(start_string) => decode_hex(start_string)?,
+                None => network.get_start_string(),
+            },
+            max_outbound: match parser_config.get(MAX_OUTBOUND) {
+                Some(max_outbound) => max_outbound.parse()?,
+                None => DEFAULT_MAX_OUTBOUND,
+            },
+            checkpoint_sync: match parser_config.get(CHECKPOINT_SYNC) {
+                Some(checkpoint_sync) => checkpoint_sync.parse()?,
+                None => DEFAULT_CHECKPOINT_SYNC,
+            },
+            read_timeout_secs: match parser_config.get(READ_TIMEOUT_SECS) {
+                Some(read_timeout_secs) => read_timeout_secs.parse()?,
+                None => DEFAULT_READ_TIMEOUT_SECS,
+            },
+            write_timeout_secs: match parser_config.get(WRITE_TIMEOUT_SECS) {
+                Some(write_timeout_secs) => write_timeout_secs.parse()?,
+                None => DEFAULT_WRITE_TIMEOUT_SECS,
+            },
+            min_relay_fee: match parser_config.get(MIN_RELAY_FEE) {
+                Some(min_relay_fee) => min_relay_fee.parse()?,
+                None => DEFAULT_MIN_RELAY_FEE,
+            },
+            sync_stall_timeout_secs: match parser_config.get(SYNC_STALL_TIMEOUT_SECS) {
+                Some(sync_stall_timeout_secs) => sync_stall_timeout_secs.parse()?,
+                None => DEFAULT_SYNC_STALL_TIMEOUT_SECS,
+            },
+            rebroadcast_interval_secs: match parser_config.get(REBROADCAST_INTERVAL_SECS) {
+                Some(rebroadcast_interval_secs) => rebroadcast_interval_secs.parse()?,
+                None => DEFAULT_REBROADCAST_INTERVAL_SECS,
+            },
+            proxy: match parser_config.get(PROXY) {
+                Some(proxy) => Some(proxy.parse()?),
+                None => None,
+            },
+            max_upload_kbps: match parser_config.get(MAX_UPLOAD_KBPS) {
+                Some(max_upload_kbps) => max_upload_kbps.parse()?,
+                None => DEFAULT_MAX_UPLOAD_KBPS,
+            },
+            max_download_kbps: match parser_config.get(MAX_DOWNLOAD_KBPS) {
+                Some(max_download_kbps) => max_download_kbps.parse()?,
+                None => DEFAULT_MAX_DOWNLOAD_KBPS,
+            },
+            max_mempool_mb: match parser_config.get(MAX_MEMPOOL_MB) {
+                Some(max_mempool_mb) => max_mempool_mb.parse()?,
+                None => DEFAULT_MAX_MEMPOOL_MB,
+            },
+            mempool_expiry_hours: match parser_config.get(MEMPOOL_EXPIRY_HOURS) {
+                Some(mempool_expiry_hours) => mempool_expiry_hours.parse()?,
+                None => DEFAULT_MEMPOOL_EXPIRY_HOURS,
+            },
+            connect_only: match parser_config.get(CONNECT_ONLY) {
+                Some(connect_only) => connect_only
+                    .split(LIST_SEPARATOR)
+                    .map(str::trim)
+                    .filter(|addr| !addr.is_empty())
+                    .map(SocketAddr::from_str)
+                    .collect::<Result<Vec<SocketAddr>, _>>()?,
+                None => vec![],
+            },
+            wallet_auth_token: parser_config
+                .get(WALLET_AUTH_TOKEN)
+                .map(|token| token.trim().to_string())
+                .filter(|token| !token.is_empty()),
+            capture_file: parser_config
+                .get(CAPTURE_FILE)
+                .map(|path| path.trim().to_string())
+                .filter(|path| !path.is_empty()),
+            txindex: match parser_config.get(TXINDEX) {
+                Some(txindex) => txindex.parse()?,
+                None => DEFAULT_TXINDEX,
+            },
+            load_snapshot: parser_config
+                .get(LOAD_SNAPSHOT)
+                .map(|path| path.trim().to_string())
+                .filter(|path| !path.is_empty()),
+            dump_snapshot: parser_config
+                .get(DUMP_SNAPSHOT)
+                .map(|path| path.trim().to_string())
+                .filter(|path| !path.is_empty()),
         })
     }
 
+    pub fn get_network(&self) -> Network {
+        self.network
+    }
     pub fn get_dns_seed(&self) -> &String {
         &self.dns_seed
     }
     pub fn get_protocol_version(&self) -> i32 {
         self.protocol_version
     }
+    /// Returns the service bits the node advertises in its version messages.
     pub fn get_services(&self) -> u64 {
         self.services
     }
     pub fn get_port(&self) -> u16 {
         self.port
     }
-    pub fn get_ip(&self) -> Ipv6Addr {
+    pub fn get_ip(&self) -> IpAddr {
         self.ip
     }
+    /// Returns the user agent the node identifies itself with (BIP14).
     pub fn get_user_agent(&self) -> String {
         self.user_agent.clone()
     }
     pub fn get_start_height(&self) -> i32 {
         self.start_height
     }
+    /// Returns whether peers are asked to announce their transactions to the node.
     pub fn get_relay(&self) -> bool {
         self.relay
     }
     pub fn get_start_string(&self) -> Vec<u8> {
         self.start_string.clone()
     }
+    pub fn get_max_outbound(&self) -> usize {
+        self.max_outbound
+    }
+    pub fn get_checkpoint_sync(&self) -> bool {
+        self.checkpoint_sync
+    }
+    pub fn get_read_timeout(&self) -> Duration {
+        Duration::from_secs(self.read_timeout_secs)
+    }
+    pub fn get_write_timeout(&self) -> Duration {
+        Duration::from_secs(self.write_timeout_secs)
+    }
+    /// Returns the minimum fee rate, in satoshis per kilobyte, of the transactions the node relays.
+    pub fn get_min_relay_fee(&self) -> u64 {
+        self.min_relay_fee
+    }
+    /// Returns how long the node may stay behind every peer without syncing before the sync is
+    /// considered stalled.
+    pub fn get_sync_stall_timeout(&self) -> Duration {
+        Duration::from_secs(self.sync_stall_timeout_secs)
+    }
+    /// Returns how long the node waits before broadcasting again the wallet transactions that are
+    /// not confirmed yet.
+    pub fn get_rebroadcast_interval(&self) -> Duration {
+        Duration::from_secs(self.rebroadcast_interval_secs)
+    }
+    /// Returns the address of the SOCKS5 proxy every outbound connection goes through, if any.
+    pub fn get_proxy(&self) -> Option<SocketAddr> {
+        self.proxy
+    }
+    /// Returns the maximum amount of bytes per second sent to the peers, 0 meaning no limit.
+    pub fn get_max_upload_rate(&self) -> u64 {
+        self.max_upload_kbps * 1000
+    }
+    /// Returns the maximum amount of bytes per second received from the peers, 0 meaning no limit.
+    pub fn get_max_download_rate(&self) -> u64 {
+        self.max_download_kbps * 1000
+    }
+    /// Returns the maximum sum of the virtual sizes of the transactions in the mempool, in bytes.
+    pub fn get_max_mempool_size(&self) -> usize {
+        self.max_mempool_mb * 1_000_000
+    }
+    /// Returns how long a transaction may stay in the mempool without being confirmed.
+    pub fn get_mempool_expiry(&self) -> Duration {
+        Duration::from_secs(self.mempool_expiry_hours * 3600)
+    }
+    /// Returns the addresses of the only peers the node connects to. If empty, the peers are
+    /// discovered through the DNS seed.
+    pub fn get_connect_only(&self) -> &Vec<SocketAddr> {
+        &self.connect_only
+    }
+    /// Returns the token the wallet must authenticate with. If `None`, the wallet is not
+    /// authenticated.
+    pub fn get_wallet_auth_token(&self) -> Option<&String> {
+        self.wallet_auth_token.as_ref()
+    }
+    /// Returns the path of the file every message exchanged with the peers is recorded to. If
+    /// `None`, messages are not recorded.
+    pub fn get_capture_file(&self) -> Option<&String> {
+        self.capture_file.as_ref()
+    }
+    /// Returns whether every transaction is indexed by its ID, so it is found without walking the
+    /// chain.
+    pub fn get_txindex(&self) -> bool {
+        self.txindex
+    }
+    /// Returns the path of the UTXO snapshot a fresh node is bootstrapped from. If `None`, the
+    /// UTXO set is built by connecting every block.
+    pub fn get_load_snapshot(&self) -> Option<&String> {
+        self.load_snapshot.as_ref()
+    }
+    /// Returns the path the UTXO set is dumped to as a snapshot when the node stops. If `None`,
+    /// no snapshot is dumped.
+    pub fn get_dump_snapshot(&self) -> Option<&String> {
+        self.dump_snapshot.as_ref()
+    }
+}
+
+/// Parses the advertised services, given either as their bits or as a comma-separated list of
+/// service names, such as `NODE_NETWORK,NODE_WITNESS`.
+///
+/// # Errors
+///
+/// Returns `SettingError::FieldNotFound` if a service name is unknown.
+fn parse_services(services: &str) -> Result<u64, SettingError> {
+    if let Ok(bits) = services.trim().parse() {
+        return Ok(bits);
+    }
+
+    services
+        .split(LIST_SEPARATOR)
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .try_fold(0, |bits, name| {
+            SERVICE_NAMES
+                .iter()
+                .find(|(service_name, _)| *service_name == name)
+                .map(|(_, bit)| bits | bit)
+                .ok_or(SettingError::FieldNotFound)
+        })
 }
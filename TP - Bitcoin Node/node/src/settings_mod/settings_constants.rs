@@ -1,3 +1,8 @@
+use crate::network::network_constants::{
+    NODE_BLOOM, NODE_NETWORK, NODE_NETWORK_LIMITED, NODE_WITNESS,
+};
+
+pub const NETWORK: &str = "network";
 pub const DNS_SEED: &str = "dns_seed";
 pub const PROCOCOL_VERSION: &str = "protocol_version";
 pub const SERVICES: &str = "services";
@@ -7,5 +12,46 @@ pub const USER_AGENT: &str = "user_agent";
 pub const START_HEIGHT: &str = "start_height";
 pub const RELAY: &str = "relay";
 pub const START_STRING: &str = "start_string";
+pub const MAX_OUTBOUND: &str = "max_outbound";
+pub const CHECKPOINT_SYNC: &str = "checkpoint_sync";
+pub const READ_TIMEOUT_SECS: &str = "read_timeout_secs";
+pub const WRITE_TIMEOUT_SECS: &str = "write_timeout_secs";
+pub const MIN_RELAY_FEE: &str = "min_relay_fee";
+pub const SYNC_STALL_TIMEOUT_SECS: &str = "sync_stall_timeout_secs";
+pub const REBROADCAST_INTERVAL_SECS: &str = "rebroadcast_interval_secs";
+pub const PROXY: &str = "proxy";
+pub const MAX_UPLOAD_KBPS: &str = "max_upload_kbps";
+pub const MAX_DOWNLOAD_KBPS: &str = "max_download_kbps";
+pub const MAX_MEMPOOL_MB: &str = "max_mempool_mb";
+pub const MEMPOOL_EXPIRY_HOURS: &str = "mempool_expiry_hours";
+pub const CONNECT_ONLY: &str = "connect_only";
+pub const WALLET_AUTH_TOKEN: &str = "wallet_auth_token";
+pub const CAPTURE_FILE: &str = "capture_file";
+pub const TXINDEX: &str = "txindex";
+pub const LOAD_SNAPSHOT: &str = "load_snapshot";
+pub const DUMP_SNAPSHOT: &str = "dump_snapshot";
 
 pub const EQUAL: &str = "=";
+pub const LIST_SEPARATOR: &str = ",";
+pub const SERVICE_NAMES: [(&str, u64); 4] = [
+    ("NODE_NETWORK", NODE_NETWORK),
+    ("NODE_BLOOM", NODE_BLOOM),
+    ("NODE_WITNESS", NODE_WITNESS),
+    ("NODE_NETWORK_LIMITED", NODE_NETWORK_LIMITED),
+];
+
+pub const DEFAULT_SERVICES: u64 = NODE_NETWORK | NODE_WITNESS;
+pub const DEFAULT_USER_AGENT: &str = concat!("/node:", env!("CARGO_PKG_VERSION"), "/");
+pub const DEFAULT_RELAY: bool = true;
+pub const DEFAULT_MAX_OUTBOUND: usize = 8;
+pub const DEFAULT_CHECKPOINT_SYNC: bool = false;
+pub const DEFAULT_READ_TIMEOUT_SECS: u64 = 300;
+pub const DEFAULT_WRITE_TIMEOUT_SECS: u64 = 30;
+pub const DEFAULT_MIN_RELAY_FEE: u64 = 1000;
+pub const DEFAULT_SYNC_STALL_TIMEOUT_SECS: u64 = 600;
+pub const DEFAULT_REBROADCAST_INTERVAL_SECS: u64 = 900;
+pub const DEFAULT_MAX_UPLOAD_KBPS: u64 = 0;
+pub const DEFAULT_MAX_DOWNLOAD_KBPS: u64 = 0;
+pub const DEFAULT_MAX_MEMPOOL_MB: usize = 300;
+pub const DEFAULT_MEMPOOL_EXPIRY_HOURS: u64 = 336;
+pub const DEFAULT_TXINDEX: bool = false;
@@ -1,10 +1,42 @@
+pub mod auth_result;
+pub mod balance;
+pub mod block_filters;
+pub mod block_status;
 pub mod broadcast_txn;
+pub mod event_batch;
+pub mod event_log;
+pub mod get_balance;
+pub mod get_block;
+pub mod get_filters;
+pub mod get_headers_since;
+pub mod get_invalid_blocks;
+pub mod get_node_info;
+pub mod get_peer_info;
 pub mod get_proof;
+pub mod get_transaction;
 pub mod get_transactions;
+pub mod get_utxos;
+pub mod invalid_blocks_info;
 pub mod merkle_block;
+pub mod node_info;
+pub mod peer_info;
+pub mod raw_block;
+pub mod reconsider_block;
+pub mod resume_events;
+pub mod send_raw_tx;
+pub mod submit_block;
+pub mod subscribe;
+pub mod transaction_info;
 pub mod transactions;
 pub mod tx_filter;
 pub mod tx_filter_error;
+pub mod tx_status;
 pub mod update_wallet;
 pub mod update_wallet_error;
+pub mod utxos;
+pub mod wallet_auth;
+pub mod wallet_frame;
+pub mod wallet_headers;
+pub mod wallet_message;
 pub mod wallet_tx;
+pub mod wallet_version;
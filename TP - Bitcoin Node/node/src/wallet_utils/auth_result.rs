@@ -0,0 +1,66 @@
+use crate::messages::command::Command;
+use std::io::Read;
+
+use crate::messages::{message_error::MessageError, read_from_bytes::read_u8_from_bytes};
+
+/// Represents an "auth_result" message, the answer to a "wallet_auth" message.
+///
+/// # Fields
+///
+/// - `command_name`: The command of the message.
+/// - `accepted`: Whether the node accepted the token of the wallet. If not, the node closes the
+///   connection right after sending the message.
+#[derive(Debug, PartialEq)]
+pub struct AuthResult {
+    command_name: Command,
+    accepted: bool,
+}
+
+impl AuthResult {
+    /// Creates a new `AuthResult` message.
+    ///
+    /// # Arguments
+    ///
+    /// - `accepted`: Whether the node accepted the token of the wallet.
+    pub fn new(accepted: bool) -> AuthResult {
+        AuthResult {
+            command_name: Command::AuthResult,
+            accepted,
+        }
+    }
+
+    /// Parses an `AuthResult` message from the given byte stream.
+    ///
+    /// # Arguments
+    ///
+    /// - `command_name`: The command of the message.
+    /// - `stream`: A mutable reference to a byte stream from which to parse the message.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `AuthResult` message, or a `MessageError` if the result
+    /// cannot be read.
+    pub fn from_bytes(
+        command_name: Command,
+        stream: &mut dyn Read,
+    ) -> Result<AuthResult, MessageError> {
+        let accepted = read_u8_from_bytes(stream)? != 0;
+
+        Ok(AuthResult {
+            command_name,
+            accepted,
+        })
+    }
+
+    /// Serializes the `AuthResult` message into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.command_name.to_bytes();
+        buffer.push(self.accepted as u8);
+
+        buffer
+    }
+
+    pub fn is_accepted(&self) -> bool {
+        self.accepted
+    }
+}
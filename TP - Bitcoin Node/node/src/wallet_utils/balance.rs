@@ -0,0 +1,112 @@
+use crate::messages::command::Command;
+use std::io::Read;
+
+use crate::messages::{
+    message_error::MessageError,
+    read_from_bytes::{read_i64_from_bytes, read_u64_from_bytes},
+};
+
+/// Represents a "balance" message, the answer to a "getbalance" message.
+///
+/// # Fields
+///
+/// - `command_name`: The command of the message.
+/// - `confirmed`: The value of the confirmed outputs that can be spent by the next block, in
+///   satoshis.
+/// - `unconfirmed`: How much the transactions of the mempool change the balance, in satoshis: the
+///   value they pay to the scripts minus the value of the outputs of the scripts they spend.
+/// - `immature`: The value of the confirmed coinbase outputs that have not matured yet, in
+///   satoshis.
+#[derive(Debug, PartialEq)]
+pub struct Balance {
+    command_name: Command,
+    confirmed: u64,
+    unconfirmed: i64,
+    immature: u64,
+}
+
+impl Balance {
+    /// Creates a new `Balance` message.
+    ///
+    /// # Arguments
+    ///
+    /// - `confirmed`: The value of the confirmed outputs that can be spent by the next block.
+    /// - `unconfirmed`: How much the transactions of the mempool change the balance.
+    /// - `immature`: The value of the confirmed coinbase outputs that have not matured yet.
+    pub fn new(confirmed: u64, unconfirmed: i64, immature: u64) -> Balance {
+        Balance {
+            command_name: Command::Balance,
+            confirmed,
+            unconfirmed,
+            immature,
+        }
+    }
+
+    /// Parses a `Balance` message from the given byte stream.
+    ///
+    /// # Arguments
+    ///
+    /// - `command_name`: The command of the message.
+    /// - `stream`: A mutable reference to a byte stream from which to parse the message.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `Balance` message, or a `MessageError` if the amounts
+    /// cannot be read.
+    pub fn from_bytes(
+        command_name: Command,
+        stream: &mut dyn Read,
+    ) -> Result<Balance, MessageError> {
+        let confirmed = read_u64_from_bytes(stream, true)?;
+        let unconfirmed = read_i64_from_bytes(stream, true)?;
+        let immature = read_u64_from_bytes(stream, true)?;
+
+        Ok(Balance {
+            command_name,
+            confirmed,
+            unconfirmed,
+            immature,
+        })
+    }
+
+    /// Serializes the `Balance` message into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.command_name.to_bytes();
+        buffer.extend(self.confirmed.to_le_bytes());
+        buffer.extend(self.unconfirmed.to_le_bytes());
+        buffer.extend(self.immature.to_le_bytes());
+
+        buffer
+    }
+
+    pub fn get_confirmed(&self) -> u64 {
+        self.confirmed
+    }
+
+    pub fn get_unconfirmed(&self) -> i64 {
+        self.unconfirmed
+    }
+
+    pub fn get_immature(&self) -> u64 {
+        self.immature
+    }
+}
+
+#[cfg(test)]
+mod balance_test {
+    use super::*;
+
+    #[test]
+    fn test_balance_to_bytes_and_from_bytes() -> Result<(), MessageError> {
+        let balance = Balance::new(150_000, -20_000, 5_000_000_000);
+        let bytes = balance.to_bytes();
+        let mut stream = &bytes[..];
+
+        let command_name = Command::from_bytes(&mut stream)?;
+        let parsed = Balance::from_bytes(command_name, &mut stream)?;
+
+        assert_eq!(parsed, balance);
+        assert_eq!(parsed.get_unconfirmed(), -20_000);
+        Ok(())
+    }
+}
@@ -0,0 +1,144 @@
+use crate::messages::command::Command;
+use crate::network::network_constants::MAX_WALLET_FRAME_SIZE;
+use std::io::Read;
+
+use crate::{
+    block_mod::block_header::BlockHeader,
+    messages::{
+        compact_size::CompactSizeUInt,
+        message_error::MessageError,
+        read_from_bytes::{read_u32_from_bytes, read_vec_from_bytes},
+    },
+};
+
+/// Represents a "filters" message, the answer to a "get_filters" message.
+///
+/// Every header is paired with the serialized compact filter of its block, which is empty if the
+/// node did not build it, such as for the blocks before its download start date.
+///
+/// # Fields
+///
+/// - `command_name`: The command of the message.
+/// - `start_height`: The height of the first header of the message.
+/// - `filters`: The block headers along with their filters, ordered by height.
+#[derive(Debug, PartialEq)]
+pub struct BlockFilters {
+    command_name: Command,
+    start_height: u32,
+    filters: Vec<(BlockHeader, Vec<u8>)>,
+}
+
+impl BlockFilters {
+    /// Creates a new `BlockFilters` message.
+    ///
+    /// # Arguments
+    ///
+    /// - `start_height`: The height of the first header of the message.
+    /// - `filters`: The block headers along with their filters, ordered by height.
+    pub fn new(start_height: u32, filters: Vec<(BlockHeader, Vec<u8>)>) -> BlockFilters {
+        BlockFilters {
+            command_name: Command::Filters,
+            start_height,
+            filters,
+        }
+    }
+
+    /// Parses a `BlockFilters` message from the given byte stream.
+    ///
+    /// # Arguments
+    ///
+    /// - `command_name`: The command of the message.
+    /// - `stream`: A mutable reference to a byte stream from which to parse the message.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `BlockFilters` message, or a `MessageError` if the
+    /// parsing fails or a filter is longer than `MAX_WALLET_FRAME_SIZE`.
+    pub fn from_bytes(
+        command_name: Command,
+        stream: &mut dyn Read,
+    ) -> Result<BlockFilters, MessageError> {
+        let start_height = read_u32_from_bytes(stream, true)?;
+        let count = read_u32_from_bytes(stream, true)?;
+        let mut filters = Vec::new();
+
+        for _ in 0..count {
+            let header = BlockHeader::from_bytes(stream)?;
+            let filter_size =
+                CompactSizeUInt::from_bytes_bounded(stream, MAX_WALLET_FRAME_SIZE as u64)?;
+            let filter = read_vec_from_bytes(stream, filter_size.value() as usize)?;
+            filters.push((header, filter));
+        }
+
+        Ok(BlockFilters {
+            command_name,
+            start_height,
+            filters,
+        })
+    }
+
+    /// Serializes the `BlockFilters` message into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.command_name.to_bytes();
+        buffer.extend(self.start_height.to_le_bytes());
+        buffer.extend((self.filters.len() as u32).to_le_bytes());
+
+        for (header, filter) in self.filters.iter() {
+            buffer.extend(header.to_bytes());
+            buffer.extend(CompactSizeUInt::from_number(filter.len() as u64).to_bytes());
+            buffer.extend(filter);
+        }
+
+        buffer
+    }
+
+    pub fn get_start_height(&self) -> u32 {
+        self.start_height
+    }
+
+    pub fn get_filters(&self) -> &Vec<(BlockHeader, Vec<u8>)> {
+        &self.filters
+    }
+}
+
+#[cfg(test)]
+mod block_filters_test {
+    use super::*;
+    use crate::wallet_utils::get_filters::GetFilters;
+
+    #[test]
+    fn test_block_filters_to_bytes_and_from_bytes() -> Result<(), MessageError> {
+        let get_filters = GetFilters::new(7);
+        let bytes = get_filters.to_bytes();
+        let mut stream = &bytes[..];
+        let command_name = Command::from_bytes(&mut stream)?;
+        assert_eq!(
+            GetFilters::from_bytes(command_name, &mut stream)?,
+            get_filters
+        );
+
+        let header = BlockHeader::new(1, vec![0; 32], vec![1; 32], 2, 3, 4);
+        let block_filters =
+            BlockFilters::new(7, vec![(header.clone(), vec![1, 0x9d]), (header, vec![])]);
+        let bytes = block_filters.to_bytes();
+        let mut stream = &bytes[..];
+
+        let command_name = Command::from_bytes(&mut stream)?;
+        let parsed = BlockFilters::from_bytes(command_name, &mut stream)?;
+
+        assert_eq!(parsed, block_filters);
+        assert_eq!(parsed.get_start_height(), 7);
+        Ok(())
+    }
+
+    #[test]
+    fn test_oversized_lengths_are_refused() {
+        // A single filter of u64::MAX bytes
+        let oversized = [[0; 4].as_slice(), &1u32.to_le_bytes(), &[0; 80], &[0xff; 9]].concat();
+
+        assert!(matches!(
+            BlockFilters::from_bytes(Command::Filters, &mut oversized.as_slice()),
+            Err(MessageError::TooManyElements)
+        ));
+    }
+}
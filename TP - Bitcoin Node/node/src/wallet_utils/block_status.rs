@@ -0,0 +1,75 @@
+use crate::messages::command::Command;
+use std::io::Read;
+
+use crate::messages::{
+    message_error::MessageError,
+    read_from_bytes::{fill_command, read_string_from_bytes},
+};
+
+/// Represents a "block_status" message, the answer to a "submitblock" message.
+///
+/// # Fields
+///
+/// - `command_name`: The command of the message.
+/// - `result`: The outcome of the submission: "accepted" if the block was connected to the main
+///   chain, "inconclusive" if it was stored in a side branch, "duplicate" if the node already had
+///   it, or "invalid" if it was rejected.
+#[derive(Debug, PartialEq)]
+pub struct BlockStatus {
+    command_name: Command,
+    result: String,
+}
+
+impl BlockStatus {
+    /// Creates a new `BlockStatus` message.
+    ///
+    /// # Arguments
+    ///
+    /// - `result`: The outcome of the submission, at most 12 characters long.
+    pub fn new(result: &str) -> BlockStatus {
+        BlockStatus {
+            command_name: Command::BlockStatus,
+            result: result.to_string(),
+        }
+    }
+
+    /// Parses a `BlockStatus` message from the given byte stream.
+    ///
+    /// # Arguments
+    ///
+    /// - `command_name`: The command of the message.
+    /// - `stream`: A mutable reference to a byte stream from which to parse the message.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `BlockStatus` message, or a `MessageError` if the result
+    /// cannot be read.
+    pub fn from_bytes(
+        command_name: Command,
+        stream: &mut dyn Read,
+    ) -> Result<BlockStatus, MessageError> {
+        let result = read_string_from_bytes(stream, 12)?;
+
+        Ok(BlockStatus {
+            command_name,
+            result,
+        })
+    }
+
+    /// Serializes the `BlockStatus` message into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.command_name.to_bytes();
+        buffer.extend(fill_command(self.result.as_str()).as_bytes());
+
+        buffer
+    }
+
+    pub fn get_result(&self) -> &String {
+        &self.result
+    }
+
+    /// Returns whether the block was connected to the main chain.
+    pub fn is_accepted(&self) -> bool {
+        self.result == "accepted"
+    }
+}
@@ -0,0 +1,182 @@
+use crate::messages::command::Command;
+use std::io::Read;
+
+use super::event_log::NodeEvent;
+use crate::messages::{
+    message_error::MessageError,
+    read_from_bytes::{
+        read_u32_from_bytes, read_u64_from_bytes, read_u8_from_bytes, read_vec_from_bytes,
+    },
+};
+
+/// Represents an "events" message, the answer to a "resume" or "subscribe" message, or a "notify"
+/// message, pushed to the subscribed wallets with the events that just happened.
+///
+/// # Fields
+///
+/// - `command_name`: The command of the message.
+/// - `session_id`: The current session of the node.
+/// - `last_seq`: The sequence number of the last event of the node, to resume from next time.
+/// - `complete`: Whether the events are all the ones the wallet missed. If not, the wallet must
+///   rescan its accounts.
+/// - `events`: The missed events, paired with their sequence number.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventBatch {
+    command_name: Command,
+    session_id: u64,
+    last_seq: u64,
+    complete: bool,
+    events: Vec<(u64, NodeEvent)>,
+}
+
+impl EventBatch {
+    /// Creates a new `EventBatch` message.
+    ///
+    /// # Arguments
+    ///
+    /// - `session_id`: The current session of the node.
+    /// - `last_seq`: The sequence number of the last event of the node.
+    /// - `events`: The missed events, or `None` if they can not be replayed.
+    pub fn new(
+        session_id: u64,
+        last_seq: u64,
+        events: Option<Vec<(u64, NodeEvent)>>,
+    ) -> EventBatch {
+        EventBatch {
+            command_name: Command::Events,
+            session_id,
+            last_seq,
+            complete: events.is_some(),
+            events: events.unwrap_or_default(),
+        }
+    }
+
+    /// Creates a new "notify" message, pushed to a subscribed wallet.
+    ///
+    /// # Arguments
+    ///
+    /// - `session_id`: The current session of the node.
+    /// - `last_seq`: The sequence number of the last event of the node.
+    /// - `events`: The events since the last one pushed, or `None` if some were already dropped.
+    pub fn notification(
+        session_id: u64,
+        last_seq: u64,
+        events: Option<Vec<(u64, NodeEvent)>>,
+    ) -> EventBatch {
+        EventBatch {
+            command_name: Command::Notify,
+            ..EventBatch::new(session_id, last_seq, events)
+        }
+    }
+
+    /// Parses an `EventBatch` message from the given byte stream.
+    ///
+    /// # Arguments
+    ///
+    /// - `command_name`: The command of the message.
+    /// - `stream`: A mutable reference to a byte stream from which to parse the message.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `EventBatch` message, or a `MessageError` if an event
+    /// cannot be read or has an unknown kind.
+    pub fn from_bytes(
+        command_name: Command,
+        stream: &mut dyn Read,
+    ) -> Result<EventBatch, MessageError> {
+        let session_id = read_u64_from_bytes(stream, true)?;
+        let last_seq = read_u64_from_bytes(stream, true)?;
+        let complete = read_u8_from_bytes(stream)? != 0;
+        let count = read_u32_from_bytes(stream, true)?;
+
+        let mut events = Vec::new();
+        for _ in 0..count {
+            let seq = read_u64_from_bytes(stream, true)?;
+            let kind = read_u8_from_bytes(stream)?;
+            let hash = read_vec_from_bytes(stream, 32)?;
+            let height = read_u32_from_bytes(stream, true)?;
+
+            let event =
+                NodeEvent::from_parts(kind, hash, height).ok_or(MessageError::ReadFromBytes)?;
+            events.push((seq, event));
+        }
+
+        Ok(EventBatch {
+            command_name,
+            session_id,
+            last_seq,
+            complete,
+            events,
+        })
+    }
+
+    /// Serializes the `EventBatch` message into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.command_name.to_bytes();
+        buffer.extend(self.session_id.to_le_bytes());
+        buffer.extend(self.last_seq.to_le_bytes());
+        buffer.push(self.complete as u8);
+        buffer.extend((self.events.len() as u32).to_le_bytes());
+
+        for (seq, event) in self.events.iter() {
+            buffer.extend(seq.to_le_bytes());
+            buffer.push(event.get_kind());
+            buffer.extend(event.get_hash());
+            buffer.extend(event.get_height().to_le_bytes());
+        }
+
+        buffer
+    }
+
+    pub fn get_session_id(&self) -> u64 {
+        self.session_id
+    }
+
+    pub fn get_last_seq(&self) -> u64 {
+        self.last_seq
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    pub fn get_events(&self) -> &Vec<(u64, NodeEvent)> {
+        &self.events
+    }
+}
+
+#[cfg(test)]
+mod event_batch_test {
+    use super::*;
+
+    #[test]
+    fn test_event_batch_to_bytes_and_from_bytes() -> Result<(), MessageError> {
+        let event_batch = EventBatch::new(
+            7,
+            3,
+            Some(vec![
+                (2, NodeEvent::NewTx(vec![1; 32])),
+                (3, NodeEvent::Reorg(vec![2; 32], 100)),
+            ]),
+        );
+        let bytes = event_batch.to_bytes();
+        let mut stream = &bytes[..];
+
+        let command_name = Command::from_bytes(&mut stream)?;
+        let parsed = EventBatch::from_bytes(command_name, &mut stream)?;
+
+        assert_eq!(parsed, event_batch);
+        assert!(!EventBatch::new(7, 3, None).is_complete());
+
+        let notification = EventBatch::notification(7, 4, Some(vec![]));
+        let bytes = notification.to_bytes();
+        let mut stream = &bytes[..];
+
+        assert_eq!(Command::from_bytes(&mut stream)?, Command::Notify);
+        assert_eq!(
+            EventBatch::from_bytes(Command::Notify, &mut stream)?,
+            notification
+        );
+        Ok(())
+    }
+}
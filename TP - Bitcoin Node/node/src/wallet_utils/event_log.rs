@@ -0,0 +1,213 @@
+use std::{collections::VecDeque, net::SocketAddr};
+
+/// Kind of each event pushed to the wallet, as encoded in an "events" message.
+pub const NEW_TX_EVENT: u8 = 0;
+pub const NEW_BLOCK_EVENT: u8 = 1;
+pub const REORG_EVENT: u8 = 2;
+/// Kind of the peer events, which are only delivered to the subscribers of the node.
+pub const PEER_CONNECTED_EVENT: u8 = 3;
+pub const PEER_DISCONNECTED_EVENT: u8 = 4;
+
+/// Represents a change in the state of the node that the wallet must learn about.
+///
+/// # Variants
+///
+/// - `NewTx`: A transaction with the given id entered the mempool.
+/// - `NewBlock`: The chain was extended with the block of the given hash, at the given height.
+/// - `Reorg`: The chain was reorganized, its new tip is the block of the given hash and height.
+/// - `PeerConnected`: A connection to the peer of the given address was established.
+/// - `PeerDisconnected`: The connection to the peer of the given address was dropped.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeEvent {
+    NewTx(Vec<u8>),
+    NewBlock(Vec<u8>, u32),
+    Reorg(Vec<u8>, u32),
+    PeerConnected(SocketAddr),
+    PeerDisconnected(SocketAddr),
+}
+
+impl NodeEvent {
+    /// Builds an event from its kind, hash and height, as read from an "events" message.
+    ///
+    /// Returns `None` if the kind is unknown.
+    pub fn from_parts(kind: u8, hash: Vec<u8>, height: u32) -> Option<NodeEvent> {
+        match kind {
+            NEW_TX_EVENT => Some(NodeEvent::NewTx(hash)),
+            NEW_BLOCK_EVENT => Some(NodeEvent::NewBlock(hash, height)),
+            REORG_EVENT => Some(NodeEvent::Reorg(hash, height)),
+            _ => None,
+        }
+    }
+
+    pub fn get_kind(&self) -> u8 {
+        match self {
+            NodeEvent::NewTx(_) => NEW_TX_EVENT,
+            NodeEvent::NewBlock(_, _) => NEW_BLOCK_EVENT,
+            NodeEvent::Reorg(_, _) => REORG_EVENT,
+            NodeEvent::PeerConnected(_) => PEER_CONNECTED_EVENT,
+            NodeEvent::PeerDisconnected(_) => PEER_DISCONNECTED_EVENT,
+        }
+    }
+
+    /// Returns the hash of the transaction or block of the event, or an empty slice for peer
+    /// events.
+    pub fn get_hash(&self) -> &[u8] {
+        match self {
+            NodeEvent::NewTx(hash) | NodeEvent::NewBlock(hash, _) | NodeEvent::Reorg(hash, _) => {
+                hash
+            }
+            NodeEvent::PeerConnected(_) | NodeEvent::PeerDisconnected(_) => &[],
+        }
+    }
+
+    /// Returns the height of the block of the event, or 0 for transaction and peer events.
+    pub fn get_height(&self) -> u32 {
+        match self {
+            NodeEvent::NewBlock(_, height) | NodeEvent::Reorg(_, height) => *height,
+            _ => 0,
+        }
+    }
+
+    /// Returns whether the event is about the chain or the mempool, the only events kept in the
+    /// log to be replayed to the wallet.
+    pub fn is_chain_event(&self) -> bool {
+        !matches!(
+            self,
+            NodeEvent::PeerConnected(_) | NodeEvent::PeerDisconnected(_)
+        )
+    }
+}
+
+/// Bounded log of the events of the node, each one with a sequence number that increases
+/// monotonically during the session of the node.
+///
+/// A wallet that reconnects sends the session and the last sequence number it saw, and the node
+/// replays the events it missed. If the session changed (the node restarted) or the missed events
+/// were already dropped from the log, the wallet has to rescan instead.
+///
+/// # Fields
+///
+/// - `session_id`: A random identifier of the session, chosen when the node starts.
+/// - `next_seq`: The sequence number of the next event, starting at 1.
+/// - `events`: The last events, paired with their sequence number.
+/// - `capacity`: The maximum amount of events kept.
+#[derive(Debug)]
+pub struct EventLog {
+    session_id: u64,
+    next_seq: u64,
+    events: VecDeque<(u64, NodeEvent)>,
+    capacity: usize,
+}
+
+impl EventLog {
+    /// Creates an empty event log for a new session, keeping up to `capacity` events.
+    pub fn new(capacity: usize) -> EventLog {
+        let mut session_id: u64 = rand::random();
+        while session_id == 0 {
+            session_id = rand::random();
+        }
+
+        EventLog {
+            session_id,
+            next_seq: 1,
+            events: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    /// Appends an event to the log, dropping the oldest one if the log is full. Peer events are
+    /// not kept, since they are never replayed to the wallet.
+    ///
+    /// Returns the sequence number given to the event, or the one of the last event for peer
+    /// events.
+    pub fn push(&mut self, event: NodeEvent) -> u64 {
+        if !event.is_chain_event() {
+            return self.get_last_seq();
+        }
+
+        let seq = self.next_seq;
+        self.next_seq += 1;
+
+        if self.capacity == 0 {
+            return seq;
+        }
+        if self.events.len() == self.capacity {
+            self.events.pop_front();
+        }
+        self.events.push_back((seq, event));
+
+        seq
+    }
+
+    /// Returns the events after `last_seq` for a wallet that last saw the given session.
+    ///
+    /// A `session_id` of 0 means the wallet never saw a session, so there is nothing to replay.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the events can not be replayed: the session is not the current one, or some of
+    /// the missed events were already dropped from the log.
+    pub fn since(&self, session_id: u64, last_seq: u64) -> Option<Vec<(u64, NodeEvent)>> {
+        if session_id == 0 {
+            return Some(vec![]);
+        }
+        if session_id != self.session_id || last_seq >= self.next_seq {
+            return None;
+        }
+
+        let oldest_seq = self
+            .events
+            .front()
+            .map(|(seq, _)| *seq)
+            .unwrap_or(self.next_seq);
+        if last_seq + 1 < oldest_seq {
+            return None;
+        }
+
+        Some(
+            self.events
+                .iter()
+                .filter(|(seq, _)| *seq > last_seq)
+                .cloned()
+                .collect(),
+        )
+    }
+
+    pub fn get_session_id(&self) -> u64 {
+        self.session_id
+    }
+
+    /// Returns the sequence number of the last event pushed, or 0 if there is none.
+    pub fn get_last_seq(&self) -> u64 {
+        self.next_seq - 1
+    }
+}
+
+#[cfg(test)]
+mod event_log_test {
+    use super::*;
+
+    #[test]
+    fn test_event_log_replays_missed_events_while_buffered() {
+        let mut event_log = EventLog::new(2);
+        let session_id = event_log.get_session_id();
+
+        assert_eq!(event_log.push(NodeEvent::NewTx(vec![1; 32])), 1);
+        assert_eq!(event_log.push(NodeEvent::NewBlock(vec![2; 32], 10)), 2);
+        assert_eq!(event_log.push(NodeEvent::Reorg(vec![3; 32], 11)), 3);
+
+        assert_eq!(
+            event_log.since(session_id, 1),
+            Some(vec![
+                (2, NodeEvent::NewBlock(vec![2; 32], 10)),
+                (3, NodeEvent::Reorg(vec![3; 32], 11)),
+            ])
+        );
+        assert_eq!(event_log.since(session_id, 3), Some(vec![]));
+        assert_eq!(event_log.since(0, 0), Some(vec![]));
+
+        assert_eq!(event_log.since(session_id, 0), None);
+        assert_eq!(event_log.since(session_id, 4), None);
+        assert_eq!(event_log.since(session_id.wrapping_add(1), 3), None);
+    }
+}
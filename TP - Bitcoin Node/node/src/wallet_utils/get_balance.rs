@@ -0,0 +1,122 @@
+use crate::messages::command::Command;
+use crate::network::network_constants::MAX_WALLET_FRAME_SIZE;
+use std::io::Read;
+
+use crate::messages::{
+    compact_size::CompactSizeUInt, message_error::MessageError,
+    read_from_bytes::read_vec_from_bytes,
+};
+
+/// Represents a "getbalance" message, sent by the wallet to receive the balance of a set of
+/// scripts, computed by the node from its UTXO set and its mempool.
+///
+/// # Fields
+///
+/// - `command_name`: The command of the message.
+/// - `pk_scripts`: The scripts whose outputs are added up, such as the ones of every address of an
+///   account.
+#[derive(Debug, PartialEq)]
+pub struct GetBalance {
+    command_name: Command,
+    pk_scripts: Vec<Vec<u8>>,
+}
+
+impl GetBalance {
+    /// Creates a new `GetBalance` message.
+    ///
+    /// # Arguments
+    ///
+    /// - `pk_scripts`: The scripts whose outputs are added up.
+    pub fn new(pk_scripts: Vec<Vec<u8>>) -> GetBalance {
+        GetBalance {
+            command_name: Command::GetBalance,
+            pk_scripts,
+        }
+    }
+
+    /// Parses a `GetBalance` message from the given byte stream.
+    ///
+    /// # Arguments
+    ///
+    /// - `command_name`: The command of the message.
+    /// - `stream`: A mutable reference to a byte stream from which to parse the message.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `GetBalance` message, or a `MessageError` if the scripts
+    /// cannot be read, or if their count or the length of one of them exceeds
+    /// `MAX_WALLET_FRAME_SIZE`.
+    pub fn from_bytes(
+        command_name: Command,
+        stream: &mut dyn Read,
+    ) -> Result<GetBalance, MessageError> {
+        let count = CompactSizeUInt::from_bytes_bounded(stream, MAX_WALLET_FRAME_SIZE as u64)?;
+        let mut pk_scripts = vec![];
+
+        for _ in 0..count.value() {
+            let pk_script_size =
+                CompactSizeUInt::from_bytes_bounded(stream, MAX_WALLET_FRAME_SIZE as u64)?;
+            pk_scripts.push(read_vec_from_bytes(
+                stream,
+                pk_script_size.value() as usize,
+            )?);
+        }
+
+        Ok(GetBalance {
+            command_name,
+            pk_scripts,
+        })
+    }
+
+    /// Serializes the `GetBalance` message into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.command_name.to_bytes();
+        buffer.extend(CompactSizeUInt::from_number(self.pk_scripts.len() as u64).to_bytes());
+
+        for pk_script in &self.pk_scripts {
+            buffer.extend(CompactSizeUInt::from_number(pk_script.len() as u64).to_bytes());
+            buffer.extend(pk_script);
+        }
+
+        buffer
+    }
+
+    pub fn get_pk_scripts(&self) -> &Vec<Vec<u8>> {
+        &self.pk_scripts
+    }
+}
+
+#[cfg(test)]
+mod get_balance_test {
+    use super::*;
+
+    #[test]
+    fn test_get_balance_to_bytes_and_from_bytes() -> Result<(), MessageError> {
+        let get_balance = GetBalance::new(vec![vec![0x51], vec![], vec![0x00, 0x14, 7, 7]]);
+        let bytes = get_balance.to_bytes();
+        let mut stream = &bytes[..];
+
+        let command_name = Command::from_bytes(&mut stream)?;
+        let parsed = GetBalance::from_bytes(command_name, &mut stream)?;
+
+        assert_eq!(parsed, get_balance);
+        assert_eq!(parsed.get_pk_scripts().len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_oversized_lengths_are_refused() {
+        // A count of u64::MAX, then a single script of u64::MAX bytes
+        let oversized_count = [0xff; 9];
+        let oversized_script = [vec![0x01], vec![0xff; 9]].concat();
+
+        assert!(matches!(
+            GetBalance::from_bytes(Command::GetBalance, &mut &oversized_count[..]),
+            Err(MessageError::TooManyElements)
+        ));
+        assert!(matches!(
+            GetBalance::from_bytes(Command::GetBalance, &mut oversized_script.as_slice()),
+            Err(MessageError::TooManyElements)
+        ));
+    }
+}
@@ -0,0 +1,66 @@
+use crate::messages::command::Command;
+use std::io::Read;
+
+use crate::messages::{message_error::MessageError, read_from_bytes::read_vec_from_bytes};
+
+/// Represents a "get_block" message, sent by a light wallet to receive a block whose compact
+/// filter matched one of its scripts.
+///
+/// # Fields
+///
+/// - `command_name`: The command of the message.
+/// - `block_hash`: The hash of the requested block.
+#[derive(Debug, PartialEq)]
+pub struct GetBlock {
+    command_name: Command,
+    block_hash: Vec<u8>,
+}
+
+impl GetBlock {
+    /// Creates a new `GetBlock` message.
+    ///
+    /// # Arguments
+    ///
+    /// - `block_hash`: The hash of the requested block.
+    pub fn new(block_hash: Vec<u8>) -> GetBlock {
+        GetBlock {
+            command_name: Command::GetBlock,
+            block_hash,
+        }
+    }
+
+    /// Parses a `GetBlock` message from the given byte stream.
+    ///
+    /// # Arguments
+    ///
+    /// - `command_name`: The command of the message.
+    /// - `stream`: A mutable reference to a byte stream from which to parse the message.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `GetBlock` message, or a `MessageError` if the block hash
+    /// cannot be read.
+    pub fn from_bytes(
+        command_name: Command,
+        stream: &mut dyn Read,
+    ) -> Result<GetBlock, MessageError> {
+        let block_hash = read_vec_from_bytes(stream, 32)?;
+
+        Ok(GetBlock {
+            command_name,
+            block_hash,
+        })
+    }
+
+    /// Serializes the `GetBlock` message into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.command_name.to_bytes();
+        buffer.extend(&self.block_hash);
+
+        buffer
+    }
+
+    pub fn get_block_hash(&self) -> Vec<u8> {
+        self.block_hash.clone()
+    }
+}
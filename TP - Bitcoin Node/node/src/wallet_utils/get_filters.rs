@@ -0,0 +1,66 @@
+use crate::messages::command::Command;
+use std::io::Read;
+
+use crate::messages::{message_error::MessageError, read_from_bytes::read_u32_from_bytes};
+
+/// Represents a "get_filters" message, sent by a light wallet to receive the block headers and
+/// the compact filters (BIP158) of the blocks from a given height on.
+///
+/// # Fields
+///
+/// - `command_name`: The command of the message.
+/// - `start_height`: The height of the first requested block.
+#[derive(Debug, PartialEq)]
+pub struct GetFilters {
+    command_name: Command,
+    start_height: u32,
+}
+
+impl GetFilters {
+    /// Creates a new `GetFilters` message.
+    ///
+    /// # Arguments
+    ///
+    /// - `start_height`: The height of the first requested block.
+    pub fn new(start_height: u32) -> GetFilters {
+        GetFilters {
+            command_name: Command::GetFilters,
+            start_height,
+        }
+    }
+
+    /// Parses a `GetFilters` message from the given byte stream.
+    ///
+    /// # Arguments
+    ///
+    /// - `command_name`: The command of the message.
+    /// - `stream`: A mutable reference to a byte stream from which to parse the message.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `GetFilters` message, or a `MessageError` if the height
+    /// cannot be read.
+    pub fn from_bytes(
+        command_name: Command,
+        stream: &mut dyn Read,
+    ) -> Result<GetFilters, MessageError> {
+        let start_height = read_u32_from_bytes(stream, true)?;
+
+        Ok(GetFilters {
+            command_name,
+            start_height,
+        })
+    }
+
+    /// Serializes the `GetFilters` message into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.command_name.to_bytes();
+        buffer.extend(self.start_height.to_le_bytes());
+
+        buffer
+    }
+
+    pub fn get_start_height(&self) -> u32 {
+        self.start_height
+    }
+}
@@ -0,0 +1,66 @@
+use crate::messages::command::Command;
+use std::io::Read;
+
+use crate::messages::{message_error::MessageError, read_from_bytes::read_u32_from_bytes};
+
+/// Represents a "hdrs_since" message, sent by the wallet to receive every block header from a
+/// given height on, and to be notified about the new headers as they arrive.
+///
+/// # Fields
+///
+/// - `command_name`: The command of the message.
+/// - `height`: The height of the first header to be sent.
+#[derive(Debug)]
+pub struct GetHeadersSince {
+    command_name: Command,
+    height: u32,
+}
+
+impl GetHeadersSince {
+    /// Creates a new `GetHeadersSince` message.
+    ///
+    /// # Arguments
+    ///
+    /// - `height`: The height of the first header to be sent.
+    pub fn new(height: u32) -> GetHeadersSince {
+        GetHeadersSince {
+            command_name: Command::HeadersSince,
+            height,
+        }
+    }
+
+    /// Parses a `GetHeadersSince` message from the given byte stream.
+    ///
+    /// # Arguments
+    ///
+    /// - `command_name`: The command of the message.
+    /// - `stream`: A mutable reference to a byte stream from which to parse the message.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `GetHeadersSince` message, or a `MessageError` if the
+    /// height cannot be read.
+    pub fn from_bytes(
+        command_name: Command,
+        stream: &mut dyn Read,
+    ) -> Result<GetHeadersSince, MessageError> {
+        let height = read_u32_from_bytes(stream, true)?;
+
+        Ok(GetHeadersSince {
+            command_name,
+            height,
+        })
+    }
+
+    /// Serializes the `GetHeadersSince` message into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.command_name.to_bytes();
+        buffer.extend(self.height.to_le_bytes());
+
+        buffer
+    }
+
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+}
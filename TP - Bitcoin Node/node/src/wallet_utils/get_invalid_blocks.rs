@@ -0,0 +1,31 @@
+use crate::messages::command::Command;
+
+/// Represents a "get_invalid" message, sent to the node to list the blocks it found invalid.
+///
+/// # Fields
+///
+/// - `command_name`: The command of the message.
+#[derive(Debug)]
+pub struct GetInvalidBlocks {
+    command_name: Command,
+}
+
+impl GetInvalidBlocks {
+    /// Creates a new `GetInvalidBlocks` message.
+    pub fn new() -> GetInvalidBlocks {
+        GetInvalidBlocks {
+            command_name: Command::GetInvalid,
+        }
+    }
+
+    /// Serializes the `GetInvalidBlocks` message into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.command_name.to_bytes()
+    }
+}
+
+impl Default for GetInvalidBlocks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
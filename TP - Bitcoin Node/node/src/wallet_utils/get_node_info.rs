@@ -0,0 +1,39 @@
+use crate::messages::command::Command;
+
+/// Represents a "getnodeinfo" message sent by the wallet to query the node's information.
+///
+/// # Fields
+///
+/// - `command_name`: The command of the message.
+#[derive(Debug)]
+pub struct GetNodeInfo {
+    command_name: Command,
+}
+
+impl GetNodeInfo {
+    /// Creates a new `GetNodeInfo` message.
+    ///
+    /// # Returns
+    ///
+    /// A new `GetNodeInfo` message.
+    pub fn new() -> GetNodeInfo {
+        GetNodeInfo {
+            command_name: Command::GetNodeInfo,
+        }
+    }
+
+    /// Serializes the `GetNodeInfo` message into a byte vector.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec<u8>` containing the serialized byte representation of the `GetNodeInfo` message.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.command_name.to_bytes()
+    }
+}
+
+impl Default for GetNodeInfo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
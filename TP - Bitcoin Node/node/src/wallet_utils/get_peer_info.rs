@@ -0,0 +1,32 @@
+use crate::messages::command::Command;
+
+/// Represents a "getpeerinfo" message, sent by the wallet to query the statistics of the peers
+/// the node is connected to.
+///
+/// # Fields
+///
+/// - `command_name`: The command of the message.
+#[derive(Debug)]
+pub struct GetPeerInfo {
+    command_name: Command,
+}
+
+impl GetPeerInfo {
+    /// Creates a new `GetPeerInfo` message.
+    pub fn new() -> GetPeerInfo {
+        GetPeerInfo {
+            command_name: Command::GetPeerInfo,
+        }
+    }
+
+    /// Serializes the `GetPeerInfo` message into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.command_name.to_bytes()
+    }
+}
+
+impl Default for GetPeerInfo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
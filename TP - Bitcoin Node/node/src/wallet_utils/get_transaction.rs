@@ -0,0 +1,66 @@
+use crate::messages::command::Command;
+use std::io::Read;
+
+use crate::messages::{message_error::MessageError, read_from_bytes::read_vec_from_bytes};
+
+/// Represents a "get_tx" message, sent by the wallet to receive a transaction along with its
+/// position in the chain.
+///
+/// # Fields
+///
+/// - `command_name`: The command of the message.
+/// - `tx_id`: The id of the transaction, as displayed by block explorers.
+#[derive(Debug)]
+pub struct GetTransaction {
+    command_name: Command,
+    tx_id: Vec<u8>,
+}
+
+impl GetTransaction {
+    /// Creates a new `GetTransaction` message.
+    ///
+    /// # Arguments
+    ///
+    /// - `tx_id`: The id of the transaction, as displayed by block explorers.
+    pub fn new(tx_id: Vec<u8>) -> GetTransaction {
+        GetTransaction {
+            command_name: Command::GetTx,
+            tx_id,
+        }
+    }
+
+    /// Parses a `GetTransaction` message from the given byte stream.
+    ///
+    /// # Arguments
+    ///
+    /// - `command_name`: The command of the message.
+    /// - `stream`: A mutable reference to a byte stream from which to parse the message.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `GetTransaction` message, or a `MessageError` if the
+    /// transaction id cannot be read.
+    pub fn from_bytes(
+        command_name: Command,
+        stream: &mut dyn Read,
+    ) -> Result<GetTransaction, MessageError> {
+        let tx_id = read_vec_from_bytes(stream, 32)?;
+
+        Ok(GetTransaction {
+            command_name,
+            tx_id,
+        })
+    }
+
+    /// Serializes the `GetTransaction` message into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.command_name.to_bytes();
+        buffer.extend(&self.tx_id);
+
+        buffer
+    }
+
+    pub fn get_tx_id(&self) -> Vec<u8> {
+        self.tx_id.clone()
+    }
+}
@@ -0,0 +1,121 @@
+use crate::messages::command::Command;
+use crate::network::network_constants::MAX_WALLET_FRAME_SIZE;
+use std::io::Read;
+
+use crate::messages::{
+    compact_size::CompactSizeUInt, message_error::MessageError,
+    read_from_bytes::read_vec_from_bytes,
+};
+
+/// Represents a "getutxos" message, sent by the wallet to receive the unspent outputs of a set of
+/// scripts, such as the ones of watched addresses, without their whole transaction history.
+///
+/// # Fields
+///
+/// - `command_name`: The command of the message.
+/// - `pk_scripts`: The scripts whose unspent outputs are requested.
+#[derive(Debug, PartialEq)]
+pub struct GetUtxos {
+    command_name: Command,
+    pk_scripts: Vec<Vec<u8>>,
+}
+
+impl GetUtxos {
+    /// Creates a new `GetUtxos` message.
+    ///
+    /// # Arguments
+    ///
+    /// - `pk_scripts`: The scripts whose unspent outputs are requested.
+    pub fn new(pk_scripts: Vec<Vec<u8>>) -> GetUtxos {
+        GetUtxos {
+            command_name: Command::GetUtxos,
+            pk_scripts,
+        }
+    }
+
+    /// Parses a `GetUtxos` message from the given byte stream.
+    ///
+    /// # Arguments
+    ///
+    /// - `command_name`: The command of the message.
+    /// - `stream`: A mutable reference to a byte stream from which to parse the message.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `GetUtxos` message, or a `MessageError` if the scripts
+    /// cannot be read, or if their count or the length of one of them exceeds
+    /// `MAX_WALLET_FRAME_SIZE`.
+    pub fn from_bytes(
+        command_name: Command,
+        stream: &mut dyn Read,
+    ) -> Result<GetUtxos, MessageError> {
+        let count = CompactSizeUInt::from_bytes_bounded(stream, MAX_WALLET_FRAME_SIZE as u64)?;
+        let mut pk_scripts = vec![];
+
+        for _ in 0..count.value() {
+            let pk_script_size =
+                CompactSizeUInt::from_bytes_bounded(stream, MAX_WALLET_FRAME_SIZE as u64)?;
+            pk_scripts.push(read_vec_from_bytes(
+                stream,
+                pk_script_size.value() as usize,
+            )?);
+        }
+
+        Ok(GetUtxos {
+            command_name,
+            pk_scripts,
+        })
+    }
+
+    /// Serializes the `GetUtxos` message into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.command_name.to_bytes();
+        buffer.extend(CompactSizeUInt::from_number(self.pk_scripts.len() as u64).to_bytes());
+
+        for pk_script in &self.pk_scripts {
+            buffer.extend(CompactSizeUInt::from_number(pk_script.len() as u64).to_bytes());
+            buffer.extend(pk_script);
+        }
+
+        buffer
+    }
+
+    pub fn get_pk_scripts(&self) -> &Vec<Vec<u8>> {
+        &self.pk_scripts
+    }
+}
+
+#[cfg(test)]
+mod get_utxos_test {
+    use super::*;
+
+    #[test]
+    fn test_get_utxos_to_bytes_and_from_bytes() -> Result<(), MessageError> {
+        let get_utxos = GetUtxos::new(vec![vec![0x51], vec![], vec![0x00, 0x14, 7, 7]]);
+        let bytes = get_utxos.to_bytes();
+        let mut stream = &bytes[..];
+
+        let command_name = Command::from_bytes(&mut stream)?;
+        let parsed = GetUtxos::from_bytes(command_name, &mut stream)?;
+
+        assert_eq!(parsed, get_utxos);
+        assert_eq!(parsed.get_pk_scripts().len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_oversized_lengths_are_refused() {
+        // A count of u64::MAX, then a single script of u64::MAX bytes
+        let oversized_count = [0xff; 9];
+        let oversized_script = [vec![0x01], vec![0xff; 9]].concat();
+
+        assert!(matches!(
+            GetUtxos::from_bytes(Command::GetUtxos, &mut &oversized_count[..]),
+            Err(MessageError::TooManyElements)
+        ));
+        assert!(matches!(
+            GetUtxos::from_bytes(Command::GetUtxos, &mut oversized_script.as_slice()),
+            Err(MessageError::TooManyElements)
+        ));
+    }
+}
@@ -0,0 +1,141 @@
+use crate::messages::command::Command;
+use crate::network::network_constants::MAX_WALLET_FRAME_SIZE;
+use std::io::Read;
+
+use crate::{
+    block_mod::invalid_blocks::{BlockRule, InvalidBlock},
+    messages::{
+        compact_size::CompactSizeUInt,
+        message_error::MessageError,
+        read_from_bytes::{
+            read_string_from_bytes, read_u32_from_bytes, read_u8_from_bytes, read_vec_from_bytes,
+        },
+    },
+};
+
+/// Represents an "invalid_blks" message, the answer to a "get_invalid" message.
+///
+/// # Fields
+///
+/// - `command_name`: The command of the message.
+/// - `blocks`: The hashes of the invalid blocks, along with the reason they are invalid.
+#[derive(Debug, PartialEq)]
+pub struct InvalidBlocksInfo {
+    command_name: Command,
+    blocks: Vec<(Vec<u8>, InvalidBlock)>,
+}
+
+impl InvalidBlocksInfo {
+    /// Creates a new `InvalidBlocksInfo` message.
+    ///
+    /// # Arguments
+    ///
+    /// - `blocks`: The hashes of the invalid blocks, along with the reason they are invalid.
+    pub fn new(blocks: Vec<(Vec<u8>, InvalidBlock)>) -> InvalidBlocksInfo {
+        InvalidBlocksInfo {
+            command_name: Command::InvalidBlocks,
+            blocks,
+        }
+    }
+
+    /// Parses an `InvalidBlocksInfo` message from the given byte stream.
+    ///
+    /// # Arguments
+    ///
+    /// - `command_name`: The command of the message.
+    /// - `stream`: A mutable reference to a byte stream from which to parse the message.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `InvalidBlocksInfo` message, or a `MessageError` if the
+    /// parsing fails, a rule is unknown or its name is longer than `MAX_WALLET_FRAME_SIZE`.
+    pub fn from_bytes(
+        command_name: Command,
+        stream: &mut dyn Read,
+    ) -> Result<InvalidBlocksInfo, MessageError> {
+        let count = read_u32_from_bytes(stream, true)?;
+        let mut blocks = Vec::new();
+
+        for _ in 0..count {
+            let block_hash = read_vec_from_bytes(stream, 32)?;
+            let rule_size =
+                CompactSizeUInt::from_bytes_bounded(stream, MAX_WALLET_FRAME_SIZE as u64)?;
+            let rule = read_string_from_bytes(stream, rule_size.value() as usize)?;
+            let rule = BlockRule::from_name(&rule).ok_or(MessageError::ReadFromBytes)?;
+            let tx_id = match read_u8_from_bytes(stream)? {
+                0 => None,
+                _ => Some(read_vec_from_bytes(stream, 32)?),
+            };
+
+            blocks.push((block_hash, InvalidBlock::new(rule, tx_id)));
+        }
+
+        Ok(InvalidBlocksInfo {
+            command_name,
+            blocks,
+        })
+    }
+
+    /// Serializes the `InvalidBlocksInfo` message into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.command_name.to_bytes();
+        buffer.extend((self.blocks.len() as u32).to_le_bytes());
+
+        for (block_hash, invalid_block) in self.blocks.iter() {
+            let rule = invalid_block.get_rule().get_name();
+
+            buffer.extend(block_hash);
+            buffer.extend(CompactSizeUInt::from_number(rule.len() as u64).to_bytes());
+            buffer.extend(rule.as_bytes());
+
+            match invalid_block.get_tx_id() {
+                Some(tx_id) => {
+                    buffer.push(1);
+                    buffer.extend(tx_id);
+                }
+                None => buffer.push(0),
+            }
+        }
+
+        buffer
+    }
+
+    pub fn get_blocks(&self) -> &Vec<(Vec<u8>, InvalidBlock)> {
+        &self.blocks
+    }
+}
+
+#[cfg(test)]
+mod invalid_blocks_info_test {
+    use super::*;
+
+    #[test]
+    fn test_invalid_blocks_info_to_bytes_and_from_bytes() -> Result<(), MessageError> {
+        let invalid_blocks_info = InvalidBlocksInfo::new(vec![
+            (
+                vec![1; 32],
+                InvalidBlock::new(BlockRule::DuplicateTx, Some(vec![2; 32])),
+            ),
+            (vec![3; 32], InvalidBlock::new(BlockRule::HighHash, None)),
+        ]);
+        let bytes = invalid_blocks_info.to_bytes();
+        let mut stream = &bytes[..];
+
+        let command_name = Command::from_bytes(&mut stream)?;
+        let parsed = InvalidBlocksInfo::from_bytes(command_name, &mut stream)?;
+
+        assert_eq!(parsed, invalid_blocks_info);
+        Ok(())
+    }
+
+    #[test]
+    fn test_oversized_lengths_are_refused() {
+        // A single block whose rule is u64::MAX bytes long
+        let oversized = [1u32.to_le_bytes().as_slice(), &[0; 32], &[0xff; 9]].concat();
+
+        assert!(matches!(
+            InvalidBlocksInfo::from_bytes(Command::InvalidBlocks, &mut oversized.as_slice()),
+            Err(MessageError::TooManyElements)
+        ));
+    }
+}
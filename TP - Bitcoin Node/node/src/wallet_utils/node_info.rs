@@ -0,0 +1,257 @@
+use crate::messages::command::Command;
+use crate::network::network_constants::MAX_WALLET_FRAME_SIZE;
+use std::io::Read;
+
+use crate::messages::{
+    compact_size::CompactSizeUInt,
+    message_error::MessageError,
+    read_from_bytes::{
+        read_string_from_bytes, read_u32_from_bytes, read_u64_from_bytes, read_u8_from_bytes,
+        read_vec_from_bytes,
+    },
+};
+
+/// Represents a "node_info" message, the node's answer to a "getnodeinfo" query.
+///
+/// # Fields
+///
+/// - `command_name`: The command of the message.
+/// - `version`: The node's software version.
+/// - `features`: The optional features enabled in the node's settings.
+/// - `uptime`: The amount of seconds the node has been running.
+/// - `network`: The name of the network the node is connected to.
+/// - `data_dir`: The directory where the node stores its data.
+/// - `headers_height`: The height of the tip of the node's header chain.
+/// - `best_hash`: The hash of the tip of the node's header chain.
+/// - `blocks`: The amount of blocks the node has downloaded.
+/// - `peer_count`: The amount of peers the node is connected to.
+/// - `sync_stalled`: Whether the sync of the node is stalled behind every peer.
+#[derive(Debug)]
+pub struct NodeInfo {
+    command_name: Command,
+    version: String,
+    features: Vec<String>,
+    uptime: u64,
+    network: String,
+    data_dir: String,
+    headers_height: u32,
+    best_hash: Vec<u8>,
+    blocks: u32,
+    peer_count: u32,
+    sync_stalled: bool,
+}
+
+/// Serializes a string prefixed by its length as a compact size.
+fn string_to_bytes(string: &str) -> Vec<u8> {
+    let mut buffer = CompactSizeUInt::from_number(string.len() as u64).to_bytes();
+    buffer.extend(string.as_bytes());
+    buffer
+}
+
+/// Reads a string prefixed by its length as a compact size, which may not exceed
+/// `MAX_WALLET_FRAME_SIZE`.
+fn read_prefixed_string(stream: &mut dyn Read) -> Result<String, MessageError> {
+    let size = CompactSizeUInt::from_bytes_bounded(stream, MAX_WALLET_FRAME_SIZE as u64)?;
+    read_string_from_bytes(stream, size.value() as usize)
+}
+
+impl NodeInfo {
+    /// Creates a new `NodeInfo` message.
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - The node's software version.
+    /// * `features` - The optional features enabled in the node's settings.
+    /// * `uptime` - The amount of seconds the node has been running.
+    /// * `network` - The name of the network the node is connected to.
+    /// * `data_dir` - The directory where the node stores its data.
+    /// * `chain_status` - The height and hash of the tip of the header chain, the amount of
+    ///   downloaded blocks, the amount of connected peers and whether the sync is stalled.
+    ///
+    /// # Returns
+    ///
+    /// A new `NodeInfo` message.
+    pub fn new(
+        version: String,
+        features: Vec<String>,
+        uptime: u64,
+        network: String,
+        data_dir: String,
+        chain_status: (u32, Vec<u8>, u32, u32, bool),
+    ) -> NodeInfo {
+        let (headers_height, best_hash, blocks, peer_count, sync_stalled) = chain_status;
+
+        NodeInfo {
+            command_name: Command::NodeInfo,
+            version,
+            features,
+            uptime,
+            network,
+            data_dir,
+            headers_height,
+            best_hash,
+            blocks,
+            peer_count,
+            sync_stalled,
+        }
+    }
+
+    /// Creates a `NodeInfo` instance by parsing the data from a byte stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `command_name` - The command of the message.
+    /// * `stream` - A mutable reference to the byte stream to read the data from.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `NodeInfo` instance or a `MessageError` if the parsing
+    /// fails, or if the amount of features or the length of a string exceeds
+    /// `MAX_WALLET_FRAME_SIZE`.
+    pub fn from_bytes(
+        command_name: Command,
+        stream: &mut dyn Read,
+    ) -> Result<NodeInfo, MessageError> {
+        let version = read_prefixed_string(stream)?;
+        let feature_count =
+            CompactSizeUInt::from_bytes_bounded(stream, MAX_WALLET_FRAME_SIZE as u64)?;
+        let mut features = Vec::new();
+
+        for _ in 0..feature_count.value() {
+            features.push(read_prefixed_string(stream)?);
+        }
+
+        let uptime = read_u64_from_bytes(stream, true)?;
+        let network = read_prefixed_string(stream)?;
+        let data_dir = read_prefixed_string(stream)?;
+        let headers_height = read_u32_from_bytes(stream, true)?;
+        let best_hash = read_vec_from_bytes(stream, 32)?;
+        let blocks = read_u32_from_bytes(stream, true)?;
+        let peer_count = read_u32_from_bytes(stream, true)?;
+        let sync_stalled = read_u8_from_bytes(stream)? != 0;
+
+        Ok(NodeInfo {
+            command_name,
+            version,
+            features,
+            uptime,
+            network,
+            data_dir,
+            headers_height,
+            best_hash,
+            blocks,
+            peer_count,
+            sync_stalled,
+        })
+    }
+
+    /// Converts the `NodeInfo` instance into a byte representation.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec<u8>` containing the serialized byte representation of the `NodeInfo` message.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.command_name.to_bytes();
+
+        buffer.extend(string_to_bytes(&self.version));
+        buffer.extend(CompactSizeUInt::from_number(self.features.len() as u64).to_bytes());
+        for feature in self.features.iter() {
+            buffer.extend(string_to_bytes(feature));
+        }
+        buffer.extend(self.uptime.to_le_bytes());
+        buffer.extend(string_to_bytes(&self.network));
+        buffer.extend(string_to_bytes(&self.data_dir));
+        buffer.extend(self.headers_height.to_le_bytes());
+        buffer.extend(&self.best_hash);
+        buffer.extend(self.blocks.to_le_bytes());
+        buffer.extend(self.peer_count.to_le_bytes());
+        buffer.push(self.sync_stalled as u8);
+
+        buffer
+    }
+
+    pub fn get_version(&self) -> &String {
+        &self.version
+    }
+
+    pub fn get_features(&self) -> &Vec<String> {
+        &self.features
+    }
+
+    pub fn get_uptime(&self) -> u64 {
+        self.uptime
+    }
+
+    pub fn get_network(&self) -> &String {
+        &self.network
+    }
+
+    pub fn get_data_dir(&self) -> &String {
+        &self.data_dir
+    }
+
+    pub fn get_headers_height(&self) -> u32 {
+        self.headers_height
+    }
+
+    pub fn get_best_hash(&self) -> &Vec<u8> {
+        &self.best_hash
+    }
+
+    pub fn get_blocks(&self) -> u32 {
+        self.blocks
+    }
+
+    pub fn get_peer_count(&self) -> u32 {
+        self.peer_count
+    }
+
+    pub fn is_sync_stalled(&self) -> bool {
+        self.sync_stalled
+    }
+}
+
+#[cfg(test)]
+mod node_info_test {
+    use super::*;
+
+    #[test]
+    fn test_node_info_to_bytes_and_from_bytes() -> Result<(), MessageError> {
+        let node_info = NodeInfo::new(
+            "0.1.0".to_string(),
+            vec!["txindex".to_string()],
+            3600,
+            "testnet".to_string(),
+            "data".to_string(),
+            (2500000, vec![7; 32], 1200, 8, true),
+        );
+        let bytes = node_info.to_bytes();
+        let mut stream = &bytes[..];
+
+        let command_name = Command::from_bytes(&mut stream)?;
+        let parsed = NodeInfo::from_bytes(command_name, &mut stream)?;
+
+        assert_eq!(parsed.get_version(), "0.1.0");
+        assert_eq!(parsed.get_features(), &vec!["txindex".to_string()]);
+        assert_eq!(parsed.get_uptime(), 3600);
+        assert_eq!(parsed.get_network(), "testnet");
+        assert_eq!(parsed.get_data_dir(), "data");
+        assert_eq!(parsed.get_headers_height(), 2500000);
+        assert_eq!(parsed.get_best_hash(), &vec![7; 32]);
+        assert_eq!(parsed.get_blocks(), 1200);
+        assert_eq!(parsed.get_peer_count(), 8);
+        assert!(parsed.is_sync_stalled());
+        Ok(())
+    }
+
+    #[test]
+    fn test_oversized_lengths_are_refused() {
+        // A version string of u64::MAX bytes
+        let oversized = [0xff; 9].to_vec();
+
+        assert!(matches!(
+            NodeInfo::from_bytes(Command::NodeInfo, &mut oversized.as_slice()),
+            Err(MessageError::TooManyElements)
+        ));
+    }
+}
@@ -0,0 +1,271 @@
+use crate::messages::command::Command;
+use crate::network::network_constants::MAX_WALLET_FRAME_SIZE;
+use std::io::Read;
+
+use crate::messages::{
+    compact_size::CompactSizeUInt,
+    message_error::MessageError,
+    read_from_bytes::{
+        read_string_from_bytes, read_u32_from_bytes, read_u64_from_bytes, read_u8_from_bytes,
+    },
+};
+
+/// Serializes a string prefixed by its length as a compact size.
+fn string_to_bytes(string: &str) -> Vec<u8> {
+    let mut buffer = CompactSizeUInt::from_number(string.len() as u64).to_bytes();
+    buffer.extend(string.as_bytes());
+    buffer
+}
+
+/// Reads a string prefixed by its length as a compact size, which may not exceed
+/// `MAX_WALLET_FRAME_SIZE`.
+fn read_prefixed_string(stream: &mut dyn Read) -> Result<String, MessageError> {
+    let size = CompactSizeUInt::from_bytes_bounded(stream, MAX_WALLET_FRAME_SIZE as u64)?;
+    read_string_from_bytes(stream, size.value() as usize)
+}
+
+/// The statistics of a peer the node is connected to.
+///
+/// # Fields
+///
+/// - `addr`: The address of the peer.
+/// - `bytes_sent`: The amount of bytes sent to the peer.
+/// - `bytes_received`: The amount of bytes received from the peer.
+/// - `last_seen`: The unix timestamp of the last message received from the peer, 0 if none.
+/// - `latency_millis`: The latency of the last ping answered by the peer, if any.
+/// - `misbehavior`: The misbehavior score of the peer.
+/// - `messages`: The amount of messages received from the peer by command.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeerDetails {
+    addr: String,
+    bytes_sent: u64,
+    bytes_received: u64,
+    last_seen: u64,
+    latency_millis: Option<u64>,
+    misbehavior: u32,
+    messages: Vec<(String, u64)>,
+}
+
+impl PeerDetails {
+    /// Creates the statistics of a peer.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address of the peer.
+    /// * `bytes` - The amount of bytes sent to and received from the peer.
+    /// * `last_seen` - The unix timestamp of the last message received from the peer.
+    /// * `latency_millis` - The latency of the last ping answered by the peer, if any.
+    /// * `misbehavior` - The misbehavior score of the peer.
+    /// * `messages` - The amount of messages received from the peer by command.
+    pub fn new(
+        addr: String,
+        bytes: (u64, u64),
+        last_seen: u64,
+        latency_millis: Option<u64>,
+        misbehavior: u32,
+        messages: Vec<(String, u64)>,
+    ) -> PeerDetails {
+        let (bytes_sent, bytes_received) = bytes;
+
+        PeerDetails {
+            addr,
+            bytes_sent,
+            bytes_received,
+            last_seen,
+            latency_millis,
+            misbehavior,
+            messages,
+        }
+    }
+
+    fn from_bytes(stream: &mut dyn Read) -> Result<PeerDetails, MessageError> {
+        let addr = read_prefixed_string(stream)?;
+        let bytes_sent = read_u64_from_bytes(stream, true)?;
+        let bytes_received = read_u64_from_bytes(stream, true)?;
+        let last_seen = read_u64_from_bytes(stream, true)?;
+        let latency_millis = match read_u8_from_bytes(stream)? {
+            0 => None,
+            _ => Some(read_u64_from_bytes(stream, true)?),
+        };
+        let misbehavior = read_u32_from_bytes(stream, true)?;
+
+        let message_count =
+            CompactSizeUInt::from_bytes_bounded(stream, MAX_WALLET_FRAME_SIZE as u64)?;
+        let mut messages = Vec::new();
+        for _ in 0..message_count.value() {
+            let command = read_prefixed_string(stream)?;
+            messages.push((command, read_u64_from_bytes(stream, true)?));
+        }
+
+        Ok(PeerDetails {
+            addr,
+            bytes_sent,
+            bytes_received,
+            last_seen,
+            latency_millis,
+            misbehavior,
+            messages,
+        })
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = string_to_bytes(&self.addr);
+        buffer.extend(self.bytes_sent.to_le_bytes());
+        buffer.extend(self.bytes_received.to_le_bytes());
+        buffer.extend(self.last_seen.to_le_bytes());
+        match self.latency_millis {
+            Some(latency_millis) => {
+                buffer.push(1);
+                buffer.extend(latency_millis.to_le_bytes());
+            }
+            None => buffer.push(0),
+        }
+        buffer.extend(self.misbehavior.to_le_bytes());
+
+        buffer.extend(CompactSizeUInt::from_number(self.messages.len() as u64).to_bytes());
+        for (command, count) in self.messages.iter() {
+            buffer.extend(string_to_bytes(command));
+            buffer.extend(count.to_le_bytes());
+        }
+
+        buffer
+    }
+
+    pub fn get_addr(&self) -> &String {
+        &self.addr
+    }
+
+    pub fn get_bytes_sent(&self) -> u64 {
+        self.bytes_sent
+    }
+
+    pub fn get_bytes_received(&self) -> u64 {
+        self.bytes_received
+    }
+
+    pub fn get_last_seen(&self) -> u64 {
+        self.last_seen
+    }
+
+    pub fn get_latency_millis(&self) -> Option<u64> {
+        self.latency_millis
+    }
+
+    pub fn get_misbehavior(&self) -> u32 {
+        self.misbehavior
+    }
+
+    pub fn get_messages(&self) -> &Vec<(String, u64)> {
+        &self.messages
+    }
+}
+
+/// Represents a "peer_info" message, the node's answer to a "getpeerinfo" query.
+///
+/// # Fields
+///
+/// - `command_name`: The command of the message.
+/// - `peers`: The statistics of every peer the node is connected to.
+#[derive(Debug, PartialEq)]
+pub struct PeerInfo {
+    command_name: Command,
+    peers: Vec<PeerDetails>,
+}
+
+impl PeerInfo {
+    /// Creates a new `PeerInfo` message.
+    ///
+    /// # Arguments
+    ///
+    /// - `peers`: The statistics of every peer the node is connected to.
+    pub fn new(peers: Vec<PeerDetails>) -> PeerInfo {
+        PeerInfo {
+            command_name: Command::PeerInfo,
+            peers,
+        }
+    }
+
+    /// Parses a `PeerInfo` message from the given byte stream.
+    ///
+    /// # Arguments
+    ///
+    /// - `command_name`: The command of the message.
+    /// - `stream`: A mutable reference to a byte stream from which to parse the message.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `PeerInfo` message, or a `MessageError` if the parsing
+    /// fails, or if the amount of messages of a peer or the length of a string exceeds
+    /// `MAX_WALLET_FRAME_SIZE`.
+    pub fn from_bytes(
+        command_name: Command,
+        stream: &mut dyn Read,
+    ) -> Result<PeerInfo, MessageError> {
+        let count = read_u32_from_bytes(stream, true)?;
+        let mut peers = Vec::new();
+
+        for _ in 0..count {
+            peers.push(PeerDetails::from_bytes(stream)?);
+        }
+
+        Ok(PeerInfo {
+            command_name,
+            peers,
+        })
+    }
+
+    /// Serializes the `PeerInfo` message into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.command_name.to_bytes();
+        buffer.extend((self.peers.len() as u32).to_le_bytes());
+
+        for peer in self.peers.iter() {
+            buffer.extend(peer.to_bytes());
+        }
+
+        buffer
+    }
+
+    pub fn get_peers(&self) -> &Vec<PeerDetails> {
+        &self.peers
+    }
+}
+
+#[cfg(test)]
+mod peer_info_test {
+    use super::*;
+
+    #[test]
+    fn test_peer_info_to_bytes_and_from_bytes() -> Result<(), MessageError> {
+        let peer_info = PeerInfo::new(vec![
+            PeerDetails::new(
+                "127.0.0.1:18333".to_string(),
+                (1200, 3400),
+                1700000000,
+                Some(85),
+                10,
+                vec![("inv".to_string(), 3), ("ping".to_string(), 2)],
+            ),
+            PeerDetails::new("[::1]:18333".to_string(), (0, 0), 0, None, 0, vec![]),
+        ]);
+        let bytes = peer_info.to_bytes();
+        let mut stream = &bytes[..];
+
+        let command_name = Command::from_bytes(&mut stream)?;
+        let parsed = PeerInfo::from_bytes(command_name, &mut stream)?;
+
+        assert_eq!(parsed, peer_info);
+        Ok(())
+    }
+
+    #[test]
+    fn test_oversized_lengths_are_refused() {
+        // A single peer whose address is u64::MAX bytes long
+        let oversized = [1u32.to_le_bytes().as_slice(), &[0xff; 9]].concat();
+
+        assert!(matches!(
+            PeerInfo::from_bytes(Command::PeerInfo, &mut oversized.as_slice()),
+            Err(MessageError::TooManyElements)
+        ));
+    }
+}
@@ -0,0 +1,88 @@
+use crate::messages::command::Command;
+use crate::network::network_constants::MAX_WALLET_FRAME_SIZE;
+use std::io::Read;
+
+use crate::messages::{
+    compact_size::CompactSizeUInt, message_error::MessageError,
+    read_from_bytes::read_vec_from_bytes,
+};
+
+/// Represents a "raw_block" message, the answer to a "get_block" message.
+///
+/// # Fields
+///
+/// - `command_name`: The command of the message.
+/// - `raw_block`: The serialized block.
+#[derive(Debug, PartialEq)]
+pub struct RawBlock {
+    command_name: Command,
+    raw_block: Vec<u8>,
+}
+
+impl RawBlock {
+    /// Creates a new `RawBlock` message.
+    ///
+    /// # Arguments
+    ///
+    /// - `raw_block`: The serialized block.
+    pub fn new(raw_block: Vec<u8>) -> RawBlock {
+        RawBlock {
+            command_name: Command::RawBlock,
+            raw_block,
+        }
+    }
+
+    /// Parses a `RawBlock` message from the given byte stream.
+    ///
+    /// # Arguments
+    ///
+    /// - `command_name`: The command of the message.
+    /// - `stream`: A mutable reference to a byte stream from which to parse the message.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `RawBlock` message, or a `MessageError` if the raw block
+    /// cannot be read or is longer than `MAX_WALLET_FRAME_SIZE`.
+    pub fn from_bytes(
+        command_name: Command,
+        stream: &mut dyn Read,
+    ) -> Result<RawBlock, MessageError> {
+        let raw_block_size =
+            CompactSizeUInt::from_bytes_bounded(stream, MAX_WALLET_FRAME_SIZE as u64)?;
+        let raw_block = read_vec_from_bytes(stream, raw_block_size.value() as usize)?;
+
+        Ok(RawBlock {
+            command_name,
+            raw_block,
+        })
+    }
+
+    /// Serializes the `RawBlock` message into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.command_name.to_bytes();
+        buffer.extend(CompactSizeUInt::from_number(self.raw_block.len() as u64).to_bytes());
+        buffer.extend(&self.raw_block);
+
+        buffer
+    }
+
+    pub fn get_raw_block(&self) -> &Vec<u8> {
+        &self.raw_block
+    }
+}
+
+#[cfg(test)]
+mod raw_block_test {
+    use super::*;
+
+    #[test]
+    fn test_oversized_lengths_are_refused() {
+        // A block of u64::MAX bytes
+        let oversized = [0xff; 9].to_vec();
+
+        assert!(matches!(
+            RawBlock::from_bytes(Command::RawBlock, &mut oversized.as_slice()),
+            Err(MessageError::TooManyElements)
+        ));
+    }
+}
@@ -0,0 +1,69 @@
+use crate::messages::command::Command;
+use std::io::Read;
+
+use crate::messages::{message_error::MessageError, read_from_bytes::read_vec_from_bytes};
+
+/// Represents a "reconsider" message, sent by the operator to remove a block from the table of
+/// invalid blocks, so it is validated again the next time it is received.
+///
+/// The node answers with a "reconsidered" message, or with a "not_found" message if the block was
+/// not in the table.
+///
+/// # Fields
+///
+/// - `command_name`: The command of the message.
+/// - `block_hash`: The hash of the block to reconsider.
+#[derive(Debug, PartialEq)]
+pub struct ReconsiderBlock {
+    command_name: Command,
+    block_hash: Vec<u8>,
+}
+
+impl ReconsiderBlock {
+    /// Creates a new `ReconsiderBlock` message.
+    ///
+    /// # Arguments
+    ///
+    /// - `block_hash`: The hash of the block to reconsider.
+    pub fn new(block_hash: Vec<u8>) -> ReconsiderBlock {
+        ReconsiderBlock {
+            command_name: Command::Reconsider,
+            block_hash,
+        }
+    }
+
+    /// Parses a `ReconsiderBlock` message from the given byte stream.
+    ///
+    /// # Arguments
+    ///
+    /// - `command_name`: The command of the message.
+    /// - `stream`: A mutable reference to a byte stream from which to parse the message.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `ReconsiderBlock` message, or a `MessageError` if the
+    /// block hash cannot be read.
+    pub fn from_bytes(
+        command_name: Command,
+        stream: &mut dyn Read,
+    ) -> Result<ReconsiderBlock, MessageError> {
+        let block_hash = read_vec_from_bytes(stream, 32)?;
+
+        Ok(ReconsiderBlock {
+            command_name,
+            block_hash,
+        })
+    }
+
+    /// Serializes the `ReconsiderBlock` message into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.command_name.to_bytes();
+        buffer.extend(&self.block_hash);
+
+        buffer
+    }
+
+    pub fn get_block_hash(&self) -> &Vec<u8> {
+        &self.block_hash
+    }
+}
@@ -0,0 +1,79 @@
+use crate::messages::command::Command;
+use std::io::Read;
+
+use crate::messages::{message_error::MessageError, read_from_bytes::read_u64_from_bytes};
+
+/// Represents a "resume" message, sent by the wallet to get the events of the node it has not seen
+/// yet, such as the ones pushed while it was disconnected.
+///
+/// The node answers with an "events" message.
+///
+/// # Fields
+///
+/// - `command_name`: The command of the message.
+/// - `session_id`: The session of the node the wallet last saw, or 0 if it never saw one.
+/// - `last_seq`: The sequence number of the last event the wallet saw.
+#[derive(Debug, PartialEq)]
+pub struct ResumeEvents {
+    command_name: Command,
+    session_id: u64,
+    last_seq: u64,
+}
+
+impl ResumeEvents {
+    /// Creates a new `ResumeEvents` message.
+    ///
+    /// # Arguments
+    ///
+    /// - `session_id`: The session of the node the wallet last saw, or 0 if it never saw one.
+    /// - `last_seq`: The sequence number of the last event the wallet saw.
+    pub fn new(session_id: u64, last_seq: u64) -> ResumeEvents {
+        ResumeEvents {
+            command_name: Command::Resume,
+            session_id,
+            last_seq,
+        }
+    }
+
+    /// Parses a `ResumeEvents` message from the given byte stream.
+    ///
+    /// # Arguments
+    ///
+    /// - `command_name`: The command of the message.
+    /// - `stream`: A mutable reference to a byte stream from which to parse the message.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `ResumeEvents` message, or a `MessageError` if the
+    /// session or the sequence number cannot be read.
+    pub fn from_bytes(
+        command_name: Command,
+        stream: &mut dyn Read,
+    ) -> Result<ResumeEvents, MessageError> {
+        let session_id = read_u64_from_bytes(stream, true)?;
+        let last_seq = read_u64_from_bytes(stream, true)?;
+
+        Ok(ResumeEvents {
+            command_name,
+            session_id,
+            last_seq,
+        })
+    }
+
+    /// Serializes the `ResumeEvents` message into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.command_name.to_bytes();
+        buffer.extend(self.session_id.to_le_bytes());
+        buffer.extend(self.last_seq.to_le_bytes());
+
+        buffer
+    }
+
+    pub fn get_session_id(&self) -> u64 {
+        self.session_id
+    }
+
+    pub fn get_last_seq(&self) -> u64 {
+        self.last_seq
+    }
+}
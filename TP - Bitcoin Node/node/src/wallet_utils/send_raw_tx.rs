@@ -0,0 +1,129 @@
+use crate::messages::command::Command;
+use crate::network::network_constants::MAX_WALLET_FRAME_SIZE;
+use std::io::Read;
+
+use crate::messages::{
+    compact_size::CompactSizeUInt,
+    message_error::MessageError,
+    read_from_bytes::{decode_hex, read_vec_from_bytes},
+};
+
+/// Represents a "sendrawtx" message, sent to the node to submit a transaction signed outside of
+/// the wallet, such as an externally signed or PSBT-finalized one.
+///
+/// The transaction is carried as raw bytes, so a malformed transaction is rejected without losing
+/// track of the messages that follow it.
+///
+/// # Fields
+///
+/// - `command_name`: The command of the message.
+/// - `raw_tx`: The serialized transaction.
+#[derive(Debug, PartialEq)]
+pub struct SendRawTx {
+    command_name: Command,
+    raw_tx: Vec<u8>,
+}
+
+impl SendRawTx {
+    /// Creates a new `SendRawTx` message.
+    ///
+    /// # Arguments
+    ///
+    /// - `raw_tx`: The serialized transaction.
+    pub fn new(raw_tx: Vec<u8>) -> SendRawTx {
+        SendRawTx {
+            command_name: Command::SendRawTx,
+            raw_tx,
+        }
+    }
+
+    /// Creates a new `SendRawTx` message from a transaction serialized as a hexadecimal string.
+    ///
+    /// # Arguments
+    ///
+    /// - `hex`: The serialized transaction, encoded as a hexadecimal string.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `SendRawTx` message, or a `MessageError` if the string is not
+    /// valid hexadecimal.
+    pub fn from_hex(hex: &str) -> Result<SendRawTx, MessageError> {
+        if !hex.len().is_multiple_of(2) {
+            return Err(MessageError::InvalidInputSendRawTx);
+        }
+
+        let raw_tx = decode_hex(hex).map_err(|_| MessageError::InvalidInputSendRawTx)?;
+
+        Ok(SendRawTx::new(raw_tx))
+    }
+
+    /// Parses a `SendRawTx` message from the given byte stream.
+    ///
+    /// # Arguments
+    ///
+    /// - `command_name`: The command of the message.
+    /// - `stream`: A mutable reference to a byte stream from which to parse the message.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `SendRawTx` message, or a `MessageError` if the raw
+    /// transaction cannot be read or is longer than `MAX_WALLET_FRAME_SIZE`.
+    pub fn from_bytes(
+        command_name: Command,
+        stream: &mut dyn Read,
+    ) -> Result<SendRawTx, MessageError> {
+        let raw_tx_size =
+            CompactSizeUInt::from_bytes_bounded(stream, MAX_WALLET_FRAME_SIZE as u64)?;
+        let raw_tx = read_vec_from_bytes(stream, raw_tx_size.value() as usize)?;
+
+        Ok(SendRawTx {
+            command_name,
+            raw_tx,
+        })
+    }
+
+    /// Serializes the `SendRawTx` message into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.command_name.to_bytes();
+        buffer.extend(CompactSizeUInt::from_number(self.raw_tx.len() as u64).to_bytes());
+        buffer.extend(&self.raw_tx);
+
+        buffer
+    }
+
+    pub fn get_raw_tx(&self) -> &Vec<u8> {
+        &self.raw_tx
+    }
+}
+
+#[cfg(test)]
+mod send_raw_tx_test {
+    use super::*;
+
+    #[test]
+    fn test_send_raw_tx_to_bytes_and_from_bytes() -> Result<(), MessageError> {
+        let send_raw_tx = SendRawTx::from_hex("0200ff")?;
+        let bytes = send_raw_tx.to_bytes();
+        let mut stream = &bytes[..];
+
+        let command_name = Command::from_bytes(&mut stream)?;
+        let parsed = SendRawTx::from_bytes(command_name, &mut stream)?;
+
+        assert_eq!(parsed, send_raw_tx);
+        assert_eq!(parsed.get_raw_tx(), &vec![2, 0, 255]);
+        assert!(SendRawTx::from_hex("0g").is_err());
+        assert!(SendRawTx::from_hex("020").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_oversized_raw_tx_is_refused() {
+        // A length of u64::MAX
+        let oversized = [0xff; 9];
+
+        assert!(matches!(
+            SendRawTx::from_bytes(Command::SendRawTx, &mut &oversized[..]),
+            Err(MessageError::TooManyElements)
+        ));
+    }
+}
@@ -0,0 +1,129 @@
+use crate::messages::command::Command;
+use crate::network::network_constants::MAX_WALLET_FRAME_SIZE;
+use std::io::Read;
+
+use crate::messages::{
+    compact_size::CompactSizeUInt,
+    message_error::MessageError,
+    read_from_bytes::{decode_hex, read_vec_from_bytes},
+};
+
+/// Represents a "submitblock" message, sent to the node to submit a block mined outside of it,
+/// such as one mined on regtest by an external miner.
+///
+/// The block is carried as raw bytes, so a malformed block is rejected without losing track of the
+/// messages that follow it.
+///
+/// # Fields
+///
+/// - `command_name`: The command of the message.
+/// - `raw_block`: The serialized block.
+#[derive(Debug, PartialEq)]
+pub struct SubmitBlock {
+    command_name: Command,
+    raw_block: Vec<u8>,
+}
+
+impl SubmitBlock {
+    /// Creates a new `SubmitBlock` message.
+    ///
+    /// # Arguments
+    ///
+    /// - `raw_block`: The serialized block.
+    pub fn new(raw_block: Vec<u8>) -> SubmitBlock {
+        SubmitBlock {
+            command_name: Command::SubmitBlock,
+            raw_block,
+        }
+    }
+
+    /// Creates a new `SubmitBlock` message from a block serialized as a hexadecimal string.
+    ///
+    /// # Arguments
+    ///
+    /// - `hex`: The serialized block, encoded as a hexadecimal string.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `SubmitBlock` message, or a `MessageError` if the string is not
+    /// valid hexadecimal.
+    pub fn from_hex(hex: &str) -> Result<SubmitBlock, MessageError> {
+        if !hex.len().is_multiple_of(2) {
+            return Err(MessageError::InvalidInputSubmitBlock);
+        }
+
+        let raw_block = decode_hex(hex).map_err(|_| MessageError::InvalidInputSubmitBlock)?;
+
+        Ok(SubmitBlock::new(raw_block))
+    }
+
+    /// Parses a `SubmitBlock` message from the given byte stream.
+    ///
+    /// # Arguments
+    ///
+    /// - `command_name`: The command of the message.
+    /// - `stream`: A mutable reference to a byte stream from which to parse the message.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `SubmitBlock` message, or a `MessageError` if the raw block
+    /// cannot be read or is longer than `MAX_WALLET_FRAME_SIZE`.
+    pub fn from_bytes(
+        command_name: Command,
+        stream: &mut dyn Read,
+    ) -> Result<SubmitBlock, MessageError> {
+        let raw_block_size =
+            CompactSizeUInt::from_bytes_bounded(stream, MAX_WALLET_FRAME_SIZE as u64)?;
+        let raw_block = read_vec_from_bytes(stream, raw_block_size.value() as usize)?;
+
+        Ok(SubmitBlock {
+            command_name,
+            raw_block,
+        })
+    }
+
+    /// Serializes the `SubmitBlock` message into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.command_name.to_bytes();
+        buffer.extend(CompactSizeUInt::from_number(self.raw_block.len() as u64).to_bytes());
+        buffer.extend(&self.raw_block);
+
+        buffer
+    }
+
+    pub fn get_raw_block(&self) -> &Vec<u8> {
+        &self.raw_block
+    }
+}
+
+#[cfg(test)]
+mod submit_block_test {
+    use super::*;
+
+    #[test]
+    fn test_submit_block_to_bytes_and_from_bytes() -> Result<(), MessageError> {
+        let submit_block = SubmitBlock::from_hex("0100ff")?;
+        let bytes = submit_block.to_bytes();
+        let mut stream = &bytes[..];
+
+        let command_name = Command::from_bytes(&mut stream)?;
+        let parsed = SubmitBlock::from_bytes(command_name, &mut stream)?;
+
+        assert_eq!(parsed, submit_block);
+        assert_eq!(parsed.get_raw_block(), &vec![1, 0, 255]);
+        assert!(SubmitBlock::from_hex("0g").is_err());
+        assert!(SubmitBlock::from_hex("010").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_oversized_raw_block_is_refused() {
+        // A length of u64::MAX
+        let oversized = [0xff; 9];
+
+        assert!(matches!(
+            SubmitBlock::from_bytes(Command::SubmitBlock, &mut &oversized[..]),
+            Err(MessageError::TooManyElements)
+        ));
+    }
+}
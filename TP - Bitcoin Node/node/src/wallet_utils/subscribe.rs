@@ -0,0 +1,80 @@
+use crate::messages::command::Command;
+use std::io::Read;
+
+use crate::messages::{message_error::MessageError, read_from_bytes::read_u64_from_bytes};
+
+/// Represents a "subscribe" message, sent by the wallet to have the node push its events as they
+/// happen instead of polling for them.
+///
+/// The node answers with an "events" message holding the events the wallet missed, like it does
+/// for a "resume" message, and then pushes a "notify" message for every new event.
+///
+/// # Fields
+///
+/// - `command_name`: The command of the message.
+/// - `session_id`: The session of the node the wallet last saw, or 0 if it never saw one.
+/// - `last_seq`: The sequence number of the last event the wallet saw.
+#[derive(Debug, PartialEq)]
+pub struct Subscribe {
+    command_name: Command,
+    session_id: u64,
+    last_seq: u64,
+}
+
+impl Subscribe {
+    /// Creates a new `Subscribe` message.
+    ///
+    /// # Arguments
+    ///
+    /// - `session_id`: The session of the node the wallet last saw, or 0 if it never saw one.
+    /// - `last_seq`: The sequence number of the last event the wallet saw.
+    pub fn new(session_id: u64, last_seq: u64) -> Subscribe {
+        Subscribe {
+            command_name: Command::Subscribe,
+            session_id,
+            last_seq,
+        }
+    }
+
+    /// Parses a `Subscribe` message from the given byte stream.
+    ///
+    /// # Arguments
+    ///
+    /// - `command_name`: The command of the message.
+    /// - `stream`: A mutable reference to a byte stream from which to parse the message.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `Subscribe` message, or a `MessageError` if the session or
+    /// the sequence number cannot be read.
+    pub fn from_bytes(
+        command_name: Command,
+        stream: &mut dyn Read,
+    ) -> Result<Subscribe, MessageError> {
+        let session_id = read_u64_from_bytes(stream, true)?;
+        let last_seq = read_u64_from_bytes(stream, true)?;
+
+        Ok(Subscribe {
+            command_name,
+            session_id,
+            last_seq,
+        })
+    }
+
+    /// Serializes the `Subscribe` message into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.command_name.to_bytes();
+        buffer.extend(self.session_id.to_le_bytes());
+        buffer.extend(self.last_seq.to_le_bytes());
+
+        buffer
+    }
+
+    pub fn get_session_id(&self) -> u64 {
+        self.session_id
+    }
+
+    pub fn get_last_seq(&self) -> u64 {
+        self.last_seq
+    }
+}
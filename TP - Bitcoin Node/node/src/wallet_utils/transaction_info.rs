@@ -0,0 +1,167 @@
+use crate::messages::command::Command;
+use crate::network::network_constants::MAX_WALLET_FRAME_SIZE;
+use std::io::Read;
+
+use crate::messages::{
+    compact_size::CompactSizeUInt,
+    message_error::MessageError,
+    read_from_bytes::{read_u32_from_bytes, read_vec_from_bytes},
+};
+
+/// Represents a "tx_info" message, the answer to a "get_tx" message.
+///
+/// It carries the raw bytes of a transaction and its position in the chain. Transactions that are
+/// still in the mempool have a zeroed block hash, and zero height, index and confirmations.
+///
+/// # Fields
+///
+/// - `command_name`: The command of the message.
+/// - `raw_tx`: The serialized transaction.
+/// - `block_hash`: The hash of the block containing the transaction.
+/// - `height`: The height of the block containing the transaction.
+/// - `index`: The index of the transaction within the block, the coinbase being at index 0.
+/// - `confirmations`: The amount of blocks on top of the transaction, its block included.
+#[derive(Debug, PartialEq)]
+pub struct TransactionInfo {
+    command_name: Command,
+    raw_tx: Vec<u8>,
+    block_hash: Vec<u8>,
+    height: u32,
+    index: u32,
+    confirmations: u32,
+}
+
+impl TransactionInfo {
+    /// Creates a new `TransactionInfo` message.
+    ///
+    /// # Arguments
+    ///
+    /// - `raw_tx`: The serialized transaction.
+    /// - `block_hash`: The hash of the block containing the transaction.
+    /// - `height`: The height of the block containing the transaction.
+    /// - `index`: The index of the transaction within the block.
+    /// - `confirmations`: The amount of blocks on top of the transaction, its block included.
+    pub fn new(
+        raw_tx: Vec<u8>,
+        block_hash: Vec<u8>,
+        height: u32,
+        index: u32,
+        confirmations: u32,
+    ) -> TransactionInfo {
+        TransactionInfo {
+            command_name: Command::TxInfo,
+            raw_tx,
+            block_hash,
+            height,
+            index,
+            confirmations,
+        }
+    }
+
+    /// Creates a new `TransactionInfo` message for a transaction that is still in the mempool.
+    pub fn unconfirmed(raw_tx: Vec<u8>) -> TransactionInfo {
+        TransactionInfo::new(raw_tx, vec![0; 32], 0, 0, 0)
+    }
+
+    /// Parses a `TransactionInfo` message from the given byte stream.
+    ///
+    /// # Arguments
+    ///
+    /// - `command_name`: The command of the message.
+    /// - `stream`: A mutable reference to a byte stream from which to parse the message.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `TransactionInfo` message, or a `MessageError` if the
+    /// parsing fails or the raw transaction is longer than `MAX_WALLET_FRAME_SIZE`.
+    pub fn from_bytes(
+        command_name: Command,
+        stream: &mut dyn Read,
+    ) -> Result<TransactionInfo, MessageError> {
+        let raw_tx_size =
+            CompactSizeUInt::from_bytes_bounded(stream, MAX_WALLET_FRAME_SIZE as u64)?;
+        let raw_tx = read_vec_from_bytes(stream, raw_tx_size.value() as usize)?;
+        let block_hash = read_vec_from_bytes(stream, 32)?;
+        let height = read_u32_from_bytes(stream, true)?;
+        let index = read_u32_from_bytes(stream, true)?;
+        let confirmations = read_u32_from_bytes(stream, true)?;
+
+        Ok(TransactionInfo {
+            command_name,
+            raw_tx,
+            block_hash,
+            height,
+            index,
+            confirmations,
+        })
+    }
+
+    /// Serializes the `TransactionInfo` message into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.command_name.to_bytes();
+        buffer.extend(CompactSizeUInt::from_number(self.raw_tx.len() as u64).to_bytes());
+        buffer.extend(&self.raw_tx);
+        buffer.extend(&self.block_hash);
+        buffer.extend(self.height.to_le_bytes());
+        buffer.extend(self.index.to_le_bytes());
+        buffer.extend(self.confirmations.to_le_bytes());
+
+        buffer
+    }
+
+    pub fn get_raw_tx(&self) -> &Vec<u8> {
+        &self.raw_tx
+    }
+
+    pub fn get_block_hash(&self) -> &Vec<u8> {
+        &self.block_hash
+    }
+
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn get_index(&self) -> u32 {
+        self.index
+    }
+
+    pub fn get_confirmations(&self) -> u32 {
+        self.confirmations
+    }
+
+    /// Returns whether the transaction is already included in a block.
+    pub fn is_confirmed(&self) -> bool {
+        self.confirmations > 0
+    }
+}
+
+#[cfg(test)]
+mod transaction_info_test {
+    use super::*;
+
+    #[test]
+    fn test_transaction_info_to_bytes_and_from_bytes() -> Result<(), MessageError> {
+        let transaction_info = TransactionInfo::new(vec![1, 2, 3], vec![4; 32], 100, 2, 6);
+        let bytes = transaction_info.to_bytes();
+        let mut stream = &bytes[..];
+
+        let command_name = Command::from_bytes(&mut stream)?;
+        let parsed = TransactionInfo::from_bytes(command_name, &mut stream)?;
+
+        assert_eq!(parsed, transaction_info);
+        assert!(parsed.is_confirmed());
+        assert!(!TransactionInfo::unconfirmed(vec![1]).is_confirmed());
+        Ok(())
+    }
+
+    #[test]
+    fn test_oversized_lengths_are_refused() {
+        // A transaction of u64::MAX bytes
+        let oversized = [0xff; 9].to_vec();
+
+        assert!(matches!(
+            TransactionInfo::from_bytes(Command::TxInfo, &mut oversized.as_slice()),
+            Err(MessageError::TooManyElements)
+        ));
+    }
+}
@@ -1,13 +1,74 @@
 use super::tx_filter_error::TxFilterError;
 use crate::{
-    block_mod::{blockchain::BlockChain, mempool::Mempool, tx_out::TxOut, utxo::UnspentTx},
+    block_mod::{
+        block::Block, blockchain::BlockChain, mempool::Mempool, outpoint::Outpoint, tx_out::TxOut,
+        utxo::UnspentTx,
+    },
     wallet_utils::{
-        get_transactions::GetTransactions, transactions::Transactions, wallet_tx::WalletTx,
+        balance::Balance, get_transactions::GetTransactions, transactions::Transactions,
+        utxos::UnspentOutput, wallet_tx::WalletTx,
     },
 };
 use chrono::{Local, NaiveDateTime};
+use std::collections::HashSet;
 use std::sync::{Arc, Mutex};
 
+/// Filters the transactions of a block based on the provided criteria.
+///
+/// # Arguments
+///
+/// * `block` - The block to filter transactions from.
+/// * `pk_script` - A reference to a vector of bytes representing the public key script to filter transactions.
+/// * `public_key` - A reference to a vector of bytes representing the public key.
+///
+/// # Returns
+///
+/// A tuple containing the transactions sent and received by the public key, or a `TxFilterError`
+/// if the time of the block is not valid.
+pub fn filter_block_transactions(
+    block: &Block,
+    pk_script: &Vec<u8>,
+    public_key: &Vec<u8>,
+) -> Result<(Vec<WalletTx>, Vec<WalletTx>), TxFilterError> {
+    let datetime = NaiveDateTime::from_timestamp_opt(block.get_header().get_time() as i64, 0)
+        .ok_or(TxFilterError::DateTimeError)?;
+    let date = datetime.date().format("%Y-%m-%d").to_string();
+
+    let mut txs_send: Vec<WalletTx> = vec![];
+    let mut txs_recv: Vec<WalletTx> = vec![];
+
+    for transaction in block.get_txn_list() {
+        let mut signatures = transaction
+            .get_tx_in_list()
+            .iter()
+            .map(|tx_in| tx_in.get_signature_script());
+        let mut witnesses = transaction
+            .get_witness()
+            .iter()
+            .map(|witness| witness.get_pubkey());
+
+        if signatures.any(|signature| {
+            signature.len() >= 33 && signature[(signature.len() - 33)..] == *public_key
+        }) || witnesses.any(|witness| witness == *public_key)
+        {
+            txs_send.push(WalletTx::new(transaction.clone(), date.clone()));
+            continue;
+        }
+
+        let pk_script_list: Vec<Vec<u8>> = transaction
+            .get_tx_out_list()
+            .iter()
+            .map(|tx_out| tx_out.get_pk_script())
+            .collect();
+
+        if pk_script_list.contains(pk_script) {
+            txs_recv.push(WalletTx::new(transaction.clone(), date.clone()));
+        }
+    }
+
+    Ok((txs_send, txs_recv))
+}
+
 /// Filters confirmed transactions from the blockchain based on the provided criteria.
 ///
 /// # Arguments
@@ -37,42 +98,13 @@ fn filter_confirmed_transactions(
     let mut confirmed_txs_recv: Vec<WalletTx> = vec![];
 
     while let Some(block) = locked_blockchain.get_block(last_block_header) {
-        let datetime = NaiveDateTime::from_timestamp_opt(block.get_header().get_time() as i64, 0)
-            .ok_or(TxFilterError::DateTimeError)?;
-        let date = datetime.date().format("%Y-%m-%d").to_string();
-
         if block.get_header().get_time() <= last_update {
             break;
         }
 
-        for transaction in block.get_txn_list() {
-            let mut signatures = transaction
-                .get_tx_in_list()
-                .iter()
-                .map(|tx_in| tx_in.get_signature_script());
-            let mut witnesses = transaction
-                .get_witness()
-                .iter()
-                .map(|witness| witness.get_pubkey());
-
-            if signatures.any(|signature| {
-                signature.len() >= 33 && signature[(signature.len() - 33)..] == public_key
-            }) || witnesses.any(|witness| witness == public_key)
-            {
-                confirmed_txs_send.push(WalletTx::new(transaction.clone(), date.clone()));
-                continue;
-            }
-
-            let pk_script_list: Vec<Vec<u8>> = transaction
-                .get_tx_out_list()
-                .iter()
-                .map(|tx_out| tx_out.get_pk_script())
-                .collect();
-
-            if pk_script_list.contains(pk_script) {
-                confirmed_txs_recv.push(WalletTx::new(transaction.clone(), date.clone()));
-            }
-        }
+        let (txs_send, txs_recv) = filter_block_transactions(block, pk_script, &public_key)?;
+        confirmed_txs_send.extend(txs_send);
+        confirmed_txs_recv.extend(txs_recv);
 
         last_block_header = block.get_previuos_block_header();
     }
@@ -153,11 +185,15 @@ fn filter_unconfirmed_transactions(
 
 /// Filters unspent transaction outputs (UTXOs) from the provided UTxO hash map based on the given criteria.
 ///
+/// The outputs locked by the script are looked up in the index of the set, keeping those created by
+/// the given transactions. Outputs created by a coinbase that has not matured yet are left out,
+/// since they cannot be spent.
+///
 /// # Arguments
 ///
 /// * `utxo` - An `Arc<Mutex<UnspentTx>>` representing the UTxO hash map to filter UTXOs from.
 /// * `confirmed_txs` - A reference to a vector of `WalletTx` representing the confirmed transactions to filter UTXOs for.
-/// * `pk_script` - A reference to a vector of bytes representing the public key script to filter UTXOs.
+/// * `pk_script` - The public key script to filter UTXOs.
 ///
 /// # Returns
 ///
@@ -165,24 +201,20 @@ fn filter_unconfirmed_transactions(
 fn filter_utxo(
     utxo: &Arc<Mutex<UnspentTx>>,
     confirmed_txs: &[WalletTx],
-    pk_script: &Vec<u8>,
+    pk_script: &[u8],
 ) -> Result<Vec<(Vec<u8>, u32, TxOut)>, TxFilterError> {
     let mut utxo_txs: Vec<(Vec<u8>, u32, TxOut)> = vec![];
+    let tx_ids: HashSet<Vec<u8>> = confirmed_txs
+        .iter()
+        .map(|transaction| transaction.get_tx().get_id(false))
+        .collect();
     let locked_utxo = utxo.lock().map_err(|_| TxFilterError::LockUtxo)?;
 
-    for transaction in confirmed_txs.iter() {
-        if let Some(outputs) = locked_utxo
-            .get_utxo()
-            .get(&transaction.get_tx().get_id(false))
-        {
-            let current_txouts = outputs.iter().filter_map(|(&index, tx_out)| {
-                if tx_out.get_pk_script() == *pk_script {
-                    Some((transaction.get_tx().get_id(false), index, tx_out.clone()))
-                } else {
-                    None
-                }
-            });
-            utxo_txs.extend(current_txouts);
+    for (key, coin) in locked_utxo.get_by_script(pk_script) {
+        let outpoint = Outpoint::new(key.get_tx_id().to_vec(), key.get_index());
+
+        if tx_ids.contains(outpoint.get_tx_id()) && locked_utxo.is_mature(&outpoint) {
+            utxo_txs.push((key.get_tx_id().to_vec(), key.get_index(), coin.to_tx_out()));
         }
     }
     drop(locked_utxo);
@@ -201,13 +233,9 @@ fn filter_mempool(
         let tx = wallet_tx.get_tx();
 
         for tx_in in tx.get_tx_in_list() {
-            let prev_output = tx_in.get_prev_output();
-            let utxo_map = locked_utxo.get_utxo();
-
-            if let Some(inner_map) = utxo_map.get(prev_output.get_tx_id()) {
-                if let Some(tx_out) = inner_map.get(&prev_output.get_index()) {
-                    used_txouts.push((tx_out.clone(), -tx_out.get_value()));
-                }
+            if let Some(tx_out) = locked_utxo.get_tx_out(tx_in.get_prev_output()) {
+                let value = tx_out.get_value();
+                used_txouts.push((tx_out, -value));
             }
         }
     }
@@ -267,3 +295,230 @@ pub fn get_wallet_txns(
         last_update,
     ))
 }
+
+/// Computes the balance of a set of scripts from the UTXO set and the mempool.
+///
+/// The confirmed outputs of the scripts are split into the ones that can be spent by the next
+/// block and the coinbase outputs that have not matured yet. The transactions of the mempool add
+/// the value they pay to the scripts and subtract the value of the outputs of the scripts they
+/// spend, whether those were confirmed or created by other transactions of the mempool.
+///
+/// # Arguments
+///
+/// * `utxo` - The unspent transaction set.
+/// * `mempool` - The mempool.
+/// * `pk_scripts` - The scripts whose outputs are added up. Repeated scripts are counted once.
+pub fn get_balance(utxo: &UnspentTx, mempool: &Mempool, pk_scripts: &[Vec<u8>]) -> Balance {
+    let pk_scripts: HashSet<&[u8]> = pk_scripts
+        .iter()
+        .map(|pk_script| pk_script.as_slice())
+        .collect();
+    let mut confirmed: u64 = 0;
+    let mut immature: u64 = 0;
+
+    for pk_script in &pk_scripts {
+        for (key, coin) in utxo.get_by_script(pk_script) {
+            let value = coin.get_value().max(0) as u64;
+
+            if utxo.is_mature(&Outpoint::new(key.get_tx_id().to_vec(), key.get_index())) {
+                confirmed += value;
+            } else {
+                immature += value;
+            }
+        }
+    }
+
+    let mut unconfirmed: i64 = 0;
+    for tx in mempool.get_txs().values() {
+        for tx_out in tx.get_tx_out_list() {
+            if pk_scripts.contains(tx_out.get_pk_script().as_slice()) {
+                unconfirmed += tx_out.get_value();
+            }
+        }
+
+        for tx_in in tx.get_tx_in_list() {
+            let outpoint = tx_in.get_prev_output();
+            let spent = utxo.get_tx_out(outpoint).or_else(|| {
+                mempool
+                    .get_txs()
+                    .get(outpoint.get_tx_id())
+                    .and_then(|parent| parent.get_tx_out_list().get(outpoint.get_index() as usize))
+                    .cloned()
+            });
+
+            if let Some(spent) = spent {
+                if pk_scripts.contains(spent.get_pk_script().as_slice()) {
+                    unconfirmed -= spent.get_value();
+                }
+            }
+        }
+    }
+
+    Balance::new(confirmed, unconfirmed, immature)
+}
+
+/// Lists the unspent outputs of a set of scripts, sorted by outpoint, along with how many blocks
+/// confirm them.
+///
+/// # Arguments
+///
+/// * `utxo` - The unspent transaction set.
+/// * `pk_scripts` - The scripts whose unspent outputs are listed. Repeated scripts are listed once.
+/// * `get_height` - Returns the height of the block confirming the transaction with the given id,
+///   if it is known.
+pub fn get_utxos<F>(utxo: &UnspentTx, pk_scripts: &[Vec<u8>], get_height: F) -> Vec<UnspentOutput>
+where
+    F: Fn(&Vec<u8>) -> Option<u32>,
+{
+    let pk_scripts: HashSet<&[u8]> = pk_scripts
+        .iter()
+        .map(|pk_script| pk_script.as_slice())
+        .collect();
+    let mut coins: Vec<_> = pk_scripts
+        .iter()
+        .flat_map(|pk_script| utxo.get_by_script(pk_script))
+        .collect();
+    coins.sort_by_key(|(key, _)| (*key.get_tx_id(), key.get_index()));
+
+    coins
+        .into_iter()
+        .map(|(key, coin)| {
+            let tx_id = key.get_tx_id().to_vec();
+            let height = coin.get_coinbase_height().or_else(|| get_height(&tx_id));
+            let confirmations = match (utxo.get_height(), height) {
+                (Some(tip_height), Some(height)) if height <= tip_height => tip_height - height + 1,
+                _ => 0,
+            };
+            let mut displayed_tx_id = tx_id;
+            displayed_tx_id.reverse();
+
+            UnspentOutput::new(
+                displayed_tx_id,
+                key.get_index(),
+                coin.get_value(),
+                coin.get_pk_script().to_vec(),
+                confirmations,
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tx_filter_test {
+    use super::*;
+    use crate::{
+        block_mod::{
+            block::test_block_bytes, block_constants::COINBASE_MATURITY, transaction::Transaction,
+            tx_in::TxIn,
+        },
+        messages::message_error::MessageError,
+    };
+
+    fn transaction(prev_tx_id: Vec<u8>, prev_index: u32, tx_out_list: Vec<TxOut>) -> Transaction {
+        Transaction::new(
+            1,
+            vec![TxIn::new(prev_tx_id, prev_index, vec![], 0xffffffff)],
+            tx_out_list,
+            0,
+            false,
+        )
+    }
+
+    #[test]
+    fn test_balance_splits_confirmed_unconfirmed_and_immature_amounts() -> Result<(), MessageError>
+    {
+        let mut utxo = UnspentTx::new();
+        let mut mempool = Mempool::new();
+        let block = Block::from_bytes(&mut test_block_bytes().as_slice())?;
+        let coinbase_out = block.get_coinbase().tx_out_list[0].clone();
+        let own_script = coinbase_out.get_pk_script();
+        utxo.update(&block, 1);
+
+        let funding = transaction(vec![0; 32], 0, vec![TxOut::new(10000, own_script.clone())]);
+        utxo.update_transaction(&funding);
+
+        // Spends the confirmed output, keeping 6000 as change, and the change is spent in turn
+        let spending = transaction(
+            funding.get_id(false),
+            0,
+            vec![
+                TxOut::new(3000, vec![0x51]),
+                TxOut::new(6000, own_script.clone()),
+            ],
+        );
+        let chained = transaction(
+            spending.get_id(false),
+            1,
+            vec![TxOut::new(5500, vec![0x51])],
+        );
+        mempool.add(spending, Some(1000));
+        mempool.add(chained, Some(500));
+
+        let balance = get_balance(&utxo, &mempool, &[own_script.clone(), own_script.clone()]);
+        assert_eq!(balance.get_confirmed(), 10000);
+        assert_eq!(balance.get_unconfirmed(), -10000);
+        assert_eq!(balance.get_immature(), coinbase_out.get_value() as u64);
+
+        for height in 2..COINBASE_MATURITY + 1 {
+            let mut next = Block::from_bytes(&mut test_block_bytes().as_slice())?;
+            next.block_header.nonce = height;
+            next.coinbase.lock_time = height;
+            next.coinbase.tx_out_list[0] = TxOut::new(1, vec![0x51]);
+            utxo.update(&next, height);
+        }
+        let balance = get_balance(&utxo, &mempool, &[own_script]);
+        assert_eq!(
+            balance.get_confirmed(),
+            10000 + coinbase_out.get_value() as u64
+        );
+        assert_eq!(balance.get_immature(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_utxos_of_scripts_are_listed_with_their_confirmations() -> Result<(), MessageError> {
+        let mut utxo = UnspentTx::new();
+        let block = Block::from_bytes(&mut test_block_bytes().as_slice())?;
+        let coinbase_out = block.get_coinbase().tx_out_list[0].clone();
+        let own_script = coinbase_out.get_pk_script();
+        utxo.update(&block, 5);
+
+        let funding = transaction(
+            vec![0; 32],
+            0,
+            vec![
+                TxOut::new(10000, vec![0x51]),
+                TxOut::new(2000, own_script.clone()),
+            ],
+        );
+        utxo.update_transaction(&funding);
+        let funding_id = funding.get_id(false);
+
+        let outputs = get_utxos(&utxo, &[vec![0x51], own_script.clone()], |tx_id| {
+            (*tx_id == funding_id).then_some(4)
+        });
+        assert_eq!(outputs.len(), 3);
+
+        let mut displayed_funding_id = funding_id.clone();
+        displayed_funding_id.reverse();
+        for output in &outputs {
+            let expected = match output.get_tx_id() == &displayed_funding_id {
+                true => (
+                    funding.get_tx_out_list()[output.get_index() as usize].get_value(),
+                    2,
+                ),
+                false => (coinbase_out.get_value(), 1),
+            };
+            assert_eq!((output.get_value(), output.get_confirmations()), expected);
+        }
+
+        let outputs = get_utxos(&utxo, &[own_script.clone(), own_script], |_| None);
+        let confirmations: Vec<u32> = outputs
+            .iter()
+            .map(|output| output.get_confirmations())
+            .collect();
+        assert_eq!(outputs.len(), 2);
+        assert!(confirmations.contains(&0) && confirmations.contains(&1));
+        Ok(())
+    }
+}
@@ -0,0 +1,145 @@
+use crate::messages::command::Command;
+use crate::network::network_constants::MAX_WALLET_FRAME_SIZE;
+use std::io::Read;
+
+use crate::messages::{
+    compact_size::CompactSizeUInt,
+    message_error::MessageError,
+    read_from_bytes::{read_string_from_bytes, read_vec_from_bytes},
+};
+
+/// Represents a "tx_status" message, the answer to a "sendrawtx" message.
+///
+/// # Fields
+///
+/// - `command_name`: The command of the message.
+/// - `tx_id`: The id of the transaction, as displayed by block explorers, or empty if the raw
+///   transaction could not be parsed.
+/// - `reject_reason`: The reason the transaction was rejected for, named as Bitcoin Core does, or
+///   empty if it was accepted into the mempool.
+#[derive(Debug, PartialEq)]
+pub struct TxStatus {
+    command_name: Command,
+    tx_id: Vec<u8>,
+    reject_reason: String,
+}
+
+impl TxStatus {
+    /// Creates a new `TxStatus` message for a transaction accepted into the mempool.
+    ///
+    /// # Arguments
+    ///
+    /// - `tx_id`: The id of the transaction, as displayed by block explorers.
+    pub fn accepted(tx_id: Vec<u8>) -> TxStatus {
+        TxStatus {
+            command_name: Command::TxStatus,
+            tx_id,
+            reject_reason: String::new(),
+        }
+    }
+
+    /// Creates a new `TxStatus` message for a rejected transaction.
+    ///
+    /// # Arguments
+    ///
+    /// - `tx_id`: The id of the transaction, as displayed by block explorers, or empty if the raw
+    ///   transaction could not be parsed.
+    /// - `reject_reason`: The reason the transaction was rejected for.
+    pub fn rejected(tx_id: Vec<u8>, reject_reason: &str) -> TxStatus {
+        TxStatus {
+            command_name: Command::TxStatus,
+            tx_id,
+            reject_reason: reject_reason.to_string(),
+        }
+    }
+
+    /// Parses a `TxStatus` message from the given byte stream.
+    ///
+    /// # Arguments
+    ///
+    /// - `command_name`: The command of the message.
+    /// - `stream`: A mutable reference to a byte stream from which to parse the message.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `TxStatus` message, or a `MessageError` if the id or the
+    /// reason cannot be read or is longer than `MAX_WALLET_FRAME_SIZE`.
+    pub fn from_bytes(
+        command_name: Command,
+        stream: &mut dyn Read,
+    ) -> Result<TxStatus, MessageError> {
+        let tx_id_size = CompactSizeUInt::from_bytes_bounded(stream, MAX_WALLET_FRAME_SIZE as u64)?;
+        let tx_id = read_vec_from_bytes(stream, tx_id_size.value() as usize)?;
+        let reject_reason_size =
+            CompactSizeUInt::from_bytes_bounded(stream, MAX_WALLET_FRAME_SIZE as u64)?;
+        let reject_reason = read_string_from_bytes(stream, reject_reason_size.value() as usize)?;
+
+        Ok(TxStatus {
+            command_name,
+            tx_id,
+            reject_reason,
+        })
+    }
+
+    /// Serializes the `TxStatus` message into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.command_name.to_bytes();
+        buffer.extend(CompactSizeUInt::from_number(self.tx_id.len() as u64).to_bytes());
+        buffer.extend(&self.tx_id);
+        buffer.extend(CompactSizeUInt::from_number(self.reject_reason.len() as u64).to_bytes());
+        buffer.extend(self.reject_reason.as_bytes());
+
+        buffer
+    }
+
+    pub fn get_tx_id(&self) -> &Vec<u8> {
+        &self.tx_id
+    }
+
+    pub fn get_reject_reason(&self) -> &String {
+        &self.reject_reason
+    }
+
+    /// Returns whether the transaction was accepted into the mempool.
+    pub fn is_accepted(&self) -> bool {
+        self.reject_reason.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tx_status_test {
+    use super::*;
+
+    #[test]
+    fn test_tx_status_to_bytes_and_from_bytes() -> Result<(), MessageError> {
+        for tx_status in [
+            TxStatus::accepted(vec![7; 32]),
+            TxStatus::rejected(vec![7; 32], "txn-mempool-conflict"),
+            TxStatus::rejected(vec![], "TX decode failed"),
+        ] {
+            let bytes = tx_status.to_bytes();
+            let mut stream = &bytes[..];
+
+            let command_name = Command::from_bytes(&mut stream)?;
+            let parsed = TxStatus::from_bytes(command_name, &mut stream)?;
+
+            assert_eq!(
+                parsed.is_accepted(),
+                tx_status.get_reject_reason().is_empty()
+            );
+            assert_eq!(parsed, tx_status);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_oversized_lengths_are_refused() {
+        // A length of u64::MAX
+        let oversized = [0xff; 9];
+
+        assert!(matches!(
+            TxStatus::from_bytes(Command::TxStatus, &mut &oversized[..]),
+            Err(MessageError::TooManyElements)
+        ));
+    }
+}
@@ -1,33 +1,593 @@
 use std::{
-    io::Write,
+    fs,
     net::TcpStream,
-    sync::{Arc, Mutex},
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::Instant,
 };
 
 use crate::{
-    block_mod::{blockchain::BlockChain, mempool::Mempool, utxo::UnspentTx},
-    messages::{read_from_bytes::read_string_from_bytes, tx::Tx},
-    network::broadcasting::broadcast_new_txn,
+    block_mod::{
+        block::Block, block_header::BlockHeader, chain_state::ChainState,
+        chain_update::ChainUpdate, header_chain::HeaderChain, transaction::Transaction,
+    },
+    messages::{command::Command, message_error::MessageError},
+    network::{
+        broadcasting::{accept_to_mempool, broadcast_new_txn, submit_block, MempoolAcceptance},
+        network_constants::{DATA_DIRECTORY, MAX_FILTERS_PER_MESSAGE, WALLET_PROTOCOL_VERSION},
+        network_error::NetworkError,
+        peer_pool::PeerPool,
+        rebroadcast::WalletTxs,
+    },
     proof_of_inclusion_mod::proof_of_inclusion::send_proof,
     settings_mod::settings::Settings,
     wallet_utils::{
-        broadcast_txn::BroadcastTxn, get_proof::GetProof, get_transactions::GetTransactions,
-        tx_filter::get_wallet_txns,
+        auth_result::AuthResult,
+        block_filters::BlockFilters,
+        block_status::BlockStatus,
+        event_batch::EventBatch,
+        event_log::NodeEvent,
+        get_balance::GetBalance,
+        get_block::GetBlock,
+        get_filters::GetFilters,
+        get_headers_since::GetHeadersSince,
+        get_transaction::GetTransaction,
+        get_utxos::GetUtxos,
+        invalid_blocks_info::InvalidBlocksInfo,
+        node_info::NodeInfo,
+        peer_info::PeerInfo,
+        raw_block::RawBlock,
+        reconsider_block::ReconsiderBlock,
+        resume_events::ResumeEvents,
+        send_raw_tx::SendRawTx,
+        submit_block::SubmitBlock,
+        subscribe::Subscribe,
+        transaction_info::TransactionInfo,
+        tx_filter::{get_balance, get_utxos, get_wallet_txns},
+        tx_status::TxStatus,
+        utxos::Utxos,
+        wallet_auth::WalletAuth,
+        wallet_frame::{read_frame, write_frame, FrameWriter},
+        wallet_headers::WalletHeaders,
+        wallet_message::{WalletRequest, WalletResponse},
+        wallet_version::WalletVersion,
     },
 };
 
 use super::update_wallet_error::UpdateWalletError;
 
+/// Gathers the node's software version, the optional features enabled in its settings, uptime,
+/// network, data directory, and the status of its chain and connections.
+///
+/// # Arguments
+///
+/// * `settings` - The node settings.
+/// * `start_time` - The instant the node was started at.
+/// * `chain_state` - The shared blockchain, UTXO set, mempool and header chain.
+/// * `peer_pool` - The shared pool of connections to other nodes.
+///
+/// # Returns
+///
+/// The `NodeInfo` message to be sent to the wallet, or an `UpdateWalletError` if the shared
+/// structures cannot be locked.
+fn get_node_info(
+    settings: &Settings,
+    start_time: Instant,
+    chain_state: &ChainState,
+    peer_pool: &Arc<Mutex<PeerPool>>,
+) -> Result<NodeInfo, UpdateWalletError> {
+    let mut features = vec![];
+
+    if settings.get_wallet_auth_token().is_some() {
+        features.push("walletauth".to_string());
+    }
+    if settings.get_proxy().is_some() {
+        features.push("proxy".to_string());
+    }
+    if settings.get_capture_file().is_some() {
+        features.push("capture".to_string());
+    }
+
+    let data_dir = match fs::canonicalize(DATA_DIRECTORY) {
+        Ok(path) => path.display().to_string(),
+        Err(_) => DATA_DIRECTORY.to_string(),
+    };
+
+    let locked_header_chain = chain_state
+        .get_header_chain()
+        .lock()
+        .map_err(|_| UpdateWalletError::LockHeaderChain)?;
+    let headers_height = locked_header_chain.get_height();
+    let best_hash = match locked_header_chain.get_headers().last() {
+        Some(header) => header.get_header(),
+        None => vec![0; 32],
+    };
+    drop(locked_header_chain);
+
+    let locked_blockchain = chain_state
+        .get_blockchain()
+        .lock()
+        .map_err(|_| UpdateWalletError::LockBlockchain)?;
+    let blocks = locked_blockchain.cant_blocks() as u32;
+    if locked_blockchain.has_tx_index() {
+        features.push("txindex".to_string());
+    }
+    drop(locked_blockchain);
+    let locked_peer_pool = peer_pool
+        .lock()
+        .map_err(|_| UpdateWalletError::LockPeerPool)?;
+    let peer_count = locked_peer_pool.len() as u32;
+    let sync_stalled = locked_peer_pool.get_peer_heights().is_stalled();
+    drop(locked_peer_pool);
+
+    Ok(NodeInfo::new(
+        env!("CARGO_PKG_VERSION").to_string(),
+        features,
+        start_time.elapsed().as_secs(),
+        settings.get_network().get_name().to_string(),
+        data_dir,
+        (headers_height, best_hash, blocks, peer_count, sync_stalled),
+    ))
+}
+
+/// Sends the wallet every header from the given height on, and subscribes it to the new headers.
+///
+/// The new headers are pushed to the wallet from a separate thread as they are appended to the
+/// header chain, one `WalletHeaders` message per header, until the wallet disconnects.
+///
+/// # Arguments
+///
+/// * `writer` - The writer of the connection to the wallet.
+/// * `header_chain` - A reference to the `HeaderChain` wrapped in an `Arc<Mutex>`.
+/// * `height` - The height of the first header to be sent.
+///
+/// # Errors
+///
+/// Returns an `UpdateWalletError` if the header chain cannot be locked or the headers cannot be
+/// written to the wallet.
+fn send_headers_since(
+    writer: &FrameWriter,
+    header_chain: &Arc<Mutex<HeaderChain>>,
+    height: u32,
+) -> Result<(), UpdateWalletError> {
+    let writer = writer.clone();
+    let (tx, rx) = mpsc::channel::<(u32, BlockHeader)>();
+
+    let mut locked_header_chain = header_chain
+        .lock()
+        .map_err(|_| UpdateWalletError::LockHeaderChain)?;
+    let height = height.max(locked_header_chain.get_start_height());
+    let headers = WalletHeaders::new(height, locked_header_chain.get_headers_since(height));
+
+    writer
+        .write_frame(&headers.to_bytes())
+        .map_err(|_| UpdateWalletError::Write)?;
+    locked_header_chain.subscribe(tx);
+    drop(locked_header_chain);
+
+    thread::spawn(move || {
+        for (height, header) in rx {
+            let headers = WalletHeaders::new(height, vec![header]);
+
+            if writer.write_frame(&headers.to_bytes()).is_err() {
+                break;
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Subscribes the wallet to the events of the node, answering with the events it missed and then
+/// pushing them as they happen.
+///
+/// Every time an event of the chain or the mempool is published, a separate thread pushes the
+/// events logged since the last one pushed to the wallet as a "notify" message, until the wallet
+/// disconnects.
+///
+/// # Arguments
+///
+/// * `writer` - The writer of the connection to the wallet.
+/// * `chain_state` - The shared blockchain, UTXO set, mempool and header chain.
+/// * `subscribe` - The message carrying the last event seen by the wallet.
+///
+/// # Errors
+///
+/// Returns an `UpdateWalletError` if the log of the events cannot be locked or the missed events
+/// cannot be written to the wallet.
+fn subscribe_wallet(
+    writer: &FrameWriter,
+    chain_state: &ChainState,
+    subscribe: Subscribe,
+) -> Result<(), UpdateWalletError> {
+    let wallet_events = chain_state.get_wallet_events().clone();
+    let locked_events = wallet_events
+        .lock()
+        .map_err(|_| UpdateWalletError::LockWalletEvents)?;
+    let session_id = locked_events.get_session_id();
+    let mut last_seq = locked_events.get_last_seq();
+    let event_batch = EventBatch::new(
+        session_id,
+        last_seq,
+        locked_events.since(subscribe.get_session_id(), subscribe.get_last_seq()),
+    );
+    // Events are logged before they are published, so subscribing while the log is locked does not
+    // miss any event logged after the ones answered now
+    let events = chain_state.get_event_bus().subscribe();
+    drop(locked_events);
+
+    writer
+        .write_frame(&event_batch.to_bytes())
+        .map_err(|_| UpdateWalletError::Write)?;
+
+    let writer = writer.clone();
+    thread::spawn(move || {
+        for event in events {
+            if !event.is_chain_event() {
+                continue;
+            }
+
+            let notification = match wallet_events.lock() {
+                Ok(locked_events) => {
+                    let events = locked_events.since(session_id, last_seq);
+                    last_seq = locked_events.get_last_seq();
+                    EventBatch::notification(session_id, last_seq, events)
+                }
+                Err(_) => break,
+            };
+            if notification.is_complete() && notification.get_events().is_empty() {
+                continue;
+            }
+            if writer.write_frame(&notification.to_bytes()).is_err() {
+                break;
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Looks a transaction up in the downloaded blocks and in the mempool.
+///
+/// # Arguments
+///
+/// * `chain_state` - The shared blockchain, UTXO set, mempool and header chain.
+/// * `tx_id` - The id of the transaction, as displayed by block explorers.
+///
+/// # Returns
+///
+/// The `TransactionInfo` message to be sent to the wallet, `None` if the transaction is unknown, or
+/// an `UpdateWalletError` if the shared structures cannot be locked.
+fn get_transaction_info(
+    chain_state: &ChainState,
+    mut tx_id: Vec<u8>,
+) -> Result<Option<TransactionInfo>, UpdateWalletError> {
+    tx_id.reverse();
+
+    let locked_blockchain = chain_state
+        .get_blockchain()
+        .lock()
+        .map_err(|_| UpdateWalletError::LockBlockchain)?;
+
+    let block = match locked_blockchain.get_transaction_block(&tx_id) {
+        Some(block_header) => locked_blockchain.get_block(&block_header.get_header()),
+        None => None,
+    };
+
+    if let Some(block) = block {
+        let block_hash = block.get_header().get_header();
+        let index = match block.get_txn_ids().iter().position(|id| *id == tx_id) {
+            Some(index) => index,
+            None => return Ok(None),
+        };
+        let raw_tx = match index {
+            0 => block.get_coinbase().to_bytes(),
+            _ => {
+                let tx = &block.get_txn_list()[index - 1];
+                tx.to_bytes(tx.is_segwit())
+            }
+        };
+        drop(locked_blockchain);
+
+        let locked_header_chain = chain_state
+            .get_header_chain()
+            .lock()
+            .map_err(|_| UpdateWalletError::LockHeaderChain)?;
+        let (height, confirmations) = match locked_header_chain.get_height_of(&block_hash) {
+            Some(height) => (height, locked_header_chain.get_height() - height + 1),
+            None => (0, 0),
+        };
+        drop(locked_header_chain);
+
+        return Ok(Some(TransactionInfo::new(
+            raw_tx,
+            block_hash,
+            height,
+            index as u32,
+            confirmations,
+        )));
+    }
+    drop(locked_blockchain);
+
+    let locked_mempool = chain_state
+        .get_mempool()
+        .lock()
+        .map_err(|_| UpdateWalletError::LockMempool)?;
+
+    Ok(locked_mempool
+        .get_txs()
+        .get(&tx_id)
+        .map(|tx| TransactionInfo::unconfirmed(tx.to_bytes(tx.is_segwit()))))
+}
+
+/// Gathers the headers of the blocks from the given height on, along with their compact filters,
+/// so a light wallet can find the blocks relevant to it without revealing its scripts.
+///
+/// At most `MAX_FILTERS_PER_MESSAGE` headers are sent, and the wallet asks for the following ones
+/// once it processed them.
+///
+/// # Arguments
+///
+/// * `chain_state` - The shared blockchain, UTXO set, mempool and header chain.
+/// * `start_height` - The height of the first requested block.
+///
+/// # Returns
+///
+/// The `BlockFilters` message to be sent to the wallet, or an `UpdateWalletError` if the shared
+/// structures cannot be locked.
+fn get_block_filters(
+    chain_state: &ChainState,
+    start_height: u32,
+) -> Result<BlockFilters, UpdateWalletError> {
+    let locked_blockchain = chain_state
+        .get_blockchain()
+        .lock()
+        .map_err(|_| UpdateWalletError::LockBlockchain)?;
+    let locked_header_chain = chain_state
+        .get_header_chain()
+        .lock()
+        .map_err(|_| UpdateWalletError::LockHeaderChain)?;
+
+    let start_height = start_height.max(locked_header_chain.get_start_height());
+    let filters = locked_header_chain
+        .get_headers_since(start_height)
+        .into_iter()
+        .take(MAX_FILTERS_PER_MESSAGE)
+        .map(|header| {
+            let filter = match locked_blockchain.get_filter(&header.get_header()) {
+                Some(filter) => filter.to_bytes(),
+                None => vec![],
+            };
+            (header, filter)
+        })
+        .collect();
+
+    drop(locked_header_chain);
+    drop(locked_blockchain);
+    Ok(BlockFilters::new(start_height, filters))
+}
+
+/// Submits a block received through a "submitblock" message, running it through the validation of
+/// the blocks received from peers and connecting it if it is valid.
+///
+/// # Arguments
+///
+/// * `settings` - The node settings.
+/// * `chain_state` - The shared blockchain, UTXO set, mempool and header chain.
+/// * `peer_pool` - The shared pool of connections the block is announced to.
+/// * `submitted` - The message carrying the raw block.
+///
+/// # Returns
+///
+/// The `BlockStatus` message to be sent back, or an `UpdateWalletError` if the shared structures
+/// cannot be locked.
+fn submit_raw_block(
+    settings: &Arc<Settings>,
+    chain_state: &ChainState,
+    peer_pool: &Arc<Mutex<PeerPool>>,
+    submitted: SubmitBlock,
+) -> Result<BlockStatus, UpdateWalletError> {
+    let block = match Block::from_bytes(&mut submitted.get_raw_block().as_slice()) {
+        Ok(block) => block,
+        Err(_) => return Ok(BlockStatus::new("invalid")),
+    };
+
+    let known = chain_state
+        .get_blockchain()
+        .lock()
+        .map_err(|_| UpdateWalletError::LockBlockchain)?
+        .get_block(&block.get_header().get_header())
+        .is_some();
+
+    if known {
+        return Ok(BlockStatus::new("duplicate"));
+    }
+
+    let result = match submit_block(settings, chain_state, peer_pool, block) {
+        Ok(ChainUpdate::SideBranch) => "inconclusive",
+        Ok(_) => "accepted",
+        Err(NetworkError::InvalidBlock) => "invalid",
+        Err(_) => return Err(UpdateWalletError::SubmitBlock),
+    };
+
+    println!("Submitted block result: {}.\n", result);
+    Ok(BlockStatus::new(result))
+}
+
+/// Submits a transaction received through a "sendrawtx" message, running it through the policy of
+/// the mempool applied to the transactions received from peers. An accepted transaction is added
+/// to the mempool, broadcast, and rebroadcast until it is confirmed.
+///
+/// # Arguments
+///
+/// * `settings` - The node settings.
+/// * `chain_state` - The shared blockchain, UTXO set, mempool and header chain.
+/// * `peer_pool` - The shared pool of connections the transaction is broadcast to.
+/// * `wallet_txs` - The shared transactions broadcast for the wallet that are not confirmed yet.
+/// * `send_raw_tx` - The message carrying the raw transaction.
+///
+/// # Returns
+///
+/// The `TxStatus` message to be sent back, or an `UpdateWalletError` if the shared structures
+/// cannot be locked or the transaction cannot be broadcast.
+fn send_raw_transaction(
+    settings: &Arc<Settings>,
+    chain_state: &ChainState,
+    peer_pool: &Arc<Mutex<PeerPool>>,
+    wallet_txs: &Arc<Mutex<WalletTxs>>,
+    send_raw_tx: SendRawTx,
+) -> Result<TxStatus, UpdateWalletError> {
+    let mut raw_tx = send_raw_tx.get_raw_tx().as_slice();
+    let tx = match Transaction::from_bytes(&mut raw_tx) {
+        Ok(tx) if raw_tx.is_empty() => tx,
+        _ => return Ok(TxStatus::rejected(vec![], "TX decode failed")),
+    };
+    let tx_id = tx.get_id(false);
+    let mut displayed_tx_id = tx_id.clone();
+    displayed_tx_id.reverse();
+
+    let fee_rate = match accept_to_mempool(settings, chain_state, &tx)
+        .map_err(|_| UpdateWalletError::LockMempool)?
+    {
+        MempoolAcceptance::Accepted(fee_rate) => fee_rate,
+        MempoolAcceptance::NonStandard => {
+            return Ok(TxStatus::rejected(displayed_tx_id, "tx-size"))
+        }
+        MempoolAcceptance::Invalid(rule) => {
+            println!("Raw transaction rejected: {}.\n", rule.get_name());
+            return Ok(TxStatus::rejected(displayed_tx_id, rule.get_name()));
+        }
+        MempoolAcceptance::Evicted => {
+            return Ok(TxStatus::rejected(displayed_tx_id, "mempool full"))
+        }
+    };
+
+    if !chain_state.publish(NodeEvent::NewTx(tx_id)) {
+        return Err(UpdateWalletError::LockWalletEvents);
+    }
+
+    broadcast_new_txn(settings, &tx, peer_pool, fee_rate)
+        .map_err(|_| UpdateWalletError::BroadcastTx)?;
+
+    wallet_txs
+        .lock()
+        .map_err(|_| UpdateWalletError::BroadcastTx)?
+        .track(tx);
+
+    println!("Raw transaction accepted into the mempool.\n");
+    Ok(TxStatus::accepted(displayed_tx_id))
+}
+
+/// Negotiates the version of the wallet protocol, answering the version announced by the wallet
+/// with the one spoken by the node.
+///
+/// # Errors
+///
+/// Returns `UpdateWalletError::IncompatibleVersion` if the wallet did not start the connection
+/// announcing its version or speaks another version, and `UpdateWalletError::Read` or
+/// `UpdateWalletError::Write` if the wallet cannot be read from or written to.
+fn negotiate_version(wallet: &mut TcpStream) -> Result<(), UpdateWalletError> {
+    let (command, payload) = match read_frame(wallet) {
+        Ok(frame) => frame,
+        Err(MessageError::UnknownCommand | MessageError::PayloadTooLarge) => {
+            return Err(UpdateWalletError::IncompatibleVersion)
+        }
+        Err(_) => return Err(UpdateWalletError::Read),
+    };
+
+    if command != Command::WalletVersion {
+        return Err(UpdateWalletError::IncompatibleVersion);
+    }
+    let wallet_version = WalletVersion::from_bytes(command, &mut payload.as_slice())
+        .map_err(|_| UpdateWalletError::IncompatibleVersion)?;
+
+    write_frame(
+        wallet,
+        &WalletVersion::new(WALLET_PROTOCOL_VERSION).to_bytes(),
+    )
+    .map_err(|_| UpdateWalletError::Write)?;
+
+    if wallet_version.get_version() != WALLET_PROTOCOL_VERSION {
+        println!(
+            "Wallet speaks version {} of the wallet protocol, the node speaks version {}.\n",
+            wallet_version.get_version(),
+            WALLET_PROTOCOL_VERSION
+        );
+        return Err(UpdateWalletError::IncompatibleVersion);
+    }
+    Ok(())
+}
+
+/// Authenticates the wallet, comparing the token it sends with the one configured in the settings
+/// and answering whether it was accepted. If no token is configured, every wallet is accepted.
+///
+/// # Errors
+///
+/// Returns `UpdateWalletError::Unauthenticated` if the wallet did not send its token or sent
+/// another one, and `UpdateWalletError::Read` or `UpdateWalletError::Write` if the wallet cannot
+/// be read from or written to.
+fn authenticate(
+    wallet: &mut TcpStream,
+    auth_token: Option<&String>,
+) -> Result<(), UpdateWalletError> {
+    let (command, payload) = match read_frame(wallet) {
+        Ok(frame) => frame,
+        Err(MessageError::UnknownCommand | MessageError::PayloadTooLarge) => {
+            return Err(UpdateWalletError::Unauthenticated)
+        }
+        Err(_) => return Err(UpdateWalletError::Read),
+    };
+
+    let accepted = command == Command::WalletAuth
+        && match WalletAuth::from_bytes(command, &mut payload.as_slice()) {
+            Ok(wallet_auth) => auth_token
+                .is_none_or(|auth_token| tokens_match(wallet_auth.get_token(), auth_token)),
+            Err(_) => false,
+        };
+
+    write_frame(wallet, &AuthResult::new(accepted).to_bytes())
+        .map_err(|_| UpdateWalletError::Write)?;
+
+    if !accepted {
+        println!("Wallet failed to authenticate.\n");
+        return Err(UpdateWalletError::Unauthenticated);
+    }
+    if auth_token.is_none() {
+        println!(
+            "No wallet authentication token is configured, the wallet was not authenticated.\n"
+        );
+    }
+    Ok(())
+}
+
+/// Compares two tokens in a time that does not depend on how many of their leading bytes match,
+/// so the configured token cannot be guessed byte by byte.
+fn tokens_match(token: &str, expected: &str) -> bool {
+    token.len() == expected.len()
+        && token
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
 /// Updates the wallet by processing incoming commands from the connected `TcpStream`.
 ///
+/// Every message is exchanged as a frame holding the length of its payload, and the connection
+/// starts with the negotiation of the version of the wallet protocol, followed by the
+/// authentication of the wallet. Frames of unknown commands are skipped whole. Once the wallet
+/// subscribes, the events of the node are pushed to it over the same connection, so every frame is
+/// written through a `FrameWriter`. A wallet subscribes once per connection, and later "subscribe"
+/// messages are ignored.
+///
 /// # Arguments
 ///
 /// * `wallet` - The `TcpStream` representing the connection to the wallet.
-/// * `blockchain` - A reference to the `BlockChain` wrapped in an `Arc<Mutex>`.
-/// * `utxo` - A reference to the `UnspentTx` wrapped in an `Arc<Mutex>`.
-/// * `mempool` - A reference to the `Mempool` wrapped in an `Arc<Mutex>`.
+/// * `chain_state` - The shared blockchain, UTXO set, mempool and header chain.
 /// * `settings` - A reference to the `Settings` wrapped in an `Arc`.
-/// * `streams` - A vector of `TcpStream` wrapped in `Arc<Mutex>`, representing connections to other nodes.
+/// * `peer_pool` - The shared pool of connections to other nodes.
+/// * `wallet_txs` - The shared transactions broadcast for the wallet that are not confirmed yet.
+/// * `start_time` - The instant the node was started at, used to report its uptime.
 ///
 /// # Errors
 ///
@@ -35,73 +595,350 @@ use super::update_wallet_error::UpdateWalletError;
 /// parsing the incoming command, sending the proof, or broadcasting the transaction.
 pub fn update_wallet(
     mut wallet: TcpStream,
-    blockchain: Arc<Mutex<BlockChain>>,
-    utxo: Arc<Mutex<UnspentTx>>,
-    mempool: Arc<Mutex<Mempool>>,
+    chain_state: ChainState,
     settings: Arc<Settings>,
-    streams: Vec<Arc<Mutex<TcpStream>>>,
+    peer_pool: Arc<Mutex<PeerPool>>,
+    wallet_txs: Arc<Mutex<WalletTxs>>,
+    start_time: Instant,
 ) -> Result<(), UpdateWalletError> {
+    let blockchain = chain_state.get_blockchain();
+    let utxo = chain_state.get_utxo();
+    let mempool = chain_state.get_mempool();
+
+    negotiate_version(&mut wallet)?;
+    authenticate(&mut wallet, settings.get_wallet_auth_token())?;
+
+    let writer = FrameWriter::new(wallet.try_clone().map_err(|_| UpdateWalletError::Write)?);
+    let mut subscribed = false;
+
     loop {
-        let command_name =
-            read_string_from_bytes(&mut wallet, 12).map_err(|_| UpdateWalletError::Read)?;
-
-        match command_name.as_str() {
-            "get_txs" => {
-                println!("Message Get Transactions received.\n");
-
-                let locked_utxo = match utxo.lock() {
-                    Ok(locked_utxo) => locked_utxo,
-                    Err(_) => {
-                        println!("Failed to lock UTXO set.");
-                        continue;
-                    }
-                };
+        let (command, payload) = match read_frame(&mut wallet) {
+            Ok(frame) => frame,
+            Err(MessageError::UnknownCommand) => continue,
+            Err(_) => return Err(UpdateWalletError::Read),
+        };
 
-                for (_, inner_map) in locked_utxo.get_utxo().iter() {
-                    for (_, tx_out) in inner_map.iter() {
-                        if tx_out.get_pk_script()
+        match command {
+            Command::Request => match WalletRequest::from_bytes(&mut payload.as_slice())
+                .map_err(|_| UpdateWalletError::Read)?
+            {
+                WalletRequest::GetTransactions(get_transactions) => {
+                    println!("Message Get Transactions received.\n");
+
+                    let locked_utxo = match utxo.lock() {
+                        Ok(locked_utxo) => locked_utxo,
+                        Err(_) => {
+                            println!("Failed to lock UTXO set.");
+                            continue;
+                        }
+                    };
+
+                    for coin in locked_utxo.get_utxo().values() {
+                        if coin.get_pk_script()
                             == [
                                 118, 169, 20, 146, 119, 213, 38, 45, 53, 68, 169, 103, 41, 106, 12,
                                 219, 70, 253, 172, 54, 247, 192, 132, 136, 172,
                             ]
-                            .to_vec()
                         {
-                            println!("\n\n{}\n\n", tx_out.get_value());
+                            println!("\n\n{}\n\n", coin.get_value());
                         }
                     }
+                    drop(locked_utxo);
+
+                    let transactions = get_wallet_txns(blockchain, utxo, mempool, get_transactions)
+                        .map_err(|_| UpdateWalletError::GetTxn)?;
+
+                    let response = WalletResponse::Transactions(transactions)
+                        .to_bytes()
+                        .map_err(|_| UpdateWalletError::Write)?;
+                    writer
+                        .write_frame(&response)
+                        .map_err(|_| UpdateWalletError::Write)?;
                 }
+                WalletRequest::GetProof(get_proof) => {
+                    println!("Message Get Proof received.\n");
+                    let mut proof = vec![];
+                    send_proof(
+                        get_proof.get_block_header(),
+                        get_proof.get_tx_id(),
+                        blockchain,
+                        &mut proof,
+                    )
+                    .map_err(|_| UpdateWalletError::SendProof)?;
+
+                    writer
+                        .write_frame(&proof)
+                        .map_err(|_| UpdateWalletError::Write)?;
+                }
+                WalletRequest::BroadcastTxn(broadcast_txn) => {
+                    println!("Message Broadcast Tx received\n.");
+
+                    let tx = broadcast_txn.get_txn();
+                    let fee_rate = match accept_to_mempool(&settings, &chain_state, &tx)
+                        .map_err(|_| UpdateWalletError::LockMempool)?
+                    {
+                        MempoolAcceptance::Accepted(fee_rate) => fee_rate,
+                        MempoolAcceptance::NonStandard => {
+                            println!(
+                                "Transaction rejected: its weight exceeds the standard ({}).\n",
+                                tx.size_breakdown()
+                            );
+                            continue;
+                        }
+                        MempoolAcceptance::Invalid(rule) => {
+                            println!("Transaction rejected: {}.\n", rule.get_name());
+                            continue;
+                        }
+                        MempoolAcceptance::Evicted => {
+                            println!("Transaction rejected: the mempool is full.\n");
+                            continue;
+                        }
+                    };
+
+                    if !chain_state.publish(NodeEvent::NewTx(tx.get_id(false))) {
+                        return Err(UpdateWalletError::LockWalletEvents);
+                    }
+
+                    broadcast_new_txn(&settings, &tx, &peer_pool, fee_rate)
+                        .map_err(|_| UpdateWalletError::BroadcastTx)?;
+
+                    wallet_txs
+                        .lock()
+                        .map_err(|_| UpdateWalletError::BroadcastTx)?
+                        .track(tx);
+                }
+            },
+            Command::HeadersSince => {
+                println!("Message Get Headers Since received.\n");
+
+                let get_headers_since =
+                    GetHeadersSince::from_bytes(command, &mut payload.as_slice())
+                        .map_err(|_| UpdateWalletError::Read)?;
+
+                send_headers_since(
+                    &writer,
+                    chain_state.get_header_chain(),
+                    get_headers_since.get_height(),
+                )?;
+            }
+            Command::GetTx => {
+                println!("Message Get Transaction received.\n");
+
+                let get_transaction = GetTransaction::from_bytes(command, &mut payload.as_slice())
+                    .map_err(|_| UpdateWalletError::Read)?;
+
+                let answer = match get_transaction_info(&chain_state, get_transaction.get_tx_id())?
+                {
+                    Some(transaction_info) => transaction_info.to_bytes(),
+                    None => Command::NotFoundWallet.to_bytes(),
+                };
+
+                writer
+                    .write_frame(&answer)
+                    .map_err(|_| UpdateWalletError::Write)?;
+            }
+            Command::GetFilters => {
+                println!("Message Get Filters received.\n");
+
+                let get_filters = GetFilters::from_bytes(command, &mut payload.as_slice())
+                    .map_err(|_| UpdateWalletError::Read)?;
+
+                let block_filters =
+                    get_block_filters(&chain_state, get_filters.get_start_height())?;
+
+                writer
+                    .write_frame(&block_filters.to_bytes())
+                    .map_err(|_| UpdateWalletError::Write)?;
+            }
+            Command::GetBlock => {
+                println!("Message Get Block received.\n");
+
+                let get_block = GetBlock::from_bytes(command, &mut payload.as_slice())
+                    .map_err(|_| UpdateWalletError::Read)?;
+
+                let answer = match blockchain
+                    .lock()
+                    .map_err(|_| UpdateWalletError::LockBlockchain)?
+                    .get_block(&get_block.get_block_hash())
+                {
+                    Some(block) => RawBlock::new(block.to_bytes()).to_bytes(),
+                    None => Command::NotFoundWallet.to_bytes(),
+                };
+
+                writer
+                    .write_frame(&answer)
+                    .map_err(|_| UpdateWalletError::Write)?;
+            }
+            Command::SubmitBlock => {
+                println!("Message Submit Block received.\n");
+
+                let submitted = SubmitBlock::from_bytes(command, &mut payload.as_slice())
+                    .map_err(|_| UpdateWalletError::Read)?;
+
+                let block_status =
+                    submit_raw_block(&settings, &chain_state, &peer_pool, submitted)?;
+
+                writer
+                    .write_frame(&block_status.to_bytes())
+                    .map_err(|_| UpdateWalletError::Write)?;
+            }
+            Command::SendRawTx => {
+                println!("Message Send Raw Transaction received.\n");
+
+                let send_raw_tx = SendRawTx::from_bytes(command, &mut payload.as_slice())
+                    .map_err(|_| UpdateWalletError::Read)?;
+
+                let tx_status = send_raw_transaction(
+                    &settings,
+                    &chain_state,
+                    &peer_pool,
+                    &wallet_txs,
+                    send_raw_tx,
+                )?;
+
+                writer
+                    .write_frame(&tx_status.to_bytes())
+                    .map_err(|_| UpdateWalletError::Write)?;
+            }
+            Command::GetBalance => {
+                println!("Message Get Balance received.\n");
+
+                let get_balance_message = GetBalance::from_bytes(command, &mut payload.as_slice())
+                    .map_err(|_| UpdateWalletError::Read)?;
+
+                let locked_utxo = utxo.lock().map_err(|_| UpdateWalletError::LockUtxo)?;
+                let locked_mempool = mempool.lock().map_err(|_| UpdateWalletError::LockMempool)?;
+                let balance = get_balance(
+                    &locked_utxo,
+                    &locked_mempool,
+                    get_balance_message.get_pk_scripts(),
+                );
+                drop(locked_mempool);
+                drop(locked_utxo);
+
+                writer
+                    .write_frame(&balance.to_bytes())
+                    .map_err(|_| UpdateWalletError::Write)?;
+            }
+            Command::GetUtxos => {
+                println!("Message Get Utxos received.\n");
+
+                let get_utxos_message = GetUtxos::from_bytes(command, &mut payload.as_slice())
+                    .map_err(|_| UpdateWalletError::Read)?;
+
+                let locked_utxo = utxo.lock().map_err(|_| UpdateWalletError::LockUtxo)?;
+                let locked_blockchain = blockchain
+                    .lock()
+                    .map_err(|_| UpdateWalletError::LockBlockchain)?;
+                let outputs =
+                    get_utxos(&locked_utxo, get_utxos_message.get_pk_scripts(), |tx_id| {
+                        let block_header = locked_blockchain.get_transaction_block(tx_id)?;
+                        locked_blockchain.get_height(&block_header.get_header())
+                    });
+                drop(locked_blockchain);
                 drop(locked_utxo);
 
-                let get_transactions =
-                    GetTransactions::from_bytes(command_name.to_string(), &mut wallet)
+                writer
+                    .write_frame(&Utxos::new(outputs).to_bytes())
+                    .map_err(|_| UpdateWalletError::Write)?;
+            }
+            Command::GetInvalid => {
+                println!("Message Get Invalid Blocks received.\n");
+
+                let blocks = chain_state
+                    .get_invalid_blocks()
+                    .lock()
+                    .map_err(|_| UpdateWalletError::LockInvalidBlocks)?
+                    .get_blocks()
+                    .iter()
+                    .map(|(hash, invalid_block)| (hash.clone(), invalid_block.clone()))
+                    .collect();
+
+                writer
+                    .write_frame(&InvalidBlocksInfo::new(blocks).to_bytes())
+                    .map_err(|_| UpdateWalletError::Write)?;
+            }
+            Command::Reconsider => {
+                println!("Message Reconsider Block received.\n");
+
+                let reconsider_block =
+                    ReconsiderBlock::from_bytes(command, &mut payload.as_slice())
                         .map_err(|_| UpdateWalletError::Read)?;
-                let transactions = get_wallet_txns(&blockchain, &utxo, &mempool, get_transactions)
-                    .map_err(|_| UpdateWalletError::GetTxn)?;
 
-                wallet
-                    .write_all(&transactions.to_bytes())
+                let reconsidered = chain_state
+                    .get_invalid_blocks()
+                    .lock()
+                    .map_err(|_| UpdateWalletError::LockInvalidBlocks)?
+                    .reconsider(reconsider_block.get_block_hash())
+                    .map_err(|_| UpdateWalletError::Write)?;
+
+                let answer = match reconsidered {
+                    true => Command::Reconsidered,
+                    false => Command::NotFoundWallet,
+                };
+
+                writer
+                    .write_frame(&answer.to_bytes())
                     .map_err(|_| UpdateWalletError::Write)?;
             }
-            "get_proof" => {
-                println!("Message Get Proof received.\n");
-                let get_proof = GetProof::from_bytes(command_name.to_string(), &mut wallet)
+            Command::Resume => {
+                println!("Message Resume Events received.\n");
+
+                let resume_events = ResumeEvents::from_bytes(command, &mut payload.as_slice())
                     .map_err(|_| UpdateWalletError::Read)?;
-                send_proof(
-                    get_proof.get_block_header(),
-                    get_proof.get_tx_id(),
-                    &blockchain,
-                    &mut wallet,
-                )
-                .map_err(|_| UpdateWalletError::SendProof)?;
-            }
-            "broadcast_tx" => {
-                println!("Message Broadcast Tx received\n.");
-
-                let broadcast_txn = BroadcastTxn::from_bytes(command_name.to_string(), &mut wallet)
+
+                let locked_events = chain_state
+                    .get_wallet_events()
+                    .lock()
+                    .map_err(|_| UpdateWalletError::LockWalletEvents)?;
+                let events = locked_events
+                    .since(resume_events.get_session_id(), resume_events.get_last_seq());
+                let event_batch = EventBatch::new(
+                    locked_events.get_session_id(),
+                    locked_events.get_last_seq(),
+                    events,
+                );
+                drop(locked_events);
+
+                if !event_batch.is_complete() {
+                    println!("The missed events are no longer kept, the wallet must rescan.\n");
+                }
+
+                writer
+                    .write_frame(&event_batch.to_bytes())
+                    .map_err(|_| UpdateWalletError::Write)?;
+            }
+            Command::Subscribe => {
+                println!("Message Subscribe received.\n");
+
+                let subscribe = Subscribe::from_bytes(command, &mut payload.as_slice())
                     .map_err(|_| UpdateWalletError::Read)?;
-                let tx_msg = Tx::new(settings.get_start_string(), broadcast_txn.get_txn());
 
-                broadcast_new_txn(tx_msg, &streams).map_err(|_| UpdateWalletError::BroadcastTx)?;
+                if !subscribed {
+                    subscribe_wallet(&writer, &chain_state, subscribe)?;
+                    subscribed = true;
+                }
+            }
+            Command::GetPeerInfo => {
+                println!("Message Get Peer Info received.\n");
+
+                let peers = peer_pool
+                    .lock()
+                    .map_err(|_| UpdateWalletError::LockPeerPool)?
+                    .get_peer_info();
+
+                writer
+                    .write_frame(&PeerInfo::new(peers).to_bytes())
+                    .map_err(|_| UpdateWalletError::Write)?;
+            }
+            Command::GetNodeInfo => {
+                println!("Message Get Node Info received.\n");
+
+                let node_info = get_node_info(&settings, start_time, &chain_state, &peer_pool)?;
+
+                writer
+                    .write_frame(&node_info.to_bytes())
+                    .map_err(|_| UpdateWalletError::Write)?;
             }
             _ => {}
         }
@@ -1,9 +1,18 @@
 #[derive(Debug)]
 pub enum UpdateWalletError {
     Read,
+    IncompatibleVersion,
+    Unauthenticated,
     Write,
     SendProof,
     BroadcastTx,
     LockMempool,
     GetTxn,
+    LockHeaderChain,
+    LockBlockchain,
+    LockPeerPool,
+    LockUtxo,
+    SubmitBlock,
+    LockInvalidBlocks,
+    LockWalletEvents,
 }
@@ -0,0 +1,196 @@
+use crate::messages::command::Command;
+use crate::network::network_constants::MAX_WALLET_FRAME_SIZE;
+use std::io::Read;
+
+use crate::messages::{
+    compact_size::CompactSizeUInt,
+    message_error::MessageError,
+    read_from_bytes::{read_i64_from_bytes, read_u32_from_bytes, read_vec_from_bytes},
+};
+
+/// Represents an unspent output of a script requested through a "getutxos" message.
+///
+/// # Fields
+///
+/// - `tx_id`: The id of the transaction that created the output, as displayed by block explorers.
+/// - `index`: The index of the output within its transaction.
+/// - `value`: The value of the output, in satoshis.
+/// - `pk_script`: The script locking the output.
+/// - `confirmations`: The number of blocks confirming the output, or 0 if the block that confirmed
+///   it is not known.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnspentOutput {
+    tx_id: Vec<u8>,
+    index: u32,
+    value: i64,
+    pk_script: Vec<u8>,
+    confirmations: u32,
+}
+
+impl UnspentOutput {
+    pub fn new(
+        tx_id: Vec<u8>,
+        index: u32,
+        value: i64,
+        pk_script: Vec<u8>,
+        confirmations: u32,
+    ) -> UnspentOutput {
+        UnspentOutput {
+            tx_id,
+            index,
+            value,
+            pk_script,
+            confirmations,
+        }
+    }
+
+    fn from_bytes(stream: &mut dyn Read) -> Result<UnspentOutput, MessageError> {
+        let tx_id = read_vec_from_bytes(stream, 32)?;
+        let index = read_u32_from_bytes(stream, true)?;
+        let value = read_i64_from_bytes(stream, true)?;
+        let pk_script_size =
+            CompactSizeUInt::from_bytes_bounded(stream, MAX_WALLET_FRAME_SIZE as u64)?;
+        let pk_script = read_vec_from_bytes(stream, pk_script_size.value() as usize)?;
+        let confirmations = read_u32_from_bytes(stream, true)?;
+
+        Ok(UnspentOutput::new(
+            tx_id,
+            index,
+            value,
+            pk_script,
+            confirmations,
+        ))
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.tx_id.clone();
+        buffer.extend(self.index.to_le_bytes());
+        buffer.extend(self.value.to_le_bytes());
+        buffer.extend(CompactSizeUInt::from_number(self.pk_script.len() as u64).to_bytes());
+        buffer.extend(&self.pk_script);
+        buffer.extend(self.confirmations.to_le_bytes());
+
+        buffer
+    }
+
+    pub fn get_tx_id(&self) -> &Vec<u8> {
+        &self.tx_id
+    }
+
+    pub fn get_index(&self) -> u32 {
+        self.index
+    }
+
+    pub fn get_value(&self) -> i64 {
+        self.value
+    }
+
+    pub fn get_pk_script(&self) -> &Vec<u8> {
+        &self.pk_script
+    }
+
+    pub fn get_confirmations(&self) -> u32 {
+        self.confirmations
+    }
+}
+
+/// Represents a "utxos" message, the answer to a "getutxos" message.
+///
+/// # Fields
+///
+/// - `command_name`: The command of the message.
+/// - `outputs`: The unspent outputs of the requested scripts.
+#[derive(Debug, PartialEq)]
+pub struct Utxos {
+    command_name: Command,
+    outputs: Vec<UnspentOutput>,
+}
+
+impl Utxos {
+    /// Creates a new `Utxos` message.
+    ///
+    /// # Arguments
+    ///
+    /// - `outputs`: The unspent outputs of the requested scripts.
+    pub fn new(outputs: Vec<UnspentOutput>) -> Utxos {
+        Utxos {
+            command_name: Command::Utxos,
+            outputs,
+        }
+    }
+
+    /// Parses a `Utxos` message from the given byte stream.
+    ///
+    /// # Arguments
+    ///
+    /// - `command_name`: The command of the message.
+    /// - `stream`: A mutable reference to a byte stream from which to parse the message.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `Utxos` message, or a `MessageError` if the outputs cannot
+    /// be read, or if their count or the length of one of their scripts exceeds
+    /// `MAX_WALLET_FRAME_SIZE`.
+    pub fn from_bytes(command_name: Command, stream: &mut dyn Read) -> Result<Utxos, MessageError> {
+        let count = CompactSizeUInt::from_bytes_bounded(stream, MAX_WALLET_FRAME_SIZE as u64)?;
+        let mut outputs = vec![];
+
+        for _ in 0..count.value() {
+            outputs.push(UnspentOutput::from_bytes(stream)?);
+        }
+
+        Ok(Utxos {
+            command_name,
+            outputs,
+        })
+    }
+
+    /// Serializes the `Utxos` message into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.command_name.to_bytes();
+        buffer.extend(CompactSizeUInt::from_number(self.outputs.len() as u64).to_bytes());
+
+        for output in &self.outputs {
+            buffer.extend(output.to_bytes());
+        }
+
+        buffer
+    }
+
+    pub fn get_outputs(&self) -> &Vec<UnspentOutput> {
+        &self.outputs
+    }
+}
+
+#[cfg(test)]
+mod utxos_test {
+    use super::*;
+
+    #[test]
+    fn test_utxos_to_bytes_and_from_bytes() -> Result<(), MessageError> {
+        let utxos = Utxos::new(vec![
+            UnspentOutput::new(vec![1; 32], 0, 5000, vec![0x51], 3),
+            UnspentOutput::new(vec![2; 32], 7, 120_000, vec![0x00, 0x14, 9, 9], 0),
+        ]);
+        let bytes = utxos.to_bytes();
+        let mut stream = &bytes[..];
+
+        let command_name = Command::from_bytes(&mut stream)?;
+        let parsed = Utxos::from_bytes(command_name, &mut stream)?;
+
+        assert_eq!(parsed, utxos);
+        assert_eq!(parsed.get_outputs()[1].get_index(), 7);
+        Ok(())
+    }
+
+    #[test]
+    fn test_oversized_lengths_are_refused() {
+        // A length of u64::MAX
+        let oversized = [0xff; 9];
+
+        assert!(matches!(
+            Utxos::from_bytes(Command::Utxos, &mut &oversized[..]),
+            Err(MessageError::TooManyElements)
+        ));
+    }
+}
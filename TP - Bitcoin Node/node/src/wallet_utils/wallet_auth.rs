@@ -0,0 +1,101 @@
+use crate::messages::command::Command;
+use std::io::Read;
+
+use crate::{
+    messages::{
+        compact_size::CompactSizeUInt, message_error::MessageError,
+        read_from_bytes::read_string_from_bytes,
+    },
+    network::network_constants::MAX_WALLET_AUTH_TOKEN_SIZE,
+};
+
+/// Represents a "wallet_auth" message, sent by the wallet right after the version negotiation to
+/// authenticate itself with the token shared with the node.
+///
+/// # Fields
+///
+/// - `command_name`: The command of the message.
+/// - `token`: The authentication token of the wallet.
+#[derive(Debug, PartialEq)]
+pub struct WalletAuth {
+    command_name: Command,
+    token: String,
+}
+
+impl WalletAuth {
+    /// Creates a new `WalletAuth` message.
+    ///
+    /// # Arguments
+    ///
+    /// - `token`: The authentication token of the wallet.
+    pub fn new(token: &str) -> WalletAuth {
+        WalletAuth {
+            command_name: Command::WalletAuth,
+            token: token.to_string(),
+        }
+    }
+
+    /// Parses a `WalletAuth` message from the given byte stream.
+    ///
+    /// # Arguments
+    ///
+    /// - `command_name`: The command of the message.
+    /// - `stream`: A mutable reference to a byte stream from which to parse the message.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `WalletAuth` message, or a `MessageError` if the token
+    /// cannot be read or is longer than `MAX_WALLET_AUTH_TOKEN_SIZE`.
+    pub fn from_bytes(
+        command_name: Command,
+        stream: &mut dyn Read,
+    ) -> Result<WalletAuth, MessageError> {
+        let token_bytes = CompactSizeUInt::from_bytes_bounded(stream, MAX_WALLET_AUTH_TOKEN_SIZE)?;
+        let token = read_string_from_bytes(stream, token_bytes.value() as usize)?;
+
+        Ok(WalletAuth {
+            command_name,
+            token,
+        })
+    }
+
+    /// Serializes the `WalletAuth` message into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.command_name.to_bytes();
+        buffer.extend(CompactSizeUInt::from_number(self.token.len() as u64).to_bytes());
+        buffer.extend(self.token.as_bytes());
+
+        buffer
+    }
+
+    pub fn get_token(&self) -> &String {
+        &self.token
+    }
+}
+
+#[cfg(test)]
+mod wallet_auth_test {
+    use super::*;
+
+    #[test]
+    fn test_wallet_auth_round_trip_and_rejects_long_tokens() -> Result<(), MessageError> {
+        let wallet_auth = WalletAuth::new("secret");
+        let bytes = wallet_auth.to_bytes();
+        let mut stream = &bytes[12..];
+
+        assert_eq!(
+            WalletAuth::from_bytes(Command::WalletAuth, &mut stream)?,
+            wallet_auth
+        );
+
+        let long_token = "a".repeat(MAX_WALLET_AUTH_TOKEN_SIZE as usize + 1);
+        let bytes = WalletAuth::new(&long_token).to_bytes();
+        let mut stream = &bytes[12..];
+
+        assert!(matches!(
+            WalletAuth::from_bytes(Command::WalletAuth, &mut stream),
+            Err(MessageError::TooManyElements)
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+use crate::{
+    messages::{
+        command::Command,
+        message_error::MessageError,
+        read_from_bytes::{read_string_from_bytes, read_u32_from_bytes, read_vec_from_bytes},
+    },
+    network::network_constants::MAX_WALLET_FRAME_SIZE,
+};
+use std::{
+    io::{self, Read, Write},
+    net::TcpStream,
+    sync::{Arc, Mutex},
+};
+
+/// Writes a message of the wallet protocol as a frame: its 12-byte command, followed by the length
+/// of its payload as a little-endian `u32` and the payload itself.
+///
+/// # Arguments
+///
+/// * `stream` - The stream to write the frame to.
+/// * `message` - The serialized message, its command followed by its payload.
+///
+/// # Errors
+///
+/// Returns an `io::Error` if the message is shorter than a command or cannot be written.
+pub fn write_frame(stream: &mut dyn Write, message: &[u8]) -> io::Result<()> {
+    if message.len() < 12 {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput));
+    }
+
+    let (command, payload) = message.split_at(12);
+    let mut frame = command.to_vec();
+    frame.extend((payload.len() as u32).to_le_bytes());
+    frame.extend(payload);
+
+    stream.write_all(&frame)
+}
+
+/// Writes frames to a connection shared by several threads, such as the one answering the requests
+/// of a wallet and the ones pushing it notifications, so their frames never interleave.
+#[derive(Debug, Clone)]
+pub struct FrameWriter {
+    stream: Arc<Mutex<TcpStream>>,
+}
+
+impl FrameWriter {
+    pub fn new(stream: TcpStream) -> FrameWriter {
+        FrameWriter {
+            stream: Arc::new(Mutex::new(stream)),
+        }
+    }
+
+    /// Writes a message as a whole frame, waiting for the frames being written by other threads.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `io::Error` if the connection cannot be locked or the frame cannot be written.
+    pub fn write_frame(&self, message: &[u8]) -> io::Result<()> {
+        let mut stream = self
+            .stream
+            .lock()
+            .map_err(|_| io::Error::from(io::ErrorKind::Other))?;
+
+        write_frame(&mut *stream, message)
+    }
+}
+
+/// Reads a frame of the wallet protocol, waiting for its whole payload.
+///
+/// Since the payload is consumed along with its frame, neither an unknown command nor a malformed
+/// payload leaves the stream in the middle of a message.
+///
+/// # Returns
+///
+/// The command of the frame along with its payload, `MessageError::UnknownCommand` if the command
+/// is unknown, or `MessageError::PayloadTooLarge` if the payload exceeds `MAX_WALLET_FRAME_SIZE`.
+pub fn read_frame(stream: &mut dyn Read) -> Result<(Command, Vec<u8>), MessageError> {
+    let name = read_string_from_bytes(stream, 12)?;
+    let length = read_u32_from_bytes(stream, true)?;
+
+    if length > MAX_WALLET_FRAME_SIZE {
+        return Err(MessageError::PayloadTooLarge);
+    }
+
+    let payload = read_vec_from_bytes(stream, length as usize)?;
+    let command = Command::from_name(&name).ok_or(MessageError::UnknownCommand)?;
+
+    Ok((command, payload))
+}
+
+#[cfg(test)]
+mod wallet_frame_test {
+    use super::*;
+    use crate::wallet_utils::{get_filters::GetFilters, get_node_info::GetNodeInfo};
+    use std::{net::TcpListener, thread};
+
+    #[test]
+    fn test_unknown_frames_are_skipped_whole() -> Result<(), MessageError> {
+        let mut stream = vec![];
+        let mut unknown = "unknown".as_bytes().to_vec();
+        unknown.resize(12, 0);
+        unknown.extend([1, 2, 3]);
+
+        write_frame(&mut stream, &unknown)?;
+        write_frame(&mut stream, &GetFilters::new(7).to_bytes())?;
+
+        let mut stream = stream.as_slice();
+        assert!(matches!(
+            read_frame(&mut stream),
+            Err(MessageError::UnknownCommand)
+        ));
+
+        let (command, payload) = read_frame(&mut stream)?;
+        assert_eq!(command, Command::GetFilters);
+        assert_eq!(
+            GetFilters::from_bytes(command, &mut payload.as_slice())?,
+            GetFilters::new(7)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_frames_written_from_several_threads_do_not_interleave() -> Result<(), MessageError> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let writer = FrameWriter::new(TcpStream::connect(listener.local_addr()?)?);
+        let (mut reader, _) = listener.accept()?;
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let writer = writer.clone();
+                thread::spawn(move || {
+                    for start_height in 0..50 {
+                        writer.write_frame(&GetFilters::new(start_height).to_bytes())?;
+                        writer.write_frame(&GetNodeInfo::new().to_bytes())?;
+                    }
+                    Ok::<(), io::Error>(())
+                })
+            })
+            .collect();
+        for handle in handles {
+            assert!(handle.join().is_ok_and(|result| result.is_ok()));
+        }
+
+        for _ in 0..400 {
+            let (command, payload) = read_frame(&mut reader)?;
+            assert!(matches!(
+                (command, payload.len()),
+                (Command::GetFilters, 4) | (Command::GetNodeInfo, 0)
+            ));
+        }
+        Ok(())
+    }
+}
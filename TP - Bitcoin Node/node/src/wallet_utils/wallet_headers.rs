@@ -0,0 +1,108 @@
+use crate::messages::command::Command;
+use std::io::Read;
+
+use crate::{
+    block_mod::block_header::BlockHeader,
+    messages::{message_error::MessageError, read_from_bytes::read_u32_from_bytes},
+};
+
+/// Represents a "wallet_hdrs" message, carrying consecutive block headers to the wallet.
+///
+/// # Fields
+///
+/// - `command_name`: The command of the message.
+/// - `start_height`: The height of the first header of the message.
+/// - `headers`: The block headers, ordered by height.
+#[derive(Debug)]
+pub struct WalletHeaders {
+    command_name: Command,
+    start_height: u32,
+    headers: Vec<BlockHeader>,
+}
+
+impl WalletHeaders {
+    /// Creates a new `WalletHeaders` message.
+    ///
+    /// # Arguments
+    ///
+    /// - `start_height`: The height of the first header of the message.
+    /// - `headers`: The block headers, ordered by height.
+    pub fn new(start_height: u32, headers: Vec<BlockHeader>) -> WalletHeaders {
+        WalletHeaders {
+            command_name: Command::WalletHeaders,
+            start_height,
+            headers,
+        }
+    }
+
+    /// Parses a `WalletHeaders` message from the given byte stream.
+    ///
+    /// # Arguments
+    ///
+    /// - `command_name`: The command of the message.
+    /// - `stream`: A mutable reference to a byte stream from which to parse the message.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `WalletHeaders` message, or a `MessageError` if the
+    /// parsing fails.
+    pub fn from_bytes(
+        command_name: Command,
+        stream: &mut dyn Read,
+    ) -> Result<WalletHeaders, MessageError> {
+        let start_height = read_u32_from_bytes(stream, true)?;
+        let count = read_u32_from_bytes(stream, true)?;
+        let mut headers = Vec::new();
+
+        for _ in 0..count {
+            headers.push(BlockHeader::from_bytes(stream)?);
+        }
+
+        Ok(WalletHeaders {
+            command_name,
+            start_height,
+            headers,
+        })
+    }
+
+    /// Serializes the `WalletHeaders` message into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.command_name.to_bytes();
+        buffer.extend(self.start_height.to_le_bytes());
+        buffer.extend((self.headers.len() as u32).to_le_bytes());
+
+        for header in self.headers.iter() {
+            buffer.extend(header.to_bytes());
+        }
+
+        buffer
+    }
+
+    pub fn get_start_height(&self) -> u32 {
+        self.start_height
+    }
+
+    pub fn get_headers(&self) -> &Vec<BlockHeader> {
+        &self.headers
+    }
+}
+
+#[cfg(test)]
+mod wallet_headers_test {
+    use super::*;
+
+    #[test]
+    fn test_wallet_headers_to_bytes_and_from_bytes() -> Result<(), MessageError> {
+        let header = BlockHeader::new(1, vec![0; 32], vec![1; 32], 2, 3, 4);
+        let wallet_headers = WalletHeaders::new(10, vec![header.clone(), header.clone()]);
+        let bytes = wallet_headers.to_bytes();
+        let mut stream = &bytes[..];
+
+        let command_name = Command::from_bytes(&mut stream)?;
+        let parsed = WalletHeaders::from_bytes(command_name, &mut stream)?;
+
+        assert_eq!(parsed.get_start_height(), 10);
+        assert_eq!(parsed.get_headers(), &vec![header.clone(), header]);
+        Ok(())
+    }
+}
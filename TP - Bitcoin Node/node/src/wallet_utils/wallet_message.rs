@@ -0,0 +1,113 @@
+use super::{
+    broadcast_txn::BroadcastTxn, get_proof::GetProof, get_transactions::GetTransactions,
+    merkle_block::MerkleBlock, transactions::Transactions,
+};
+use crate::{
+    messages::{command::Command, message_error::MessageError},
+    network::network_constants::MAX_WALLET_FRAME_SIZE,
+};
+use bincode::Options;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::io::Read;
+
+/// A request of the wallet to the node, sent as the bincode-encoded payload of a "request" message.
+/// Adding a request to the wallet protocol only takes adding a variant.
+#[derive(Debug, Serialize, Deserialize)]
+pub enum WalletRequest {
+    GetTransactions(GetTransactions),
+    GetProof(GetProof),
+    BroadcastTxn(BroadcastTxn),
+}
+
+/// A response of the node to a `WalletRequest`, sent as the bincode-encoded payload of a
+/// "response" message.
+#[derive(Debug, Serialize, Deserialize)]
+pub enum WalletResponse {
+    Transactions(Transactions),
+    MerkleBlock(MerkleBlock),
+    NotFound,
+}
+
+impl WalletRequest {
+    /// Serializes the request into a "request" message.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MessageError::PayloadTooLarge` if the request exceeds `MAX_WALLET_FRAME_SIZE`.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MessageError> {
+        encode(Command::Request, self)
+    }
+
+    /// Parses the payload of a "request" message.
+    pub fn from_bytes(stream: &mut dyn Read) -> Result<WalletRequest, MessageError> {
+        decode(stream)
+    }
+}
+
+impl WalletResponse {
+    /// Serializes the response into a "response" message.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MessageError::PayloadTooLarge` if the response exceeds `MAX_WALLET_FRAME_SIZE`.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MessageError> {
+        encode(Command::Response, self)
+    }
+
+    /// Parses the payload of a "response" message.
+    pub fn from_bytes(stream: &mut dyn Read) -> Result<WalletResponse, MessageError> {
+        decode(stream)
+    }
+}
+
+/// Returns the bincode options shared by the wallet and the node, bounding the size of the
+/// messages to the one of a frame.
+fn options() -> impl Options {
+    bincode::DefaultOptions::new().with_limit(MAX_WALLET_FRAME_SIZE as u64)
+}
+
+fn encode<T: Serialize>(command: Command, message: &T) -> Result<Vec<u8>, MessageError> {
+    let mut buffer = command.to_bytes();
+    buffer.extend(options().serialize(message)?);
+
+    Ok(buffer)
+}
+
+fn decode<T: DeserializeOwned>(stream: &mut dyn Read) -> Result<T, MessageError> {
+    Ok(options().deserialize_from(stream)?)
+}
+
+#[cfg(test)]
+mod wallet_message_test {
+    use super::*;
+    use crate::block_mod::tx_out::TxOut;
+
+    #[test]
+    fn test_responses_round_trip() -> Result<(), MessageError> {
+        let tx_out = TxOut::new(1000, vec![0x51]);
+        let response = WalletResponse::Transactions(Transactions::new(
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+            vec![(vec![7; 32], 1, tx_out.clone())],
+            vec![(tx_out.clone(), 1000)],
+            1_700_000_000,
+        ));
+
+        let bytes = response.to_bytes()?;
+        assert_eq!(Command::from_bytes(&mut &bytes[..12])?, Command::Response);
+
+        let transactions = match WalletResponse::from_bytes(&mut &bytes[12..])? {
+            WalletResponse::Transactions(transactions) => transactions,
+            other => panic!("unexpected response: {:?}", other),
+        };
+        let (txid, index, utxo) = &transactions.get_utxo()[0];
+
+        assert_eq!((txid, *index), (&vec![7; 32], 1));
+        assert_eq!(utxo.to_bytes(), tx_out.to_bytes());
+        assert_eq!(transactions.get_used_txouts()[0].1, 1000);
+        assert_eq!(transactions.get_last_update(), 1_700_000_000);
+        Ok(())
+    }
+}
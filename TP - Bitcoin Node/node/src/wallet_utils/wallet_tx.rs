@@ -1,11 +1,8 @@
-use crate::{
-    block_mod::transaction::Transaction,
-    messages::{message_error::MessageError, read_from_bytes::read_string_from_bytes},
-};
-use std::io::Read;
+use crate::block_mod::transaction::Transaction;
+use serde::{Deserialize, Serialize};
 
 /// Represents a wallet transaction.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct WalletTx {
     transaction: Transaction,
     date: String,
@@ -26,40 +23,6 @@ impl WalletTx {
         WalletTx { transaction, date }
     }
 
-    /// Creates a `WalletTx` object by deserializing it from a byte stream.
-    ///
-    /// # Arguments
-    ///
-    /// * `stream`: A mutable reference to a type that implements the `Read` trait, providing the byte stream to deserialize from.
-    ///
-    /// # Returns
-    ///
-    /// A `Result` containing either the deserialized `WalletTx` object or a `MessageError` if deserialization fails.
-    ///
-    /// # Errors
-    ///
-    /// The function can return a `MessageError` if there is an error during deserialization.
-    pub fn from_bytes(stream: &mut dyn Read) -> Result<WalletTx, MessageError> {
-        let transaction = Transaction::from_bytes(stream)?;
-        let date = read_string_from_bytes(stream, 10)?;
-
-        Ok(WalletTx { transaction, date })
-    }
-
-    /// Serializes the `WalletTx` object into a byte vector.
-    ///
-    /// # Returns
-    ///
-    /// A byte vector containing the serialized representation of the `WalletTx` object.
-    pub fn to_bytes(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-
-        buffer.extend(&self.transaction.to_bytes(self.transaction.is_segwit()));
-        buffer.extend(self.date.as_bytes());
-
-        buffer
-    }
-
     pub fn get_tx(&self) -> &Transaction {
         &self.transaction
     }
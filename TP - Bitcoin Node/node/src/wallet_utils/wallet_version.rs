@@ -0,0 +1,66 @@
+use crate::messages::command::Command;
+use std::io::Read;
+
+use crate::messages::{message_error::MessageError, read_from_bytes::read_u32_from_bytes};
+
+/// Represents a "wallet_ver" message, the first message exchanged on a connection between the
+/// wallet and the node, through which both announce the version of the wallet protocol they speak.
+///
+/// # Fields
+///
+/// - `command_name`: The command of the message.
+/// - `version`: The version of the wallet protocol.
+#[derive(Debug, PartialEq)]
+pub struct WalletVersion {
+    command_name: Command,
+    version: u32,
+}
+
+impl WalletVersion {
+    /// Creates a new `WalletVersion` message.
+    ///
+    /// # Arguments
+    ///
+    /// - `version`: The version of the wallet protocol.
+    pub fn new(version: u32) -> WalletVersion {
+        WalletVersion {
+            command_name: Command::WalletVersion,
+            version,
+        }
+    }
+
+    /// Parses a `WalletVersion` message from the given byte stream.
+    ///
+    /// # Arguments
+    ///
+    /// - `command_name`: The command of the message.
+    /// - `stream`: A mutable reference to a byte stream from which to parse the message.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `WalletVersion` message, or a `MessageError` if the version
+    /// cannot be read.
+    pub fn from_bytes(
+        command_name: Command,
+        stream: &mut dyn Read,
+    ) -> Result<WalletVersion, MessageError> {
+        let version = read_u32_from_bytes(stream, true)?;
+
+        Ok(WalletVersion {
+            command_name,
+            version,
+        })
+    }
+
+    /// Serializes the `WalletVersion` message into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.command_name.to_bytes();
+        buffer.extend(self.version.to_le_bytes());
+
+        buffer
+    }
+
+    pub fn get_version(&self) -> u32 {
+        self.version
+    }
+}
@@ -1,3 +1,10 @@
+use crate::transactions::{
+    create_transactios_constants::{
+        DEFAULT_DUPLICATE_PAYMENT_WINDOW_DAYS, DEFAULT_MIN_INCOMING_AMOUNT,
+    },
+    payment_warnings::get_receive_address,
+    transaction_defaults::TransactionDefaults,
+};
 use crate::user_info::UserInfo;
 use node::wallet_utils::transactions::Transactions;
 use std::collections::HashMap;
@@ -7,6 +14,11 @@ use std::collections::HashMap;
 pub struct Accounts {
     accounts: HashMap<String, UserInfo>,
     current_username: String,
+    min_incoming_amount: i64,
+    show_dust: bool,
+    tx_defaults: TransactionDefaults,
+    duplicate_payment_window: i64,
+    pending_rescans: Vec<String>,
 }
 
 impl Accounts {
@@ -14,6 +26,24 @@ impl Accounts {
         Accounts {
             accounts: HashMap::new(),
             current_username: String::default(),
+            min_incoming_amount: DEFAULT_MIN_INCOMING_AMOUNT,
+            show_dust: false,
+            tx_defaults: TransactionDefaults::default(),
+            duplicate_payment_window: DEFAULT_DUPLICATE_PAYMENT_WINDOW_DAYS,
+            pending_rescans: Vec::new(),
+        }
+    }
+
+    /// Creates an empty collection of accounts with a custom minimum incoming amount.
+    ///
+    /// # Arguments
+    ///
+    /// * `min_incoming_amount` - The minimum amount, in satoshis, an incoming transaction must
+    ///   carry to trigger a notification and to be listed in the history by default.
+    pub fn with_min_incoming_amount(min_incoming_amount: i64) -> Accounts {
+        Accounts {
+            min_incoming_amount,
+            ..Accounts::new()
         }
     }
 
@@ -50,10 +80,109 @@ impl Accounts {
         }
     }
 
+    /// Updates an account other than the current one with the transactions received from the node.
+    ///
+    /// # Arguments
+    ///
+    /// * `user_name` - The name of the account to update.
+    /// * `transactions` - The transactions received from the node.
+    pub fn update_account(&mut self, user_name: &str, transactions: &Transactions) {
+        if let Some(user_info) = self.accounts.get_mut(user_name) {
+            user_info.update(transactions);
+        }
+    }
+
+    pub fn get_account_info(&self, user_name: &str) -> Option<&UserInfo> {
+        self.accounts.get(user_name)
+    }
+
+    /// Schedules an account to have its whole history fetched from the node, as happens with
+    /// accounts imported from a wallet dump.
+    pub fn schedule_rescan(&mut self, user_name: String) {
+        if !self.pending_rescans.contains(&user_name) {
+            self.pending_rescans.push(user_name);
+        }
+    }
+
+    /// Schedules every account to have its whole history fetched from the node, as happens when
+    /// the node could not replay the events missed by the wallet.
+    pub fn schedule_full_rescan(&mut self) {
+        let mut user_names: Vec<String> = self.accounts.keys().cloned().collect();
+        user_names.sort();
+
+        for user_name in user_names {
+            self.schedule_rescan(user_name);
+        }
+    }
+
+    /// Returns whether accounts are waiting to be rescanned.
+    pub fn has_pending_rescan(&self) -> bool {
+        !self.pending_rescans.is_empty()
+    }
+
+    /// Takes the next account waiting to be rescanned, if any.
+    pub fn take_pending_rescan(&mut self) -> Option<String> {
+        if self.pending_rescans.is_empty() {
+            return None;
+        }
+        Some(self.pending_rescans.remove(0))
+    }
+
     pub fn get_accounts_count(&self) -> usize {
         self.accounts.iter().len()
     }
 
+    pub fn get_min_incoming_amount(&self) -> i64 {
+        self.min_incoming_amount
+    }
+
+    pub fn set_min_incoming_amount(&mut self, min_incoming_amount: i64) {
+        self.min_incoming_amount = min_incoming_amount;
+    }
+
+    pub fn get_show_dust(&self) -> bool {
+        self.show_dust
+    }
+
+    pub fn set_show_dust(&mut self, show_dust: bool) {
+        self.show_dust = show_dust;
+    }
+
+    pub fn get_tx_defaults(&self) -> &TransactionDefaults {
+        &self.tx_defaults
+    }
+
+    /// Sets the version and input sequence number of the transactions created from now on,
+    /// warning about the ones that disable replace-by-fee or relative lock times.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx_defaults` - The new transaction version and input sequence number.
+    pub fn set_tx_defaults(&mut self, tx_defaults: TransactionDefaults) {
+        for warning in tx_defaults.get_warnings() {
+            println!("Warning: {}", warning);
+        }
+        self.tx_defaults = tx_defaults;
+    }
+
+    pub fn get_duplicate_payment_window(&self) -> i64 {
+        self.duplicate_payment_window
+    }
+
+    /// Sets the amount of days a previous payment to the same recipient and amount is considered
+    /// recent enough to warn about a duplicate payment.
+    pub fn set_duplicate_payment_window(&mut self, duplicate_payment_window: i64) {
+        self.duplicate_payment_window = duplicate_payment_window;
+    }
+
+    /// Returns the receive addresses of every account of the wallet.
+    pub fn get_own_addresses(&self) -> Vec<Vec<u8>> {
+        self.accounts
+            .values()
+            .filter_map(|user_info| get_receive_address(user_info).ok())
+            .collect()
+    }
+
     pub fn set_actual_account(&mut self, active_account: String) {
         if self.accounts.get(&active_account).is_some() {
             self.current_username = active_account;
@@ -19,3 +19,7 @@ pub const UPPERCASE_HRP: &str = "TB";
 pub const SEPARATOR: &str = "1";
 pub const P2WPKH_HASH_LEN: usize = 20;
 pub const P2WPKH_VERSION: u8 = 0;
+pub const P2TR_VERSION: u8 = 1;
+pub const P2TR_PROGRAM_LEN: usize = 32;
+pub const BECH32_CONST: u32 = 1;
+pub const BECH32M_CONST: u32 = 0x2bc830a3;
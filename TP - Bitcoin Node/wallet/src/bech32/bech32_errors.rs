@@ -15,6 +15,7 @@ pub enum WitnessProgramError {
     InvalidPadding,
     InvalidVersion,
     InvalidLength,
+    InvalidVariant,
     UnsopportedConversion,
 }
 
@@ -1,5 +1,23 @@
 use super::{bech32_constants::*, bech32_errors::Bech32Error};
 
+/// Represents the checksum variants of the Bech32 encoding: the original one, used by version 0
+/// witness programs, and Bech32m (BIP350), used by every later version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bech32Variant {
+    Bech32,
+    Bech32m,
+}
+
+impl Bech32Variant {
+    /// Returns the constant the checksum of the variant is computed against.
+    fn constant(&self) -> u32 {
+        match self {
+            Bech32Variant::Bech32 => BECH32_CONST,
+            Bech32Variant::Bech32m => BECH32M_CONST,
+        }
+    }
+}
+
 /// Represents a Bech32-encoded data structure.
 ///
 /// This struct holds information about a Bech32-encoded data, which is commonly used in Bitcoin
@@ -9,6 +27,7 @@ use super::{bech32_constants::*, bech32_errors::Bech32Error};
 pub struct Bech32 {
     hrp: String,
     data: Vec<u8>,
+    variant: Bech32Variant,
 }
 
 impl Bech32 {
@@ -27,13 +46,22 @@ impl Bech32 {
     ///
     /// Returns a new `Bech32` instance containing the provided HRP and binary data.
     pub fn new(hrp: String, data: Vec<u8>) -> Self {
-        Bech32 { hrp, data }
+        Bech32 {
+            hrp,
+            data,
+            variant: Bech32Variant::Bech32,
+        }
+    }
+
+    /// Creates a new instance of `Bech32` whose checksum is computed with the given variant.
+    pub fn with_variant(hrp: String, data: Vec<u8>, variant: Bech32Variant) -> Self {
+        Bech32 { hrp, data, variant }
     }
 
     /// Creates a new `Bech32` instance from a Bech32-encoded address.
     ///
     /// This function decodes a Bech32-encoded address, verifies its checksum, and constructs a new `Bech32` instance
-    /// with the extracted human-readable part (HRP) and data bytes. Bech32 is a human-readable format commonly used
+    /// with the extracted human-readable part (HRP) and data bytes, and the variant its checksum matches. Bech32 is a human-readable format commonly used
     /// in Bitcoin and other cryptographic applications for encoding data using a base32 representation with error-detection codes.
     ///
     /// # Arguments
@@ -51,11 +79,12 @@ impl Bech32 {
 
         let lowercase_hrp: String = hrp.chars().map(|c| c.to_lowercase().to_string()).collect();
 
-        validate_checksum(lowercase_hrp.as_bytes(), &data_bytes)?;
+        let variant = validate_checksum(lowercase_hrp.as_bytes(), &data_bytes)?;
 
         Ok(Bech32 {
             hrp: lowercase_hrp,
             data: data_bytes[..data_bytes.len() - 6].to_vec(),
+            variant,
         })
     }
 
@@ -73,7 +102,7 @@ impl Bech32 {
         let hrp_bytes = self.hrp.clone().into_bytes();
         let mut data_bytes = self.data.clone();
 
-        data_bytes.extend(create_checksum(&data_bytes));
+        data_bytes.extend(create_checksum(&data_bytes, self.variant));
 
         let mut address = format!("{}{}", LOWERCASE_HRP, SEPARATOR);
 
@@ -90,6 +119,10 @@ impl Bech32 {
     pub fn data(&self) -> &[u8] {
         &self.data
     }
+
+    pub fn variant(&self) -> Bech32Variant {
+        self.variant
+    }
 }
 
 /// Splits a Bech32-encoded address into its human-readable part (HRP) and data segments.
@@ -234,7 +267,7 @@ fn polymod(values: Vec<u8>) -> u32 {
 ///
 /// This function validates the checksum of a Bech32 address by performing the necessary expansion of the human-readable
 /// part (HRP) and data, and then calculating the polynomial modulo operation using the expanded values. If the calculated
-/// checksum is not equal to 1, it indicates an invalid checksum, resulting in an error.
+/// checksum matches neither the Bech32 nor the Bech32m constant, it indicates an invalid checksum, resulting in an error.
 ///
 /// # Arguments
 ///
@@ -243,18 +276,18 @@ fn polymod(values: Vec<u8>) -> u32 {
 ///
 /// # Returns
 ///
-/// Returns `Ok(())` if the checksum validation is successful, or an `Err` containing a `Bech32Error`
+/// Returns the variant of the checksum if the checksum validation is successful, or an `Err` containing a `Bech32Error`
 /// if the checksum validation fails.
-fn validate_checksum(hrp: &[u8], data: &[u8]) -> Result<(), Bech32Error> {
+fn validate_checksum(hrp: &[u8], data: &[u8]) -> Result<Bech32Variant, Bech32Error> {
     let mut encoded_hrp = expand_hrp(hrp);
 
     encoded_hrp.extend(data);
 
-    if polymod(encoded_hrp) != 1u32 {
-        return Err(Bech32Error::InvalidChecksum);
+    match polymod(encoded_hrp) {
+        BECH32_CONST => Ok(Bech32Variant::Bech32),
+        BECH32M_CONST => Ok(Bech32Variant::Bech32m),
+        _ => Err(Bech32Error::InvalidChecksum),
     }
-
-    Ok(())
 }
 
 /// Creates a checksum for a Bech32 address.
@@ -266,18 +299,19 @@ fn validate_checksum(hrp: &[u8], data: &[u8]) -> Result<(), Bech32Error> {
 /// # Arguments
 ///
 /// * `data` - The data part of the Bech32 address.
+/// * `variant` - The variant of the checksum.
 ///
 /// # Returns
 ///
 /// Returns a vector of bytes representing the calculated checksum.
-fn create_checksum(data: &[u8]) -> Vec<u8> {
+fn create_checksum(data: &[u8], variant: Bech32Variant) -> Vec<u8> {
     let mut values = expand_hrp(&LOWERCASE_HRP.to_string().into_bytes());
     let mut checksum = Vec::new();
 
     values.extend(data);
     values.extend(&[0u8; 6]);
 
-    let polymod = polymod(values) ^ 1;
+    let polymod = polymod(values) ^ variant.constant();
 
     for index in 0..6 {
         checksum.push(((polymod >> (5 * (5 - index))) & 0x1f) as u8);
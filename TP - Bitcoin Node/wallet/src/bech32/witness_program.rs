@@ -1,4 +1,8 @@
-use super::{bech32_constants::*, bech32_errors::WitnessProgramError, bech32mod::Bech32};
+use super::{
+    bech32_constants::*,
+    bech32_errors::WitnessProgramError,
+    bech32mod::{Bech32, Bech32Variant},
+};
 
 /// Represents a Segregated Witness (SegWit) witness program.
 ///
@@ -7,7 +11,7 @@ use super::{bech32_constants::*, bech32_errors::WitnessProgramError, bech32mod::
 #[derive(Debug, Clone)]
 pub struct WitnessProgram {
     version: u8,
-    program: Vec<u8>, //Hash160 of public key
+    program: Vec<u8>, //Hash160 of public key, or taproot output key
 }
 
 impl WitnessProgram {
@@ -34,10 +38,31 @@ impl WitnessProgram {
         Ok(witness_program)
     }
 
+    /// Creates a new version 1 `WitnessProgram` paying to a taproot output key (BIP341).
+    ///
+    /// # Arguments
+    ///
+    /// * `output_key` - The x-only taproot output key, 32 bytes long.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the newly created `WitnessProgram` instance if the key has the right length,
+    /// or an `Err` containing a `WitnessProgramError` otherwise.
+    pub fn new_taproot(output_key: Vec<u8>) -> Result<Self, WitnessProgramError> {
+        let witness_program = WitnessProgram {
+            version: P2TR_VERSION,
+            program: output_key,
+        };
+
+        witness_program.validate()?;
+        Ok(witness_program)
+    }
+
     /// Creates a new `WitnessProgram` instance from a Bech32-encoded address.
     ///
     /// This function decodes a Bech32-encoded address, extracts the version and witness program data,
-    /// and constructs a new `WitnessProgram` instance with the extracted data.
+    /// and constructs a new `WitnessProgram` instance with the extracted data. Version 0 programs must be
+    /// encoded with a Bech32 checksum and later versions with a Bech32m one (BIP350).
     ///
     /// # Arguments
     ///
@@ -50,10 +75,16 @@ impl WitnessProgram {
     pub fn from_address(address: String) -> Result<WitnessProgram, WitnessProgramError> {
         let b32 = Bech32::from_address(address)?;
 
-        let (version, program) = b32.data().split_at(1);
+        let (version, program) = match b32.data().split_first() {
+            Some((version, program)) => (*version, program),
+            None => return Err(WitnessProgramError::InvalidAddress),
+        };
+        if b32.variant() != variant_of(version) {
+            return Err(WitnessProgramError::InvalidVariant);
+        }
 
         let witness_program = WitnessProgram {
-            version: version.to_vec()[0],
+            version,
             program: convert_bits(program.to_vec(), 8)?,
         };
 
@@ -78,15 +109,28 @@ impl WitnessProgram {
         let mut data = vec![self.version];
         data.extend(program);
 
-        let address = Bech32::new(LOWERCASE_HRP.to_string(), data).to_address()?;
+        let address =
+            Bech32::with_variant(LOWERCASE_HRP.to_string(), data, variant_of(self.version))
+                .to_address()?;
 
         WitnessProgram::from_address(address.clone())?;
 
         Ok(address)
     }
 
+    /// Returns whether the witness program pays to a taproot output key.
+    pub fn is_taproot(&self) -> bool {
+        self.version == P2TR_VERSION
+    }
+
+    /// Converts the witness program into the script paying to it: its version opcode, `OP_0` or `OP_1` to `OP_16`,
+    /// followed by the push of the program.
     pub fn to_pk_script(&self) -> Vec<u8> {
-        let mut pk_script = vec![self.version];
+        let version_opcode = match self.version {
+            0 => 0x00,
+            version => 0x50 + version,
+        };
+        let mut pk_script = vec![version_opcode];
         pk_script.push(self.program.len() as u8);
         pk_script.extend(&self.program);
 
@@ -102,9 +146,13 @@ impl WitnessProgram {
     ///
     /// Returns a vector containing the P2WPKH script bytes.
     pub fn validate(&self) -> Result<(), WitnessProgramError> {
-        if self.version != P2WPKH_VERSION {
-            return Err(WitnessProgramError::InvalidVersion);
-        } else if self.program.len() != P2WPKH_HASH_LEN {
+        let expected_len = match self.version {
+            P2WPKH_VERSION => P2WPKH_HASH_LEN,
+            P2TR_VERSION => P2TR_PROGRAM_LEN,
+            _ => return Err(WitnessProgramError::InvalidVersion),
+        };
+
+        if self.program.len() != expected_len {
             return Err(WitnessProgramError::InvalidLength);
         }
 
@@ -112,6 +160,14 @@ impl WitnessProgram {
     }
 }
 
+/// Returns the checksum variant the addresses of witness programs of the given version are encoded with.
+fn variant_of(version: u8) -> Bech32Variant {
+    match version {
+        P2WPKH_VERSION => Bech32Variant::Bech32,
+        _ => Bech32Variant::Bech32m,
+    }
+}
+
 fn get_from(to: u32) -> Result<u32, WitnessProgramError> {
     match to {
         5 => Ok(8),
@@ -172,7 +228,7 @@ fn convert_bits(data: Vec<u8>, to: u32) -> Result<Vec<u8>, WitnessProgramError>
 #[cfg(test)]
 mod bech32_test {
     use super::WitnessProgram;
-    use crate::bech32::bech32_errors::WitnessProgramError;
+    use crate::bech32::{bech32_errors::WitnessProgramError, bech32mod::Bech32};
 
     #[test]
     pub fn test_valid_address() {
@@ -249,4 +305,24 @@ mod bech32_test {
 
         Ok(())
     }
+
+    #[test]
+    pub fn test_taproot_address() -> Result<(), WitnessProgramError> {
+        let address = "tb1pqqqqp399et2xygdj5xreqhjjvcmzhxw4aywxecjdzew6hylgvsesf3hn0c";
+        let wp = WitnessProgram::from_address(address.to_string())?;
+
+        assert!(wp.is_taproot());
+        assert_eq!(wp.to_address()?, address);
+        assert_eq!(wp.to_pk_script()[..2], [0x51, 32]);
+
+        // A taproot program encoded with a Bech32 checksum is rejected
+        let data = Bech32::from_address(address.to_string())?.data().to_vec();
+        let bech32_address = Bech32::new("tb".to_string(), data).to_address()?;
+        assert!(matches!(
+            WitnessProgram::from_address(bech32_address),
+            Err(WitnessProgramError::InvalidVariant)
+        ));
+
+        Ok(())
+    }
 }
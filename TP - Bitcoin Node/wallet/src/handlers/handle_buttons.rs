@@ -2,26 +2,43 @@ use super::handle_styles::set_button_style;
 use super::handler_constants::*;
 use crate::accounts::Accounts;
 use crate::interface_error::InterfaceError;
+use crate::node_connection::NodeConnection;
 use crate::proof_of_inclusion::get_proof_of_inclusion::*;
 use crate::transactions::create_transaction_error::TransactionCreateError;
 use crate::transactions::create_transactions::{
-    address_from_pubkey, create_transaction, is_string_bech32,
+    address_from_pubkey, create_transaction, is_string_bech32, pk_script_from_pubkey,
 };
+use crate::transactions::create_transactios_constants::{
+    BTC_TO_SATOSHI, SECONDS_PER_DAY, TX_DRAFTS_FILE_PATH,
+};
+use crate::transactions::payment_warnings::check_payment_warnings;
+use crate::transactions::tx_drafts::{TxDraft, TxDrafts};
+use crate::update_wallet::{request_node_info, request_peer_info, request_transaction};
+use crate::views::node_info_view::create_node_info_view;
+use crate::views::peers_view::create_peers_view;
 use crate::views::transaction_tree_view::create_transaction_tree_view;
 use crate::views::transaction_view::create_transaction_view;
+use crate::wallet_import::dump_import::import_wallet_dump;
 use gtk::Window;
 use gtk::{prelude::*, Clipboard, CssProvider, Entry, Fixed, Label, ListStore, SpinButton, Widget};
 use gtk::{Box, Builder, Button};
-use gtk::{Dialog, Image};
+use gtk::{Dialog, FileChooserAction, FileChooserDialog, Image, ResponseType};
 use node::block_mod::tx_out::TxOut;
-use node::messages::read_from_bytes::{decode_hex, encode_hex, read_string_from_bytes};
+use node::messages::command::Command;
+use node::messages::read_from_bytes::{decode_hex, encode_hex};
 use node::wallet_utils::broadcast_txn::BroadcastTxn;
 use node::wallet_utils::get_proof::GetProof;
-use node::wallet_utils::merkle_block::MerkleBlock;
-use std::io::Write;
-use std::net::TcpStream;
+use node::wallet_utils::wallet_frame::{read_frame, write_frame};
+use node::wallet_utils::wallet_message::{WalletRequest, WalletResponse};
+use std::cell::RefCell;
+use std::fs;
+use std::rc::Rc;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The recipients of a payment, each with the address it pays to and the amount, in satoshis.
+type TargetList = Vec<(Vec<u8>, i64)>;
 
 /// Sets various buttons on the interface.
 ///
@@ -38,20 +55,23 @@ use std::sync::Mutex;
 pub fn set_buttons(
     builder: &Builder,
     accounts: Arc<Mutex<Accounts>>,
-    node: Arc<Mutex<TcpStream>>,
+    node: Arc<Mutex<NodeConnection>>,
     store: &ListStore,
 ) -> Result<(), InterfaceError> {
     set_login_button(builder, accounts.clone())?;
     set_overview_button(builder)?;
     set_send_button(builder)?;
     set_receive_button(builder)?;
-    set_transactions_button(builder, store)?;
+    set_transactions_button(builder, store, accounts.clone())?;
     set_add_recipient_button(builder)?;
     set_clear_all_button(builder)?;
     set_copy_button(builder)?;
     set_poi_button(builder)?;
-    set_send_transaction_button(builder, node.clone(), accounts)?;
-    set_make_proof_button(builder, node)?;
+    set_send_transaction_button(builder, node.clone(), accounts.clone())?;
+    set_draft_buttons(builder, accounts.clone())?;
+    set_make_proof_button(builder, node.clone())?;
+    set_node_info_button(builder, node)?;
+    set_import_wallet_button(builder, accounts)?;
     set_new_account_button(builder)?;
     set_return_button(builder)?;
     set_ok_button(builder)?;
@@ -194,15 +214,20 @@ fn set_receive_button(builder: &Builder) -> Result<(), InterfaceError> {
 ///
 /// * `builder` - A reference to the builder object.
 /// * `store` - A reference to the list store.
+/// * `accounts` - A shared mutable reference to the accounts.
 ///
 /// # Errors
 ///
 /// Returns an `InterfaceError` if the button or box objects are missing.
-fn set_transactions_button(builder: &Builder, store: &ListStore) -> Result<(), InterfaceError> {
+fn set_transactions_button(
+    builder: &Builder,
+    store: &ListStore,
+    accounts: Arc<Mutex<Accounts>>,
+) -> Result<(), InterfaceError> {
     let transactions_button: Button = builder
         .get_object(TRANSACTIONS_BUTTON)
         .ok_or(InterfaceError::MissingButton)?;
-    let transactions_tree_view = create_transaction_tree_view(store).upcast();
+    let transactions_tree_view = create_transaction_tree_view(store, accounts).upcast();
     let content_box: Box = builder
         .get_object(CONTENT_BOX)
         .ok_or(InterfaceError::MissingBox)?;
@@ -214,6 +239,150 @@ fn set_transactions_button(builder: &Builder, store: &ListStore) -> Result<(), I
     Ok(())
 }
 
+/// Sets the functionality of the node info button.
+///
+/// When clicked, the node is queried for its version, features, uptime, network and data
+/// directory, along with the statistics of its peers, which are displayed in the content box.
+///
+/// # Arguments
+///
+/// * `builder` - A reference to the builder object.
+/// * `node` - A shared mutable reference to the TCP stream node.
+///
+/// # Errors
+///
+/// Returns an `InterfaceError` if the button or box objects are missing.
+fn set_node_info_button(
+    builder: &Builder,
+    node: Arc<Mutex<NodeConnection>>,
+) -> Result<(), InterfaceError> {
+    let node_info_button: Button = builder
+        .get_object(NODE_INFO_BUTTON)
+        .ok_or(InterfaceError::MissingButton)?;
+    let content_box: Box = builder
+        .get_object(CONTENT_BOX)
+        .ok_or(InterfaceError::MissingBox)?;
+
+    node_info_button.connect_clicked(move |_| match request_node_info(&node) {
+        Ok(node_info) => {
+            let node_info_view = create_node_info_view(&node_info);
+            match request_peer_info(&node) {
+                Ok(peer_info) => {
+                    node_info_view.pack_start(&create_peers_view(&peer_info), false, false, 0)
+                }
+                Err(err) => println!("{:?}", err),
+            }
+            replace_content(&content_box, node_info_view.upcast_ref());
+            node_info_view.show_all();
+        }
+        Err(err) => println!("{:?}", err),
+    });
+    Ok(())
+}
+
+/// Sets up the import button, which imports the keys of a Bitcoin Core `dumpwallet` file or
+/// descriptor list as new accounts.
+///
+/// # Arguments
+///
+/// * `builder` - A reference to the GTK `Builder` object.
+/// * `accounts` - The shared accounts of the wallet.
+///
+/// # Description
+///
+/// Upon clicking the import button, a file chooser is shown. The keys of the chosen file are added
+/// as accounts, which are listed along the other accounts and rescanned on the next updates. A
+/// summary with the imported accounts and the lines that failed is shown once the import finishes.
+///
+/// # Errors
+///
+/// Returns an `InterfaceError` if the button, window, box or label objects are missing.
+fn set_import_wallet_button(
+    builder: &Builder,
+    accounts: Arc<Mutex<Accounts>>,
+) -> Result<(), InterfaceError> {
+    let import_wallet_button: Button = builder
+        .get_object(IMPORT_WALLET_BUTTON)
+        .ok_or(InterfaceError::MissingButton)?;
+    let main_window: Window = builder
+        .get_object(MAIN_WINDOW)
+        .ok_or(InterfaceError::MissingWindow)?;
+    let accounts_box: Box = builder
+        .get_object(ACCOUNTS_BOX)
+        .ok_or(InterfaceError::MissingBox)?;
+    let actual_account_label: Label = builder
+        .get_object("actual_account_label")
+        .ok_or(InterfaceError::MissingLabel)?;
+    let builder_clone = builder.clone();
+
+    import_wallet_button.connect_clicked(move |_| {
+        let file_chooser = FileChooserDialog::with_buttons(
+            Some("Import Wallet Dump"),
+            Some(&main_window),
+            FileChooserAction::Open,
+            &[
+                ("_Cancel", ResponseType::Cancel),
+                ("_Open", ResponseType::Accept),
+            ],
+        );
+
+        let path = match file_chooser.run() {
+            ResponseType::Accept => file_chooser.get_filename(),
+            _ => None,
+        };
+        file_chooser.close();
+
+        let content = match path.map(fs::read_to_string) {
+            Some(Ok(content)) => content,
+            Some(Err(_)) => {
+                show_error(
+                    &builder_clone,
+                    "Import Failed",
+                    "The chosen file could not be read.",
+                );
+                return;
+            }
+            None => return,
+        };
+
+        let mut locked_accounts = match accounts.lock() {
+            Ok(locked_accounts) => locked_accounts,
+            Err(_) => return,
+        };
+        let report = import_wallet_dump(&content, &mut locked_accounts);
+        actual_account_label.set_text(locked_accounts.get_current_username());
+        drop(locked_accounts);
+
+        for username in report.get_imported() {
+            let account_button = Button::with_label(username);
+            let username_account = username.clone();
+            let shared_accounts = accounts.clone();
+            let shared_actual_account_label = actual_account_label.clone();
+
+            account_button.connect_clicked(move |_| {
+                if let Ok(mut locked_accounts) = shared_accounts.lock() {
+                    locked_accounts.set_actual_account(username_account.clone());
+                    shared_actual_account_label.set_text(&username_account);
+                }
+            });
+
+            accounts_box.add(&account_button);
+            account_button.show();
+        }
+
+        let mut summary = format!(
+            "{} accounts were imported and will be rescanned.",
+            report.get_imported().len()
+        );
+        for (line, err) in report.get_errors() {
+            summary += &format!("\n \n - Line {}: {}", line, err);
+        }
+
+        show_error(&builder_clone, "Import Finished", &summary);
+    });
+    Ok(())
+}
+
 /// Clears the text in the provided GTK entry widget.
 ///
 /// # Arguments
@@ -696,13 +865,14 @@ fn valid_transaction_id(transaction_id: &str) -> bool {
 /// 1. Retrieves the "Make Proof" button, block header entry, and transaction ID entry from the builder.
 /// 2. Retrieves the necessary dialog elements for error display.
 /// 3. Connects the "Make Proof" button's click event to the corresponding action.
-/// 4. Validates the block header and transaction ID input provided by the user.
-/// 5. Sends a request to the specified node to obtain a proof of inclusion.
-/// 6. Displays the result in a dialog window.
+/// 4. If the block header was left empty, asks the node for the block containing the transaction.
+/// 5. Validates the block header and transaction ID input provided by the user.
+/// 6. Sends a request to the specified node to obtain a proof of inclusion.
+/// 7. Displays the result in a dialog window.
 ///
 fn set_make_proof_button(
     builder: &Builder,
-    node: Arc<Mutex<TcpStream>>,
+    node: Arc<Mutex<NodeConnection>>,
 ) -> Result<(), InterfaceError> {
     let make_proof_button: Button = builder
         .get_object(MAKE_PROOF_BUTTON)
@@ -741,9 +911,47 @@ fn set_make_proof_button(
         .ok_or(InterfaceError::MissingImage)?;
 
     make_proof_button.connect_clicked(move |_| {
-        let block_header_text = block_header_entry.get_text();
         let transaction_id_text = transaction_id_entry.get_text();
 
+        // When only the transaction is given, the node is asked for the block containing it
+        if block_header_entry.get_text().is_empty()
+            && valid_transaction_id(transaction_id_text.as_str())
+        {
+            let tx_id = match decode_hex(transaction_id_text.as_str()) {
+                Ok(tx_id) => tx_id,
+                Err(_) => return,
+            };
+
+            match request_transaction(&node, tx_id) {
+                Ok(Some(transaction_info)) if transaction_info.is_confirmed() => {
+                    let mut block_hash = transaction_info.get_block_hash().clone();
+                    block_hash.reverse();
+
+                    if let Ok(block_hash) = encode_hex(&block_hash) {
+                        block_header_entry.set_text(&block_hash);
+                    }
+                }
+                Ok(Some(_)) => {
+                    poi_title_label.set_text("Unconfirmed Transaction");
+                    poi_advice_label
+                        .set_text("The transaction is still waiting to be included in a block.");
+                    poi_success_image.hide();
+                    poi_dialog.show();
+                    return;
+                }
+                Ok(None) => {
+                    poi_title_label.set_text("Invalid Proof of Inclusion");
+                    poi_advice_label.set_text("The transaction was not found by the node");
+                    poi_success_image.hide();
+                    poi_dialog.show();
+                    return;
+                }
+                Err(_) => return,
+            }
+        }
+
+        let block_header_text = block_header_entry.get_text();
+
         if !valid_block_header(block_header_text.as_str())
             || !valid_transaction_id(transaction_id_text.as_str())
         {
@@ -775,35 +983,41 @@ fn set_make_proof_button(
             Err(_) => return,
         };
         let get_proof = GetProof::new(block_header, tx_id);
+        println!("Se envia el get_proof: {:?}", get_proof);
+
+        let request = match WalletRequest::GetProof(get_proof).to_bytes() {
+            Ok(request) => request,
+            Err(_) => return,
+        };
 
         let mut locked_node = match node.lock() {
             Ok(locked_node) => locked_node,
             Err(_) => return,
         };
 
-        if locked_node.write_all(&get_proof.to_bytes()).is_err() {
+        if write_frame(&mut *locked_node, &request).is_err() {
             return;
         };
 
-        println!("Se envia el get_proof: {:?}", get_proof);
-
-        let command_name = match read_string_from_bytes(&mut *locked_node, 12) {
-            Ok(command_name) => command_name,
+        let (command_name, payload) = match read_frame(&mut *locked_node) {
+            Ok(frame) => frame,
             Err(_) => return,
         };
 
         println!("Command name despues de get proof: {}", command_name);
-        if command_name != MERKLE_BLOCK {
-            //implementar mensaje
-            poi_title_label.set_text("Invalid Proof of Inclusion");
-            poi_advice_label.set_text("The transaction was not found in block");
-            poi_success_image.hide();
-            poi_dialog.show();
-            drop(locked_node);
+        if command_name != Command::Response {
             return;
         }
-        let merkle_block = match MerkleBlock::from_bytes(command_name, &mut *locked_node) {
-            Ok(merkle_block) => merkle_block,
+        let merkle_block = match WalletResponse::from_bytes(&mut payload.as_slice()) {
+            Ok(WalletResponse::MerkleBlock(merkle_block)) => merkle_block,
+            Ok(_) => {
+                poi_title_label.set_text("Invalid Proof of Inclusion");
+                poi_advice_label.set_text("The transaction was not found in block");
+                poi_success_image.hide();
+                poi_dialog.show();
+                drop(locked_node);
+                return;
+            }
             Err(_) => return,
         };
 
@@ -864,7 +1078,7 @@ fn set_make_proof_button(
 /// 5. Stores the target value and amount as a tuple in the vector.
 /// 6. Returns the vector of target values and amounts.
 ///
-fn get_target_list(transaction_box: &Box) -> Result<Vec<(Vec<u8>, i64)>, InterfaceError> {
+fn get_target_list(transaction_box: &Box) -> Result<TargetList, InterfaceError> {
     let mut target_list = vec![];
 
     for tx in transaction_box.get_children() {
@@ -892,7 +1106,14 @@ fn get_target_list(transaction_box: &Box) -> Result<Vec<(Vec<u8>, i64)>, Interfa
     Ok(target_list)
 }
 
-fn show_error(builder: &Builder, title: &str, text: &str) {
+/// Shows a message to the user in the dialog window of the interface.
+///
+/// # Arguments
+///
+/// * `builder` - A reference to the builder object.
+/// * `title` - The title of the message.
+/// * `text` - The text of the message.
+pub fn show_error(builder: &Builder, title: &str, text: &str) {
     let user_authentication_dialog: Dialog = match builder
         .get_object("user_authentication_window")
         .ok_or(InterfaceError::MissingDialog)
@@ -933,7 +1154,7 @@ fn show_error(builder: &Builder, title: &str, text: &str) {
 /// # Arguments
 ///
 /// * `builder` - The Builder object for accessing UI elements.
-/// * `node` - An Arc-wrapped Mutex-wrapped TcpStream for communication with the node.
+/// * `node` - An Arc-wrapped Mutex-wrapped NodeConnection for communication with the node.
 /// * `accounts` - An Arc-wrapped Mutex for accessing account information.
 ///
 /// # Returns
@@ -949,15 +1170,17 @@ fn show_error(builder: &Builder, title: &str, text: &str) {
 /// 2. Connects the "Send Transaction" button to a click event handler using the `connect_clicked` method.
 /// 3. In the click event handler, retrieves the target list, fee value, and private key from the UI elements.
 /// 4. Acquires a lock on the accounts Mutex to access the account information.
-/// 5. If the account information is available, attempts to create a transaction using the `create_transaction` function.
-/// 6. If the transaction creation is successful, creates a new transaction view and updates the transaction box.
-/// 7. Handles any errors that may occur during the transaction creation process, such as insufficient funds.
-/// 8. Drops the lock on the accounts Mutex.
-/// 9. Returns `Ok(())` if the function executes successfully.
+/// 5. Warns the user if a recipient was already paid the same amount recently or is one of the
+///    wallet's own addresses, and only proceeds if the same payment is sent again.
+/// 6. If the account information is available, attempts to create a transaction using the `create_transaction` function.
+/// 7. If the transaction creation is successful, creates a new transaction view and updates the transaction box.
+/// 8. Handles any errors that may occur during the transaction creation process, such as insufficient funds.
+/// 9. Drops the lock on the accounts Mutex.
+/// 10. Returns `Ok(())` if the function executes successfully.
 ///
 fn set_send_transaction_button(
     builder: &Builder,
-    node: Arc<Mutex<TcpStream>>,
+    node: Arc<Mutex<NodeConnection>>,
     accounts: Arc<Mutex<Accounts>>,
 ) -> Result<(), InterfaceError> {
     let builder_clone = builder.clone();
@@ -970,6 +1193,7 @@ fn set_send_transaction_button(
     let spin_button_fee: SpinButton = builder
         .get_object(FEE_SPIN_BUTTON)
         .ok_or(InterfaceError::MissingSpinButton)?;
+    let warned_targets: Rc<RefCell<TargetList>> = Rc::new(RefCell::new(vec![]));
 
     send_transaction_button.connect_clicked( move |_| {
         let target_list = match get_target_list(&transaction_box){
@@ -987,6 +1211,20 @@ fn set_send_transaction_button(
 
         if let Ok(locked_accounts) = accounts.lock(){
             if let Some(user_info) = locked_accounts.get_current_account_info(){
+                let today = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|duration| (duration.as_secs() / SECONDS_PER_DAY) as i64)
+                    .unwrap_or_default();
+                let warnings = check_payment_warnings(&target_list, user_info, &locked_accounts.get_own_addresses(), today, locked_accounts.get_duplicate_payment_window());
+
+                if !warnings.is_empty() && *warned_targets.borrow() != target_list {
+                    let text: Vec<String> = warnings.iter().map(|warning| warning.to_string()).collect();
+                    show_error(&builder_clone, "Payment Warning", &format!("{} Press send again to confirm.", text.join(" ")));
+                    *warned_targets.borrow_mut() = target_list;
+                    return;
+                }
+                warned_targets.borrow_mut().clear();
+
                 let private_key = user_info.get_private_key();
                 let fee = spin_button_fee.get_value() * 100000000.0;
                 let used_txouts: Vec<TxOut> = user_info.get_used_txouts()
@@ -995,17 +1233,30 @@ fn set_send_transaction_button(
                 .map(|(txout, _)| txout.clone())
                 .collect();
 
-                match create_transaction(target_list, user_info.get_utxo(), private_key, fee as i64, &used_txouts, user_info.get_bech32()){
+                match create_transaction(target_list, user_info.get_utxo(), private_key, fee as i64, &used_txouts, user_info.get_bech32(), locked_accounts.get_tx_defaults()){
                     Ok(transaction) => {
+                        let size_breakdown = transaction.size_breakdown();
+                        if !size_breakdown.is_standard() {
+                            show_error(&builder_clone, "Transaction Too Large", &format!("The transaction exceeds the standard weight accepted by the network ({}).", size_breakdown));
+                            return;
+                        }
+
                         if let Ok(mut locked_node) = node.lock() {
                             println!("Connection established to broadcast transaction:\n{:?}", transaction);
-                            let broadcast_txn = BroadcastTxn::new(transaction.clone());
+                            println!("Transaction size: {}", size_breakdown);
+                            let broadcast_txn = match WalletRequest::BroadcastTxn(BroadcastTxn::new(transaction.clone())).to_bytes() {
+                                Ok(broadcast_txn) => broadcast_txn,
+                                Err(_) => {
+                                    println!("Error when broadcasting new transaction to node.");
+                                    return;
+                                }
+                            };
 
                             if let Ok(tx) = encode_hex(&transaction.to_bytes(user_info.get_bech32())){
                                 println!("\nTransaction:\n{:?}\n\n", tx);
                             }
 
-                            if locked_node.write_all(&broadcast_txn.to_bytes(user_info.get_bech32())).is_err(){
+                            if write_frame(&mut *locked_node, &broadcast_txn).is_err(){
                                 println!("Error when broadcasting new transaction to node.");
                                 return;
                             }
@@ -1031,6 +1282,182 @@ fn set_send_transaction_button(
     Ok(())
 }
 
+/// Replaces the outputs of the transaction box with the recipients and amounts of a draft.
+///
+/// # Arguments
+///
+/// * `transaction_box` - The box holding one view per output of the transaction.
+/// * `draft` - The draft whose outputs are restored.
+fn fill_transaction_box(transaction_box: &Box, draft: &TxDraft) {
+    transaction_box.foreach(|child| {
+        transaction_box.remove(child);
+    });
+
+    for (address, amount) in draft.get_targets() {
+        let new_transaction = match create_transaction_view(transaction_box.clone()) {
+            Ok(new_transaction) => new_transaction,
+            Err(_) => return,
+        };
+
+        for fixed_child in new_transaction.get_children() {
+            if let Some(spin_button) = fixed_child.downcast_ref::<gtk::SpinButton>() {
+                spin_button.set_value(*amount as f64 / BTC_TO_SATOSHI);
+            } else if let Some(entry) = fixed_child.downcast_ref::<gtk::Entry>() {
+                entry.set_text(&String::from_utf8_lossy(address));
+            }
+        }
+
+        transaction_box.add(&new_transaction);
+        new_transaction.show_all();
+    }
+}
+
+/// Sets up the functionality for the "Save draft", "Load draft" and "Export draft" buttons.
+///
+/// Saving a draft stores the recipients, amounts and fee of the transaction box under the name of
+/// the draft entry, along with the outputs of the current account selected to fund it, so an
+/// in-progress send survives the app closing. Loading a draft restores its recipients, amounts
+/// and fee, and exporting it shows the unsigned transaction spending the selected outputs, so it
+/// can be reviewed before it is sent.
+///
+/// # Arguments
+///
+/// * `builder` - The Builder object for accessing UI elements.
+/// * `accounts` - An Arc-wrapped Mutex for accessing account information.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the function executes successfully, or an `InterfaceError` if any UI
+/// elements are missing or the drafts file cannot be loaded.
+fn set_draft_buttons(
+    builder: &Builder,
+    accounts: Arc<Mutex<Accounts>>,
+) -> Result<(), InterfaceError> {
+    let save_draft_button: Button = builder
+        .get_object(SAVE_DRAFT_BUTTON)
+        .ok_or(InterfaceError::MissingButton)?;
+    let load_draft_button: Button = builder
+        .get_object(LOAD_DRAFT_BUTTON)
+        .ok_or(InterfaceError::MissingButton)?;
+    let export_draft_button: Button = builder
+        .get_object(EXPORT_DRAFT_BUTTON)
+        .ok_or(InterfaceError::MissingButton)?;
+    let draft_name_entry: Entry = builder
+        .get_object(DRAFT_NAME_ENTRY)
+        .ok_or(InterfaceError::MissingEntry)?;
+    let transaction_box: Box = builder
+        .get_object(TX_BOX)
+        .ok_or(InterfaceError::MissingBox)?;
+    let spin_button_fee: SpinButton = builder
+        .get_object(FEE_SPIN_BUTTON)
+        .ok_or(InterfaceError::MissingSpinButton)?;
+    let drafts = TxDrafts::new(TX_DRAFTS_FILE_PATH).map_err(|_| InterfaceError::TxCreate)?;
+    let drafts: Rc<RefCell<TxDrafts>> = Rc::new(RefCell::new(drafts));
+
+    let builder_clone = builder.clone();
+    let shared_accounts = accounts.clone();
+    let (name_entry, tx_box, fee_button, shared_drafts) = (
+        draft_name_entry.clone(),
+        transaction_box.clone(),
+        spin_button_fee.clone(),
+        drafts.clone(),
+    );
+    save_draft_button.connect_clicked(move |_| {
+        let target_list = match get_target_list(&tx_box) {
+            Ok(target_list) => target_list,
+            Err(_) => {
+                show_error(&builder_clone, "Incomplete Draft", "Please, fill in the address and amount of every recipient.");
+                return;
+            }
+        };
+        let fee = (fee_button.get_value() * BTC_TO_SATOSHI) as i64;
+
+        if let Ok(locked_accounts) = shared_accounts.lock() {
+            if let Some(user_info) = locked_accounts.get_current_account_info() {
+                let used_txouts: Vec<TxOut> = user_info.get_used_txouts()
+                .iter()
+                .filter(|(_, amount)| *amount < 0)
+                .map(|(txout, _)| txout.clone())
+                .collect();
+
+                let result = TxDraft::select(target_list, fee, user_info.get_utxo(), &used_txouts, locked_accounts.get_tx_defaults())
+                    .and_then(|draft| shared_drafts.borrow_mut().save(&name_entry.get_text(), draft));
+
+                match result {
+                    Ok(()) => println!("Draft {} has been saved.", name_entry.get_text()),
+                    Err(TransactionCreateError::InvalidDraft) => {
+                        show_error(&builder_clone, "Invalid Draft Name", "Please, name the draft without using ';'.");
+                    },
+                    Err(TransactionCreateError::InsufficientFunds) | Err(TransactionCreateError::UnavailableOutput) => {
+                        show_error(&builder_clone, "Insufficient Funds", "There are not enough available funds in the account to fund this draft.");
+                    },
+                    Err(_) => {
+                        show_error(&builder_clone, "Draft Not Saved", "The draft could not be written to the drafts file.");
+                    }
+                }
+            }
+            drop(locked_accounts);
+        }
+    });
+
+    let builder_clone = builder.clone();
+    let (name_entry, shared_drafts) = (draft_name_entry.clone(), drafts.clone());
+    load_draft_button.connect_clicked(move |_| {
+        match shared_drafts.borrow().get(&name_entry.get_text()) {
+            Some(draft) => {
+                fill_transaction_box(&transaction_box, draft);
+                spin_button_fee.set_value(draft.get_fee() as f64 / BTC_TO_SATOSHI);
+            }
+            None => {
+                show_error(
+                    &builder_clone,
+                    "Draft Not Found",
+                    &format!(
+                        "There is no draft with that name. Saved drafts: {}.",
+                        shared_drafts.borrow().get_names().join(", ")
+                    ),
+                );
+            }
+        }
+    });
+
+    let builder_clone = builder.clone();
+    export_draft_button.connect_clicked(move |_| {
+        let draft_name = draft_name_entry.get_text();
+        let shared_drafts = drafts.borrow();
+        let draft = match shared_drafts.get(&draft_name) {
+            Some(draft) => draft,
+            None => {
+                show_error(&builder_clone, "Draft Not Found", "There is no draft with that name.");
+                return;
+            }
+        };
+
+        if let Ok(locked_accounts) = accounts.lock() {
+            if let Some(user_info) = locked_accounts.get_current_account_info() {
+                let change_pk_script = match pk_script_from_pubkey(&user_info.get_public_key(), user_info.get_bech32()) {
+                    Ok(change_pk_script) => change_pk_script,
+                    Err(_) => return,
+                };
+
+                match draft.to_unsigned_transaction(user_info.get_utxo(), &change_pk_script, user_info.get_bech32(), locked_accounts.get_tx_defaults()) {
+                    Ok(transaction) => {
+                        if let Ok(tx) = encode_hex(&transaction.to_bytes(false)) {
+                            show_error(&builder_clone, "Unsigned Transaction", &tx);
+                        }
+                    },
+                    Err(_) => {
+                        show_error(&builder_clone, "Unavailable Outputs", "Some of the outputs selected by the draft have been spent. Please, save the draft again.");
+                    }
+                }
+            }
+            drop(locked_accounts);
+        }
+    });
+
+    Ok(())
+}
+
 /// Sets up the functionality for the "Send Transaction" button.
 ///
 /// This function connects the "Send Transaction" button to a click event handler. When clicked, it
@@ -1041,7 +1468,7 @@ fn set_send_transaction_button(
 /// # Arguments
 ///
 /// * `builder` - The Builder object for accessing UI elements.
-/// * `node` - An Arc-wrapped Mutex-wrapped TcpStream for communication with the node.
+/// * `node` - An Arc-wrapped Mutex-wrapped NodeConnection for communication with the node.
 /// * `accounts` - An Arc-wrapped Mutex for accessing account information.
 ///
 /// # Returns
@@ -40,15 +40,21 @@ pub const CLEAR_ALL_BUTTON: &str = "clear_all_button";
 pub const POI_BUTTON: &str = "poi_button";
 pub const POI_BOX: &str = "poi_box";
 
+pub const NODE_INFO_BUTTON: &str = "node_info_button";
+
+pub const IMPORT_WALLET_BUTTON: &str = "import_wallet_button";
+
 pub const MAKE_PROOF_BUTTON: &str = "make_proof_button";
 pub const BLOCK_HEADER_ENTRY: &str = "block_header_entry";
 pub const TRANSACTION_ID_ENTRY: &str = "transaction_id_entry";
 
-pub const MERKLE_BLOCK: &str = "merkleblock";
-
 pub const SEND_TX_BUTTON: &str = "send_transaction_button";
 pub const TX_BOX: &str = "transaction_box";
 pub const FEE_SPIN_BUTTON: &str = "fee_spin_button";
+pub const DRAFT_NAME_ENTRY: &str = "draft_name_entry";
+pub const SAVE_DRAFT_BUTTON: &str = "save_draft_button";
+pub const LOAD_DRAFT_BUTTON: &str = "load_draft_button";
+pub const EXPORT_DRAFT_BUTTON: &str = "export_draft_button";
 pub const NEW_ACCOUNT_BUTTON: &str = "new_account_button";
 pub const RETURN_BUTTON: &str = "return_button";
 
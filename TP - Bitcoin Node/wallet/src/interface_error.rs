@@ -26,6 +26,9 @@ pub enum InterfaceError {
     MissingAddress,
     MissingAmount,
     WitnessProgramError,
+    InvalidNodeData,
+    IncompatibleNode,
+    Unauthenticated,
 }
 
 impl From<glib::Error> for InterfaceError {
@@ -2,8 +2,13 @@ pub mod accounts;
 pub mod bech32;
 pub mod handlers;
 pub mod interface_error;
+pub mod light_client;
+pub mod node_connection;
 pub mod proof_of_inclusion;
 pub mod transactions;
 pub mod update_wallet;
 pub mod user_info;
 pub mod views;
+pub mod wallet_event;
+pub mod wallet_import;
+pub mod wallet_settings;
@@ -0,0 +1,199 @@
+use crate::{
+    interface_error::InterfaceError, node_connection::NodeConnection, user_info::UserInfo,
+};
+use node::{
+    block_mod::{
+        block::Block, block_filter::BlockFilter, block_header::BlockHeader, tx_out::TxOut,
+    },
+    messages::command::Command,
+    network::network_constants::MAX_FILTERS_PER_MESSAGE,
+    wallet_utils::{
+        block_filters::BlockFilters,
+        get_block::GetBlock,
+        get_filters::GetFilters,
+        raw_block::RawBlock,
+        transactions::Transactions,
+        tx_filter::filter_block_transactions,
+        wallet_frame::{read_frame, write_frame},
+        wallet_tx::WalletTx,
+    },
+};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// Requests to the node the headers and compact filters of the blocks from the given height on.
+fn request_filters(
+    node: &Arc<Mutex<NodeConnection>>,
+    start_height: u32,
+) -> Result<BlockFilters, InterfaceError> {
+    let mut locked_node = node.lock().map_err(|_| InterfaceError::LockNode)?;
+
+    write_frame(&mut *locked_node, &GetFilters::new(start_height).to_bytes())
+        .map_err(|_| InterfaceError::Write)?;
+
+    let (command_name, payload) =
+        read_frame(&mut *locked_node).map_err(|_| InterfaceError::Read)?;
+
+    if command_name != Command::Filters {
+        return Err(InterfaceError::Read);
+    }
+
+    BlockFilters::from_bytes(command_name, &mut payload.as_slice())
+        .map_err(|_| InterfaceError::Read)
+}
+
+/// Requests a block to the node, checking that it matches the given header.
+///
+/// # Errors
+///
+/// Returns an `InterfaceError` if the node cannot be reached, does not have the block, or sent a
+/// block whose hash or merkle root does not match the header.
+fn request_block(
+    node: &Arc<Mutex<NodeConnection>>,
+    header: &BlockHeader,
+) -> Result<Block, InterfaceError> {
+    let mut locked_node = node.lock().map_err(|_| InterfaceError::LockNode)?;
+
+    write_frame(
+        &mut *locked_node,
+        &GetBlock::new(header.get_header()).to_bytes(),
+    )
+    .map_err(|_| InterfaceError::Write)?;
+
+    let (command_name, payload) =
+        read_frame(&mut *locked_node).map_err(|_| InterfaceError::Read)?;
+
+    let raw_block = match command_name {
+        Command::RawBlock => RawBlock::from_bytes(command_name, &mut payload.as_slice())
+            .map_err(|_| InterfaceError::Read)?,
+        Command::NotFoundWallet => return Err(InterfaceError::InvalidNodeData),
+        _ => return Err(InterfaceError::Read),
+    };
+    drop(locked_node);
+
+    let block = Block::from_bytes(&mut raw_block.get_raw_block().as_slice())
+        .map_err(|_| InterfaceError::InvalidNodeData)?;
+
+    if block.get_header() != header || !block.proof_of_inclusion() {
+        return Err(InterfaceError::InvalidNodeData);
+    }
+    Ok(block)
+}
+
+/// Returns the outputs of the given transactions paying to the public key script.
+fn owned_outputs(txs: &[WalletTx], pk_script: &[u8]) -> Vec<(Vec<u8>, u32, TxOut)> {
+    let mut utxo = vec![];
+
+    for wallet_tx in txs {
+        let tx = wallet_tx.get_tx();
+
+        for (index, tx_out) in tx.get_tx_out_list().iter().enumerate() {
+            if tx_out.get_pk_script() == pk_script {
+                utxo.push((tx.get_id(false), index as u32, tx_out.clone()));
+            }
+        }
+    }
+    utxo
+}
+
+/// Keeps the wallet up to date using only block headers and compact filters (BIP158), instead of
+/// letting the node scan the blocks for the scripts of the wallet.
+///
+/// The headers are checked to carry a valid proof of work and to extend the ones already
+/// processed, and only the blocks whose filters match the script of an account are downloaded and
+/// checked against their headers, so the node learns as little as possible about the wallet. As
+/// the mempool is not scanned, unconfirmed transactions are not reported in this mode.
+#[derive(Debug, Default)]
+pub struct LightClient {
+    tips: HashMap<String, (u32, Vec<u8>)>,
+}
+
+impl LightClient {
+    /// Creates a light client that has not processed any header yet.
+    pub fn new() -> LightClient {
+        LightClient {
+            tips: HashMap::new(),
+        }
+    }
+
+    /// Processes the headers and filters of the blocks found since the last update of an account,
+    /// gathering the transactions of the blocks matching its script.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The shared `NodeConnection` representing the connection to the node.
+    /// * `user_name` - The name of the account.
+    /// * `user_info` - The account whose transactions are requested.
+    /// * `pk_script` - The public key script of the account.
+    ///
+    /// # Returns
+    ///
+    /// The transactions of the account found in the new blocks, or an `InterfaceError` if the node
+    /// cannot be reached or sent headers, filters or blocks that are not valid.
+    pub fn sync_account(
+        &mut self,
+        node: &Arc<Mutex<NodeConnection>>,
+        user_name: &str,
+        user_info: &UserInfo,
+        pk_script: &[u8],
+    ) -> Result<Transactions, InterfaceError> {
+        let (mut height, mut tip) = self.tips.get(user_name).cloned().unwrap_or_default();
+        let scripts = vec![pk_script.to_vec()];
+        let public_key = user_info.get_public_key();
+        let mut last_update = user_info.get_last_update();
+        let mut txs_send = vec![];
+        let mut txs_recv = vec![];
+
+        loop {
+            let block_filters = request_filters(node, height)?;
+            height = height.max(block_filters.get_start_height());
+
+            for (header, filter) in block_filters.get_filters() {
+                if !header.proof_of_work()
+                    || (!tip.is_empty() && *header.get_previuos_block_header() != tip)
+                {
+                    return Err(InterfaceError::InvalidNodeData);
+                }
+
+                let block_hash = header.get_header();
+
+                if !filter.is_empty() {
+                    let filter = BlockFilter::from_bytes(filter.clone())
+                        .map_err(|_| InterfaceError::InvalidNodeData)?;
+
+                    if filter.match_any(&block_hash, &scripts) {
+                        let block = request_block(node, header)?;
+                        let (send, recv) =
+                            filter_block_transactions(&block, &scripts[0], &public_key)
+                                .map_err(|_| InterfaceError::InvalidNodeData)?;
+                        txs_send.extend(send);
+                        txs_recv.extend(recv);
+                    }
+                }
+
+                tip = block_hash;
+                height += 1;
+                last_update = header.get_time();
+            }
+
+            if block_filters.get_filters().len() < MAX_FILTERS_PER_MESSAGE {
+                break;
+            }
+        }
+        self.tips.insert(user_name.to_string(), (height, tip));
+
+        let utxo = owned_outputs(&[txs_send.clone(), txs_recv.clone()].concat(), pk_script);
+
+        Ok(Transactions::new(
+            txs_send,
+            txs_recv,
+            vec![],
+            vec![],
+            utxo,
+            vec![],
+            last_update,
+        ))
+    }
+}
@@ -3,6 +3,8 @@ use glib::Priority;
 use glib::Type;
 use gtk::Builder;
 use gtk::ListStore;
+use node::settings_mod::settings_error::SettingError;
+use std::env;
 use std::net::IpAddr;
 use std::net::Ipv4Addr;
 use std::net::SocketAddr;
@@ -13,22 +15,96 @@ use std::thread;
 use wallet::accounts::Accounts;
 use wallet::handlers::handle_buttons::set_buttons;
 use wallet::handlers::handle_windows::set_windows;
+use wallet::node_connection::NodeConnection;
+use wallet::transactions::transaction_defaults::TransactionDefaults;
 use wallet::transactions::transaction_view::update_transaction_list;
-use wallet::update_wallet::update_wallet;
+use wallet::update_wallet::{authenticate, negotiate_version, update_wallet};
+use wallet::wallet_event::WalletEvent;
+use wallet::wallet_settings::WalletSettings;
+
+/// The environment variable holding the token the wallet authenticates with. It is not taken as an
+/// argument so it does not show up in the list of processes.
+const AUTH_TOKEN_VAR: &str = "WALLET_AUTH_TOKEN";
 
 fn main() {
     let socket: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8000);
-    let node: Arc<Mutex<TcpStream>> = match TcpStream::connect(socket) {
-        Ok(conexion) => Arc::new(Mutex::new(conexion)),
+    let node: Arc<Mutex<NodeConnection>> = match TcpStream::connect(socket) {
+        Ok(conexion) => Arc::new(Mutex::new(NodeConnection::new(conexion))),
         Err(_) => {
             println!("Failed to connect to node.");
             return;
         }
     };
 
-    let accounts: Arc<Mutex<Accounts>> = Arc::new(Mutex::new(Accounts::new()));
+    if negotiate_version(&node).is_err() {
+        println!("Node speaks an incompatible version of the wallet protocol.");
+        return;
+    }
+
+    let auth_token = env::var(AUTH_TOKEN_VAR).unwrap_or_default();
+    if let Err(err) = authenticate(&node, &auth_token) {
+        println!("Failed to authenticate with node: {:?}.", err);
+        return;
+    }
+
+    let settings = match env::args()
+        .nth(1)
+        .map(|path| WalletSettings::from_file(&path))
+    {
+        Some(Ok(settings)) => settings,
+        Some(Err(err)) => {
+            println!("Error when reading settings: {:?}", err);
+            return;
+        }
+        None => {
+            println!("{:?}", SettingError::FileNotFound);
+            return;
+        }
+    };
+
+    let mut accounts = Accounts::with_min_incoming_amount(settings.get_min_incoming_amount());
+
+    let tx_defaults = accounts.get_tx_defaults();
+    let version = match env::args().nth(2).map(|version| version.parse::<i32>()) {
+        Some(Ok(version)) => version,
+        Some(Err(_)) => {
+            println!("Invalid transaction version.");
+            return;
+        }
+        None => tx_defaults.get_version(),
+    };
+    let sequence = match env::args().nth(3).map(|sequence| sequence.parse::<u32>()) {
+        Some(Ok(sequence)) => sequence,
+        Some(Err(_)) => {
+            println!("Invalid input sequence number.");
+            return;
+        }
+        None => tx_defaults.get_sequence(),
+    };
+    accounts.set_tx_defaults(TransactionDefaults::new(version, sequence));
+
+    if let Some(window) = env::args().nth(4) {
+        match window.parse::<i64>() {
+            Ok(window) => accounts.set_duplicate_payment_window(window),
+            Err(_) => {
+                println!("Invalid duplicate payment window: {}", window);
+                return;
+            }
+        }
+    }
+
+    let light_mode = match env::args().nth(5).as_deref() {
+        Some("light") => true,
+        Some(mode) => {
+            println!("Invalid wallet mode: {}", mode);
+            return;
+        }
+        None => false,
+    };
+
+    let accounts: Arc<Mutex<Accounts>> = Arc::new(Mutex::new(accounts));
 
-    let (tx_sender, tx_recv): (glib::Sender<bool>, glib::Receiver<bool>) =
+    let (events_sender, events_recv): (glib::Sender<WalletEvent>, glib::Receiver<WalletEvent>) =
         MainContext::channel(Priority::default());
     let shared_accounts = accounts.clone();
     let shared_node = node.clone();
@@ -58,7 +134,8 @@ fn main() {
             println!("{:?}", err);
         };
 
-        if let Err(err) = update_transaction_list(&builder, store, shared_accounts.clone(), tx_recv)
+        if let Err(err) =
+            update_transaction_list(&builder, store, shared_accounts.clone(), events_recv)
         {
             println!("{:?}", err);
         };
@@ -66,7 +143,7 @@ fn main() {
         gtk::main();
     });
 
-    if let Err(err) = update_wallet(accounts, node, tx_sender) {
+    if let Err(err) = update_wallet(accounts, node, events_sender, light_mode) {
         println!("{:?}", err);
     };
     if let Err(err) = handle_interface.join() {
@@ -0,0 +1,164 @@
+use node::{
+    messages::{command::Command, message_error::MessageError},
+    wallet_utils::{
+        event_batch::EventBatch,
+        wallet_frame::{read_frame, write_frame},
+    },
+};
+use std::{
+    collections::VecDeque,
+    io::{self, Read, Write},
+    net::TcpStream,
+};
+
+/// Represents the connection to the node, over which the node answers the requests of the wallet
+/// and, once the wallet subscribed to its events, pushes "notify" messages at any time.
+///
+/// Reading the connection only yields the answers to the requests: the notifications read before
+/// an answer are set aside, and taken later on with `take_notifications`.
+///
+/// # Fields
+///
+/// - `stream`: The TCP connection to the node.
+/// - `answer`: The frame of the answer being read.
+/// - `position`: How much of the answer was already read.
+/// - `notifications`: The notifications set aside, in the order they were pushed.
+#[derive(Debug)]
+pub struct NodeConnection {
+    stream: TcpStream,
+    answer: Vec<u8>,
+    position: usize,
+    notifications: VecDeque<EventBatch>,
+}
+
+impl NodeConnection {
+    pub fn new(stream: TcpStream) -> NodeConnection {
+        NodeConnection {
+            stream,
+            answer: Vec::new(),
+            position: 0,
+            notifications: VecDeque::new(),
+        }
+    }
+
+    /// Takes the notifications pushed by the node, including the ones that arrived since the last
+    /// request, without waiting for new ones.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `io::Error` if the node closed the connection or it cannot be read.
+    pub fn take_notifications(&mut self) -> io::Result<Vec<EventBatch>> {
+        while self.has_pending_data()? {
+            if let Some(answer) = self.read_next_frame()? {
+                println!(
+                    "Unexpected answer from the node skipped: {} bytes.",
+                    answer.len()
+                );
+            }
+        }
+        Ok(self.notifications.drain(..).collect())
+    }
+
+    /// Returns whether the node sent data that was not read yet.
+    fn has_pending_data(&self) -> io::Result<bool> {
+        self.stream.set_nonblocking(true)?;
+        let peeked = self.stream.peek(&mut [0u8; 1]);
+        self.stream.set_nonblocking(false)?;
+
+        match peeked {
+            Ok(0) => Err(io::Error::from(io::ErrorKind::UnexpectedEof)),
+            Ok(_) => Ok(true),
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Reads the next frame of the connection, setting it aside if it is a notification.
+    ///
+    /// # Returns
+    ///
+    /// The whole frame if it is not a notification, `None` if it is one or its command is unknown.
+    fn read_next_frame(&mut self) -> io::Result<Option<Vec<u8>>> {
+        let (command, payload) = match read_frame(&mut self.stream) {
+            Ok(frame) => frame,
+            Err(MessageError::UnknownCommand) => return Ok(None),
+            Err(_) => return Err(io::Error::from(io::ErrorKind::InvalidData)),
+        };
+
+        if command == Command::Notify {
+            match EventBatch::from_bytes(command, &mut payload.as_slice()) {
+                Ok(notification) => self.notifications.push_back(notification),
+                Err(_) => println!("Malformed notification from the node skipped."),
+            }
+            return Ok(None);
+        }
+
+        let mut message = command.to_bytes();
+        message.extend(payload);
+
+        let mut frame = vec![];
+        write_frame(&mut frame, &message)?;
+        Ok(Some(frame))
+    }
+}
+
+impl Read for NodeConnection {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.answer.len() {
+            if let Some(answer) = self.read_next_frame()? {
+                self.answer = answer;
+                self.position = 0;
+            }
+        }
+
+        let count = (&self.answer[self.position..]).read(buf)?;
+        self.position += count;
+        Ok(count)
+    }
+}
+
+impl Write for NodeConnection {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stream.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+#[cfg(test)]
+mod node_connection_test {
+    use super::*;
+    use node::{
+        messages::read_from_bytes::read_u32_from_bytes,
+        wallet_utils::{event_log::NodeEvent, get_filters::GetFilters},
+    };
+    use std::{net::TcpListener, thread, time::Duration};
+
+    #[test]
+    fn test_notifications_are_set_aside_from_answers() -> Result<(), MessageError> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let mut connection = NodeConnection::new(TcpStream::connect(listener.local_addr()?)?);
+        let (mut node, _) = listener.accept()?;
+
+        let notification =
+            EventBatch::notification(7, 1, Some(vec![(1, NodeEvent::NewTx(vec![1; 32]))]));
+        write_frame(&mut node, &notification.to_bytes())?;
+        write_frame(&mut node, &GetFilters::new(9).to_bytes())?;
+
+        let (command, payload) = read_frame(&mut connection)?;
+        assert_eq!(command, Command::GetFilters);
+        assert_eq!(read_u32_from_bytes(&mut payload.as_slice(), true)?, 9);
+
+        write_frame(&mut node, &notification.to_bytes())?;
+        thread::sleep(Duration::from_millis(100));
+
+        assert_eq!(
+            connection.take_notifications()?,
+            vec![notification.clone(), notification]
+        );
+        assert!(connection.take_notifications()?.is_empty());
+        Ok(())
+    }
+}
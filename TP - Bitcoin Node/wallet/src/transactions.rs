@@ -1,4 +1,7 @@
 pub mod create_transaction_error;
 pub mod create_transactions;
 pub mod create_transactios_constants;
+pub mod payment_warnings;
+pub mod transaction_defaults;
 pub mod transaction_view;
+pub mod tx_drafts;
@@ -13,6 +13,8 @@ pub enum TransactionCreateError {
     GetPrivateKey,
     WitnessCreationError,
     WitnessProgramError,
+    InvalidDraft,
+    DraftFile,
 }
 
 impl From<MessageError> for TransactionCreateError {
@@ -39,6 +41,12 @@ impl From<std::fmt::Error> for TransactionCreateError {
     }
 }
 
+impl From<std::io::Error> for TransactionCreateError {
+    fn from(_: std::io::Error) -> TransactionCreateError {
+        TransactionCreateError::DraftFile
+    }
+}
+
 impl From<secp256k1::Error> for TransactionCreateError {
     fn from(_: secp256k1::Error) -> TransactionCreateError {
         TransactionCreateError::GetPrivateKey
@@ -1,7 +1,14 @@
-use super::create_transaction_error::TransactionCreateError;
+use super::{
+    create_transaction_error::TransactionCreateError,
+    create_transactios_constants::{P2PKH_ADDRESS_VERSION, P2SH_ADDRESS_VERSION},
+    transaction_defaults::TransactionDefaults,
+};
 use crate::bech32::witness_program::WitnessProgram;
 use bitcoin_hashes::{hash160, sha256, sha256d, Hash};
-use node::block_mod::{script::Script, transaction::Transaction, tx_in::TxIn, tx_out::TxOut};
+use node::block_mod::{
+    script::Script, script_command::ScriptCommand, transaction::Transaction, tx_in::TxIn,
+    tx_out::TxOut,
+};
 use secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
 
 /// Checks if a given string is a valid Bech32-encoded address.
@@ -65,13 +72,35 @@ pub fn address_from_pubkey(
         return Ok(witness_program.to_address()?.as_bytes().to_vec());
     }
 
-    let version_prefix = [0x6f];
-    let double_hash = sha256d::Hash::hash(&[&version_prefix[..], &h160[..]].concat());
-    let checksum = &double_hash[..4];
+    Ok(encode_base58_check(P2PKH_ADDRESS_VERSION, &h160))
+}
+
+/// Generates the P2SH address of a redeem script, such as a multisig script.
+///
+/// The address is the Base58Check encoding of the P2SH version byte and the hash160 of the
+/// redeem script, so funds sent to it can only be spent by revealing the script and satisfying it.
+///
+/// # Arguments
+///
+/// * `redeem_script`: The serialized redeem script.
+///
+/// # Returns
+///
+/// A vector of bytes representing the generated P2SH address.
+pub fn address_from_redeem_script(redeem_script: &[u8]) -> Vec<u8> {
+    let h160 = hash160::Hash::hash(redeem_script).to_byte_array();
+
+    encode_base58_check(P2SH_ADDRESS_VERSION, &h160)
+}
 
-    let input = [&version_prefix[..], &h160[..], checksum].concat();
+/// Encodes a version byte and a payload in Base58Check, appending the first four bytes of their
+/// double SHA-256 as a checksum.
+fn encode_base58_check(version: u8, payload: &[u8]) -> Vec<u8> {
+    let versioned = [&[version][..], payload].concat();
+    let double_hash = sha256d::Hash::hash(&versioned);
+    let input = [&versioned[..], &double_hash[..4]].concat();
 
-    Ok(bs58::encode(input).into_vec())
+    bs58::encode(input).into_vec()
 }
 
 /// Decodes a Base58-encoded Bitcoin address into its raw bytes representation.
@@ -94,6 +123,12 @@ fn decode_base58(address: &Vec<u8>) -> Vec<u8> {
     Vec::new()
 }
 
+/// Returns the version byte of a Base58-encoded Bitcoin address, which tells P2PKH addresses
+/// from P2SH ones, or `None` if the address cannot be decoded.
+fn base58_version(address: &Vec<u8>) -> Option<u8> {
+    bs58::decode(address).into_vec().ok()?.first().copied()
+}
+
 /// Generates a P2PKH or P2WPKH script from a public key.
 ///
 /// This function generates a Pay-to-Public-Key-Hash (P2PKH) or Pay-to-Witness-Public-Key-Hash (P2WPKH) script
@@ -116,10 +151,10 @@ pub fn pk_script_from_pubkey(
     Ok(pk_script_from_address(&address, p2wpkh))
 }
 
-/// Generates a P2PKH or P2WPKH script from an address.
+/// Generates a P2PKH, P2SH or P2WPKH script from an address.
 ///
 /// This function generates a Pay-to-Public-Key-Hash (P2PKH) or Pay-to-Witness-Public-Key-Hash (P2WPKH) script
-/// from a given address.
+/// from a given address, or a Pay-to-Script-Hash (P2SH) one if the address has the P2SH version byte.
 ///
 /// # Arguments
 ///
@@ -141,16 +176,39 @@ pub fn pk_script_from_address(address: &Vec<u8>, p2wpkh: bool) -> Vec<u8> {
     }
 
     let h160 = decode_base58(address);
+    if base58_version(address) == Some(P2SH_ADDRESS_VERSION) {
+        return pk_script_from_script_hash(h160);
+    }
+
     let script = Script::new(Some(vec![
-        vec![0x76],
-        vec![0xa9],
-        h160,
-        vec![0x88],
-        vec![0xac],
+        ScriptCommand::Op(0x76),
+        ScriptCommand::Op(0xa9),
+        ScriptCommand::Push(h160),
+        ScriptCommand::Op(0x88),
+        ScriptCommand::Op(0xac),
     ]));
     script.to_bytes()
 }
 
+/// Generates the P2SH script paying to the hash of a redeem script: `OP_HASH160 <hash160>
+/// OP_EQUAL`.
+///
+/// # Arguments
+///
+/// * `script_hash`: The hash160 of the redeem script.
+///
+/// # Returns
+///
+/// A vector of bytes representing the generated script.
+pub fn pk_script_from_script_hash(script_hash: Vec<u8>) -> Vec<u8> {
+    Script::new(Some(vec![
+        ScriptCommand::Op(0xa9),
+        ScriptCommand::Push(script_hash),
+        ScriptCommand::Op(0x87),
+    ]))
+    .to_bytes()
+}
+
 /// Creates a list of transaction outputs (TxOut) from a list of target addresses and amounts, along with a fee.
 ///
 /// This function generates a list of transaction outputs (TxOut) based on the provided target addresses
@@ -213,6 +271,7 @@ fn was_txout_used(txout: TxOut, used_txouts: &[TxOut]) -> bool {
 /// * `utxo`: A mutable vector of tuples containing UTXOs as (prev_txout_hash, prev_txout_index, TxOut).
 /// * `total_amount`: The total amount to spend in the transaction.
 /// * `used_txouts`: A slice containing the list of used transaction outputs to avoid.
+/// * `sequence`: The sequence number of every input.
 ///
 /// # Returns
 ///
@@ -223,6 +282,7 @@ fn create_txin_list(
     mut utxo: Vec<(Vec<u8>, u32, TxOut)>,
     total_amount: i64,
     used_txouts: &[TxOut],
+    sequence: u32,
 ) -> Result<(Vec<TxIn>, Vec<i64>), TransactionCreateError> {
     let mut txin_list = vec![];
     let mut amount_list = vec![];
@@ -236,7 +296,7 @@ fn create_txin_list(
                 continue;
             }
 
-            txin_list.push(TxIn::new(txout.0, txout.1, vec![], 0xffffffff));
+            txin_list.push(TxIn::new(txout.0, txout.1, vec![], sequence));
 
             amount_list.push(txout.2.get_value());
 
@@ -304,7 +364,10 @@ fn sign_transaction(
 
             signature.push(0x01);
 
-            let script = Script::new(Some(vec![signature, pubkey.clone()]));
+            let script = Script::new(Some(vec![
+                ScriptCommand::Push(signature),
+                ScriptCommand::Push(pubkey.clone()),
+            ]));
 
             transaction.set_signature(i, script.to_bytes());
             transaction.set_witness(vec![]);
@@ -312,6 +375,48 @@ fn sign_transaction(
     }
 }
 
+/// Creates a new unsigned transaction by selecting the coins to spend and assembling inputs and
+/// outputs, sending the change back to the given script.
+///
+/// # Arguments
+///
+/// * `targets`: A vector of tuples containing recipient addresses and amounts.
+/// * `utxo`: A vector of tuples containing UTXO (unspent transaction outputs) information.
+/// * `fee`: The transaction fee to be paid.
+/// * `used_txouts`: A slice of used transaction outputs.
+/// * `change_pk_script`: The script the change is sent to.
+/// * `p2wpkh`: A boolean indicating whether the transaction spends P2WPKH outputs.
+/// * `defaults`: The version of the transaction and the sequence number of its inputs.
+///
+/// # Returns
+///
+/// A `Result` containing the unsigned transaction along with the amount of each of its inputs, or
+/// a `TransactionCreateError` if there are not enough funds.
+pub fn create_unsigned_transaction(
+    targets: Vec<(Vec<u8>, i64)>,
+    utxo: Vec<(Vec<u8>, u32, TxOut)>,
+    fee: i64,
+    used_txouts: &[TxOut],
+    change_pk_script: &[u8],
+    p2wpkh: bool,
+    defaults: &TransactionDefaults,
+) -> Result<(Transaction, Vec<i64>), TransactionCreateError> {
+    let (mut txout_list, total_amount) = create_txout_list(targets, fee);
+    let (txin_list, mut amount_list) =
+        create_txin_list(utxo, total_amount, used_txouts, defaults.get_sequence())?;
+
+    if let Some(change) = amount_list.pop() {
+        if change > 0 {
+            let txout_change = TxOut::new(change, change_pk_script.to_vec());
+            txout_list.push(txout_change);
+        }
+    }
+
+    let transaction = Transaction::new(defaults.get_version(), txin_list, txout_list, 0, p2wpkh);
+
+    Ok((transaction, amount_list))
+}
+
 /// Creates a new transaction by assembling inputs, outputs, and signing.
 ///
 /// This function constructs a new transaction by combining the provided inputs, outputs,
@@ -326,6 +431,7 @@ fn sign_transaction(
 /// * `fee`: The transaction fee to be paid.
 /// * `used_txouts`: A slice of used transaction outputs.
 /// * `p2wpkh`: A boolean indicating whether to use P2WPKH signing.
+/// * `defaults`: The version of the transaction and the sequence number of its inputs.
 ///
 /// # Returns
 ///
@@ -337,6 +443,7 @@ pub fn create_transaction(
     fee: i64,
     used_txouts: &[TxOut],
     p2wpkh: bool,
+    defaults: &TransactionDefaults,
 ) -> Result<Transaction, TransactionCreateError> {
     let secp = Secp256k1::new();
 
@@ -347,17 +454,15 @@ pub fn create_transaction(
         .to_vec();
     let pk_script = pk_script_from_pubkey(&public_key, p2wpkh)?;
 
-    let (mut txout_list, total_amount) = create_txout_list(targets, fee);
-    let (txin_list, mut amount_list) = create_txin_list(utxo, total_amount, used_txouts)?;
-
-    if let Some(change) = amount_list.pop() {
-        if change > 0 {
-            let txout_change = TxOut::new(change, pk_script.clone());
-            txout_list.push(txout_change);
-        }
-    }
-
-    let mut transaction = Transaction::new(1, txin_list, txout_list, 0, p2wpkh);
+    let (mut transaction, amount_list) = create_unsigned_transaction(
+        targets,
+        utxo,
+        fee,
+        used_txouts,
+        &pk_script,
+        p2wpkh,
+        defaults,
+    )?;
 
     sign_transaction(
         &mut transaction,
@@ -376,7 +481,10 @@ mod create_transactions_test {
 
     use bitcoin_hashes::*;
     use node::{
-        block_mod::{script::Script, transaction::Transaction, tx_in::TxIn, tx_out::TxOut},
+        block_mod::{
+            script::Script, script_command::ScriptCommand, transaction::Transaction, tx_in::TxIn,
+            tx_out::TxOut,
+        },
         messages::read_from_bytes::{decode_hex, encode_hex},
     };
     use secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
@@ -384,8 +492,10 @@ mod create_transactions_test {
     use crate::transactions::{
         create_transaction_error::TransactionCreateError,
         create_transactions::{
-            address_from_pubkey, decode_base58, is_array_bech32, is_string_bech32, sign_transaction,
+            address_from_pubkey, address_from_redeem_script, base58_version, decode_base58,
+            is_array_bech32, is_string_bech32, sign_transaction,
         },
+        create_transactios_constants::P2SH_ADDRESS_VERSION,
     };
 
     use super::pk_script_from_address;
@@ -412,22 +522,22 @@ mod create_transactions_test {
         let change_amount = 0.0009 * 100000000.0;
         let change_h160 = decode_base58(&address);
         let change_script = Script::new(Some(vec![
-            vec![0x76],
-            vec![0xa9],
-            change_h160,
-            vec![0x88],
-            vec![0xac],
+            ScriptCommand::Op(0x76),
+            ScriptCommand::Op(0xa9),
+            ScriptCommand::Push(change_h160),
+            ScriptCommand::Op(0x88),
+            ScriptCommand::Op(0xac),
         ]));
         let change_txout = TxOut::new(change_amount as i64, change_script.to_bytes());
 
         let target_amount = 0.0021 * 100000000.0;
         let target_h160 = decode_base58(&target);
         let target_script = Script::new(Some(vec![
-            vec![0x76],
-            vec![0xa9],
-            target_h160,
-            vec![0x88],
-            vec![0xac],
+            ScriptCommand::Op(0x76),
+            ScriptCommand::Op(0xa9),
+            ScriptCommand::Push(target_h160),
+            ScriptCommand::Op(0x88),
+            ScriptCommand::Op(0xac),
         ]));
         let target_txout = TxOut::new(target_amount as i64, target_script.to_bytes());
 
@@ -452,7 +562,10 @@ mod create_transactions_test {
             .serialize()
             .to_vec();
 
-        let signature_script = Script::new(Some(vec![sig, sec]));
+        let signature_script = Script::new(Some(vec![
+            ScriptCommand::Push(sig),
+            ScriptCommand::Push(sec),
+        ]));
 
         tx.set_signature(0, signature_script.to_bytes());
 
@@ -495,6 +608,25 @@ mod create_transactions_test {
         Ok(())
     }
 
+    #[test]
+    pub fn test_p2sh_address_and_pk_script() -> Result<(), TransactionCreateError> {
+        // OP_1 <pubkey> OP_1 OP_CHECKMULTISIG
+        let redeem_script = decode_hex(
+            "51210279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f8179851ae",
+        )?;
+        let script_hash = hash160::Hash::hash(&redeem_script).to_byte_array().to_vec();
+        let address = address_from_redeem_script(&redeem_script);
+
+        assert_eq!(address.first(), Some(&b'2'));
+        assert_eq!(base58_version(&address), Some(P2SH_ADDRESS_VERSION));
+        assert_eq!(
+            pk_script_from_address(&address, false),
+            vec![vec![0xa9, 20], script_hash, vec![0x87]].concat()
+        );
+
+        Ok(())
+    }
+
     #[test]
     pub fn test_pk_script_from_pubkey() -> Result<(), TransactionCreateError> {
         let public_key =
@@ -525,22 +657,22 @@ mod create_transactions_test {
         let change_amount = 0.0009 * 100000000.0;
         let change_h160 = decode_base58(&address);
         let change_script = Script::new(Some(vec![
-            vec![0x76],
-            vec![0xa9],
-            change_h160,
-            vec![0x88],
-            vec![0xac],
+            ScriptCommand::Op(0x76),
+            ScriptCommand::Op(0xa9),
+            ScriptCommand::Push(change_h160),
+            ScriptCommand::Op(0x88),
+            ScriptCommand::Op(0xac),
         ]));
         let change_txout = TxOut::new(change_amount as i64, change_script.to_bytes());
 
         let target_amount = 0.0021 * 100000000.0;
         let target_h160 = decode_base58(&target);
         let target_script = Script::new(Some(vec![
-            vec![0x76],
-            vec![0xa9],
-            target_h160,
-            vec![0x88],
-            vec![0xac],
+            ScriptCommand::Op(0x76),
+            ScriptCommand::Op(0xa9),
+            ScriptCommand::Push(target_h160),
+            ScriptCommand::Op(0x88),
+            ScriptCommand::Op(0xac),
         ]));
         let target_txout = TxOut::new(target_amount as i64, target_script.to_bytes());
 
@@ -568,7 +700,10 @@ mod create_transactions_test {
 
         println!("len sec {}", sec.len());
 
-        let signature_script = Script::new(Some(vec![sig, sec]));
+        let signature_script = Script::new(Some(vec![
+            ScriptCommand::Push(sig),
+            ScriptCommand::Push(sec),
+        ]));
 
         tx.set_signature(0, signature_script.to_bytes());
 
@@ -6,7 +6,33 @@ pub const SENT: &str = "Sent";
 pub const RECEIVED: &str = "Received";
 
 pub const BTC: &str = "BTC";
+pub const OP_RETURN_LABEL: &str = "Data";
+pub const INCOMING_TX_TITLE: &str = "Incoming Transaction";
+
+pub const MIN_INCOMING_AMOUNT: &str = "min_incoming_amount";
+pub const DEFAULT_MIN_INCOMING_AMOUNT: i64 = 546;
 
 pub const DISPONIBLE_VALUE: &str = "disponible_value";
 pub const PENDIENTE_VALUE: &str = "pendiente_value";
 pub const TOTAL_VALUE: &str = "total_value";
+pub const CHAIN_STATUS_LABEL: &str = "chain_status_label";
+
+pub const P2PKH_ADDRESS_VERSION: u8 = 0x6f;
+pub const P2SH_ADDRESS_VERSION: u8 = 0xc4;
+
+pub const DEFAULT_TX_VERSION: i32 = 2;
+pub const DEFAULT_SEQUENCE: u32 = 0xfffffffd;
+pub const MAX_RBF_SEQUENCE: u32 = 0xfffffffd;
+
+pub const RBF_DISABLED_WARNING: &str =
+    "The chosen sequence disables replace-by-fee: the transaction will not be replaceable.";
+pub const RELATIVE_LOCK_IGNORED_WARNING: &str =
+    "The chosen sequence encodes a relative lock time, which is only enforced for version 2 transactions.";
+
+pub const DEFAULT_DUPLICATE_PAYMENT_WINDOW_DAYS: i64 = 7;
+pub const SECONDS_PER_DAY: u64 = 86400;
+
+pub const TX_DRAFTS_FILE_PATH: &str = "tx_drafts.txt";
+pub const DRAFT_FIELD_SEPARATOR: char = ';';
+pub const DRAFT_LIST_SEPARATOR: char = ',';
+pub const DRAFT_PAIR_SEPARATOR: char = ':';
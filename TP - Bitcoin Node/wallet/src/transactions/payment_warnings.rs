@@ -0,0 +1,175 @@
+use super::{
+    create_transaction_error::TransactionCreateError,
+    create_transactions::{address_from_pubkey, is_string_bech32, pk_script_from_address},
+};
+use crate::user_info::UserInfo;
+use node::wallet_utils::wallet_tx::WalletTx;
+use std::fmt;
+
+/// Represents the reasons a payment may be a mistake, shown to the user before broadcasting it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaymentWarning {
+    /// The recipient was already paid the same amount within the duplicate payment window.
+    DuplicatePayment(String, i64),
+    /// The recipient is one of the receive addresses of the wallet's own accounts.
+    OwnAddress(String),
+}
+
+impl fmt::Display for PaymentWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PaymentWarning::DuplicatePayment(address, amount) => write!(
+                f,
+                "{} was already paid {} satoshis recently.",
+                address, amount
+            ),
+            PaymentWarning::OwnAddress(address) => {
+                write!(f, "{} is one of your own receive addresses.", address)
+            }
+        }
+    }
+}
+
+/// Converts a `YYYY-MM-DD` date into the amount of days since the Unix epoch.
+///
+/// # Arguments
+///
+/// * `date` - The date, as stored in the wallet transactions.
+///
+/// # Returns
+///
+/// The amount of days since 1970-01-01, or `None` if the date is malformed.
+pub fn days_from_date(date: &str) -> Option<i64> {
+    let mut fields = date.split('-').map(|field| field.parse::<i64>());
+    let (year, month, day) = match (fields.next(), fields.next(), fields.next()) {
+        (Some(Ok(year)), Some(Ok(month)), Some(Ok(day))) => (year, month, day),
+        _ => return None,
+    };
+
+    // Days from civil algorithm, counting years from March so leap days fall at the end
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    Some(era * 146097 + day_of_era - 719468)
+}
+
+/// Checks whether a transaction from the history paid the given amount to the given script.
+fn pays(wallet_tx: &WalletTx, pk_script: &[u8], amount: i64) -> bool {
+    wallet_tx
+        .get_tx()
+        .get_tx_out_list()
+        .iter()
+        .any(|txout| txout.get_pk_script() == pk_script && txout.get_value() == amount)
+}
+
+/// Checks a list of payments against the sent transactions history and the wallet's own
+/// addresses.
+///
+/// # Arguments
+///
+/// * `targets` - The recipient addresses and amounts about to be paid.
+/// * `user_info` - The account sending the payments.
+/// * `own_addresses` - The receive addresses of every account of the wallet.
+/// * `today` - The current date, as days since the Unix epoch.
+/// * `window_days` - The amount of days a previous payment is considered recent.
+///
+/// # Returns
+///
+/// The warnings found, which is empty if the payments look legitimate.
+pub fn check_payment_warnings(
+    targets: &[(Vec<u8>, i64)],
+    user_info: &UserInfo,
+    own_addresses: &[Vec<u8>],
+    today: i64,
+    window_days: i64,
+) -> Vec<PaymentWarning> {
+    let mut warnings = vec![];
+
+    for (address, amount) in targets {
+        let string_address = String::from_utf8_lossy(address).to_string();
+
+        if own_addresses.contains(address) {
+            warnings.push(PaymentWarning::OwnAddress(string_address.clone()));
+        }
+
+        let pk_script = pk_script_from_address(address, is_string_bech32(string_address.clone()));
+        let paid_recently = user_info
+            .get_confirmed_txs_send()
+            .iter()
+            .chain(user_info.get_unconfirmed_txs_send().iter())
+            .filter(|wallet_tx| match days_from_date(wallet_tx.get_date()) {
+                Some(date) => today - date <= window_days,
+                None => false,
+            })
+            .any(|wallet_tx| pays(wallet_tx, &pk_script, *amount));
+
+        if paid_recently {
+            warnings.push(PaymentWarning::DuplicatePayment(string_address, *amount));
+        }
+    }
+
+    warnings
+}
+
+/// Returns the receive address of an account.
+///
+/// # Arguments
+///
+/// * `user_info` - The account.
+pub fn get_receive_address(user_info: &UserInfo) -> Result<Vec<u8>, TransactionCreateError> {
+    address_from_pubkey(&user_info.get_public_key(), user_info.get_bech32())
+}
+
+#[cfg(test)]
+mod payment_warnings_test {
+    use super::*;
+    use node::{
+        block_mod::{transaction::Transaction, tx_out::TxOut},
+        wallet_utils::transactions::Transactions,
+    };
+
+    #[test]
+    fn test_days_from_date() {
+        assert_eq!(days_from_date("1970-01-01"), Some(0));
+        assert_eq!(days_from_date("2000-03-01"), Some(11017));
+        assert_eq!(days_from_date("2023-13"), None);
+    }
+
+    #[test]
+    fn test_payment_warnings() -> Result<(), TransactionCreateError> {
+        let target = b"mp3PDnKDtxPYrPKcYLGX1pXMe6KwAsfquD".to_vec();
+        let pk_script = pk_script_from_address(&target, false);
+        let tx = Transaction::new(2, vec![], vec![TxOut::new(1000, pk_script)], 0, false);
+        let wallet_tx = WalletTx::new(tx, "2023-06-25".to_string());
+
+        let mut user_info = UserInfo::new(vec![], vec![], false);
+        user_info.update(&Transactions::new(
+            vec![wallet_tx],
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+            0,
+        ));
+
+        let today = days_from_date("2023-06-27").unwrap_or_default();
+        let targets = vec![(target.clone(), 1000)];
+
+        assert_eq!(
+            check_payment_warnings(&targets, &user_info, &[target.clone()], today, 7),
+            vec![
+                PaymentWarning::OwnAddress(String::from_utf8_lossy(&target).to_string()),
+                PaymentWarning::DuplicatePayment(
+                    String::from_utf8_lossy(&target).to_string(),
+                    1000
+                )
+            ]
+        );
+        assert!(check_payment_warnings(&targets, &user_info, &[], today, 1).is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,93 @@
+use super::create_transactios_constants::*;
+use node::block_mod::relative_lock_time::RelativeLockTime;
+
+/// Represents the version and input sequence number used when building new transactions.
+///
+/// Version 2 transactions enable BIP68 relative lock times, which are encoded in the sequence of
+/// each input, while any sequence below `0xfffffffe` signals replace-by-fee.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransactionDefaults {
+    version: i32,
+    sequence: u32,
+}
+
+impl TransactionDefaults {
+    pub fn new(version: i32, sequence: u32) -> TransactionDefaults {
+        TransactionDefaults { version, sequence }
+    }
+
+    /// Returns whether transactions built with these defaults signal replace-by-fee.
+    pub fn signals_rbf(&self) -> bool {
+        self.sequence <= MAX_RBF_SEQUENCE
+    }
+
+    /// Returns the relative lock time each input will impose, as enforced by nodes under BIP68.
+    pub fn get_relative_lock_time(&self) -> Option<RelativeLockTime> {
+        RelativeLockTime::from_sequence(self.version, self.sequence)
+    }
+
+    /// Returns the warnings the user should be shown about the chosen defaults.
+    ///
+    /// # Returns
+    ///
+    /// A list of warnings, which is empty if the defaults behave as expected.
+    pub fn get_warnings(&self) -> Vec<&'static str> {
+        let mut warnings = vec![];
+
+        if !self.signals_rbf() {
+            warnings.push(RBF_DISABLED_WARNING);
+        }
+
+        if self.get_relative_lock_time().is_none()
+            && RelativeLockTime::from_sequence(DEFAULT_TX_VERSION, self.sequence).is_some()
+        {
+            warnings.push(RELATIVE_LOCK_IGNORED_WARNING);
+        }
+
+        warnings
+    }
+
+    pub fn get_version(&self) -> i32 {
+        self.version
+    }
+
+    pub fn get_sequence(&self) -> u32 {
+        self.sequence
+    }
+}
+
+impl Default for TransactionDefaults {
+    fn default() -> Self {
+        Self::new(DEFAULT_TX_VERSION, DEFAULT_SEQUENCE)
+    }
+}
+
+#[cfg(test)]
+mod transaction_defaults_test {
+    use super::*;
+
+    #[test]
+    fn test_default_transaction_signals_rbf() {
+        let defaults = TransactionDefaults::default();
+
+        assert!(defaults.signals_rbf());
+        assert!(defaults.get_relative_lock_time().is_none());
+        assert!(defaults.get_warnings().is_empty());
+    }
+
+    #[test]
+    fn test_transaction_defaults_warnings() {
+        let final_sequence = TransactionDefaults::new(2, 0xffffffff);
+        let version_1_lock = TransactionDefaults::new(1, 10);
+
+        assert_eq!(final_sequence.get_warnings(), vec![RBF_DISABLED_WARNING]);
+        assert_eq!(
+            version_1_lock.get_warnings(),
+            vec![RELATIVE_LOCK_IGNORED_WARNING]
+        );
+        assert_eq!(
+            TransactionDefaults::new(2, 10).get_relative_lock_time(),
+            Some(RelativeLockTime::Blocks(10))
+        );
+    }
+}
@@ -2,7 +2,7 @@ use gtk::{prelude::BuilderExtManual, Builder, Label, LabelExt};
 use std::sync::{Arc, Mutex};
 
 use node::{
-    block_mod::{transaction::Transaction, tx_out::TxOut},
+    block_mod::{script::Script, transaction::Transaction, tx_out::TxOut},
     messages::read_from_bytes::encode_hex,
     wallet_utils::wallet_tx::WalletTx,
 };
@@ -11,7 +11,51 @@ use glib::{clone, Continue, Receiver};
 use gtk::{prelude::GtkListStoreExtManual, GtkListStoreExt, ListStore};
 
 use super::{create_transactions::pk_script_from_pubkey, create_transactios_constants::*};
-use crate::{accounts::Accounts, interface_error::InterfaceError};
+use crate::{
+    accounts::Accounts,
+    handlers::handle_buttons::show_error,
+    interface_error::InterfaceError,
+    wallet_event::{ChainStatus, WalletEvent},
+};
+
+/// Returns the data embedded in the null-data (`OP_RETURN`) outputs of a transaction.
+///
+/// The data is shown as text if it is printable UTF-8, and in hexadecimal otherwise, so users can
+/// inspect data-carrying transactions.
+///
+/// # Arguments
+///
+/// * `transaction` - The transaction whose outputs are inspected.
+///
+/// # Returns
+///
+/// The data of every null-data output, separated by commas, or `None` if the transaction has no
+/// null-data output.
+pub fn op_return_text(transaction: &Transaction) -> Option<String> {
+    let texts: Vec<String> = transaction
+        .get_tx_out_list()
+        .iter()
+        .filter_map(|tx_out| {
+            Script::from_bytes(&tx_out.get_pk_script())
+                .ok()?
+                .op_return_data()
+        })
+        .map(|data| match String::from_utf8(data.clone()) {
+            Ok(text) if !text.chars().any(char::is_control) => text,
+            _ => encode_hex(&data).unwrap_or_default(),
+        })
+        .collect();
+
+    (!texts.is_empty()).then(|| texts.join(", "))
+}
+
+/// Returns the type shown for a transaction in the list, followed by the data it embeds, if any.
+fn tx_type_label(transaction: &Transaction, tx_type: &str) -> String {
+    match op_return_text(transaction) {
+        Some(data) => format!("{} ({}: {})", tx_type, OP_RETURN_LABEL, data),
+        None => tx_type.to_string(),
+    }
+}
 
 /// Calculates the received balance for a specific public key script in a transaction.
 ///
@@ -30,15 +74,52 @@ use crate::{accounts::Accounts, interface_error::InterfaceError};
 ///
 /// The received balance for the specified public key script in the transaction,
 /// expressed as a floating-point value in BTC.
-pub fn received_balance(transaction: &Transaction, pk_script: &Vec<u8>) -> f64 {
-    let mut balance = 0;
+pub fn received_balance(transaction: &Transaction, pk_script: &[u8]) -> f64 {
+    received_amount(transaction, pk_script) as f64 / BTC_TO_SATOSHI
+}
+
+/// Calculates the amount, in satoshis, received by a specific public key script in a transaction.
+///
+/// # Arguments
+///
+/// * `transaction` - The transaction to calculate the received amount from.
+/// * `pk_script` - The public key script to match against the transaction outputs.
+///
+/// # Returns
+///
+/// The sum of the values of every output paying to the specified public key script.
+pub fn received_amount(transaction: &Transaction, pk_script: &[u8]) -> i64 {
+    let mut amount = 0;
 
     for tx_out in transaction.get_tx_out_list() {
         if *pk_script == tx_out.get_pk_script() {
-            balance += tx_out.get_value();
+            amount += tx_out.get_value();
         }
     }
-    balance as f64 / BTC_TO_SATOSHI
+    amount
+}
+
+/// Checks whether an incoming transaction is a dust deposit.
+///
+/// A deposit is considered dust when the amount received by the public key script is lower than
+/// the wallet's minimum incoming amount. Dust deposits are hidden from the history by default and
+/// do not trigger notifications, but they are still counted in the balances.
+///
+/// # Arguments
+///
+/// * `transaction` - The incoming transaction.
+/// * `pk_script` - The public key script of the recipient.
+/// * `min_incoming_amount` - The minimum incoming amount, in satoshis.
+///
+/// # Returns
+///
+/// Returns `true` if the received amount is below the minimum incoming amount.
+pub fn is_dust_deposit(
+    transaction: &Transaction,
+    pk_script: &[u8],
+    min_incoming_amount: i64,
+) -> bool {
+    received_amount(transaction, pk_script) < min_incoming_amount
 }
 
 /// Calculates the sent balance for a specific public key script in a transaction.
@@ -150,7 +231,7 @@ fn update_tx_send(
             &[
                 &state,
                 &tx.get_date(),
-                &tx_type,
+                &tx_type_label(tx.get_tx(), tx_type),
                 &encode_hex(&txn).map_err(|_| InterfaceError::DecodeHex)?,
                 &format!("-{}", send_balance(tx.get_tx(), pub_key)),
             ],
@@ -174,6 +255,7 @@ fn update_tx_send(
 /// * `state` - The current state of the transactions.
 /// * `tx_type` - The type of the transactions.
 /// * `pk_script` - The public key script of the recipient.
+/// * `min_incoming_amount` - The minimum incoming amount, in satoshis, or `None` to list dust deposits too.
 ///
 /// # Returns
 ///
@@ -185,8 +267,15 @@ fn update_tx_recv(
     state: &str,
     tx_type: &str,
     pk_script: &Vec<u8>,
+    min_incoming_amount: Option<i64>,
 ) -> Result<(), InterfaceError> {
     for tx in transactions {
+        if let Some(min_amount) = min_incoming_amount {
+            if is_dust_deposit(tx.get_tx(), pk_script, min_amount) {
+                continue;
+            }
+        }
+
         let mut txn = tx.get_tx().get_id(false);
         txn.reverse();
 
@@ -196,7 +285,7 @@ fn update_tx_recv(
             &[
                 &state,
                 &tx.get_date(),
-                &tx_type,
+                &tx_type_label(tx.get_tx(), tx_type),
                 &encode_hex(&txn).map_err(|_| InterfaceError::DecodeHex)?,
                 &format!("{}", received_balance(tx.get_tx(), pk_script)),
             ],
@@ -211,7 +300,8 @@ fn update_tx_recv(
 /// to the `Accounts` structure. It clears the list store and retrieves the actual account from the
 /// locked accounts. It obtains the public key and generates the public key script. It then calls the
 /// `update_tx_send` and `update_tx_recv` functions to update the transaction list view for confirmed
-/// and unconfirmed sent and received transactions. Finally, it drops the lock on the accounts and
+/// and unconfirmed sent and received transactions. Dust deposits are left out of the list unless the
+/// accounts are set to show them. Finally, it drops the lock on the accounts and
 /// returns `Ok(())` if the update is successful.
 ///
 /// # Arguments
@@ -235,6 +325,11 @@ pub fn update_transactions(
         .ok_or(InterfaceError::LockAccounts)?;
     let pub_key = actual_account.get_public_key();
     let pk_script = pk_script_from_pubkey(&pub_key, actual_account.get_bech32())?;
+    let min_incoming_amount = if locked_accounts.get_show_dust() {
+        None
+    } else {
+        Some(locked_accounts.get_min_incoming_amount())
+    };
 
     update_tx_send(
         actual_account.get_confirmed_txs_send(),
@@ -249,6 +344,7 @@ pub fn update_transactions(
         CONFIRMED,
         RECEIVED,
         &pk_script,
+        min_incoming_amount,
     )?;
 
     update_tx_send(
@@ -264,6 +360,7 @@ pub fn update_transactions(
         UNCONFIRMED,
         RECEIVED,
         &pk_script,
+        min_incoming_amount,
     )?;
 
     drop(locked_accounts);
@@ -315,35 +412,32 @@ fn update_balance_labels(
     Ok(())
 }
 
-/// Updates the transaction list and balance labels in the user interface.
+/// Updates the transaction list, balance labels and chain status label as events arrive.
 ///
-/// This function updates the transaction list and balance labels in the user interface based on
-/// the account information. It takes the builder, the list store, the shared `Accounts` object,
-/// and a receiver for transaction update signals as input. It retrieves the labels for the available,
-/// pending, and total balances from the builder. It then attaches a signal handler to the transaction
-/// update receiver. Whenever a transaction update signal is received, the function calls the
-/// `update_transactions` function to update the transaction list in the user interface, and the
-/// `update_balance_labels` function to update the balance labels. If there is any error during the
-/// update process, the function returns `Continue(false)` to stop further signal processing.
-/// Otherwise, it returns `Continue(true)` to continue processing subsequent signals.
+/// This function attaches a handler to the receiver of wallet events. Each event only refreshes
+/// the views it affects: a `NewTx` event for the current account rebuilds the transaction list and
+/// the balance labels, a `Reorg` event rebuilds them as well since confirmed transactions may have
+/// changed, an `IncomingTx` event is shown in a dialog, and every chain event updates the chain
+/// status label. If there is any error during the update process, the handler returns
+/// `Continue(false)` to stop further event processing. Otherwise, it returns `Continue(true)` to
+/// continue processing subsequent events.
 ///
 /// # Arguments
 ///
 /// * `builder` - The builder object containing the user interface elements.
 /// * `store` - The list store for the transaction list.
 /// * `accounts` - The shared `Accounts` object.
-/// * `txs_recv` - The receiver for transaction update signals.
+/// * `events_recv` - The receiver of wallet events.
 ///
 /// # Returns
 ///
-/// Returns `Ok(())` if the transaction list and balance labels are successfully updated, or an
-/// `InterfaceError` if there is an error retrieving the necessary user interface elements or
-/// during the update process.
+/// Returns `Ok(())` if the handler is successfully attached, or an `InterfaceError` if there is an
+/// error retrieving the necessary user interface elements.
 pub fn update_transaction_list(
     builder: &Builder,
     store: ListStore,
     accounts: Arc<Mutex<Accounts>>,
-    txs_recv: Receiver<bool>,
+    events_recv: Receiver<WalletEvent>,
 ) -> Result<(), InterfaceError> {
     let available: Label = builder
         .get_object(DISPONIBLE_VALUE)
@@ -354,15 +448,39 @@ pub fn update_transaction_list(
     let total: Label = builder
         .get_object(TOTAL_VALUE)
         .ok_or(InterfaceError::MissingLabel)?;
+    let chain_status_label: Label = builder
+        .get_object(CHAIN_STATUS_LABEL)
+        .ok_or(InterfaceError::MissingLabel)?;
+    let mut chain_status = ChainStatus::default();
+    let builder = builder.clone();
 
-    txs_recv.attach(
+    events_recv.attach(
         None,
         clone!(@weak store => @default-return Continue(false),
-            move |_| {
-                if update_transactions(store, accounts.clone()).is_err() || update_balance_labels(&available, &pending, &total, accounts.clone()).is_err(){
+            move |event| {
+                let refresh_transactions = match &event {
+                    WalletEvent::NewTx(username) => match accounts.lock() {
+                        Ok(locked_accounts) => locked_accounts.get_current_username() == username,
+                        Err(_) => return Continue(false),
+                    },
+                    WalletEvent::IncomingTx(username, amount) => {
+                        let btc_amount = *amount as f64 / BTC_TO_SATOSHI;
+                        let text = format!("{} received {} {}.", username, btc_amount, BTC);
+                        show_error(&builder, INCOMING_TX_TITLE, &text);
+                        false
+                    }
+                    WalletEvent::Reorg(_) => true,
+                    _ => false,
+                };
+
+                if refresh_transactions && (update_transactions(store, accounts.clone()).is_err() || update_balance_labels(&available, &pending, &total, accounts.clone()).is_err()) {
                     return Continue(false);
                 }
 
+                if chain_status.apply(&event) {
+                    chain_status_label.set_text(&chain_status.to_string());
+                }
+
                 Continue(true)
             }
         ),
@@ -0,0 +1,344 @@
+use super::{
+    create_transaction_error::TransactionCreateError,
+    create_transactions::create_unsigned_transaction, create_transactios_constants::*,
+    transaction_defaults::TransactionDefaults,
+};
+use node::{
+    block_mod::{transaction::Transaction, tx_out::TxOut},
+    messages::read_from_bytes::{decode_hex, encode_hex},
+};
+use std::{
+    collections::BTreeMap,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+};
+
+/// Represents a send that is still being prepared: its recipients and amounts, its fee, and the
+/// outputs selected to fund it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TxDraft {
+    targets: Vec<(Vec<u8>, i64)>,
+    fee: i64,
+    inputs: Vec<(Vec<u8>, u32)>,
+}
+
+/// Keeps the named drafts of the wallet, persisted in a file so they survive the app closing.
+///
+/// Each line of the file holds a draft: its name, its fee, its recipients and amounts, and the
+/// outputs it spends, separated by `;`.
+#[derive(Debug)]
+pub struct TxDrafts {
+    drafts: BTreeMap<String, TxDraft>,
+    file_path: String,
+}
+
+impl TxDraft {
+    pub fn new(targets: Vec<(Vec<u8>, i64)>, fee: i64, inputs: Vec<(Vec<u8>, u32)>) -> TxDraft {
+        TxDraft {
+            targets,
+            fee,
+            inputs,
+        }
+    }
+
+    /// Creates a draft selecting the outputs that fund it, the same way a transaction is created.
+    ///
+    /// # Arguments
+    ///
+    /// * `targets` - The recipient addresses and amounts.
+    /// * `fee` - The fee of the transaction.
+    /// * `utxo` - The unspent outputs of the account.
+    /// * `used_txouts` - The outputs already spent by unconfirmed transactions.
+    /// * `defaults` - The version of the transaction and the sequence number of its inputs.
+    ///
+    /// # Returns
+    ///
+    /// The draft, or a `TransactionCreateError` if the account cannot fund it.
+    pub fn select(
+        targets: Vec<(Vec<u8>, i64)>,
+        fee: i64,
+        utxo: Vec<(Vec<u8>, u32, TxOut)>,
+        used_txouts: &[TxOut],
+        defaults: &TransactionDefaults,
+    ) -> Result<TxDraft, TransactionCreateError> {
+        let (transaction, _) = create_unsigned_transaction(
+            targets.clone(),
+            utxo,
+            fee,
+            used_txouts,
+            &[],
+            false,
+            defaults,
+        )?;
+
+        let inputs = transaction
+            .get_tx_in_list()
+            .iter()
+            .map(|tx_in| {
+                let outpoint = tx_in.get_prev_output();
+                (outpoint.get_tx_id().clone(), outpoint.get_index())
+            })
+            .collect();
+
+        Ok(TxDraft::new(targets, fee, inputs))
+    }
+
+    /// Builds the unsigned transaction of the draft, spending only the outputs it selected, so it
+    /// can be reviewed before it is signed.
+    ///
+    /// # Arguments
+    ///
+    /// * `utxo` - The unspent outputs of the account.
+    /// * `change_pk_script` - The script the change is sent to.
+    /// * `p2wpkh` - Whether the account spends P2WPKH outputs.
+    /// * `defaults` - The version of the transaction and the sequence number of its inputs.
+    ///
+    /// # Returns
+    ///
+    /// The unsigned transaction, or `TransactionCreateError::UnavailableOutput` if some selected
+    /// output was spent since the draft was saved.
+    pub fn to_unsigned_transaction(
+        &self,
+        utxo: Vec<(Vec<u8>, u32, TxOut)>,
+        change_pk_script: &[u8],
+        p2wpkh: bool,
+        defaults: &TransactionDefaults,
+    ) -> Result<Transaction, TransactionCreateError> {
+        let mut selected = vec![];
+
+        for (tx_id, index) in self.inputs.iter().rev() {
+            let output = utxo
+                .iter()
+                .find(|(utxo_id, utxo_index, _)| utxo_id == tx_id && utxo_index == index)
+                .ok_or(TransactionCreateError::UnavailableOutput)?;
+            selected.push(output.clone());
+        }
+
+        let (transaction, _) = create_unsigned_transaction(
+            self.targets.clone(),
+            selected,
+            self.fee,
+            &[],
+            change_pk_script,
+            p2wpkh,
+            defaults,
+        )?;
+
+        Ok(transaction)
+    }
+
+    /// Serializes the draft into a line of the drafts file, after its name.
+    fn to_line(&self) -> Result<String, TransactionCreateError> {
+        let targets: Vec<String> = self
+            .targets
+            .iter()
+            .map(|(address, amount)| {
+                format!(
+                    "{}{}{}",
+                    String::from_utf8_lossy(address),
+                    DRAFT_PAIR_SEPARATOR,
+                    amount
+                )
+            })
+            .collect();
+
+        let mut inputs: Vec<String> = vec![];
+        for (tx_id, index) in self.inputs.iter() {
+            inputs.push(format!(
+                "{}{}{}",
+                encode_hex(tx_id)?,
+                DRAFT_PAIR_SEPARATOR,
+                index
+            ));
+        }
+
+        Ok(format!(
+            "{}{}{}{}{}",
+            self.fee,
+            DRAFT_FIELD_SEPARATOR,
+            targets.join(&DRAFT_LIST_SEPARATOR.to_string()),
+            DRAFT_FIELD_SEPARATOR,
+            inputs.join(&DRAFT_LIST_SEPARATOR.to_string())
+        ))
+    }
+
+    /// Parses a draft from the fields of a line of the drafts file that follow its name.
+    fn from_fields(fields: &[&str]) -> Result<TxDraft, TransactionCreateError> {
+        let [fee, targets, inputs] = fields else {
+            return Err(TransactionCreateError::InvalidDraft);
+        };
+
+        let mut draft = TxDraft::new(vec![], fee.parse()?, vec![]);
+
+        for target in targets
+            .split(DRAFT_LIST_SEPARATOR)
+            .filter(|t| !t.is_empty())
+        {
+            let (address, amount) = target
+                .split_once(DRAFT_PAIR_SEPARATOR)
+                .ok_or(TransactionCreateError::InvalidDraft)?;
+            draft
+                .targets
+                .push((address.as_bytes().to_vec(), amount.parse()?));
+        }
+
+        for input in inputs.split(DRAFT_LIST_SEPARATOR).filter(|i| !i.is_empty()) {
+            let (tx_id, index) = input
+                .split_once(DRAFT_PAIR_SEPARATOR)
+                .ok_or(TransactionCreateError::InvalidDraft)?;
+            draft.inputs.push((decode_hex(tx_id)?, index.parse()?));
+        }
+
+        Ok(draft)
+    }
+
+    pub fn get_targets(&self) -> &Vec<(Vec<u8>, i64)> {
+        &self.targets
+    }
+
+    pub fn get_fee(&self) -> i64 {
+        self.fee
+    }
+
+    pub fn get_inputs(&self) -> &Vec<(Vec<u8>, u32)> {
+        &self.inputs
+    }
+}
+
+impl TxDrafts {
+    /// Loads the drafts saved in the given file, which is created if it does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_path` - The path of the drafts file.
+    pub fn new(file_path: &str) -> Result<TxDrafts, TransactionCreateError> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .read(true)
+            .open(file_path)?;
+        let mut drafts = BTreeMap::new();
+
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            let fields: Vec<&str> = line.split(DRAFT_FIELD_SEPARATOR).collect();
+
+            if let Some((name, fields)) = fields.split_first() {
+                drafts.insert(name.to_string(), TxDraft::from_fields(fields)?);
+            }
+        }
+
+        Ok(TxDrafts {
+            drafts,
+            file_path: file_path.to_string(),
+        })
+    }
+
+    /// Saves a draft under the given name, replacing the draft previously saved under it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `TransactionCreateError::InvalidDraft` if the name is empty or holds a separator of
+    /// the file, or `TransactionCreateError::DraftFile` if the file cannot be written.
+    pub fn save(&mut self, name: &str, draft: TxDraft) -> Result<(), TransactionCreateError> {
+        if name.is_empty() || name.contains(DRAFT_FIELD_SEPARATOR) || name.contains('\n') {
+            return Err(TransactionCreateError::InvalidDraft);
+        }
+
+        self.drafts.insert(name.to_string(), draft);
+        self.write()
+    }
+
+    /// Removes the draft saved under the given name, such as once it has been sent.
+    ///
+    /// # Returns
+    ///
+    /// The removed draft, if there was one.
+    pub fn remove(&mut self, name: &str) -> Result<Option<TxDraft>, TransactionCreateError> {
+        let draft = self.drafts.remove(name);
+
+        if draft.is_some() {
+            self.write()?;
+        }
+        Ok(draft)
+    }
+
+    /// Rewrites the drafts file with the drafts currently kept.
+    fn write(&self) -> Result<(), TransactionCreateError> {
+        let mut file = File::create(&self.file_path)?;
+
+        for (name, draft) in self.drafts.iter() {
+            writeln!(
+                file,
+                "{}{}{}",
+                name,
+                DRAFT_FIELD_SEPARATOR,
+                draft.to_line()?
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&TxDraft> {
+        self.drafts.get(name)
+    }
+
+    pub fn get_names(&self) -> Vec<String> {
+        self.drafts.keys().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tx_drafts_test {
+    use super::*;
+    use std::fs;
+
+    fn utxo() -> Vec<(Vec<u8>, u32, TxOut)> {
+        vec![
+            (vec![1; 32], 0, TxOut::new(5000, vec![0; 25])),
+            (vec![2; 32], 1, TxOut::new(7000, vec![0; 25])),
+        ]
+    }
+
+    #[test]
+    fn test_draft_keeps_its_coin_selection() -> Result<(), TransactionCreateError> {
+        let targets = vec![(b"mnJvq7mbGiPNNhUne4FAqq27Q8xZrAsVun".to_vec(), 6000)];
+        let defaults = TransactionDefaults::default();
+        let draft = TxDraft::select(targets, 500, utxo(), &[], &defaults)?;
+
+        assert_eq!(draft.get_inputs(), &vec![(vec![2; 32], 1)]);
+
+        let transaction = draft.to_unsigned_transaction(utxo(), &[1; 25], false, &defaults)?;
+        assert_eq!(transaction.get_tx_in_list().len(), 1);
+        assert_eq!(transaction.get_tx_out_list()[1].get_value(), 500);
+
+        assert!(draft
+            .to_unsigned_transaction(utxo()[..1].to_vec(), &[1; 25], false, &defaults)
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_drafts_are_persisted() -> Result<(), TransactionCreateError> {
+        let file_path = "test_tx_drafts.txt";
+        let _ = fs::remove_file(file_path);
+        let draft = TxDraft::new(
+            vec![(b"mnJvq7mbGiPNNhUne4FAqq27Q8xZrAsVun".to_vec(), 6000)],
+            500,
+            vec![(vec![2; 32], 1)],
+        );
+
+        let mut drafts = TxDrafts::new(file_path)?;
+        drafts.save("rent", draft.clone())?;
+        drafts.save("groceries", TxDraft::new(vec![], 100, vec![]))?;
+        assert!(drafts.save("bad;name", draft.clone()).is_err());
+        drafts.remove("groceries")?;
+
+        let loaded = TxDrafts::new(file_path)?;
+        fs::remove_file(file_path)?;
+
+        assert_eq!(loaded.get_names(), vec!["rent".to_string()]);
+        assert_eq!(loaded.get("rent"), Some(&draft));
+        Ok(())
+    }
+}
@@ -1,84 +1,593 @@
 use crate::{
-    accounts::Accounts, interface_error::InterfaceError,
-    transactions::create_transactions::pk_script_from_pubkey,
+    accounts::Accounts,
+    interface_error::InterfaceError,
+    light_client::LightClient,
+    node_connection::NodeConnection,
+    transactions::{
+        create_transactions::pk_script_from_pubkey,
+        transaction_view::{is_dust_deposit, received_amount},
+    },
+    user_info::UserInfo,
+    wallet_event::{ChainWatcher, EventCursor, WalletEvent},
 };
 use node::{
-    messages::read_from_bytes::read_string_from_bytes,
-    wallet_utils::{get_transactions::GetTransactions, transactions::Transactions},
+    messages::command::Command,
+    network::network_constants::WALLET_PROTOCOL_VERSION,
+    wallet_utils::{
+        auth_result::AuthResult,
+        event_batch::EventBatch,
+        event_log::NodeEvent,
+        get_node_info::GetNodeInfo,
+        get_peer_info::GetPeerInfo,
+        get_transaction::GetTransaction,
+        get_transactions::GetTransactions,
+        node_info::NodeInfo,
+        peer_info::PeerInfo,
+        subscribe::Subscribe,
+        transaction_info::TransactionInfo,
+        transactions::Transactions,
+        wallet_auth::WalletAuth,
+        wallet_frame::{read_frame, write_frame},
+        wallet_message::{WalletRequest, WalletResponse},
+        wallet_version::WalletVersion,
+    },
 };
 use std::{
-    io::Write,
-    net::TcpStream,
     sync::{Arc, Mutex},
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
+/// How often the notifications pushed by the node are checked for. Checking them does not reach
+/// the node, it only reads what the node already sent.
+const NOTIFICATION_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How often the node's status is requested when it pushed no events, as the sync progress and
+/// the peer count change without them.
+const STATUS_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Notifies the interface about the newly confirmed incoming transactions of the current account.
+///
+/// Only the transactions whose received amount reaches the wallet's minimum incoming amount
+/// trigger a notification; dust deposits are silently processed.
+///
+/// # Arguments
+///
+/// * `transactions` - The transactions received from the node.
+/// * `username` - The name of the current account.
+/// * `pk_script` - The public key script of the current account.
+/// * `min_incoming_amount` - The minimum incoming amount, in satoshis.
+/// * `events_sender` - The sender of the events to the interface.
+///
+/// # Errors
+///
+/// Returns `InterfaceError::Send` if the interface is no longer receiving events.
+fn notify_incoming_transactions(
+    transactions: &Transactions,
+    username: &str,
+    pk_script: &[u8],
+    min_incoming_amount: i64,
+    events_sender: &glib::Sender<WalletEvent>,
+) -> Result<(), InterfaceError> {
+    for tx in transactions.get_confirmed_txs_recv() {
+        if !is_dust_deposit(tx.get_tx(), pk_script, min_incoming_amount) {
+            events_sender
+                .send(WalletEvent::IncomingTx(
+                    username.to_string(),
+                    received_amount(tx.get_tx(), pk_script),
+                ))
+                .map_err(|_| InterfaceError::Send)?;
+        }
+    }
+    Ok(())
+}
+
+/// Announces to the node the version of the wallet protocol the wallet speaks and checks that the
+/// node speaks the same one. It must be the first exchange on the connection.
+///
+/// # Arguments
+///
+/// * `node` - A shared mutable reference to the TCP stream node.
+///
+/// # Errors
+///
+/// Returns `InterfaceError::IncompatibleNode` if the node answers with another version, or another
+/// `InterfaceError` if the node cannot be locked, written to or read from.
+pub fn negotiate_version(node: &Arc<Mutex<NodeConnection>>) -> Result<(), InterfaceError> {
+    let mut locked_node = node.lock().map_err(|_| InterfaceError::LockNode)?;
+
+    write_frame(
+        &mut *locked_node,
+        &WalletVersion::new(WALLET_PROTOCOL_VERSION).to_bytes(),
+    )
+    .map_err(|_| InterfaceError::Write)?;
+
+    let (command_name, payload) =
+        read_frame(&mut *locked_node).map_err(|_| InterfaceError::Read)?;
+
+    if command_name != Command::WalletVersion {
+        return Err(InterfaceError::IncompatibleNode);
+    }
+
+    let version = WalletVersion::from_bytes(command_name, &mut payload.as_slice())
+        .map_err(|_| InterfaceError::Read)?;
+
+    if version.get_version() != WALLET_PROTOCOL_VERSION {
+        return Err(InterfaceError::IncompatibleNode);
+    }
+
+    drop(locked_node);
+    Ok(())
+}
+
+/// Authenticates the wallet with the node through the token shared with it. It must follow the
+/// negotiation of the version of the wallet protocol.
+///
+/// # Arguments
+///
+/// * `node` - A shared mutable reference to the TCP stream node.
+/// * `token` - The authentication token configured in the node.
+///
+/// # Errors
+///
+/// Returns `InterfaceError::Unauthenticated` if the node rejects the token, or another
+/// `InterfaceError` if the node cannot be locked, written to or read from.
+pub fn authenticate(node: &Arc<Mutex<NodeConnection>>, token: &str) -> Result<(), InterfaceError> {
+    let mut locked_node = node.lock().map_err(|_| InterfaceError::LockNode)?;
+
+    write_frame(&mut *locked_node, &WalletAuth::new(token).to_bytes())
+        .map_err(|_| InterfaceError::Write)?;
+
+    let (command_name, payload) =
+        read_frame(&mut *locked_node).map_err(|_| InterfaceError::Read)?;
+
+    if command_name != Command::AuthResult {
+        return Err(InterfaceError::Read);
+    }
+
+    let auth_result = AuthResult::from_bytes(command_name, &mut payload.as_slice())
+        .map_err(|_| InterfaceError::Read)?;
+
+    drop(locked_node);
+    if !auth_result.is_accepted() {
+        return Err(InterfaceError::Unauthenticated);
+    }
+    Ok(())
+}
+
+/// Requests the node's information.
+///
+/// # Arguments
+///
+/// * `node` - A shared mutable reference to the TCP stream node.
+///
+/// # Errors
+///
+/// Returns an `InterfaceError` if the node cannot be locked, written to or read from.
+pub fn request_node_info(node: &Arc<Mutex<NodeConnection>>) -> Result<NodeInfo, InterfaceError> {
+    let mut locked_node = node.lock().map_err(|_| InterfaceError::LockNode)?;
+
+    write_frame(&mut *locked_node, &GetNodeInfo::new().to_bytes())
+        .map_err(|_| InterfaceError::Write)?;
+
+    let (command_name, payload) =
+        read_frame(&mut *locked_node).map_err(|_| InterfaceError::Read)?;
+
+    if command_name != Command::NodeInfo {
+        return Err(InterfaceError::Read);
+    }
+
+    let node_info = NodeInfo::from_bytes(command_name, &mut payload.as_slice())
+        .map_err(|_| InterfaceError::Read)?;
+
+    drop(locked_node);
+    Ok(node_info)
+}
+
+/// Requests the statistics of the peers the node is connected to.
+///
+/// # Arguments
+///
+/// * `node` - A shared mutable reference to the TCP stream node.
+///
+/// # Errors
+///
+/// Returns an `InterfaceError` if the node cannot be locked, written to or read from.
+pub fn request_peer_info(node: &Arc<Mutex<NodeConnection>>) -> Result<PeerInfo, InterfaceError> {
+    let mut locked_node = node.lock().map_err(|_| InterfaceError::LockNode)?;
+
+    write_frame(&mut *locked_node, &GetPeerInfo::new().to_bytes())
+        .map_err(|_| InterfaceError::Write)?;
+
+    let (command_name, payload) =
+        read_frame(&mut *locked_node).map_err(|_| InterfaceError::Read)?;
+
+    if command_name != Command::PeerInfo {
+        return Err(InterfaceError::Read);
+    }
+
+    let peer_info = PeerInfo::from_bytes(command_name, &mut payload.as_slice())
+        .map_err(|_| InterfaceError::Read)?;
+
+    drop(locked_node);
+    Ok(peer_info)
+}
+
+/// Subscribes the wallet to the events of the node, which pushes them from now on as "notify"
+/// messages, and requests the events pushed since the last one seen by the wallet.
+///
+/// # Arguments
+///
+/// * `node` - A shared mutable reference to the TCP stream node.
+/// * `cursor` - The last event of the node seen by the wallet.
+///
+/// # Errors
+///
+/// Returns an `InterfaceError` if the node cannot be locked, written to or read from.
+fn subscribe(
+    node: &Arc<Mutex<NodeConnection>>,
+    cursor: &EventCursor,
+) -> Result<EventBatch, InterfaceError> {
+    let mut locked_node = node.lock().map_err(|_| InterfaceError::LockNode)?;
+
+    write_frame(
+        &mut *locked_node,
+        &Subscribe::new(cursor.get_session_id(), cursor.get_last_seq()).to_bytes(),
+    )
+    .map_err(|_| InterfaceError::Write)?;
+
+    let (command_name, payload) =
+        read_frame(&mut *locked_node).map_err(|_| InterfaceError::Read)?;
+
+    if command_name != Command::Events {
+        return Err(InterfaceError::Read);
+    }
+
+    let event_batch = EventBatch::from_bytes(command_name, &mut payload.as_slice())
+        .map_err(|_| InterfaceError::Read)?;
+
+    drop(locked_node);
+    Ok(event_batch)
+}
+
+/// Takes the notifications the node pushed since they were last taken, without waiting for new
+/// ones.
+///
+/// # Errors
+///
+/// Returns an `InterfaceError` if the node cannot be locked, closed the connection or cannot be
+/// read from.
+fn take_notifications(
+    node: &Arc<Mutex<NodeConnection>>,
+) -> Result<Vec<EventBatch>, InterfaceError> {
+    node.lock()
+        .map_err(|_| InterfaceError::LockNode)?
+        .take_notifications()
+        .map_err(|_| InterfaceError::Read)
+}
+
+/// Applies a batch of events of the node, scheduling a rescan of every account if the node could
+/// not replay all the events missed by the wallet.
+///
+/// # Arguments
+///
+/// * `accounts` - The shared `Accounts` object.
+/// * `cursor` - The last event of the node seen by the wallet, moved to the last one received.
+/// * `event_batch` - The events pushed by the node.
+///
+/// # Returns
+///
+/// Whether the wallet must be refreshed, as the batch held events or events were missed.
+fn apply_events(
+    accounts: &Arc<Mutex<Accounts>>,
+    cursor: &mut EventCursor,
+    event_batch: &EventBatch,
+) -> Result<bool, InterfaceError> {
+    for (seq, event) in event_batch.get_events() {
+        match event {
+            NodeEvent::NewTx(_) => println!("Event {}: new transaction in the mempool.", seq),
+            NodeEvent::NewBlock(_, height) => println!("Event {}: new block at {}.", seq, height),
+            NodeEvent::Reorg(_, height) => println!("Event {}: reorganization to {}.", seq, height),
+            NodeEvent::PeerConnected(_) | NodeEvent::PeerDisconnected(_) => {}
+        }
+    }
+
+    if cursor.advance(event_batch) {
+        println!("Some events of the node were missed, rescanning every account.");
+        accounts
+            .lock()
+            .map_err(|_| InterfaceError::LockAccounts)?
+            .schedule_full_rescan();
+        return Ok(true);
+    }
+    Ok(!event_batch.get_events().is_empty())
+}
+
+/// Requests a transaction to the node, along with the block containing it, its height, its index
+/// within the block and its confirmations.
+///
+/// # Arguments
+///
+/// * `node` - A shared mutable reference to the TCP stream node.
+/// * `tx_id` - The id of the transaction, as displayed by block explorers.
+///
+/// # Returns
+///
+/// The information of the transaction, or `None` if the node does not know it.
+///
+/// # Errors
+///
+/// Returns an `InterfaceError` if the node cannot be locked, written to or read from.
+pub fn request_transaction(
+    node: &Arc<Mutex<NodeConnection>>,
+    tx_id: Vec<u8>,
+) -> Result<Option<TransactionInfo>, InterfaceError> {
+    let mut locked_node = node.lock().map_err(|_| InterfaceError::LockNode)?;
+
+    write_frame(&mut *locked_node, &GetTransaction::new(tx_id).to_bytes())
+        .map_err(|_| InterfaceError::Write)?;
+
+    let (command_name, payload) =
+        read_frame(&mut *locked_node).map_err(|_| InterfaceError::Read)?;
+
+    match command_name {
+        Command::TxInfo => {
+            let transaction_info =
+                TransactionInfo::from_bytes(command_name, &mut payload.as_slice())
+                    .map_err(|_| InterfaceError::Read)?;
+            Ok(Some(transaction_info))
+        }
+        Command::NotFoundWallet => Ok(None),
+        _ => Err(InterfaceError::Read),
+    }
+}
+
+/// Requests to the node the transactions of an account made since its last update.
+///
+/// # Arguments
+///
+/// * `node` - The shared `NodeConnection` representing the connection to the node.
+/// * `user_info` - The account whose transactions are requested.
+/// * `pk_script` - The public key script of the account.
+///
+/// # Returns
+///
+/// The transactions received from the node, or an `InterfaceError` if the node cannot be reached.
+fn request_transactions(
+    node: &Arc<Mutex<NodeConnection>>,
+    user_info: &UserInfo,
+    pk_script: &[u8],
+) -> Result<Transactions, InterfaceError> {
+    let get_transactions = WalletRequest::GetTransactions(GetTransactions::new(
+        pk_script.to_vec(),
+        user_info.get_public_key(),
+        user_info.get_last_update(),
+    ))
+    .to_bytes()
+    .map_err(|_| InterfaceError::Write)?;
+    let mut locked_node = node.lock().map_err(|_| InterfaceError::LockNode)?;
+
+    write_frame(&mut *locked_node, &get_transactions).map_err(|_| InterfaceError::Write)?;
+
+    let (command_name, payload) =
+        read_frame(&mut *locked_node).map_err(|_| InterfaceError::Read)?;
+
+    if command_name != Command::Response {
+        return Err(InterfaceError::Read);
+    }
+
+    match WalletResponse::from_bytes(&mut payload.as_slice()) {
+        Ok(WalletResponse::Transactions(transactions)) => Ok(transactions),
+        _ => Err(InterfaceError::Read),
+    }
+}
+
+/// Fetches the transactions of an account made since its last update, either from the node's
+/// scan of its blocks or, in light mode, from the compact filters of the blocks.
+///
+/// # Arguments
+///
+/// * `node` - The shared `NodeConnection` representing the connection to the node.
+/// * `light_client` - The light client, if the wallet runs in light mode.
+/// * `user_name` - The name of the account.
+/// * `user_info` - The account whose transactions are requested.
+/// * `pk_script` - The public key script of the account.
+fn fetch_transactions(
+    node: &Arc<Mutex<NodeConnection>>,
+    light_client: &mut Option<LightClient>,
+    user_name: &str,
+    user_info: &UserInfo,
+    pk_script: &[u8],
+) -> Result<Transactions, InterfaceError> {
+    match light_client {
+        Some(light_client) => light_client.sync_account(node, user_name, user_info, pk_script),
+        None => request_transactions(node, user_info, pk_script),
+    }
+}
+
+/// Fetches the history of the next account waiting to be rescanned, such as an account imported
+/// from a wallet dump. Only one account is rescanned per update, so the current account is not
+/// delayed by large imports. The current account is skipped, as it is already updated from its
+/// whole history.
+///
+/// # Arguments
+///
+/// * `accounts` - The locked accounts of the wallet.
+/// * `node` - The shared `NodeConnection` representing the connection to the node.
+/// * `light_client` - The light client, if the wallet runs in light mode.
+fn rescan_next_account(
+    accounts: &mut Accounts,
+    node: &Arc<Mutex<NodeConnection>>,
+    light_client: &mut Option<LightClient>,
+) -> Result<(), InterfaceError> {
+    let user_name = match accounts.take_pending_rescan() {
+        Some(user_name) => user_name,
+        None => return Ok(()),
+    };
+
+    if user_name == *accounts.get_current_username() {
+        return Ok(());
+    }
+
+    if let Some(user_info) = accounts.get_account_info(&user_name) {
+        let pk_script = pk_script_from_pubkey(&user_info.get_public_key(), user_info.get_bech32())?;
+        let transactions =
+            fetch_transactions(node, light_client, &user_name, user_info, &pk_script)?;
+
+        println!("Rescan of imported account {} finished.", user_name);
+        accounts.update_account(&user_name, &transactions);
+    }
+    Ok(())
+}
+
+/// Updates the current account with the transactions made since its last update, and rescans one
+/// of the accounts waiting to be rescanned.
+///
+/// A `NewTx` event is sent whenever the current account received transactions or was switched to.
+///
+/// # Returns
+///
+/// `false` if the transactions could not be derived from the compact filters in light mode, in
+/// which case the update must be retried, as the node may be serving a chain that is being
+/// reorganized.
+fn update_accounts(
+    accounts: &Arc<Mutex<Accounts>>,
+    node: &Arc<Mutex<NodeConnection>>,
+    events_sender: &glib::Sender<WalletEvent>,
+    light_client: &mut Option<LightClient>,
+    last_username: &mut String,
+) -> Result<bool, InterfaceError> {
+    let mut locked_accounts = accounts.lock().map_err(|_| InterfaceError::LockAccounts)?;
+
+    if let Some(user_info) = locked_accounts.get_current_account_info() {
+        let pk_script = pk_script_from_pubkey(&user_info.get_public_key(), user_info.get_bech32())?;
+        let username = locked_accounts.get_current_username().clone();
+        let transactions =
+            match fetch_transactions(node, light_client, &username, user_info, &pk_script) {
+                Ok(transactions) => transactions,
+                Err(err) if light_client.is_some() => {
+                    println!("Failed to sync from compact filters: {:?}", err);
+                    return Ok(false);
+                }
+                Err(err) => return Err(err),
+            };
+
+        if !transactions.is_empty() {
+            println!("\n------------------------------------------------------------\n{}'s account with public key {:?}\n", locked_accounts.get_current_username(), &user_info.get_public_key());
+            println!(
+                "Get Transaction message sent with public key script: {:?}",
+                pk_script
+            );
+        }
+
+        notify_incoming_transactions(
+            &transactions,
+            &username,
+            &pk_script,
+            locked_accounts.get_min_incoming_amount(),
+            events_sender,
+        )?;
+
+        locked_accounts.update(&transactions);
+
+        if !transactions.is_empty() || username != *last_username {
+            events_sender
+                .send(WalletEvent::NewTx(username.clone()))
+                .map_err(|_| InterfaceError::Send)?;
+            *last_username = username;
+        }
+    }
+
+    rescan_next_account(&mut locked_accounts, node, light_client)?;
+    Ok(true)
+}
+
+/// Returns whether the accounts must be updated even though the node pushed no events: another
+/// account was switched to, or accounts are waiting to be rescanned.
+fn accounts_need_update(
+    accounts: &Arc<Mutex<Accounts>>,
+    last_username: &str,
+) -> Result<bool, InterfaceError> {
+    let locked_accounts = accounts.lock().map_err(|_| InterfaceError::LockAccounts)?;
+
+    let switched = locked_accounts.get_current_account_info().is_some()
+        && *locked_accounts.get_current_username() != last_username;
+    Ok(switched || locked_accounts.has_pending_rescan())
+}
+
 /// Updates the wallet by retrieving and processing transactions from the node.
 ///
-/// This function continuously loops and updates the wallet by retrieving and processing
-/// transactions from the node. It takes the shared `Accounts` object, the shared `TcpStream`
-/// representing the connection to the node, and a sender for transaction update signals as input.
-/// Within the loop, it locks the `Accounts` object to access the current user's information. If
-/// there is an actual account, it retrieves the necessary information, such as the public key and
-/// the last update timestamp. It then locks the `TcpStream` to communicate with the node and
-/// requests transactions using the `GetTransactions` command. The retrieved transactions are
-/// processed and updated in the user's account. Finally, a transaction update signal is sent using
-/// the provided sender. The loop continues to execute after a brief sleep of 10 seconds.
+/// The wallet first subscribes to the events of the node, replaying the ones it missed, and from
+/// then on the node pushes its chain and mempool events as they happen. Whenever events are
+/// pushed, another account is switched to or accounts are waiting to be rescanned, the current
+/// account is updated with the transactions made since its last update and one account waiting to
+/// be rescanned is updated as well, rescanning every account if some events were missed. The
+/// notifications are checked for every `NOTIFICATION_POLL_INTERVAL`, which does not reach the
+/// node. The node's status is requested after every update, and at least every
+/// `STATUS_POLL_INTERVAL`, and the changes since it was last requested are sent as `NewBlock`,
+/// `Reorg`, `SyncProgress` and `PeerCountChanged` events.
+///
+/// In light mode, the transactions are derived from the compact filters of the blocks instead, and
+/// a failure to fetch them is reported without stopping the updates, which are retried until they
+/// succeed, as the node may be serving a chain that is being reorganized.
 ///
 /// # Arguments
 ///
 /// * `accounts` - The shared `Accounts` object.
-/// * `node` - The shared `TcpStream` representing the connection to the node.
-/// * `txs_sender` - The sender for transaction update signals.
+/// * `node` - The shared `NodeConnection` representing the connection to the node.
+/// * `events_sender` - The sender through which events are delivered to the interface.
+/// * `light_mode` - Whether the wallet only relies on headers and compact filters.
 ///
 /// # Returns
 ///
 /// Returns `Ok(())` if the wallet is successfully updated, or an `InterfaceError` if there is an
-/// error while retrieving transactions, processing them, or sending the transaction update signal.
+/// error while retrieving transactions, processing them, or sending the events.
 pub fn update_wallet(
     accounts: Arc<Mutex<Accounts>>,
-    node: Arc<Mutex<TcpStream>>,
-    txs_sender: glib::Sender<bool>,
+    node: Arc<Mutex<NodeConnection>>,
+    events_sender: glib::Sender<WalletEvent>,
+    light_mode: bool,
 ) -> Result<(), InterfaceError> {
-    loop {
-        let mut locked_accounts = accounts.lock().map_err(|_| InterfaceError::LockAccounts)?;
-
-        if let Some(user_info) = locked_accounts.get_current_account_info() {
-            let mut locked_node = node.lock().map_err(|_| InterfaceError::LockNode)?;
-            let pk_script =
-                pk_script_from_pubkey(&user_info.get_public_key(), user_info.get_bech32())?;
-            let get_transactions = GetTransactions::new(
-                pk_script.clone(),
-                user_info.get_public_key(),
-                user_info.get_last_update(),
-            );
+    let mut chain_watcher = ChainWatcher::new();
+    let mut event_cursor = EventCursor::new();
+    let mut light_client = light_mode.then(LightClient::new);
+    let mut last_username = String::new();
 
-            locked_node
-                .write_all(&get_transactions.to_bytes())
-                .map_err(|_| InterfaceError::Write)?;
+    let event_batch = subscribe(&node, &event_cursor)?;
+    apply_events(&accounts, &mut event_cursor, &event_batch)?;
+    let mut pending_update = true;
+    let mut last_status: Option<Instant> = None;
 
-            let _ =
-                read_string_from_bytes(&mut *locked_node, 12).map_err(|_| InterfaceError::Read)?;
-            let transactions =
-                Transactions::from_bytes(&mut *locked_node).map_err(|_| InterfaceError::Read)?;
-
-            drop(locked_node);
+    loop {
+        let update = pending_update || accounts_need_update(&accounts, &last_username)?;
+        if update {
+            pending_update = !update_accounts(
+                &accounts,
+                &node,
+                &events_sender,
+                &mut light_client,
+                &mut last_username,
+            )?;
+        }
 
-            if !transactions.is_empty() {
-                println!("\n------------------------------------------------------------\n{}'s account with public key {:?}\n", locked_accounts.get_current_username(), &user_info.get_public_key());
-                println!(
-                    "Get Transaction message sent with public key script: {:?}",
-                    pk_script
-                );
+        if update || last_status.is_none_or(|last| last.elapsed() >= STATUS_POLL_INTERVAL) {
+            match request_node_info(&node) {
+                Ok(node_info) => {
+                    for event in chain_watcher.get_events(&node_info) {
+                        events_sender
+                            .send(event)
+                            .map_err(|_| InterfaceError::Send)?;
+                    }
+                }
+                Err(err) => println!("Failed to request the node's status: {:?}", err),
             }
+            last_status = Some(Instant::now());
+        }
 
-            locked_accounts.update(&transactions);
+        thread::sleep(NOTIFICATION_POLL_INTERVAL);
 
-            txs_sender.send(true).map_err(|_| InterfaceError::Send)?;
+        for event_batch in take_notifications(&node)? {
+            pending_update |= apply_events(&accounts, &mut event_cursor, &event_batch)?;
         }
-
-        drop(locked_accounts);
-        thread::sleep(Duration::from_secs(5));
     }
 }
@@ -1,3 +1,5 @@
+pub mod node_info_view;
+pub mod peers_view;
 pub mod transaction_tree_view;
 pub mod transaction_view;
 pub mod views_constants;
@@ -0,0 +1,68 @@
+use super::views_constants::*;
+use gtk::{Align, Box, BoxExt, Grid, GridExt, Label, LabelExt, Orientation, WidgetExt};
+use node::wallet_utils::node_info::NodeInfo;
+
+/// Formats an amount of seconds as hours, minutes and seconds.
+fn format_uptime(uptime: u64) -> String {
+    format!(
+        "{}h {}m {}s",
+        uptime / 3600,
+        (uptime % 3600) / 60,
+        uptime % 60
+    )
+}
+
+/// Creates the view displaying the information reported by the node.
+///
+/// # Arguments
+///
+/// * `node_info` - The information reported by the node.
+///
+/// # Returns
+///
+/// A `Box` holding a grid with the node's version, features, uptime, network, data directory and
+/// sync status.
+pub fn create_node_info_view(node_info: &NodeInfo) -> Box {
+    let node_info_box = Box::new(Orientation::Vertical, 0);
+    let grid = Grid::new();
+
+    grid.set_halign(Align::Center);
+    grid.set_row_spacing(10);
+    grid.set_column_spacing(20);
+    grid.set_margin_top(20);
+
+    let features = if node_info.get_features().is_empty() {
+        NO_FEATURES.to_string()
+    } else {
+        node_info.get_features().join(", ")
+    };
+
+    let sync = if node_info.is_sync_stalled() {
+        SYNC_STALLED.to_string()
+    } else {
+        SYNC_PROGRESSING.to_string()
+    };
+
+    let rows = [
+        (NODE_VERSION_LABEL, node_info.get_version().to_string()),
+        (NODE_FEATURES_LABEL, features),
+        (NODE_UPTIME_LABEL, format_uptime(node_info.get_uptime())),
+        (NODE_NETWORK_LABEL, node_info.get_network().to_string()),
+        (NODE_DATA_DIR_LABEL, node_info.get_data_dir().to_string()),
+        (NODE_SYNC_LABEL, sync),
+    ];
+
+    for (row, (title, value)) in rows.iter().enumerate() {
+        let title_label = Label::new(Some(*title));
+        title_label.set_halign(Align::Start);
+        let value_label = Label::new(Some(value.as_str()));
+        value_label.set_halign(Align::Start);
+        value_label.set_selectable(true);
+
+        grid.attach(&title_label, 0, row as i32, 1, 1);
+        grid.attach(&value_label, 1, row as i32, 1, 1);
+    }
+
+    node_info_box.pack_start(&grid, false, false, 0);
+    node_info_box
+}
@@ -0,0 +1,91 @@
+use super::views_constants::*;
+use gtk::{Align, Box, BoxExt, Grid, GridExt, Label, LabelExt, Orientation, WidgetExt};
+use node::wallet_utils::peer_info::{PeerDetails, PeerInfo};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Formats the unix timestamp a peer was last heard from as the seconds elapsed since then.
+fn format_last_seen(last_seen: u64) -> String {
+    if last_seen == 0 {
+        return UNKNOWN_VALUE.to_string();
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.as_secs())
+        .unwrap_or(last_seen);
+    format!("{}s ago", now.saturating_sub(last_seen))
+}
+
+/// Returns the values displayed in the row of a peer, in the order of the columns.
+fn peer_row(peer: &PeerDetails) -> [String; 6] {
+    let messages: Vec<String> = peer
+        .get_messages()
+        .iter()
+        .map(|(command, count)| format!("{} {}", command, count))
+        .collect();
+
+    [
+        peer.get_addr().to_string(),
+        format!(
+            "{} kB / {} kB",
+            peer.get_bytes_sent() / 1000,
+            peer.get_bytes_received() / 1000
+        ),
+        format_last_seen(peer.get_last_seen()),
+        match peer.get_latency_millis() {
+            Some(latency) => format!("{} ms", latency),
+            None => UNKNOWN_VALUE.to_string(),
+        },
+        peer.get_misbehavior().to_string(),
+        messages.join(", "),
+    ]
+}
+
+/// Creates the view displaying the statistics of the peers the node is connected to.
+///
+/// # Arguments
+///
+/// * `peer_info` - The statistics reported by the node.
+///
+/// # Returns
+///
+/// A `Box` holding a grid with a row per peer, showing its address, traffic, last time it was
+/// heard from, ping latency, misbehavior score and the messages it sent by command.
+pub fn create_peers_view(peer_info: &PeerInfo) -> Box {
+    let peers_box = Box::new(Orientation::Vertical, 0);
+    let grid = Grid::new();
+
+    grid.set_halign(Align::Center);
+    grid.set_row_spacing(10);
+    grid.set_column_spacing(20);
+    grid.set_margin_top(20);
+
+    let titles = [
+        PEER_ADDR_LABEL,
+        PEER_TRAFFIC_LABEL,
+        PEER_LAST_SEEN_LABEL,
+        PEER_LATENCY_LABEL,
+        PEER_MISBEHAVIOR_LABEL,
+        PEER_MESSAGES_LABEL,
+    ];
+
+    for (column, title) in titles.iter().enumerate() {
+        let title_label = Label::new(Some(*title));
+        title_label.set_halign(Align::Start);
+        grid.attach(&title_label, column as i32, 0, 1, 1);
+    }
+
+    for (row, peer) in peer_info.get_peers().iter().enumerate() {
+        for (column, value) in peer_row(peer).iter().enumerate() {
+            let value_label = Label::new(Some(value.as_str()));
+            value_label.set_halign(Align::Start);
+            value_label.set_selectable(true);
+            value_label.set_line_wrap(true);
+
+            grid.attach(&value_label, column as i32, row as i32 + 1, 1, 1);
+        }
+    }
+
+    peers_box.pack_start(&grid, false, false, 0);
+    peers_box
+}
@@ -3,11 +3,45 @@ use gtk::{
     EntryCompletionExt, EntryExt, ListStore, TreeModelExt, TreeModelFilter, TreeModelFilterExt,
     TreeModelSort, TreeView, TreeViewColumn, TreeViewColumnExt, TreeViewExt, TreeViewGridLines,
 };
-use std::{cell::RefCell, rc::Rc};
+use gtk::{CheckButton, ToggleButtonExt};
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    sync::{Arc, Mutex},
+};
 
 use super::views_constants::*;
+use crate::{accounts::Accounts, transactions::transaction_view::update_transactions};
+
+/// Creates a check button that toggles whether dust deposits are listed in the transaction history.
+///
+/// # Arguments
+///
+/// * `store` - The list store holding the transaction history.
+/// * `accounts` - The shared `Accounts` object where the preference is stored.
+///
+/// # Returns
+///
+/// The check button, already connected to refresh the history whenever it is toggled.
+fn create_show_dust_check_button(store: &ListStore, accounts: Arc<Mutex<Accounts>>) -> CheckButton {
+    let show_dust_check_button = CheckButton::with_label(SHOW_DUST_LABEL);
+    let store = store.clone();
+
+    show_dust_check_button.connect_toggled(move |check_button| {
+        if let Ok(mut locked_accounts) = accounts.lock() {
+            locked_accounts.set_show_dust(check_button.get_active());
+            drop(locked_accounts);
+        }
+
+        if let Err(err) = update_transactions(store.clone(), accounts.clone()) {
+            println!("{:?}", err);
+        }
+    });
+
+    show_dust_check_button
+}
 
-pub fn create_transaction_tree_view(store: &ListStore) -> gtk::Box {
+pub fn create_transaction_tree_view(store: &ListStore, accounts: Arc<Mutex<Accounts>>) -> gtk::Box {
     let transaction_tree_view = TreeView::new();
 
     // Set the model for the TreeView
@@ -78,6 +112,12 @@ pub fn create_transaction_tree_view(store: &ListStore) -> gtk::Box {
     // Create a VBox to hold the TreeView and Entry widget
     let vbox = gtk::Box::new(gtk::Orientation::Vertical, 0);
     vbox.pack_start(&entry_widget, false, false, 0);
+    vbox.pack_start(
+        &create_show_dust_check_button(store, accounts),
+        false,
+        false,
+        0,
+    );
     vbox.pack_start(&transaction_tree_view, true, true, 0);
 
     vbox
@@ -6,6 +6,26 @@ pub const AMOUNT: &str = "Amount(BTC)";
 
 pub const TEXT: &str = "text";
 
+pub const NODE_VERSION_LABEL: &str = "Version:";
+pub const NODE_FEATURES_LABEL: &str = "Features:";
+pub const NODE_UPTIME_LABEL: &str = "Uptime:";
+pub const NODE_NETWORK_LABEL: &str = "Network:";
+pub const NODE_DATA_DIR_LABEL: &str = "Data directory:";
+pub const NODE_SYNC_LABEL: &str = "Sync:";
+pub const NO_FEATURES: &str = "None";
+pub const SYNC_STALLED: &str = "Stalled, every peer is far ahead";
+pub const SYNC_PROGRESSING: &str = "Progressing";
+
+pub const PEER_ADDR_LABEL: &str = "Peer";
+pub const PEER_TRAFFIC_LABEL: &str = "Sent / received";
+pub const PEER_LAST_SEEN_LABEL: &str = "Last seen";
+pub const PEER_LATENCY_LABEL: &str = "Ping";
+pub const PEER_MISBEHAVIOR_LABEL: &str = "Misbehavior";
+pub const PEER_MESSAGES_LABEL: &str = "Messages";
+pub const UNKNOWN_VALUE: &str = "-";
+
+pub const SHOW_DUST_LABEL: &str = "Show dust deposits";
+
 pub const SEARCH_TEXT_HELP: &str = "Enter address, transaction id, or label to search";
 
 pub const PAY_TO_LABEL: &str = "Pay To:";
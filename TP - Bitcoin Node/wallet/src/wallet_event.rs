@@ -0,0 +1,200 @@
+use node::wallet_utils::{event_batch::EventBatch, node_info::NodeInfo};
+use std::fmt;
+
+/// Represents an update the wallet library reports to the interface, so each view can refresh
+/// only the parts affected by it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WalletEvent {
+    /// A new block extended the node's chain, at the given height.
+    NewBlock(u32),
+    /// The account with the given name received new or changed transactions.
+    NewTx(String),
+    /// The account with the given name received a confirmed transaction paying it the given
+    /// amount, in satoshis, at least the wallet's minimum incoming amount.
+    IncomingTx(String, i64),
+    /// The tip of the node's chain was replaced by another one, now at the given height.
+    Reorg(u32),
+    /// The node has downloaded the first amount of blocks out of a chain of the second height.
+    SyncProgress(u32, u32),
+    /// The node is now connected to the given amount of peers.
+    PeerCountChanged(u32),
+}
+
+/// Keeps track of the last status reported by the node, turning every new report into the
+/// events that describe what changed since the previous one.
+#[derive(Debug, Default)]
+pub struct ChainWatcher {
+    last_info: Option<(u32, Vec<u8>, u32, u32)>,
+}
+
+impl ChainWatcher {
+    pub fn new() -> ChainWatcher {
+        ChainWatcher::default()
+    }
+
+    /// Compares the status reported by the node with the previous one.
+    ///
+    /// # Arguments
+    ///
+    /// * `node_info` - The information reported by the node.
+    ///
+    /// # Returns
+    ///
+    /// The events describing the changes, which are every status event the first time.
+    pub fn get_events(&mut self, node_info: &NodeInfo) -> Vec<WalletEvent> {
+        let height = node_info.get_headers_height();
+        let best_hash = node_info.get_best_hash().clone();
+        let blocks = node_info.get_blocks();
+        let peer_count = node_info.get_peer_count();
+        let mut events = vec![];
+
+        match &self.last_info {
+            None => {
+                events.push(WalletEvent::NewBlock(height));
+                events.push(WalletEvent::SyncProgress(blocks, height));
+                events.push(WalletEvent::PeerCountChanged(peer_count));
+            }
+            Some((last_height, last_hash, last_blocks, last_peer_count)) => {
+                if best_hash != *last_hash {
+                    if height > *last_height {
+                        events.push(WalletEvent::NewBlock(height));
+                    } else {
+                        events.push(WalletEvent::Reorg(height));
+                    }
+                }
+                if blocks != *last_blocks || height != *last_height {
+                    events.push(WalletEvent::SyncProgress(blocks, height));
+                }
+                if peer_count != *last_peer_count {
+                    events.push(WalletEvent::PeerCountChanged(peer_count));
+                }
+            }
+        }
+
+        self.last_info = Some((height, best_hash, blocks, peer_count));
+        events
+    }
+}
+
+/// Keeps track of the last event of the node seen by the wallet, so the events pushed while it was
+/// disconnected can be replayed with a "resume" message instead of rescanning every account.
+#[derive(Debug, Default)]
+pub struct EventCursor {
+    session_id: u64,
+    last_seq: u64,
+}
+
+impl EventCursor {
+    pub fn new() -> EventCursor {
+        EventCursor::default()
+    }
+
+    /// Moves the cursor to the last event of the batch received from the node.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the wallet must rescan its accounts, as the node could not replay every event it
+    /// missed, or restarted since the wallet last saw it.
+    pub fn advance(&mut self, event_batch: &EventBatch) -> bool {
+        let missed_events = self.session_id != 0
+            && (!event_batch.is_complete() || event_batch.get_session_id() != self.session_id);
+
+        self.session_id = event_batch.get_session_id();
+        self.last_seq = event_batch.get_last_seq();
+        missed_events
+    }
+
+    pub fn get_session_id(&self) -> u64 {
+        self.session_id
+    }
+
+    pub fn get_last_seq(&self) -> u64 {
+        self.last_seq
+    }
+}
+
+/// Represents the status of the node's chain and connections as shown by the interface.
+#[derive(Debug, Default)]
+pub struct ChainStatus {
+    height: u32,
+    blocks: u32,
+    peer_count: u32,
+}
+
+impl ChainStatus {
+    /// Updates the status with an event.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the event changed the status, `false` if it is not a chain event.
+    pub fn apply(&mut self, event: &WalletEvent) -> bool {
+        match event {
+            WalletEvent::NewBlock(height) | WalletEvent::Reorg(height) => self.height = *height,
+            WalletEvent::SyncProgress(blocks, height) => {
+                self.blocks = *blocks;
+                self.height = *height;
+            }
+            WalletEvent::PeerCountChanged(peer_count) => self.peer_count = *peer_count,
+            WalletEvent::NewTx(_) | WalletEvent::IncomingTx(..) => return false,
+        }
+        true
+    }
+}
+
+impl fmt::Display for ChainStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Block {} ({} downloaded) - {} peers",
+            self.height, self.blocks, self.peer_count
+        )
+    }
+}
+
+#[cfg(test)]
+mod wallet_event_test {
+    use super::*;
+
+    fn node_info(height: u32, hash: u8, blocks: u32, peer_count: u32) -> NodeInfo {
+        NodeInfo::new(
+            "0.1.0".to_string(),
+            vec![],
+            0,
+            "testnet".to_string(),
+            "data".to_string(),
+            (height, vec![hash; 32], blocks, peer_count, false),
+        )
+    }
+
+    #[test]
+    fn test_chain_watcher_events() {
+        let mut watcher = ChainWatcher::new();
+
+        assert_eq!(watcher.get_events(&node_info(10, 1, 5, 8)).len(), 3);
+        assert!(watcher.get_events(&node_info(10, 1, 5, 8)).is_empty());
+        assert_eq!(
+            watcher.get_events(&node_info(11, 2, 5, 7)),
+            vec![
+                WalletEvent::NewBlock(11),
+                WalletEvent::SyncProgress(5, 11),
+                WalletEvent::PeerCountChanged(7)
+            ]
+        );
+        assert_eq!(
+            watcher.get_events(&node_info(11, 3, 5, 7)),
+            vec![WalletEvent::Reorg(11)]
+        );
+    }
+
+    #[test]
+    fn test_event_cursor_asks_for_rescan_when_events_are_missed() {
+        let mut cursor = EventCursor::new();
+
+        assert!(!cursor.advance(&EventBatch::new(7, 3, Some(vec![]))));
+        assert_eq!((cursor.get_session_id(), cursor.get_last_seq()), (7, 3));
+        assert!(!cursor.advance(&EventBatch::new(7, 5, Some(vec![]))));
+        assert!(cursor.advance(&EventBatch::new(7, 9, None)));
+        assert!(cursor.advance(&EventBatch::new(8, 1, Some(vec![]))));
+        assert_eq!((cursor.get_session_id(), cursor.get_last_seq()), (8, 1));
+    }
+}
@@ -0,0 +1,3 @@
+pub mod dump_import;
+pub mod import_constants;
+pub mod import_error;
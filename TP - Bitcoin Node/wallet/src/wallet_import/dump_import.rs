@@ -0,0 +1,318 @@
+use super::{import_constants::*, import_error::ImportError};
+use crate::accounts::Accounts;
+use bitcoin_hashes::{sha256d, Hash};
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+
+/// The keys read from a wallet dump and the lines that failed, each with its line number.
+pub type ParsedWalletDump = (Vec<(usize, ImportedKey)>, Vec<(usize, ImportError)>);
+
+/// Represents a key read from a wallet dump or a descriptor list.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ImportedKey {
+    label: Option<String>,
+    private_key: Vec<u8>,
+    public_key: Vec<u8>,
+    bech32: bool,
+}
+
+/// Represents the outcome of an import: the accounts created and the lines that failed.
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    imported: Vec<String>,
+    errors: Vec<(usize, ImportError)>,
+}
+
+impl ImportReport {
+    pub fn get_imported(&self) -> &Vec<String> {
+        &self.imported
+    }
+
+    pub fn get_errors(&self) -> &Vec<(usize, ImportError)> {
+        &self.errors
+    }
+}
+
+/// Decodes a testnet private key in Wallet Import Format.
+///
+/// # Arguments
+///
+/// * `wif` - The Base58Check encoded private key.
+///
+/// # Returns
+///
+/// The raw private key and whether its public key must be compressed, or an `ImportError` if
+/// the key is malformed, its checksum does not match or it does not belong to testnet.
+pub fn decode_wif(wif: &str) -> Result<(Vec<u8>, bool), ImportError> {
+    let decoded = bs58::decode(wif)
+        .into_vec()
+        .map_err(|_| ImportError::InvalidWif)?;
+
+    if decoded.len() < WIF_CHECKSUM_LENGTH + 1 + PRIVATE_KEY_LENGTH {
+        return Err(ImportError::InvalidWif);
+    }
+
+    let (payload, checksum) = decoded.split_at(decoded.len() - WIF_CHECKSUM_LENGTH);
+
+    if sha256d::Hash::hash(payload)[..WIF_CHECKSUM_LENGTH] != *checksum {
+        return Err(ImportError::InvalidChecksum);
+    }
+
+    if payload[0] != TESTNET_WIF_PREFIX {
+        return Err(ImportError::WrongNetwork);
+    }
+
+    match &payload[1..] {
+        [key @ .., WIF_COMPRESSED_FLAG] if key.len() == PRIVATE_KEY_LENGTH => {
+            Ok((key.to_vec(), true))
+        }
+        key if key.len() == PRIVATE_KEY_LENGTH => Ok((key.to_vec(), false)),
+        _ => Err(ImportError::InvalidWif),
+    }
+}
+
+/// Builds an imported key from a WIF private key, deriving its public key.
+fn imported_key(
+    wif: &str,
+    label: Option<String>,
+    bech32: bool,
+) -> Result<ImportedKey, ImportError> {
+    let (private_key, compressed) = decode_wif(wif)?;
+    let secret_key =
+        SecretKey::from_slice(&private_key).map_err(|_| ImportError::InvalidPrivateKey)?;
+    let public_key = PublicKey::from_secret_key(&Secp256k1::new(), &secret_key);
+
+    let public_key = if compressed {
+        public_key.serialize().to_vec()
+    } else {
+        public_key.serialize_uncompressed().to_vec()
+    };
+
+    Ok(ImportedKey {
+        label,
+        private_key,
+        public_key,
+        bech32,
+    })
+}
+
+/// Decodes the percent-encoded characters of a label, as written by `dumpwallet`.
+fn decode_label(label: &str) -> String {
+    let mut bytes = vec![];
+    let mut chars = label.bytes();
+
+    while let Some(byte) = chars.next() {
+        if byte != b'%' {
+            bytes.push(byte);
+            continue;
+        }
+
+        let hex: Vec<u8> = chars.by_ref().take(2).collect();
+        match u8::from_str_radix(&String::from_utf8_lossy(&hex), 16) {
+            Ok(decoded) => bytes.push(decoded),
+            Err(_) => bytes.extend([b'%'].iter().chain(hex.iter())),
+        }
+    }
+
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+/// Parses a line of a `dumpwallet` file, such as
+/// `cVt4o7BGAig1UXywgGSmARhxMdzP5qvQsxKkSsc1XEkw3tDTQFpy 2023-06-25T00:00:00Z label=savings # addr=tb1q...`.
+///
+/// # Returns
+///
+/// The key of the line, `None` if the line holds no importable key, or an `ImportError` if the
+/// line is malformed.
+fn parse_dump_line(line: &str) -> Result<Option<ImportedKey>, ImportError> {
+    let (data, comment) = line.split_once(COMMENT_MARK).unwrap_or((line, ""));
+    let fields: Vec<&str> = data.split_whitespace().collect();
+
+    if fields.is_empty() {
+        return Ok(None);
+    }
+
+    if fields.len() < 2 {
+        return Err(ImportError::MalformedLine);
+    }
+
+    if fields.contains(&HD_SEED_FIELD) {
+        return Ok(None);
+    }
+
+    let label = fields
+        .iter()
+        .find_map(|field| field.strip_prefix(LABEL_FIELD))
+        .filter(|label| !label.is_empty())
+        .map(decode_label);
+    let bech32 = comment
+        .split_whitespace()
+        .filter_map(|field| field.strip_prefix(ADDRESS_FIELD))
+        .any(|addresses| addresses.starts_with(BECH32_PREFIX));
+
+    imported_key(fields[0], label, bech32).map(Some)
+}
+
+/// Parses a line of a descriptor list, such as `wpkh(cVt4o7BGAig1UXywgGSmARhxMdzP5qvQsxKkSsc1XEkw3tDTQFpy)#8zl0zxma`.
+///
+/// # Returns
+///
+/// The key of the descriptor, or an `ImportError` if the descriptor is not a single key `pkh()`
+/// or `wpkh()` descriptor.
+fn parse_descriptor(line: &str) -> Result<Option<ImportedKey>, ImportError> {
+    let descriptor = line
+        .trim_matches(|c: char| c == '"' || c == ',' || c.is_whitespace())
+        .split(COMMENT_MARK)
+        .next()
+        .unwrap_or_default();
+
+    let (key, bech32) = if let Some(key) = descriptor.strip_prefix(WPKH_DESCRIPTOR) {
+        (key, true)
+    } else if let Some(key) = descriptor.strip_prefix(PKH_DESCRIPTOR) {
+        (key, false)
+    } else {
+        return Err(ImportError::UnsupportedDescriptor);
+    };
+
+    let key = key
+        .strip_suffix(')')
+        .ok_or(ImportError::UnsupportedDescriptor)?;
+
+    imported_key(key, None, bech32).map(Some)
+}
+
+/// Parses a whole `dumpwallet` file or descriptor list.
+///
+/// # Arguments
+///
+/// * `content` - The content of the file.
+///
+/// # Returns
+///
+/// The keys found, each with the line number it was read from, and the lines that failed.
+pub fn parse_wallet_dump(content: &str) -> ParsedWalletDump {
+    let mut keys = vec![];
+    let mut errors = vec![];
+
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+
+        if trimmed.is_empty() || trimmed.starts_with(COMMENT_MARK) {
+            continue;
+        }
+
+        let parsed = if trimmed.contains(PKH_DESCRIPTOR) {
+            parse_descriptor(trimmed)
+        } else {
+            parse_dump_line(trimmed)
+        };
+
+        match parsed {
+            Ok(Some(key)) => keys.push((index + 1, key)),
+            Ok(None) => {}
+            Err(err) => errors.push((index + 1, err)),
+        }
+    }
+
+    (keys, errors)
+}
+
+/// Imports the keys of a `dumpwallet` file or descriptor list as new accounts.
+///
+/// Each key becomes an account named after its label, or after the line it was read from if it
+/// has none, and is scheduled for a rescan so its history is fetched from the node. The current
+/// account is kept.
+///
+/// # Arguments
+///
+/// * `content` - The content of the file.
+/// * `accounts` - The accounts of the wallet.
+///
+/// # Returns
+///
+/// A report with the imported accounts and the lines that failed to import.
+pub fn import_wallet_dump(content: &str, accounts: &mut Accounts) -> ImportReport {
+    let (keys, errors) = parse_wallet_dump(content);
+    let current_username = accounts.get_current_username().clone();
+    let mut report = ImportReport {
+        imported: vec![],
+        errors,
+    };
+
+    for (line, key) in keys {
+        let user_name = key
+            .label
+            .unwrap_or_else(|| format!("{}_{}", IMPORTED_ACCOUNT_PREFIX, line));
+
+        accounts.add_account(
+            user_name.clone(),
+            key.public_key,
+            key.private_key,
+            key.bech32,
+        );
+        accounts.schedule_rescan(user_name.clone());
+        report.imported.push(user_name);
+    }
+
+    if !current_username.is_empty() {
+        accounts.set_actual_account(current_username);
+    }
+
+    for (line, err) in report.errors.iter() {
+        println!("Line {} could not be imported: {}.", line, err);
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod dump_import_test {
+    use super::*;
+
+    fn encode_wif(private_key: &[u8], compressed: bool) -> String {
+        let mut payload = vec![TESTNET_WIF_PREFIX];
+        payload.extend(private_key);
+
+        if compressed {
+            payload.push(WIF_COMPRESSED_FLAG);
+        }
+
+        let checksum = sha256d::Hash::hash(&payload);
+        payload.extend(&checksum[..WIF_CHECKSUM_LENGTH]);
+
+        bs58::encode(payload).into_string()
+    }
+
+    #[test]
+    fn test_decode_wif() {
+        let wif = encode_wif(&[1; 32], true);
+
+        assert_eq!(decode_wif(&wif), Ok((vec![1; 32], true)));
+        assert_eq!(
+            decode_wif(&encode_wif(&[1; 32], false)),
+            Ok((vec![1; 32], false))
+        );
+        assert_eq!(decode_wif("not a key"), Err(ImportError::InvalidWif));
+    }
+
+    #[test]
+    fn test_parse_wallet_dump() {
+        let wif = encode_wif(&[1; 32], true);
+        let content = format!(
+            "# Wallet dump created by Bitcoin v25.0.0\n\n\
+            {} 2023-06-25T00:00:00Z label=my%20savings # addr=tb1qexample\n\
+            {} 2023-06-25T00:00:00Z hdseed=1 # addr=mexample\n\
+            pkh({})#8zl0zxma\n\
+            sh(wpkh({}))\n",
+            wif, wif, wif, wif
+        );
+
+        let (keys, errors) = parse_wallet_dump(&content);
+
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[0].0, 3);
+        assert_eq!(keys[0].1.label, Some("my savings".to_string()));
+        assert!(keys[0].1.bech32);
+        assert!(!keys[1].1.bech32);
+        assert_eq!(errors, vec![(6, ImportError::UnsupportedDescriptor)]);
+    }
+}
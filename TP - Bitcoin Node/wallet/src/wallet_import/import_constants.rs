@@ -0,0 +1,15 @@
+pub const TESTNET_WIF_PREFIX: u8 = 0xef;
+pub const WIF_CHECKSUM_LENGTH: usize = 4;
+pub const WIF_COMPRESSED_FLAG: u8 = 0x01;
+pub const PRIVATE_KEY_LENGTH: usize = 32;
+
+pub const COMMENT_MARK: char = '#';
+pub const LABEL_FIELD: &str = "label=";
+pub const ADDRESS_FIELD: &str = "addr=";
+pub const HD_SEED_FIELD: &str = "hdseed=1";
+pub const BECH32_PREFIX: &str = "tb1";
+
+pub const WPKH_DESCRIPTOR: &str = "wpkh(";
+pub const PKH_DESCRIPTOR: &str = "pkh(";
+
+pub const IMPORTED_ACCOUNT_PREFIX: &str = "imported";
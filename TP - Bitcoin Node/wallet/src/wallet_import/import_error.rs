@@ -0,0 +1,25 @@
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ImportError {
+    InvalidWif,
+    InvalidChecksum,
+    WrongNetwork,
+    InvalidPrivateKey,
+    UnsupportedDescriptor,
+    MalformedLine,
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            ImportError::InvalidWif => "the key is not a valid WIF private key",
+            ImportError::InvalidChecksum => "the key checksum does not match",
+            ImportError::WrongNetwork => "the key does not belong to testnet",
+            ImportError::InvalidPrivateKey => "the private key is out of range",
+            ImportError::UnsupportedDescriptor => "only pkh() and wpkh() descriptors are supported",
+            ImportError::MalformedLine => "the line does not follow the dump format",
+        };
+        write!(f, "{}", description)
+    }
+}
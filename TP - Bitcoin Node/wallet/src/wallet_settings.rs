@@ -0,0 +1,87 @@
+use crate::transactions::create_transactios_constants::{
+    DEFAULT_MIN_INCOMING_AMOUNT, MIN_INCOMING_AMOUNT,
+};
+use node::settings_mod::{settings_constants::EQUAL, settings_error::SettingError};
+use std::collections::HashMap;
+use std::fs;
+
+/// Configuration settings of the wallet, loaded from its settings file.
+#[derive(Debug)]
+pub struct WalletSettings {
+    min_incoming_amount: i64,
+}
+
+impl WalletSettings {
+    /// Loads the settings from a file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - A string slice representing the path to the settings file.
+    ///
+    /// # Errors
+    ///
+    /// - `SettingError::FileNotFound`: If the settings file could not be read.
+    /// - `SettingError::TokenNotFound`: If the file has a line that is not a known setting.
+    /// - `SettingError::FieldNotFound`: If a setting has a value that cannot be parsed.
+    pub fn from_file(path: &str) -> Result<WalletSettings, SettingError> {
+        WalletSettings::from_contents(&fs::read_to_string(path)?)
+    }
+
+    /// Parses the contents of a settings file, one `name=value` setting per line. Settings left
+    /// out of the file take their default value.
+    fn from_contents(contents: &str) -> Result<WalletSettings, SettingError> {
+        let mut parser_config: HashMap<String, String> = HashMap::new();
+
+        for line in contents.lines() {
+            let token: Vec<&str> = line.splitn(2, EQUAL).collect();
+
+            if token[0] == MIN_INCOMING_AMOUNT && token.len() == 2 {
+                parser_config.insert(token[0].to_string(), token[1].to_string());
+            } else {
+                return Err(SettingError::TokenNotFound);
+            }
+        }
+
+        Ok(WalletSettings {
+            min_incoming_amount: match parser_config.get(MIN_INCOMING_AMOUNT) {
+                Some(min_incoming_amount) => min_incoming_amount.trim().parse()?,
+                None => DEFAULT_MIN_INCOMING_AMOUNT,
+            },
+        })
+    }
+
+    /// Returns the minimum amount, in satoshis, an incoming transaction must reach to trigger a
+    /// notification and be listed by default.
+    pub fn get_min_incoming_amount(&self) -> i64 {
+        self.min_incoming_amount
+    }
+}
+
+#[cfg(test)]
+mod wallet_settings_test {
+    use super::*;
+
+    #[test]
+    fn test_min_incoming_amount_is_read_from_the_settings() {
+        let settings = WalletSettings::from_contents("min_incoming_amount=10000\n").unwrap();
+        assert_eq!(settings.get_min_incoming_amount(), 10000);
+
+        let settings = WalletSettings::from_contents("").unwrap();
+        assert_eq!(
+            settings.get_min_incoming_amount(),
+            DEFAULT_MIN_INCOMING_AMOUNT
+        );
+    }
+
+    #[test]
+    fn test_invalid_settings_are_rejected() {
+        assert!(matches!(
+            WalletSettings::from_contents("min_incoming=10000"),
+            Err(SettingError::TokenNotFound)
+        ));
+        assert!(matches!(
+            WalletSettings::from_contents("min_incoming_amount=dust"),
+            Err(SettingError::FieldNotFound)
+        ));
+    }
+}